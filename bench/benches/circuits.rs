@@ -0,0 +1,116 @@
+//! Prove and verify the standard circuits with every backend.
+//!
+//! `cargo bench -p zkp-bench -- sha256` runs a single circuit; the setup is
+//! done once per circuit and backend, outside of the measurements.
+
+use ark_bls12_381::{Bls12_381 as E, Fr};
+use ark_std::test_rng;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use zkp_bench::{MerkleMembership, Rollup, Sha256Blocks};
+use zkp_r1cs::ConstraintSynthesizer;
+
+const SHA256_BLOCKS: [usize; 2] = [1, 4];
+const MERKLE_DEPTHS: [usize; 2] = [16, 32];
+const ROLLUP_DEPTH: usize = 10;
+const ROLLUP_TRANSFERS: [usize; 2] = [1, 4];
+
+fn groth16<C: ConstraintSynthesizer<Fr> + Clone>(
+    c: &mut Criterion,
+    group: &str,
+    param: usize,
+    blank: C,
+    circuit: C,
+    public_inputs: &[Fr],
+) {
+    use zkp_groth16::{
+        create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    };
+
+    let rng = &mut test_rng();
+    let params = generate_random_parameters::<E, _, _>(blank, rng).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+    let proof = create_random_proof(&params, circuit.clone(), rng).unwrap();
+    assert!(verify_proof(&pvk, &proof, public_inputs).unwrap());
+
+    let mut group = c.benchmark_group(group);
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("groth16/prove", param), |b| {
+        b.iter(|| create_random_proof(&params, circuit.clone(), rng).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("groth16/verify", param), |b| {
+        b.iter(|| verify_proof(&pvk, &proof, public_inputs).unwrap())
+    });
+    group.finish();
+}
+
+fn spartan<C: ConstraintSynthesizer<Fr> + Clone>(
+    c: &mut Criterion,
+    group: &str,
+    param: usize,
+    blank: C,
+    circuit: C,
+    public_inputs: &[Fr],
+) {
+    use zkp_spartan::snark::{create_random_proof, generate_random_parameters, verify_proof};
+
+    let rng = &mut test_rng();
+    let params = generate_random_parameters::<E, _, _>(blank, rng).unwrap();
+    let (pk, vk) = params.keypair();
+    let proof = create_random_proof(&pk, circuit.clone(), rng).unwrap();
+    assert!(verify_proof(&vk, &proof, public_inputs).unwrap());
+
+    let mut group = c.benchmark_group(group);
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("spartan/prove", param), |b| {
+        b.iter(|| create_random_proof(&pk, circuit.clone(), rng).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("spartan/verify", param), |b| {
+        b.iter(|| verify_proof(&vk, &proof, public_inputs).unwrap())
+    });
+    group.finish();
+}
+
+fn all_backends<C: ConstraintSynthesizer<Fr> + Clone>(
+    c: &mut Criterion,
+    group: &str,
+    param: usize,
+    blank: C,
+    circuit: C,
+    public_inputs: &[Fr],
+) {
+    groth16(c, group, param, blank.clone(), circuit.clone(), public_inputs);
+    spartan(c, group, param, blank, circuit, public_inputs);
+}
+
+fn sha256(c: &mut Criterion) {
+    let rng = &mut test_rng();
+    for &blocks in SHA256_BLOCKS.iter() {
+        let circuit = Sha256Blocks::random(blocks, rng);
+        let public_inputs = circuit.public_inputs();
+        let blank = Sha256Blocks::blank(blocks);
+        all_backends(c, "sha256", blocks, blank, circuit, &public_inputs);
+    }
+}
+
+fn merkle(c: &mut Criterion) {
+    let rng = &mut test_rng();
+    for &depth in MERKLE_DEPTHS.iter() {
+        let circuit = MerkleMembership::<Fr>::random(depth, rng);
+        let public_inputs = circuit.public_inputs();
+        let blank = MerkleMembership::blank(depth);
+        all_backends(c, "merkle", depth, blank, circuit, &public_inputs);
+    }
+}
+
+fn rollup(c: &mut Criterion) {
+    let rng = &mut test_rng();
+    for &transfers in ROLLUP_TRANSFERS.iter() {
+        let circuit = Rollup::<Fr>::random(ROLLUP_DEPTH, transfers, rng);
+        let public_inputs = circuit.public_inputs();
+        let blank = Rollup::blank(ROLLUP_DEPTH, transfers);
+        all_backends(c, "rollup", transfers, blank, circuit, &public_inputs);
+    }
+}
+
+criterion_group!(benches, sha256, merkle, rollup);
+criterion_main!(benches);
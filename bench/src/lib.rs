@@ -0,0 +1,19 @@
+//! Standard circuits to benchmark the proof systems with, so that changes to
+//! the MSM, the FFT or the provers are measured on the same workloads:
+//!
+//! - [`Sha256Blocks`]: `k` compression blocks of SHA-256;
+//! - [`MerkleMembership`]: a MiMC Merkle path of depth `d`;
+//! - [`Rollup`]: `n` transfers between the accounts of a MiMC Merkle tree.
+//!
+//! Every circuit has a `blank` constructor for the setup, a `random`
+//! constructor carrying a valid witness, and `public_inputs` for the
+//! verifier. The criterion harnesses are in `benches/`.
+#![warn(future_incompatible, nonstandard_style, rust_2018_idioms)]
+
+pub mod merkle;
+pub mod rollup;
+pub mod sha256;
+
+pub use merkle::{MerkleMembership, MerkleTree};
+pub use rollup::{Rollup, Transfer};
+pub use sha256::Sha256Blocks;
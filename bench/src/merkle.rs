@@ -0,0 +1,219 @@
+use ark_ff::PrimeField;
+use rand::Rng;
+use zkp_gadgets::algebra::boolean::AllocatedBit;
+use zkp_gadgets::hashes::mimc::{block, block_enforce};
+use zkp_r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError, Variable};
+
+/// A complete binary Merkle tree of depth `depth`, with `parent = E(left,
+/// right)` for the MiMC block `E`.
+#[derive(Clone)]
+pub struct MerkleTree<F: PrimeField> {
+    // layers[0] are the leaves, the last layer is the root.
+    layers: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> MerkleTree<F> {
+    /// The tree over `leaves`, padded with zeros to `2^depth` leaves.
+    pub fn new(depth: usize, mut leaves: Vec<F>) -> Self {
+        assert!(leaves.len() <= 1 << depth);
+        leaves.resize(1 << depth, F::zero());
+
+        let mut layers = vec![leaves];
+        for _ in 0..depth {
+            let layer = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| block(pair[0], pair[1]))
+                .collect();
+            layers.push(layer);
+        }
+        MerkleTree { layers }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    pub fn root(&self) -> F {
+        self.layers[self.depth()][0]
+    }
+
+    pub fn leaf(&self, index: usize) -> F {
+        self.layers[0][index]
+    }
+
+    /// The siblings of the path from leaf `index` up to the root.
+    pub fn path(&self, index: usize) -> Vec<F> {
+        (0..self.depth())
+            .map(|level| self.layers[level][(index >> level) ^ 1])
+            .collect()
+    }
+
+    pub fn update(&mut self, index: usize, leaf: F) {
+        self.layers[0][index] = leaf;
+        for level in 0..self.depth() {
+            let i = index >> (level + 1);
+            let (left, right) = (self.layers[level][2 * i], self.layers[level][2 * i + 1]);
+            self.layers[level + 1][i] = block(left, right);
+        }
+    }
+}
+
+/// The root of the path from `leaf` at `index` with `siblings`.
+pub fn root_from_path<F: PrimeField>(leaf: F, index: usize, siblings: &[F]) -> F {
+    siblings
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (level, sibling)| {
+            if (index >> level) & 1 == 1 {
+                block(*sibling, node)
+            } else {
+                block(node, *sibling)
+            }
+        })
+}
+
+/// Allocate the `depth` bits of a leaf index, least significant first.
+pub fn alloc_index<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    index: Option<usize>,
+    depth: usize,
+) -> Result<Vec<AllocatedBit>, SynthesisError> {
+    (0..depth)
+        .map(|level| {
+            AllocatedBit::alloc(
+                cs.ns(|| format!("index bit {}", level)),
+                index.map(|index| (index >> level) & 1 == 1),
+            )
+        })
+        .collect()
+}
+
+/// Enforce the path from `leaf` at the position given by `index` bits, and
+/// return the root.
+pub fn enforce_root<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    leaf: (Variable, Option<F>),
+    index: &[AllocatedBit],
+    siblings: &[Option<F>],
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    assert_eq!(index.len(), siblings.len());
+
+    let mut node = leaf;
+    for (level, (bit, sibling)) in index.iter().zip(siblings).enumerate() {
+        let mut cs = cs.ns(|| format!("level {}", level));
+        let var_sibling = cs.alloc(
+            || "sibling",
+            || sibling.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+
+        // the node is the right child iff its bit is set.
+        let (left, right) = match (bit.get_value(), node.1, *sibling) {
+            (Some(true), Some(n), Some(s)) => (Some(s), Some(n)),
+            (Some(false), Some(n), Some(s)) => (Some(n), Some(s)),
+            _ => (None, None),
+        };
+        let var_left = cs.alloc(|| "left", || left.ok_or(SynthesisError::AssignmentMissing))?;
+        let var_right = cs.alloc(
+            || "right",
+            || right.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        cs.enforce(
+            || "left = node + bit * (sibling - node)",
+            |lc| lc + bit.get_variable(),
+            |lc| lc + var_sibling - node.0,
+            |lc| lc + var_left - node.0,
+        );
+        cs.enforce(
+            || "right = node + sibling - left",
+            |lc| lc + node.0 + var_sibling - var_left,
+            |lc| lc + CS::one(),
+            |lc| lc + var_right,
+        );
+
+        node = block_enforce(cs.ns(|| "mimc"), (var_left, left), (var_right, right))?;
+    }
+
+    Ok(node)
+}
+
+/// Membership of a private leaf in a Merkle tree of depth `depth`, whose root
+/// is the public input.
+#[derive(Clone)]
+pub struct MerkleMembership<F: PrimeField> {
+    pub depth: usize,
+    pub leaf: Option<F>,
+    pub index: Option<usize>,
+    pub siblings: Option<Vec<F>>,
+}
+
+impl<F: PrimeField> MerkleMembership<F> {
+    pub fn blank(depth: usize) -> Self {
+        MerkleMembership {
+            depth,
+            leaf: None,
+            index: None,
+            siblings: None,
+        }
+    }
+
+    /// A random leaf and path; the rest of the tree is never built, so
+    /// `depth` can be as large as needed.
+    pub fn random<R: Rng>(depth: usize, rng: &mut R) -> Self {
+        MerkleMembership {
+            depth,
+            leaf: Some(F::rand(rng)),
+            index: Some(rng.gen_range(0, 1 << depth.min(63))),
+            siblings: Some((0..depth).map(|_| F::rand(rng)).collect()),
+        }
+    }
+
+    fn root(&self) -> Option<F> {
+        match (self.leaf, self.index, &self.siblings) {
+            (Some(leaf), Some(index), Some(siblings)) => {
+                Some(root_from_path(leaf, index, siblings))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn public_inputs(&self) -> Vec<F> {
+        vec![self.root().expect("public inputs of a blank circuit")]
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for MerkleMembership<F> {
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let root = self.root();
+        let var_root = cs.alloc_input(|| "root", || root.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let var_leaf = cs.alloc(
+            || "leaf",
+            || self.leaf.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let index = alloc_index(cs.ns(|| "index"), self.index, self.depth)?;
+        let siblings = match self.siblings {
+            Some(siblings) => siblings.into_iter().map(Some).collect(),
+            None => vec![None; self.depth],
+        };
+        let (var_computed, _) = enforce_root(
+            cs.ns(|| "path"),
+            (var_leaf, self.leaf),
+            &index,
+            &siblings,
+        )?;
+
+        cs.enforce(
+            || "root",
+            |lc| lc + var_computed,
+            |lc| lc + CS::one(),
+            |lc| lc + var_root,
+        );
+
+        Ok(())
+    }
+}
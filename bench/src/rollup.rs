@@ -0,0 +1,219 @@
+use ark_ff::PrimeField;
+use rand::Rng;
+use zkp_gadgets::algebra::boolean::u64_into_boolean_vec_le;
+use zkp_r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, LinearCombination, SynthesisError, Variable,
+};
+
+use crate::merkle::{alloc_index, enforce_root, MerkleTree};
+
+/// A transfer of `amount` from account `from` to account `to`.
+#[derive(Clone, Copy, Debug)]
+pub struct Transfer {
+    pub from: usize,
+    pub to: usize,
+    pub amount: u64,
+}
+
+/// The witness of one transfer: the balances before it and the paths of
+/// the sender before it and of the receiver after the sender is debited.
+#[derive(Clone)]
+struct Step<F: PrimeField> {
+    transfer: Transfer,
+    sender_balance: u64,
+    sender_path: Vec<F>,
+    receiver_balance: u64,
+    receiver_path: Vec<F>,
+}
+
+/// A batch of `transfers` applied in order to a Merkle tree of depth `depth`
+/// whose leaves are the account balances. Every transfer opens and updates
+/// the sender and receiver leaves, and range checks the amount and the new
+/// balances to 64 bits. The public inputs are the roots before and after
+/// the batch.
+#[derive(Clone)]
+pub struct Rollup<F: PrimeField> {
+    pub depth: usize,
+    pub transfers: usize,
+    steps: Option<Vec<Step<F>>>,
+    roots: Option<(F, F)>,
+}
+
+impl<F: PrimeField> Rollup<F> {
+    pub fn blank(depth: usize, transfers: usize) -> Self {
+        Rollup {
+            depth,
+            transfers,
+            steps: None,
+            roots: None,
+        }
+    }
+
+    /// Random balances below `2^32` in all `2^depth` accounts, and random
+    /// transfers between them.
+    pub fn random<R: Rng>(depth: usize, transfers: usize, rng: &mut R) -> Self {
+        assert!(depth > 0);
+        let accounts = 1 << depth;
+        let mut balances: Vec<u64> = (0..accounts).map(|_| rng.gen::<u32>() as u64).collect();
+        let mut tree = MerkleTree::new(depth, balances.iter().map(|b| F::from(*b)).collect());
+        let old_root = tree.root();
+
+        let mut steps = Vec::with_capacity(transfers);
+        for _ in 0..transfers {
+            let from = rng.gen_range(0, accounts);
+            let to = (from + rng.gen_range(1, accounts)) % accounts;
+            let amount = rng.gen_range(0, balances[from] + 1);
+
+            let sender_balance = balances[from];
+            let sender_path = tree.path(from);
+            balances[from] -= amount;
+            tree.update(from, F::from(balances[from]));
+
+            let receiver_balance = balances[to];
+            let receiver_path = tree.path(to);
+            balances[to] += amount;
+            tree.update(to, F::from(balances[to]));
+
+            steps.push(Step {
+                transfer: Transfer { from, to, amount },
+                sender_balance,
+                sender_path,
+                receiver_balance,
+                receiver_path,
+            });
+        }
+
+        Rollup {
+            depth,
+            transfers,
+            steps: Some(steps),
+            roots: Some((old_root, tree.root())),
+        }
+    }
+
+    pub fn public_inputs(&self) -> Vec<F> {
+        let (old_root, new_root) = self.roots.expect("public inputs of a blank circuit");
+        vec![old_root, new_root]
+    }
+}
+
+/// Allocate `value`, enforcing that it fits in 64 bits.
+fn alloc_u64<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    value: Option<u64>,
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let value_f = value.map(F::from);
+    let var = cs.alloc(
+        || "value",
+        || value_f.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+
+    let bits = u64_into_boolean_vec_le(cs.ns(|| "bits"), value)?;
+    let mut coeff = F::one();
+    let mut packed = LinearCombination::zero();
+    for bit in &bits {
+        packed = packed + &bit.lc(CS::one(), coeff);
+        coeff.double_in_place();
+    }
+    cs.enforce(
+        || "packing",
+        |_| packed,
+        |lc| lc + CS::one(),
+        |lc| lc + var,
+    );
+
+    Ok((var, value_f))
+}
+
+/// Enforce `a + sign * b = c`, for `sign` one or minus one.
+fn enforce_sum<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    a: Variable,
+    b: Variable,
+    sign: F,
+    c: Variable,
+) {
+    cs.enforce(
+        || "a + sign * b = c",
+        |lc| lc + a + (sign, b),
+        |lc| lc + CS::one(),
+        |lc| lc + c,
+    );
+}
+
+fn enforce_equal<F: PrimeField, CS: ConstraintSystem<F>>(mut cs: CS, a: Variable, b: Variable) {
+    cs.enforce(
+        || "a = b",
+        |lc| lc + a,
+        |lc| lc + CS::one(),
+        |lc| lc + b,
+    );
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for Rollup<F> {
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let (old_root, new_root) = match self.roots {
+            Some((old_root, new_root)) => (Some(old_root), Some(new_root)),
+            None => (None, None),
+        };
+        let var_old_root = cs.alloc_input(
+            || "old root",
+            || old_root.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let var_new_root = cs.alloc_input(
+            || "new root",
+            || new_root.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+
+        let mut root = var_old_root;
+        for i in 0..self.transfers {
+            let mut cs = cs.ns(|| format!("transfer {}", i));
+            let step = self.steps.as_ref().map(|steps| &steps[i]);
+            let transfer = step.map(|s| s.transfer);
+            let path = |path: Option<&Vec<F>>| match path {
+                Some(path) => path.iter().copied().map(Some).collect(),
+                None => vec![None; self.depth],
+            };
+
+            let amount = alloc_u64(cs.ns(|| "amount"), transfer.map(|t| t.amount))?;
+
+            // debit the sender.
+            let from = alloc_index(cs.ns(|| "from"), transfer.map(|t| t.from), self.depth)?;
+            let sender_path = path(step.map(|s| &s.sender_path));
+            let sender = step.map(|s| s.sender_balance);
+            let balance = alloc_u64(cs.ns(|| "sender balance"), sender)?;
+            let (opened, _) =
+                enforce_root(cs.ns(|| "sender opening"), balance, &from, &sender_path)?;
+            enforce_equal(cs.ns(|| "sender root"), opened, root);
+
+            let debited = alloc_u64(
+                cs.ns(|| "sender new balance"),
+                step.map(|s| s.sender_balance - s.transfer.amount),
+            )?;
+            enforce_sum(cs.ns(|| "debit"), balance.0, amount.0, -F::one(), debited.0);
+            root = enforce_root(cs.ns(|| "sender update"), debited, &from, &sender_path)?.0;
+
+            // credit the receiver.
+            let to = alloc_index(cs.ns(|| "to"), transfer.map(|t| t.to), self.depth)?;
+            let receiver_path = path(step.map(|s| &s.receiver_path));
+            let receiver = step.map(|s| s.receiver_balance);
+            let balance = alloc_u64(cs.ns(|| "receiver balance"), receiver)?;
+            let (opened, _) =
+                enforce_root(cs.ns(|| "receiver opening"), balance, &to, &receiver_path)?;
+            enforce_equal(cs.ns(|| "receiver root"), opened, root);
+
+            let credited = alloc_u64(
+                cs.ns(|| "receiver new balance"),
+                step.map(|s| s.receiver_balance + s.transfer.amount),
+            )?;
+            enforce_sum(cs.ns(|| "credit"), balance.0, amount.0, F::one(), credited.0);
+            root = enforce_root(cs.ns(|| "receiver update"), credited, &to, &receiver_path)?.0;
+        }
+        enforce_equal(cs.ns(|| "new root"), root, var_new_root);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use ark_ff::PrimeField;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use zkp_gadgets::algebra::boolean::{AllocatedBit, Boolean};
+use zkp_gadgets::hashes::sha256::sha256;
+use zkp_r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+
+/// Knowledge of a preimage of a SHA-256 digest, where the padded preimage is
+/// `blocks` blocks long. The digest bits are the public inputs.
+#[derive(Clone)]
+pub struct Sha256Blocks {
+    pub blocks: usize,
+    pub preimage: Option<Vec<u8>>,
+}
+
+impl Sha256Blocks {
+    /// The length of the longest preimage padded to `blocks` blocks.
+    pub fn preimage_len(blocks: usize) -> usize {
+        64 * blocks - 9
+    }
+
+    pub fn blank(blocks: usize) -> Self {
+        assert!(blocks > 0);
+        Sha256Blocks {
+            blocks,
+            preimage: None,
+        }
+    }
+
+    pub fn random<R: Rng>(blocks: usize, rng: &mut R) -> Self {
+        assert!(blocks > 0);
+        let preimage = (0..Self::preimage_len(blocks)).map(|_| rng.gen()).collect();
+        Sha256Blocks {
+            blocks,
+            preimage: Some(preimage),
+        }
+    }
+
+    fn digest(&self) -> Option<Vec<u8>> {
+        self.preimage
+            .as_ref()
+            .map(|preimage| Sha256::digest(preimage).to_vec())
+    }
+
+    pub fn public_inputs<F: PrimeField>(&self) -> Vec<F> {
+        let digest = self.digest().expect("public inputs of a blank circuit");
+        (0..256)
+            .map(|i| {
+                if bit_be(&digest, i) {
+                    F::one()
+                } else {
+                    F::zero()
+                }
+            })
+            .collect()
+    }
+}
+
+fn bit_be(bytes: &[u8], i: usize) -> bool {
+    (bytes[i / 8] >> (7 - i % 8)) & 1 == 1
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for Sha256Blocks {
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let digest = self.digest();
+
+        let mut preimage = Vec::with_capacity(8 * Self::preimage_len(self.blocks));
+        for i in 0..8 * Self::preimage_len(self.blocks) {
+            let value = self.preimage.as_ref().map(|p| bit_be(p, i));
+            let bit = AllocatedBit::alloc(cs.ns(|| format!("preimage bit {}", i)), value)?;
+            preimage.push(Boolean::from(bit));
+        }
+
+        let output = sha256(cs.ns(|| "sha256"), &preimage)?;
+
+        for (i, bit) in output.iter().enumerate() {
+            let value = digest.as_ref().map(|d| bit_be(d, i));
+            let input = cs.alloc_input(
+                || format!("digest bit {}", i),
+                || match value {
+                    Some(true) => Ok(F::one()),
+                    Some(false) => Ok(F::zero()),
+                    None => Err(SynthesisError::AssignmentMissing),
+                },
+            )?;
+            cs.enforce(
+                || format!("digest bit {}", i),
+                |_| bit.lc(CS::one(), F::one()),
+                |lc| lc + CS::one(),
+                |lc| lc + input,
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use ark_bls12_381::{Bls12_381 as E, Fr};
+use ark_ff::One;
+use ark_std::test_rng;
+use zkp_bench::{MerkleMembership, MerkleTree, Rollup, Sha256Blocks};
+use zkp_gadgets::hashes::mimc::block;
+use zkp_groth16::{
+    create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+};
+use zkp_r1cs::ConstraintSynthesizer;
+
+fn groth16<C: ConstraintSynthesizer<Fr>>(blank: C, circuit: C, public_inputs: &[Fr]) {
+    let rng = &mut test_rng();
+    let params = generate_random_parameters::<E, _, _>(blank, rng).unwrap();
+    let pvk = prepare_verifying_key(&params.vk);
+    let proof = create_random_proof(&params, circuit, rng).unwrap();
+    assert!(verify_proof(&pvk, &proof, public_inputs).unwrap());
+
+    let mut wrong = public_inputs.to_vec();
+    wrong[0] += Fr::one();
+    assert!(!verify_proof(&pvk, &proof, &wrong).unwrap());
+}
+
+#[test]
+fn merkle_tree() {
+    let leaves: Vec<_> = (0..5u64).map(Fr::from).collect();
+    let mut tree = MerkleTree::new(3, leaves);
+    assert_eq!(
+        tree.root(),
+        zkp_bench::merkle::root_from_path(tree.leaf(3), 3, &tree.path(3))
+    );
+
+    tree.update(6, Fr::one());
+    let left = block(tree.leaf(6), tree.leaf(7));
+    assert_eq!(tree.path(4)[1], left);
+    assert_eq!(
+        tree.root(),
+        zkp_bench::merkle::root_from_path(Fr::one(), 6, &tree.path(6))
+    );
+}
+
+#[test]
+fn sha256_blocks() {
+    let circuit = Sha256Blocks::random(1, &mut test_rng());
+    let public_inputs = circuit.public_inputs();
+    groth16(Sha256Blocks::blank(1), circuit, &public_inputs);
+}
+
+#[test]
+fn merkle_membership() {
+    let circuit = MerkleMembership::<Fr>::random(4, &mut test_rng());
+    let public_inputs = circuit.public_inputs();
+    groth16(MerkleMembership::blank(4), circuit, &public_inputs);
+}
+
+#[test]
+fn rollup() {
+    let circuit = Rollup::<Fr>::random(3, 2, &mut test_rng());
+    let public_inputs = circuit.public_inputs();
+    groth16(Rollup::blank(3, 2), circuit, &public_inputs);
+}
@@ -192,6 +192,119 @@ where
     G: Curve,
     C: ConstraintSynthesizer<G::Fr>,
     R: Rng,
+{
+    let (r1cs_circuit, input) = synthesize::<G, C>(circuit)?;
+    let num_constraints = r1cs_circuit.CL.len();
+
+    // create generators
+    // n_max
+    let n_max = cmp::max(input.aL.len(), input.w.len());
+    let N = n_max.next_power_of_two(); // N must be greater than or equal to n & n_w
+    let g_vec_N = create_generators::<G, _>(rng, N);
+    let h_vec_N = create_generators::<G, _>(rng, N);
+    let gh = create_generators::<G, _>(rng, 2);
+    let g = gh[0];
+    let h = gh[1];
+    let u = G::Projective::rand(rng).into_affine();
+
+    let n = num_constraints;
+    let k = input.s.len();
+    let n_w = input.w.len();
+    let generators = Generators {
+        g_vec_N,
+        h_vec_N,
+        g,
+        h,
+        u,
+        n,
+        N,
+        k,
+        n_w,
+    };
+
+    let proof = prove(&generators, &r1cs_circuit, &input, rng);
+
+    Ok((generators, r1cs_circuit.matrix_to_map(), proof))
+}
+
+/// Synthesize the circuit into the R1CS matrices, and derive generators for
+/// its shape with `hash_generators`, so that the verifier can fix them once
+/// instead of taking them from the prover.
+pub fn create_circuit_generators<G, C>(
+    circuit: C,
+) -> Result<(Generators<G>, R1csCircuit<G>), SynthesisError>
+where
+    G: Curve,
+    C: ConstraintSynthesizer<G::Fr>,
+{
+    let (r1cs_circuit, input) = synthesize::<G, C>(circuit)?;
+
+    let n = r1cs_circuit.CL.len();
+    let k = input.s.len();
+    let n_w = input.w.len();
+    let N = cmp::max(input.aL.len(), n_w).next_power_of_two();
+
+    let mut points = hash_generators::<G>(b"R1CS", 2 * N + 3);
+    let (g, h, u) = (points[2 * N], points[2 * N + 1], points[2 * N + 2]);
+    points.truncate(2 * N);
+    let h_vec_N = points.split_off(N);
+    let g_vec_N = points;
+
+    let generators = Generators {
+        g_vec_N,
+        h_vec_N,
+        g,
+        h,
+        u,
+        n,
+        N,
+        k,
+        n_w,
+    };
+
+    Ok((generators, r1cs_circuit.matrix_to_map()))
+}
+
+/// Prove the circuit under fixed generators, which must have been derived
+/// for a circuit of the same shape.
+pub fn create_proof<G, C, R>(
+    gens: &Generators<G>,
+    circuit: C,
+    rng: &mut R,
+) -> Result<Proof<G>, SynthesisError>
+where
+    G: Curve,
+    C: ConstraintSynthesizer<G::Fr>,
+    R: Rng,
+{
+    let (r1cs_circuit, input) = synthesize::<G, C>(circuit)?;
+    if r1cs_circuit.CL.len() != gens.n
+        || input.s.len() != gens.k
+        || input.w.len() != gens.n_w
+        || cmp::max(input.aL.len(), input.w.len()) > gens.N
+    {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+
+    Ok(prove(gens, &r1cs_circuit, &input, rng))
+}
+
+/// Synthesize the circuit into the R1CS matrices, without proving. Only the
+/// shape of the circuit matters, so the verifier can rebuild the circuit from
+/// any satisfying (or dummy) assignment.
+pub fn create_r1cs_circuit<G, C>(circuit: C) -> Result<R1csCircuit<G>, SynthesisError>
+where
+    G: Curve,
+    C: ConstraintSynthesizer<G::Fr>,
+{
+    let (r1cs_circuit, _) = synthesize::<G, C>(circuit)?;
+    Ok(r1cs_circuit.matrix_to_map())
+}
+
+fn synthesize<G, C>(circuit: C) -> Result<(R1csCircuit<G>, Assignment<G>), SynthesisError>
+where
+    G: Curve,
+    C: ConstraintSynthesizer<G::Fr>,
 {
     let mut prover = ProvingAssignment::<G::Fr> {
         at: vec![],
@@ -270,35 +383,7 @@ where
         w: prover.aux_assignment,
     };
 
-    // create generators
-    // n_max
-    let n_max = cmp::max(input.aL.len(), input.w.len());
-    let N = n_max.next_power_of_two(); // N must be greater than or equal to n & n_w
-    let g_vec_N = create_generators::<G, _>(rng, N);
-    let h_vec_N = create_generators::<G, _>(rng, N);
-    let gh = create_generators::<G, _>(rng, 2);
-    let g = gh[0];
-    let h = gh[1];
-    let u = G::Projective::rand(rng).into_affine();
-
-    let n = num_constraints;
-    let k = input.s.len();
-    let n_w = input.w.len();
-    let generators = Generators {
-        g_vec_N,
-        h_vec_N,
-        g,
-        h,
-        u,
-        n,
-        N,
-        k,
-        n_w,
-    };
-
-    let proof = prove(&generators, &r1cs_circuit, &input, rng);
-
-    Ok((generators, r1cs_circuit.matrix_to_map(), proof))
+    Ok((r1cs_circuit, input))
 }
 
 // bulletproofs arithmetic circuit proof with R1CS format
@@ -598,6 +683,20 @@ pub fn verify_proof<G: Curve>(
     r1cs_circuit: &R1csCircuit<G>,
     public_inputs: &[G::Fr],
 ) -> Result<bool, SynthesisError> {
+    // reject malformed generators and proofs instead of indexing out of them.
+    if gens.n == 0
+        || gens.n > gens.N
+        || gens.n_w > gens.N
+        || !gens.N.is_power_of_two()
+        || gens.g_vec_N.len() != gens.N
+        || gens.h_vec_N.len() != gens.N
+        || public_inputs.len() + 1 != gens.k
+        || proof.l_x.len() != gens.N
+        || proof.r_x.len() != gens.N
+    {
+        return Ok(false);
+    }
+
     let mut transcript = Transcript::new(b"protocol3");
     let zero = G::Fr::zero();
     let one = G::Fr::one();
@@ -790,7 +889,9 @@ pub fn verify_proof<G: Curve>(
             + &proof.T_9.mul(xxxx * &(xxxx * &x))
             + &proof.T_10.mul(xxxx * &(xxxx * &xx));
 
-    assert_eq!(checkT_lhs, checkT_rhs);
+    if checkT_lhs != checkT_rhs {
+        return Ok(false);
+    }
 
     let y_n_neg: Vec<G::Fr> = (0..gens.N).map(|i| -one * &y_n[i]).collect();
     let P = proof.A_I.mul(xx)
@@ -809,6 +910,26 @@ pub fn verify_proof<G: Curve>(
     Ok(P == checkP)
 }
 
+/// Derive `len` generators by hashing `label` and a counter onto the curve,
+/// so that nobody knows a discrete logarithm between any two of them.
+pub fn hash_generators<G: Curve>(label: &'static [u8], len: usize) -> Vec<G::Affine> {
+    let mut transcript = Transcript::new(b"generators");
+    transcript.append_message(b"label", label);
+
+    let mut generators = Vec::with_capacity(len);
+    while generators.len() < len {
+        let mut bytes = [0u8; 64];
+        transcript.challenge_bytes(b"point", &mut bytes);
+        if let Some(point) = G::Affine::from_random_bytes(&bytes) {
+            let point = point.mul_by_cofactor();
+            if !point.is_zero() {
+                generators.push(point);
+            }
+        }
+    }
+    generators
+}
+
 pub fn create_generators<G: Curve, R: Rng>(rng: &mut R, len: usize) -> Vec<G::Affine> {
     let mut generators = Vec::new();
     for _ in 0..len {
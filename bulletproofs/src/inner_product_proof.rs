@@ -111,8 +111,14 @@ pub fn verify<G: Curve>(
 ) -> bool {
     let mut transcript = Transcript::new(b"protocol2");
     let lg_n = proof.L_vec.len();
+    if lg_n != proof.R_vec.len()
+        || lg_n >= 32
+        || g_vec.len() != 1 << lg_n
+        || h_vec.len() != g_vec.len()
+    {
+        return false;
+    }
     let n = 1 << lg_n;
-    assert_eq!(lg_n, proof.R_vec.len());
 
     let mut x_sq_vec = Vec::with_capacity(lg_n);
     let mut x_inv_sq_vec = Vec::with_capacity(lg_n);
@@ -31,7 +31,10 @@ pub use arithmetic_circuit::create_random_proof;
 /// standard interface for verify proof.
 pub use arithmetic_circuit::verify_proof;
 
-pub use arithmetic_circuit::{Generators, Proof, R1csCircuit};
+pub use arithmetic_circuit::{
+    create_circuit_generators, create_proof, create_r1cs_circuit, hash_generators, Generators,
+    Proof, R1csCircuit,
+};
 
 // Q (vector, zQ) * Qxn (matrix, WL, WR, WO) = n (vector, zQW)
 pub fn vector_matrix_product<F: Field>(v: &Vec<F>, m: &Vec<Vec<F>>) -> Vec<F> {
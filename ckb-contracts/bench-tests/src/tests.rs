@@ -666,10 +666,10 @@ fn test_plonk() {
     println!("Plonk: size of the circuit: {}", cs.size());
 
     println!("Plonk: setting up srs...");
-    let srs = PlonkInst::setup(8, rng).unwrap();
+    let srs = PlonkInst::setup(cs.srs_degree().unwrap(), rng).unwrap();
 
     println!("Plonk: generating keys...");
-    let (pk, vk) = PlonkInst::keygen(&srs, &cs, ks).unwrap();
+    let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks).unwrap();
     let mut vk_bytes = Vec::new();
     vk.serialize_unchecked(&mut vk_bytes).unwrap();
     println!("Plonk: VerifyKey length: {}", vk_bytes.len());
@@ -0,0 +1,4 @@
+//! The parts of the toolkit its binaries share and a prover can call, e.g.
+//! the encrypted witness files of `zkp-witness`, see `witness`.
+
+pub mod witness;
@@ -0,0 +1,225 @@
+//! Encrypted witness files.
+//!
+//! A witness file holds the circuit arguments sealed with ChaCha20-Poly1305,
+//! so secret inputs never sit on disk in the clear between witness generation
+//! and proving. The layout is `MAGIC || VERSION || nonce || ciphertext`; the
+//! header and the circuit name are authenticated as associated data, so a
+//! file sealed for one circuit is rejected by another. The plaintext is the
+//! JSON array of the circuit arguments, which `seal` takes from a file or
+//! the standard input with `read_args` rather than from the command line,
+//! where other users could see them.
+
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use std::io::{Read, Write};
+use std::path::Path;
+use zeroize::{Zeroize, Zeroizing};
+
+const MAGIC: &[u8; 4] = b"ZKPW";
+const VERSION: u8 = 1;
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const HEADER_SIZE: usize = MAGIC.len() + 1 + NONCE_SIZE;
+
+/// The key witness files are sealed with, zeroed on drop.
+pub struct WitnessKey([u8; KEY_SIZE]);
+
+impl Drop for WitnessKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl WitnessKey {
+    /// Generate a fresh key and write it to `path`, readable by the owner only.
+    pub fn generate(path: &Path) -> Result<WitnessKey, String> {
+        let mut key = WitnessKey([0u8; KEY_SIZE]);
+        rand::thread_rng().fill_bytes(&mut key.0);
+
+        write_private(path, &key.0)?;
+
+        Ok(key)
+    }
+
+    pub fn load(path: &Path) -> Result<WitnessKey, String> {
+        let mut bytes =
+            std::fs::read(path).map_err(|e| format!("cannot read key file {:?}: {}", path, e))?;
+        if bytes.len() != KEY_SIZE {
+            bytes.zeroize();
+            return Err(format!("key file {:?} is not a witness key", path));
+        }
+
+        let mut key = WitnessKey([0u8; KEY_SIZE]);
+        key.0.copy_from_slice(&bytes);
+        bytes.zeroize();
+        Ok(key)
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(&Key::from(self.0))
+    }
+}
+
+/// Write `bytes` to a new file at `path`, readable by the owner only.
+pub fn write_private(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("cannot create {:?}: {}", path, e))?;
+    file.write_all(bytes)
+        .map_err(|e| format!("cannot write {:?}: {}", path, e))
+}
+
+/// Read the circuit arguments from `reader`, separated by whitespace. They
+/// are zeroized when dropped, as is what is read.
+pub fn read_args<R: Read>(mut reader: R) -> Result<Zeroizing<Vec<String>>, String> {
+    let mut input = Zeroizing::new(String::new());
+    reader
+        .read_to_string(&mut input)
+        .map_err(|e| format!("cannot read the arguments: {}", e))?;
+    Ok(Zeroizing::new(
+        input.split_whitespace().map(str::to_owned).collect(),
+    ))
+}
+
+fn aad(header: &[u8], circuit: &str) -> Vec<u8> {
+    [header, circuit.as_bytes()].concat()
+}
+
+/// Seal the arguments of `circuit`.
+pub fn seal(key: &WitnessKey, circuit: &str, args: &[String]) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(MAGIC);
+    header.push(VERSION);
+    header.extend_from_slice(&nonce);
+
+    let mut plaintext = serde_json::to_vec(args).map_err(|e| e.to_string())?;
+    let ciphertext = key.cipher().encrypt(
+        &Nonce::from(nonce),
+        Payload {
+            msg: &plaintext,
+            aad: &aad(&header, circuit),
+        },
+    );
+    plaintext.zeroize();
+    let ciphertext = ciphertext.map_err(|_| "witness encryption failed".to_owned())?;
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Open a witness file sealed for `circuit`, returning its arguments, which
+/// are zeroized when dropped.
+pub fn open(
+    key: &WitnessKey,
+    circuit: &str,
+    bytes: &[u8],
+) -> Result<Zeroizing<Vec<String>>, String> {
+    if bytes.len() < HEADER_SIZE || &bytes[..MAGIC.len()] != MAGIC {
+        return Err("not a witness file".to_owned());
+    }
+    if bytes[MAGIC.len()] != VERSION {
+        return Err(format!(
+            "unsupported witness file version {}",
+            bytes[MAGIC.len()]
+        ));
+    }
+
+    let (header, ciphertext) = bytes.split_at(HEADER_SIZE);
+    let mut nonce = [0u8; NONCE_SIZE];
+    nonce.copy_from_slice(&header[MAGIC.len() + 1..]);
+    let mut plaintext = key
+        .cipher()
+        .decrypt(
+            &Nonce::from(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &aad(header, circuit),
+            },
+        )
+        .map_err(|_| format!("witness file is corrupted or not sealed for {}", circuit))?;
+
+    let args = serde_json::from_slice(&plaintext)
+        .map(Zeroizing::new)
+        .map_err(|e| e.to_string());
+    plaintext.zeroize();
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> WitnessKey {
+        let mut key = WitnessKey([0u8; KEY_SIZE]);
+        rand::thread_rng().fill_bytes(&mut key.0);
+        key
+    }
+
+    fn args() -> Vec<String> {
+        vec!["3".to_owned(), "35".to_owned()]
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let key = key();
+        let sealed = seal(&key, "mini", &args()).unwrap();
+        assert_eq!(*open(&key, "mini", &sealed).unwrap(), args());
+
+        // the nonce is fresh for every file.
+        assert_ne!(seal(&key, "mini", &args()).unwrap(), sealed);
+    }
+
+    #[test]
+    fn test_read_args() {
+        let args = read_args(&b"3\n 35\t"[..]).unwrap();
+        assert_eq!(*args, self::args());
+        assert!(read_args(&b""[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_open_wrong_circuit_or_key() {
+        let key = key();
+        let sealed = seal(&key, "mini", &args()).unwrap();
+        assert!(open(&key, "hash", &sealed).is_err());
+        assert!(open(&self::key(), "mini", &sealed).is_err());
+    }
+
+    #[test]
+    fn test_open_tampered() {
+        let key = key();
+        let sealed = seal(&key, "mini", &args()).unwrap();
+
+        // a byte of the nonce, of the ciphertext and of the tag.
+        for &i in &[MAGIC.len() + 1, HEADER_SIZE, sealed.len() - 1] {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            assert!(open(&key, "mini", &tampered).is_err());
+        }
+        assert!(open(&key, "mini", &sealed[..sealed.len() - 1]).is_err());
+        assert!(open(&key, "mini", &sealed[..HEADER_SIZE - 1]).is_err());
+    }
+
+    #[test]
+    fn test_open_unknown_version() {
+        let key = key();
+        let mut sealed = seal(&key, "mini", &args()).unwrap();
+        sealed[MAGIC.len()] = VERSION + 1;
+        let err = open(&key, "mini", &sealed).unwrap_err();
+        assert!(err.contains("version"));
+
+        sealed[0] ^= 1;
+        assert_eq!(open(&key, "mini", &sealed).unwrap_err(), "not a witness file");
+    }
+}
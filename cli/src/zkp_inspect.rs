@@ -0,0 +1,76 @@
+use ark_poly::univariate::DensePolynomial;
+use ark_serialize::*;
+use blake2::Blake2s;
+use std::env;
+use std::path::PathBuf;
+
+macro_rules! handle_object {
+    ($field:ty, $pc:ty, $object:expr, $bytes:expr) => {
+        match $object {
+            "proof" => {
+                use zkp_plonk::Proof;
+                let proof = Proof::<$field, $pc>::deserialize(&$bytes[..])
+                    .map_err(|e| format!("Proof invalid: {:?}", e))?;
+                println!("{}", proof.inspect());
+            }
+            "vk" => {
+                use zkp_plonk::VerifierKey;
+                let vk = VerifierKey::<$field, $pc>::deserialize(&$bytes[..])
+                    .map_err(|e| format!("Verifier key invalid: {:?}", e))?;
+                println!("{}", vk.inspect());
+            }
+            _ => return Err(format!("OBJECT: {} not implement.", $object)),
+        };
+    };
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 5 {
+        println!("zkp-inspect");
+        println!();
+        println!("Usage: zkp-inspect [SCHEME] [CURVE] [OBJECT] [FILE]");
+        println!();
+        println!("SCHEME:");
+        println!("    plonk_kzg -- PlonK with the KZG polynomial commitment.");
+        println!("    plonk_ipa -- PlonK with the inner product argument commitment.");
+        println!();
+        println!("CURVE:");
+        println!("    bls12_381 -- BLS12_381 pairing curve.");
+        println!();
+        println!("OBJECT:");
+        println!("    proof -- a serialized proof.");
+        println!("    vk    -- a serialized verifier key.");
+        println!();
+
+        return Err("Params invalid!".to_owned());
+    }
+
+    let (scheme, curve, object) = (args[1].as_str(), args[2].as_str(), args[3].as_str());
+    let bytes = std::fs::read(PathBuf::from(&args[4])).expect("file not found!");
+
+    match curve {
+        "bls12_381" => {
+            use ark_bls12_381::{Bls12_381, Fr, G1Affine};
+            match scheme {
+                "plonk_kzg" => {
+                    use ark_poly_commit::marlin_pc::MarlinKZG10;
+                    handle_object!(Fr, MarlinKZG10<Bls12_381, DensePolynomial<Fr>>, object, bytes);
+                }
+                "plonk_ipa" => {
+                    use ark_poly_commit::ipa_pc::InnerProductArgPC;
+                    handle_object!(
+                        Fr,
+                        InnerProductArgPC<G1Affine, Blake2s, DensePolynomial<Fr>>,
+                        object,
+                        bytes
+                    );
+                }
+                _ => return Err(format!("SCHEME: {} not implement.", scheme)),
+            }
+        }
+        _ => return Err(format!("Curve: {} not implement.", curve)),
+    }
+
+    Ok(())
+}
@@ -1,12 +1,14 @@
 use ark_serialize::*;
 use serde_json::json;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use zkp_curve::Curve;
 
 mod circuits;
 use circuits::{CliCircuit, Publics};
 
+use zkp_cli::witness::{self, WitnessKey};
+
 use circuits::hash::Hash;
 use circuits::mini::Mini;
 
@@ -128,6 +130,7 @@ fn main() -> Result<(), String> {
         println!("zkp-prove");
         println!("");
         println!("Usage: zkp-prove [SCHEME] [CURVE] [CIRCUIT] [ARGUMENTS]");
+        println!("       zkp-prove [SCHEME] [CURVE] [CIRCUIT] --witness [WITNESS_FILE] --key [KEY_FILE]");
         println!("");
         println!("SCHEME:");
         println!("    groth16       -- Groth16 zero-knowledge proof system.");
@@ -145,6 +148,9 @@ fn main() -> Result<(), String> {
         println!("CIRCUIT ARGUMENTS:");
         println!("    [arguments]    -- circuits arguments.");
         println!("");
+        println!("WITNESS FILE:");
+        println!("    circuits arguments encrypted by `zkp-witness seal`.");
+        println!();
         println!("");
 
         return Err("Params invalid!".to_owned());
@@ -152,10 +158,23 @@ fn main() -> Result<(), String> {
 
     let (curve, scheme, circuit) = (args[2].as_str(), args[1].as_str(), args[3].as_str());
 
+    let sealed_args;
+    let circuit_args = if args.len() > 4 && args[4] == "--witness" {
+        if args.len() != 8 || args[6] != "--key" {
+            return Err("Usage: --witness [WITNESS_FILE] --key [KEY_FILE]".to_owned());
+        }
+        let key = WitnessKey::load(Path::new(&args[7]))?;
+        let bytes = std::fs::read(&args[5]).map_err(|e| e.to_string())?;
+        sealed_args = witness::open(&key, circuit, &bytes)?;
+        &sealed_args[..]
+    } else {
+        &args[4..]
+    };
+
     match curve {
         "bls12_381" => {
             use ark_bls12_381::Bls12_381;
-            handle_circuit!(Bls12_381, curve, scheme, circuit, &args[4..]);
+            handle_circuit!(Bls12_381, curve, scheme, circuit, circuit_args);
         }
         _ => return Err(format!("Curve: {} not implement.", curve)),
     }
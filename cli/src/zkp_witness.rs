@@ -0,0 +1,57 @@
+use std::env;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use zkp_cli::witness::{self, WitnessKey};
+
+fn usage() -> Result<(), String> {
+    println!("zkp-witness");
+    println!();
+    println!("Usage: zkp-witness keygen [KEY_FILE]");
+    println!("       zkp-witness seal [KEY_FILE] [CIRCUIT] [WITNESS_FILE] [ARGUMENTS_FILE]");
+    println!();
+    println!("COMMANDS:");
+    println!("    keygen  -- Generate a witness key, readable by the owner only.");
+    println!("    seal    -- Encrypt the circuit arguments into a witness file.");
+    println!();
+    println!("The arguments of `seal` are read from ARGUMENTS_FILE, or from stdin if it");
+    println!("is `-` or omitted, separated by whitespace.");
+    println!();
+    println!("Prove with: zkp-prove [SCHEME] [CURVE] [CIRCUIT] --witness [WITNESS_FILE] --key [KEY_FILE]");
+    println!();
+
+    Err("Params invalid!".to_owned())
+}
+
+fn main() -> Result<(), String> {
+    let args: Vec<_> = env::args().collect();
+    if args.len() < 3 {
+        return usage();
+    }
+
+    match args[1].as_str() {
+        "keygen" => {
+            WitnessKey::generate(Path::new(&args[2]))?;
+            println!("Witness key: {}", args[2]);
+        }
+        "seal" => {
+            if args.len() < 5 || args.len() > 6 {
+                return usage();
+            }
+            let key = WitnessKey::load(Path::new(&args[2]))?;
+            let circuit_args = match args.get(5).map(String::as_str) {
+                None | Some("-") => witness::read_args(io::stdin())?,
+                Some(path) => witness::read_args(
+                    File::open(path).map_err(|e| format!("cannot read {:?}: {}", path, e))?,
+                )?,
+            };
+            let bytes = witness::seal(&key, &args[3], &circuit_args)?;
+            witness::write_private(Path::new(&args[4]), &bytes)?;
+            println!("Witness file: {}", args[4]);
+        }
+        _ => return usage(),
+    }
+
+    Ok(())
+}
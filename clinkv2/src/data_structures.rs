@@ -0,0 +1,408 @@
+use ark_ff::{FftField, Field};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_serialize::*;
+use core::marker::PhantomData;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::estimate::{Estimate, Shape};
+use crate::pc::PolynomialCommitment;
+use crate::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable,
+};
+use crate::{String, Vec};
+
+/// The proof in Clinkv2, with the commitments `C` and the opening proof `P`
+/// of its polynomial commitment scheme.
+#[derive(Clone, Debug, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof<F: Field, C, P>
+where
+    C: CanonicalSerialize + CanonicalDeserialize,
+    P: CanonicalSerialize + CanonicalDeserialize,
+{
+    pub r_mid_comms: Vec<C>,
+    pub q_comm: C,
+    pub r_mid_q_values: Vec<F>,
+    pub r_mid_q_proof: P,
+    pub opening_challenge: F,
+}
+
+fn push_constraints<F: Field>(
+    l: LinearCombination<F>,
+    constraints: &mut [Vec<(F, Index)>],
+    this_constraint: usize,
+) {
+    for (var, coeff) in l.as_ref() {
+        match var.get_unchecked() {
+            Index::Input(i) => constraints[this_constraint].push((*coeff, Index::Input(i))),
+            Index::Aux(i) => constraints[this_constraint].push((*coeff, Index::Aux(i))),
+        }
+    }
+}
+
+/// The constraints and the assignment of a circuit, to prove it under `PC`.
+pub struct ProveAssignment<F: Field, PC> {
+    // Constraints
+    pub at: Vec<Vec<(F, Index)>>,
+    pub bt: Vec<Vec<(F, Index)>>,
+    pub ct: Vec<Vec<(F, Index)>>,
+
+    // Assignments of variables
+    // Two-demension vector
+    pub input_assignment: Vec<Vec<F>>,
+    pub aux_assignment: Vec<Vec<F>>,
+
+    pub(crate) io_cur: usize,
+    pub(crate) aux_cur: usize,
+    /// The capacity of a new column, see `with_instances`.
+    column_capacity: usize,
+    /// The index of the first instance, which starts the columns.
+    first_instance: usize,
+
+    _pc: PhantomData<fn() -> PC>,
+}
+
+impl<F: Field, PC> Default for ProveAssignment<F, PC> {
+    fn default() -> ProveAssignment<F, PC> {
+        ProveAssignment::with_column_capacity(0)
+    }
+}
+
+impl<F: Field, PC> ProveAssignment<F, PC> {
+    fn with_column_capacity(column_capacity: usize) -> Self {
+        ProveAssignment {
+            at: vec![],
+            bt: vec![],
+            ct: vec![],
+            input_assignment: vec![],
+            aux_assignment: vec![],
+            io_cur: 0usize,
+            aux_cur: 0usize,
+            column_capacity,
+            first_instance: 0usize,
+            _pc: PhantomData,
+        }
+    }
+
+    /// The number of instances synthesized so far.
+    pub fn num_instances(&self) -> usize {
+        self.input_assignment.first().map_or(0, Vec::len)
+    }
+
+    /// Synthesizes `circuit` as the next instance. With `with_instances`,
+    /// the instances of a large batch are fed one by one from a stream,
+    /// without holding them or growing the columns on the way.
+    pub fn push_instance<C: ConstraintSynthesizer<F>>(
+        &mut self,
+        circuit: C,
+    ) -> Result<(), SynthesisError> {
+        let index = self.num_instances();
+        circuit.generate_constraints(self, index)
+    }
+
+    fn new_column(&self, value: F) -> Vec<F> {
+        let mut column = Vec::with_capacity(self.column_capacity);
+        column.push(value);
+        column
+    }
+
+    /// Appends the instances and the constraints of `other`, whose
+    /// instances follow these.
+    #[cfg(feature = "parallel")]
+    fn append(&mut self, other: &Self) {
+        self.at.extend_from_slice(&other.at);
+        self.bt.extend_from_slice(&other.bt);
+        self.ct.extend_from_slice(&other.ct);
+        let capacity = self.column_capacity;
+        append_columns(&mut self.input_assignment, &other.input_assignment, capacity);
+        append_columns(&mut self.aux_assignment, &other.aux_assignment, capacity);
+    }
+}
+
+/// Appends the values of `other` to `columns`, creating them with
+/// `capacity` if there are none yet. The values are copied, so that those of
+/// `other` are overwritten when it is dropped.
+#[cfg(feature = "parallel")]
+fn append_columns<F: Field>(columns: &mut Vec<Vec<F>>, other: &[Vec<F>], capacity: usize) {
+    if columns.is_empty() {
+        columns.resize_with(other.len(), || Vec::with_capacity(capacity));
+    }
+    for (column, other) in columns.iter_mut().zip(other) {
+        column.extend_from_slice(other);
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<F: Field, PC> ProveAssignment<F, PC> {
+    /// Synthesizes `circuits` as the next instances, in parallel. Every
+    /// thread synthesizes runs of consecutive instances, with their global
+    /// indices, into assignments of its own, which are then appended in
+    /// order. The constraints come from the run of the instance enforcing
+    /// them, so the result is that of `push_instance` on each circuit.
+    pub fn push_instances_par<C>(&mut self, circuits: Vec<C>) -> Result<(), SynthesisError>
+    where
+        C: ConstraintSynthesizer<F> + Send,
+    {
+        let start = self.num_instances();
+        let runs = circuits
+            .into_par_iter()
+            .enumerate()
+            .fold(
+                || Ok(None),
+                |run: Result<Option<Self>, SynthesisError>, (i, circuit)| {
+                    let mut run = run?.unwrap_or_else(|| {
+                        let mut run = Self::with_column_capacity(0);
+                        run.first_instance = start + i;
+                        run
+                    });
+                    circuit.generate_constraints(&mut run, start + i)?;
+                    Ok(Some(run))
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        for run in runs.iter().flatten() {
+            self.append(run);
+        }
+        Ok(())
+    }
+}
+
+impl<F: FftField, PC: PolynomialCommitment<F>> ProveAssignment<F, PC> {
+    /// An assignment for `n` instances, whose columns are allocated once at
+    /// the size of the evaluation domain, so that
+    /// `create_random_proof_in_place` interpolates them where they are.
+    pub fn with_instances(n: usize) -> Result<Self, SynthesisError> {
+        let domain = GeneralEvaluationDomain::<F>::new(n)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        Ok(Self::with_column_capacity(domain.size()))
+    }
+
+    /// The predicted peak memory and prove time of `create_random_proof`
+    /// for this assignment.
+    pub fn estimate(&self) -> Result<Estimate, SynthesisError> {
+        let shape = Shape::new(
+            &self.at,
+            &self.bt,
+            &self.ct,
+            &self.input_assignment,
+            &self.aux_assignment,
+        )?;
+        Ok(Estimate::prover::<F>(&shape, PC::COMMIT_MSMS, PC::OPEN_MSMS))
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<F: Field, PC> Drop for ProveAssignment<F, PC> {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        self.aux_assignment
+            .iter_mut()
+            .flatten()
+            .for_each(Zeroize::zeroize);
+    }
+}
+
+impl<F: Field, PC> ConstraintSystem<F> for ProveAssignment<F, PC> {
+    type Root = Self;
+
+    #[inline]
+    fn alloc<FN, A, AR>(&mut self, _: A, f: FN, i: usize) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if i == self.first_instance {
+            if self.aux_assignment.is_empty() {
+                self.aux_cur = 0;
+            }
+            let column = self.new_column(f()?);
+            self.aux_assignment.push(column);
+            let index = self.aux_cur;
+            self.aux_cur += 1;
+            Ok(Variable::new_unchecked(Index::Aux(index)))
+        } else {
+            if i == self.first_instance + self.aux_assignment[0].len() {
+                self.aux_cur = 0;
+            }
+            self.aux_assignment[self.aux_cur].push(f()?);
+            let index = self.aux_cur;
+            self.aux_cur += 1;
+            Ok(Variable::new_unchecked(Index::Aux(index)))
+        }
+    }
+
+    #[inline]
+    fn alloc_input<FN, A, AR>(&mut self, _: A, f: FN, i: usize) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if i == self.first_instance {
+            if self.input_assignment.is_empty() {
+                self.io_cur = 0;
+            }
+            let column = self.new_column(f()?);
+            self.input_assignment.push(column);
+            let index = self.io_cur;
+            self.io_cur += 1;
+            Ok(Variable::new_unchecked(Index::Input(index)))
+        } else {
+            if i == self.first_instance + self.input_assignment[0].len() {
+                self.io_cur = 0;
+            }
+            self.input_assignment[self.io_cur].push(f()?);
+            let index = self.io_cur;
+            self.io_cur += 1;
+            Ok(Variable::new_unchecked(Index::Input(index)))
+        }
+    }
+
+    #[inline]
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        let num_constraints = self.num_constraints();
+
+        self.at.push(Vec::new());
+        self.bt.push(Vec::new());
+        self.ct.push(Vec::new());
+
+        push_constraints(a(LinearCombination::zero()), &mut self.at, num_constraints);
+        push_constraints(b(LinearCombination::zero()), &mut self.bt, num_constraints);
+        push_constraints(c(LinearCombination::zero()), &mut self.ct, num_constraints);
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.at.len()
+    }
+}
+
+/// The constraints of a circuit, to verify its proofs under `PC`.
+pub struct VerifyAssignment<F: Field, PC> {
+    // Constraints
+    pub at: Vec<Vec<(F, Index)>>,
+    pub bt: Vec<Vec<(F, Index)>>,
+    pub ct: Vec<Vec<(F, Index)>>,
+
+    // Assignments of variables
+    // Two-demension vector
+    pub input_assignment: Vec<Vec<F>>,
+    pub aux_assignment: Vec<Vec<F>>,
+
+    pub(crate) io_cur: usize,
+    pub(crate) aux_cur: usize,
+
+    _pc: PhantomData<fn() -> PC>,
+}
+
+impl<F: Field, PC> Default for VerifyAssignment<F, PC> {
+    fn default() -> VerifyAssignment<F, PC> {
+        VerifyAssignment {
+            at: vec![],
+            bt: vec![],
+            ct: vec![],
+            input_assignment: vec![],
+            aux_assignment: vec![],
+            io_cur: 0usize,
+            aux_cur: 0usize,
+            _pc: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, PC> ConstraintSystem<F> for VerifyAssignment<F, PC> {
+    type Root = Self;
+
+    #[inline]
+    fn alloc<FN, A, AR>(&mut self, _: A, _f: FN, _i: usize) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index = self.aux_cur;
+        self.aux_cur += 1;
+        Ok(Variable::new_unchecked(Index::Aux(index)))
+    }
+
+    #[inline]
+    fn alloc_input<FN, A, AR>(
+        &mut self,
+        _: A,
+        _f: FN,
+        _i: usize,
+    ) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index = self.io_cur;
+        self.io_cur += 1;
+        Ok(Variable::new_unchecked(Index::Input(index)))
+    }
+
+    #[inline]
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        let num_constraints = self.num_constraints();
+
+        self.at.push(Vec::new());
+        self.bt.push(Vec::new());
+        self.ct.push(Vec::new());
+
+        push_constraints(a(LinearCombination::zero()), &mut self.at, num_constraints);
+        push_constraints(b(LinearCombination::zero()), &mut self.bt, num_constraints);
+        push_constraints(c(LinearCombination::zero()), &mut self.ct, num_constraints);
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.at.len()
+    }
+}
@@ -0,0 +1,86 @@
+//! The versioned encoding of proofs and keys, to hand them to a verifier in
+//! another process or to store them on-chain.
+//!
+//! A proof, a committer key or a verifier key is a version byte,
+//! `ENCODING_VERSION`, a byte for what it holds, `1` for a verifier key, `2`
+//! for a proof and `3` for a committer key, a byte for its scheme, `1` for
+//! `kzg10` and `2` for `ipa`, and its compressed canonical encoding. The
+//! committer key of `ipa` is also its verifier key, and is encoded as a
+//! committer key.
+
+use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use zkp_curve::Curve;
+
+use crate::{ipa, kzg10, Vec};
+
+/// The version of the versioned encoding written and read by this crate.
+pub const ENCODING_VERSION: u8 = 1;
+
+/// What a versioned encoding holds, its second byte.
+#[derive(Clone, Copy)]
+enum Kind {
+    VerifierKey = 1,
+    Proof = 2,
+    ProveKey = 3,
+}
+
+/// The scheme of a versioned encoding, its third byte.
+#[derive(Clone, Copy)]
+enum Scheme {
+    Kzg10 = 1,
+    Ipa = 2,
+}
+
+/// `value` in the versioned encoding, as a `kind` of `scheme`.
+fn versioned<T: CanonicalSerialize>(
+    kind: Kind,
+    scheme: Scheme,
+    value: &T,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut bytes = Vec::with_capacity(3 + value.serialized_size());
+    bytes.extend_from_slice(&[ENCODING_VERSION, kind as u8, scheme as u8]);
+    value.serialize(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The value in `bytes`, if they are all of a versioned encoding of a `kind`
+/// of `scheme` in this version.
+fn unversioned<T: CanonicalDeserialize>(
+    bytes: &[u8],
+    kind: Kind,
+    scheme: Scheme,
+) -> Result<T, SerializationError> {
+    let mut body = match bytes {
+        [ENCODING_VERSION, k, s, body @ ..] if *k == kind as u8 && *s == scheme as u8 => body,
+        _ => return Err(SerializationError::InvalidData),
+    };
+    let value = T::deserialize(&mut body)?;
+    if !body.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(value)
+}
+
+/// Implement `to_versioned_bytes` and `from_versioned_bytes` for `$ty`.
+macro_rules! impl_versioned {
+    ([$($generics:tt)*] $ty:ty, $kind:expr, $scheme:expr) => {
+        impl<$($generics)*> $ty {
+            /// The value in the versioned encoding.
+            pub fn to_versioned_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+                versioned($kind, $scheme, self)
+            }
+
+            /// Reads a value in the versioned encoding from all of `bytes`.
+            pub fn from_versioned_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+                unversioned(bytes, $kind, $scheme)
+            }
+        }
+    };
+}
+
+impl_versioned!([E: PairingEngine] kzg10::Proof<E>, Kind::Proof, Scheme::Kzg10);
+impl_versioned!([E: PairingEngine] kzg10::VerifyKey<E>, Kind::VerifierKey, Scheme::Kzg10);
+impl_versioned!(['a, E: PairingEngine] kzg10::ProveKey<'a, E>, Kind::ProveKey, Scheme::Kzg10);
+impl_versioned!([G: Curve] ipa::Proof<G>, Kind::Proof, Scheme::Ipa);
+impl_versioned!([G: Curve] ipa::ProveKey<G>, Kind::ProveKey, Scheme::Ipa);
@@ -0,0 +1,121 @@
+//! Rough estimates of the prover's resources, for placing jobs before proving.
+
+use ark_ff::FftField;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_std::log2;
+use core::{mem::size_of, time::Duration};
+
+use crate::r1cs::{Index, SynthesisError};
+use crate::Vec;
+
+/// Field multiplications per group addition.
+const ADD_MULS: u64 = 12;
+
+/// An affine point is counted as three field elements, which covers the
+/// larger base fields of pairing-friendly curves.
+const POINT_ELEMENTS: usize = 3;
+
+/// The predicted cost of a proof, from the domain size and the number of
+/// multi-scalar multiplications. The time is single-threaded and only right
+/// to within a small factor; the memory is an upper bound of the prover's
+/// working set, including the commitment key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Estimate {
+    /// The size of the evaluation domain, and of every MSM.
+    pub domain_size: usize,
+    /// The number of FFTs over the domain.
+    pub ffts: usize,
+    /// The number of MSMs.
+    pub msms: usize,
+    /// The peak memory of the prover, in bytes.
+    pub peak_memory: usize,
+    /// The single-threaded prove time.
+    pub prove_time: Duration,
+}
+
+/// The sizes of a synthesized circuit that the prover's cost depends on.
+pub(crate) struct Shape {
+    variables: usize,
+    aux: usize,
+    copies: usize,
+    constraints: usize,
+    nonzeros_ab: usize,
+    nonzeros_c: usize,
+    n: usize,
+}
+
+impl Shape {
+    pub(crate) fn new<F: FftField>(
+        at: &[Vec<(F, Index)>],
+        bt: &[Vec<(F, Index)>],
+        ct: &[Vec<(F, Index)>],
+        input_assignment: &[Vec<F>],
+        aux_assignment: &[Vec<F>],
+    ) -> Result<Self, SynthesisError> {
+        let copies = input_assignment.first().map_or(0, Vec::len);
+        let n = GeneralEvaluationDomain::<F>::new(copies)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?
+            .size();
+        let nonzeros = |m: &[Vec<(F, Index)>]| m.iter().map(Vec::len).sum::<usize>();
+
+        Ok(Shape {
+            variables: input_assignment.len() + aux_assignment.len(),
+            aux: aux_assignment.len(),
+            copies,
+            constraints: at.len(),
+            nonzeros_ab: nonzeros(at) + nonzeros(bt),
+            nonzeros_c: nonzeros(ct),
+            n,
+        })
+    }
+}
+
+impl Estimate {
+    /// The estimate under a scheme whose commitment to one polynomial takes
+    /// `commit_msms` MSMs and whose batched opening takes `open_msms`.
+    pub(crate) fn prover<F: FftField>(shape: &Shape, commit_msms: usize, open_msms: usize) -> Self {
+        let n = shape.n;
+        let field_bytes = F::zero().serialized_size();
+        let mut muls = 0;
+        let mut ffts = 0;
+        let mut fft = |count: usize| {
+            ffts += count;
+            count as u64 * (n / 2) as u64 * log2(n) as u64
+        };
+
+        // every variable is interpolated, a and b of every constraint are
+        // extended to the coset, and the sum of c is moved to the coset and
+        // the quotient back.
+        muls += fft(shape.variables + 2 * shape.constraints + 2);
+        // the linear combinations of a, b and c over coefficients, and the
+        // products and sums on the coset.
+        muls += ((shape.nonzeros_ab + shape.nonzeros_c) * n) as u64;
+        muls += (3 * shape.constraints * n) as u64;
+
+        let msms = commit_msms * (shape.aux + 1) + open_msms;
+        let c = log2(n) as usize * 7 / 10 + 2;
+        let windows = (field_bytes * 8).div_ceil(c);
+        muls += (msms * windows * (n + (1 << c))) as u64 * ADD_MULS;
+
+        // the assignments, their polynomials and the quotient, 6 vectors
+        // of the quotient computation, the commitment key and the combined
+        // opening polynomial.
+        let elements = shape.variables * shape.copies
+            + (shape.variables + 1) * n
+            + 6 * n
+            + POINT_ELEMENTS * (n + 1)
+            + n;
+        let constraints =
+            (shape.nonzeros_ab + shape.nonzeros_c) * (field_bytes + size_of::<Index>());
+
+        // a multiplication of `l` limbs takes about `l^2` nanoseconds.
+        let limbs = (field_bytes as u64).div_ceil(8);
+        Estimate {
+            domain_size: n,
+            ffts,
+            msms,
+            peak_memory: elements * field_bytes + constraints,
+            prove_time: Duration::from_nanos(muls * limbs * limbs),
+        }
+    }
+}
@@ -143,6 +143,15 @@ impl<G: Curve> Randomness<G> {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<G: Curve> Drop for Randomness<G> {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        self.rand.zeroize();
+        self.shifted_rand.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
 /// `Proof` is an evaluation proof that is output by `InnerProductArg::open`.
 #[derive(Derivative, CanonicalSerialize, CanonicalDeserialize)]
 #[derivative(
@@ -872,4 +881,5 @@ impl core::fmt::Display for Error {
     }
 }
 
-//impl ark_std::io::Error for Error {}
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -1,277 +1,140 @@
-use ark_ff::Field;
-use ark_serialize::*;
-use core::marker::PhantomData;
+use ark_poly::univariate::DensePolynomial;
 use digest::Digest;
+use rand::{Rng, RngCore};
 use zkp_curve::Curve;
 
 pub mod ipa;
-pub mod prover;
-pub mod verifier;
 
 pub use ipa::InnerProductArgPC;
-pub use prover::create_random_proof;
-pub use verifier::verify_proof;
 pub type ProveKey<G> = ipa::CommitterKey<G>;
 pub type VerifyKey<G> = ipa::VerifierKey<G>;
 
-use crate::{String, Vec};
-
-use crate::r1cs::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use crate::pc::{Committer, PolynomialCommitment};
+use crate::r1cs::SynthesisError;
+use crate::Vec;
 
 type IPAPC<G, D> = InnerProductArgPC<G, D>;
-type IPAProof<G> = ipa::Proof<G>;
-type IPAComm<G> = ipa::Commitment<G>;
 
 /// The proof in Clinkv2.
-#[derive(Clone, Debug, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct Proof<G: Curve> {
-    pub r_mid_comms: Vec<IPAComm<G>>,
-    pub q_comm: IPAComm<G>,
-    pub r_mid_q_values: Vec<G::Fr>,
-    pub r_mid_q_proof: IPAProof<G>,
-    pub opening_challenge: G::Fr,
+pub type Proof<G> = crate::Proof<<G as Curve>::Fr, ipa::Commitment<G>, ipa::Proof<G>>;
+pub type ProveAssignment<G, D> = crate::ProveAssignment<<G as Curve>::Fr, IPAPC<G, D>>;
+pub type VerifyAssignment<G, D> = crate::VerifyAssignment<<G as Curve>::Fr, IPAPC<G, D>>;
+
+/// `create_random_proof` on the threads of `pool` instead of the global
+/// pool, so that concurrent proofs can be given disjoint sets of cores.
+#[cfg(feature = "parallel")]
+pub fn create_random_proof_in<G, D, R>(
+    pool: &crate::ThreadPool,
+    circuit: &ProveAssignment<G, D>,
+    ipa_ck: &ProveKey<G>,
+    rng: &mut R,
+) -> Result<Proof<G>, SynthesisError>
+where
+    G: Curve,
+    D: Digest,
+    R: Rng + Send,
+{
+    crate::create_random_proof_in(pool, circuit, ipa_ck, rng)
 }
 
-fn push_constraints<F: Field>(
-    l: LinearCombination<F>,
-    constraints: &mut [Vec<(F, Index)>],
-    this_constraint: usize,
-) {
-    for (var, coeff) in l.as_ref() {
-        match var.get_unchecked() {
-            Index::Input(i) => constraints[this_constraint].push((*coeff, Index::Input(i))),
-            Index::Aux(i) => constraints[this_constraint].push((*coeff, Index::Aux(i))),
-        }
-    }
+pub fn create_random_proof<G, D, R>(
+    circuit: &ProveAssignment<G, D>,
+    ipa_ck: &ProveKey<G>,
+    rng: &mut R,
+) -> Result<Proof<G>, SynthesisError>
+where
+    G: Curve,
+    D: Digest,
+    R: Rng,
+{
+    crate::create_random_proof(circuit, ipa_ck, rng)
 }
 
-pub struct ProveAssignment<G: Curve, D: Digest> {
-    // Constraints
-    pub at: Vec<Vec<(G::Fr, Index)>>,
-    pub bt: Vec<Vec<(G::Fr, Index)>>,
-    pub ct: Vec<Vec<(G::Fr, Index)>>,
-
-    // Assignments of variables
-    // Two-demension vector
-    pub input_assignment: Vec<Vec<G::Fr>>,
-    pub aux_assignment: Vec<Vec<G::Fr>>,
-
-    pub(crate) io_cur: usize,
-    pub(crate) aux_cur: usize,
-    _digest: PhantomData<D>,
+pub fn create_random_proof_in_place<G, D, R>(
+    circuit: ProveAssignment<G, D>,
+    ipa_ck: &ProveKey<G>,
+    rng: &mut R,
+) -> Result<Proof<G>, SynthesisError>
+where
+    G: Curve,
+    D: Digest,
+    R: Rng,
+{
+    crate::create_random_proof_in_place(circuit, ipa_ck, rng)
 }
 
-impl<G: Curve, D: Digest> Default for ProveAssignment<G, D> {
-    fn default() -> ProveAssignment<G, D> {
-        ProveAssignment {
-            at: vec![],
-            bt: vec![],
-            ct: vec![],
-            input_assignment: vec![],
-            aux_assignment: vec![],
-            io_cur: 0usize,
-            aux_cur: 0usize,
-            _digest: PhantomData::<D>,
-        }
-    }
+pub fn verify_proof<G: Curve, D: Digest>(
+    circuit: &VerifyAssignment<G, D>,
+    ipa_vk: &VerifyKey<G>,
+    proof: &Proof<G>,
+    io: &Vec<Vec<G::Fr>>,
+) -> Result<bool, SynthesisError> {
+    crate::verify_proof(circuit, ipa_vk, proof, io)
 }
 
-impl<G: Curve, D: Digest> ConstraintSystem<G::Fr> for ProveAssignment<G, D> {
-    type Root = Self;
-
-    #[inline]
-    fn alloc<F, A, AR>(&mut self, _: A, f: F, i: usize) -> Result<Variable, SynthesisError>
-    where
-        F: FnOnce() -> Result<G::Fr, SynthesisError>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        if i == 0 {
-            if self.aux_assignment.len() == 0 {
-                self.aux_cur = 0;
-            }
-            let mut aux_varj_vec = vec![];
-            aux_varj_vec.push(f()?);
-            self.aux_assignment.push(aux_varj_vec);
-            let index = self.aux_cur;
-            self.aux_cur += 1;
-            Ok(Variable::new_unchecked(Index::Aux(index)))
-        } else {
-            if i == self.aux_assignment[0].len() {
-                self.aux_cur = 0;
-            }
-            self.aux_assignment[self.aux_cur].push(f()?);
-            let index = self.aux_cur;
-            self.aux_cur += 1;
-            Ok(Variable::new_unchecked(Index::Aux(index)))
-        }
-    }
-
-    #[inline]
-    fn alloc_input<F, A, AR>(&mut self, _: A, f: F, i: usize) -> Result<Variable, SynthesisError>
-    where
-        F: FnOnce() -> Result<G::Fr, SynthesisError>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        if i == 0 {
-            if self.input_assignment.len() == 0 {
-                self.io_cur = 0;
-            }
-            let mut io_varj_vec = vec![];
-            io_varj_vec.push(f()?);
-            self.input_assignment.push(io_varj_vec);
-            let index = self.io_cur;
-            self.io_cur += 1;
-            Ok(Variable::new_unchecked(Index::Input(index)))
-        } else {
-            if i == self.input_assignment[0].len() {
-                self.io_cur = 0;
-            }
-            self.input_assignment[self.io_cur].push(f()?);
-            let index = self.io_cur;
-            self.io_cur += 1;
-            Ok(Variable::new_unchecked(Index::Input(index)))
-        }
-    }
-
-    #[inline]
-    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
-    where
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-        LA: FnOnce(LinearCombination<G::Fr>) -> LinearCombination<G::Fr>,
-        LB: FnOnce(LinearCombination<G::Fr>) -> LinearCombination<G::Fr>,
-        LC: FnOnce(LinearCombination<G::Fr>) -> LinearCombination<G::Fr>,
-    {
-        let num_constraints = self.num_constraints();
-
-        self.at.push(Vec::new());
-        self.bt.push(Vec::new());
-        self.ct.push(Vec::new());
-
-        push_constraints(a(LinearCombination::zero()), &mut self.at, num_constraints);
-        push_constraints(b(LinearCombination::zero()), &mut self.bt, num_constraints);
-        push_constraints(c(LinearCombination::zero()), &mut self.ct, num_constraints);
-    }
-
-    fn push_namespace<NR, N>(&mut self, _: N)
-    where
-        NR: Into<String>,
-        N: FnOnce() -> NR,
-    {
-        // Do nothing; we don't care about namespaces in this context.
-    }
-
-    fn pop_namespace(&mut self) {
-        // Do nothing; we don't care about namespaces in this context.
-    }
-
-    fn get_root(&mut self) -> &mut Self::Root {
-        self
-    }
-
-    fn num_constraints(&self) -> usize {
-        self.at.len()
+/// Every commitment also commits to the shifted polynomial, and the
+/// opening's recursive halving adds up to two MSMs plus one for the hiding
+/// polynomial.
+impl<G: Curve, D: Digest> PolynomialCommitment<G::Fr> for IPAPC<G, D> {
+    type VerifierKey = VerifyKey<G>;
+    type Commitment = ipa::Commitment<G>;
+    type Randomness = ipa::Randomness<G>;
+    type Proof = ipa::Proof<G>;
+
+    const COMMIT_MSMS: usize = 2;
+    const OPEN_MSMS: usize = 3;
+
+    fn batch_check(
+        vk: &VerifyKey<G>,
+        commitments: &[Self::Commitment],
+        point: G::Fr,
+        values: &[G::Fr],
+        proof: &Self::Proof,
+        opening_challenge: G::Fr,
+        degree_bound: usize,
+    ) -> Result<bool, SynthesisError> {
+        Ok(Self::check(
+            vk,
+            commitments,
+            point,
+            values,
+            proof,
+            opening_challenge,
+            degree_bound,
+        )?)
     }
 }
 
-pub struct VerifyAssignment<G: Curve, D: Digest> {
-    // Constraints
-    pub at: Vec<Vec<(G::Fr, Index)>>,
-    pub bt: Vec<Vec<(G::Fr, Index)>>,
-    pub ct: Vec<Vec<(G::Fr, Index)>>,
-
-    // Assignments of variables
-    // Two-demension vector
-    pub input_assignment: Vec<Vec<G::Fr>>,
-    pub aux_assignment: Vec<Vec<G::Fr>>,
-
-    pub(crate) io_cur: usize,
-    pub(crate) aux_cur: usize,
-
-    _digest: PhantomData<D>,
-}
-
-impl<G: Curve, D: Digest> Default for VerifyAssignment<G, D> {
-    fn default() -> VerifyAssignment<G, D> {
-        VerifyAssignment {
-            at: vec![],
-            bt: vec![],
-            ct: vec![],
-            input_assignment: vec![],
-            aux_assignment: vec![],
-            io_cur: 0usize,
-            aux_cur: 0usize,
-            _digest: PhantomData::<D>,
-        }
-    }
-}
-
-impl<G: Curve, D: Digest> ConstraintSystem<G::Fr> for VerifyAssignment<G, D> {
-    type Root = Self;
-
-    #[inline]
-    fn alloc<F, A, AR>(&mut self, _: A, _f: F, _i: usize) -> Result<Variable, SynthesisError>
-    where
-        F: FnOnce() -> Result<G::Fr, SynthesisError>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        let index = self.aux_cur;
-        self.aux_cur += 1;
-        Ok(Variable::new_unchecked(Index::Aux(index)))
-    }
-
-    #[inline]
-    fn alloc_input<F, A, AR>(&mut self, _: A, _f: F, _i: usize) -> Result<Variable, SynthesisError>
-    where
-        F: FnOnce() -> Result<G::Fr, SynthesisError>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        let index = self.io_cur;
-        self.io_cur += 1;
-        Ok(Variable::new_unchecked(Index::Input(index)))
-    }
-
-    #[inline]
-    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
-    where
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-        LA: FnOnce(LinearCombination<G::Fr>) -> LinearCombination<G::Fr>,
-        LB: FnOnce(LinearCombination<G::Fr>) -> LinearCombination<G::Fr>,
-        LC: FnOnce(LinearCombination<G::Fr>) -> LinearCombination<G::Fr>,
-    {
-        let num_constraints = self.num_constraints();
-
-        self.at.push(Vec::new());
-        self.bt.push(Vec::new());
-        self.ct.push(Vec::new());
-
-        push_constraints(a(LinearCombination::zero()), &mut self.at, num_constraints);
-        push_constraints(b(LinearCombination::zero()), &mut self.bt, num_constraints);
-        push_constraints(c(LinearCombination::zero()), &mut self.ct, num_constraints);
-    }
-
-    fn push_namespace<NR, N>(&mut self, _: N)
-    where
-        NR: Into<String>,
-        N: FnOnce() -> NR,
-    {
-        // Do nothing; we don't care about namespaces in this context.
-    }
-
-    fn pop_namespace(&mut self) {
-        // Do nothing; we don't care about namespaces in this context.
-    }
-
-    fn get_root(&mut self) -> &mut Self::Root {
-        self
-    }
-
-    fn num_constraints(&self) -> usize {
-        self.at.len()
+impl<G: Curve, D: Digest> Committer<G::Fr, IPAPC<G, D>> for ProveKey<G> {
+    fn commit<R: RngCore>(
+        &self,
+        polynomials: &[DensePolynomial<G::Fr>],
+        hiding_bound: usize,
+        degree_bound: usize,
+        rng: &mut R,
+    ) -> Result<(Vec<ipa::Commitment<G>>, Vec<ipa::Randomness<G>>), SynthesisError> {
+        Ok(IPAPC::<G, D>::commit(self, polynomials, hiding_bound, degree_bound, Some(rng))?)
+    }
+
+    fn batch_open<R: RngCore>(
+        &self,
+        polynomials: &[DensePolynomial<G::Fr>],
+        commitments: &[ipa::Commitment<G>],
+        point: G::Fr,
+        opening_challenge: G::Fr,
+        rands: &[ipa::Randomness<G>],
+        degree_bound: usize,
+        rng: &mut R,
+    ) -> Result<ipa::Proof<G>, SynthesisError> {
+        Ok(IPAPC::<G, D>::open(
+            self,
+            polynomials,
+            commitments,
+            point,
+            opening_challenge,
+            rands,
+            degree_bound,
+            Some(rng),
+        )?)
     }
 }
@@ -0,0 +1,305 @@
+//! Compress clinkv2 proofs into a single PLONK proof.
+//!
+//! Verifying a clinkv2 proof is a batched KZG opening at `zeta`, plus the
+//! check `sum_i eta^i * (a_i * b_i - c_i) = q(zeta) * Z_H(zeta)` on the opened
+//! values, which costs time linear in the size of the circuit. The second
+//! part is proven for a whole batch of proofs in one PLONK circuit, while the
+//! pairings are deferred: every opening is reduced to a [`DeferredOpening`],
+//! and all of them are decided at once with a single product of pairings.
+//!
+//! The settlement verifier recomputes the public inputs with
+//! [`compress_public_inputs`], checks the PLONK proof and calls [`decide`].
+
+use ark_ec::PairingEngine;
+use ark_ff::{FftField, Field, One, Zero};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use rand::Rng;
+use zkp_plonk::{Composer, Variable};
+
+use crate::{
+    kzg10::{
+        kzg10::{Commitment, Proof as OpeningProof},
+        Proof, VerifyAssignment, VerifyKey, KZG10,
+    },
+    r1cs::{Index, SynthesisError},
+    verifier::challenges,
+    Vec,
+};
+
+/// A clinkv2 proof together with its inputs and outputs.
+pub type Statement<'a, E> = (&'a Proof<E>, &'a [Vec<<E as PairingEngine>::Fr>]);
+
+/// The KZG opening of a clinkv2 proof, with the pairing check left to
+/// [`decide`].
+#[derive(Clone, Copy, Debug)]
+pub struct DeferredOpening<E: PairingEngine> {
+    pub commitment: Commitment<E>,
+    pub point: E::Fr,
+    pub value: E::Fr,
+    pub proof: OpeningProof<E>,
+}
+
+impl<E: PairingEngine> DeferredOpening<E> {
+    pub fn new(vk: &VerifyKey<E>, proof: &Proof<E>) -> Result<Self, SynthesisError> {
+        let (_, zeta) = challenges(proof)?;
+        let comms = [&proof.r_mid_comms[..], &[proof.q_comm][..]].concat();
+        let (commitment, value) = KZG10::accumulate_commitments_and_values(
+            vk,
+            &comms,
+            &proof.r_mid_q_values,
+            proof.opening_challenge,
+        )?;
+
+        Ok(DeferredOpening {
+            commitment: Commitment(commitment.into()),
+            point: zeta,
+            value,
+            proof: proof.r_mid_q_proof,
+        })
+    }
+}
+
+/// Check all `openings` with one product of two pairings.
+pub fn decide<E: PairingEngine, R: Rng>(
+    vk: &VerifyKey<E>,
+    openings: &[DeferredOpening<E>],
+    rng: &mut R,
+) -> Result<bool, SynthesisError> {
+    let comms: Vec<_> = openings.iter().map(|o| o.commitment).collect();
+    let points: Vec<_> = openings.iter().map(|o| o.point).collect();
+    let values: Vec<_> = openings.iter().map(|o| o.value).collect();
+    let proofs: Vec<_> = openings.iter().map(|o| o.proof).collect();
+
+    Ok(KZG10::batch_check_to_mul_values(
+        vk, &comms, &points, &values, &proofs, rng,
+    )?)
+}
+
+/// `[eta, zeta, r_mid_q_values.., io..]` of one statement.
+fn statement_inputs<E: PairingEngine>(
+    (proof, io): Statement<'_, E>,
+) -> Result<Vec<E::Fr>, SynthesisError> {
+    let (eta, zeta) = challenges(proof)?;
+
+    let mut inputs = vec![eta, zeta];
+    inputs.extend_from_slice(&proof.r_mid_q_values);
+    for row in io {
+        inputs.extend_from_slice(row);
+    }
+
+    Ok(inputs)
+}
+
+/// The public inputs of [`compress_circuit`] for `statements`, where `size`
+/// is the number of gates of the circuit, fixed at keygen.
+pub fn compress_public_inputs<E: PairingEngine>(
+    statements: &[Statement<'_, E>],
+    size: usize,
+) -> Result<Vec<E::Fr>, SynthesisError> {
+    let mut inputs = Vec::with_capacity(size);
+    for statement in statements {
+        // public input gates hold `var - pi = 0`, with `-pi` as the input.
+        inputs.extend(statement_inputs(*statement)?.into_iter().map(|x| -x));
+    }
+    if inputs.len() > size {
+        return Err(SynthesisError::TooManyInputs {
+            inputs: inputs.len(),
+            size,
+        });
+    }
+    inputs.resize(size, E::Fr::zero());
+
+    Ok(inputs)
+}
+
+/// The PLONK circuit checking the R1CS relation of every statement. Its shape
+/// only depends on `circuit` and on the number and sizes of the statements,
+/// so the same keys serve all batches of that shape.
+pub fn compress_circuit<E: PairingEngine>(
+    circuit: &VerifyAssignment<E>,
+    statements: &[Statement<'_, E>],
+) -> Result<Composer<E::Fr>, SynthesisError> {
+    let mut cs = Composer::new();
+
+    // public inputs come first, in the order of `compress_public_inputs`.
+    let mut inputs = Vec::with_capacity(statements.len());
+    for statement in statements {
+        let vars: Vec<_> = statement_inputs(*statement)?
+            .into_iter()
+            .map(|x| {
+                let var = cs.alloc_and_assign(x);
+                cs.constrain_to_constant(var, E::Fr::zero(), x);
+                (var, x)
+            })
+            .collect();
+        inputs.push(vars);
+    }
+
+    let zero = constant(&mut cs, E::Fr::zero());
+    let one = constant(&mut cs, E::Fr::one());
+    for ((proof, io), vars) in statements.iter().zip(inputs) {
+        enforce_r1cs(
+            &mut cs,
+            circuit,
+            proof.r_mid_q_values.len(),
+            io,
+            &vars,
+            zero,
+            one,
+        )?;
+    }
+
+    Ok(cs)
+}
+
+type Wire<F> = (Variable, F);
+
+fn constant<F: FftField>(cs: &mut Composer<F>, value: F) -> Wire<F> {
+    let var = cs.alloc_and_assign(value);
+    cs.constrain_to_constant(var, value, F::zero());
+    (var, value)
+}
+
+/// `lc * l + rc * r`
+fn add<F: FftField>(cs: &mut Composer<F>, l: Wire<F>, lc: F, r: Wire<F>, rc: F) -> Wire<F> {
+    let value = lc * l.1 + rc * r.1;
+    let var = cs.alloc_and_assign(value);
+    cs.create_add_gate((l.0, lc), (r.0, rc), var, None, F::zero(), F::zero());
+    (var, value)
+}
+
+/// `coeff * l * r`
+fn mul<F: FftField>(cs: &mut Composer<F>, l: Wire<F>, r: Wire<F>, coeff: F) -> Wire<F> {
+    let value = coeff * l.1 * r.1;
+    let var = cs.alloc_and_assign(value);
+    cs.create_mul_gate(l.0, r.0, var, None, coeff, F::zero(), F::zero());
+    (var, value)
+}
+
+/// `l * r + sign * aux`
+fn mul_add<F: FftField>(
+    cs: &mut Composer<F>,
+    l: Wire<F>,
+    r: Wire<F>,
+    aux: Wire<F>,
+    sign: F,
+) -> Wire<F> {
+    let value = l.1 * r.1 + sign * aux.1;
+    let var = cs.alloc_and_assign(value);
+    cs.create_mul_gate(
+        l.0,
+        r.0,
+        var,
+        Some((aux.0, sign)),
+        F::one(),
+        F::zero(),
+        F::zero(),
+    );
+    (var, value)
+}
+
+fn inverse<F: FftField>(
+    cs: &mut Composer<F>,
+    x: Wire<F>,
+    one: Wire<F>,
+) -> Result<Wire<F>, SynthesisError> {
+    let value = x.1.inverse().ok_or(SynthesisError::DivisionByZero)?;
+    let var = cs.alloc_and_assign(value);
+    cs.create_mul_gate(x.0, var, one.0, None, F::one(), F::zero(), F::zero());
+    Ok((var, value))
+}
+
+fn pow<F: FftField>(cs: &mut Composer<F>, base: Wire<F>, exp: u64, one: Wire<F>) -> Wire<F> {
+    let mut result = one;
+    for i in (0..64 - exp.leading_zeros()).rev() {
+        result = mul(cs, result, result, F::one());
+        if exp >> i & 1 == 1 {
+            result = mul(cs, result, base, F::one());
+        }
+    }
+    result
+}
+
+fn linear_combination<F: FftField>(
+    cs: &mut Composer<F>,
+    terms: &[(F, Index)],
+    r_io: &[Wire<F>],
+    r_mid_q: &[Wire<F>],
+    zero: Wire<F>,
+) -> Result<Wire<F>, SynthesisError> {
+    let mut acc = zero;
+    for (coeff, index) in terms {
+        let term = match index {
+            Index::Input(j) => r_io.get(*j),
+            Index::Aux(j) => r_mid_q.get(*j),
+        }
+        .ok_or(SynthesisError::IncorrectIndex)?;
+        acc = add(cs, acc, F::one(), *term, *coeff);
+    }
+    Ok(acc)
+}
+
+/// The R1CS check of `verify_proof`, over the public input wires of one
+/// statement.
+fn enforce_r1cs<E: PairingEngine>(
+    cs: &mut Composer<E::Fr>,
+    circuit: &VerifyAssignment<E>,
+    m_mid_q: usize,
+    io: &[Vec<E::Fr>],
+    vars: &[Wire<E::Fr>],
+    zero: Wire<E::Fr>,
+    one: Wire<E::Fr>,
+) -> Result<(), SynthesisError> {
+    let (eta, zeta) = (vars[0], vars[1]);
+    let r_mid_q = &vars[2..2 + m_mid_q];
+    let q = r_mid_q[m_mid_q - 1];
+
+    let n = io[0].len();
+    let domain = GeneralEvaluationDomain::<E::Fr>::new(n)
+        .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+    let size = domain.size();
+
+    // Z_H(zeta) = zeta^size - 1
+    let zeta_n = pow(cs, zeta, size as u64, one);
+    let vanishing = add(cs, zeta_n, E::Fr::one(), one, -E::Fr::one());
+
+    // L_i(zeta) = Z_H(zeta) * w^i / (size * (zeta - w^i))
+    let size_inv = E::Fr::from(size as u64).inverse().unwrap();
+    let mut lagrange = Vec::with_capacity(n);
+    for i in 0..n {
+        let w_i = domain.element(i);
+        let denominator = add(cs, zeta, E::Fr::one(), one, -w_i);
+        let denominator_inv = inverse(cs, denominator, one)?;
+        lagrange.push(mul(cs, vanishing, denominator_inv, w_i * size_inv));
+    }
+
+    let mut offset = 2 + m_mid_q;
+    let mut r_io = Vec::with_capacity(io.len());
+    for row in io {
+        let mut acc = zero;
+        for (l, x) in lagrange.iter().zip(&vars[offset..offset + row.len()]) {
+            acc = mul_add(cs, *l, *x, acc, E::Fr::one());
+        }
+        offset += row.len();
+        r_io.push(acc);
+    }
+
+    let mut sum = zero;
+    let mut eta_i = one;
+    for ((at, bt), ct) in circuit.at.iter().zip(&circuit.bt).zip(&circuit.ct) {
+        let a = linear_combination(cs, at, &r_io, r_mid_q, zero)?;
+        let b = linear_combination(cs, bt, &r_io, r_mid_q, zero)?;
+        let c = linear_combination(cs, ct, &r_io, r_mid_q, zero)?;
+        let term = mul_add(cs, a, b, c, -E::Fr::one());
+        sum = mul_add(cs, eta_i, term, sum, E::Fr::one());
+        eta_i = mul(cs, eta_i, eta, E::Fr::one());
+    }
+
+    let rhs = mul(cs, q, vanishing, E::Fr::one());
+    if sum.1 != rhs.1 {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    cs.assert_equal(sum.0, rhs.0);
+
+    Ok(())
+}
@@ -350,6 +350,13 @@ impl<E: PairingEngine> Randomness<E> {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<E: PairingEngine> Drop for Randomness<E> {
+    fn drop(&mut self) {
+        crate::zeroize_polys(Some(&mut self.blinding_polynomial));
+    }
+}
+
 impl<'a, E: PairingEngine> Add<&'a Randomness<E>> for Randomness<E> {
     type Output = Self;
 
@@ -704,7 +711,7 @@ impl<E: PairingEngine> KZG10<E> {
         polynomials: &[DensePolynomial<E::Fr>],
         point: E::Fr,
         opening_challenge: E::Fr,
-        rands: &Vec<Randomness<E>>,
+        rands: &[Randomness<E>],
     ) -> Result<Proof<E>, Error> {
         let mut p = DensePolynomial::zero();
         let mut r = Randomness::empty();
@@ -759,7 +766,7 @@ impl<E: PairingEngine> KZG10<E> {
         Ok(lhs == rhs)
     }
 
-    fn accumulate_commitments_and_values<'a>(
+    pub(crate) fn accumulate_commitments_and_values<'a>(
         _vk: &VerifierKey<E>,
         commitments: &[Commitment<E>],
         values: &[E::Fr],
@@ -905,7 +912,7 @@ fn skip_leading_zeros_and_convert_to_bigints<F: PrimeField>(
     p: &DensePolynomial<F>,
 ) -> (usize, Vec<F::BigInt>) {
     let mut num_leading_zeros = 0;
-    while p.coeffs[num_leading_zeros].is_zero() && num_leading_zeros < p.coeffs.len() {
+    while num_leading_zeros < p.coeffs.len() && p.coeffs[num_leading_zeros].is_zero() {
         num_leading_zeros += 1;
     }
     let coeffs = convert_to_bigints(&p.coeffs[num_leading_zeros..]);
@@ -1079,4 +1086,5 @@ impl core::fmt::Display for Error {
     }
 }
 
-//impl ark_std::io::Error for Error {}
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -1,269 +1,116 @@
 use ark_ec::PairingEngine;
-use ark_ff::Field;
-use ark_serialize::*;
+use ark_poly::univariate::DensePolynomial;
+use rand::{Rng, RngCore};
 
 pub mod kzg10;
-pub mod prover;
-pub mod verifier;
+
+#[cfg(feature = "plonk")]
+pub mod compress;
 
 pub use kzg10::KZG10;
-pub use prover::create_random_proof;
-pub use verifier::verify_proof;
 pub type VerifyKey<E> = kzg10::VerifierKey<E>;
 pub type ProveKey<'a, E> = kzg10::Powers<'a, E>;
 
-use crate::{String, Vec};
-
-use super::r1cs::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
-
-type Kzg10Proof<E> = kzg10::Proof<E>;
-type Kzg10Comm<E> = kzg10::Commitment<E>;
+use crate::pc::{Committer, PolynomialCommitment};
+use crate::r1cs::SynthesisError;
+use crate::Vec;
 
 /// The proof in Clinkv2.
-#[derive(Clone, Debug, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct Proof<E: PairingEngine> {
-    pub r_mid_comms: Vec<Kzg10Comm<E>>,
-    pub q_comm: Kzg10Comm<E>,
-    pub r_mid_q_values: Vec<E::Fr>,
-    pub r_mid_q_proof: Kzg10Proof<E>,
-    pub opening_challenge: E::Fr,
+pub type Proof<E> = crate::Proof<<E as PairingEngine>::Fr, kzg10::Commitment<E>, kzg10::Proof<E>>;
+pub type ProveAssignment<E> = crate::ProveAssignment<<E as PairingEngine>::Fr, KZG10<E>>;
+pub type VerifyAssignment<E> = crate::VerifyAssignment<<E as PairingEngine>::Fr, KZG10<E>>;
+
+/// `create_random_proof` on the threads of `pool` instead of the global
+/// pool, so that concurrent proofs can be given disjoint sets of cores.
+#[cfg(feature = "parallel")]
+pub fn create_random_proof_in<E: PairingEngine, R: Rng + Send>(
+    pool: &crate::ThreadPool,
+    circuit: &ProveAssignment<E>,
+    kzg10_ck: &ProveKey<'_, E>,
+    rng: &mut R,
+) -> Result<Proof<E>, SynthesisError> {
+    crate::create_random_proof_in(pool, circuit, kzg10_ck, rng)
 }
 
-fn push_constraints<F: Field>(
-    l: LinearCombination<F>,
-    constraints: &mut [Vec<(F, Index)>],
-    this_constraint: usize,
-) {
-    for (var, coeff) in l.as_ref() {
-        match var.get_unchecked() {
-            Index::Input(i) => constraints[this_constraint].push((*coeff, Index::Input(i))),
-            Index::Aux(i) => constraints[this_constraint].push((*coeff, Index::Aux(i))),
-        }
-    }
+pub fn create_random_proof<E: PairingEngine, R: Rng>(
+    circuit: &ProveAssignment<E>,
+    kzg10_ck: &ProveKey<'_, E>,
+    rng: &mut R,
+) -> Result<Proof<E>, SynthesisError> {
+    crate::create_random_proof(circuit, kzg10_ck, rng)
 }
 
-pub struct ProveAssignment<E: PairingEngine> {
-    // Constraints
-    pub at: Vec<Vec<(E::Fr, Index)>>,
-    pub bt: Vec<Vec<(E::Fr, Index)>>,
-    pub ct: Vec<Vec<(E::Fr, Index)>>,
-
-    // Assignments of variables
-    // Two-demension vector
-    pub input_assignment: Vec<Vec<E::Fr>>,
-    pub aux_assignment: Vec<Vec<E::Fr>>,
-
-    pub(crate) io_cur: usize,
-    pub(crate) aux_cur: usize,
+pub fn create_random_proof_in_place<E: PairingEngine, R: Rng>(
+    circuit: ProveAssignment<E>,
+    kzg10_ck: &ProveKey<'_, E>,
+    rng: &mut R,
+) -> Result<Proof<E>, SynthesisError> {
+    crate::create_random_proof_in_place(circuit, kzg10_ck, rng)
 }
 
-impl<E: PairingEngine> Default for ProveAssignment<E> {
-    fn default() -> ProveAssignment<E> {
-        ProveAssignment {
-            at: vec![],
-            bt: vec![],
-            ct: vec![],
-            input_assignment: vec![],
-            aux_assignment: vec![],
-            io_cur: 0usize,
-            aux_cur: 0usize,
-        }
-    }
+pub fn verify_proof<E: PairingEngine>(
+    circuit: &VerifyAssignment<E>,
+    kzg10_vk: &VerifyKey<E>,
+    proof: &Proof<E>,
+    io: &Vec<Vec<E::Fr>>,
+) -> Result<bool, SynthesisError> {
+    crate::verify_proof(circuit, kzg10_vk, proof, io)
 }
 
-impl<E: PairingEngine> ConstraintSystem<E::Fr> for ProveAssignment<E> {
-    type Root = Self;
-
-    #[inline]
-    fn alloc<F, A, AR>(&mut self, _: A, f: F, i: usize) -> Result<Variable, SynthesisError>
-    where
-        F: FnOnce() -> Result<E::Fr, SynthesisError>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        if i == 0 {
-            if self.aux_assignment.len() == 0 {
-                self.aux_cur = 0;
-            }
-            let mut aux_varj_vec = vec![];
-            aux_varj_vec.push(f()?);
-            self.aux_assignment.push(aux_varj_vec);
-            let index = self.aux_cur;
-            self.aux_cur += 1;
-            Ok(Variable::new_unchecked(Index::Aux(index)))
-        } else {
-            if i == self.aux_assignment[0].len() {
-                self.aux_cur = 0;
-            }
-            self.aux_assignment[self.aux_cur].push(f()?);
-            let index = self.aux_cur;
-            self.aux_cur += 1;
-            Ok(Variable::new_unchecked(Index::Aux(index)))
-        }
-    }
-
-    #[inline]
-    fn alloc_input<F, A, AR>(&mut self, _: A, f: F, i: usize) -> Result<Variable, SynthesisError>
-    where
-        F: FnOnce() -> Result<E::Fr, SynthesisError>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        if i == 0 {
-            if self.input_assignment.len() == 0 {
-                self.io_cur = 0;
-            }
-            let mut io_varj_vec = vec![];
-            io_varj_vec.push(f()?);
-            self.input_assignment.push(io_varj_vec);
-            let index = self.io_cur;
-            self.io_cur += 1;
-            Ok(Variable::new_unchecked(Index::Input(index)))
-        } else {
-            if i == self.input_assignment[0].len() {
-                self.io_cur = 0;
-            }
-            self.input_assignment[self.io_cur].push(f()?);
-            let index = self.io_cur;
-            self.io_cur += 1;
-            Ok(Variable::new_unchecked(Index::Input(index)))
-        }
-    }
-
-    #[inline]
-    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
-    where
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-        LA: FnOnce(LinearCombination<E::Fr>) -> LinearCombination<E::Fr>,
-        LB: FnOnce(LinearCombination<E::Fr>) -> LinearCombination<E::Fr>,
-        LC: FnOnce(LinearCombination<E::Fr>) -> LinearCombination<E::Fr>,
-    {
-        let num_constraints = self.num_constraints();
-
-        self.at.push(Vec::new());
-        self.bt.push(Vec::new());
-        self.ct.push(Vec::new());
-
-        push_constraints(a(LinearCombination::zero()), &mut self.at, num_constraints);
-        push_constraints(b(LinearCombination::zero()), &mut self.bt, num_constraints);
-        push_constraints(c(LinearCombination::zero()), &mut self.ct, num_constraints);
-    }
-
-    fn push_namespace<NR, N>(&mut self, _: N)
-    where
-        NR: Into<String>,
-        N: FnOnce() -> NR,
-    {
-        // Do nothing; we don't care about namespaces in this context.
-    }
-
-    fn pop_namespace(&mut self) {
-        // Do nothing; we don't care about namespaces in this context.
-    }
-
-    fn get_root(&mut self) -> &mut Self::Root {
-        self
+/// Every witness polynomial and the quotient take one commitment, and the
+/// batched opening one more. Degree bounds are not enforced beyond the
+/// size of the powers.
+impl<E: PairingEngine> PolynomialCommitment<E::Fr> for KZG10<E> {
+    type VerifierKey = VerifyKey<E>;
+    type Commitment = kzg10::Commitment<E>;
+    type Randomness = kzg10::Randomness<E>;
+    type Proof = kzg10::Proof<E>;
+
+    const COMMIT_MSMS: usize = 1;
+    const OPEN_MSMS: usize = 1;
+
+    fn batch_check(
+        vk: &VerifyKey<E>,
+        commitments: &[Self::Commitment],
+        point: E::Fr,
+        values: &[E::Fr],
+        proof: &Self::Proof,
+        opening_challenge: E::Fr,
+        _degree_bound: usize,
+    ) -> Result<bool, SynthesisError> {
+        Ok(KZG10::batch_check(vk, commitments, point, values, proof, opening_challenge)?)
     }
-
-    fn num_constraints(&self) -> usize {
-        self.at.len()
-    }
-}
-
-pub struct VerifyAssignment<E: PairingEngine> {
-    // Constraints
-    pub at: Vec<Vec<(E::Fr, Index)>>,
-    pub bt: Vec<Vec<(E::Fr, Index)>>,
-    pub ct: Vec<Vec<(E::Fr, Index)>>,
-
-    // Assignments of variables
-    // Two-demension vector
-    pub input_assignment: Vec<Vec<E::Fr>>,
-    pub aux_assignment: Vec<Vec<E::Fr>>,
-
-    pub(crate) io_cur: usize,
-    pub(crate) aux_cur: usize,
 }
 
-impl<E: PairingEngine> Default for VerifyAssignment<E> {
-    fn default() -> VerifyAssignment<E> {
-        VerifyAssignment {
-            at: vec![],
-            bt: vec![],
-            ct: vec![],
-            input_assignment: vec![],
-            aux_assignment: vec![],
-            io_cur: 0usize,
-            aux_cur: 0usize,
+impl<E: PairingEngine> Committer<E::Fr, KZG10<E>> for ProveKey<'_, E> {
+    fn commit<R: RngCore>(
+        &self,
+        polynomials: &[DensePolynomial<E::Fr>],
+        hiding_bound: usize,
+        _degree_bound: usize,
+        rng: &mut R,
+    ) -> Result<(Vec<kzg10::Commitment<E>>, Vec<kzg10::Randomness<E>>), SynthesisError> {
+        let mut comms = Vec::with_capacity(polynomials.len());
+        let mut rands = Vec::with_capacity(polynomials.len());
+        for polynomial in polynomials {
+            let (comm, rand) =
+                KZG10::commit(self, polynomial, Some(hiding_bound), Some(&mut *rng))?;
+            comms.push(comm);
+            rands.push(rand);
         }
-    }
-}
-
-impl<E: PairingEngine> ConstraintSystem<E::Fr> for VerifyAssignment<E> {
-    type Root = Self;
-
-    #[inline]
-    fn alloc<F, A, AR>(&mut self, _: A, _f: F, _i: usize) -> Result<Variable, SynthesisError>
-    where
-        F: FnOnce() -> Result<E::Fr, SynthesisError>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        let index = self.aux_cur;
-        self.aux_cur += 1;
-        Ok(Variable::new_unchecked(Index::Aux(index)))
-    }
-
-    #[inline]
-    fn alloc_input<F, A, AR>(&mut self, _: A, _f: F, _i: usize) -> Result<Variable, SynthesisError>
-    where
-        F: FnOnce() -> Result<E::Fr, SynthesisError>,
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-    {
-        let index = self.io_cur;
-        self.io_cur += 1;
-        Ok(Variable::new_unchecked(Index::Input(index)))
-    }
-
-    #[inline]
-    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
-    where
-        A: FnOnce() -> AR,
-        AR: Into<String>,
-        LA: FnOnce(LinearCombination<E::Fr>) -> LinearCombination<E::Fr>,
-        LB: FnOnce(LinearCombination<E::Fr>) -> LinearCombination<E::Fr>,
-        LC: FnOnce(LinearCombination<E::Fr>) -> LinearCombination<E::Fr>,
-    {
-        let num_constraints = self.num_constraints();
-
-        self.at.push(Vec::new());
-        self.bt.push(Vec::new());
-        self.ct.push(Vec::new());
-
-        push_constraints(a(LinearCombination::zero()), &mut self.at, num_constraints);
-        push_constraints(b(LinearCombination::zero()), &mut self.bt, num_constraints);
-        push_constraints(c(LinearCombination::zero()), &mut self.ct, num_constraints);
-    }
-
-    fn push_namespace<NR, N>(&mut self, _: N)
-    where
-        NR: Into<String>,
-        N: FnOnce() -> NR,
-    {
-        // Do nothing; we don't care about namespaces in this context.
-    }
-
-    fn pop_namespace(&mut self) {
-        // Do nothing; we don't care about namespaces in this context.
-    }
-
-    fn get_root(&mut self) -> &mut Self::Root {
-        self
-    }
-
-    fn num_constraints(&self) -> usize {
-        self.at.len()
+        Ok((comms, rands))
+    }
+
+    fn batch_open<R: RngCore>(
+        &self,
+        polynomials: &[DensePolynomial<E::Fr>],
+        _commitments: &[kzg10::Commitment<E>],
+        point: E::Fr,
+        opening_challenge: E::Fr,
+        rands: &[kzg10::Randomness<E>],
+        _degree_bound: usize,
+        _rng: &mut R,
+    ) -> Result<kzg10::Proof<E>, SynthesisError> {
+        Ok(KZG10::batch_open(self, polynomials, point, opening_challenge, rands)?)
     }
 }
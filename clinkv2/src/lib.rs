@@ -1,4 +1,29 @@
 //! An implementation of the `CLINKv2`.
+//!
+//! The prover and the verifier are generic over a `pc::PolynomialCommitment`
+//! scheme; `kzg10` and `ipa` instantiate them with KZG10 and with inner
+//! product arguments.
+//!
+//! The provers record `tracing` spans for the witness, quotient and opening
+//! phases, with their sizes. `ProveAssignment::estimate` predicts the peak
+//! memory and prove time of a synthesized circuit before proving it.
+//!
+//! For large batches, `ProveAssignment::with_instances` and `push_instance`
+//! take the instances one by one from a stream into columns allocated once,
+//! and `create_random_proof_in_place` interpolates them there.
+//!
+//! With the `parallel` feature, `create_random_proof_in` proves on a given
+//! rayon `ThreadPool` instead of the global one, and
+//! `ProveAssignment::push_instances_par` synthesizes a batch of instances
+//! on all cores.
+//!
+//! Proofs and keys of both schemes have a versioned encoding,
+//! `to_versioned_bytes` and `from_versioned_bytes`, tagged with
+//! `ENCODING_VERSION`, what they hold and their scheme, to send them to a
+//! verifier in another process or store them on-chain.
+//!
+//! With the `plonk` feature, `r1cs::plonk::PlonkAssignment` compiles a
+//! circuit into a PLONK `Composer`, to prove it under a universal setup.
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(unused, future_incompatible, nonstandard_style, rust_2018_idioms)]
 #![allow(clippy::op_ref, clippy::suspicious_op_assign_impl)]
@@ -22,8 +47,45 @@ use std::{borrow::Cow, string::String, vec::Vec};
 /// Clinkv2 unique r1cs.
 pub mod r1cs;
 
+/// The polynomial commitment schemes of the proofs.
+pub mod pc;
+
+mod data_structures;
+pub use data_structures::{Proof, ProveAssignment, VerifyAssignment};
+
+mod prover;
+#[cfg(feature = "parallel")]
+pub use prover::create_random_proof_in;
+pub use prover::{create_random_proof, create_random_proof_in_place};
+
+mod verifier;
+pub use verifier::verify_proof;
+
 /// Clinkv2-kzg10 scheme.
 pub mod kzg10;
 
 /// Clinkv2-ipa scheme.
 pub mod ipa;
+
+mod estimate;
+pub use estimate::Estimate;
+
+mod encoding;
+pub use encoding::ENCODING_VERSION;
+
+#[cfg(feature = "parallel")]
+pub use rayon::{ThreadPool, ThreadPoolBuilder};
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+/// Overwrite the coefficients of polynomials derived from the witness.
+#[cfg(feature = "zeroize")]
+pub(crate) fn zeroize_polys<'a, F: ark_ff::Field>(
+    polys: impl IntoIterator<Item = &'a mut ark_poly::univariate::DensePolynomial<F>>,
+) {
+    use zeroize::Zeroize;
+    polys
+        .into_iter()
+        .for_each(|p| p.coeffs.iter_mut().for_each(Zeroize::zeroize));
+}
@@ -0,0 +1,71 @@
+//! The polynomial commitment schemes the prover and the verifier are generic
+//! over. A scheme commits to the witness polynomials and the quotient, and
+//! opens all of them at one point with a single batched proof.
+//!
+//! `kzg10` and `ipa` implement them, and alias the generic types and
+//! functions under their old names.
+
+use ark_ff::{FftField, ToBytes};
+use ark_poly::univariate::DensePolynomial;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use core::fmt::Debug;
+use rand::RngCore;
+
+use crate::r1cs::SynthesisError;
+use crate::Vec;
+
+/// A polynomial commitment scheme over `F`, as the verifier sees it.
+pub trait PolynomialCommitment<F: FftField>: Sized {
+    type VerifierKey;
+    type Commitment: Clone + Debug + Eq + ToBytes + CanonicalSerialize + CanonicalDeserialize;
+    type Randomness;
+    type Proof: Clone + Debug + Eq + CanonicalSerialize + CanonicalDeserialize;
+
+    /// The MSMs of a commitment to one polynomial, for
+    /// `ProveAssignment::estimate`.
+    const COMMIT_MSMS: usize;
+    /// The MSMs of a batched opening.
+    const OPEN_MSMS: usize;
+
+    /// Checks that `values` are the evaluations at `point` of the
+    /// polynomials of `commitments`, of degree at most `degree_bound`, by
+    /// `proof` of their combination under `opening_challenge`.
+    fn batch_check(
+        vk: &Self::VerifierKey,
+        commitments: &[Self::Commitment],
+        point: F,
+        values: &[F],
+        proof: &Self::Proof,
+        opening_challenge: F,
+        degree_bound: usize,
+    ) -> Result<bool, SynthesisError>;
+}
+
+/// A committer key of `PC`. It is a trait of its own so that the key may
+/// borrow its powers, as `kzg10::ProveKey` does.
+pub trait Committer<F: FftField, PC: PolynomialCommitment<F>> {
+    /// Commits to each of `polynomials`, hiding it with a polynomial of
+    /// degree `hiding_bound`.
+    #[allow(clippy::type_complexity)]
+    fn commit<R: RngCore>(
+        &self,
+        polynomials: &[DensePolynomial<F>],
+        hiding_bound: usize,
+        degree_bound: usize,
+        rng: &mut R,
+    ) -> Result<(Vec<PC::Commitment>, Vec<PC::Randomness>), SynthesisError>;
+
+    /// Opens `polynomials` at `point`, combined under `opening_challenge`,
+    /// see `PolynomialCommitment::batch_check`.
+    #[allow(clippy::too_many_arguments)]
+    fn batch_open<R: RngCore>(
+        &self,
+        polynomials: &[DensePolynomial<F>],
+        commitments: &[PC::Commitment],
+        point: F,
+        opening_challenge: F,
+        rands: &[PC::Randomness],
+        degree_bound: usize,
+        rng: &mut R,
+    ) -> Result<PC::Proof, SynthesisError>;
+}
@@ -1,8 +1,8 @@
-use ark_ec::PairingEngine;
-use ark_ff::{Field, One, ToBytes, UniformRand, Zero};
+use ark_ff::{FftField, ToBytes};
 use ark_poly::polynomial::univariate::DensePolynomial;
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain, Polynomial, UVPolynomial};
 use ark_std::{cfg_iter, cfg_iter_mut};
+use core::mem;
 use merlin::Transcript;
 use rand::Rng;
 
@@ -13,52 +13,127 @@ use rand::Rng;
 use rayon::prelude::*;
 
 use crate::{
-    kzg10::{Proof, ProveAssignment, ProveKey, KZG10},
+    pc::{Committer, PolynomialCommitment},
     r1cs::{Index, SynthesisError},
+    Proof, ProveAssignment, Vec,
 };
 
-pub fn create_random_proof<E: PairingEngine, R: Rng>(
-    circuit: &ProveAssignment<E>,
-    kzg10_ck: &ProveKey<'_, E>,
+/// `create_random_proof` on the threads of `pool` instead of the global
+/// pool, so that concurrent proofs can be given disjoint sets of cores.
+#[cfg(feature = "parallel")]
+pub fn create_random_proof_in<F, PC, K, R>(
+    pool: &crate::ThreadPool,
+    circuit: &ProveAssignment<F, PC>,
+    ck: &K,
+    rng: &mut R,
+) -> Result<Proof<F, PC::Commitment, PC::Proof>, SynthesisError>
+where
+    F: FftField,
+    PC: PolynomialCommitment<F>,
+    PC::Commitment: Send,
+    PC::Proof: Send,
+    K: Committer<F, PC> + Sync,
+    R: Rng + Send,
+{
+    pool.install(|| create_random_proof(circuit, ck, rng))
+}
+
+/// Proves `circuit` under the committer key `ck` of `PC`.
+pub fn create_random_proof<F, PC, K, R>(
+    circuit: &ProveAssignment<F, PC>,
+    ck: &K,
+    rng: &mut R,
+) -> Result<Proof<F, PC::Commitment, PC::Proof>, SynthesisError>
+where
+    F: FftField,
+    PC: PolynomialCommitment<F>,
+    K: Committer<F, PC>,
+    R: Rng,
+{
+    let m_io = circuit.input_assignment.len();
+    let columns = circuit
+        .input_assignment
+        .iter()
+        .chain(&circuit.aux_assignment)
+        .cloned()
+        .collect();
+    prove(circuit, m_io, columns, ck, rng)
+}
+
+/// `create_random_proof` taking `circuit` by value, whose columns are
+/// interpolated where they are instead of copied, so that the prover holds
+/// every column once. With `ProveAssignment::with_instances`, they are not
+/// reallocated either.
+pub fn create_random_proof_in_place<F, PC, K, R>(
+    mut circuit: ProveAssignment<F, PC>,
+    ck: &K,
     rng: &mut R,
-) -> Result<Proof<E>, SynthesisError> {
-    // Number of io variables (statements)
+) -> Result<Proof<F, PC::Commitment, PC::Proof>, SynthesisError>
+where
+    F: FftField,
+    PC: PolynomialCommitment<F>,
+    K: Committer<F, PC>,
+    R: Rng,
+{
     let m_io = circuit.input_assignment.len();
+    let mut columns = mem::take(&mut circuit.input_assignment);
+    columns.append(&mut circuit.aux_assignment);
+    prove(&circuit, m_io, columns, ck, rng)
+}
+
+/// Proves the constraints of `circuit` on `columns`, the values of its `m_io`
+/// io variables and then of its aux variables.
+fn prove<F, PC, K, R>(
+    circuit: &ProveAssignment<F, PC>,
+    m_io: usize,
+    columns: Vec<Vec<F>>,
+    ck: &K,
+    rng: &mut R,
+) -> Result<Proof<F, PC::Commitment, PC::Proof>, SynthesisError>
+where
+    F: FftField,
+    PC: PolynomialCommitment<F>,
+    K: Committer<F, PC>,
+    R: Rng,
+{
     // Number of aux variables (witnesses)
-    let m_mid = circuit.aux_assignment.len();
+    let m_mid = columns.len() - m_io;
     // Number of all variables
     // let m = m_io + m_mid;
     // Number of copies
-    let n = circuit.input_assignment[0].len();
+    let n = columns[0].len();
+    let span = tracing::info_span!("prove", m_io, m_mid, n);
+    let _enter = span.enter();
 
     // println!("m_io: {:?}, m_mid: {:?}, n: {:?}", m_io, m_mid, n);
 
     let mut transcript = Transcript::new(b"CLINKv2");
 
     // Compute and commit witness polynomials
-    let domain: GeneralEvaluationDomain<E::Fr> =
-        EvaluationDomain::<E::Fr>::new(n).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+    let domain: GeneralEvaluationDomain<F> =
+        EvaluationDomain::<F>::new(n).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
 
     let domain_size = domain.size();
     // println!("domain_size: {:?}", domain_size);
 
     let mut r_q_polys = vec![];
-    let mut r_mid_comms = vec![];
     let mut r_mid_q_values = vec![];
-    let mut r_mid_q_rands = vec![];
 
-    let zero = E::Fr::zero();
-    let one = E::Fr::one();
-    let hiding_bound = Some(2);
+    let zero = F::zero();
+    let one = F::one();
+    let hiding_bound = 2;
+    let degree_bound = domain_size - 1;
 
     //let mut rj_commit_time = Duration::new(0, 0);
     //let mut rj_ifft_time = Duration::new(0, 0);
 
-    for j in 0..m_io {
+    let witness = tracing::debug_span!("witness", polynomials = m_io + m_mid, domain_size);
+    let guard = witness.enter();
+    let mut columns = columns.into_iter();
+    for mut rj_coeffs in columns.by_ref().take(m_io) {
         //let start = Instant::now();
 
-        // println!("circuit.input_assignment: {:?}", &circuit.input_assignment);
-        let rj_coeffs = domain.ifft(&circuit.input_assignment[j]);
+        domain.ifft_in_place(&mut rj_coeffs);
         // println!("rj_coeffs: {:?}", rj_coeffs);
 
         //rj_ifft_time += start.elapsed();
@@ -67,11 +142,11 @@ pub fn create_random_proof<E: PairingEngine, R: Rng>(
         r_q_polys.push(rj_poly);
     }
 
-    for j in 0..m_mid {
+    for mut rj_coeffs in columns {
         // IFFT
         //let start = Instant::now();
 
-        let rj_coeffs = domain.ifft(&circuit.aux_assignment[j]);
+        domain.ifft_in_place(&mut rj_coeffs);
 
         //rj_ifft_time += start.elapsed();
 
@@ -83,25 +158,29 @@ pub fn create_random_proof<E: PairingEngine, R: Rng>(
         let vanishing_poly = domain.vanishing_polynomial();
         rj_poly += &(&rho_poly * &vanishing_poly.into());
 
-        //let start2 = Instant::now();
-        let (rj_comm, rj_rand) = KZG10::<E>::commit(&kzg10_ck, &rj_poly, hiding_bound, Some(rng))?;
-        //rj_commit_time += start2.elapsed();
         r_q_polys.push(rj_poly);
-        r_mid_comms.push(rj_comm);
-        r_mid_q_rands.push(rj_rand);
     }
     //println!("rj_ifft_time: {:?}", rj_ifft_time);
+
+    //let start2 = Instant::now();
+    let (r_mid_comms, mut r_mid_q_rands) =
+        ck.commit(&r_q_polys[m_io..], hiding_bound, degree_bound, rng)?;
+    //rj_commit_time += start2.elapsed();
     //println!("rj_commit_time: {:?}", rj_commit_time);
+    drop(guard);
+
     let mut r_mid_comms_bytes = vec![];
     r_mid_comms.write(&mut r_mid_comms_bytes)?;
     transcript.append_message(b"witness polynomial commitments", &r_mid_comms_bytes);
 
     let mut c = [0u8; 31];
     transcript.challenge_bytes(b"batching challenge", &mut c);
-    let eta = E::Fr::from_random_bytes(&c).unwrap();
+    let eta = F::from_random_bytes(&c).unwrap();
 
     // Compute and commit quotient polynomials
     let m_abc = circuit.at.len();
+    let quotient = tracing::debug_span!("quotient", constraints = m_abc, domain_size);
+    let guard = quotient.enter();
     let mut sum_coset_ab = vec![zero; domain_size];
     let mut sum_c = vec![zero; domain_size];
 
@@ -145,6 +224,8 @@ pub fn create_random_proof<E: PairingEngine, R: Rng>(
         // on coset: n values of a*b on coset
         let coset_ab_values = domain.mul_polynomials_in_evaluation_domain(&ai, &bi);
 
+        #[cfg(feature = "zeroize")]
+        crate::zeroize_polys(vec![&mut ai, &mut bi]);
         drop(ai);
         drop(bi);
 
@@ -153,30 +234,22 @@ pub fn create_random_proof<E: PairingEngine, R: Rng>(
             .zip(&mut sum_coset_ab)
             .for_each(|(coset_abij, sum_coset_ab_j)| *sum_coset_ab_j += &(eta_i * (*coset_abij)));
 
-        let mut ci_values = vec![zero; domain_size];
+        // coefficients of \sum{eta^i * c}, from the polynomials as the
+        // values may have been interpolated in place
         for (coeff, index) in (&circuit.ct[i]).into_iter() {
-            match index {
-                Index::Input(j) => {
-                    cfg_iter_mut!(&mut ci_values)
-                        .zip(&circuit.input_assignment[*j])
-                        .for_each(|(cij, rij)| *cij += &(*rij * coeff));
-                }
-                Index::Aux(j) => {
-                    cfg_iter_mut!(&mut ci_values)
-                        .zip(&circuit.aux_assignment[*j])
-                        .for_each(|(cij, rij)| *cij += &(*rij * coeff));
-                }
+            let id = match index {
+                Index::Input(j) => *j,
+                Index::Aux(j) => m_io + *j,
             };
+            let eta_i_coeff = eta_i * coeff;
+            cfg_iter_mut!(sum_c)
+                .zip(&r_q_polys[id].coeffs)
+                .for_each(|(sum_c_j, rij)| *sum_c_j += &(eta_i_coeff * rij));
         }
-        // on original domain: n values of \sum{eta^i * c} on original domain
-        cfg_iter!(ci_values)
-            .zip(&mut sum_c)
-            .for_each(|(cij, sum_c_j)| *sum_c_j += &(eta_i * (*cij)));
 
         eta_i = eta_i * &eta;
     }
 
-    domain.ifft_in_place(&mut sum_c);
     // on coset: n values of \sum{eta^i * c} on coset
     domain.coset_fft_in_place(&mut sum_c);
 
@@ -191,28 +264,29 @@ pub fn create_random_proof<E: PairingEngine, R: Rng>(
     //abci_fft_time += start.elapsed();
     //println!("abci_fft_time: {:?}", abci_fft_time);
 
-    let q_poly = DensePolynomial::from_coefficients_vec(sum_coset_ab);
+    r_q_polys.push(DensePolynomial::from_coefficients_vec(sum_coset_ab));
 
     // Commit to quotient polynomial
     //let start2 = Instant::now();
 
-    let (q_comm, q_rand) = KZG10::<E>::commit(&kzg10_ck, &q_poly, hiding_bound, Some(rng))?;
+    let (q_comms, q_rands) =
+        ck.commit(&r_q_polys[m_io + m_mid..], hiding_bound, degree_bound, rng)?;
+    r_mid_q_rands.extend(q_rands);
 
     //q_commit_time += start2.elapsed();
     //println!("q_commit_time: {:?}", q_commit_time);
 
+    drop(guard);
+
     let mut q_comm_bytes = vec![];
-    q_comm.write(&mut q_comm_bytes)?;
+    q_comms[0].write(&mut q_comm_bytes)?;
     transcript.append_message(b"quotient polynomial commitments", &q_comm_bytes);
 
     // Prove
     // Generate a challenge
     let mut c = [0u8; 31];
     transcript.challenge_bytes(b"random point", &mut c);
-    let zeta = E::Fr::from_random_bytes(&c).unwrap();
-
-    r_q_polys.push(q_poly);
-    r_mid_q_rands.push(q_rand);
+    let zeta = F::from_random_bytes(&c).unwrap();
 
     //let mut open_r_mid_q_time = Duration::new(0, 0);
     //let start = Instant::now();
@@ -222,25 +296,34 @@ pub fn create_random_proof<E: PairingEngine, R: Rng>(
         r_mid_q_values.push(value);
     }
 
-    let opening_challenge = E::Fr::rand(rng);
-    let r_mid_q_proof = KZG10::<E>::batch_open(
-        &kzg10_ck,
+    let open = tracing::debug_span!("open", polynomials = m_mid + 1);
+    let guard = open.enter();
+    let opening_challenge = F::rand(rng);
+    let r_mid_q_comms = [&r_mid_comms[..], &q_comms[..]].concat();
+    let r_mid_q_proof = ck.batch_open(
         &r_q_polys[m_io..],
+        &r_mid_q_comms,
         zeta,
         opening_challenge,
         &r_mid_q_rands,
+        degree_bound,
+        rng,
     )?;
+    drop(guard);
 
     //open_r_mid_q_time += start.elapsed();
     //println!("open_r_mid_q_time: {:?}", open_r_mid_q_time);
 
     let proof = Proof {
         r_mid_comms,
-        q_comm,
+        q_comm: q_comms[0].clone(),
         r_mid_q_values,
         r_mid_q_proof,
         opening_challenge,
     };
 
+    #[cfg(feature = "zeroize")]
+    crate::zeroize_polys(&mut r_q_polys[m_io..]);
+
     Ok(proof)
 }
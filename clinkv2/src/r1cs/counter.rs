@@ -0,0 +1,110 @@
+use ark_ff::FftField;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use core::marker::PhantomData;
+
+use super::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use crate::String;
+
+/// A constraint system that only counts. It never calls the assignment
+/// functions, so a circuit can be sized, e.g. with `srs_degree`, before its
+/// witnesses exist.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConstraintCounter<F: FftField> {
+    /// The io variables of one copy.
+    pub num_inputs: usize,
+    /// The aux variables of one copy.
+    pub num_aux: usize,
+    /// The number of copies the circuit was synthesized for.
+    pub num_copies: usize,
+    num_constraints: usize,
+    // the copy being allocated and the next index in it, per kind.
+    io_cur: (usize, usize),
+    aux_cur: (usize, usize),
+    _field: PhantomData<F>,
+}
+
+impl<F: FftField> ConstraintCounter<F> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The degree of the commitment key that proving needs, i.e. the size of
+    /// the domain of the copies.
+    pub fn srs_degree(&self) -> Result<usize, SynthesisError> {
+        GeneralEvaluationDomain::<F>::new(self.num_copies)
+            .map(|d| d.size())
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)
+    }
+
+    /// The next index of copy `i`, counting the variables of copy 0.
+    fn next(cur: &mut (usize, usize), count: &mut usize, i: usize) -> usize {
+        if cur.0 != i {
+            *cur = (i, 0);
+        }
+        if i == 0 {
+            *count += 1;
+        }
+        cur.1 += 1;
+        cur.1 - 1
+    }
+}
+
+impl<F: FftField> ConstraintSystem<F> for ConstraintCounter<F> {
+    type Root = Self;
+
+    #[inline]
+    fn alloc<FN, A, AR>(&mut self, _: A, _f: FN, i: usize) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_copies = self.num_copies.max(i + 1);
+        let index = Self::next(&mut self.aux_cur, &mut self.num_aux, i);
+        Ok(Variable::new_unchecked(Index::Aux(index)))
+    }
+
+    #[inline]
+    fn alloc_input<FN, A, AR>(&mut self, _: A, _f: FN, i: usize) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.num_copies = self.num_copies.max(i + 1);
+        let index = Self::next(&mut self.io_cur, &mut self.num_inputs, i);
+        Ok(Variable::new_unchecked(Index::Input(index)))
+    }
+
+    #[inline]
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, _a: LA, _b: LB, _c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        self.num_constraints += 1;
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+}
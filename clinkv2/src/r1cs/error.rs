@@ -26,6 +26,8 @@ pub enum SynthesisError {
     UnconstrainedVariable,
     /// Incorrect Index during Variable allocation.
     IncorrectIndex,
+    /// There are `inputs` public inputs for a circuit with room for `size`.
+    TooManyInputs { inputs: usize, size: usize },
     /// Error when committing polynomials.
     KZG10PolyComError(KZG10Error),
     /// Error when committing polynomials.
@@ -53,7 +55,12 @@ impl From<IPAError> for SynthesisError {
 #[cfg(feature = "std")]
 impl std::error::Error for SynthesisError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        None
+        match self {
+            SynthesisError::IoError(err) => Some(err),
+            SynthesisError::KZG10PolyComError(err) => Some(err),
+            SynthesisError::IPAPolyComError(err) => Some(err),
+            _ => None,
+        }
     }
 }
 
@@ -77,13 +84,11 @@ impl fmt::Display for SynthesisError {
             SynthesisError::IncorrectIndex => {
                 write!(f, "Incorrect Index during Variable allocation")
             }
-            /*SynthesisError::PolyCommitError => {
-                write!(f, "Error when committing polynomials")
-            }*/
-            SynthesisError::KZG10PolyComError(err) => {
-                write!(f, "KZG10 PolyCommit error: {:?}", err)
+            SynthesisError::TooManyInputs { inputs, size } => {
+                write!(f, "{} public inputs exceed the {} available", inputs, size)
             }
-            SynthesisError::IPAPolyComError(err) => write!(f, "IPA PolyCommit error: {:?}", err),
+            SynthesisError::KZG10PolyComError(err) => write!(f, "KZG10 PolyCommit error: {}", err),
+            SynthesisError::IPAPolyComError(err) => write!(f, "IPA PolyCommit error: {}", err),
         }
     }
 }
@@ -3,13 +3,18 @@ use core::cmp::Ordering;
 use smallvec::SmallVec as StackVec;
 
 mod constraint_system;
+mod counter;
 mod error;
 mod impl_constraint_var;
 mod impl_lc;
 
 pub use constraint_system::{ConstraintSynthesizer, ConstraintSystem, Namespace};
+pub use counter::ConstraintCounter;
 pub use error::SynthesisError;
 
+#[cfg(feature = "plonk")]
+pub mod plonk;
+
 type SmallVec<F> = StackVec<[(Variable, F); 16]>;
 
 /// Represents a variable in a constraint system.
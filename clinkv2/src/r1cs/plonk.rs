@@ -0,0 +1,223 @@
+//! Compile a clinkv2 circuit into a PLONK `Composer`, so that it is proven
+//! under a universal setup without being rewritten.
+//!
+//! `PlonkAssignment` is a constraint system like `ProveAssignment`: the
+//! circuit is synthesized once per copy, and the constraints enforced on
+//! copy 0 hold for every copy. `into_composer` then lays out, copy by copy,
+//! a gate per input, first, and for each constraint `A * B = C` the
+//! linear-combination gates of `A`, `B` and `C` and one mul gate. The input
+//! `CS::one()` of each copy is the constant one, and the other inputs are
+//! public, in the order of `public_inputs`.
+
+use ark_ff::FftField;
+use zkp_plonk::{Composer, Variable as PlonkVariable};
+
+use super::{ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use crate::{String, Vec};
+
+/// A constraint system that compiles into a PLONK composer.
+pub struct PlonkAssignment<F: FftField> {
+    cs: Composer<F>,
+    /// Whether the assignments are not called, see `new_counting`.
+    counting: bool,
+    /// The variables of the inputs and their values, per copy.
+    inputs: Vec<Vec<(PlonkVariable, F)>>,
+    /// The variables of the aux, per copy.
+    aux: Vec<Vec<PlonkVariable>>,
+    /// The constraints `A * B = C` enforced on copy 0.
+    constraints: Vec<[Vec<(F, Index)>; 3]>,
+}
+
+impl<F: FftField> Default for PlonkAssignment<F> {
+    fn default() -> Self {
+        PlonkAssignment {
+            cs: Composer::new(),
+            counting: false,
+            inputs: Vec::new(),
+            aux: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+}
+
+impl<F: FftField> PlonkAssignment<F> {
+    /// A constraint system that never calls the assignments, and compiles
+    /// into a counting composer, e.g. to generate the PLONK keys from the
+    /// circuit without its witnesses, see `Composer::new_counting`.
+    pub fn new_counting() -> Self {
+        PlonkAssignment {
+            cs: Composer::new_counting(),
+            counting: true,
+            ..Self::default()
+        }
+    }
+
+    /// The composer of the circuit, with the gates of all its copies.
+    pub fn into_composer(mut self) -> Result<Composer<F>, SynthesisError> {
+        let one = F::one();
+        for inputs in &self.inputs {
+            for (k, (var, value)) in inputs.iter().enumerate() {
+                if k == 0 {
+                    self.cs.constrain_to_constant(*var, one, F::zero());
+                } else {
+                    self.cs.constrain_to_constant(*var, F::zero(), *value);
+                }
+            }
+        }
+
+        let constraints = core::mem::take(&mut self.constraints);
+        for copy in 0..self.inputs.len().max(self.aux.len()) {
+            for [a, b, c] in &constraints {
+                let a = self.combine(copy, a)?;
+                let b = self.combine(copy, b)?;
+                let c = self.combine(copy, c)?;
+                self.cs
+                    .create_mul_gate(a, b, c, None, one, F::zero(), F::zero());
+            }
+        }
+
+        Ok(self.cs)
+    }
+
+    /// The variable of `lc` in `copy`, with its gates if it is not a single
+    /// variable. The terms of `CS::one()` are folded into the constant.
+    fn combine(&mut self, copy: usize, lc: &[(F, Index)]) -> Result<PlonkVariable, SynthesisError> {
+        let mut constant = F::zero();
+        let mut terms = Vec::with_capacity(lc.len());
+        for (coeff, index) in lc {
+            let var = match index {
+                Index::Input(0) => {
+                    constant += coeff;
+                    continue;
+                }
+                Index::Input(k) => self
+                    .inputs
+                    .get(copy)
+                    .and_then(|inputs| inputs.get(*k))
+                    .map(|(var, _)| *var),
+                Index::Aux(k) => self.aux.get(copy).and_then(|aux| aux.get(*k)).copied(),
+            };
+            terms.push((var.ok_or(SynthesisError::IncorrectIndex)?, *coeff));
+        }
+
+        match terms[..] {
+            [(var, coeff)] if coeff.is_one() && constant.is_zero() => Ok(var),
+            _ => Ok(self.cs.create_linear_combination_gate(&terms, constant)),
+        }
+    }
+
+    fn value<FN>(&self, f: FN) -> Result<F, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+    {
+        if self.counting {
+            Ok(F::zero())
+        } else {
+            f()
+        }
+    }
+}
+
+/// The public inputs of the composer of `PlonkAssignment` for `io`, as
+/// given to `verify_proof`: the inputs of each copy, with zero for
+/// `CS::one()`, then zero for the other gates up to `size`, the number of
+/// gates of the composer or `info.n` of its verifier key, as
+/// `Composer::public_inputs`.
+pub fn public_inputs<F: FftField>(io: &[Vec<F>], size: usize) -> Vec<F> {
+    let copies = io.first().map_or(0, Vec::len);
+    let mut public_inputs: Vec<F> = (0..copies)
+        .flat_map(|copy| {
+            io.iter().enumerate().map(move |(k, values)| {
+                // input gates hold `var - pi = 0`, with `-pi` as the input.
+                if k == 0 {
+                    F::zero()
+                } else {
+                    -values[copy]
+                }
+            })
+        })
+        .collect();
+    public_inputs.resize(size.max(public_inputs.len()), F::zero());
+    public_inputs
+}
+
+fn lc_terms<F: FftField>(lc: LinearCombination<F>) -> Vec<(F, Index)> {
+    lc.as_ref()
+        .iter()
+        .map(|(var, coeff)| (*coeff, var.get_unchecked()))
+        .collect()
+}
+
+impl<F: FftField> ConstraintSystem<F> for PlonkAssignment<F> {
+    type Root = Self;
+
+    #[inline]
+    fn alloc<FN, A, AR>(&mut self, _: A, f: FN, i: usize) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = self.value(f)?;
+        if self.aux.len() <= i {
+            self.aux.resize(i + 1, Vec::new());
+        }
+        let var = self.cs.alloc_and_assign(value);
+        self.aux[i].push(var);
+        Ok(Variable::new_unchecked(Index::Aux(self.aux[i].len() - 1)))
+    }
+
+    #[inline]
+    fn alloc_input<FN, A, AR>(&mut self, _: A, f: FN, i: usize) -> Result<Variable, SynthesisError>
+    where
+        FN: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = self.value(f)?;
+        if self.inputs.len() <= i {
+            self.inputs.resize(i + 1, Vec::new());
+        }
+        let var = self.cs.alloc_and_assign(value);
+        self.inputs[i].push((var, value));
+        Ok(Variable::new_unchecked(Index::Input(
+            self.inputs[i].len() - 1,
+        )))
+    }
+
+    #[inline]
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        self.constraints.push([
+            lc_terms(a(LinearCombination::zero())),
+            lc_terms(b(LinearCombination::zero())),
+            lc_terms(c(LinearCombination::zero())),
+        ]);
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+}
@@ -0,0 +1,123 @@
+//! Serde support through the canonical encoding: a hex string in
+//! human-readable formats such as JSON, and raw bytes otherwise.
+
+use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{fmt, string::String, vec::Vec};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use zkp_curve::Curve;
+
+use crate::{ipa, kzg10};
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 * bytes.len());
+    for byte in bytes {
+        hex.push(HEX[(byte >> 4) as usize] as char);
+        hex.push(HEX[(byte & 0xf) as usize] as char);
+    }
+    hex
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+struct BytesVisitor;
+
+impl<'de> de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "canonically serialized bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+fn serialize<T: CanonicalSerialize, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut bytes = Vec::with_capacity(value.serialized_size());
+    value
+        .serialize(&mut bytes)
+        .map_err(<S::Error as ser::Error>::custom)?;
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&to_hex(&bytes))
+    } else {
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+fn deserialize<'de, T: CanonicalDeserialize, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    let bytes = if deserializer.is_human_readable() {
+        let hex = <String as Deserialize>::deserialize(deserializer)?;
+        from_hex(&hex).ok_or_else(|| <D::Error as de::Error>::custom("invalid hex string"))?
+    } else {
+        deserializer.deserialize_bytes(BytesVisitor)?
+    };
+    T::deserialize(&bytes[..]).map_err(<D::Error as de::Error>::custom)
+}
+
+/// Implement serde for `$ty` by its canonical encoding.
+macro_rules! impl_serde {
+    ([$($generics:tt)*] $ty:ty) => {
+        impl<$($generics)*> Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serialize(self, serializer)
+            }
+        }
+
+        impl<'de, $($generics)*> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserialize(deserializer)
+            }
+        }
+    };
+}
+
+impl_serde!([E: PairingEngine] kzg10::Proof<E>);
+impl_serde!([E: PairingEngine] kzg10::VerifyKey<E>);
+impl_serde!(['a, E: PairingEngine] kzg10::ProveKey<'a, E>);
+impl_serde!([G: Curve] ipa::Proof<G>);
+// also the verifier key of the ipa scheme.
+impl_serde!([G: Curve] ipa::ProveKey<G>);
+
+#[cfg(test)]
+mod tests {
+    use super::{from_hex, to_hex};
+
+    #[test]
+    fn hex() {
+        let bytes = [0x00, 0x1f, 0xa0, 0xff];
+        assert_eq!(to_hex(&bytes), "001fa0ff");
+        assert_eq!(from_hex("001fA0ff").unwrap(), bytes);
+        assert!(from_hex("001").is_none());
+        assert!(from_hex("0g").is_none());
+    }
+}
@@ -1,27 +1,23 @@
-use ark_ec::PairingEngine;
-use ark_ff::{Field, One, ToBytes, Zero};
+use ark_ff::{FftField, Field, ToBytes};
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain, Polynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use merlin::Transcript;
 
 use crate::{
-    kzg10::{Proof, VerifyAssignment, VerifyKey, KZG10},
+    pc::PolynomialCommitment,
     r1cs::{Index, SynthesisError},
-    Vec,
+    Proof, Vec, VerifyAssignment,
 };
 
-pub fn verify_proof<E: PairingEngine>(
-    circuit: &VerifyAssignment<E>,
-    kzg10_vk: &VerifyKey<E>,
-    proof: &Proof<E>,
-    io: &Vec<Vec<E::Fr>>,
-) -> Result<bool, SynthesisError> {
+/// Derive the batching challenge `eta` and the evaluation point `zeta` of
+/// `proof` from the transcript.
+pub(crate) fn challenges<F, C, P>(proof: &Proof<F, C, P>) -> Result<(F, F), SynthesisError>
+where
+    F: Field,
+    C: ToBytes + CanonicalSerialize + CanonicalDeserialize,
+    P: CanonicalSerialize + CanonicalDeserialize,
+{
     let mut transcript = Transcript::new(b"CLINKv2");
-    let zero = E::Fr::zero();
-    let one = E::Fr::one();
-    let m_abc = circuit.at.len();
-    let m_io = io.len();
-    let m_mid = proof.r_mid_comms.len();
-    let n = io[0].len();
 
     let mut r_mid_comms_bytes = vec![];
     proof.r_mid_comms.write(&mut r_mid_comms_bytes)?;
@@ -29,7 +25,7 @@ pub fn verify_proof<E: PairingEngine>(
 
     let mut c = [0u8; 31];
     transcript.challenge_bytes(b"batching challenge", &mut c);
-    let eta = E::Fr::from_random_bytes(&c).unwrap();
+    let eta = F::from_random_bytes(&c).unwrap();
 
     let mut q_comm_bytes = vec![];
     proof.q_comm.write(&mut q_comm_bytes)?;
@@ -37,23 +33,45 @@ pub fn verify_proof<E: PairingEngine>(
 
     c = [0u8; 31];
     transcript.challenge_bytes(b"random point", &mut c);
-    let zeta = E::Fr::from_random_bytes(&c).unwrap();
+    let zeta = F::from_random_bytes(&c).unwrap();
 
-    let r_mid_q_comms = [&proof.r_mid_comms, &[proof.q_comm][..]].concat();
+    Ok((eta, zeta))
+}
 
-    assert!(KZG10::<E>::batch_check(
-        &kzg10_vk,
+/// Verifies `proof` of `circuit` with the inputs and outputs `io` under the
+/// verifier key `vk` of `PC`.
+pub fn verify_proof<F: FftField, PC: PolynomialCommitment<F>>(
+    circuit: &VerifyAssignment<F, PC>,
+    vk: &PC::VerifierKey,
+    proof: &Proof<F, PC::Commitment, PC::Proof>,
+    io: &Vec<Vec<F>>,
+) -> Result<bool, SynthesisError> {
+    let zero = F::zero();
+    let one = F::one();
+    let m_abc = circuit.at.len();
+    let m_io = io.len();
+    let m_mid = proof.r_mid_comms.len();
+    let n = io[0].len();
+
+    let (eta, zeta) = challenges(proof)?;
+
+    let r_mid_q_comms = [&proof.r_mid_comms[..], &[proof.q_comm.clone()][..]].concat();
+
+    let domain: GeneralEvaluationDomain<F> =
+        EvaluationDomain::<F>::new(n).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+    let degree_bound = domain.size() - 1;
+
+    if !PC::batch_check(
+        vk,
         &r_mid_q_comms,
         zeta,
         &proof.r_mid_q_values,
         &proof.r_mid_q_proof,
-        proof.opening_challenge
-    )?);
-
-    let domain: GeneralEvaluationDomain<E::Fr> =
-        EvaluationDomain::<E::Fr>::new(n).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-
-    //let domain_size = domain.size();
+        proof.opening_challenge,
+        degree_bound,
+    )? {
+        return Ok(false);
+    }
 
     let mut r_io_values = vec![];
     let lag_values = domain.evaluate_all_lagrange_coefficients(zeta);
@@ -99,7 +117,5 @@ pub fn verify_proof<E: PairingEngine>(
         ab_c += &(eta_i * &(ai * &bi - &ci));
         eta_i = eta_i * &eta;
     }
-    assert_eq!(ab_c, proof.r_mid_q_values[m_mid] * &vanishing_value);
-
-    Ok(true)
+    Ok(ab_c == proof.r_mid_q_values[m_mid] * &vanishing_value)
 }
@@ -0,0 +1,127 @@
+#![cfg(feature = "plonk")]
+
+use ark_bls12_381::{Bls12_381 as E, Fr};
+use ark_ff::{One, PrimeField};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_std::test_rng;
+use blake2::Blake2s;
+use zkp_clinkv2::kzg10::{
+    compress::{compress_circuit, compress_public_inputs, decide, DeferredOpening},
+    create_random_proof, verify_proof, ProveAssignment, VerifyAssignment, KZG10,
+};
+use zkp_clinkv2::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+use zkp_plonk::Plonk;
+
+type PlonkInst = Plonk<Fr, Blake2s, MarlinKZG10<E, DensePolynomial<Fr>>>;
+
+/// x * (y + 2) = z
+struct Mini<F: PrimeField> {
+    x: Option<F>,
+    y: Option<F>,
+    z: Option<F>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for Mini<F> {
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+        index: usize,
+    ) -> Result<(), SynthesisError> {
+        cs.alloc_input(|| "", || Ok(F::one()), index)?;
+        let var_x = cs.alloc(
+            || "x",
+            || self.x.ok_or(SynthesisError::AssignmentMissing),
+            index,
+        )?;
+        let var_y = cs.alloc(
+            || "y",
+            || self.y.ok_or(SynthesisError::AssignmentMissing),
+            index,
+        )?;
+        let var_z = cs.alloc_input(
+            || "z",
+            || self.z.ok_or(SynthesisError::AssignmentMissing),
+            index,
+        )?;
+
+        if index == 0 {
+            cs.enforce(
+                || "x * (y + 2) = z",
+                |lc| lc + var_x,
+                |lc| lc + var_y + (F::from(2u32), CS::one()),
+                |lc| lc + var_z,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn compress_clinkv2_kzg10() {
+    let rng = &mut test_rng();
+
+    let n = 4;
+    let kzg10_pp = KZG10::<E>::setup(n, false, rng).unwrap();
+    let (kzg10_ck, kzg10_vk) = KZG10::<E>::trim(&kzg10_pp, n).unwrap();
+
+    let mut proofs = vec![];
+    let mut ios = vec![];
+    for k in 0..2u32 {
+        let mut prover_pa = ProveAssignment::<E>::default();
+        let mut output = vec![];
+        for i in 0..n {
+            let (x, y) = (Fr::from(k + 2), Fr::from(i as u32));
+            let z = x * (y + Fr::from(2u32));
+            let c = Mini {
+                x: Some(x),
+                y: Some(y),
+                z: Some(z),
+            };
+            c.generate_constraints(&mut prover_pa, i).unwrap();
+            output.push(z);
+        }
+        proofs.push(create_random_proof(&prover_pa, &kzg10_ck, rng).unwrap());
+        ios.push(vec![vec![Fr::one(); n], output]);
+    }
+
+    let mut verifier_pa = VerifyAssignment::<E>::default();
+    let c = Mini::<Fr> {
+        x: None,
+        y: None,
+        z: None,
+    };
+    c.generate_constraints(&mut verifier_pa, 0).unwrap();
+    for (proof, io) in proofs.iter().zip(&ios) {
+        assert!(verify_proof(&verifier_pa, &kzg10_vk, proof, io).unwrap());
+    }
+
+    let statements: Vec<_> = proofs.iter().zip(&ios).map(|(p, io)| (p, &io[..])).collect();
+
+    // prover: one PLONK proof for the R1CS checks of the whole batch.
+    let cs = compress_circuit(&verifier_pa, &statements).unwrap();
+    let ks = [
+        Fr::one(),
+        Fr::from(7_u64),
+        Fr::from(13_u64),
+        Fr::from(17_u64),
+    ];
+    let srs = PlonkInst::setup(cs.srs_degree().unwrap(), rng).unwrap();
+    let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks).unwrap();
+    let proof = PlonkInst::prove(&pk, &cs, rng).unwrap();
+
+    // settlement: the PLONK proof plus one pairing product for all openings.
+    let public_inputs = compress_public_inputs(&statements, cs.size()).unwrap();
+    assert!(PlonkInst::verify(&vk, &public_inputs, proof).unwrap());
+
+    let openings: Vec<_> = proofs
+        .iter()
+        .map(|p| DeferredOpening::new(&kzg10_vk, p).unwrap())
+        .collect();
+    assert!(decide(&kzg10_vk, &openings, rng).unwrap());
+
+    let mut tampered = openings.clone();
+    tampered[1].value += Fr::one();
+    assert!(!decide(&kzg10_vk, &tampered, rng).unwrap());
+}
@@ -3,7 +3,9 @@ use ark_ff::{One, PrimeField};
 use ark_serialize::*;
 use ark_std::test_rng;
 use std::time::Instant;
-use zkp_clinkv2::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+use zkp_clinkv2::r1cs::{
+    ConstraintCounter, ConstraintSynthesizer, ConstraintSystem, SynthesisError,
+};
 
 pub struct Clinkv2Mini<F: PrimeField> {
     pub x: Option<F>,
@@ -96,6 +98,10 @@ fn mini_clinkv2_kzg10() {
     io.push(one);
     io.push(output);
 
+    let estimate = prover_pa.estimate().unwrap();
+    assert_eq!(estimate.domain_size, degree);
+    assert_eq!(estimate.msms, prover_pa.aux_assignment.len() + 2);
+
     let proof = create_random_proof(&prover_pa, &kzg10_ck, rng).unwrap();
     let p_time = p_start.elapsed();
     println!("[Clinkv2 Kzg10] Prove time       : {:?}", p_time);
@@ -121,6 +127,23 @@ fn mini_clinkv2_kzg10() {
     let vk2 = VerifyKey::<E>::deserialize(&vk_bytes[..]).unwrap();
     let proof2 = Proof::<E>::deserialize(&proof_bytes[..]).unwrap();
     assert!(verify_proof::<E>(&verifier_pa, &vk2, &proof2, &io).unwrap());
+
+    #[cfg(feature = "serde")]
+    {
+        let vk3: VerifyKey<E> =
+            serde_json::from_str(&serde_json::to_string(&kzg10_vk).unwrap()).unwrap();
+        let proof3: Proof<E> =
+            serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+        assert!(verify_proof::<E>(&verifier_pa, &vk3, &proof3, &io).unwrap());
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+        use zkp_clinkv2::{kzg10::create_random_proof_in, ThreadPoolBuilder};
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+        let proof4 = create_random_proof_in(&pool, &prover_pa, &kzg10_ck, rng).unwrap();
+        assert!(verify_proof::<E>(&verifier_pa, &kzg10_vk, &proof4, &io).unwrap());
+    }
 }
 
 #[test]
@@ -169,6 +192,10 @@ fn mini_clinkv2_ipa() {
     io.push(one);
     io.push(output);
 
+    let estimate = prover_pa.estimate().unwrap();
+    assert_eq!(estimate.domain_size, degree);
+    assert_eq!(estimate.msms, 2 * (prover_pa.aux_assignment.len() + 1) + 3);
+
     let proof = create_random_proof(&prover_pa, &ipa_ck, rng).unwrap();
     let p_time = p_start.elapsed();
     println!("[Clinkv2 Ipa] Prove time         : {:?}", p_time);
@@ -195,3 +222,245 @@ fn mini_clinkv2_ipa() {
     let proof2 = Proof::<E>::deserialize(&proof_bytes[..]).unwrap();
     assert!(verify_proof::<E, Blake2s>(&verifier_pa, &vk2, &proof2, &io).unwrap());
 }
+
+#[test]
+fn mini_clinkv2_count() {
+    let n: usize = 100;
+    let mut counter = ConstraintCounter::<Fr>::new();
+    for i in 0..n {
+        let c = Clinkv2Mini::<Fr> {
+            x: None,
+            y: None,
+            z: None,
+            num: 10,
+        };
+        c.generate_constraints(&mut counter, i).unwrap();
+    }
+
+    assert_eq!(counter.num_inputs, 2);
+    assert_eq!(counter.num_aux, 2);
+    assert_eq!(counter.num_copies, n);
+    assert_eq!(counter.num_constraints(), 1);
+    assert_eq!(counter.srs_degree().unwrap(), n.next_power_of_two());
+}
+
+/// Proves and verifies the mini circuit through the generic pipeline.
+fn mini_clinkv2_generic<PC, K>(ck: &K, vk: &PC::VerifierKey) -> bool
+where
+    PC: zkp_clinkv2::pc::PolynomialCommitment<Fr>,
+    K: zkp_clinkv2::pc::Committer<Fr, PC>,
+{
+    use zkp_clinkv2::{create_random_proof, verify_proof, ProveAssignment, VerifyAssignment};
+
+    let rng = &mut test_rng();
+    let n: usize = 16;
+
+    let mut prover_pa = ProveAssignment::<Fr, PC>::default();
+    let mut output: Vec<Fr> = vec![];
+    for i in 0..n {
+        let c = Clinkv2Mini::<Fr> {
+            x: Some(Fr::from(2u32)),
+            y: Some(Fr::from(3u32)),
+            z: Some(Fr::from(10u32)),
+            num: 10,
+        };
+        output.push(Fr::from(10u32));
+        c.generate_constraints(&mut prover_pa, i).unwrap();
+    }
+    let io = vec![vec![Fr::one(); n], output];
+    let proof = create_random_proof(&prover_pa, ck, rng).unwrap();
+
+    let c = Clinkv2Mini::<Fr> {
+        x: None,
+        y: None,
+        z: None,
+        num: 10,
+    };
+    let mut verifier_pa = VerifyAssignment::<Fr, PC>::default();
+    c.generate_constraints(&mut verifier_pa, 0usize).unwrap();
+    verify_proof(&verifier_pa, vk, &proof, &io).unwrap()
+}
+
+#[test]
+fn mini_clinkv2_generic_backends() {
+    use blake2::Blake2s;
+    use zkp_clinkv2::{ipa::InnerProductArgPC, kzg10::KZG10};
+
+    let rng = &mut test_rng();
+    let degree = 16;
+
+    let kzg10_pp = KZG10::<E>::setup(degree, false, rng).unwrap();
+    let (kzg10_ck, kzg10_vk) = KZG10::<E>::trim(&kzg10_pp, degree).unwrap();
+    assert!(mini_clinkv2_generic::<KZG10<E>, _>(&kzg10_ck, &kzg10_vk));
+
+    let ipa_pp = InnerProductArgPC::<E, Blake2s>::setup(degree, rng).unwrap();
+    let (ipa_ck, ipa_vk) = InnerProductArgPC::<E, Blake2s>::trim(&ipa_pp, degree).unwrap();
+    assert!(mini_clinkv2_generic::<InnerProductArgPC<E, Blake2s>, _>(&ipa_ck, &ipa_vk));
+}
+
+#[test]
+fn mini_clinkv2_streaming() {
+    use zkp_clinkv2::kzg10::{
+        create_random_proof, create_random_proof_in_place, verify_proof, ProveAssignment,
+        VerifyAssignment, KZG10,
+    };
+
+    let rng = &mut test_rng();
+    let n: usize = 100;
+    let degree: usize = n.next_power_of_two();
+    let kzg10_pp = KZG10::<E>::setup(degree, false, rng).unwrap();
+    let (kzg10_ck, kzg10_vk) = KZG10::<E>::trim(&kzg10_pp, degree).unwrap();
+
+    let instance = || Clinkv2Mini::<Fr> {
+        x: Some(Fr::from(2u32)),
+        y: Some(Fr::from(3u32)),
+        z: Some(Fr::from(10u32)),
+        num: 10,
+    };
+
+    let mut prover_pa = ProveAssignment::<E>::default();
+    let mut streamed_pa = ProveAssignment::<E>::with_instances(n).unwrap();
+    for i in 0..n {
+        instance().generate_constraints(&mut prover_pa, i).unwrap();
+        streamed_pa.push_instance(instance()).unwrap();
+    }
+    assert_eq!(streamed_pa.num_instances(), n);
+    assert_eq!(streamed_pa.aux_assignment, prover_pa.aux_assignment);
+    assert!(streamed_pa.aux_assignment[0].capacity() >= degree);
+
+    let proof = create_random_proof(&prover_pa, &kzg10_ck, &mut test_rng()).unwrap();
+    let streamed = create_random_proof_in_place(streamed_pa, &kzg10_ck, &mut test_rng()).unwrap();
+    assert_eq!(proof, streamed);
+
+    let c = Clinkv2Mini::<Fr> {
+        x: None,
+        y: None,
+        z: None,
+        num: 10,
+    };
+    let mut verifier_pa = VerifyAssignment::<E>::default();
+    c.generate_constraints(&mut verifier_pa, 0usize).unwrap();
+    let io = vec![vec![Fr::one(); n], vec![Fr::from(10u32); n]];
+    assert!(verify_proof(&verifier_pa, &kzg10_vk, &streamed, &io).unwrap());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn mini_clinkv2_parallel_synthesis() {
+    use zkp_clinkv2::kzg10::{
+        create_random_proof, verify_proof, ProveAssignment, VerifyAssignment, KZG10,
+    };
+
+    let rng = &mut test_rng();
+    let n: usize = 100;
+    let degree: usize = n.next_power_of_two();
+    let kzg10_pp = KZG10::<E>::setup(degree, false, rng).unwrap();
+    let (kzg10_ck, kzg10_vk) = KZG10::<E>::trim(&kzg10_pp, degree).unwrap();
+
+    let instance = |i: usize| Clinkv2Mini::<Fr> {
+        x: Some(Fr::from(2u32)),
+        y: Some(Fr::from(i as u32)),
+        z: Some(Fr::from(2 * (i as u32 + 2))),
+        num: 10,
+    };
+
+    let mut prover_pa = ProveAssignment::<E>::default();
+    for i in 0..n {
+        prover_pa.push_instance(instance(i)).unwrap();
+    }
+    let mut parallel_pa = ProveAssignment::<E>::default();
+    parallel_pa.push_instances_par((0..n / 2).map(instance).collect()).unwrap();
+    parallel_pa.push_instances_par((n / 2..n).map(instance).collect()).unwrap();
+    assert_eq!(parallel_pa.num_instances(), n);
+    assert_eq!(parallel_pa.input_assignment, prover_pa.input_assignment);
+    assert_eq!(parallel_pa.aux_assignment, prover_pa.aux_assignment);
+    assert_eq!(parallel_pa.at, prover_pa.at);
+    assert_eq!(parallel_pa.bt, prover_pa.bt);
+    assert_eq!(parallel_pa.ct, prover_pa.ct);
+
+    let proof = create_random_proof(&parallel_pa, &kzg10_ck, rng).unwrap();
+
+    let c = Clinkv2Mini::<Fr> {
+        x: None,
+        y: None,
+        z: None,
+        num: 10,
+    };
+    let mut verifier_pa = VerifyAssignment::<E>::default();
+    c.generate_constraints(&mut verifier_pa, 0usize).unwrap();
+    let io = vec![
+        vec![Fr::one(); n],
+        (0..n).map(|i| Fr::from(2 * (i as u32 + 2))).collect(),
+    ];
+    assert!(verify_proof(&verifier_pa, &kzg10_vk, &proof, &io).unwrap());
+}
+
+#[test]
+fn mini_clinkv2_versioned() {
+    use blake2::Blake2s;
+    use zkp_clinkv2::{ipa, kzg10, ENCODING_VERSION};
+
+    let rng = &mut test_rng();
+    let n: usize = 100;
+    let degree: usize = n.next_power_of_two();
+    let instance = || Clinkv2Mini::<Fr> {
+        x: Some(Fr::from(2u32)),
+        y: Some(Fr::from(3u32)),
+        z: Some(Fr::from(10u32)),
+        num: 10,
+    };
+
+    let kzg10_pp = kzg10::KZG10::<E>::setup(degree, false, rng).unwrap();
+    let (kzg10_ck, kzg10_vk) = kzg10::KZG10::<E>::trim(&kzg10_pp, degree).unwrap();
+    let mut prover_pa = kzg10::ProveAssignment::<E>::default();
+    for _ in 0..n {
+        prover_pa.push_instance(instance()).unwrap();
+    }
+    let proof = kzg10::create_random_proof(&prover_pa, &kzg10_ck, rng).unwrap();
+
+    let proof_bytes = proof.to_versioned_bytes().unwrap();
+    assert_eq!(proof_bytes[..3], [ENCODING_VERSION, 2, 1]);
+    assert_eq!(kzg10::Proof::<E>::from_versioned_bytes(&proof_bytes).unwrap(), proof);
+    let vk_bytes = kzg10_vk.to_versioned_bytes().unwrap();
+    assert_eq!(kzg10::VerifyKey::<E>::from_versioned_bytes(&vk_bytes).unwrap(), kzg10_vk);
+    let ck_bytes = kzg10_ck.to_versioned_bytes().unwrap();
+    let ck = kzg10::ProveKey::<E>::from_versioned_bytes(&ck_bytes).unwrap();
+    assert_eq!(ck.to_versioned_bytes().unwrap(), ck_bytes);
+
+    // another version, kind or scheme, or trailing bytes, are rejected.
+    let mut bytes = proof_bytes.clone();
+    bytes[0] = ENCODING_VERSION + 1;
+    assert!(kzg10::Proof::<E>::from_versioned_bytes(&bytes).is_err());
+    assert!(kzg10::Proof::<E>::from_versioned_bytes(&vk_bytes).is_err());
+    assert!(ipa::Proof::<E>::from_versioned_bytes(&proof_bytes).is_err());
+    let mut bytes = proof_bytes.clone();
+    bytes.push(0);
+    assert!(kzg10::Proof::<E>::from_versioned_bytes(&bytes).is_err());
+
+    let ipa_pp = ipa::InnerProductArgPC::<E, Blake2s>::setup(degree, rng).unwrap();
+    let (ipa_ck, ipa_vk) = ipa::InnerProductArgPC::<E, Blake2s>::trim(&ipa_pp, degree).unwrap();
+    let mut prover_pa = ipa::ProveAssignment::<E, Blake2s>::default();
+    for _ in 0..n {
+        prover_pa.push_instance(instance()).unwrap();
+    }
+    let proof = ipa::create_random_proof(&prover_pa, &ipa_ck, rng).unwrap();
+
+    let proof_bytes = proof.to_versioned_bytes().unwrap();
+    assert_eq!(proof_bytes[..3], [ENCODING_VERSION, 2, 2]);
+    let proof2 = ipa::Proof::<E>::from_versioned_bytes(&proof_bytes).unwrap();
+    let vk_bytes = ipa_vk.to_versioned_bytes().unwrap();
+    let vk2 = ipa::VerifyKey::<E>::from_versioned_bytes(&vk_bytes).unwrap();
+    assert_eq!(vk2.to_versioned_bytes().unwrap(), vk_bytes);
+    assert!(kzg10::Proof::<E>::from_versioned_bytes(&proof_bytes).is_err());
+
+    let c = Clinkv2Mini::<Fr> {
+        x: None,
+        y: None,
+        z: None,
+        num: 10,
+    };
+    let mut verifier_pa = ipa::VerifyAssignment::<E, Blake2s>::default();
+    c.generate_constraints(&mut verifier_pa, 0usize).unwrap();
+    let io = vec![vec![Fr::one(); n], vec![Fr::from(10u32); n]];
+    assert!(ipa::verify_proof(&verifier_pa, &vk2, &proof2, &io).unwrap());
+}
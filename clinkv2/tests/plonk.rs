@@ -0,0 +1,107 @@
+#![cfg(feature = "plonk")]
+
+use ark_bls12_381::{Bls12_381 as E, Fr};
+use ark_ff::{One, PrimeField};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_std::test_rng;
+use blake2::Blake2s;
+use zkp_clinkv2::r1cs::plonk::{public_inputs, PlonkAssignment};
+use zkp_clinkv2::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+use zkp_plonk::{MockProver, Plonk};
+
+type PlonkInst = Plonk<Fr, Blake2s, MarlinKZG10<E, DensePolynomial<Fr>>>;
+
+/// x * (y + 2) = z, and 3 * x + y = w
+struct Mini<F: PrimeField> {
+    x: Option<F>,
+    y: Option<F>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for Mini<F> {
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+        index: usize,
+    ) -> Result<(), SynthesisError> {
+        cs.alloc_input(|| "", || Ok(F::one()), index)?;
+        let var_x = cs.alloc(
+            || "x",
+            || self.x.ok_or(SynthesisError::AssignmentMissing),
+            index,
+        )?;
+        let var_y = cs.alloc(
+            || "y",
+            || self.y.ok_or(SynthesisError::AssignmentMissing),
+            index,
+        )?;
+        let z = self.x.and_then(|x| self.y.map(|y| x * (y + F::from(2u32))));
+        let var_z = cs.alloc_input(|| "z", || z.ok_or(SynthesisError::AssignmentMissing), index)?;
+        let w = self.x.and_then(|x| self.y.map(|y| F::from(3u32) * x + y));
+        let var_w = cs.alloc_input(|| "w", || w.ok_or(SynthesisError::AssignmentMissing), index)?;
+
+        if index == 0 {
+            cs.enforce(
+                || "x * (y + 2) = z",
+                |lc| lc + var_x,
+                |lc| lc + var_y + (F::from(2u32), CS::one()),
+                |lc| lc + var_z,
+            );
+            cs.enforce(
+                || "(3 * x + y) * 1 = w",
+                |lc| lc + (F::from(3u32), var_x) + var_y,
+                |lc| lc + CS::one(),
+                |lc| lc + var_w,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn clinkv2_circuit_on_plonk() {
+    let rng = &mut test_rng();
+    let n = 4;
+    let ks = [
+        Fr::one(),
+        Fr::from(7_u64),
+        Fr::from(13_u64),
+        Fr::from(17_u64),
+    ];
+
+    // the keys come from the circuit without its witnesses.
+    let mut counting = PlonkAssignment::<Fr>::new_counting();
+    for i in 0..n {
+        let c = Mini::<Fr> { x: None, y: None };
+        c.generate_constraints(&mut counting, i).unwrap();
+    }
+    let shape = counting.into_composer().unwrap();
+    let srs = PlonkInst::setup(shape.srs_degree().unwrap(), rng).unwrap();
+    let (pk, vk) = PlonkInst::keygen(&srs, &shape, &ks).unwrap();
+
+    let mut prover = PlonkAssignment::<Fr>::default();
+    let mut io = vec![vec![Fr::one(); n], vec![], vec![]];
+    for i in 0..n {
+        let (x, y) = (Fr::from(i as u32 + 2), Fr::from(5u32));
+        let c = Mini {
+            x: Some(x),
+            y: Some(y),
+        };
+        c.generate_constraints(&mut prover, i).unwrap();
+        io[1].push(x * (y + Fr::from(2u32)));
+        io[2].push(Fr::from(3u32) * x + y);
+    }
+    let cs = prover.into_composer().unwrap();
+    assert_eq!(cs.size(), shape.size());
+    assert_eq!(public_inputs(&io, vk.info.n), cs.public_inputs());
+    assert_eq!(MockProver::run(&cs, &public_inputs(&io, vk.info.n)), Ok(()));
+
+    let proof = PlonkInst::prove(&pk, &cs, rng).unwrap();
+    assert!(PlonkInst::verify(&vk, &public_inputs(&io, vk.info.n), proof).unwrap());
+
+    io[2][1] += Fr::one();
+    assert!(MockProver::run(&cs, &public_inputs(&io, vk.info.n)).is_err());
+    let proof = PlonkInst::prove(&pk, &cs, rng).unwrap();
+    assert!(!PlonkInst::verify(&vk, &public_inputs(&io, vk.info.n), proof).unwrap());
+}
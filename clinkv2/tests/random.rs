@@ -0,0 +1,249 @@
+//! Differential tests over random circuits: honest proofs must verify, while
+//! proofs of a wrong witness or checked against wrong outputs must not.
+
+use ark_bls12_381::{Bls12_381 as E, Fr};
+use ark_ff::{One, PrimeField};
+use ark_std::test_rng;
+use blake2::Blake2s;
+use rand::{Rng, RngCore};
+use zkp_clinkv2::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError, Variable};
+
+const INSTANCES: usize = 8;
+const ROUNDS: usize = 3;
+
+/// A gate `o = coeff * l * r` or `o = coeff * l + r`, over earlier wires.
+#[derive(Clone, Copy)]
+struct Gate<F> {
+    mul: bool,
+    l: usize,
+    r: usize,
+    coeff: F,
+}
+
+/// The random circuit: a few private inputs, a chain of gates, and the last
+/// wire as the public output.
+struct Shape<F> {
+    inputs: usize,
+    gates: Vec<Gate<F>>,
+}
+
+impl<F: PrimeField> Shape<F> {
+    fn random<R: Rng>(rng: &mut R) -> Self {
+        let inputs = rng.gen_range(1, 4);
+        let gates = (0..rng.gen_range(4, 16))
+            .map(|i| Gate {
+                mul: rng.gen(),
+                l: rng.gen_range(0, inputs + i),
+                r: rng.gen_range(0, inputs + i),
+                coeff: F::rand(rng),
+            })
+            .collect();
+        Shape { inputs, gates }
+    }
+
+    /// All wire values of one instance; the output of `bad_gate` is off by
+    /// one.
+    fn assign<R: Rng>(&self, bad_gate: Option<usize>, rng: &mut R) -> Vec<F> {
+        let mut values: Vec<F> = (0..self.inputs).map(|_| F::rand(rng)).collect();
+        for (i, gate) in self.gates.iter().enumerate() {
+            let (l, r) = (values[gate.l], values[gate.r]);
+            let mut o = if gate.mul {
+                gate.coeff * l * r
+            } else {
+                gate.coeff * l + r
+            };
+            if Some(i) == bad_gate {
+                o += F::one();
+            }
+            values.push(o);
+        }
+        values
+    }
+}
+
+struct RandomCircuit<'a, F> {
+    shape: &'a Shape<F>,
+    values: Option<&'a [F]>,
+}
+
+impl<'a, F: PrimeField> ConstraintSynthesizer<F> for RandomCircuit<'a, F> {
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+        index: usize,
+    ) -> Result<(), SynthesisError> {
+        cs.alloc_input(|| "", || Ok(F::one()), index)?;
+
+        let value = |i: usize| {
+            self.values
+                .map(|v| v[i])
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        let mut vars: Vec<Variable> = Vec::new();
+        for i in 0..self.shape.inputs {
+            vars.push(cs.alloc(|| "input", || value(i), index)?);
+        }
+
+        for (i, gate) in self.shape.gates.iter().enumerate() {
+            let o = cs.alloc(|| "gate", || value(self.shape.inputs + i), index)?;
+            let (l, r) = (vars[gate.l], vars[gate.r]);
+            if index == 0 {
+                if gate.mul {
+                    cs.enforce(
+                        || "o = coeff * l * r",
+                        |lc| lc + (gate.coeff, l),
+                        |lc| lc + r,
+                        |lc| lc + o,
+                    );
+                } else {
+                    cs.enforce(
+                        || "o = coeff * l + r",
+                        |lc| lc + (gate.coeff, l) + r,
+                        |lc| lc + CS::one(),
+                        |lc| lc + o,
+                    );
+                }
+            }
+            vars.push(o);
+        }
+
+        let last = vars.len() - 1;
+        let output = cs.alloc_input(|| "output", || value(last), index)?;
+        if index == 0 {
+            cs.enforce(
+                || "output",
+                |lc| lc + vars[last],
+                |lc| lc + CS::one(),
+                |lc| lc + output,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The witnesses of all instances, with a wrong witness in one instance if
+/// `bad` is set, and their io.
+fn instances<R: Rng>(
+    shape: &Shape<Fr>,
+    bad: bool,
+    rng: &mut R,
+) -> (Vec<Vec<Fr>>, Vec<Vec<Fr>>) {
+    let bad_instance = rng.gen_range(0, INSTANCES);
+    let values: Vec<_> = (0..INSTANCES)
+        .map(|i| {
+            let bad_gate = if bad && i == bad_instance {
+                Some(rng.gen_range(0, shape.gates.len()))
+            } else {
+                None
+            };
+            shape.assign(bad_gate, rng)
+        })
+        .collect();
+
+    let outputs = values.iter().map(|v| *v.last().unwrap()).collect();
+    (values, vec![vec![Fr::one(); INSTANCES], outputs])
+}
+
+fn tamper<R: RngCore>(io: &mut [Vec<Fr>], rng: &mut R) {
+    let i = rng.next_u32() as usize % INSTANCES;
+    io[1][i] += Fr::one();
+}
+
+fn kzg10_round_trip(shape: &Shape<Fr>, values: &[Vec<Fr>], io: &[Vec<Fr>]) -> bool {
+    use zkp_clinkv2::kzg10::{
+        create_random_proof, verify_proof, ProveAssignment, VerifyAssignment, KZG10,
+    };
+
+    let rng = &mut test_rng();
+    let degree = INSTANCES.next_power_of_two();
+    let pp = KZG10::<E>::setup(degree, false, rng).unwrap();
+    let (ck, vk) = KZG10::<E>::trim(&pp, degree).unwrap();
+
+    let mut prover_pa = ProveAssignment::<E>::default();
+    for (i, v) in values.iter().enumerate() {
+        let c = RandomCircuit {
+            shape,
+            values: Some(&v[..]),
+        };
+        c.generate_constraints(&mut prover_pa, i).unwrap();
+    }
+    let proof = create_random_proof(&prover_pa, &ck, rng).unwrap();
+
+    let mut verifier_pa = VerifyAssignment::<E>::default();
+    let c = RandomCircuit::<Fr> {
+        shape,
+        values: None,
+    };
+    c.generate_constraints(&mut verifier_pa, 0).unwrap();
+    verify_proof(&verifier_pa, &vk, &proof, &io.to_vec()).unwrap()
+}
+
+fn ipa_round_trip(shape: &Shape<Fr>, values: &[Vec<Fr>], io: &[Vec<Fr>]) -> bool {
+    use zkp_clinkv2::ipa::{
+        create_random_proof, verify_proof, InnerProductArgPC, ProveAssignment, VerifyAssignment,
+    };
+
+    let rng = &mut test_rng();
+    let degree = INSTANCES.next_power_of_two();
+    let pp = InnerProductArgPC::<E, Blake2s>::setup(degree, rng).unwrap();
+    let (ck, vk) = InnerProductArgPC::<E, Blake2s>::trim(&pp, degree).unwrap();
+
+    let mut prover_pa = ProveAssignment::<E, Blake2s>::default();
+    for (i, v) in values.iter().enumerate() {
+        let c = RandomCircuit {
+            shape,
+            values: Some(&v[..]),
+        };
+        c.generate_constraints(&mut prover_pa, i).unwrap();
+    }
+    let proof = create_random_proof(&prover_pa, &ck, rng).unwrap();
+
+    let mut verifier_pa = VerifyAssignment::<E, Blake2s>::default();
+    let c = RandomCircuit::<Fr> {
+        shape,
+        values: None,
+    };
+    c.generate_constraints(&mut verifier_pa, 0).unwrap();
+    verify_proof(&verifier_pa, &vk, &proof, &io.to_vec()).unwrap()
+}
+
+fn round_trip(shape: &Shape<Fr>, values: &[Vec<Fr>], io: &[Vec<Fr>]) -> (bool, bool) {
+    (
+        kzg10_round_trip(shape, values, io),
+        ipa_round_trip(shape, values, io),
+    )
+}
+
+#[test]
+fn random_circuits_complete() {
+    let rng = &mut test_rng();
+    for _ in 0..ROUNDS {
+        let shape = Shape::random(rng);
+        let (values, io) = instances(&shape, false, rng);
+        assert_eq!(round_trip(&shape, &values, &io), (true, true));
+    }
+}
+
+#[test]
+fn random_circuits_wrong_witness() {
+    let rng = &mut test_rng();
+    for _ in 0..ROUNDS {
+        let shape = Shape::random(rng);
+        let (values, io) = instances(&shape, true, rng);
+        assert_eq!(round_trip(&shape, &values, &io), (false, false));
+    }
+}
+
+#[test]
+fn random_circuits_wrong_outputs() {
+    let rng = &mut test_rng();
+    for _ in 0..ROUNDS {
+        let shape = Shape::random(rng);
+        let (values, mut io) = instances(&shape, false, rng);
+        tamper(&mut io, rng);
+        assert_eq!(round_trip(&shape, &values, &io), (false, false));
+    }
+}
+
@@ -0,0 +1,364 @@
+//! Twisted Edwards point gadgets over an embedded curve whose base field is
+//! the circuit field (e.g. Jubjub inside a BLS12-381 circuit).
+
+use ark_ec::{models::TEModelParameters, twisted_edwards_extended::GroupAffine, AffineCurve};
+use ark_ff::{PrimeField, Zero};
+use zkp_r1cs::{ConstraintSystem, SynthesisError, Variable};
+
+use crate::algebra::boolean::Boolean;
+
+/// A point `(x, y)` on the twisted Edwards curve `a * x^2 + y^2 = 1 + d * x^2 * y^2`.
+pub struct EdwardsPoint<F: PrimeField, P: TEModelParameters<BaseField = F>> {
+    x: Variable,
+    y: Variable,
+    value: Option<GroupAffine<P>>,
+}
+
+impl<F: PrimeField, P: TEModelParameters<BaseField = F>> Clone for EdwardsPoint<F, P> {
+    fn clone(&self) -> Self {
+        EdwardsPoint {
+            x: self.x,
+            y: self.y,
+            value: self.value,
+        }
+    }
+}
+
+fn alloc_product<F, CS>(
+    mut cs: CS,
+    a: (Variable, Option<F>),
+    b: (Variable, Option<F>),
+) -> Result<(Variable, Option<F>), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let value = match (a.1, b.1) {
+        (Some(a), Some(b)) => Some(a * b),
+        _ => None,
+    };
+    let var = cs.alloc(
+        || "product",
+        || value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(
+        || "product = a * b",
+        |lc| lc + a.0,
+        |lc| lc + b.0,
+        |lc| lc + var,
+    );
+
+    Ok((var, value))
+}
+
+impl<F: PrimeField, P: TEModelParameters<BaseField = F>> EdwardsPoint<F, P> {
+    pub fn get_x(&self) -> Variable {
+        self.x
+    }
+
+    pub fn get_y(&self) -> Variable {
+        self.y
+    }
+
+    pub fn get_value(&self) -> Option<GroupAffine<P>> {
+        self.value
+    }
+
+    fn x_value(&self) -> Option<F> {
+        self.value.map(|p| p.x)
+    }
+
+    fn y_value(&self) -> Option<F> {
+        self.value.map(|p| p.y)
+    }
+
+    /// Allocate a private point and enforce that it is on the curve.
+    pub fn alloc<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        value: Option<GroupAffine<P>>,
+    ) -> Result<Self, SynthesisError> {
+        let x = cs.alloc(
+            || "x",
+            || value.map(|p| p.x).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let y = cs.alloc(
+            || "y",
+            || value.map(|p| p.y).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let point = EdwardsPoint { x, y, value };
+        point.enforce_on_curve(cs.ns(|| "on curve"))?;
+
+        Ok(point)
+    }
+
+    /// Allocate a public point and enforce that it is on the curve.
+    pub fn alloc_input<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        value: Option<GroupAffine<P>>,
+    ) -> Result<Self, SynthesisError> {
+        let x = cs.alloc_input(
+            || "x",
+            || value.map(|p| p.x).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let y = cs.alloc_input(
+            || "y",
+            || value.map(|p| p.y).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let point = EdwardsPoint { x, y, value };
+        point.enforce_on_curve(cs.ns(|| "on curve"))?;
+
+        Ok(point)
+    }
+
+    /// Allocate a point that is fixed to a known constant.
+    pub fn constant<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        point: GroupAffine<P>,
+    ) -> Result<Self, SynthesisError> {
+        let x = cs.alloc(|| "x", || Ok(point.x))?;
+        let y = cs.alloc(|| "y", || Ok(point.y))?;
+        cs.enforce(
+            || "x = constant",
+            |lc| lc + x,
+            |lc| lc + CS::one(),
+            |lc| lc + (point.x, CS::one()),
+        );
+        cs.enforce(
+            || "y = constant",
+            |lc| lc + y,
+            |lc| lc + CS::one(),
+            |lc| lc + (point.y, CS::one()),
+        );
+
+        Ok(EdwardsPoint {
+            x,
+            y,
+            value: Some(point),
+        })
+    }
+
+    /// The prime order subgroup generator of the curve, as a constant.
+    pub fn generator<CS: ConstraintSystem<F>>(cs: CS) -> Result<Self, SynthesisError> {
+        Self::constant(cs, GroupAffine::<P>::prime_subgroup_generator())
+    }
+
+    fn enforce_on_curve<CS: ConstraintSystem<F>>(&self, mut cs: CS) -> Result<(), SynthesisError> {
+        let x2 = alloc_product(
+            cs.ns(|| "x^2"),
+            (self.x, self.x_value()),
+            (self.x, self.x_value()),
+        )?;
+        let y2 = alloc_product(
+            cs.ns(|| "y^2"),
+            (self.y, self.y_value()),
+            (self.y, self.y_value()),
+        )?;
+        let x2y2 = alloc_product(cs.ns(|| "x^2 * y^2"), x2, y2)?;
+
+        // a * x^2 + y^2 = 1 + d * x^2 * y^2
+        cs.enforce(
+            || "curve equation",
+            |lc| lc + (P::COEFF_A, x2.0) + y2.0 - CS::one() - (P::COEFF_D, x2y2.0),
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+
+        Ok(())
+    }
+
+    /// Complete twisted Edwards addition (7 constraints).
+    pub fn add<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let x1 = (self.x, self.x_value());
+        let y1 = (self.y, self.y_value());
+        let x2 = (other.x, other.x_value());
+        let y2 = (other.y, other.y_value());
+
+        let x1y2 = alloc_product(cs.ns(|| "x1 * y2"), x1, y2)?;
+        let y1x2 = alloc_product(cs.ns(|| "y1 * x2"), y1, x2)?;
+        let x1x2 = alloc_product(cs.ns(|| "x1 * x2"), x1, x2)?;
+        let y1y2 = alloc_product(cs.ns(|| "y1 * y2"), y1, y2)?;
+        let t = alloc_product(cs.ns(|| "x1 * x2 * y1 * y2"), x1y2, y1x2)?;
+
+        let value = match (self.value, other.value) {
+            (Some(p), Some(q)) => {
+                let mut sum = p;
+                sum += &q;
+                Some(sum)
+            }
+            _ => None,
+        };
+
+        let x3 = cs.alloc(
+            || "x3",
+            || value.map(|p| p.x).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let y3 = cs.alloc(
+            || "y3",
+            || value.map(|p| p.y).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+
+        // x3 * (1 + d * t) = x1 * y2 + y1 * x2
+        cs.enforce(
+            || "x3 denominator",
+            |lc| lc + x3,
+            |lc| lc + CS::one() + (P::COEFF_D, t.0),
+            |lc| lc + x1y2.0 + y1x2.0,
+        );
+        // y3 * (1 - d * t) = y1 * y2 - a * x1 * x2
+        cs.enforce(
+            || "y3 denominator",
+            |lc| lc + y3,
+            |lc| lc + CS::one() - (P::COEFF_D, t.0),
+            |lc| lc + y1y2.0 - (P::COEFF_A, x1x2.0),
+        );
+
+        Ok(EdwardsPoint {
+            x: x3,
+            y: y3,
+            value,
+        })
+    }
+
+    pub fn double<CS: ConstraintSystem<F>>(&self, cs: CS) -> Result<Self, SynthesisError> {
+        self.add(cs, self)
+    }
+
+    /// Returns `if_true` when `cond` holds, otherwise `if_false`.
+    pub fn select<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        cond: &Boolean,
+        if_true: &Self,
+        if_false: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let value = match cond.get_value() {
+            Some(true) => if_true.value,
+            Some(false) => if_false.value,
+            None => None,
+        };
+
+        let x = cs.alloc(
+            || "x",
+            || value.map(|p| p.x).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let y = cs.alloc(
+            || "y",
+            || value.map(|p| p.y).ok_or(SynthesisError::AssignmentMissing),
+        )?;
+
+        // cond * (t - f) = r - f
+        cs.enforce(
+            || "select x",
+            |_| cond.lc(CS::one(), F::one()),
+            |lc| lc + if_true.x - if_false.x,
+            |lc| lc + x - if_false.x,
+        );
+        cs.enforce(
+            || "select y",
+            |_| cond.lc(CS::one(), F::one()),
+            |lc| lc + if_true.y - if_false.y,
+            |lc| lc + y - if_false.y,
+        );
+
+        Ok(EdwardsPoint { x, y, value })
+    }
+
+    /// Variable-base scalar multiplication by little-endian bits.
+    pub fn mul_bits_le<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        bits: &[Boolean],
+    ) -> Result<Self, SynthesisError> {
+        let mut acc = Self::constant(cs.ns(|| "identity"), GroupAffine::<P>::zero())?;
+        let mut base = self.clone();
+
+        for (i, bit) in bits.iter().enumerate() {
+            let sum = acc.add(cs.ns(|| format!("add {}", i)), &base)?;
+            acc = Self::select(cs.ns(|| format!("select {}", i)), bit, &sum, &acc)?;
+            if i + 1 < bits.len() {
+                base = base.double(cs.ns(|| format!("double {}", i)))?;
+            }
+        }
+
+        Ok(acc)
+    }
+
+    pub fn enforce_equal<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<(), SynthesisError> {
+        cs.enforce(
+            || "x equal",
+            |lc| lc + self.x - other.x,
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+        cs.enforce(
+            || "y equal",
+            |lc| lc + self.y - other.y,
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_ec::ProjectiveCurve;
+    use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsParameters, Fq, Fr as JubjubFr};
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+    use zkp_r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::algebra::boolean::AllocatedBit;
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    type Point = EdwardsPoint<Fq, EdwardsParameters>;
+
+    #[test]
+    fn test_edwards_add() {
+        let rng = &mut test_rng();
+        let g = EdwardsAffine::prime_subgroup_generator();
+        let p = g.mul(JubjubFr::rand(rng)).into_affine();
+        let q = g.mul(JubjubFr::rand(rng)).into_affine();
+
+        let mut cs = TestConstraintSystem::<Fq>::new();
+        let a = Point::alloc(cs.ns(|| "p"), Some(p)).unwrap();
+        let b = Point::alloc(cs.ns(|| "q"), Some(q)).unwrap();
+        let c = a.add(cs.ns(|| "p + q"), &b).unwrap();
+
+        let mut sum = p;
+        sum += &q;
+        assert_eq!(c.get_value().unwrap(), sum);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_edwards_mul_bits() {
+        let rng = &mut test_rng();
+        let g = EdwardsAffine::prime_subgroup_generator();
+        let p = g.mul(JubjubFr::rand(rng)).into_affine();
+        let k: u64 = 0b1011_0110;
+
+        let mut cs = TestConstraintSystem::<Fq>::new();
+        let a = Point::alloc(cs.ns(|| "p"), Some(p)).unwrap();
+        let bits: Vec<Boolean> = (0..8)
+            .map(|i| {
+                AllocatedBit::alloc(cs.ns(|| format!("bit {}", i)), Some(k >> i & 1 == 1))
+                    .unwrap()
+                    .into()
+            })
+            .collect();
+        let r = a.mul_bits_le(cs.ns(|| "k * p"), &bits).unwrap();
+
+        assert_eq!(r.get_value().unwrap(), p.mul(JubjubFr::from(k)).into_affine());
+        assert!(cs.is_satisfied());
+    }
+}
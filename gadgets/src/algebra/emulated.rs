@@ -0,0 +1,789 @@
+//! Arithmetic of a prime field `T` emulated in the field `F` of the circuit,
+//! e.g. the base field of secp256k1 or of ed25519 in the scalar field of
+//! BLS12-381.
+//!
+//! An element is held as the 64-bit limbs of its integer, least significant
+//! first, each range-checked by its bits. An operation allocates its result
+//! and enforces a relation `L = 0 mod p`, where `L` is a signed sum of
+//! products and elements (`enforce_relation`), as the integer identity
+//! `L = q p` for an allocated signed quotient `q`. As polynomials in
+//! `X = 2^64`:
+//!
+//! - the coefficients of a product `A(X) B(X)` are allocated and checked at
+//!   the `2n - 1` points `0, 1, ..`, that is modulo each `X - j`, which fixes
+//!   the product of degree `2n - 2` as by the CRT, in `2n - 1` constraints
+//!   instead of the `n^2` of the limb products;
+//! - `L(X) - Q(X) P(X)` is checked to vanish at `X = 2^64` by carrying each
+//!   coefficient into the next one, with range-checked carries.
+//!
+//! So a relation of several products, e.g. the slope of a point addition,
+//! takes a single quotient and carry chain. The limbs of a result are checked
+//! to fit in `64 n` bits but not to be below `p`, unless `enforce_reduced`,
+//! so `enforce_equal` compares elements modulo `p`.
+
+use ark_ff::{FpParameters, PrimeField};
+use zkp_r1cs::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
+
+use crate::Vec;
+
+use crate::algebra::boolean::Boolean;
+use crate::algebra::uint32::alloc_bits;
+use crate::algebra::witness_inverse;
+
+/// The limbs of a product as linear combinations, with their values when
+/// assigned.
+type Coefficients<F> = (Vec<LinearCombination<F>>, Option<Vec<F>>);
+
+const LIMB_BITS: usize = 64;
+
+/// The bits of a carry, which is offset by `2^(CARRY_BITS - 1)` to be
+/// non-negative.
+const CARRY_BITS: usize = 72;
+
+/// The most limbs of an element, so that the carries fit in `CARRY_BITS`.
+const MAX_LIMBS: usize = 8;
+
+#[derive(Clone)]
+pub struct EmulatedFp<F: PrimeField, T: PrimeField> {
+    // Least significant limb first
+    limbs: Vec<LinearCombination<F>>,
+    value: Option<T>,
+}
+
+impl<F: PrimeField, T: PrimeField> EmulatedFp<F, T> {
+    pub fn get_value(&self) -> Option<T> {
+        self.value
+    }
+
+    pub fn get_limbs(&self) -> &[LinearCombination<F>] {
+        &self.limbs
+    }
+
+    fn num_limbs() -> usize {
+        let n = num_limbs::<T>();
+        assert!(n <= MAX_LIMBS, "emulated field too large");
+        // the products of limbs and the carries must not wrap around `F`.
+        assert!(F::size_in_bits() > 2 * LIMB_BITS + CARRY_BITS);
+        n
+    }
+
+    /// Allocate an element, with range-checked limbs.
+    pub fn alloc<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        value: Option<T>,
+    ) -> Result<Self, SynthesisError> {
+        let values = value.map(|v| limbs_of(&v));
+        let limbs = (0..Self::num_limbs())
+            .map(|i| {
+                alloc_limb(
+                    cs.ns(|| format!("limb {}", i)),
+                    values.as_ref().map(|v| v[i]),
+                )
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(EmulatedFp { limbs, value })
+    }
+
+    /// A constant element, as the constant limbs on `one`.
+    pub fn constant(value: T, one: Variable) -> Self {
+        let limbs = limbs_of(&value)
+            .iter()
+            .map(|limb| LinearCombination::zero() + (F::from(*limb), one))
+            .collect();
+
+        EmulatedFp {
+            limbs,
+            value: Some(value),
+        }
+    }
+
+    /// The limbs of the value, in `F`.
+    fn limb_values(&self) -> Option<Vec<F>> {
+        self.value
+            .map(|v| limbs_of(&v).iter().map(|l| F::from(*l)).collect())
+    }
+
+    pub fn add<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a + b),
+            _ => None,
+        };
+        let sum = Self::alloc(cs.ns(|| "sum"), value)?;
+        Self::enforce_relation(
+            cs.ns(|| "relation"),
+            &[],
+            &[(1, self), (1, other), (-1, &sum)],
+        )?;
+
+        Ok(sum)
+    }
+
+    pub fn sub<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a - b),
+            _ => None,
+        };
+        let difference = Self::alloc(cs.ns(|| "difference"), value)?;
+        Self::enforce_relation(
+            cs.ns(|| "relation"),
+            &[],
+            &[(1, self), (-1, other), (-1, &difference)],
+        )?;
+
+        Ok(difference)
+    }
+
+    pub fn mul<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a * b),
+            _ => None,
+        };
+        let product = Self::alloc(cs.ns(|| "product"), value)?;
+        Self::enforce_relation(cs.ns(|| "relation"), &[(1, self, other)], &[(-1, &product)])?;
+
+        Ok(product)
+    }
+
+    /// The inverse, enforced by `self * inverse = 1`, which also enforces
+    /// that `self` is not zero.
+    pub fn inverse<CS: ConstraintSystem<F>>(&self, mut cs: CS) -> Result<Self, SynthesisError> {
+        let value = match self.value {
+            Some(v) => Some(witness_inverse(&v).ok_or(SynthesisError::DivisionByZero)?),
+            None => None,
+        };
+        let inverse = Self::alloc(cs.ns(|| "inverse"), value)?;
+        Self::enforce_relation(
+            cs.ns(|| "relation"),
+            &[(1, self, &inverse)],
+            &[(-1, &Self::constant(T::one(), CS::one()))],
+        )?;
+
+        Ok(inverse)
+    }
+
+    /// Enforce that `self` and `other` are equal modulo `p`.
+    pub fn enforce_equal<CS: ConstraintSystem<F>>(
+        &self,
+        cs: CS,
+        other: &Self,
+    ) -> Result<(), SynthesisError> {
+        Self::enforce_relation(cs, &[], &[(1, self), (-1, other)])
+    }
+
+    /// Returns `if_true` when `cond` holds, otherwise `if_false`. The limbs
+    /// are selected from range-checked limbs, so they are not checked again.
+    pub fn select<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        cond: &Boolean,
+        if_true: &Self,
+        if_false: &Self,
+    ) -> Result<Self, SynthesisError> {
+        // f + cond (t - f), without branching on the secret `cond`
+        let value = match (cond.get_value(), if_true.value, if_false.value) {
+            (Some(cond), Some(t), Some(f)) => Some(f + (t - f) * T::from(cond as u64)),
+            _ => None,
+        };
+        let values = value.map(|v| limbs_of(&v));
+
+        let mut limbs = Vec::with_capacity(if_true.limbs.len());
+        for (i, (t, f)) in if_true.limbs.iter().zip(if_false.limbs.iter()).enumerate() {
+            let limb = cs.alloc(
+                || format!("limb {}", i),
+                || {
+                    values
+                        .as_ref()
+                        .map(|v| F::from(v[i]))
+                        .ok_or(SynthesisError::AssignmentMissing)
+                },
+            )?;
+            // cond * (t - f) = r - f
+            cs.enforce(
+                || format!("select limb {}", i),
+                |_| cond.lc(CS::one(), F::one()),
+                |_| t.clone() - f,
+                |lc| lc + limb - f,
+            );
+            limbs.push(LinearCombination::from(limb));
+        }
+
+        Ok(EmulatedFp { limbs, value })
+    }
+
+    /// The `64 n` bits of the limbs, least significant first. They are the
+    /// bits of the value only if the limbs are reduced, see
+    /// `enforce_reduced`.
+    pub fn to_bits<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+    ) -> Result<Vec<Boolean>, SynthesisError> {
+        let values = self.value.map(|v| limbs_of(&v));
+        let mut bits = Vec::with_capacity(LIMB_BITS * self.limbs.len());
+        for (i, limb) in self.limbs.iter().enumerate() {
+            let (limb_bits, lc) = alloc_bits(
+                cs.ns(|| format!("limb {}", i)),
+                values.as_ref().map(|v| u128::from(v[i])),
+                LIMB_BITS,
+            )?;
+            cs.enforce(
+                || format!("limb {} packing", i),
+                |_| lc,
+                |lc| lc + CS::one(),
+                |_| limb.clone(),
+            );
+            bits.extend(limb_bits);
+        }
+
+        Ok(bits)
+    }
+
+    /// Enforce that the limbs are the integer of the value, below `p`, by
+    /// allocating `p - 1 - self` in range-checked limbs.
+    pub fn enforce_reduced<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+    ) -> Result<(), SynthesisError> {
+        let max = big_sub(&modulus::<T>(), &[1]);
+        let values = self.value.map(|v| big_sub(&max, &limbs_of(&v)));
+        let difference = (0..self.limbs.len())
+            .map(|i| {
+                alloc_limb(
+                    cs.ns(|| format!("difference limb {}", i)),
+                    values.as_ref().map(|v| v[i]),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // self + difference - (p - 1), as a polynomial in 2^64
+        let e = self
+            .limbs
+            .iter()
+            .zip(difference.iter())
+            .zip(max.iter())
+            .map(|((a, d), m)| a.clone() + d - (F::from(*m), CS::one()))
+            .collect();
+        let e_values = match (self.limb_values(), values) {
+            (Some(a), Some(d)) => Some(
+                a.iter()
+                    .zip(d.iter())
+                    .zip(max.iter())
+                    .map(|((a, d), m)| *a + F::from(*d) - F::from(*m))
+                    .collect(),
+            ),
+            _ => None,
+        };
+
+        enforce_carries(cs.ns(|| "carries"), e, e_values)
+    }
+
+    /// Enforce `sum k a b + sum m c = 0 mod p` over the `products` and
+    /// `terms`, with small signed coefficients `k` and `m`, by a single
+    /// quotient and carry chain.
+    pub fn enforce_relation<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        products: &[(i64, &Self, &Self)],
+        terms: &[(i64, &Self)],
+    ) -> Result<(), SynthesisError> {
+        let n = Self::num_limbs();
+        let p = modulus::<T>();
+
+        // |L| < weight 2^bits_l, and p >= 2^(size_in_bits - 1)
+        let weight: u64 = products
+            .iter()
+            .map(|(k, _, _)| k.unsigned_abs())
+            .chain(terms.iter().map(|(m, _)| m.unsigned_abs()))
+            .sum();
+        assert!(weight > 0, "empty relation");
+        assert!(
+            (weight as usize + 2) * (n + 1) < 1 << (CARRY_BITS - 2 - LIMB_BITS),
+            "relation too large for the carries"
+        );
+        let bits_l = if products.is_empty() {
+            LIMB_BITS * n
+        } else {
+            2 * LIMB_BITS * n
+        };
+        let weight_bits = 64 - weight.leading_zeros() as usize;
+        // |q| < 2^(q_bits - 1)
+        let q_bits = bits_l + weight_bits + 2 - T::size_in_bits();
+        let quotient_limbs = q_bits.div_ceil(LIMB_BITS);
+
+        // The coefficients of L(X)
+        let len = if products.is_empty() { n } else { 2 * n - 1 }.max(quotient_limbs + n - 1);
+        let mut e = vec![LinearCombination::zero(); len];
+        let mut e_values = Some(vec![F::zero(); len]);
+        for (i, (k, a, b)) in products.iter().enumerate() {
+            let k = signed::<F>(*k);
+            let (c, c_values) = a.product_coefficients(cs.ns(|| format!("product {}", i)), b)?;
+            for (j, c) in c.iter().enumerate() {
+                e[j] = e[j].clone() + (k, c);
+            }
+            e_values = match (e_values, c_values) {
+                (Some(mut e), Some(c)) => {
+                    for (j, c) in c.iter().enumerate() {
+                        e[j] += k * c;
+                    }
+                    Some(e)
+                }
+                _ => None,
+            };
+        }
+        for (m, c) in terms {
+            let m = signed::<F>(*m);
+            for (j, limb) in c.limbs.iter().enumerate() {
+                e[j] = e[j].clone() + (m, limb);
+            }
+            e_values = match (e_values, c.limb_values()) {
+                (Some(mut e), Some(c)) => {
+                    for (j, c) in c.iter().enumerate() {
+                        e[j] += m * c;
+                    }
+                    Some(e)
+                }
+                _ => None,
+            };
+        }
+
+        let q_values = Self::quotient_value(products, terms, quotient_limbs);
+        let q = (0..quotient_limbs)
+            .map(|i| {
+                alloc_limb(
+                    cs.ns(|| format!("q limb {}", i)),
+                    q_values.as_ref().map(|q| q[i]),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // E(X) = L(X) - Q(X) P(X) + 2^63 X^(limbs - 1) P(X)
+        let offset = F::from(1u64 << 63);
+        for (j, p) in p.iter().enumerate() {
+            let p = F::from(*p);
+            for (i, q) in q.iter().enumerate() {
+                e[i + j] = e[i + j].clone() + (-p, q);
+            }
+            e[quotient_limbs - 1 + j] = e[quotient_limbs - 1 + j].clone() + (offset * p, CS::one());
+        }
+        e_values = match (e_values, &q_values) {
+            (Some(mut e), Some(q)) => {
+                for (j, p) in p.iter().enumerate() {
+                    let p = F::from(*p);
+                    for (i, q) in q.iter().enumerate() {
+                        e[i + j] -= F::from(*q) * p;
+                    }
+                    e[quotient_limbs - 1 + j] += offset * p;
+                }
+                Some(e)
+            }
+            _ => None,
+        };
+
+        enforce_carries(cs.ns(|| "carries"), e, e_values)
+    }
+
+    /// The quotient `q = L / p`, offset by `2^(64 limbs - 1)` to be
+    /// non-negative. As `L = q p` exactly, `q` is `L` times the inverse of
+    /// `p` modulo `2^(64 limbs)`, which fixes the signed `q` in two's
+    /// complement.
+    fn quotient_value(
+        products: &[(i64, &Self, &Self)],
+        terms: &[(i64, &Self)],
+        quotient_limbs: usize,
+    ) -> Option<Vec<u64>> {
+        let mut l = vec![0u64; quotient_limbs];
+        for (k, a, b) in products {
+            let ab = big_mul(&limbs_of(&a.value?), &limbs_of(&b.value?), quotient_limbs);
+            accumulate(&mut l, &ab, *k);
+        }
+        for (m, c) in terms {
+            accumulate(&mut l, &limbs_of(&c.value?), *m);
+        }
+
+        let inverse = inverse_mod_2k(&modulus::<T>(), quotient_limbs);
+        let mut q = big_mul(&l, &inverse, quotient_limbs);
+        q[quotient_limbs - 1] = q[quotient_limbs - 1].wrapping_add(1 << 63);
+        Some(q)
+    }
+
+    /// Allocate the coefficients of `A(X) B(X)`, checked at the points
+    /// `0, 1, .., 2n - 2`.
+    fn product_coefficients<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<Coefficients<F>, SynthesisError> {
+        let n = self.limbs.len();
+
+        let values = match (self.limb_values(), other.limb_values()) {
+            (Some(a), Some(b)) => {
+                let mut c = vec![F::zero(); 2 * n - 1];
+                for (i, a) in a.iter().enumerate() {
+                    for (j, b) in b.iter().enumerate() {
+                        c[i + j] += *a * b;
+                    }
+                }
+                Some(c)
+            }
+            _ => None,
+        };
+        let coefficients = (0..2 * n - 1)
+            .map(|k| {
+                let var = cs.alloc(
+                    || format!("coefficient {}", k),
+                    || {
+                        values
+                            .as_ref()
+                            .map(|c| c[k])
+                            .ok_or(SynthesisError::AssignmentMissing)
+                    },
+                )?;
+                Ok(LinearCombination::from(var))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        for x in 0..2 * n - 1 {
+            let point = F::from(x as u64);
+            let eval = |poly: &[LinearCombination<F>]| {
+                let mut coeff = F::one();
+                let mut lc = LinearCombination::zero();
+                for c in poly {
+                    lc = lc + (coeff, c);
+                    coeff *= point;
+                }
+                lc
+            };
+            let (a, b, c) = (eval(&self.limbs), eval(&other.limbs), eval(&coefficients));
+            cs.enforce(
+                || format!("A({0}) * B({0}) = C({0})", x),
+                |_| a,
+                |_| b,
+                |_| c,
+            );
+        }
+
+        Ok((coefficients, values))
+    }
+}
+
+/// Enforce that the polynomial `E(X)` of the coefficients `e`, with
+/// `|e_k| < 2^(CARRY_BITS + LIMB_BITS - 2)`, vanishes at `X = 2^64`, by
+/// carrying each coefficient into the next one: `e_k + c_(k-1) = c_k 2^64`,
+/// with range-checked carries and no carry out of the top coefficient.
+pub(crate) fn enforce_carries<F, CS>(
+    mut cs: CS,
+    e: Vec<LinearCombination<F>>,
+    e_values: Option<Vec<F>>,
+) -> Result<(), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let len = e.len();
+    let shift = F::from(2u64).pow([LIMB_BITS as u64]);
+    let shift_inverse = shift.inverse().unwrap();
+    let offset = F::from(2u64).pow([CARRY_BITS as u64 - 1]);
+    let mut carry = LinearCombination::zero();
+    let mut carry_value = Some(F::zero());
+    for (k, e) in e.into_iter().enumerate() {
+        let sum = e + &carry;
+        let sum_value = match (e_values.as_ref(), carry_value) {
+            (Some(e), Some(c)) => Some(e[k] + c),
+            _ => None,
+        };
+
+        if k + 1 == len {
+            cs.enforce(
+                || format!("carry {}", k),
+                |_| sum,
+                |lc| lc + CS::one(),
+                |lc| lc,
+            );
+            break;
+        }
+
+        carry_value = sum_value.map(|s| s * shift_inverse);
+        let offset_value = carry_value.map(|c| {
+            let repr = (c + offset).into_repr();
+            let limbs = repr.as_ref();
+            u128::from(limbs[0]) | (u128::from(limbs[1]) << 64)
+        });
+        let (_, offset_carry) =
+            alloc_bits(cs.ns(|| format!("carry {}", k)), offset_value, CARRY_BITS)?;
+        carry = offset_carry - (offset, CS::one());
+
+        cs.enforce(
+            || format!("e {0} + carry {1} = carry {0} * 2^64", k, k as isize - 1),
+            |_| sum,
+            |lc| lc + CS::one(),
+            |_| carry.clone() * shift,
+        );
+    }
+
+    Ok(())
+}
+
+/// Allocate a limb packed from its bits, so that the linear combinations of
+/// the limbs stay short.
+fn alloc_limb<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    value: Option<u64>,
+) -> Result<LinearCombination<F>, SynthesisError> {
+    let limb = cs.alloc(
+        || "limb",
+        || value.map(F::from).ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    let (_, lc) = alloc_bits(cs.ns(|| "bits"), value.map(u128::from), LIMB_BITS)?;
+    cs.enforce(|| "packing", |_| lc, |lc| lc + CS::one(), |lc| lc + limb);
+
+    Ok(LinearCombination::from(limb))
+}
+
+/// The limbs of the modulus, which may be fewer than those of `T::BigInt`.
+fn num_limbs<T: PrimeField>() -> usize {
+    T::size_in_bits().div_ceil(64)
+}
+
+fn limbs_of<T: PrimeField>(value: &T) -> Vec<u64> {
+    value.into_repr().as_ref()[..num_limbs::<T>()].to_vec()
+}
+
+fn modulus<T: PrimeField>() -> Vec<u64> {
+    <T::Params as FpParameters>::MODULUS.as_ref()[..num_limbs::<T>()].to_vec()
+}
+
+fn signed<F: PrimeField>(k: i64) -> F {
+    if k < 0 {
+        -F::from(k.unsigned_abs())
+    } else {
+        F::from(k as u64)
+    }
+}
+
+/// l + k x modulo `2^(64 l.len())`, on little-endian limbs.
+fn accumulate(l: &mut Vec<u64>, x: &[u64], k: i64) {
+    let kx = big_mul(x, &[k.unsigned_abs()], l.len());
+    if k < 0 {
+        *l = big_sub(l, &kx);
+    } else {
+        let len = l.len();
+        *l = big_add(l, &kx);
+        l.truncate(len);
+    }
+}
+
+/// a + b, on little-endian limbs.
+fn big_add(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let len = a.len().max(b.len()) + 1;
+    let mut carry = 0u128;
+    (0..len)
+        .map(|i| {
+            let t =
+                u128::from(*a.get(i).unwrap_or(&0)) + u128::from(*b.get(i).unwrap_or(&0)) + carry;
+            carry = t >> 64;
+            t as u64
+        })
+        .collect()
+}
+
+/// a - b modulo `2^(64 a.len())`, on little-endian limbs.
+fn big_sub(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let mut borrow = false;
+    a.iter()
+        .enumerate()
+        .map(|(i, a)| {
+            let (t, b1) = a.overflowing_sub(*b.get(i).unwrap_or(&0));
+            let (t, b2) = t.overflowing_sub(borrow as u64);
+            borrow = b1 || b2;
+            t
+        })
+        .collect()
+}
+
+/// a * b modulo `2^(64 len)`, on little-endian limbs.
+fn big_mul(a: &[u64], b: &[u64], len: usize) -> Vec<u64> {
+    let mut r = vec![0u64; len];
+    for (i, a) in a.iter().enumerate().take(len) {
+        let mut carry = 0u128;
+        for j in 0..len - i {
+            let t =
+                u128::from(r[i + j]) + u128::from(*a) * u128::from(*b.get(j).unwrap_or(&0)) + carry;
+            r[i + j] = t as u64;
+            carry = t >> 64;
+        }
+    }
+    r
+}
+
+/// The inverse of an odd `p` modulo `2^(64 len)`, by Newton's iteration,
+/// which doubles the correct low bits of `x` each time.
+fn inverse_mod_2k(p: &[u64], len: usize) -> Vec<u64> {
+    let mut two = vec![0u64; len];
+    two[0] = 2;
+    let mut x = vec![0u64; len];
+    x[0] = 1;
+    for _ in 0..(LIMB_BITS * len).next_power_of_two().trailing_zeros() {
+        let px = big_mul(p, &x, len);
+        x = big_mul(&x, &big_sub(&two, &px), len);
+    }
+    x
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::{Fq, Fr};
+    use ark_ed_on_bls12_381::Fr as JubjubFr;
+    use ark_ff::{BigInteger, One, UniformRand};
+    use ark_std::test_rng;
+    use zkp_r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::algebra::boolean::AllocatedBit;
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    fn emulated_arithmetic<T: PrimeField>() {
+        let rng = &mut test_rng();
+
+        for _ in 0..5 {
+            let (a, b) = (T::rand(rng), T::rand(rng));
+
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            let var_a = EmulatedFp::<Fr, T>::alloc(cs.ns(|| "a"), Some(a)).unwrap();
+            let var_b = EmulatedFp::<Fr, T>::alloc(cs.ns(|| "b"), Some(b)).unwrap();
+
+            let sum = var_a.add(cs.ns(|| "a + b"), &var_b).unwrap();
+            let difference = var_a.sub(cs.ns(|| "a - b"), &var_b).unwrap();
+            let product = var_a.mul(cs.ns(|| "a * b"), &var_b).unwrap();
+            let inverse = var_a.inverse(cs.ns(|| "inverse of a")).unwrap();
+            assert_eq!(sum.get_value(), Some(a + b));
+            assert_eq!(difference.get_value(), Some(a - b));
+            assert_eq!(product.get_value(), Some(a * b));
+            assert_eq!(inverse.get_value(), Some(a.inverse().unwrap()));
+
+            // (a + b) (a - b) = a^2 - b^2
+            let lhs = sum.mul(cs.ns(|| "lhs"), &difference).unwrap();
+            let a2 = var_a.mul(cs.ns(|| "a^2"), &var_a).unwrap();
+            let b2 = var_b.mul(cs.ns(|| "b^2"), &var_b).unwrap();
+            let rhs = a2.sub(cs.ns(|| "rhs"), &b2).unwrap();
+            lhs.enforce_equal(cs.ns(|| "lhs = rhs"), &rhs).unwrap();
+
+            let one = EmulatedFp::<Fr, T>::constant(T::one(), TestConstraintSystem::<Fr>::one());
+            let zero = one.sub(cs.ns(|| "1 - 1"), &one).unwrap();
+            assert_eq!(zero.get_value(), Some(T::zero()));
+
+            assert!(cs.is_satisfied());
+
+            // a wrong product coefficient
+            let path = "a * b/relation/product 0/coefficient 0";
+            let c = cs.get(path);
+            cs.set(path, c + Fr::one());
+            assert!(!cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_emulated_arithmetic() {
+        // 6 limbs
+        emulated_arithmetic::<Fq>();
+        // 4 limbs
+        emulated_arithmetic::<JubjubFr>();
+    }
+
+    #[test]
+    fn test_emulated_enforce_equal() {
+        let rng = &mut test_rng();
+        let (a, b) = (Fq::rand(rng), Fq::rand(rng));
+
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let var_a = EmulatedFp::<Fr, Fq>::alloc(cs.ns(|| "a"), Some(a)).unwrap();
+        let var_b = EmulatedFp::<Fr, Fq>::alloc(cs.ns(|| "b"), Some(b)).unwrap();
+        var_a.enforce_equal(cs.ns(|| "a = b"), &var_b).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_emulated_relation() {
+        let rng = &mut test_rng();
+        let (a, b, c) = (Fq::rand(rng), Fq::rand(rng), Fq::rand(rng));
+        // 2 a b - 3 c^2 + a - d = 0
+        let d = a * b + a * b - c * c - c * c - c * c + a;
+
+        let relation = |d: Fq| {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            let var_a = EmulatedFp::<Fr, Fq>::alloc(cs.ns(|| "a"), Some(a)).unwrap();
+            let var_b = EmulatedFp::<Fr, Fq>::alloc(cs.ns(|| "b"), Some(b)).unwrap();
+            let var_c = EmulatedFp::<Fr, Fq>::alloc(cs.ns(|| "c"), Some(c)).unwrap();
+            let var_d = EmulatedFp::<Fr, Fq>::alloc(cs.ns(|| "d"), Some(d)).unwrap();
+            EmulatedFp::enforce_relation(
+                cs.ns(|| "relation"),
+                &[(2, &var_a, &var_b), (-3, &var_c, &var_c)],
+                &[(1, &var_a), (-1, &var_d)],
+            )
+            .unwrap();
+            cs.is_satisfied()
+        };
+        assert!(relation(d));
+        assert!(!relation(d + Fq::one()));
+    }
+
+    #[test]
+    fn test_emulated_select_and_bits() {
+        let rng = &mut test_rng();
+        let (a, b) = (Fq::rand(rng), Fq::rand(rng));
+
+        for cond in [true, false].iter() {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            let var_a = EmulatedFp::<Fr, Fq>::alloc(cs.ns(|| "a"), Some(a)).unwrap();
+            let var_b = EmulatedFp::<Fr, Fq>::alloc(cs.ns(|| "b"), Some(b)).unwrap();
+            let bit = AllocatedBit::alloc(cs.ns(|| "cond"), Some(*cond)).unwrap();
+            let r = EmulatedFp::select(cs.ns(|| "select"), &bit.into(), &var_a, &var_b).unwrap();
+            let expected = if *cond { a } else { b };
+            assert_eq!(r.get_value(), Some(expected));
+
+            r.enforce_reduced(cs.ns(|| "reduced")).unwrap();
+            let bits = r.to_bits(cs.ns(|| "bits")).unwrap();
+            let repr = expected.into_repr();
+            for (i, bit) in bits.iter().enumerate() {
+                assert_eq!(bit.get_value(), Some(repr.get_bit(i)));
+            }
+            assert!(cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_emulated_enforce_reduced() {
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        // the limbs of 1 + p, a value of 1 which is not reduced
+        let limbs = big_add(&modulus::<Fq>(), &[1])
+            .iter()
+            .take(6)
+            .enumerate()
+            .map(|(i, l)| alloc_limb(cs.ns(|| format!("limb {}", i)), Some(*l)).unwrap())
+            .collect();
+        let one = EmulatedFp::<Fr, Fq> {
+            limbs,
+            value: Some(Fq::one()),
+        };
+        one.enforce_reduced(cs.ns(|| "reduced")).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_inverse_mod_2k() {
+        let p = modulus::<Fq>();
+        let inverse = inverse_mod_2k(&p, 7);
+        let mut one = vec![0u64; 7];
+        one[0] = 1;
+        assert_eq!(big_mul(&p, &inverse, 7), one);
+    }
+}
@@ -1,6 +1,8 @@
 use ark_ff::PrimeField;
 use zkp_r1cs::{ConstraintSystem, SynthesisError, Variable};
 
+use crate::algebra::witness_inverse;
+
 #[derive(Clone)]
 pub struct AllocatedFr<F: PrimeField> {
     value: Option<F>,
@@ -87,11 +89,7 @@ impl<F: PrimeField> AllocatedFr<F> {
             || "elhemeral inverse",
             || {
                 let tmp = self.value.ok_or(SynthesisError::AssignmentMissing)?;
-                if tmp == F::zero() {
-                    Err(SynthesisError::DivisionByZero)
-                } else {
-                    tmp.inverse().ok_or(SynthesisError::AssignmentMissing)
-                }
+                witness_inverse(&tmp).ok_or(SynthesisError::DivisionByZero)
             },
         )?;
 
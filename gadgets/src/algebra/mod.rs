@@ -1,3 +1,30 @@
+use ark_ff::PrimeField;
+
 pub mod boolean;
+pub mod edwards;
+pub mod emulated;
 pub mod fr;
 pub mod uint32;
+pub mod uint64;
+pub mod weierstrass;
+
+/// The inverse of a witness value, `None` for zero. With the
+/// `secret-hardened` feature it is `x^(p - 2)`, in time independent of `x`
+/// but for whether it is zero.
+pub(crate) fn witness_inverse<F: PrimeField>(x: &F) -> Option<F> {
+    #[cfg(feature = "secret-hardened")]
+    {
+        use ark_ff::{BigInteger, FpParameters};
+
+        let mut exp = F::Params::MODULUS;
+        exp.sub_noborrow(&F::BigInt::from(2));
+        let inverse = x.pow(exp);
+        if x.is_zero() {
+            None
+        } else {
+            Some(inverse)
+        }
+    }
+    #[cfg(not(feature = "secret-hardened"))]
+    x.inverse()
+}
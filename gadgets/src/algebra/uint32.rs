@@ -1,5 +1,5 @@
-use ark_ff::{Field, PrimeField};
-use zkp_r1cs::{ConstraintSystem, LinearCombination, SynthesisError};
+use ark_ff::{BigInteger, Field, PrimeField};
+use zkp_r1cs::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
 
 use crate::Vec;
 
@@ -63,6 +63,10 @@ impl UInt32 {
         Ok(UInt32 { bits, value })
     }
 
+    pub fn get_value(&self) -> Option<u32> {
+        self.value
+    }
+
     pub fn into_bits_be(mut self) -> Vec<Boolean> {
         self.bits.reverse();
         self.bits
@@ -174,6 +178,30 @@ impl UInt32 {
         }
     }
 
+    pub fn rotl(&self, by: usize) -> Self {
+        self.rotr(32 - by % 32)
+    }
+
+    pub fn shl(&self, by: usize) -> Self {
+        let by = by % 32;
+
+        let fill = Boolean::constant(false);
+
+        let new_bits = Some(&fill)
+            .into_iter()
+            .cycle()
+            .take(by) // The low bits are zeros
+            .chain(self.bits.iter())
+            .take(32) // The high bits are lost
+            .cloned()
+            .collect();
+
+        UInt32 {
+            bits: new_bits,
+            value: self.value.map(|v| v << by as u32),
+        }
+    }
+
     fn triop<F, CS, FN, U>(
         mut cs: CS,
         a: &Self,
@@ -367,6 +395,144 @@ impl UInt32 {
             value: modular_value,
         })
     }
+
+    /// Add `other` modulo 2^32, returning the sum and the carry out of the
+    /// top bit. The bits of the sum are allocated, which enforces its range.
+    pub fn add_with_carry<F, CS>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<(Self, Boolean), SynthesisError>
+    where
+        F: PrimeField,
+        CS: ConstraintSystem<F>,
+    {
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(u128::from(a) + u128::from(b)),
+            _ => None,
+        };
+
+        let lhs = bits_lc(&self.bits, CS::one()) + &bits_lc(&other.bits, CS::one());
+        let (mut bits, rhs) = alloc_bits(cs.ns(|| "sum"), value, 33)?;
+        cs.enforce(|| "a + b = sum", |_| lhs, |lc| lc + CS::one(), |_| rhs);
+
+        let carry = bits.pop().unwrap();
+        Ok((
+            UInt32 {
+                bits,
+                value: value.map(|v| v as u32),
+            },
+            carry,
+        ))
+    }
+
+    /// Multiply by `other`, returning the low and the high 32 bits of the
+    /// product. The bits of the product are allocated, which enforces its
+    /// range.
+    pub fn mul_wide<F, CS>(&self, mut cs: CS, other: &Self) -> Result<(Self, Self), SynthesisError>
+    where
+        F: PrimeField,
+        CS: ConstraintSystem<F>,
+    {
+        // The product must not wrap around the field.
+        assert!(F::size_in_bits() > 64);
+
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(u128::from(a) * u128::from(b)),
+            _ => None,
+        };
+
+        let a = bits_lc(&self.bits, CS::one());
+        let b = bits_lc(&other.bits, CS::one());
+        let (mut bits, product) = alloc_bits(cs.ns(|| "product"), value, 64)?;
+        cs.enforce(|| "a * b = product", |_| a, |_| b, |_| product);
+
+        let high = bits.split_off(32);
+        Ok((
+            UInt32 {
+                bits,
+                value: value.map(|v| v as u32),
+            },
+            UInt32 {
+                bits: high,
+                value: value.map(|v| (v >> 32) as u32),
+            },
+        ))
+    }
+
+    /// Multiply by `other` modulo 2^32.
+    pub fn mul<F, CS>(&self, cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        F: PrimeField,
+        CS: ConstraintSystem<F>,
+    {
+        Ok(self.mul_wide(cs, other)?.0)
+    }
+}
+
+/// `bits`, least significant first, as a linear combination.
+pub(crate) fn bits_lc<F: Field>(bits: &[Boolean], one: Variable) -> LinearCombination<F> {
+    let mut coeff = F::one();
+    let mut lc = LinearCombination::zero();
+    for bit in bits {
+        lc = lc + &bit.lc(one, coeff);
+        coeff.double_in_place();
+    }
+    lc
+}
+
+/// Allocate the `num_bits` low bits of `value`, least significant first,
+/// and return them with the linear combination of their sum.
+pub(crate) fn alloc_bits<F, CS>(
+    cs: CS,
+    value: Option<u128>,
+    num_bits: usize,
+) -> Result<(Vec<Boolean>, LinearCombination<F>), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    alloc_bits_with(cs, value.map(|v| move |i| (v >> i) & 1 == 1), num_bits)
+}
+
+/// `alloc_bits` for a value wider than 128 bits, e.g. the `into_repr` of a
+/// field element.
+pub(crate) fn alloc_repr_bits<F, CS, B>(
+    cs: CS,
+    value: Option<B>,
+    num_bits: usize,
+) -> Result<(Vec<Boolean>, LinearCombination<F>), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+    B: BigInteger,
+{
+    alloc_bits_with(cs, value.map(|r| move |i| r.get_bit(i)), num_bits)
+}
+
+fn alloc_bits_with<F, CS, B>(
+    mut cs: CS,
+    get_bit: Option<B>,
+    num_bits: usize,
+) -> Result<(Vec<Boolean>, LinearCombination<F>), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+    B: Fn(usize) -> bool,
+{
+    let mut coeff = F::one();
+    let mut lc = LinearCombination::zero();
+    let mut bits = Vec::with_capacity(num_bits);
+    for i in 0..num_bits {
+        let bit = AllocatedBit::alloc(
+            cs.ns(|| format!("bit {}", i)),
+            get_bit.as_ref().map(|get_bit| get_bit(i)),
+        )?;
+        lc += (coeff, bit.get_variable());
+        bits.push(Boolean::from(bit));
+        coeff.double_in_place();
+    }
+    Ok((bits, lc))
 }
 
 #[cfg(test)]
@@ -631,6 +797,92 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_uint32_shl_rotl() {
+        let rng = &mut test_rng();
+
+        for _ in 0..50 {
+            for i in 0..60 {
+                let num = rng.next_u32();
+                let a = UInt32::constant(num).shl(i);
+                let b = UInt32::constant(num.wrapping_shl(i as u32));
+                assert_eq!(a.value.unwrap(), num.wrapping_shl(i as u32));
+                for (a, b) in a.bits.iter().zip(b.bits.iter()) {
+                    assert_eq!(a.get_value().unwrap(), b.get_value().unwrap());
+                }
+
+                let a = UInt32::constant(num).rotl(i);
+                let b = UInt32::constant(num.rotate_left(i as u32));
+                assert_eq!(a.value.unwrap(), num.rotate_left(i as u32));
+                for (a, b) in a.bits.iter().zip(b.bits.iter()) {
+                    assert_eq!(a.get_value().unwrap(), b.get_value().unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_uint32_add_with_carry() {
+        let rng = &mut test_rng();
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+
+            let a = rng.next_u32();
+            let b = rng.next_u32();
+            let (expected, overflow) = a.overflowing_add(b);
+
+            let a_bit = UInt32::alloc(cs.ns(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt32::alloc(cs.ns(|| "b_bit"), Some(b)).unwrap();
+            let (r, carry) = a_bit.add_with_carry(cs.ns(|| "addition"), &b_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(r.get_value(), Some(expected));
+            assert_eq!(carry.get_value(), Some(overflow));
+            assert_eq!(UInt32::from_bits(&r.into_bits()).value, Some(expected));
+
+            // Flip a bit and see if the addition constraint still works
+            if cs.get("addition/sum/bit 0/boolean").is_zero() {
+                cs.set("addition/sum/bit 0/boolean", Fr::one());
+            } else {
+                cs.set("addition/sum/bit 0/boolean", Fr::zero());
+            }
+
+            assert!(!cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_uint32_mul() {
+        let rng = &mut test_rng();
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+
+            let a = rng.next_u32();
+            let b = rng.next_u32();
+            let expected = u64::from(a) * u64::from(b);
+
+            let a_bit = UInt32::alloc(cs.ns(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt32::constant(b);
+            let (low, high) = a_bit.mul_wide(cs.ns(|| "mul_wide"), &b_bit).unwrap();
+            let r = a_bit.mul(cs.ns(|| "mul"), &b_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(low.get_value(), Some(expected as u32));
+            assert_eq!(high.get_value(), Some((expected >> 32) as u32));
+            assert_eq!(r.get_value(), Some(a.wrapping_mul(b)));
+
+            if cs.get("mul/product/bit 63/boolean").is_zero() {
+                cs.set("mul/product/bit 63/boolean", Fr::one());
+            } else {
+                cs.set("mul/product/bit 63/boolean", Fr::zero());
+            }
+
+            assert!(!cs.is_satisfied());
+        }
+    }
+
     #[test]
     fn test_uint32_sha256_maj() {
         let rng = &mut test_rng();
@@ -0,0 +1,374 @@
+use ark_ff::{Field, PrimeField};
+use zkp_r1cs::{ConstraintSystem, SynthesisError};
+
+use crate::Vec;
+
+use crate::algebra::boolean::{AllocatedBit, Boolean};
+use crate::algebra::uint32::{alloc_bits, bits_lc};
+
+/// Represents an interpretation of 64 `Boolean` objects as an
+/// unsigned integer.
+#[derive(Clone)]
+pub struct UInt64 {
+    // Least significant bit first
+    bits: Vec<Boolean>,
+    value: Option<u64>,
+}
+
+impl UInt64 {
+    /// Construct a constant `UInt64` from a `u64`
+    pub fn constant(value: u64) -> Self {
+        let mut bits = Vec::with_capacity(64);
+        let mut tmp = value;
+        for _ in 0..64 {
+            bits.push(Boolean::constant(tmp & 1 == 1));
+            tmp >>= 1;
+        }
+
+        UInt64 {
+            bits,
+            value: Some(value),
+        }
+    }
+
+    /// Allocate a `UInt64` in the constraint system
+    pub fn alloc<E, CS>(mut cs: CS, value: Option<u64>) -> Result<Self, SynthesisError>
+    where
+        E: Field,
+        CS: ConstraintSystem<E>,
+    {
+        let values = match value {
+            Some(mut val) => {
+                let mut v = Vec::with_capacity(64);
+                for _ in 0..64 {
+                    v.push(Some(val & 1 == 1));
+                    val >>= 1;
+                }
+                v
+            }
+            None => vec![None; 64],
+        };
+
+        let bits = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| {
+                Ok(Boolean::from(AllocatedBit::alloc(
+                    cs.ns(|| format!("allocated bit {}", i)),
+                    v,
+                )?))
+            })
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+        Ok(UInt64 { bits, value })
+    }
+
+    pub fn get_value(&self) -> Option<u64> {
+        self.value
+    }
+
+    /// Turns this `UInt64` into its little-endian byte order representation.
+    pub fn into_bits(self) -> Vec<Boolean> {
+        self.bits
+    }
+
+    /// Converts a little-endian byte order representation of bits into a
+    /// `UInt64`.
+    pub fn from_bits(bits: &[Boolean]) -> Self {
+        assert_eq!(bits.len(), 64);
+
+        let mut value = Some(0u64);
+        for b in bits.iter().rev() {
+            value.as_mut().map(|v| *v <<= 1);
+
+            match b.get_value() {
+                Some(true) => {
+                    value.as_mut().map(|v| *v |= 1);
+                }
+                Some(false) => {}
+                None => value = None,
+            }
+        }
+
+        UInt64 {
+            value,
+            bits: bits.to_vec(),
+        }
+    }
+
+    pub fn rotr(&self, by: usize) -> Self {
+        let by = by % 64;
+
+        let new_bits = self
+            .bits
+            .iter()
+            .skip(by)
+            .chain(self.bits.iter())
+            .take(64)
+            .cloned()
+            .collect();
+
+        UInt64 {
+            bits: new_bits,
+            value: self.value.map(|v| v.rotate_right(by as u32)),
+        }
+    }
+
+    pub fn rotl(&self, by: usize) -> Self {
+        self.rotr(64 - by % 64)
+    }
+
+    pub fn shr(&self, by: usize) -> Self {
+        let by = by % 64;
+
+        let fill = Boolean::constant(false);
+
+        let new_bits = self
+            .bits
+            .iter() // The bits are least significant first
+            .skip(by) // Skip the bits that will be lost during the shift
+            .chain(Some(&fill).into_iter().cycle()) // Rest will be zeros
+            .take(64) // Only 64 bits needed!
+            .cloned()
+            .collect();
+
+        UInt64 {
+            bits: new_bits,
+            value: self.value.map(|v| v >> by as u32),
+        }
+    }
+
+    pub fn shl(&self, by: usize) -> Self {
+        let by = by % 64;
+
+        let fill = Boolean::constant(false);
+
+        let new_bits = Some(&fill)
+            .into_iter()
+            .cycle()
+            .take(by) // The low bits are zeros
+            .chain(self.bits.iter())
+            .take(64) // The high bits are lost
+            .cloned()
+            .collect();
+
+        UInt64 {
+            bits: new_bits,
+            value: self.value.map(|v| v << by as u32),
+        }
+    }
+
+    /// XOR this `UInt64` with another `UInt64`
+    pub fn xor<F, CS>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        F: Field,
+        CS: ConstraintSystem<F>,
+    {
+        let new_value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(a ^ b),
+            _ => None,
+        };
+
+        let bits = self
+            .bits
+            .iter()
+            .zip(other.bits.iter())
+            .enumerate()
+            .map(|(i, (a, b))| Boolean::xor(cs.ns(|| format!("xor of bit {}", i)), a, b))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UInt64 {
+            bits,
+            value: new_value,
+        })
+    }
+
+    /// Add `other` modulo 2^64, returning the sum and the carry out of the
+    /// top bit. The bits of the sum are allocated, which enforces its range.
+    pub fn add_with_carry<F, CS>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<(Self, Boolean), SynthesisError>
+    where
+        F: PrimeField,
+        CS: ConstraintSystem<F>,
+    {
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(u128::from(a) + u128::from(b)),
+            _ => None,
+        };
+
+        let lhs = bits_lc(&self.bits, CS::one()) + &bits_lc(&other.bits, CS::one());
+        let (mut bits, rhs) = alloc_bits(cs.ns(|| "sum"), value, 65)?;
+        cs.enforce(|| "a + b = sum", |_| lhs, |lc| lc + CS::one(), |_| rhs);
+
+        let carry = bits.pop().unwrap();
+        Ok((
+            UInt64 {
+                bits,
+                value: value.map(|v| v as u64),
+            },
+            carry,
+        ))
+    }
+
+    /// Multiply by `other`, returning the low and the high 64 bits of the
+    /// product. The bits of the product are allocated, which enforces its
+    /// range.
+    pub fn mul_wide<F, CS>(&self, mut cs: CS, other: &Self) -> Result<(Self, Self), SynthesisError>
+    where
+        F: PrimeField,
+        CS: ConstraintSystem<F>,
+    {
+        // The product must not wrap around the field.
+        assert!(F::size_in_bits() > 128);
+
+        let value = match (self.value, other.value) {
+            (Some(a), Some(b)) => Some(u128::from(a) * u128::from(b)),
+            _ => None,
+        };
+
+        let a = bits_lc(&self.bits, CS::one());
+        let b = bits_lc(&other.bits, CS::one());
+        let (mut bits, product) = alloc_bits(cs.ns(|| "product"), value, 128)?;
+        cs.enforce(|| "a * b = product", |_| a, |_| b, |_| product);
+
+        let high = bits.split_off(64);
+        Ok((
+            UInt64 {
+                bits,
+                value: value.map(|v| v as u64),
+            },
+            UInt64 {
+                bits: high,
+                value: value.map(|v| (v >> 64) as u64),
+            },
+        ))
+    }
+
+    /// Multiply by `other` modulo 2^64.
+    pub fn mul<F, CS>(&self, cs: CS, other: &Self) -> Result<Self, SynthesisError>
+    where
+        F: PrimeField,
+        CS: ConstraintSystem<F>,
+    {
+        Ok(self.mul_wide(cs, other)?.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, Zero};
+    use ark_std::test_rng;
+    use rand::prelude::*;
+    use zkp_r1cs::ConstraintSystem;
+
+    use super::UInt64;
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    #[test]
+    fn test_uint64_shifts() {
+        let rng = &mut test_rng();
+
+        for _ in 0..50 {
+            for i in 0..100 {
+                let num = rng.next_u64();
+                let a = UInt64::constant(num);
+                let cases = [
+                    (a.shr(i), num.wrapping_shr(i as u32)),
+                    (a.shl(i), num.wrapping_shl(i as u32)),
+                    (a.rotr(i), num.rotate_right(i as u32)),
+                    (a.rotl(i), num.rotate_left(i as u32)),
+                ];
+                for (a, expected) in cases.iter() {
+                    assert_eq!(a.value, Some(*expected));
+                    assert_eq!(UInt64::from_bits(&a.bits).value, Some(*expected));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_uint64_xor() {
+        let rng = &mut test_rng();
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+
+            let a = rng.next_u64();
+            let b = rng.next_u64();
+
+            let a_bit = UInt64::alloc(cs.ns(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt64::constant(b);
+            let r = a_bit.xor(cs.ns(|| "xor"), &b_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(r.value, Some(a ^ b));
+            assert_eq!(UInt64::from_bits(&r.bits).value, Some(a ^ b));
+        }
+    }
+
+    #[test]
+    fn test_uint64_add_with_carry() {
+        let rng = &mut test_rng();
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+
+            let a = rng.next_u64();
+            let b = rng.next_u64();
+            let (expected, overflow) = a.overflowing_add(b);
+
+            let a_bit = UInt64::alloc(cs.ns(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt64::alloc(cs.ns(|| "b_bit"), Some(b)).unwrap();
+            let (r, carry) = a_bit.add_with_carry(cs.ns(|| "addition"), &b_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(r.get_value(), Some(expected));
+            assert_eq!(carry.get_value(), Some(overflow));
+            assert_eq!(UInt64::from_bits(&r.into_bits()).value, Some(expected));
+
+            // Flip a bit and see if the addition constraint still works
+            if cs.get("addition/sum/bit 0/boolean").is_zero() {
+                cs.set("addition/sum/bit 0/boolean", Fr::one());
+            } else {
+                cs.set("addition/sum/bit 0/boolean", Fr::zero());
+            }
+
+            assert!(!cs.is_satisfied());
+        }
+    }
+
+    #[test]
+    fn test_uint64_mul() {
+        let rng = &mut test_rng();
+
+        for _ in 0..100 {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+
+            let a = rng.next_u64();
+            let b = rng.next_u64();
+            let expected = u128::from(a) * u128::from(b);
+
+            let a_bit = UInt64::alloc(cs.ns(|| "a_bit"), Some(a)).unwrap();
+            let b_bit = UInt64::alloc(cs.ns(|| "b_bit"), Some(b)).unwrap();
+            let (low, high) = a_bit.mul_wide(cs.ns(|| "mul_wide"), &b_bit).unwrap();
+            let r = a_bit.mul(cs.ns(|| "mul"), &b_bit).unwrap();
+
+            assert!(cs.is_satisfied());
+            assert_eq!(low.get_value(), Some(expected as u64));
+            assert_eq!(high.get_value(), Some((expected >> 64) as u64));
+            assert_eq!(r.get_value(), Some(a.wrapping_mul(b)));
+
+            if cs.get("mul/product/bit 127/boolean").is_zero() {
+                cs.set("mul/product/bit 127/boolean", Fr::one());
+            } else {
+                cs.set("mul/product/bit 127/boolean", Fr::zero());
+            }
+
+            assert!(!cs.is_satisfied());
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Short Weierstrass point gadgets over a curve whose base field is emulated
+//! in the circuit field (e.g. secp256k1 inside a BLS12-381 circuit).
+//!
+//! The affine formulas are incomplete: an addition enforces that the points
+//! have distinct `x`, and a doubling divides by `2 y`, which is not zero on a
+//! curve of odd order. The identity has no affine point, so a scalar
+//! multiplication starts from an offset point (see `ecdsa::constraints`).
+
+use ark_ec::{models::SWModelParameters, short_weierstrass_jacobian::GroupAffine};
+use ark_ff::{Field, One, PrimeField, Zero};
+use zkp_r1cs::{ConstraintSystem, SynthesisError, Variable};
+
+use crate::algebra::boolean::Boolean;
+use crate::algebra::emulated::EmulatedFp;
+use crate::algebra::witness_inverse;
+
+/// A point `(x, y)` on the curve `y^2 = x^3 + a * x + b`.
+pub struct WeierstrassPoint<F: PrimeField, P: SWModelParameters>
+where
+    P::BaseField: PrimeField,
+{
+    x: EmulatedFp<F, P::BaseField>,
+    y: EmulatedFp<F, P::BaseField>,
+    value: Option<GroupAffine<P>>,
+}
+
+impl<F: PrimeField, P: SWModelParameters> Clone for WeierstrassPoint<F, P>
+where
+    P::BaseField: PrimeField,
+{
+    fn clone(&self) -> Self {
+        WeierstrassPoint {
+            x: self.x.clone(),
+            y: self.y.clone(),
+            value: self.value,
+        }
+    }
+}
+
+impl<F: PrimeField, P: SWModelParameters> WeierstrassPoint<F, P>
+where
+    P::BaseField: PrimeField,
+{
+    pub fn get_x(&self) -> &EmulatedFp<F, P::BaseField> {
+        &self.x
+    }
+
+    pub fn get_y(&self) -> &EmulatedFp<F, P::BaseField> {
+        &self.y
+    }
+
+    pub fn get_value(&self) -> Option<GroupAffine<P>> {
+        self.value
+    }
+
+    /// Allocate a private point and enforce that it is on the curve.
+    pub fn alloc<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        value: Option<GroupAffine<P>>,
+    ) -> Result<Self, SynthesisError> {
+        let x = EmulatedFp::alloc(cs.ns(|| "x"), value.map(|p| p.x))?;
+        let y = EmulatedFp::alloc(cs.ns(|| "y"), value.map(|p| p.y))?;
+        let point = WeierstrassPoint { x, y, value };
+        point.enforce_on_curve(cs.ns(|| "on curve"))?;
+
+        Ok(point)
+    }
+
+    /// A constant point, as the constant limbs on `one`.
+    pub fn constant(point: GroupAffine<P>, one: Variable) -> Self {
+        WeierstrassPoint {
+            x: EmulatedFp::constant(point.x, one),
+            y: EmulatedFp::constant(point.y, one),
+            value: Some(point),
+        }
+    }
+
+    fn enforce_on_curve<CS: ConstraintSystem<F>>(&self, mut cs: CS) -> Result<(), SynthesisError> {
+        let x2 = self.x.mul(cs.ns(|| "x^2"), &self.x)?;
+        let a = EmulatedFp::constant(P::COEFF_A, CS::one());
+        let b = EmulatedFp::constant(P::COEFF_B, CS::one());
+
+        // y^2 - x^2 * x - a * x - b = 0
+        let mut products = vec![(1, &self.y, &self.y), (-1, &x2, &self.x)];
+        if !P::COEFF_A.is_zero() {
+            products.push((-1, &a, &self.x));
+        }
+        EmulatedFp::enforce_relation(cs.ns(|| "curve equation"), &products, &[(-1, &b)])
+    }
+
+    /// Incomplete affine addition, of points with distinct `x`.
+    pub fn add<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let values = match (self.value, other.value) {
+            (Some(p), Some(q)) => {
+                let w = witness_inverse(&(q.x - p.x)).ok_or(SynthesisError::DivisionByZero)?;
+                let lambda = (q.y - p.y) * w;
+                let x3 = lambda.square() - p.x - q.x;
+                let y3 = lambda * (p.x - x3) - p.y;
+                Some((w, lambda, GroupAffine::new(x3, y3, false)))
+            }
+            _ => None,
+        };
+
+        let w = EmulatedFp::alloc(cs.ns(|| "w"), values.map(|v| v.0))?;
+        let lambda = EmulatedFp::alloc(cs.ns(|| "lambda"), values.map(|v| v.1))?;
+        let value = values.map(|v| v.2);
+        let x3 = EmulatedFp::alloc(cs.ns(|| "x3"), value.map(|p| p.x))?;
+        let y3 = EmulatedFp::alloc(cs.ns(|| "y3"), value.map(|p| p.y))?;
+        let one = EmulatedFp::constant(P::BaseField::one(), CS::one());
+
+        // w * (x2 - x1) = 1
+        EmulatedFp::enforce_relation(
+            cs.ns(|| "distinct x"),
+            &[(1, &w, &other.x), (-1, &w, &self.x)],
+            &[(-1, &one)],
+        )?;
+        // lambda * (x2 - x1) = y2 - y1
+        EmulatedFp::enforce_relation(
+            cs.ns(|| "slope"),
+            &[(1, &lambda, &other.x), (-1, &lambda, &self.x)],
+            &[(-1, &other.y), (1, &self.y)],
+        )?;
+        Self::enforce_result(cs, &lambda, self, other, &x3, &y3)?;
+
+        Ok(WeierstrassPoint {
+            x: x3,
+            y: y3,
+            value,
+        })
+    }
+
+    /// Affine doubling, of a point with `y` not zero.
+    pub fn double<CS: ConstraintSystem<F>>(&self, mut cs: CS) -> Result<Self, SynthesisError> {
+        let values = match self.value {
+            Some(p) => {
+                let x2 = p.x.square();
+                let lambda = (x2.double() + x2 + P::COEFF_A)
+                    * witness_inverse(&p.y.double()).ok_or(SynthesisError::DivisionByZero)?;
+                let x3 = lambda.square() - p.x.double();
+                let y3 = lambda * (p.x - x3) - p.y;
+                Some((lambda, GroupAffine::new(x3, y3, false)))
+            }
+            None => None,
+        };
+
+        let lambda = EmulatedFp::alloc(cs.ns(|| "lambda"), values.map(|v| v.0))?;
+        let value = values.map(|v| v.1);
+        let x3 = EmulatedFp::alloc(cs.ns(|| "x3"), value.map(|p| p.x))?;
+        let y3 = EmulatedFp::alloc(cs.ns(|| "y3"), value.map(|p| p.y))?;
+        let a = EmulatedFp::constant(P::COEFF_A, CS::one());
+
+        // 2 * lambda * y = 3 * x^2 + a
+        EmulatedFp::enforce_relation(
+            cs.ns(|| "slope"),
+            &[(2, &lambda, &self.y), (-3, &self.x, &self.x)],
+            &[(-1, &a)],
+        )?;
+        Self::enforce_result(cs, &lambda, self, self, &x3, &y3)?;
+
+        Ok(WeierstrassPoint {
+            x: x3,
+            y: y3,
+            value,
+        })
+    }
+
+    /// Enforce `(x3, y3)`, the sum of `p` and `q` on the line of slope
+    /// `lambda`.
+    fn enforce_result<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        lambda: &EmulatedFp<F, P::BaseField>,
+        p: &Self,
+        q: &Self,
+        x3: &EmulatedFp<F, P::BaseField>,
+        y3: &EmulatedFp<F, P::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        // lambda^2 = x1 + x2 + x3
+        EmulatedFp::enforce_relation(
+            cs.ns(|| "x3 = lambda^2 - x1 - x2"),
+            &[(1, lambda, lambda)],
+            &[(-1, &p.x), (-1, &q.x), (-1, x3)],
+        )?;
+        // lambda * (x1 - x3) = y1 + y3
+        EmulatedFp::enforce_relation(
+            cs.ns(|| "y3 = lambda * (x1 - x3) - y1"),
+            &[(1, lambda, &p.x), (-1, lambda, x3)],
+            &[(-1, &p.y), (-1, y3)],
+        )
+    }
+
+    /// Returns `if_true` when `cond` holds, otherwise `if_false`.
+    pub fn select<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        cond: &Boolean,
+        if_true: &Self,
+        if_false: &Self,
+    ) -> Result<Self, SynthesisError> {
+        let value = match cond.get_value() {
+            Some(true) => if_true.value,
+            Some(false) => if_false.value,
+            None => None,
+        };
+        let x = EmulatedFp::select(cs.ns(|| "x"), cond, &if_true.x, &if_false.x)?;
+        let y = EmulatedFp::select(cs.ns(|| "y"), cond, &if_true.y, &if_false.y)?;
+
+        Ok(WeierstrassPoint { x, y, value })
+    }
+
+    pub fn enforce_equal<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        other: &Self,
+    ) -> Result<(), SynthesisError> {
+        self.x.enforce_equal(cs.ns(|| "x equal"), &other.x)?;
+        self.y.enforce_equal(cs.ns(|| "y equal"), &other.y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::{g1::Parameters, Fq, Fr, G1Affine};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{One, UniformRand};
+    use ark_std::test_rng;
+    use zkp_r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    type Point = WeierstrassPoint<Fr, Parameters>;
+
+    #[test]
+    fn test_weierstrass_add_and_double() {
+        let rng = &mut test_rng();
+        let g = G1Affine::prime_subgroup_generator();
+        let p = g.mul(Fr::rand(rng)).into_affine();
+        let q = g.mul(Fr::rand(rng)).into_affine();
+
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let a = Point::alloc(cs.ns(|| "p"), Some(p)).unwrap();
+        let b = Point::alloc(cs.ns(|| "q"), Some(q)).unwrap();
+        let sum = a.add(cs.ns(|| "p + q"), &b).unwrap();
+        let double = a.double(cs.ns(|| "2p")).unwrap();
+
+        let mut expected = p.into_projective();
+        expected.add_assign_mixed(&q);
+        assert_eq!(sum.get_value(), Some(expected.into_affine()));
+        assert_eq!(
+            double.get_value(),
+            Some(p.into_projective().double().into_affine())
+        );
+
+        let c = Point::constant(expected.into_affine(), TestConstraintSystem::<Fr>::one());
+        sum.enforce_equal(cs.ns(|| "p + q = c"), &c).unwrap();
+        assert!(cs.is_satisfied());
+
+        // p + p needs a doubling.
+        assert!(a.add(cs.ns(|| "p + p"), &a).is_err());
+    }
+
+    #[test]
+    fn test_weierstrass_on_curve() {
+        let rng = &mut test_rng();
+        let p = G1Affine::prime_subgroup_generator()
+            .mul(Fr::rand(rng))
+            .into_affine();
+        let not_on_curve = GroupAffine::new(p.x, p.y + Fq::one(), false);
+
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        Point::alloc(cs.ns(|| "p"), Some(p)).unwrap();
+        assert!(cs.is_satisfied());
+        Point::alloc(cs.ns(|| "not on curve"), Some(not_on_curve)).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+}
@@ -0,0 +1,174 @@
+use ark_ec::{models::TEModelParameters, twisted_edwards_extended::GroupAffine};
+use ark_ff::{BigInteger, PrimeField};
+use zkp_r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, LinearCombination, SynthesisError, Variable,
+};
+
+use crate::algebra::boolean::Boolean;
+use crate::algebra::uint32::alloc_repr_bits;
+use crate::algebra::edwards::EdwardsPoint;
+use crate::hashes::mimc;
+use crate::Vec;
+
+use super::{DisclosurePolicy, Signature};
+
+/// Proves knowledge of attributes signed by the public issuer key, revealing
+/// the attributes listed in the policy and proving its lower bounds.
+///
+/// Public inputs: issuer key `x, y`, then the disclosed attributes in policy order.
+pub struct CredentialCircuit<F: PrimeField, P: TEModelParameters<BaseField = F>> {
+    pub num_attributes: usize,
+    pub policy: DisclosurePolicy<F>,
+    pub issuer: Option<GroupAffine<P>>,
+    pub attributes: Option<Vec<F>>,
+    pub signature: Option<Signature<P>>,
+}
+
+impl<F: PrimeField, P: TEModelParameters<BaseField = F>> CredentialCircuit<F, P> {
+    /// A circuit without witness, used for parameter generation.
+    pub fn blank(num_attributes: usize, policy: DisclosurePolicy<F>) -> Self {
+        CredentialCircuit {
+            num_attributes,
+            policy,
+            issuer: None,
+            attributes: None,
+            signature: None,
+        }
+    }
+}
+
+/// Allocate the `num_bits` little-endian bits of `value` and enforce that they
+/// pack to `packed`.
+fn alloc_packed_bits<F, CS, B>(
+    mut cs: CS,
+    value: Option<B>,
+    num_bits: usize,
+    packed: LinearCombination<F>,
+) -> Result<Vec<Boolean>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+    B: BigInteger,
+{
+    let (bits, lc) = alloc_repr_bits(cs.ns(|| "bits"), value, num_bits)?;
+    cs.enforce(
+        || "packing constraint",
+        |_| lc,
+        |lc| lc + CS::one(),
+        |_| packed,
+    );
+
+    Ok(bits)
+}
+
+impl<F: PrimeField, P: TEModelParameters<BaseField = F>> ConstraintSynthesizer<F>
+    for CredentialCircuit<F, P>
+{
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        // the policy, e.g. of a blank circuit, may name attributes it has not.
+        let n = self.num_attributes;
+        if self.policy.disclosed.iter().any(|i| *i >= n)
+            || self.policy.lower_bounds.iter().any(|(i, _, _)| *i >= n)
+            || matches!(&self.attributes, Some(a) if a.len() != n)
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let pk = EdwardsPoint::<F, P>::alloc_input(cs.ns(|| "issuer"), self.issuer)?;
+
+        let mut attributes: Vec<(Variable, Option<F>)> = Vec::with_capacity(self.num_attributes);
+        for i in 0..self.num_attributes {
+            let value = self.attributes.as_ref().map(|a| a[i]);
+            let var = cs.alloc(
+                || format!("attribute {}", i),
+                || value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+            attributes.push((var, value));
+        }
+
+        for (j, i) in self.policy.disclosed.iter().enumerate() {
+            let (var, value) = attributes[*i];
+            let input = cs.alloc_input(
+                || format!("disclosed {}", j),
+                || value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+            cs.enforce(
+                || format!("disclosed {} = attribute {}", j, i),
+                |lc| lc + input - var,
+                |lc| lc + CS::one(),
+                |lc| lc,
+            );
+        }
+
+        // message = MiMC chain over the attributes, starting from zero.
+        let zero = cs.alloc(|| "zero", || Ok(F::zero()))?;
+        cs.enforce(
+            || "zero = 0",
+            |lc| lc + zero,
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+        let mut message = (zero, Some(F::zero()));
+        for (i, attribute) in attributes.iter().enumerate() {
+            message = mimc::block_enforce(cs.ns(|| format!("commit {}", i)), message, *attribute)?;
+        }
+
+        let r = EdwardsPoint::<F, P>::alloc(
+            cs.ns(|| "R"),
+            self.signature.as_ref().map(|s| s.r),
+        )?;
+
+        // c = H(R, pk, message)
+        let r_value = r.get_value();
+        let pk_value = pk.get_value();
+        let mut c = (zero, Some(F::zero()));
+        for (i, input) in [
+            (r.get_x(), r_value.map(|p| p.x)),
+            (r.get_y(), r_value.map(|p| p.y)),
+            (pk.get_x(), pk_value.map(|p| p.x)),
+            (pk.get_y(), pk_value.map(|p| p.y)),
+            message,
+        ]
+        .iter()
+        .enumerate()
+        {
+            c = mimc::block_enforce(cs.ns(|| format!("challenge {}", i)), c, *input)?;
+        }
+
+        let c_bits = alloc_packed_bits(
+            cs.ns(|| "challenge bits"),
+            c.1.map(|c| c.into_repr()),
+            F::size_in_bits(),
+            LinearCombination::<F>::zero() + c.0,
+        )?;
+
+        let (s_bits, _) = alloc_repr_bits(
+            cs.ns(|| "s bits"),
+            self.signature.as_ref().map(|s| s.s.into_repr()),
+            P::ScalarField::size_in_bits(),
+        )?;
+
+        // s * G = R + c * pk
+        let g = EdwardsPoint::<F, P>::generator(cs.ns(|| "generator"))?;
+        let lhs = g.mul_bits_le(cs.ns(|| "s * G"), &s_bits)?;
+        let c_pk = pk.mul_bits_le(cs.ns(|| "c * pk"), &c_bits)?;
+        let rhs = r.add(cs.ns(|| "R + c * pk"), &c_pk)?;
+        lhs.enforce_equal(cs.ns(|| "signature equation"), &rhs)?;
+
+        // attribute - minimum = sum(2^k * b_k)
+        for (j, (i, minimum, num_bits)) in self.policy.lower_bounds.iter().enumerate() {
+            let (var, value) = attributes[*i];
+            alloc_packed_bits(
+                cs.ns(|| format!("lower bound {}", j)),
+                value.map(|v| (v - minimum).into_repr()),
+                *num_bits,
+                LinearCombination::<F>::zero() + var - (*minimum, CS::one()),
+            )?;
+        }
+
+        Ok(())
+    }
+}
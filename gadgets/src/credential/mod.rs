@@ -0,0 +1,195 @@
+//! Selective-disclosure credentials.
+//!
+//! An issuer signs a MiMC commitment to an attribute list with a Schnorr
+//! signature (see `schnorr`) over an embedded twisted Edwards curve (e.g.
+//! Jubjub). The holder later proves possession of a valid credential for a
+//! known issuer while revealing only the chosen attributes, optionally
+//! proving lower bounds (e.g. `age >= 18`) on hidden ones.
+
+use ark_ec::{models::TEModelParameters, twisted_edwards_extended::GroupAffine};
+use ark_ff::{BigInteger, PrimeField, UniformRand};
+use rand::Rng;
+
+use crate::hashes::mimc;
+use crate::schnorr;
+use crate::Vec;
+
+mod circuit;
+pub use circuit::CredentialCircuit;
+pub use schnorr::Signature;
+
+/// Which attributes the holder reveals, and which lower bounds it proves on
+/// the hidden ones. The policy is agreed on by holder and verifier.
+#[derive(Clone, Debug, Default)]
+pub struct DisclosurePolicy<F: PrimeField> {
+    /// Indexes of the attributes revealed as public inputs, in order.
+    pub disclosed: Vec<usize>,
+    /// `(index, minimum, num_bits)`: proves `minimum <= attribute < minimum + 2^num_bits`.
+    pub lower_bounds: Vec<(usize, F, usize)>,
+}
+
+/// Commit to an attribute list with a MiMC chain `h_{i+1} = E(h_i, a_i)`.
+pub fn commit_attributes<F: PrimeField>(attributes: &[F]) -> F {
+    attributes
+        .iter()
+        .fold(F::zero(), |h, a| mimc::block(h, *a))
+}
+
+/// The credential issuer, holding the signing key.
+pub struct Issuer<F: PrimeField, P: TEModelParameters<BaseField = F>> {
+    sk: P::ScalarField,
+    pk: GroupAffine<P>,
+}
+
+impl<F: PrimeField, P: TEModelParameters<BaseField = F>> Issuer<F, P> {
+    pub fn new<R: Rng>(rng: &mut R) -> Self {
+        let sk = P::ScalarField::rand(rng);
+        let pk = schnorr::public_key(&sk);
+        Issuer { sk, pk }
+    }
+
+    pub fn public_key(&self) -> GroupAffine<P> {
+        self.pk
+    }
+
+    /// Sign the commitment of `attributes` and hand the credential to the holder.
+    pub fn issue<R: Rng>(&self, attributes: Vec<F>, rng: &mut R) -> Credential<F, P> {
+        let message = commit_attributes(&attributes);
+        let signature = schnorr::sign(&self.sk, message, rng);
+
+        Credential {
+            attributes,
+            issuer: self.pk,
+            signature,
+        }
+    }
+}
+
+/// A credential as held by its owner.
+pub struct Credential<F: PrimeField, P: TEModelParameters<BaseField = F>> {
+    pub attributes: Vec<F>,
+    pub issuer: GroupAffine<P>,
+    pub signature: Signature<P>,
+}
+
+impl<F: PrimeField, P: TEModelParameters<BaseField = F>> Credential<F, P> {
+    /// Natively check the issuer signature over the attributes.
+    pub fn verify(&self) -> bool {
+        let message = commit_attributes(&self.attributes);
+        schnorr::verify(&self.issuer, message, &self.signature)
+    }
+
+    /// Build the proving circuit for `policy` and the public inputs a verifier
+    /// expects: the issuer key `(x, y)` followed by the disclosed attributes.
+    ///
+    /// Returns `None` if the policy references a missing attribute or a lower
+    /// bound does not hold.
+    pub fn present(&self, policy: &DisclosurePolicy<F>) -> Option<(CredentialCircuit<F, P>, Vec<F>)> {
+        let n = self.attributes.len();
+        if policy.disclosed.iter().any(|i| *i >= n) {
+            return None;
+        }
+        for (i, min, num_bits) in &policy.lower_bounds {
+            let diff = *self.attributes.get(*i)? - min;
+            let bits = diff.into_repr();
+            if bits.num_bits() as usize > *num_bits {
+                return None;
+            }
+        }
+
+        let mut publics = vec![self.issuer.x, self.issuer.y];
+        publics.extend(policy.disclosed.iter().map(|i| self.attributes[*i]));
+
+        let circuit = CredentialCircuit {
+            num_attributes: n,
+            policy: policy.clone(),
+            issuer: Some(self.issuer),
+            attributes: Some(self.attributes.clone()),
+            signature: Some(self.signature.clone()),
+        };
+
+        Some((circuit, publics))
+    }
+}
+
+/// The public inputs a verifier must supply for a presentation.
+pub fn public_inputs<F: PrimeField, P: TEModelParameters<BaseField = F>>(
+    issuer: &GroupAffine<P>,
+    disclosed_values: &[F],
+) -> Vec<F> {
+    let mut publics = vec![issuer.x, issuer.y];
+    publics.extend_from_slice(disclosed_values);
+    publics
+}
+
+#[cfg(test)]
+mod test {
+    use ark_ed_on_bls12_381::{EdwardsParameters, Fq};
+    use ark_std::test_rng;
+    use zkp_r1cs::ConstraintSynthesizer;
+
+    use super::*;
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    fn attributes() -> Vec<Fq> {
+        // [user id, age, country code]
+        vec![Fq::from(1234u32), Fq::from(27u32), Fq::from(86u32)]
+    }
+
+    fn policy() -> DisclosurePolicy<Fq> {
+        DisclosurePolicy {
+            disclosed: vec![2],
+            lower_bounds: vec![(1, Fq::from(18u32), 8)],
+        }
+    }
+
+    #[test]
+    fn test_credential_presentation() {
+        let rng = &mut test_rng();
+        let issuer = Issuer::<Fq, EdwardsParameters>::new(rng);
+        let credential = issuer.issue(attributes(), rng);
+        assert!(credential.verify());
+
+        let (circuit, publics) = credential.present(&policy()).unwrap();
+        assert_eq!(
+            publics,
+            public_inputs(&issuer.public_key(), &[Fq::from(86u32)])
+        );
+
+        let mut cs = TestConstraintSystem::<Fq>::new();
+        circuit.generate_constraints(&mut cs).unwrap();
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_credential_tampered() {
+        let rng = &mut test_rng();
+        let issuer = Issuer::<Fq, EdwardsParameters>::new(rng);
+        let mut credential = issuer.issue(attributes(), rng);
+        credential.attributes[1] = Fq::from(30u32);
+        assert!(!credential.verify());
+
+        let (circuit, _) = credential.present(&policy()).unwrap();
+        let mut cs = TestConstraintSystem::<Fq>::new();
+        circuit.generate_constraints(&mut cs).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_credential_lower_bound_not_met() {
+        let rng = &mut test_rng();
+        let issuer = Issuer::<Fq, EdwardsParameters>::new(rng);
+        let mut attrs = attributes();
+        attrs[1] = Fq::from(16u32);
+        let credential = issuer.issue(attrs, rng);
+
+        assert!(credential.present(&policy()).is_none());
+    }
+
+    #[test]
+    fn test_credential_blank_policy_out_of_range() {
+        let mut cs = TestConstraintSystem::<Fq>::new();
+        let circuit = CredentialCircuit::<Fq, EdwardsParameters>::blank(2, policy());
+        assert!(circuit.generate_constraints(&mut cs).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! ECDSA verification in a circuit, over the base and the scalar fields of
+//! the curve emulated in the circuit field.
+//!
+//! The public key, the message hash and the signature are all witnesses, so
+//! a proof attests to a signature without revealing the message or the key:
+//! the caller reveals what it needs of them, e.g. a commitment to the key or
+//! its membership in a Merkle tree.
+//!
+//! `R = u1 G + u2 Q` is computed by a joint double-and-add over the bits of
+//! `u1 = z / s` and `u2 = r / s`, adding `G`, `Q` or `G + Q`. The affine
+//! formulas are incomplete, so the sum starts from a point `H` of unknown
+//! discrete logarithm, which keeps it away from the identity and the added
+//! points, and `2^len H` is subtracted at the end.
+
+use ark_ec::{
+    models::SWModelParameters, short_weierstrass_jacobian::GroupAffine, AffineCurve,
+    ProjectiveCurve,
+};
+use ark_ff::{Field, FpParameters, One, PrimeField, SquareRootField};
+use zkp_r1cs::{ConstraintSystem, LinearCombination, SynthesisError};
+
+use crate::algebra::boolean::{AllocatedBit, Boolean};
+use crate::algebra::emulated::{enforce_carries, EmulatedFp};
+use crate::algebra::weierstrass::WeierstrassPoint;
+use crate::Vec;
+
+use super::x_to_scalar;
+
+/// Enforce that `(r, s)` is a signature of the message hash `z` for
+/// `public_key`.
+pub fn enforce_verify<F, P, CS>(
+    mut cs: CS,
+    public_key: &WeierstrassPoint<F, P>,
+    z: &EmulatedFp<F, P::ScalarField>,
+    r: &EmulatedFp<F, P::ScalarField>,
+    s: &EmulatedFp<F, P::ScalarField>,
+) -> Result<(), SynthesisError>
+where
+    F: PrimeField,
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    // r and s are in [1, n)
+    r.enforce_reduced(cs.ns(|| "r reduced"))?;
+    r.inverse(cs.ns(|| "inverse of r"))?;
+    let w = s.inverse(cs.ns(|| "inverse of s"))?;
+
+    let u1 = z.mul(cs.ns(|| "u1"), &w)?;
+    let u2 = r.mul(cs.ns(|| "u2"), &w)?;
+    let u1_bits = u1.to_bits(cs.ns(|| "u1 bits"))?;
+    let u2_bits = u2.to_bits(cs.ns(|| "u2 bits"))?;
+
+    let generator = GroupAffine::<P>::prime_subgroup_generator();
+    let g = WeierstrassPoint::constant(generator, CS::one());
+    let g_plus_q = g.add(cs.ns(|| "G + Q"), public_key)?;
+
+    let offset = offset_point::<P>();
+    let mut acc = WeierstrassPoint::constant(offset, CS::one());
+    for (i, (b1, b2)) in u1_bits.iter().zip(u2_bits.iter()).enumerate().rev() {
+        acc = acc.double(cs.ns(|| format!("double {}", i)))?;
+
+        // b1 ? (b2 ? G + Q : G) : Q, added unless both bits are zero
+        let t = WeierstrassPoint::select(cs.ns(|| format!("G or G + Q {}", i)), b2, &g_plus_q, &g)?;
+        let t = WeierstrassPoint::select(cs.ns(|| format!("addend {}", i)), b1, &t, public_key)?;
+        let sum = acc.add(cs.ns(|| format!("add {}", i)), &t)?;
+        let neither = Boolean::and(cs.ns(|| format!("neither {}", i)), &b1.not(), &b2.not())?;
+        acc = WeierstrassPoint::select(cs.ns(|| format!("select {}", i)), &neither, &acc, &sum)?;
+    }
+
+    // R = acc - 2^len H, which is not the identity as the addition enforces
+    // distinct x.
+    let shift = P::ScalarField::from(2u64).pow(&[u1_bits.len() as u64]);
+    let correction = -offset.mul(shift.into_repr()).into_affine();
+    let point = acc.add(
+        cs.ns(|| "R"),
+        &WeierstrassPoint::constant(correction, CS::one()),
+    )?;
+
+    enforce_x_mod_n::<_, P, _>(cs.ns(|| "x of R = r mod n"), point.get_x(), r)
+}
+
+/// Enforce `x = r mod n`, for `r` reduced, as the integer identity
+/// `x = r + k n` with a bit `k`, as `x < p < 2n` once reduced.
+fn enforce_x_mod_n<F, P, CS>(
+    mut cs: CS,
+    x: &EmulatedFp<F, P::BaseField>,
+    r: &EmulatedFp<F, P::ScalarField>,
+) -> Result<(), SynthesisError>
+where
+    F: PrimeField,
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    // p < 2^|n| <= 2n
+    assert!(
+        x.get_limbs().len() == r.get_limbs().len()
+            && P::BaseField::size_in_bits() <= P::ScalarField::size_in_bits(),
+        "base field too large for the scalar field"
+    );
+    let n = <P::ScalarField as PrimeField>::Params::MODULUS;
+    let n = n.as_ref();
+
+    x.enforce_reduced(cs.ns(|| "x reduced"))?;
+
+    let x_repr = x.get_value().map(|x| x.into_repr());
+    let r_repr = r.get_value().map(|r| r.into_repr());
+    let k_value = x
+        .get_value()
+        .map(|x| x_to_scalar::<P>(&x).into_repr().as_ref() != x.into_repr().as_ref());
+    let k = AllocatedBit::alloc(cs.ns(|| "k"), k_value)?;
+
+    // x - r - k n, as a polynomial in 2^64
+    let e = x
+        .get_limbs()
+        .iter()
+        .zip(r.get_limbs().iter())
+        .zip(n.iter())
+        .map(|((x, r), n)| x.clone() - r - (F::from(*n), k.get_variable()))
+        .collect::<Vec<LinearCombination<F>>>();
+    let e_values = match (x_repr, r_repr, k_value) {
+        (Some(x), Some(r), Some(k)) => Some(
+            x.as_ref()
+                .iter()
+                .zip(r.as_ref().iter())
+                .zip(n.iter())
+                .map(|((x, r), n)| F::from(*x) - F::from(*r) - F::from(*n * k as u64))
+                .collect(),
+        ),
+        _ => None,
+    };
+
+    enforce_carries(cs.ns(|| "carries"), e, e_values)
+}
+
+/// The point of the least `x = 1, 2, ..` on the curve, whose discrete
+/// logarithm is unknown.
+fn offset_point<P: SWModelParameters>() -> GroupAffine<P> {
+    assert_eq!(P::COFACTOR, &[1], "curve of composite order");
+
+    let mut x = P::BaseField::one();
+    loop {
+        let y2 = x.square() * x + P::COEFF_A * x + P::COEFF_B;
+        if let Some(y) = y2.sqrt() {
+            return GroupAffine::new(x, y, false);
+        }
+        x += P::BaseField::one();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, UniformRand};
+    use ark_std::test_rng;
+    use zkp_r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::ecdsa::secp256k1::{Fr as Scalar, Secp256k1Parameters};
+    use crate::ecdsa::{public_key, sign, Signature};
+    use crate::test_constraint_system::CheckingConstraintSystem;
+
+    type Affine = GroupAffine<Secp256k1Parameters>;
+
+    // The two scalar multiplications take some two million constraints, too
+    // many to keep by name, so they are checked as they are enforced.
+    fn verify(pk: Affine, z: Scalar, r: Scalar, s: Scalar) -> bool {
+        let mut cs = CheckingConstraintSystem::<Fr>::new();
+        let var_pk = WeierstrassPoint::alloc(cs.ns(|| "public key"), Some(pk)).unwrap();
+        let var_z = EmulatedFp::alloc(cs.ns(|| "z"), Some(z)).unwrap();
+        let var_r = EmulatedFp::alloc(cs.ns(|| "r"), Some(r)).unwrap();
+        let var_s = EmulatedFp::alloc(cs.ns(|| "s"), Some(s)).unwrap();
+        enforce_verify(cs.ns(|| "verify"), &var_pk, &var_z, &var_r, &var_s).unwrap();
+        cs.is_satisfied()
+    }
+
+    fn signed() -> (Affine, Scalar, Signature<Secp256k1Parameters>) {
+        let rng = &mut test_rng();
+        let secret_key = Scalar::rand(rng);
+        let z = Scalar::rand(rng);
+        let signature = sign::<Secp256k1Parameters, _>(&secret_key, &z, rng);
+        (public_key(&secret_key), z, signature)
+    }
+
+    #[test]
+    fn test_ecdsa_verify() {
+        let (pk, z, signature) = signed();
+        assert!(verify(pk, z, signature.r, signature.s));
+        assert!(!verify(pk, z + Scalar::one(), signature.r, signature.s));
+    }
+
+    #[test]
+    fn test_ecdsa_verify_tampered_signature() {
+        let (pk, z, signature) = signed();
+        assert!(!verify(pk, z, signature.r + Scalar::one(), signature.s));
+        assert!(!verify(pk, z, signature.r, signature.s + Scalar::one()));
+    }
+}
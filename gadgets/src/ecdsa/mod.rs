@@ -0,0 +1,146 @@
+//! ECDSA signatures over a short Weierstrass curve of prime order, e.g. the
+//! secp256k1 signatures of bitcoin and ethereum wallets, and their
+//! verification in a circuit (see `constraints`).
+//!
+//! A message is signed as its hash `z`, already taken to a scalar, e.g. by
+//! `from_be_bytes_mod_order` of a SHA-256 or Keccak-256 digest.
+
+use ark_ec::{
+    models::SWModelParameters, short_weierstrass_jacobian::GroupAffine, AffineCurve,
+    ProjectiveCurve,
+};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use rand::Rng;
+
+use crate::algebra::witness_inverse;
+
+pub mod constraints;
+pub mod secp256k1;
+
+/// A signature `(r, s)`, with `r` the `x` of `k G` modulo `n` and
+/// `s = (z + r d) / k` for the secret key `d`.
+pub struct Signature<P: SWModelParameters> {
+    pub r: P::ScalarField,
+    pub s: P::ScalarField,
+}
+
+impl<P: SWModelParameters> Clone for Signature<P> {
+    fn clone(&self) -> Self {
+        Signature {
+            r: self.r,
+            s: self.s,
+        }
+    }
+}
+
+/// The `x` of a point, as an integer modulo `n`.
+pub fn x_to_scalar<P: SWModelParameters>(x: &P::BaseField) -> P::ScalarField
+where
+    P::BaseField: PrimeField,
+{
+    let shift = P::ScalarField::from(2u64).pow(&[64]);
+    x.into_repr()
+        .as_ref()
+        .iter()
+        .rev()
+        .fold(P::ScalarField::zero(), |acc, limb| {
+            acc * shift + P::ScalarField::from(*limb)
+        })
+}
+
+pub fn public_key<P: SWModelParameters>(secret_key: &P::ScalarField) -> GroupAffine<P> {
+    GroupAffine::<P>::prime_subgroup_generator()
+        .mul(secret_key.into_repr())
+        .into_affine()
+}
+
+pub fn sign<P, R>(secret_key: &P::ScalarField, z: &P::ScalarField, rng: &mut R) -> Signature<P>
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+    R: Rng,
+{
+    loop {
+        let k = P::ScalarField::rand(rng);
+        if k.is_zero() {
+            continue;
+        }
+        let r = x_to_scalar::<P>(&public_key::<P>(&k).x);
+        let s = (*z + r * secret_key) * witness_inverse(&k).unwrap();
+        if !r.is_zero() && !s.is_zero() {
+            return Signature { r, s };
+        }
+    }
+}
+
+/// Whether `signature` of `z` is valid for `public_key`, i.e. the `x` of
+/// `R = (z / s) G + (r / s) Q` is `r` modulo `n`.
+pub fn verify<P>(public_key: &GroupAffine<P>, z: &P::ScalarField, signature: &Signature<P>) -> bool
+where
+    P: SWModelParameters,
+    P::BaseField: PrimeField,
+{
+    let w = match signature.s.inverse() {
+        Some(w) => w,
+        None => return false,
+    };
+    if signature.r.is_zero() {
+        return false;
+    }
+
+    let u1 = *z * w;
+    let u2 = signature.r * w;
+    let point = (GroupAffine::<P>::prime_subgroup_generator().mul(u1.into_repr())
+        + &public_key.mul(u2.into_repr()))
+        .into_affine();
+    !point.is_zero() && x_to_scalar::<P>(&point.x) == signature.r
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::{field_new, One};
+    use ark_std::test_rng;
+
+    use super::secp256k1::{Fq, Fr, Secp256k1Parameters};
+    use super::*;
+
+    #[test]
+    fn test_ecdsa() {
+        let rng = &mut test_rng();
+        let secret_key = Fr::rand(rng);
+        let pk = public_key::<Secp256k1Parameters>(&secret_key);
+        let z = Fr::rand(rng);
+
+        let signature = sign::<Secp256k1Parameters, _>(&secret_key, &z, rng);
+        assert!(verify(&pk, &z, &signature));
+        assert!(!verify(&pk, &(z + Fr::one()), &signature));
+
+        let other = public_key::<Secp256k1Parameters>(&(secret_key + Fr::one()));
+        assert!(!verify(&other, &z, &signature));
+
+        let mut forged = signature.clone();
+        forged.s += Fr::one();
+        assert!(!verify(&pk, &z, &forged));
+    }
+
+    #[test]
+    fn test_secp256k1_public_key() {
+        // 2 G
+        let pk = public_key::<Secp256k1Parameters>(&Fr::from(2u64));
+        assert!(pk.is_on_curve());
+        assert_eq!(
+            pk.x,
+            field_new!(
+                Fq,
+                "89565891926547004231252920425935692360644145829622209833684329913297188986597"
+            )
+        );
+        assert_eq!(
+            pk.y,
+            field_new!(
+                Fq,
+                "12158399299693830322967808612713398636155367887041628176798871954788371653930"
+            )
+        );
+    }
+}
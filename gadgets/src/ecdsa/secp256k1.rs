@@ -0,0 +1,261 @@
+//! The secp256k1 curve `y^2 = x^3 + 7` of bitcoin and ethereum, over
+//! `p = 2^256 - 2^32 - 977`, of prime order `n`.
+//!
+//! Both moduli take all 256 bits, while the additions of `Fp256` do not carry
+//! out of its top limb, so the fields are backed by five limbs instead.
+
+use ark_ec::{
+    models::{ModelParameters, SWModelParameters},
+    short_weierstrass_jacobian::{GroupAffine, GroupProjective},
+};
+use ark_ff::{
+    biginteger::BigInteger320 as BigInteger,
+    field_new,
+    fields::{FftParameters, Fp320, Fp320Parameters, FpParameters},
+};
+
+pub type Fq = Fp320<FqParameters>;
+
+pub type Fr = Fp320<FrParameters>;
+
+pub type Affine = GroupAffine<Secp256k1Parameters>;
+
+pub type Projective = GroupProjective<Secp256k1Parameters>;
+
+pub struct FqParameters;
+
+impl Fp320Parameters for FqParameters {}
+
+impl FftParameters for FqParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 1;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0xfffffffefffffc2f,
+        0xfffffffefffffc2e,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x0000000000000000,
+    ]);
+}
+
+impl FpParameters for FqParameters {
+    /// p = 2^256 - 2^32 - 977
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0xfffffffefffffc2f,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x0000000000000000,
+    ]);
+
+    const MODULUS_BITS: u32 = 256;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    /// At most the top limb is shaved, see `Fp320::rand`.
+    const REPR_SHAVE_BITS: u32 = 63;
+
+    /// R = 2^320 mod p
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([
+        0x0000000000000000,
+        0x00000001000003d1,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+    ]);
+
+    /// R^2 = 2^640 mod p
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([
+        0x0000000000000000,
+        0x0000000000000000,
+        0x000007a2000e90a1,
+        0x0000000000000001,
+        0x0000000000000000,
+    ]);
+
+    /// INV = -(p^{-1} mod 2^64) mod 2^64
+    const INV: u64 = 0xd838091dd2253531;
+
+    /// GENERATOR = 3
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([
+        0x0000000000000000,
+        0x0000000300000b73,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+    ]);
+
+    /// (Self::MODULUS - 1) / 2
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xffffffff7ffffe17,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x7fffffffffffffff,
+        0x0000000000000000,
+    ]);
+
+    /// t for 2^s * t = MODULUS - 1
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([
+        0xffffffff7ffffe17,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x7fffffffffffffff,
+        0x0000000000000000,
+    ]);
+
+    /// (t - 1) / 2
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xffffffffbfffff0b,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+        0x0000000000000000,
+    ]);
+}
+
+pub struct FrParameters;
+
+impl Fp320Parameters for FrParameters {}
+
+impl FftParameters for FrParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 6;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0x0112cb0f605a214a,
+        0x92225daffb794500,
+        0x7e42003a6ccb6212,
+        0x55980b07bc222114,
+        0x0000000000000000,
+    ]);
+}
+
+impl FpParameters for FrParameters {
+    /// n = 2^256 - 432420386565659656852420866390673177327
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0xbfd25e8cd0364141,
+        0xbaaedce6af48a03b,
+        0xfffffffffffffffe,
+        0xffffffffffffffff,
+        0x0000000000000000,
+    ]);
+
+    const MODULUS_BITS: u32 = 256;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    /// At most the top limb is shaved, see `Fp320::rand`.
+    const REPR_SHAVE_BITS: u32 = 63;
+
+    /// R = 2^320 mod n
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([
+        0x0000000000000000,
+        0x402da1732fc9bebf,
+        0x4551231950b75fc4,
+        0x0000000000000001,
+        0x0000000000000000,
+    ]);
+
+    /// R^2 = 2^640 mod n
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([
+        0x1e004f504dfd7f79,
+        0x08fcf59774a052ea,
+        0x27c4120fc94e1653,
+        0x3c1a6191e5702644,
+        0x0000000000000000,
+    ]);
+
+    /// INV = -(n^{-1} mod 2^64) mod 2^64
+    const INV: u64 = 0x4b0dff665588b13f;
+
+    /// GENERATOR = 7
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([
+        0x0000000000000000,
+        0xc13f6a264e843739,
+        0xe537f5b135039e5d,
+        0x0000000000000008,
+        0x0000000000000000,
+    ]);
+
+    /// (Self::MODULUS - 1) / 2
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xdfe92f46681b20a0,
+        0x5d576e7357a4501d,
+        0xffffffffffffffff,
+        0x7fffffffffffffff,
+        0x0000000000000000,
+    ]);
+
+    /// t for 2^s * t = MODULUS - 1
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([
+        0xeeff497a3340d905,
+        0xfaeabb739abd2280,
+        0xffffffffffffffff,
+        0x03ffffffffffffff,
+        0x0000000000000000,
+    ]);
+
+    /// (t - 1) / 2
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0x777fa4bd19a06c82,
+        0xfd755db9cd5e9140,
+        0xffffffffffffffff,
+        0x01ffffffffffffff,
+        0x0000000000000000,
+    ]);
+}
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secp256k1Parameters;
+
+impl ModelParameters for Secp256k1Parameters {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+}
+
+impl SWModelParameters for Secp256k1Parameters {
+    /// COEFF_A = 0
+    const COEFF_A: Fq = field_new!(Fq, "0");
+
+    /// COEFF_B = 7
+    const COEFF_B: Fq = field_new!(Fq, "7");
+
+    /// COFACTOR = 1
+    const COFACTOR: &'static [u64] = &[0x1];
+
+    /// COFACTOR_INV = 1
+    const COFACTOR_INV: Fr = field_new!(Fr, "1");
+
+    /// AFFINE_GENERATOR_COEFFS = (G_GENERATOR_X, G_GENERATOR_Y)
+    const AFFINE_GENERATOR_COEFFS: (Self::BaseField, Self::BaseField) =
+        (G_GENERATOR_X, G_GENERATOR_Y);
+}
+
+pub const G_GENERATOR_X: Fq = field_new!(
+    Fq,
+    "55066263022277343669578718895168534326250603453777594175500187360389116729240"
+);
+
+pub const G_GENERATOR_Y: Fq = field_new!(
+    Fq,
+    "32670510020758816978083085130507043184471273380659243275938904335757337482424"
+);
@@ -0,0 +1,200 @@
+//! A common interface of the hashes over the field of the circuit: MiMC,
+//! Poseidon and Rescue-Prime.
+//!
+//! A circuit written against [`AlgebraicHashGadget`] takes the hash as a type
+//! parameter, so that it can be swapped, e.g. to compare constraint counts,
+//! without rewriting the circuit.
+
+use ark_ff::PrimeField;
+use zkp_r1cs::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
+
+use crate::Vec;
+
+pub trait AlgebraicHashGadget<F: PrimeField> {
+    /// The name of the hash, e.g. to label benchmarks.
+    const NAME: &'static str;
+
+    /// Compresses two field elements into one.
+    fn compress(xl: F, xr: F) -> F;
+
+    /// Enforces `compress` on allocated variables, returning the output
+    /// variable and its value.
+    fn compress_enforce<CS: ConstraintSystem<F>>(
+        cs: CS,
+        xl: (Variable, Option<F>),
+        xr: (Variable, Option<F>),
+    ) -> Result<(Variable, Option<F>), SynthesisError>;
+
+    /// Hashes `input` by chaining `compress` from zero.
+    fn hash(input: &[F]) -> F {
+        input.iter().fold(F::zero(), |h, x| Self::compress(h, *x))
+    }
+
+    /// Enforces `hash` on allocated variables, returning the output variable
+    /// and its value.
+    fn hash_enforce<CS: ConstraintSystem<F>>(
+        mut cs: CS,
+        input: &[(Variable, Option<F>)],
+    ) -> Result<(Variable, Option<F>), SynthesisError> {
+        let zero = cs.alloc(|| "zero", || Ok(F::zero()))?;
+        cs.enforce(|| "zero = 0", |lc| lc + zero, |lc| lc + CS::one(), |lc| lc);
+
+        let mut h = (zero, Some(F::zero()));
+        for (i, x) in input.iter().enumerate() {
+            h = Self::compress_enforce(cs.ns(|| format!("compress_{}", i)), h, *x)?;
+        }
+        Ok(h)
+    }
+}
+
+/// Enforces `x^5`, in three constraints.
+pub(crate) fn pow5_enforce<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    x: &LinearCombination<F>,
+    value: Option<F>,
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let x2_value = value.map(|v| v.square());
+    let x2 = cs.alloc(
+        || "x^2",
+        || x2_value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(|| "x^2 = x * x", |lc| lc + x, |lc| lc + x, |lc| lc + x2);
+
+    let x4_value = x2_value.map(|v| v.square());
+    let x4 = cs.alloc(
+        || "x^4",
+        || x4_value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(
+        || "x^4 = x^2 * x^2",
+        |lc| lc + x2,
+        |lc| lc + x2,
+        |lc| lc + x4,
+    );
+
+    let x5_value = x4_value.and_then(|x4| value.map(|x| x4 * x));
+    let x5 = cs.alloc(
+        || "x^5",
+        || x5_value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(|| "x^5 = x^4 * x", |lc| lc + x4, |lc| lc + x, |lc| lc + x5);
+
+    Ok((x5, x5_value))
+}
+
+/// Enforces `x^(1/5)`, given as the exponent `inverse`, in three constraints
+/// on its fifth power.
+pub(crate) fn pow5_inverse_enforce<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    x: &LinearCombination<F>,
+    value: Option<F>,
+    inverse: &[u64],
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let y_value = value.map(|v| v.pow(inverse));
+    let y = cs.alloc(|| "y", || y_value.ok_or(SynthesisError::AssignmentMissing))?;
+
+    let y2_value = y_value.map(|v| v.square());
+    let y2 = cs.alloc(
+        || "y^2",
+        || y2_value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(|| "y^2 = y * y", |lc| lc + y, |lc| lc + y, |lc| lc + y2);
+
+    let y4_value = y2_value.map(|v| v.square());
+    let y4 = cs.alloc(
+        || "y^4",
+        || y4_value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(
+        || "y^4 = y^2 * y^2",
+        |lc| lc + y2,
+        |lc| lc + y2,
+        |lc| lc + y4,
+    );
+    cs.enforce(|| "x = y^4 * y", |lc| lc + y4, |lc| lc + y, |lc| lc + x);
+
+    Ok((y, y_value))
+}
+
+/// `mds * state`, on the linear combinations of the state and on its values.
+pub(crate) fn mix<F: PrimeField>(
+    mds: &[impl AsRef<[F]>],
+    state: &[LinearCombination<F>],
+    values: &[Option<F>],
+) -> (Vec<LinearCombination<F>>, Vec<Option<F>>) {
+    mds.iter()
+        .map(|row| {
+            let row = row.as_ref();
+            let lc = row
+                .iter()
+                .zip(state)
+                .fold(LinearCombination::zero(), |lc, (m, s)| lc + (*m, s));
+            let value = row
+                .iter()
+                .zip(values)
+                .map(|(m, v)| v.map(|v| *m * v))
+                .sum::<Option<F>>();
+            (lc, value)
+        })
+        .unzip()
+}
+
+/// Allocates the output of a permutation, the linear combination `state_0`.
+pub(crate) fn output_enforce<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    state_0: &LinearCombination<F>,
+    value: Option<F>,
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let output = cs.alloc(
+        || "output",
+        || value.ok_or(SynthesisError::AssignmentMissing),
+    )?;
+    cs.enforce(
+        || "output = state[0]",
+        |lc| lc + state_0,
+        |lc| lc + CS::one(),
+        |lc| lc + output,
+    );
+    Ok((output, value))
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+    use zkp_r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::hashes::{
+        mimc::MimcGadget, poseidon::PoseidonGadget, rescue_prime::RescuePrimeGadget,
+    };
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    fn hash_enforce<H: AlgebraicHashGadget<Fr>>(constraints: usize) {
+        let rng = &mut test_rng();
+        let input: Vec<Fr> = (0..3).map(|_| Fr::rand(rng)).collect();
+
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let vars: Vec<_> = input
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let var = cs.alloc(|| format!("input_{}", i), || Ok(*x)).unwrap();
+                (var, Some(*x))
+            })
+            .collect();
+        let (_, value) = H::hash_enforce(cs.ns(|| H::NAME), &vars).unwrap();
+
+        assert_eq!(value, Some(H::hash(&input)));
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 1 + 3 * constraints, "{}", H::NAME);
+    }
+
+    #[test]
+    fn test_algebraic_hashes() {
+        hash_enforce::<MimcGadget<Fr>>(644);
+        hash_enforce::<PoseidonGadget<Fr>>(3 * 271 + 1);
+        hash_enforce::<RescuePrimeGadget<Fr>>(3 * 6 * 14 + 1);
+    }
+}
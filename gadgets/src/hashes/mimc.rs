@@ -8,6 +8,7 @@ use zkp_r1cs::{ConstraintSystem, SynthesisError, Variable};
 use crate::Vec;
 
 use super::abstract_hash::{AbstractHash, AbstractHashOutput};
+use super::algebraic::AlgebraicHashGadget;
 
 /// This is we used MiMC rounds constant.
 pub const MIMC_ROUNDS: usize = 322;
@@ -99,7 +100,7 @@ pub fn mimc<F: PrimeField, CS: ConstraintSystem<F>>(
     b: Option<&[u8]>,
 ) -> Result<Option<F>, SynthesisError> {
     let constants = constants::<F>();
-    let (mut xl_value, mut xr_value, image_value) = match b {
+    let (xl_value, xr_value, image_value) = match b {
         Some(bytes) => {
             let (xl, xr, image) = mimc_hash(bytes, &constants);
             (Some(xl), Some(xr), Some(image))
@@ -107,15 +108,44 @@ pub fn mimc<F: PrimeField, CS: ConstraintSystem<F>>(
         None => (None, None, None),
     };
 
-    let mut var_xl = cs.alloc(
+    let var_xl = cs.alloc(
         || "preimage xl",
         || xl_value.ok_or(SynthesisError::AssignmentMissing),
     )?;
-    let mut var_xr = cs.alloc(
+    let var_xr = cs.alloc(
         || "preimage xr",
         || xr_value.ok_or(SynthesisError::AssignmentMissing),
     )?;
 
+    block_enforce_with_constants(cs, (var_xl, xl_value), (var_xr, xr_value), &constants)?;
+
+    Ok(image_value)
+}
+
+/// One MiMC block `E(xl, xr)` over field elements, the building block of the sponge.
+pub fn block<F: PrimeField>(xl: F, xr: F) -> F {
+    mimc_block(xl, xr, &constants())
+}
+
+/// Enforce one MiMC block over already allocated variables, returning the
+/// output variable and its value.
+pub fn block_enforce<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: CS,
+    xl: (Variable, Option<F>),
+    xr: (Variable, Option<F>),
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    block_enforce_with_constants(cs, xl, xr, &constants::<F>())
+}
+
+fn block_enforce_with_constants<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    xl: (Variable, Option<F>),
+    xr: (Variable, Option<F>),
+    constants: &[F],
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let (mut var_xl, mut xl_value) = xl;
+    let (mut var_xr, mut xr_value) = xr;
+
     for i in 0..MIMC_ROUNDS {
         let mut n_cs = cs.ns(|| format!("rounds_{}", i));
 
@@ -156,7 +186,7 @@ pub fn mimc<F: PrimeField, CS: ConstraintSystem<F>>(
         var_xl = var_new_xl;
     }
 
-    Ok(image_value)
+    Ok((var_xl, xl_value))
 }
 
 /// implement AbstractHashOutput.
@@ -238,6 +268,25 @@ impl<F: PrimeField> AbstractHash<F> for AbstractHashMimc<F> {
     }
 }
 
+/// MiMC as an `AlgebraicHashGadget`, compressing with one block.
+pub struct MimcGadget<F>(core::marker::PhantomData<F>);
+
+impl<F: PrimeField> AlgebraicHashGadget<F> for MimcGadget<F> {
+    const NAME: &'static str = "mimc";
+
+    fn compress(xl: F, xr: F) -> F {
+        block(xl, xr)
+    }
+
+    fn compress_enforce<CS: ConstraintSystem<F>>(
+        cs: CS,
+        xl: (Variable, Option<F>),
+        xr: (Variable, Option<F>),
+    ) -> Result<(Variable, Option<F>), SynthesisError> {
+        block_enforce(cs, xl, xr)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ark_bls12_381::Fr;
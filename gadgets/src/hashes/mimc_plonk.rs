@@ -0,0 +1,55 @@
+//! MiMC blocks of arithmetic gates on the PLONK composer. The composer keeps
+//! the values of its variables to itself, so a block takes each variable
+//! with its value.
+
+use ark_ff::PrimeField;
+use zkp_plonk::{Composer, Variable};
+
+/// One MiMC block `E(xl, xr)` with `constants`, as `mimc::block`, in three
+/// gates per round.
+pub fn block<F: PrimeField>(
+    cs: &mut Composer<F>,
+    constants: &[F],
+    xl: (Variable, F),
+    xr: (Variable, F),
+) -> (Variable, F) {
+    let (mut var_xl, mut xl) = xl;
+    let (mut var_xr, mut xr) = xr;
+    for c in constants.iter() {
+        // t = xL + Ci
+        let t = xl + c;
+        let var_t = cs.alloc_and_assign(t);
+        cs.create_add_gate(
+            (var_xl, F::one()),
+            (var_xl, F::zero()),
+            var_t,
+            None,
+            *c,
+            F::zero(),
+        );
+
+        // t2 = t^2
+        let t2 = t.square();
+        let var_t2 = cs.alloc_and_assign(t2);
+        cs.create_mul_gate(var_t, var_t, var_t2, None, F::one(), F::zero(), F::zero());
+
+        // new_xL = t2 * t + xR
+        let new_xl = t2 * t + xr;
+        let var_new_xl = cs.alloc_and_assign(new_xl);
+        cs.create_mul_gate(
+            var_t2,
+            var_t,
+            var_new_xl,
+            Some((var_xr, F::one())),
+            F::one(),
+            F::zero(),
+            F::zero(),
+        );
+
+        xr = xl;
+        var_xr = var_xl;
+        xl = new_xl;
+        var_xl = var_new_xl;
+    }
+    (var_xl, xl)
+}
@@ -3,6 +3,10 @@ pub mod abstract_hash;
 pub mod blake2s;
 pub mod sha256;
 
+pub mod algebraic;
 pub mod mimc;
+#[cfg(feature = "plonk")]
+pub mod mimc_plonk;
 pub mod poseidon;
 pub mod rescue;
+pub mod rescue_prime;
@@ -5,11 +5,12 @@
 // @Author: YunLi (liyunscss@gmail.com)
 
 use ark_ff::{BigInteger, BitIteratorBE, FromBytes, PrimeField};
-use zkp_r1cs::{ConstraintSystem, SynthesisError, Variable};
+use zkp_r1cs::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
 
 use crate::Vec;
 
 use super::abstract_hash::{AbstractHash, AbstractHashOutput};
+use super::algebraic::{mix, output_enforce, pow5_enforce, AlgebraicHashGadget};
 
 // Hash Poseidon utilizes Sponge Construction
 // r, bitrate; c, capacity; M, state value, equal to r + c;
@@ -584,6 +585,48 @@ pub fn poseidon_block<F: PrimeField>(xl: F, xr: F, rc: &PoseidonConstant<F>) ->
     state[0]
 }
 
+/// One Poseidon block over field elements.
+pub fn block<F: PrimeField>(xl: F, xr: F) -> F {
+    poseidon_block(xl, xr, &constants())
+}
+
+/// Enforce one Poseidon block over already allocated variables, returning the
+/// output variable and its value.
+pub fn block_enforce<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    xl: (Variable, Option<F>),
+    xr: (Variable, Option<F>),
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let rc = constants::<F>();
+
+    let mut state = vec![xl.0.into(), xr.0.into(), LinearCombination::zero()];
+    let mut values = vec![xl.1, xr.1, Some(F::zero())];
+    for i in 0..POSEIDON_ROUNDS {
+        let cs = &mut cs.ns(|| format!("round_{}", i));
+        let full = i < (RF / 2) || i > (RF / 2);
+        for (j, ((s, value), c)) in state
+            .iter_mut()
+            .zip(values.iter_mut())
+            .zip(rc.ark[i].iter())
+            .enumerate()
+        {
+            *s += (*c, CS::one());
+            *value = value.map(|v| v + c);
+            if full || j == M - 1 {
+                let (var, v) = pow5_enforce(cs.ns(|| format!("sbox_{}", j)), s, *value)?;
+                *s = var.into();
+                *value = v;
+            }
+        }
+
+        let (mixed, mixed_values) = mix(&rc.mds, &state, &values);
+        state = mixed;
+        values = mixed_values;
+    }
+
+    output_enforce(cs.ns(|| "output"), &state[0], values[0])
+}
+
 /// poseidon hash function.
 fn poseidon_hash<F: PrimeField>(b: &[u8], constants: &PoseidonConstant<F>) -> (F, F, F) {
     let mut v: Vec<F> = Vec::new();
@@ -836,6 +879,25 @@ impl<F: PrimeField> AbstractHash<F> for AbstractHashPoseidon<F> {
     }
 }
 
+/// Poseidon as an `AlgebraicHashGadget`, compressing with one block.
+pub struct PoseidonGadget<F>(core::marker::PhantomData<F>);
+
+impl<F: PrimeField> AlgebraicHashGadget<F> for PoseidonGadget<F> {
+    const NAME: &'static str = "poseidon";
+
+    fn compress(xl: F, xr: F) -> F {
+        block(xl, xr)
+    }
+
+    fn compress_enforce<CS: ConstraintSystem<F>>(
+        cs: CS,
+        xl: (Variable, Option<F>),
+        xr: (Variable, Option<F>),
+    ) -> Result<(Variable, Option<F>), SynthesisError> {
+        block_enforce(cs, xl, xr)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ark_bls12_381::Fr;
@@ -0,0 +1,236 @@
+//! Circuits for the [Rescue-Prime] hash function.
+//!
+//! The state holds 3 elements, a rate of 2 and a capacity of 1, the S-box is
+//! `x^5` and its inverse, and a block takes 14 rounds, as the specification
+//! gives for these parameters and 128 bits of security. The field must not
+//! have `5 | p - 1`, as the scalar fields of BN-256 and BLS12-381.
+//!
+//! The round constants are derived from a seed as those of MiMC, and the MDS
+//! matrix is the Cauchy matrix `1 / (i + j + 3)`, so they are defined for any
+//! field but do not match the test vectors of the specification.
+//!
+//! [Rescue-Prime]: https://eprint.iacr.org/2020/1143
+
+use ark_ff::{FpParameters, PrimeField};
+use zkp_r1cs::{ConstraintSystem, LinearCombination, SynthesisError, Variable};
+
+use crate::Vec;
+
+use super::algebraic::AlgebraicHashGadget;
+use super::algebraic::{mix, output_enforce, pow5_enforce, pow5_inverse_enforce};
+
+/// The width of the state.
+const M: usize = 3;
+
+/// The number of rounds, each of an S-box and an inverse S-box layer.
+pub const RESCUE_PRIME_ROUNDS: usize = 14;
+
+const ALPHA: u64 = 5;
+
+/// The seed of the round constants.
+pub const SEED: [u8; 32] = [1; 32];
+
+pub struct RescuePrimeConstant<F: PrimeField> {
+    /// The constants added after each layer.
+    pub constants: [[F; M]; 2 * RESCUE_PRIME_ROUNDS],
+    pub mds: [[F; M]; M], // MDS matrix
+    /// `1 / ALPHA` modulo `p - 1`, as the exponent of the inverse S-box.
+    pub inverse_alpha: Vec<u64>,
+}
+
+impl<F: PrimeField> RescuePrimeConstant<F> {
+    /// The constants with round constants derived from `seed`.
+    pub fn with_seed(seed: [u8; 32]) -> RescuePrimeConstant<F> {
+        use rand::{Rng, SeedableRng};
+        let rng = &mut rand::rngs::StdRng::from_seed(seed);
+
+        let mut constants = [[F::zero(); M]; 2 * RESCUE_PRIME_ROUNDS];
+        for c in constants.iter_mut().flat_map(|c| c.iter_mut()) {
+            *c = loop {
+                let bytes: [u8; 32] = rng.gen();
+                if let Some(f) = F::from_random_bytes(&bytes) {
+                    break f;
+                }
+            };
+        }
+
+        let mut mds = [[F::zero(); M]; M];
+        for (i, row) in mds.iter_mut().enumerate() {
+            for (j, m) in row.iter_mut().enumerate() {
+                *m = F::from((i + j + M) as u64).inverse().unwrap();
+            }
+        }
+
+        RescuePrimeConstant {
+            constants,
+            mds,
+            inverse_alpha: inverse_alpha::<F>(),
+        }
+    }
+}
+
+/// `1 / ALPHA` modulo `p - 1`, as `(k (p - 1) + 1) / ALPHA` for the `k` that
+/// divides, in little-endian limbs.
+fn inverse_alpha<F: PrimeField>() -> Vec<u64> {
+    let mut p_minus_one = <F::Params as FpParameters>::MODULUS.as_ref().to_vec();
+    // p is odd.
+    p_minus_one[0] -= 1;
+
+    for k in 1..ALPHA as u128 {
+        let mut carry = 1u128;
+        let mut n: Vec<u64> = p_minus_one
+            .iter()
+            .map(|limb| {
+                let t = *limb as u128 * k + carry;
+                carry = t >> 64;
+                t as u64
+            })
+            .collect();
+        n.push(carry as u64);
+
+        let mut rem = 0u128;
+        for limb in n.iter_mut().rev() {
+            let t = (rem << 64) | *limb as u128;
+            *limb = (t / ALPHA as u128) as u64;
+            rem = t % ALPHA as u128;
+        }
+        if rem == 0 {
+            return n;
+        }
+    }
+    panic!("x^{} is not a permutation of the field", ALPHA)
+}
+
+pub fn constants<F: PrimeField>() -> RescuePrimeConstant<F> {
+    RescuePrimeConstant::<F>::with_seed(SEED)
+}
+
+pub fn rescue_prime_block<F: PrimeField>(xl: F, xr: F, rc: &RescuePrimeConstant<F>) -> F {
+    let mut state = [xl, xr, F::zero()];
+    for i in 0..2 * RESCUE_PRIME_ROUNDS {
+        for s in state.iter_mut() {
+            *s = if i % 2 == 0 {
+                s.pow(&[ALPHA])
+            } else {
+                s.pow(&rc.inverse_alpha)
+            };
+        }
+
+        // matrix multiplication
+        let mut tmp = [F::zero(); M];
+        for (t, row) in tmp.iter_mut().zip(rc.mds.iter()) {
+            *t = row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum();
+        }
+        for ((s, t), c) in state.iter_mut().zip(tmp.iter()).zip(rc.constants[i].iter()) {
+            *s = *t + c;
+        }
+    }
+    // c == 1
+    state[0]
+}
+
+/// One Rescue-Prime block over field elements.
+pub fn block<F: PrimeField>(xl: F, xr: F) -> F {
+    rescue_prime_block(xl, xr, &constants())
+}
+
+/// Enforce one Rescue-Prime block over already allocated variables,
+/// returning the output variable and its value.
+pub fn block_enforce<F: PrimeField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    xl: (Variable, Option<F>),
+    xr: (Variable, Option<F>),
+) -> Result<(Variable, Option<F>), SynthesisError> {
+    let rc = constants::<F>();
+
+    let mut state = vec![xl.0.into(), xr.0.into(), LinearCombination::zero()];
+    let mut values = vec![xl.1, xr.1, Some(F::zero())];
+    for i in 0..2 * RESCUE_PRIME_ROUNDS {
+        let cs = &mut cs.ns(|| format!("layer_{}", i));
+        for (j, (s, value)) in state.iter_mut().zip(values.iter_mut()).enumerate() {
+            let cs = cs.ns(|| format!("sbox_{}", j));
+            let (var, v) = if i % 2 == 0 {
+                pow5_enforce(cs, s, *value)?
+            } else {
+                pow5_inverse_enforce(cs, s, *value, &rc.inverse_alpha)?
+            };
+            *s = var.into();
+            *value = v;
+        }
+
+        let (mixed, mixed_values) = mix(&rc.mds, &state, &values);
+        state = mixed;
+        values = mixed_values;
+        for ((s, value), c) in state
+            .iter_mut()
+            .zip(values.iter_mut())
+            .zip(rc.constants[i].iter())
+        {
+            *s += (*c, CS::one());
+            *value = value.map(|v| v + c);
+        }
+    }
+
+    output_enforce(cs.ns(|| "output"), &state[0], values[0])
+}
+
+/// Rescue-Prime as an `AlgebraicHashGadget`, compressing with one block.
+pub struct RescuePrimeGadget<F>(core::marker::PhantomData<F>);
+
+impl<F: PrimeField> AlgebraicHashGadget<F> for RescuePrimeGadget<F> {
+    const NAME: &'static str = "rescue-prime";
+
+    fn compress(xl: F, xr: F) -> F {
+        block(xl, xr)
+    }
+
+    fn compress_enforce<CS: ConstraintSystem<F>>(
+        cs: CS,
+        xl: (Variable, Option<F>),
+        xr: (Variable, Option<F>),
+    ) -> Result<(Variable, Option<F>), SynthesisError> {
+        block_enforce(cs, xl, xr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr;
+    use ark_ff::{Field, UniformRand};
+    use ark_std::test_rng;
+    use zkp_r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    #[test]
+    fn test_inverse_alpha() {
+        let rng = &mut test_rng();
+        let rc = constants::<Fr>();
+        for _ in 0..10 {
+            let x = Fr::rand(rng);
+            assert_eq!(x.pow(&rc.inverse_alpha).pow(&[ALPHA]), x);
+        }
+    }
+
+    #[test]
+    fn test_rescue_prime_block() {
+        let rng = &mut test_rng();
+        let (xl, xr) = (Fr::rand(rng), Fr::rand(rng));
+
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let var_xl = cs.alloc(|| "xl", || Ok(xl)).unwrap();
+        let var_xr = cs.alloc(|| "xr", || Ok(xr)).unwrap();
+        let (_, value) = block_enforce(
+            cs.ns(|| "rescue-prime"),
+            (var_xl, Some(xl)),
+            (var_xr, Some(xr)),
+        )
+        .unwrap();
+
+        assert_eq!(value, Some(block(xl, xr)));
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), 3 * 6 * RESCUE_PRIME_ROUNDS + 1);
+        assert_ne!(block(xl, xr), block(xr, xl));
+    }
+}
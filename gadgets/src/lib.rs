@@ -16,10 +16,15 @@ use alloc::{string::String, vec::Vec};
 use std::{string::String, vec::Vec};
 
 pub mod algebra;
+pub mod credential;
+pub mod ecdsa;
 pub mod hashes;
 pub mod lookup;
 pub mod merkletree;
 pub mod operator;
+#[cfg(any(feature = "bulletproofs", feature = "plonk"))]
+pub mod range;
+pub mod schnorr;
 
 #[cfg(test)]
 mod test_constraint_system;
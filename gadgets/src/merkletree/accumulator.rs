@@ -0,0 +1,211 @@
+//! A dynamic set accumulator on a fixed-depth Merkle tree over field elements.
+//!
+//! The root is the accumulator value kept as on-chain state. Elements can be
+//! added and removed, and the holder of an element can prove membership
+//! against the current root, natively or in a circuit (see
+//! `accumulator_constraints`). Removed slots are reset to the empty leaf, so
+//! old membership proofs stop verifying once the root is updated.
+
+use ark_ff::PrimeField;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::hashes::mimc;
+use crate::Vec;
+
+/// Two-to-one compression used by the accumulator tree.
+pub fn merge<F: PrimeField>(left: F, right: F) -> F {
+    mimc::block(left, right)
+}
+
+pub struct Accumulator<F: PrimeField> {
+    depth: usize,
+    /// `levels[0]` are the leaves, `levels[depth]` holds the root.
+    levels: Vec<Vec<F>>,
+    /// the root of an empty subtree at every height.
+    empty: Vec<F>,
+    positions: BTreeMap<F::BigInt, usize>,
+    next_index: usize,
+}
+
+/// The authentication path of one element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MembershipProof<F: PrimeField> {
+    pub index: usize,
+    /// siblings from the leaf level up.
+    pub lemmas: Vec<F>,
+}
+
+impl<F: PrimeField> MembershipProof<F> {
+    pub fn root(&self, element: &F) -> F {
+        let mut node = *element;
+        for (height, sibling) in self.lemmas.iter().enumerate() {
+            node = if (self.index >> height) & 1 == 0 {
+                merge(node, *sibling)
+            } else {
+                merge(*sibling, node)
+            };
+        }
+        node
+    }
+
+    pub fn verify(&self, root: &F, element: &F) -> bool {
+        !element.is_zero()
+            && self.lemmas.len() < 64
+            && self.index >> self.lemmas.len() == 0
+            && &self.root(element) == root
+    }
+}
+
+impl<F: PrimeField> Accumulator<F> {
+    /// An empty accumulator holding at most `2^depth` elements.
+    pub fn new(depth: usize) -> Self {
+        let mut empty = Vec::with_capacity(depth + 1);
+        empty.push(F::zero());
+        for height in 0..depth {
+            empty.push(merge(empty[height], empty[height]));
+        }
+
+        Accumulator {
+            depth,
+            levels: vec![Vec::new(); depth + 1],
+            empty,
+            positions: BTreeMap::new(),
+            next_index: 0,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn root(&self) -> F {
+        self.node(self.depth, 0)
+    }
+
+    pub fn contains(&self, element: &F) -> bool {
+        self.positions.contains_key(&element.into_repr())
+    }
+
+    fn node(&self, height: usize, index: usize) -> F {
+        self.levels[height]
+            .get(index)
+            .copied()
+            .unwrap_or(self.empty[height])
+    }
+
+    fn set_leaf(&mut self, index: usize, value: F) {
+        let mut index = index;
+        let mut node = value;
+        for height in 0..=self.depth {
+            let level = &mut self.levels[height];
+            if level.len() <= index {
+                level.resize(index + 1, self.empty[height]);
+            }
+            level[index] = node;
+
+            if height < self.depth {
+                let sibling = self.node(height, index ^ 1);
+                node = if index & 1 == 0 {
+                    merge(node, sibling)
+                } else {
+                    merge(sibling, node)
+                };
+                index >>= 1;
+            }
+        }
+    }
+
+    /// Add `element`, returning its slot. Returns `None` if the element is
+    /// zero (the empty leaf), already present, or the tree is full.
+    pub fn add(&mut self, element: F) -> Option<usize> {
+        if element.is_zero() || self.contains(&element) || self.next_index >> self.depth != 0 {
+            return None;
+        }
+
+        let index = self.next_index;
+        self.next_index += 1;
+        self.positions.insert(element.into_repr(), index);
+        self.set_leaf(index, element);
+
+        Some(index)
+    }
+
+    /// Remove `element`. Its slot is not reused.
+    pub fn remove(&mut self, element: &F) -> bool {
+        match self.positions.remove(&element.into_repr()) {
+            Some(index) => {
+                self.set_leaf(index, F::zero());
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn prove_membership(&self, element: &F) -> Option<MembershipProof<F>> {
+        let index = *self.positions.get(&element.into_repr())?;
+        let lemmas = (0..self.depth)
+            .map(|height| self.node(height, (index >> height) ^ 1))
+            .collect();
+
+        Some(MembershipProof { index, lemmas })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+
+    use super::*;
+
+    #[test]
+    fn test_accumulator() {
+        let mut acc = Accumulator::<Fr>::new(4);
+        let empty_root = acc.root();
+
+        let elements: Vec<Fr> = (1..6u32).map(Fr::from).collect();
+        for (i, e) in elements.iter().enumerate() {
+            assert_eq!(acc.add(*e), Some(i));
+        }
+        assert_eq!(acc.add(elements[0]), None);
+        assert_eq!(acc.add(Fr::from(0u32)), None);
+
+        let root = acc.root();
+        for e in &elements {
+            let proof = acc.prove_membership(e).unwrap();
+            assert!(proof.verify(&root, e));
+        }
+        assert!(acc.prove_membership(&Fr::from(100u32)).is_none());
+
+        let proof = acc.prove_membership(&elements[2]).unwrap();
+        assert!(acc.remove(&elements[2]));
+        assert!(!acc.contains(&elements[2]));
+        assert!(!proof.verify(&acc.root(), &elements[2]));
+
+        for e in &elements {
+            assert!(acc.remove(e) || e == &elements[2]);
+        }
+        assert_eq!(acc.root(), empty_root);
+    }
+
+    #[test]
+    fn test_accumulator_full() {
+        let mut acc = Accumulator::<Fr>::new(2);
+        for i in 1..5u32 {
+            assert!(acc.add(Fr::from(i)).is_some());
+        }
+        assert!(acc.add(Fr::from(5u32)).is_none());
+    }
+}
@@ -0,0 +1,162 @@
+//! Accumulator membership gadgets.
+
+use ark_ff::PrimeField;
+use zkp_r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError, Variable};
+
+use crate::hashes::mimc::MimcGadget;
+
+use super::accumulator::MembershipProof;
+use super::path_constraints::MerkleProofGadget;
+
+pub struct MembershipGadget<F: PrimeField> {
+    depth: usize,
+    proof: Option<MembershipProof<F>>,
+}
+
+impl<F: PrimeField> MembershipGadget<F> {
+    /// `proof` is `None` when synthesizing without a witness.
+    pub fn new(depth: usize, proof: Option<MembershipProof<F>>) -> Self {
+        MembershipGadget { depth, proof }
+    }
+
+    /// Enforce that `element` is a member of the accumulator with `root`.
+    /// Empty and removed slots hold zero, so `element` must be nonzero.
+    pub fn enforce_membership<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        root: (Variable, Option<F>),
+        element: (Variable, Option<F>),
+    ) -> Result<(), SynthesisError> {
+        if let Some(p) = &self.proof {
+            if p.lemmas.len() != self.depth {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        }
+
+        let inverse = cs.alloc(
+            || "element inverse",
+            || {
+                let value = element.1.ok_or(SynthesisError::AssignmentMissing)?;
+                Ok(value.inverse().unwrap_or_else(F::zero))
+            },
+        )?;
+        cs.enforce(
+            || "element is nonzero",
+            |lc| lc + element.0,
+            |lc| lc + inverse,
+            |lc| lc + CS::one(),
+        );
+
+        MerkleProofGadget::<F, MimcGadget<F>>::new(
+            self.depth,
+            self.proof.as_ref().map(|p| p.index),
+            self.proof.as_ref().map(|p| p.lemmas.clone()),
+        )
+        .enforce_membership(cs, root.0, element)
+    }
+}
+
+/// Proves that a private element is in the accumulator with the public root.
+pub struct MembershipCircuit<F: PrimeField> {
+    pub depth: usize,
+    pub root: Option<F>,
+    pub element: Option<F>,
+    pub proof: Option<MembershipProof<F>>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for MembershipCircuit<F> {
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let root = cs.alloc_input(
+            || "root",
+            || self.root.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let element = cs.alloc(
+            || "element",
+            || self.element.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+
+        MembershipGadget::new(self.depth, self.proof).enforce_membership(
+            cs.ns(|| "membership"),
+            (root, self.root),
+            (element, self.element),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+
+    use super::super::accumulator::Accumulator;
+    use super::*;
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    #[test]
+    fn test_accumulator_membership() {
+        let mut acc = Accumulator::<Fr>::new(3);
+        let elements: Vec<Fr> = (1..6u32).map(Fr::from).collect();
+        for e in &elements {
+            acc.add(*e);
+        }
+        let root = acc.root();
+
+        for e in &elements {
+            let c = MembershipCircuit {
+                depth: acc.depth(),
+                root: Some(root),
+                element: Some(*e),
+                proof: acc.prove_membership(e),
+            };
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            c.generate_constraints(&mut cs).unwrap();
+            assert!(cs.is_satisfied());
+        }
+
+        // revoked element no longer proves against the new root.
+        let proof = acc.prove_membership(&elements[1]);
+        acc.remove(&elements[1]);
+        let c = MembershipCircuit {
+            depth: acc.depth(),
+            root: Some(acc.root()),
+            element: Some(elements[1]),
+            proof: proof.clone(),
+        };
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        c.generate_constraints(&mut cs).unwrap();
+        assert!(!cs.is_satisfied());
+
+        // zero is the empty leaf: it hashes to the root both at the revoked
+        // slot and at an unused one, and must still be rejected.
+        let revoked = proof.clone().unwrap();
+        let mut unused = acc.prove_membership(&elements[4]).unwrap();
+        unused.index = 5;
+        unused.lemmas[0] = elements[4];
+        for p in [revoked, unused] {
+            assert_eq!(p.root(&Fr::from(0u32)), acc.root());
+            let c = MembershipCircuit {
+                depth: acc.depth(),
+                root: Some(acc.root()),
+                element: Some(Fr::from(0u32)),
+                proof: Some(p),
+            };
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            c.generate_constraints(&mut cs).unwrap();
+            assert!(!cs.is_satisfied());
+        }
+
+        // a short proof is an error, not a panic.
+        let mut short = acc.prove_membership(&elements[0]).unwrap();
+        short.lemmas.pop();
+        let c = MembershipCircuit {
+            depth: acc.depth(),
+            root: Some(acc.root()),
+            element: Some(elements[0]),
+            proof: Some(short),
+        };
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        assert!(c.generate_constraints(&mut cs).is_err());
+    }
+}
@@ -1,2 +1,8 @@
+pub mod accumulator;
+pub mod accumulator_constraints;
 pub mod cbmt;
 pub mod cbmt_constraints;
+pub mod path_constraints;
+pub mod smt;
+#[cfg(feature = "plonk")]
+pub mod smt_plonk;
@@ -0,0 +1,182 @@
+//! Merkle authentication path gadgets, generic over the algebraic hashes.
+//!
+//! The tree has a fixed depth and merges two children with
+//! `AlgebraicHashGadget::compress`, so the same circuit can be built on MiMC,
+//! Poseidon or Rescue-Prime.
+
+use ark_ff::PrimeField;
+use core::marker::PhantomData;
+use zkp_r1cs::{ConstraintSystem, SynthesisError, Variable};
+
+use crate::algebra::boolean::AllocatedBit;
+use crate::hashes::algebraic::AlgebraicHashGadget;
+use crate::Vec;
+
+pub struct MerkleProofGadget<F: PrimeField, H: AlgebraicHashGadget<F>> {
+    depth: usize,
+    index: Option<usize>,
+    /// siblings from the leaf level up.
+    lemmas: Option<Vec<F>>,
+    _hash: PhantomData<H>,
+}
+
+impl<F: PrimeField, H: AlgebraicHashGadget<F>> MerkleProofGadget<F, H> {
+    /// The path of the leaf at `index`, `None` when synthesizing without a
+    /// witness.
+    pub fn new(depth: usize, index: Option<usize>, lemmas: Option<Vec<F>>) -> Self {
+        MerkleProofGadget {
+            depth,
+            index,
+            lemmas,
+            _hash: PhantomData,
+        }
+    }
+
+    /// The root of the tree with `leaf` at `index` and the siblings `lemmas`.
+    pub fn root(index: usize, lemmas: &[F], leaf: F) -> F {
+        lemmas
+            .iter()
+            .enumerate()
+            .fold(leaf, |node, (height, sibling)| {
+                if (index >> height) & 1 == 0 {
+                    H::compress(node, *sibling)
+                } else {
+                    H::compress(*sibling, node)
+                }
+            })
+    }
+
+    /// Enforce the root of the path from `leaf`, returning the root variable
+    /// and its value.
+    pub fn compute_root<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        leaf: (Variable, Option<F>),
+    ) -> Result<(Variable, Option<F>), SynthesisError> {
+        let mut node = leaf;
+        for height in 0..self.depth {
+            let mut cs = cs.ns(|| format!("height {}", height));
+
+            let bit = AllocatedBit::alloc(
+                cs.ns(|| "is right"),
+                self.index.map(|index| (index >> height) & 1 == 1),
+            )?;
+            let sibling_value = self
+                .lemmas
+                .as_ref()
+                .and_then(|lemmas| lemmas.get(height).copied());
+            let sibling = cs.alloc(
+                || "sibling",
+                || sibling_value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+
+            let (left_value, right_value) = match (bit.get_value(), node.1, sibling_value) {
+                (Some(true), Some(n), Some(s)) => (Some(s), Some(n)),
+                (Some(false), Some(n), Some(s)) => (Some(n), Some(s)),
+                _ => (None, None),
+            };
+            let left = cs.alloc(
+                || "left",
+                || left_value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+            let right = cs.alloc(
+                || "right",
+                || right_value.ok_or(SynthesisError::AssignmentMissing),
+            )?;
+
+            // left = node + bit * (sibling - node)
+            cs.enforce(
+                || "select left",
+                |lc| lc + bit.get_variable(),
+                |lc| lc + sibling - node.0,
+                |lc| lc + left - node.0,
+            );
+            // left + right = node + sibling
+            cs.enforce(
+                || "select right",
+                |lc| lc + left + right - node.0 - sibling,
+                |lc| lc + CS::one(),
+                |lc| lc,
+            );
+
+            node =
+                H::compress_enforce(cs.ns(|| "merge"), (left, left_value), (right, right_value))?;
+        }
+
+        Ok(node)
+    }
+
+    /// Enforce that `leaf` is in the tree with `root`.
+    pub fn enforce_membership<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        root: Variable,
+        leaf: (Variable, Option<F>),
+    ) -> Result<(), SynthesisError> {
+        let node = self.compute_root(cs.ns(|| "path"), leaf)?;
+
+        cs.enforce(
+            || "root must equal last node",
+            |lc| lc + node.0,
+            |lc| lc + CS::one(),
+            |lc| lc + root,
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+
+    use super::*;
+    use crate::hashes::{
+        mimc::MimcGadget, poseidon::PoseidonGadget, rescue_prime::RescuePrimeGadget,
+    };
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    fn merkle_proof<H: AlgebraicHashGadget<Fr>>() {
+        let depth = 3;
+        let leaves: Vec<Fr> = (1..=(1u32 << depth)).map(Fr::from).collect();
+
+        let mut levels = vec![leaves.clone()];
+        for height in 0..depth {
+            let level = levels[height]
+                .chunks(2)
+                .map(|pair| H::compress(pair[0], pair[1]))
+                .collect();
+            levels.push(level);
+        }
+        let root = levels[depth][0];
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let lemmas: Vec<Fr> = (0..depth)
+                .map(|height| levels[height][(index >> height) ^ 1])
+                .collect();
+            assert_eq!(
+                MerkleProofGadget::<Fr, H>::root(index, &lemmas, *leaf),
+                root
+            );
+
+            // the leaf at the neighbouring index does not open to the root.
+            for (index, satisfied) in [(index, true), (index ^ 1, false)].iter() {
+                let mut cs = TestConstraintSystem::<Fr>::new();
+                let var_root = cs.alloc_input(|| "root", || Ok(root)).unwrap();
+                let var_leaf = cs.alloc(|| "leaf", || Ok(*leaf)).unwrap();
+
+                MerkleProofGadget::<Fr, H>::new(depth, Some(*index), Some(lemmas.clone()))
+                    .enforce_membership(cs.ns(|| H::NAME), var_root, (var_leaf, Some(*leaf)))
+                    .unwrap();
+                assert_eq!(cs.is_satisfied(), *satisfied, "{}", H::NAME);
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_proof() {
+        merkle_proof::<MimcGadget<Fr>>();
+        merkle_proof::<PoseidonGadget<Fr>>();
+        merkle_proof::<RescuePrimeGadget<Fr>>();
+    }
+}
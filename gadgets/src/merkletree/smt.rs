@@ -0,0 +1,203 @@
+//! A sparse Merkle tree over field elements.
+//!
+//! A key of `depth` bits is the index of its leaf, and the leaf holds the
+//! value of the key, zero when the key is absent. So a proof of membership
+//! and a proof of non-membership are the same authentication path, opening
+//! the leaf of the key to a value or to zero (see `smt_plonk` for the gadgets
+//! on the PLONK composer). Only the non-empty nodes are stored.
+
+use ark_ff::{BigInteger, PrimeField};
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::hashes::mimc;
+use crate::Vec;
+
+/// Two-to-one compression used by the sparse Merkle tree.
+pub fn merge<F: PrimeField>(left: F, right: F) -> F {
+    mimc::block(left, right)
+}
+
+pub struct SparseMerkleTree<F: PrimeField> {
+    depth: usize,
+    /// the non-empty nodes, by height and index at that height.
+    nodes: BTreeMap<(usize, F::BigInt), F>,
+    /// the root of an empty subtree at every height.
+    empty: Vec<F>,
+}
+
+/// The authentication path of the leaf of a key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmtProof<F: PrimeField> {
+    /// siblings from the leaf level up.
+    pub lemmas: Vec<F>,
+}
+
+impl<F: PrimeField> SmtProof<F> {
+    pub fn root(&self, key: &F, value: &F) -> F {
+        let key = key.into_repr();
+        let mut node = *value;
+        for (height, sibling) in self.lemmas.iter().enumerate() {
+            node = if key.get_bit(height) {
+                merge(*sibling, node)
+            } else {
+                merge(node, *sibling)
+            };
+        }
+        node
+    }
+
+    /// Whether `key` holds `value` in the tree with `root`.
+    pub fn verify(&self, root: &F, key: &F, value: &F) -> bool {
+        key.into_repr().num_bits() as usize <= self.lemmas.len() && &self.root(key, value) == root
+    }
+
+    /// Whether `key` is absent from the tree with `root`.
+    pub fn verify_non_membership(&self, root: &F, key: &F) -> bool {
+        self.verify(root, key, &F::zero())
+    }
+}
+
+impl<F: PrimeField> SparseMerkleTree<F> {
+    /// An empty tree of the keys below `2^depth`. The depth is below the
+    /// size of the field, so that a key has a single path.
+    pub fn new(depth: usize) -> Self {
+        assert!(depth < F::size_in_bits(), "depth too large for the field");
+
+        let mut empty = Vec::with_capacity(depth + 1);
+        empty.push(F::zero());
+        for height in 0..depth {
+            empty.push(merge(empty[height], empty[height]));
+        }
+
+        SparseMerkleTree {
+            depth,
+            nodes: BTreeMap::new(),
+            empty,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn root(&self) -> F {
+        self.node(self.depth, F::BigInt::from(0))
+    }
+
+    /// The value of `key`, zero when it is absent.
+    pub fn get(&self, key: &F) -> F {
+        self.node(0, key.into_repr())
+    }
+
+    fn node(&self, height: usize, index: F::BigInt) -> F {
+        self.nodes
+            .get(&(height, index))
+            .copied()
+            .unwrap_or(self.empty[height])
+    }
+
+    /// The index at `height` of the path of `key`, and of its sibling.
+    fn indices(key: &F, height: usize) -> (F::BigInt, F::BigInt) {
+        let mut index = key.into_repr();
+        index.divn(height as u32);
+        let mut sibling = index;
+        if index.is_odd() {
+            sibling.sub_noborrow(&F::BigInt::from(1));
+        } else {
+            sibling.add_nocarry(&F::BigInt::from(1));
+        }
+        (index, sibling)
+    }
+
+    /// Set `key` to `value`, or remove it if `value` is zero, returning its
+    /// previous value. Panics if `key` is not below `2^depth`.
+    pub fn update(&mut self, key: &F, value: F) -> F {
+        assert!(
+            key.into_repr().num_bits() as usize <= self.depth,
+            "key out of range"
+        );
+        let old = self.get(key);
+
+        let mut node = value;
+        for height in 0..=self.depth {
+            let (index, sibling) = Self::indices(key, height);
+            if node == self.empty[height] {
+                self.nodes.remove(&(height, index));
+            } else {
+                self.nodes.insert((height, index), node);
+            }
+
+            if height < self.depth {
+                let sibling = self.node(height, sibling);
+                node = if index.is_odd() {
+                    merge(sibling, node)
+                } else {
+                    merge(node, sibling)
+                };
+            }
+        }
+
+        old
+    }
+
+    /// The path of `key`, proving its value or, if it is absent, its
+    /// non-membership.
+    pub fn prove(&self, key: &F) -> SmtProof<F> {
+        let lemmas = (0..self.depth)
+            .map(|height| {
+                let (_, sibling) = Self::indices(key, height);
+                self.node(height, sibling)
+            })
+            .collect();
+
+        SmtProof { lemmas }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::Zero;
+
+    use super::*;
+
+    #[test]
+    fn test_sparse_merkle_tree() {
+        let mut smt = SparseMerkleTree::<Fr>::new(16);
+        let empty_root = smt.root();
+
+        let keys: Vec<Fr> = [3u32, 4, 1000, 65535]
+            .iter()
+            .map(|k| Fr::from(*k))
+            .collect();
+        for (i, key) in keys.iter().enumerate() {
+            assert!(smt.update(key, Fr::from(i as u32 + 1)).is_zero());
+        }
+        let root = smt.root();
+
+        for (i, key) in keys.iter().enumerate() {
+            let proof = smt.prove(key);
+            assert!(proof.verify(&root, key, &Fr::from(i as u32 + 1)));
+            assert!(!proof.verify_non_membership(&root, key));
+        }
+
+        let absent = Fr::from(5u32);
+        let proof = smt.prove(&absent);
+        assert!(proof.verify_non_membership(&root, &absent));
+        assert!(!proof.verify(&root, &absent, &Fr::from(1u32)));
+
+        // a key out of range has no path.
+        let proof = smt.prove(&Fr::from(65536u32 + 3));
+        assert!(!proof.verify(&root, &Fr::from(65536u32 + 3), &Fr::from(1u32)));
+
+        for key in &keys {
+            assert!(!smt.update(key, Fr::zero()).is_zero());
+        }
+        assert_eq!(smt.root(), empty_root);
+    }
+}
@@ -0,0 +1,235 @@
+//! Sparse Merkle tree gadgets on the PLONK composer.
+//!
+//! The key is decomposed into its `depth` bits, which select the order of
+//! each merge, so a key has a single path and a proof of non-membership
+//! cannot open another leaf. The merges are MiMC blocks of arithmetic gates
+//! (see `hashes::mimc_plonk`).
+//! The composer keeps the values of its variables to itself, so the gadgets
+//! take each variable with its value.
+
+use ark_ff::{BigInteger, PrimeField};
+use zkp_plonk::{Composer, Variable};
+
+use crate::algebra::witness_inverse;
+use crate::hashes::{mimc, mimc_plonk};
+use crate::Vec;
+
+use super::smt::SmtProof;
+
+pub struct SmtGadget<F: PrimeField> {
+    depth: usize,
+    constants: [F; mimc::MIMC_ROUNDS],
+}
+
+impl<F: PrimeField> SmtGadget<F> {
+    /// The gadgets of a tree of `depth`, as `SparseMerkleTree::new`.
+    pub fn new(depth: usize) -> Self {
+        assert!(depth < F::size_in_bits(), "depth too large for the field");
+        SmtGadget {
+            depth,
+            constants: mimc::constants(),
+        }
+    }
+
+    /// Enforce that `key` holds `value` in the tree with `root`. The value
+    /// must not be zero, which is the value of an absent key.
+    pub fn enforce_membership(
+        &self,
+        cs: &mut Composer<F>,
+        root: Variable,
+        key: (Variable, F),
+        value: (Variable, F),
+        proof: &SmtProof<F>,
+    ) {
+        // value * 1 / value == 1
+        let inverse = cs.alloc_and_assign(witness_inverse(&value.1).unwrap_or_else(F::zero));
+        cs.create_mul_gate(
+            value.0,
+            inverse,
+            value.0,
+            Some((value.0, F::one())),
+            F::one(),
+            -F::one(),
+            F::zero(),
+        );
+
+        let bits = self.key_bits(cs, key);
+        let lemmas = self.alloc_lemmas(cs, proof);
+        let node = self.path_root(cs, &bits, &lemmas, value);
+        cs.assert_equal(node.0, root);
+    }
+
+    /// Enforce that `key` is absent from the tree with `root`.
+    pub fn enforce_non_membership(
+        &self,
+        cs: &mut Composer<F>,
+        root: Variable,
+        key: (Variable, F),
+        proof: &SmtProof<F>,
+    ) {
+        let zero = cs.alloc_and_assign(F::zero());
+        cs.constrain_to_constant(zero, F::zero(), F::zero());
+
+        let bits = self.key_bits(cs, key);
+        let lemmas = self.alloc_lemmas(cs, proof);
+        let node = self.path_root(cs, &bits, &lemmas, (zero, F::zero()));
+        cs.assert_equal(node.0, root);
+    }
+
+    /// Enforce that setting `key` from `old_value` to `new_value` takes the
+    /// tree from `old_root` to `new_root`. Either value may be zero, so this
+    /// also inserts and removes keys, e.g. adds a nullifier to a set after
+    /// its non-membership.
+    #[allow(clippy::too_many_arguments)]
+    pub fn enforce_update(
+        &self,
+        cs: &mut Composer<F>,
+        old_root: Variable,
+        new_root: Variable,
+        key: (Variable, F),
+        old_value: (Variable, F),
+        new_value: (Variable, F),
+        proof: &SmtProof<F>,
+    ) {
+        let bits = self.key_bits(cs, key);
+        let lemmas = self.alloc_lemmas(cs, proof);
+        let old_node = self.path_root(cs, &bits, &lemmas, old_value);
+        cs.assert_equal(old_node.0, old_root);
+        let new_node = self.path_root(cs, &bits, &lemmas, new_value);
+        cs.assert_equal(new_node.0, new_root);
+    }
+
+    /// The `depth` bits of `key`, from the leaf level up, constrained to
+    /// sum to `key`.
+    fn key_bits(&self, cs: &mut Composer<F>, key: (Variable, F)) -> Vec<(Variable, bool)> {
+        let repr = key.1.into_repr();
+        let mut coeff = F::one();
+        let mut terms = Vec::with_capacity(self.depth);
+        let bits: Vec<_> = (0..self.depth)
+            .map(|i| {
+                let bit = repr.get_bit(i);
+                let var = cs.alloc_and_assign(F::from(bit as u64));
+                cs.boolean_gate(var);
+                terms.push((var, coeff));
+                coeff.double_in_place();
+                (var, bit)
+            })
+            .collect();
+
+        let sum = cs.create_linear_combination_gate(&terms, F::zero());
+        cs.assert_equal(sum, key.0);
+        bits
+    }
+
+    fn alloc_lemmas(&self, cs: &mut Composer<F>, proof: &SmtProof<F>) -> Vec<(Variable, F)> {
+        (0..self.depth)
+            .map(|height| {
+                let value = proof.lemmas.get(height).copied().unwrap_or_else(F::zero);
+                (cs.alloc_and_assign(value), value)
+            })
+            .collect()
+    }
+
+    /// The root of the path of `leaf`, the leaf of the key of `bits`.
+    fn path_root(
+        &self,
+        cs: &mut Composer<F>,
+        bits: &[(Variable, bool)],
+        lemmas: &[(Variable, F)],
+        leaf: (Variable, F),
+    ) -> (Variable, F) {
+        let mut node = leaf;
+        for (bit, sibling) in bits.iter().zip(lemmas) {
+            let (left, right) = if bit.1 {
+                (sibling.1, node.1)
+            } else {
+                (node.1, sibling.1)
+            };
+            let var_left = cs.select(bit.0, sibling.0, node.0);
+            let var_right = cs.select(bit.0, node.0, sibling.0);
+            node = self.mimc_block(cs, (var_left, left), (var_right, right));
+        }
+        node
+    }
+
+    /// One MiMC block `E(xl, xr)`, as `mimc::block`.
+    fn mimc_block(
+        &self,
+        cs: &mut Composer<F>,
+        xl: (Variable, F),
+        xr: (Variable, F),
+    ) -> (Variable, F) {
+        mimc_plonk::block(cs, &self.constants, xl, xr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::Zero;
+    use zkp_plonk::MockProver;
+
+    use super::super::smt::SparseMerkleTree;
+    use super::*;
+
+    #[test]
+    fn test_smt_gadgets() {
+        let depth = 8;
+        let mut smt = SparseMerkleTree::<Fr>::new(depth);
+        let (key, value) = (Fr::from(42u32), Fr::from(7u32));
+        let absent = Fr::from(43u32);
+        smt.update(&key, value);
+        let root = smt.root();
+        let gadget = SmtGadget::new(depth);
+
+        let circuit = |key: Fr, value: Option<Fr>| {
+            let mut cs = Composer::new();
+            let var_root = cs.alloc_and_assign(root);
+            let var_key = cs.alloc_and_assign(key);
+            let proof = smt.prove(&key);
+            match value {
+                Some(value) => {
+                    let var_value = cs.alloc_and_assign(value);
+                    gadget.enforce_membership(
+                        &mut cs,
+                        var_root,
+                        (var_key, key),
+                        (var_value, value),
+                        &proof,
+                    );
+                }
+                None => gadget.enforce_non_membership(&mut cs, var_root, (var_key, key), &proof),
+            }
+            MockProver::run(&cs, cs.public_inputs()).is_ok()
+        };
+        assert!(circuit(key, Some(value)));
+        assert!(!circuit(key, Some(Fr::from(8u32))));
+        assert!(!circuit(key, None));
+        assert!(circuit(absent, None));
+        assert!(!circuit(absent, Some(value)));
+
+        // insert the absent key.
+        let proof = smt.prove(&absent);
+        let old_root = smt.root();
+        smt.update(&absent, value);
+        let new_root = smt.root();
+
+        let mut cs = Composer::new();
+        let var_old_root = cs.alloc_and_assign(old_root);
+        let var_new_root = cs.alloc_and_assign(new_root);
+        let var_key = cs.alloc_and_assign(absent);
+        let var_old_value = cs.alloc_and_assign(Fr::zero());
+        let var_new_value = cs.alloc_and_assign(value);
+        cs.constrain_to_constant(var_old_value, Fr::zero(), Fr::zero());
+        gadget.enforce_update(
+            &mut cs,
+            var_old_root,
+            var_new_root,
+            (var_key, absent),
+            (var_old_value, Fr::zero()),
+            (var_new_value, value),
+            &proof,
+        );
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+    }
+}
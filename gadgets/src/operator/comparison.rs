@@ -0,0 +1,196 @@
+//! Comparisons of field elements as integers of `num_bits` bits.
+//!
+//! `a < b` is read off the top bit of `2^num_bits + a - b`, which is
+//! decomposed into `num_bits + 1` bits. The comparison is only sound for
+//! operands below `2^num_bits`, so an operand which is not known to be in
+//! range, e.g. a witness, must be checked with `enforce_range` first.
+
+use ark_ff::PrimeField;
+use zkp_r1cs::{ConstraintSystem, SynthesisError, Variable};
+
+use crate::algebra::boolean::Boolean;
+use crate::algebra::uint32::alloc_repr_bits;
+use crate::Vec;
+
+/// Allocate the `num_bits` bits of `value` and enforce that they sum to it,
+/// i.e. that `value < 2^num_bits`. Returns the bits, least significant first.
+pub fn enforce_range<F, CS>(
+    mut cs: CS,
+    value: (Variable, Option<F>),
+    num_bits: usize,
+) -> Result<Vec<Boolean>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    assert!(num_bits < F::size_in_bits());
+
+    let (bits, lc) = alloc_repr_bits(cs.ns(|| "bits"), value.1.map(|v| v.into_repr()), num_bits)?;
+    cs.enforce(|| "packing", |_| lc, |lc| lc + CS::one(), |lc| lc + value.0);
+
+    Ok(bits)
+}
+
+/// Whether `a < b`, for `a` and `b` below `2^num_bits`.
+pub fn is_lt<F, CS>(
+    cs: CS,
+    a: (Variable, Option<F>),
+    b: (Variable, Option<F>),
+    num_bits: usize,
+) -> Result<Boolean, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    // a < b iff 2^n + a - b < 2^n
+    Ok(difference_top_bit(cs, a, b, F::zero(), num_bits)?.not())
+}
+
+/// Whether `a <= b`, for `a` and `b` below `2^num_bits`.
+pub fn is_leq<F, CS>(
+    cs: CS,
+    a: (Variable, Option<F>),
+    b: (Variable, Option<F>),
+    num_bits: usize,
+) -> Result<Boolean, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    // a <= b iff 2^n + a - b - 1 < 2^n
+    Ok(difference_top_bit(cs, a, b, F::one(), num_bits)?.not())
+}
+
+/// Enforce `a < b`, for `a` and `b` below `2^num_bits`.
+pub fn assert_lt<F, CS>(
+    mut cs: CS,
+    a: (Variable, Option<F>),
+    b: (Variable, Option<F>),
+    num_bits: usize,
+) -> Result<(), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let lt = is_lt(cs.ns(|| "is_lt"), a, b, num_bits)?;
+    Boolean::enforce_equal(cs.ns(|| "a < b"), &lt, &Boolean::constant(true))
+}
+
+/// Enforce `a <= b`, for `a` and `b` below `2^num_bits`, e.g. that an amount
+/// is covered by a balance.
+pub fn assert_leq<F, CS>(
+    mut cs: CS,
+    a: (Variable, Option<F>),
+    b: (Variable, Option<F>),
+    num_bits: usize,
+) -> Result<(), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let leq = is_leq(cs.ns(|| "is_leq"), a, b, num_bits)?;
+    Boolean::enforce_equal(cs.ns(|| "a <= b"), &leq, &Boolean::constant(true))
+}
+
+/// The bit `num_bits` of `2^num_bits + a - b - offset`, which is set iff
+/// `a - b - offset` is not negative.
+fn difference_top_bit<F, CS>(
+    mut cs: CS,
+    a: (Variable, Option<F>),
+    b: (Variable, Option<F>),
+    offset: F,
+    num_bits: usize,
+) -> Result<Boolean, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    // The difference must not wrap around the field.
+    assert!(num_bits + 1 < F::size_in_bits());
+
+    let shift = F::from(2u64).pow([num_bits as u64]);
+    let value = match (a.1, b.1) {
+        (Some(a), Some(b)) => Some(shift + a - b - offset),
+        _ => None,
+    };
+
+    let (mut bits, lc) = alloc_repr_bits(
+        cs.ns(|| "difference"),
+        value.map(|v| v.into_repr()),
+        num_bits + 1,
+    )?;
+    cs.enforce(
+        || "2^n + a - b - offset = difference",
+        |lc| lc + (shift - offset, CS::one()) + a.0 - b.0,
+        |lc| lc + CS::one(),
+        |_| lc,
+    );
+
+    Ok(bits.pop().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Fr;
+    use zkp_r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::test_constraint_system::TestConstraintSystem;
+
+    fn alloc<CS: ConstraintSystem<Fr>>(
+        cs: &mut CS,
+        name: &str,
+        value: u64,
+    ) -> (Variable, Option<Fr>) {
+        let value = Fr::from(value);
+        (cs.alloc(|| name, || Ok(value)).unwrap(), Some(value))
+    }
+
+    #[test]
+    fn test_comparison() {
+        let num_bits = 8;
+        let values = [0u64, 1, 2, 100, 254, 255];
+
+        for a in values.iter() {
+            for b in values.iter() {
+                let mut cs = TestConstraintSystem::<Fr>::new();
+                let var_a = alloc(&mut cs, "a", *a);
+                let var_b = alloc(&mut cs, "b", *b);
+                enforce_range(cs.ns(|| "a range"), var_a, num_bits).unwrap();
+                enforce_range(cs.ns(|| "b range"), var_b, num_bits).unwrap();
+
+                let lt = is_lt(cs.ns(|| "a < b"), var_a, var_b, num_bits).unwrap();
+                let leq = is_leq(cs.ns(|| "a <= b"), var_a, var_b, num_bits).unwrap();
+                assert_eq!(lt.get_value(), Some(a < b));
+                assert_eq!(leq.get_value(), Some(a <= b));
+                assert!(cs.is_satisfied());
+
+                let mut cs_lt = TestConstraintSystem::<Fr>::new();
+                let var_a = alloc(&mut cs_lt, "a", *a);
+                let var_b = alloc(&mut cs_lt, "b", *b);
+                assert_lt(cs_lt.ns(|| "assert"), var_a, var_b, num_bits).unwrap();
+                assert_eq!(cs_lt.is_satisfied(), a < b);
+
+                let mut cs_leq = TestConstraintSystem::<Fr>::new();
+                let var_a = alloc(&mut cs_leq, "a", *a);
+                let var_b = alloc(&mut cs_leq, "b", *b);
+                assert_leq(cs_leq.ns(|| "assert"), var_a, var_b, num_bits).unwrap();
+                assert_eq!(cs_leq.is_satisfied(), a <= b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_enforce_range() {
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let var = alloc(&mut cs, "value", 255);
+        let bits = enforce_range(cs.ns(|| "range"), var, 8).unwrap();
+        assert_eq!(bits.len(), 8);
+        assert!(cs.is_satisfied());
+
+        let mut cs = TestConstraintSystem::<Fr>::new();
+        let var = alloc(&mut cs, "value", 256);
+        enforce_range(cs.ns(|| "range"), var, 8).unwrap();
+        assert!(!cs.is_satisfied());
+    }
+}
@@ -1,2 +1,3 @@
+pub mod comparison;
 pub mod multieq;
 pub mod rangeproof;
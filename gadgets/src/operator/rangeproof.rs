@@ -3,6 +3,7 @@ use zkp_r1cs::{
     ConstraintSynthesizer, ConstraintSystem, LinearCombination, SynthesisError, Variable,
 };
 
+use crate::algebra::witness_inverse;
 use crate::Vec;
 
 pub struct RangeProof<F: PrimeField> {
@@ -61,11 +62,7 @@ impl<F: PrimeField> ConstraintSynthesizer<F> for RangeProof<F> {
         let mut bits: Vec<Option<F>> = Vec::new();
 
         for b in BitIteratorBE::new(alpha_value.into_repr()) {
-            if b {
-                bits.push(Some(F::one()));
-            } else {
-                bits.push(Some(F::zero()));
-            }
+            bits.push(Some(F::from(b as u64)));
         }
         for i in 0..(n + 1) {
             alpha_bits.push(bits[bits.len() - 1 - i as usize]);
@@ -89,25 +86,14 @@ impl<F: PrimeField> ConstraintSynthesizer<F> for RangeProof<F> {
 
         let mut sum_value = F::zero();
         for i in 0..n {
-            if !alpha_bits[i as usize].unwrap().is_zero() {
-                sum_value.add_assign(&F::one())
-            };
+            sum_value.add_assign(&alpha_bits[i as usize].unwrap());
         }
 
-        let (inv_value, not_all_zeros) = if sum_value.is_zero() {
-            (Some(F::zero()), Some(F::zero()))
-        } else {
-            (Some(sum_value.inverse().unwrap()), Some(F::one()))
-        };
+        let inv_value = witness_inverse(&sum_value).unwrap_or_else(F::zero);
+        let not_all_zeros = F::from(!sum_value.is_zero() as u64);
 
-        let inv = cs.alloc(
-            || "inv",
-            || inv_value.ok_or(SynthesisError::AssignmentMissing),
-        )?;
-        let output = cs.alloc(
-            || "output",
-            || not_all_zeros.ok_or(SynthesisError::AssignmentMissing),
-        )?;
+        let inv = cs.alloc(|| "inv", || Ok(inv_value))?;
+        let output = cs.alloc(|| "output", || Ok(not_all_zeros))?;
 
         // 1 * (2^n + B - A) = alpha_packed
         cs.enforce(
@@ -152,7 +138,7 @@ impl<F: PrimeField> ConstraintSynthesizer<F> for RangeProof<F> {
 
         // less = less_or_eq * not_all_zeros
         let mut less_value = Some(F::one());
-        if less_or_equal_value.is_zero() || not_all_zeros.unwrap().is_zero() {
+        if less_or_equal_value.is_zero() || not_all_zeros.is_zero() {
             less_value = Some(F::zero());
         }
         let less = cs.alloc(
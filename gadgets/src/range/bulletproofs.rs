@@ -0,0 +1,171 @@
+use ark_ec::PairingEngine;
+use ark_ff::{PrimeField, Zero};
+use core::marker::PhantomData;
+use rand::Rng;
+use zkp_bulletproofs::{
+    create_circuit_generators, create_proof, verify_proof, Generators, Proof, R1csCircuit,
+};
+use zkp_r1cs::{ConstraintSynthesizer, ConstraintSystem, LinearCombination, SynthesisError};
+
+use crate::algebra::boolean::AllocatedBit;
+use crate::hashes::mimc;
+
+use super::{check_opening, Opening, RangeError};
+
+/// Proves `commitment = MiMC(value, blinding)` and `value < 2^bits`.
+///
+/// Public inputs: `commitment`.
+struct RangeCircuit<F: PrimeField> {
+    bits: usize,
+    commitment: Option<F>,
+    opening: Option<Opening<F>>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for RangeCircuit<F> {
+    fn generate_constraints<CS: ConstraintSystem<F>>(
+        self,
+        cs: &mut CS,
+    ) -> Result<(), SynthesisError> {
+        let commitment = cs.alloc_input(
+            || "commitment",
+            || self.commitment.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+
+        let value = self.opening.map(|o| o.value);
+        let value_f = value.map(F::from);
+        let var_value = cs.alloc(
+            || "value",
+            || value_f.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+        let blinding = self.opening.map(|o| o.blinding);
+        let var_blinding = cs.alloc(
+            || "blinding",
+            || blinding.ok_or(SynthesisError::AssignmentMissing),
+        )?;
+
+        let (image, _) = mimc::block_enforce(
+            cs.ns(|| "commit"),
+            (var_value, value_f),
+            (var_blinding, blinding),
+        )?;
+        cs.enforce(
+            || "commitment = MiMC(value, blinding)",
+            |lc| lc + image - commitment,
+            |lc| lc + CS::one(),
+            |lc| lc,
+        );
+
+        // value = sum(2^i * b_i)
+        let mut lc = LinearCombination::<F>::zero();
+        let mut coeff = F::one();
+        for i in 0..self.bits {
+            let bit = AllocatedBit::alloc(
+                cs.ns(|| format!("bit {}", i)),
+                value.map(|v| v >> i & 1 == 1),
+            )?;
+            lc = lc + (coeff, bit.get_variable());
+            coeff = coeff.double();
+        }
+        cs.enforce(
+            || "packing constraint",
+            |_| lc,
+            |lc| lc + CS::one(),
+            |lc| lc + var_value,
+        );
+
+        Ok(())
+    }
+}
+
+/// The range circuit for a fixed bit width, shared by prover and verifier,
+/// with the generators fixed at setup. The verifier never takes generators
+/// from the prover, who could otherwise pick them with known discrete logs.
+pub struct RangeParams<E: PairingEngine> {
+    bits: usize,
+    generators: Generators<E>,
+    r1cs: R1csCircuit<E>,
+}
+
+pub struct RangeProof<E: PairingEngine> {
+    proof: Proof<E>,
+}
+
+/// The setup of the Bulletproofs backend, which is empty: its generators are
+/// hashed onto the curve by `setup`, so no ceremony is needed and both sides
+/// can run the setup themselves.
+#[derive(Clone, Copy, Debug)]
+pub struct RangeSrs<E: PairingEngine>(PhantomData<E>);
+
+impl<E: PairingEngine> Default for RangeSrs<E> {
+    fn default() -> Self {
+        RangeSrs(PhantomData)
+    }
+}
+
+/// The degree of the setup that `setup` needs for `bits`, none here.
+pub fn srs_degree<F: PrimeField>(bits: usize) -> Result<usize, RangeError> {
+    check_bits(bits)?;
+    Ok(0)
+}
+
+fn check_bits(bits: usize) -> Result<(), RangeError> {
+    if bits == 0 || bits > 64 {
+        return Err(RangeError::UnsupportedBits(bits));
+    }
+    Ok(())
+}
+
+pub fn setup<E: PairingEngine>(
+    _srs: &RangeSrs<E>,
+    bits: usize,
+) -> Result<RangeParams<E>, RangeError> {
+    check_bits(bits)?;
+
+    // the matrices only depend on the shape, so any assignment will do.
+    let opening = Opening {
+        value: 0,
+        blinding: E::Fr::zero(),
+    };
+    let (generators, r1cs) = create_circuit_generators::<E, _>(RangeCircuit {
+        bits,
+        commitment: Some(opening.commit()),
+        opening: Some(opening),
+    })?;
+
+    Ok(RangeParams {
+        bits,
+        generators,
+        r1cs,
+    })
+}
+
+pub fn prove_range<E: PairingEngine, R: Rng>(
+    params: &RangeParams<E>,
+    value_commitment: E::Fr,
+    opening: &Opening<E::Fr>,
+    rng: &mut R,
+) -> Result<RangeProof<E>, RangeError> {
+    check_opening(value_commitment, opening, params.bits)?;
+
+    let circuit = RangeCircuit {
+        bits: params.bits,
+        commitment: Some(value_commitment),
+        opening: Some(*opening),
+    };
+    let proof = create_proof(&params.generators, circuit, rng)?;
+
+    Ok(RangeProof { proof })
+}
+
+pub fn verify_range<E: PairingEngine>(
+    params: &RangeParams<E>,
+    value_commitment: E::Fr,
+    proof: RangeProof<E>,
+) -> Result<bool, RangeError> {
+    Ok(verify_proof(
+        &params.generators,
+        &proof.proof,
+        &params.r1cs,
+        &[value_commitment],
+    )?)
+}
@@ -0,0 +1,190 @@
+//! Standalone range proofs over a committed value.
+//!
+//! The holder of `value_commitment = MiMC(value, blinding)` proves that
+//! `value < 2^bits` without revealing it. The proving backend is chosen by
+//! feature flags: the `plonk` feature selects a PLONK circuit which looks the
+//! chunks of the value up in a range table, otherwise the `bulletproofs`
+//! feature selects a Bulletproofs R1CS proof. Both backends expose the same
+//! `setup`, `prove_range` and `verify_range` functions over a `RangeSrs` of
+//! a degree of at least `srs_degree(bits)`: the PLONK one is a universal
+//! setup from a ceremony, while the Bulletproofs one is empty, as its
+//! generators are hashed onto the curve.
+
+use ark_ff::PrimeField;
+use core::fmt;
+use zkp_r1cs::SynthesisError;
+
+use crate::hashes::mimc;
+use crate::String;
+
+#[cfg(feature = "plonk")]
+mod plonk;
+#[cfg(feature = "plonk")]
+pub use self::plonk::{
+    prove_range, setup, srs_degree, verify_range, RangeParams, RangeProof, RangeSrs,
+};
+
+#[cfg(all(feature = "bulletproofs", not(feature = "plonk")))]
+mod bulletproofs;
+#[cfg(all(feature = "bulletproofs", not(feature = "plonk")))]
+pub use self::bulletproofs::{
+    prove_range, setup, srs_degree, verify_range, RangeParams, RangeProof, RangeSrs,
+};
+
+/// The opening of a value commitment, known only to the prover.
+#[derive(Clone, Copy, Debug)]
+pub struct Opening<F: PrimeField> {
+    pub value: u64,
+    pub blinding: F,
+}
+
+impl<F: PrimeField> Opening<F> {
+    pub fn commit(&self) -> F {
+        commit_value(self.value, self.blinding)
+    }
+}
+
+/// Commit to `value` as `MiMC(value, blinding)`.
+pub fn commit_value<F: PrimeField>(value: u64, blinding: F) -> F {
+    mimc::block(F::from(value), blinding)
+}
+
+#[derive(Debug)]
+pub enum RangeError {
+    /// The backend does not support this bit width.
+    UnsupportedBits(usize),
+    /// The opened value does not fit in the proven bit width.
+    ValueOutOfRange,
+    /// The opening does not match the value commitment.
+    CommitmentMismatch,
+    Synthesis(SynthesisError),
+    Backend(String),
+}
+
+impl From<SynthesisError> for RangeError {
+    fn from(e: SynthesisError) -> RangeError {
+        RangeError::Synthesis(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RangeError {}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            RangeError::UnsupportedBits(bits) => write!(f, "unsupported range of {} bits", bits),
+            RangeError::ValueOutOfRange => write!(f, "value is out of range"),
+            RangeError::CommitmentMismatch => write!(f, "opening does not match commitment"),
+            RangeError::Synthesis(err) => write!(f, "synthesis error: {}", err),
+            RangeError::Backend(err) => write!(f, "backend error: {}", err),
+        }
+    }
+}
+
+/// Check the opening against the commitment and the bit width, before proving.
+pub(crate) fn check_opening<F: PrimeField>(
+    value_commitment: F,
+    opening: &Opening<F>,
+    bits: usize,
+) -> Result<(), RangeError> {
+    if bits < 64 && opening.value >> bits != 0 {
+        return Err(RangeError::ValueOutOfRange);
+    }
+    if opening.commit() != value_commitment {
+        return Err(RangeError::CommitmentMismatch);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::{Bls12_381 as E, Fr};
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+
+    use super::*;
+
+    #[cfg(feature = "plonk")]
+    fn params<R: rand::Rng>(bits: usize, rng: &mut R) -> RangeParams<E> {
+        use ark_poly::univariate::DensePolynomial;
+        use ark_poly_commit::{marlin_pc::MarlinKZG10, PolynomialCommitment};
+
+        // a test setup, whose trapdoor the test knows.
+        let degree = srs_degree::<Fr>(bits).unwrap();
+        let srs = MarlinKZG10::<E, DensePolynomial<Fr>>::setup(degree, None, rng).unwrap();
+        setup::<E>(&srs, bits).unwrap()
+    }
+
+    #[cfg(not(feature = "plonk"))]
+    fn params<R: rand::Rng>(bits: usize, _rng: &mut R) -> RangeParams<E> {
+        setup::<E>(&RangeSrs::default(), bits).unwrap()
+    }
+
+    #[test]
+    fn test_range_proof() {
+        let rng = &mut test_rng();
+        for &(bits, value) in &[(16, 40_000), (12, 4_000), (5, 31), (64, u64::MAX)] {
+            let params = params(bits, rng);
+
+            let opening = Opening {
+                value,
+                blinding: Fr::rand(rng),
+            };
+            let commitment = opening.commit();
+            let proof = prove_range(&params, commitment, &opening, rng).unwrap();
+            assert!(verify_range(&params, commitment, proof).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_range_proof_wrong_commitment() {
+        let rng = &mut test_rng();
+        let params = params(16, rng);
+
+        let opening = Opening {
+            value: 40_000,
+            blinding: Fr::rand(rng),
+        };
+        let commitment = opening.commit();
+        let proof = prove_range(&params, commitment, &opening, rng).unwrap();
+        let other = commit_value(opening.value + 1, opening.blinding);
+        assert!(!verify_range(&params, other, proof).unwrap());
+    }
+
+    #[test]
+    fn test_range_proof_other_width() {
+        let rng = &mut test_rng();
+        let other = params(32, rng);
+        let params = params(16, rng);
+
+        let opening = Opening {
+            value: 40_000,
+            blinding: Fr::rand(rng),
+        };
+        let commitment = opening.commit();
+        let proof = prove_range(&params, commitment, &opening, rng).unwrap();
+        assert!(!verify_range(&other, commitment, proof).unwrap_or(false));
+    }
+
+    #[test]
+    fn test_range_proof_bad_opening() {
+        let rng = &mut test_rng();
+        let params = params(16, rng);
+
+        let opening = Opening {
+            value: 1 << 16,
+            blinding: Fr::rand(rng),
+        };
+        let commitment = opening.commit();
+        assert!(prove_range(&params, commitment, &opening, rng).is_err());
+
+        let opening = Opening {
+            value: 1,
+            blinding: Fr::rand(rng),
+        };
+        let other = commit_value(2, opening.blinding);
+        assert!(prove_range(&params, other, &opening, rng).is_err());
+    }
+}
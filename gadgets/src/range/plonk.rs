@@ -0,0 +1,166 @@
+use ark_ec::PairingEngine;
+use ark_ff::{PrimeField, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use blake2::Blake2s;
+use rand::Rng;
+use zkp_plonk::{Composer, Plonk, Proof, ProverKey, UniversalParams, VerifierKey};
+
+use crate::hashes::mimc;
+use crate::Vec;
+
+use super::{check_opening, Opening, RangeError};
+
+type PC<E> = MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>;
+type PlonkInst<E> = Plonk<<E as PairingEngine>::Fr, Blake2s, PC<E>>;
+
+/// The setup of the PLONK backend, a universal setup from a ceremony.
+pub type RangeSrs<E> = UniversalParams<<E as PairingEngine>::Fr, PC<E>>;
+
+fn ks<F: PrimeField>() -> [F; 4] {
+    [F::one(), F::from(7u64), F::from(13u64), F::from(17u64)]
+}
+
+/// The bits of a chunk checked by one lookup. A table of `2^8` rows keeps
+/// the domain small, while a 64-bit value takes only 8 lookups.
+const CHUNK_BITS: usize = 8;
+
+fn check_bits(bits: usize) -> Result<(), RangeError> {
+    if bits == 0 || bits > 64 {
+        return Err(RangeError::UnsupportedBits(bits));
+    }
+    Ok(())
+}
+
+/// Builds the circuit proving `commitment = MiMC(value, blinding)` and
+/// `value < 2^bits`, with the chunks of `value` looked up in a range table.
+/// The commitment is the public input of the first gate.
+fn circuit<F: PrimeField>(
+    bits: usize,
+    commitment: F,
+    opening: &Opening<F>,
+) -> Result<Composer<F>, RangeError> {
+    let mut cs = Composer::new();
+    let constants = mimc::constants::<F>();
+    let chunk_bits = bits.min(CHUNK_BITS) as u32;
+    let table = cs.add_range_table(chunk_bits);
+
+    let var_commitment = cs.alloc_and_assign(commitment);
+    cs.constrain_to_constant(var_commitment, F::zero(), commitment);
+
+    let value = F::from(opening.value);
+    let var_value = cs.alloc_and_assign(value);
+    cs.decompose_with_table(var_value, bits as u32, table, chunk_bits)
+        .map_err(|_| RangeError::ValueOutOfRange)?;
+
+    let (mut xl, mut xr) = (value, opening.blinding);
+    let (mut var_xl, mut var_xr) = (var_value, cs.alloc_and_assign(xr));
+    for c in constants.iter() {
+        // t = xL + Ci
+        let t = xl + c;
+        let var_t = cs.alloc_and_assign(t);
+        cs.create_add_gate((var_xl, F::one()), (var_xl, F::zero()), var_t, None, *c, F::zero());
+
+        // t2 = t^2
+        let t2 = t.square();
+        let var_t2 = cs.alloc_and_assign(t2);
+        cs.create_mul_gate(var_t, var_t, var_t2, None, F::one(), F::zero(), F::zero());
+
+        // new_xL = t2 * t + xR
+        let new_xl = t2 * t + xr;
+        let var_new_xl = cs.alloc_and_assign(new_xl);
+        cs.create_mul_gate(
+            var_t2,
+            var_t,
+            var_new_xl,
+            Some((var_xr, F::one())),
+            F::one(),
+            F::zero(),
+            F::zero(),
+        );
+
+        xr = xl;
+        var_xr = var_xl;
+        xl = new_xl;
+        var_xl = var_new_xl;
+    }
+    cs.assert_equal(var_xl, var_commitment);
+
+    Ok(cs)
+}
+
+/// The PLONK keys for a fixed bit width, shared by prover and verifier.
+pub struct RangeParams<E: PairingEngine> {
+    bits: usize,
+    size: usize,
+    pk: ProverKey<E::Fr, PC<E>>,
+    vk: VerifierKey<E::Fr, PC<E>>,
+}
+
+pub struct RangeProof<E: PairingEngine> {
+    proof: Proof<E::Fr, PC<E>>,
+}
+
+/// The shape of the circuit for `bits`, which does not depend on the value.
+fn shape<F: PrimeField>(bits: usize) -> Result<Composer<F>, RangeError> {
+    let opening = Opening {
+        value: 0,
+        blinding: F::zero(),
+    };
+    circuit(bits, opening.commit(), &opening)
+}
+
+/// The degree of the universal setup that `setup` needs for `bits`.
+pub fn srs_degree<F: PrimeField>(bits: usize) -> Result<usize, RangeError> {
+    check_bits(bits)?;
+    shape::<F>(bits)?
+        .srs_degree()
+        .map_err(|e| RangeError::Backend(format!("{:?}", e)))
+}
+
+/// The keys for `bits`, committed with `srs`. It is a universal setup from a
+/// ceremony, e.g. loaded with `Plonk::load_srs`, of a degree of at least
+/// `srs_degree(bits)`: whoever knows its trapdoor can forge range proofs.
+pub fn setup<E: PairingEngine>(
+    srs: &RangeSrs<E>,
+    bits: usize,
+) -> Result<RangeParams<E>, RangeError> {
+    check_bits(bits)?;
+
+    // the selectors only depend on the shape, so any assignment will do.
+    let cs = shape::<E::Fr>(bits)?;
+    let size = cs.size();
+    let (pk, vk) = PlonkInst::<E>::keygen(srs, &cs, &ks())
+        .map_err(|e| RangeError::Backend(format!("{:?}", e)))?;
+
+    Ok(RangeParams { bits, size, pk, vk })
+}
+
+pub fn prove_range<E: PairingEngine, R: Rng>(
+    params: &RangeParams<E>,
+    value_commitment: E::Fr,
+    opening: &Opening<E::Fr>,
+    rng: &mut R,
+) -> Result<RangeProof<E>, RangeError> {
+    check_opening(value_commitment, opening, params.bits)?;
+
+    let cs = circuit(params.bits, value_commitment, opening)?;
+    let proof = PlonkInst::<E>::prove(&params.pk, &cs, rng)
+        .map_err(|e| RangeError::Backend(format!("{:?}", e)))?;
+
+    Ok(RangeProof { proof })
+}
+
+pub fn verify_range<E: PairingEngine>(
+    params: &RangeParams<E>,
+    value_commitment: E::Fr,
+    proof: RangeProof<E>,
+) -> Result<bool, RangeError> {
+    // the commitment is bound by the first gate, as `var - pi = 0`.
+    let mut public_inputs = Vec::with_capacity(params.size);
+    public_inputs.push(-value_commitment);
+    public_inputs.resize(params.size, E::Fr::zero());
+
+    PlonkInst::<E>::verify(&params.vk, &public_inputs, proof.proof)
+        .map_err(|e| RangeError::Backend(format!("{:?}", e)))
+}
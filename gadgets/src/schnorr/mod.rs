@@ -0,0 +1,116 @@
+//! Schnorr signatures over a twisted Edwards curve embedded in the field,
+//! e.g. Jubjub over BLS12-381, and their verification on the PLONK composer
+//! (see `plonk`), far cheaper than ECDSA over an emulated field.
+//!
+//! The challenge is a MiMC chain over `R`, the public key and the message,
+//! which are all elements of the field, so it is computed natively in the
+//! circuit, and taken to a scalar by its bits.
+
+use ark_ec::{
+    models::TEModelParameters, twisted_edwards_extended::GroupAffine, AffineCurve, ProjectiveCurve,
+};
+use ark_ff::{BigInteger, PrimeField, UniformRand, Zero};
+use rand::Rng;
+
+use crate::hashes::mimc;
+
+#[cfg(feature = "plonk")]
+pub mod plonk;
+
+/// A Schnorr signature `(R, s)` with `s * G = R + H(R, pk, m) * pk`.
+pub struct Signature<P: TEModelParameters> {
+    pub r: GroupAffine<P>,
+    pub s: P::ScalarField,
+}
+
+impl<P: TEModelParameters> Clone for Signature<P> {
+    fn clone(&self) -> Self {
+        Signature {
+            r: self.r,
+            s: self.s,
+        }
+    }
+}
+
+/// Fiat-Shamir challenge of the signature, kept in the circuit field.
+pub fn challenge<F: PrimeField, P: TEModelParameters<BaseField = F>>(
+    r: &GroupAffine<P>,
+    pk: &GroupAffine<P>,
+    message: F,
+) -> F {
+    [r.x, r.y, pk.x, pk.y, message]
+        .iter()
+        .fold(F::zero(), |h, v| mimc::block(h, *v))
+}
+
+/// The challenge as a scalar, i.e. its bits modulo the group order.
+pub fn challenge_to_scalar<F: PrimeField, P: TEModelParameters<BaseField = F>>(
+    c: F,
+) -> P::ScalarField {
+    P::ScalarField::from_le_bytes_mod_order(&c.into_repr().to_bytes_le())
+}
+
+pub fn public_key<P: TEModelParameters>(secret_key: &P::ScalarField) -> GroupAffine<P> {
+    GroupAffine::<P>::prime_subgroup_generator()
+        .mul(secret_key.into_repr())
+        .into_affine()
+}
+
+pub fn sign<F, P, R>(secret_key: &P::ScalarField, message: F, rng: &mut R) -> Signature<P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    R: Rng,
+{
+    let k = P::ScalarField::rand(rng);
+    let r = public_key::<P>(&k);
+    let pk = public_key::<P>(secret_key);
+    let c = challenge_to_scalar::<F, P>(challenge(&r, &pk, message));
+
+    Signature {
+        r,
+        s: k + &(c * secret_key),
+    }
+}
+
+/// Whether `signature` of `message` is valid for `public_key`, i.e.
+/// `s * G = R + c * pk`.
+pub fn verify<F, P>(public_key: &GroupAffine<P>, message: F, signature: &Signature<P>) -> bool
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let c = challenge_to_scalar::<F, P>(challenge(&signature.r, public_key, message));
+
+    let lhs = GroupAffine::<P>::prime_subgroup_generator().mul(signature.s.into_repr());
+    let rhs = signature.r.into_projective() + &public_key.mul(c.into_repr());
+    !public_key.is_zero() && lhs == rhs
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ed_on_bls12_381::{EdwardsParameters, Fq, Fr};
+    use ark_ff::One;
+    use ark_std::test_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_schnorr() {
+        let rng = &mut test_rng();
+        let secret_key = Fr::rand(rng);
+        let pk = public_key::<EdwardsParameters>(&secret_key);
+        let message = Fq::from(42u32);
+
+        let signature = sign::<_, EdwardsParameters, _>(&secret_key, message, rng);
+        assert!(verify(&pk, message, &signature));
+        assert!(!verify(&pk, message + Fq::one(), &signature));
+
+        let other = public_key::<EdwardsParameters>(&(secret_key + Fr::one()));
+        assert!(!verify(&other, message, &signature));
+
+        let mut forged = signature.clone();
+        forged.s += Fr::one();
+        assert!(!verify(&pk, message, &forged));
+    }
+}
@@ -0,0 +1,113 @@
+//! Schnorr verification on the PLONK composer, with the curve gates of the
+//! composer and MiMC blocks of arithmetic gates for the challenge.
+//!
+//! The public key is a variable, so the caller either makes it public or
+//! binds it otherwise, e.g. to a leaf of a Merkle tree. It must be in the
+//! prime order subgroup, as the challenge multiplies it by its bits and not
+//! by its value modulo the group order.
+
+use ark_ec::{models::TEModelParameters, twisted_edwards_extended::GroupAffine, AffineCurve};
+use ark_ff::{BigInteger, PrimeField};
+use zkp_plonk::{Composer, ComposerError, Point, Variable};
+
+use crate::hashes::{mimc, mimc_plonk};
+
+use super::Signature;
+
+pub struct SchnorrGadget<F: PrimeField> {
+    constants: [F; mimc::MIMC_ROUNDS],
+}
+
+impl<F: PrimeField> SchnorrGadget<F> {
+    pub fn new() -> Self {
+        SchnorrGadget {
+            constants: mimc::constants(),
+        }
+    }
+
+    /// Enforce that `signature` is a signature of `message` for
+    /// `public_key`: `s * G = R + H(R, pk, m) * pk`.
+    pub fn enforce_verify<P: TEModelParameters<BaseField = F>>(
+        &self,
+        cs: &mut Composer<F>,
+        public_key: (Point, GroupAffine<P>),
+        message: (Variable, F),
+        signature: &Signature<P>,
+    ) -> Result<(), ComposerError> {
+        let (pk, pk_value) = public_key;
+        let r = cs.alloc_point(&signature.r);
+
+        // c = H(R, pk, message), a MiMC chain from zero
+        let zero = cs.alloc_and_assign(F::zero());
+        cs.constrain_to_constant(zero, F::zero(), F::zero());
+        let inputs = [
+            (r.x(), signature.r.x),
+            (r.y(), signature.r.y),
+            (pk.x(), pk_value.x),
+            (pk.y(), pk_value.y),
+            message,
+        ];
+        let c = inputs.iter().fold((zero, F::zero()), |h, input| {
+            mimc_plonk::block(cs, &self.constants, h, *input)
+        });
+
+        let c_bits = cs.decompose(c.0, F::size_in_bits() as u32)?;
+
+        // s is below the order of the prime subgroup, hence below the modulus.
+        let s = F::from_le_bytes_mod_order(&signature.s.into_repr().to_bytes_le());
+        let s = cs.alloc_and_assign(s);
+        let s_bits = cs.decompose(s, P::ScalarField::size_in_bits() as u32)?;
+
+        // s * G = R + c * pk
+        let g = cs.constant_point(&GroupAffine::<P>::prime_subgroup_generator());
+        let lhs = cs.mul_point::<P>(g, &s_bits);
+        let c_pk = cs.mul_point::<P>(pk, &c_bits);
+        let rhs = cs.add_points::<P>(r, c_pk);
+        cs.assert_equal(lhs.x(), rhs.x());
+        cs.assert_equal(lhs.y(), rhs.y());
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> Default for SchnorrGadget<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ed_on_bls12_381::{EdwardsParameters, Fq, Fr};
+    use ark_ff::{One, UniformRand};
+    use ark_std::test_rng;
+    use zkp_plonk::MockProver;
+
+    use super::super::{public_key, sign};
+    use super::*;
+
+    #[test]
+    fn test_schnorr_gadget() {
+        let rng = &mut test_rng();
+        let secret_key = Fr::rand(rng);
+        let pk = public_key::<EdwardsParameters>(&secret_key);
+        let message = Fq::from(42u32);
+        let signature = sign::<_, EdwardsParameters, _>(&secret_key, message, rng);
+        let gadget = SchnorrGadget::new();
+
+        let circuit = |message: Fq, signature: &Signature<EdwardsParameters>| {
+            let mut cs = Composer::new();
+            let var_pk = cs.alloc_point(&pk);
+            let var_message = cs.alloc_and_assign(message);
+            gadget
+                .enforce_verify(&mut cs, (var_pk, pk), (var_message, message), signature)
+                .unwrap();
+            MockProver::run(&cs, cs.public_inputs()).is_ok()
+        };
+        assert!(circuit(message, &signature));
+        assert!(!circuit(message + Fq::one(), &signature));
+
+        let mut forged = signature.clone();
+        forged.s += Fr::one();
+        assert!(!circuit(message, &forged));
+    }
+}
@@ -418,6 +418,110 @@ impl<F: PrimeField> ConstraintSystem<F> for TestConstraintSystem<F> {
     }
 }
 
+/// Constraint system checking each constraint as it is enforced, without
+/// keeping it nor naming variables, for circuits too large for
+/// `TestConstraintSystem`.
+pub struct CheckingConstraintSystem<F: PrimeField> {
+    current_namespace: Vec<String>,
+    inputs: Vec<F>,
+    aux: Vec<F>,
+    num_constraints: usize,
+    unsatisfied: Option<String>,
+}
+
+#[allow(dead_code)]
+impl<F: PrimeField> CheckingConstraintSystem<F> {
+    pub fn new() -> CheckingConstraintSystem<F> {
+        CheckingConstraintSystem {
+            current_namespace: vec![],
+            inputs: vec![F::one()],
+            aux: vec![],
+            num_constraints: 0,
+            unsatisfied: None,
+        }
+    }
+
+    /// The path of the first unsatisfied constraint.
+    pub fn which_is_unsatisfied(&self) -> Option<&str> {
+        self.unsatisfied.as_deref()
+    }
+
+    pub fn is_satisfied(&self) -> bool {
+        self.unsatisfied.is_none()
+    }
+
+    fn eval(&self, lc: &LinearCombination<F>) -> F {
+        lc.as_ref()
+            .iter()
+            .map(|(var, coeff)| match var.get_unchecked() {
+                Index::Input(index) => self.inputs[index] * coeff,
+                Index::Aux(index) => self.aux[index] * coeff,
+            })
+            .sum()
+    }
+}
+
+impl<F: PrimeField> ConstraintSystem<F> for CheckingConstraintSystem<F> {
+    type Root = Self;
+
+    fn alloc<T, A, AR>(&mut self, _: A, f: T) -> Result<Variable, SynthesisError>
+    where
+        T: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux.push(f()?);
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<T, A, AR>(&mut self, _: A, f: T) -> Result<Variable, SynthesisError>
+    where
+        T: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inputs.push(f()?);
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len() - 1)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        self.num_constraints += 1;
+        let a = self.eval(&a(LinearCombination::zero()));
+        let b = self.eval(&b(LinearCombination::zero()));
+        let c = self.eval(&c(LinearCombination::zero()));
+        if a * b != c && self.unsatisfied.is_none() {
+            self.unsatisfied = Some(compute_path(&self.current_namespace, annotation().into()));
+        }
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.current_namespace.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self) {
+        assert!(self.current_namespace.pop().is_some());
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+}
+
 #[test]
 fn test_cs() {
     use ark_bls12_381::Fr;
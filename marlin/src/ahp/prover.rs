@@ -1,7 +1,7 @@
 use ark_ff::{fields, PrimeField, Zero};
 use ark_poly::{polynomial::univariate::DensePolynomial, UVPolynomial};
 use ark_poly::{EvaluationDomain, Evaluations as EvaluationsOnDomain, GeneralEvaluationDomain};
-use ark_std::{cfg_into_iter, cfg_iter, cfg_iter_mut};
+use ark_std::{cfg_into_iter, cfg_iter_mut};
 use rand::RngCore;
 use zkp_r1cs::{ConstraintSynthesizer, SynthesisError};
 
@@ -10,14 +10,14 @@ use rayon::prelude::*;
 
 use crate::{ToString, Vec};
 
-use crate::ahp::arithmetic::BivariatePoly;
+use crate::ahp::arithmetic::{BivariatePoly, MatrixPolynomials};
 use crate::ahp::constraint_systems::ProverConstraintSystem;
 use crate::ahp::indexer::{Index, IndexInfo};
 use crate::ahp::verifier::{VerifierFirstMsg, VerifierSecondMsg};
 use crate::ahp::{Error, AHP};
 use crate::pc::LabeledPolynomial;
 
-pub struct ProverState<'a, 'b, F: PrimeField> {
+pub struct ProverState<'a, F: PrimeField> {
     index: &'a Index<'a, F>,
 
     formatted_input_assignment: Vec<F>,
@@ -26,10 +26,6 @@ pub struct ProverState<'a, 'b, F: PrimeField> {
     z_a: Vec<F>,
     z_b: Vec<F>,
 
-    w: Option<LabeledPolynomial<'b, F>>,
-    mask: Option<LabeledPolynomial<'b, F>>,
-    z_m: Option<(LabeledPolynomial<'b, F>, LabeledPolynomial<'b, F>)>,
-
     verifier_msg: Option<VerifierFirstMsg<F>>,
 
     zk_bound: usize,
@@ -37,9 +33,53 @@ pub struct ProverState<'a, 'b, F: PrimeField> {
     domain_x: GeneralEvaluationDomain<F>,
     domain_h: GeneralEvaluationDomain<F>,
     domain_k: GeneralEvaluationDomain<F>,
+
+    buffers: BufferPool<F>,
+}
+
+/// Evaluation vectors that the quotient computations of the second and
+/// third rounds hand back once used, to be taken again by the next
+/// computation instead of allocated.
+#[derive(Default)]
+struct BufferPool<F> {
+    buffers: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> BufferPool<F> {
+    /// `len` zeros, in the smallest held buffer that is large enough.
+    fn take(&mut self, len: usize) -> Vec<F> {
+        let mut buffer = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.capacity() >= len)
+            .min_by_key(|(_, b)| b.capacity())
+            .map(|(i, _)| i)
+            .map(|i| self.buffers.swap_remove(i))
+            .unwrap_or_else(|| Vec::with_capacity(len));
+        buffer.clear();
+        buffer.resize(len, F::zero());
+        buffer
+    }
+
+    fn give(&mut self, buffers: impl IntoIterator<Item = Vec<F>>) {
+        self.buffers.extend(buffers);
+    }
+
+    /// `poly.evaluate_over_domain_by_ref(domain)`, into a held buffer.
+    fn evaluate(
+        &mut self,
+        poly: &DensePolynomial<F>,
+        domain: GeneralEvaluationDomain<F>,
+    ) -> Vec<F> {
+        let mut evals = self.take(domain.size());
+        evals[..poly.coeffs.len()].copy_from_slice(&poly.coeffs);
+        domain.fft_in_place(&mut evals);
+        evals
+    }
 }
 
-impl<'a, 'b, F: PrimeField> ProverState<'a, 'b, F> {
+impl<'a, F: PrimeField> ProverState<'a, F> {
     pub fn public_input(&self) -> Vec<F> {
         self.formatted_input_assignment[1..].to_vec()
     }
@@ -83,10 +123,10 @@ impl<'b, F: PrimeField> ProverThirdOracles<'b, F> {
 
 impl<F: PrimeField> AHP<F> {
     // add assignments
-    pub fn prover_init<'a, 'b, C: ConstraintSynthesizer<F>>(
+    pub fn prover_init<'a, C: ConstraintSynthesizer<F>>(
         index: &'a Index<'a, F>,
         c: C,
-    ) -> Result<ProverState<'a, 'b, F>, Error> {
+    ) -> Result<ProverState<'a, F>, Error> {
         let mut pcs = ProverConstraintSystem::new();
         c.generate_constraints(&mut pcs)?;
         pcs.make_matrices_square();
@@ -135,22 +175,20 @@ impl<F: PrimeField> AHP<F> {
             witness_assignment,
             z_a: z_a,
             z_b: z_b,
-            w: None,
-            mask: None,
-            z_m: None,
             verifier_msg: None,
             zk_bound,
             domain_x,
             domain_h,
             domain_k,
+            buffers: BufferPool::default(),
         })
     }
 
     // polynomial w, z_a, z_b, mask
     pub fn prover_first_round<'a, 'b, R: RngCore>(
-        mut state: ProverState<'a, 'b, F>,
+        mut state: ProverState<'a, F>,
         rng: &mut R,
-    ) -> Result<(ProverState<'a, 'b, F>, ProverFirstOracles<'b, F>), Error> {
+    ) -> Result<(ProverState<'a, F>, ProverFirstOracles<'b, F>), Error> {
         let zk_bound = state.zk_bound;
         let domain_h = state.domain_h;
         let domain_x = state.domain_x;
@@ -166,13 +204,9 @@ impl<F: PrimeField> AHP<F> {
 
         let ratio = domain_h.size() / domain_x.size();
 
-        let mut w_extended = state.witness_assignment.clone();
-        w_extended.extend(vec![
-            F::zero();
-            domain_h.size()
-                - domain_x.size()
-                - state.witness_assignment.len()
-        ]);
+        // the witness, z_a and z_b are only needed in this round.
+        let mut w_extended = core::mem::take(&mut state.witness_assignment);
+        w_extended.resize(domain_h.size() - domain_x.size(), F::zero());
         let w_evals_on_h = cfg_into_iter!(0..domain_h.size())
             .map(|i| {
                 if i % ratio == 0 {
@@ -191,12 +225,12 @@ impl<F: PrimeField> AHP<F> {
         let (w_poly, remainder) = w_poly.divide_by_vanishing_poly(domain_x).unwrap();
         assert!(remainder.is_zero());
 
-        let z_a_poly = &EvaluationsOnDomain::from_vec_and_domain(state.z_a.clone(), domain_h)
-            .interpolate()
+        let z_a = core::mem::take(&mut state.z_a);
+        let z_a_poly = &EvaluationsOnDomain::from_vec_and_domain(z_a, domain_h).interpolate()
             + &(&DensePolynomial::<F>::rand(zk_bound - 1, rng) * &v_h);
 
-        let z_b_poly = &EvaluationsOnDomain::from_vec_and_domain(state.z_b.clone(), domain_h)
-            .interpolate()
+        let z_b = core::mem::take(&mut state.z_b);
+        let z_b_poly = &EvaluationsOnDomain::from_vec_and_domain(z_b, domain_h).interpolate()
             + &(&DensePolynomial::<F>::rand(zk_bound - 1, rng) * &v_h);
 
         let mask_degree = 3 * domain_h.size() + 2 * zk_bound - 3;
@@ -204,20 +238,14 @@ impl<F: PrimeField> AHP<F> {
         let sigma = (mask_poly.divide_by_vanishing_poly(domain_h).unwrap().1)[0]; // r_0
         mask_poly[0] -= sigma; // forcing r_0 = 0, sum_over_h(mask_poly) = 0
 
-        let w = LabeledPolynomial::new_owned("w".to_string(), w_poly, None, Some(zk_bound));
-        let z_a = LabeledPolynomial::new_owned("z_a".to_string(), z_a_poly, None, Some(zk_bound));
-        let z_b = LabeledPolynomial::new_owned("z_b".to_string(), z_b_poly, None, Some(zk_bound));
-        let mask = LabeledPolynomial::new_owned("mask".to_string(), mask_poly, None, None);
-
+        // the oracles own the polynomials; the later rounds, the commitments
+        // and the openings borrow them.
         let oracles = ProverFirstOracles {
-            w: w.clone(),
-            z_a: z_a.clone(),
-            z_b: z_b.clone(),
-            mask: mask.clone(),
+            w: LabeledPolynomial::new_owned("w".to_string(), w_poly, None, Some(zk_bound)),
+            z_a: LabeledPolynomial::new_owned("z_a".to_string(), z_a_poly, None, Some(zk_bound)),
+            z_b: LabeledPolynomial::new_owned("z_b".to_string(), z_b_poly, None, Some(zk_bound)),
+            mask: LabeledPolynomial::new_owned("mask".to_string(), mask_poly, None, None),
         };
-        state.w = Some(w);
-        state.mask = Some(mask);
-        state.z_m = Some((z_a, z_b));
         Ok((state, oracles))
     }
 
@@ -228,9 +256,10 @@ impl<F: PrimeField> AHP<F> {
     }
 
     pub fn prover_second_round<'a, 'b>(
-        mut state: ProverState<'a, 'b, F>,
+        mut state: ProverState<'a, F>,
+        first_oracles: &ProverFirstOracles<'_, F>,
         verifier_msg: &VerifierFirstMsg<F>,
-    ) -> Result<(ProverState<'a, 'b, F>, ProverSecondOracles<'b, F>), Error> {
+    ) -> Result<(ProverState<'a, F>, ProverSecondOracles<'b, F>), Error> {
         let domain_h = state.domain_h;
         let domain_x = GeneralEvaluationDomain::new(state.formatted_input_assignment.len())
             .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
@@ -242,7 +271,7 @@ impl<F: PrimeField> AHP<F> {
         } = *verifier_msg;
 
         // z_m
-        let (z_a, z_b) = state.z_m.as_ref().unwrap();
+        let ProverFirstOracles { w, z_a, z_b, mask } = first_oracles;
         let z_c_poly = z_a.polynomial() * z_b.polynomial();
         let mut m_coeffs = z_c_poly.coeffs;
         cfg_iter_mut!(m_coeffs).for_each(|c| *c *= &eta_c);
@@ -274,13 +303,12 @@ impl<F: PrimeField> AHP<F> {
             domain_x,
         )
         .interpolate();
-        let w_poly = state.w.as_ref().unwrap();
-        let mut z_poly = w_poly.polynomial().mul_by_vanishing_poly(domain_x);
+        let mut z_poly = w.polynomial().mul_by_vanishing_poly(domain_x);
         cfg_iter_mut!(z_poly.coeffs)
             .zip(&x_poly.coeffs)
             .for_each(|(z, x)| *z += x);
         // h_1, g_1
-        let mask_poly = state.mask.as_ref().unwrap().polynomial();
+        let mask_poly = mask.polynomial();
         let domain_size = *[
             mask_poly.coeffs.len(),
             r_alpha_poly.coeffs.len() + m_poly.coeffs.len(),
@@ -291,19 +319,23 @@ impl<F: PrimeField> AHP<F> {
         .unwrap();
         let domain = GeneralEvaluationDomain::new(domain_size)
             .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-        let mut r_alpha_evals = r_alpha_poly.evaluate_over_domain_by_ref(domain);
-        let m_evals = m_poly.evaluate_over_domain_by_ref(domain);
-        let t_evals = t_poly.evaluate_over_domain_by_ref(domain);
-        let z_evals = z_poly.evaluate_over_domain_by_ref(domain);
-        cfg_iter_mut!(r_alpha_evals.evals)
-            .zip(&m_evals.evals)
-            .zip(&t_evals.evals)
-            .zip(z_evals.evals)
+        let buffers = &mut state.buffers;
+        let mut r_alpha_evals = buffers.evaluate(&r_alpha_poly, domain);
+        let m_evals = buffers.evaluate(&m_poly, domain);
+        let t_evals = buffers.evaluate(&t_poly, domain);
+        let z_evals = buffers.evaluate(&z_poly, domain);
+        cfg_iter_mut!(r_alpha_evals)
+            .zip(&m_evals)
+            .zip(&t_evals)
+            .zip(&z_evals)
             .for_each(|(((r, m), t), z)| {
                 *r *= m;
                 *r -= *t * z
             });
-        let q_1_poly = mask_poly + &r_alpha_evals.interpolate();
+        domain.ifft_in_place(&mut r_alpha_evals);
+        let sum_poly = DensePolynomial::from_coefficients_vec(r_alpha_evals);
+        let q_1_poly = mask_poly + &sum_poly;
+        buffers.give(vec![sum_poly.coeffs, m_evals, t_evals, z_evals]);
         let (h_1_poly, x_g_1_poly) = q_1_poly.divide_by_vanishing_poly(domain_h).unwrap();
         let g_1_poly = DensePolynomial::from_coefficients_slice(&x_g_1_poly.coeffs[1..]);
         let oracles = ProverSecondOracles {
@@ -329,7 +361,7 @@ impl<F: PrimeField> AHP<F> {
     }
 
     pub fn prover_third_round<'a, 'b>(
-        state: ProverState<'a, 'b, F>,
+        state: ProverState<'a, F>,
         verifier_msg: &VerifierSecondMsg<F>,
     ) -> Result<ProverThirdOracles<'b, F>, Error> {
         let beta = verifier_msg.beta;
@@ -338,6 +370,7 @@ impl<F: PrimeField> AHP<F> {
             domain_h,
             domain_k,
             verifier_msg,
+            mut buffers,
             ..
         } = state;
         let VerifierFirstMsg {
@@ -354,13 +387,13 @@ impl<F: PrimeField> AHP<F> {
             &index.c_star_polys,
         );
 
-        let mut inverse_a = Vec::with_capacity(domain_k.size());
-        let mut inverse_b = Vec::with_capacity(domain_k.size());
-        let mut inverse_c = Vec::with_capacity(domain_k.size());
+        let mut inverse_a = buffers.take(domain_k.size());
+        let mut inverse_b = buffers.take(domain_k.size());
+        let mut inverse_c = buffers.take(domain_k.size());
         for i in 0..domain_k.size() {
-            inverse_a.push((beta - a_star.row_evals_on_k[i]) * (alpha - a_star.col_evals_on_k[i]));
-            inverse_b.push((beta - b_star.row_evals_on_k[i]) * (alpha - b_star.col_evals_on_k[i]));
-            inverse_c.push((beta - c_star.row_evals_on_k[i]) * (alpha - c_star.col_evals_on_k[i]));
+            inverse_a[i] = (beta - a_star.row_evals_on_k[i]) * (alpha - a_star.col_evals_on_k[i]);
+            inverse_b[i] = (beta - b_star.row_evals_on_k[i]) * (alpha - b_star.col_evals_on_k[i]);
+            inverse_c[i] = (beta - c_star.row_evals_on_k[i]) * (alpha - c_star.col_evals_on_k[i]);
         }
         fields::batch_inversion(&mut inverse_a);
         fields::batch_inversion(&mut inverse_b);
@@ -373,42 +406,41 @@ impl<F: PrimeField> AHP<F> {
                 + eta_c * c_star.val_evals_on_k[i] * inverse_c[i];
             t_evals_on_k.push(t * v_h_at_alpha * v_h_at_beta);
         }
+        buffers.give(vec![inverse_a, inverse_b, inverse_c]);
 
         let t_poly = EvaluationsOnDomain::from_vec_and_domain(t_evals_on_k, domain_k).interpolate();
         let g_2_poly = DensePolynomial::from_coefficients_slice(&t_poly.coeffs[1..]);
 
-        let denom_a: Vec<_> = cfg_iter!(a_star.row_evals_on_b.evals)
-            .zip(&a_star.col_evals_on_b.evals)
-            .zip(&a_star.row_col_evals_on_b.evals)
-            .map(|((r, c), r_c)| beta * alpha - (alpha * r) - (beta * c) + r_c)
-            .collect();
-        let denom_b: Vec<_> = cfg_iter!(b_star.row_evals_on_b.evals)
-            .zip(&b_star.col_evals_on_b.evals)
-            .zip(&b_star.row_col_evals_on_b.evals)
-            .map(|((r, c), r_c)| beta * alpha - (alpha * r) - (beta * c) + r_c)
-            .collect();
-        let denom_c: Vec<_> = cfg_iter!(c_star.row_evals_on_b.evals)
-            .zip(&c_star.col_evals_on_b.evals)
-            .zip(&c_star.row_col_evals_on_b.evals)
-            .map(|((r, c), r_c)| beta * alpha - (alpha * r) - (beta * c) + r_c)
-            .collect();
+        let mut denominator = |star: &MatrixPolynomials<'_, F>| {
+            let mut denom = buffers.take(star.row_evals_on_b.evals.len());
+            cfg_iter_mut!(denom)
+                .zip(&star.row_evals_on_b.evals)
+                .zip(&star.col_evals_on_b.evals)
+                .zip(&star.row_col_evals_on_b.evals)
+                .for_each(|(((d, r), c), r_c)| *d = beta * alpha - (alpha * r) - (beta * c) + r_c);
+            denom
+        };
+        let denom_a = denominator(a_star);
+        let denom_b = denominator(b_star);
+        let denom_c = denominator(c_star);
 
         let domain_b = GeneralEvaluationDomain::new(3 * domain_k.size() - 3)
             .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
 
-        let a_evals_on_b = cfg_into_iter!(0..domain_b.size())
-            .map(|i| {
-                let tmp = eta_a * a_star.val_evals_on_b.evals[i] * denom_b[i] * denom_c[i]
-                    + eta_b * b_star.val_evals_on_b.evals[i] * denom_c[i] * denom_a[i]
-                    + eta_c * c_star.val_evals_on_b.evals[i] * denom_a[i] * denom_b[i];
-                tmp * v_h_at_alpha * v_h_at_beta
-            })
-            .collect();
+        let mut a_evals_on_b = buffers.take(domain_b.size());
+        cfg_iter_mut!(a_evals_on_b).enumerate().for_each(|(i, a)| {
+            let tmp = eta_a * a_star.val_evals_on_b.evals[i] * denom_b[i] * denom_c[i]
+                + eta_b * b_star.val_evals_on_b.evals[i] * denom_c[i] * denom_a[i]
+                + eta_c * c_star.val_evals_on_b.evals[i] * denom_a[i] * denom_b[i];
+            *a = tmp * v_h_at_alpha * v_h_at_beta
+        });
         let a_poly = EvaluationsOnDomain::from_vec_and_domain(a_evals_on_b, domain_b).interpolate();
 
-        let b_evals_on_b = cfg_into_iter!(0..domain_b.size())
-            .map(|i| denom_a[i] * denom_b[i] * denom_c[i])
-            .collect();
+        let mut b_evals_on_b = buffers.take(domain_b.size());
+        cfg_iter_mut!(b_evals_on_b)
+            .enumerate()
+            .for_each(|(i, b)| *b = denom_a[i] * denom_b[i] * denom_c[i]);
+        buffers.give(vec![denom_a, denom_b, denom_c]);
         let b_poly = EvaluationsOnDomain::from_vec_and_domain(b_evals_on_b, domain_b).interpolate();
 
         let h_2_poly = (&a_poly - &(&b_poly * &t_poly))
@@ -1,70 +1,233 @@
-use ark_ff::{FromBytes, ToBytes};
+use ark_ec::PairingEngine;
+use ark_ff::{Field, ToBytes};
 use merlin::Transcript;
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaChaRng;
+use zkp_r1cs::SynthesisError;
 
-use crate::Vec;
+use crate::pc::Commitment;
+use crate::{IndexVerifierKey, Vec};
 
-/// A `SeedableRng` that refreshes its seed by hashing together the previous seed
-/// and the new seed material.
+/// The name of the transcript. The version is bumped whenever the order or
+/// encoding of the absorbed items changes, so that proofs of one version
+/// never verify under another.
+pub const PROTOCOL_NAME: &[u8] = b"MARLIN-KZG10-v1";
+
+/// One operation on a transcript, with the bytes that went in or came out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptEntry {
+    Absorb {
+        label: &'static [u8],
+        bytes: Vec<u8>,
+    },
+    Squeeze {
+        bytes: Vec<u8>,
+    },
+}
+
+/// How a replayed transcript departs from the recorded one, see
+/// `verify_proof_replayed`.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The entry at `entry` differs from the recorded one, or is past its end.
+    Diverges { entry: usize },
+    /// The transcript stops after `entries` of the recorded entries.
+    Stops { entries: usize },
+    Synthesis(SynthesisError),
+}
+
+impl From<SynthesisError> for ReplayError {
+    fn from(err: SynthesisError) -> Self {
+        ReplayError::Synthesis(err)
+    }
+}
+
+/// What a transcript does with its entries.
+pub(crate) enum TranscriptMode {
+    Plain,
+    /// Keeps every entry, for `FiatShamirRng::into_entries`.
+    Record(Vec<TranscriptEntry>),
+    /// Compares every entry with the next one of a recorded transcript,
+    /// keeping the position of the first that differs.
+    Replay {
+        entries: Vec<TranscriptEntry>,
+        position: usize,
+        diverged: Option<usize>,
+    },
+}
+
+impl TranscriptMode {
+    pub(crate) fn replay(entries: Vec<TranscriptEntry>) -> Self {
+        TranscriptMode::Replay {
+            entries,
+            position: 0,
+            diverged: None,
+        }
+    }
+}
+
+/// A `SeedableRng` over a labeled transcript. Every absorbed item is appended
+/// under its own label, and the rng is reseeded from the transcript.
 // TODO: later: re-evaluate decision about ChaChaRng
 pub struct FiatShamirRng {
     r: ChaChaRng,
-    seed: [u8; 32],
+    transcript: Transcript,
+    mode: TranscriptMode,
 }
 
 impl RngCore for FiatShamirRng {
     #[inline]
     fn next_u32(&mut self) -> u32 {
-        self.r.next_u32()
+        let x = self.r.next_u32();
+        self.squeezed(&x.to_le_bytes());
+        x
     }
 
     #[inline]
     fn next_u64(&mut self) -> u64 {
-        self.r.next_u64()
+        let x = self.r.next_u64();
+        self.squeezed(&x.to_le_bytes());
+        x
     }
 
     #[inline]
     fn fill_bytes(&mut self, dest: &mut [u8]) {
         self.r.fill_bytes(dest);
+        self.squeezed(dest);
     }
 
     #[inline]
     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
-        Ok(self.r.fill_bytes(dest))
+        self.fill_bytes(dest);
+        Ok(())
     }
 }
 
 impl FiatShamirRng {
-    /// Create a new `Self` by initializing with a fresh seed.
-    /// `self.seed = H(self.seed || new_seed)`.
-    #[inline]
-    pub fn from_seed<'a, T: 'a + ToBytes>(s: &'a T) -> Self {
-        let mut bytes = Vec::new();
-        s.write(&mut bytes).expect("failed to convert to bytes");
+    /// A transcript of `PROTOCOL_NAME` over the curve of `E`, seeded with the
+    /// index and the public input.
+    pub fn new<E: PairingEngine>(ivk: &IndexVerifierKey<E>, public_input: &[E::Fr]) -> Self {
+        Self::with_mode(TranscriptMode::Plain, ivk, public_input)
+    }
+
+    pub(crate) fn with_mode<E: PairingEngine>(
+        mode: TranscriptMode,
+        ivk: &IndexVerifierKey<E>,
+        public_input: &[E::Fr],
+    ) -> Self {
+        let mut rng = Self {
+            r: ChaChaRng::from_seed([0u8; 32]),
+            transcript: Transcript::new(PROTOCOL_NAME),
+            mode,
+        };
+        // `Transcript::new` appends the protocol name as the domain separator.
+        rng.log(TranscriptEntry::Absorb {
+            label: b"dom-sep",
+            bytes: PROTOCOL_NAME.to_vec(),
+        });
+        // a curve is identified by the moduli of its base and scalar fields.
+        rng.append_message(b"base field", &modulus_bytes::<E::Fq>());
+        rng.append_message(b"scalar field", &modulus_bytes::<E::Fr>());
+        rng.absorb(b"index", ivk);
+        rng.absorb(b"public input", &public_input);
+        rng
+    }
 
-        let mut seed: [u8; 32] = [0u8; 32];
-        let mut transcript = Transcript::new(b"MARLINSEED");
-        transcript.append_message(b"Seed", &bytes);
-        transcript.challenge_bytes(b"x", &mut seed);
+    /// The recorded entries, or none if the transcript was not recording.
+    pub(crate) fn into_entries(self) -> Vec<TranscriptEntry> {
+        match self.mode {
+            TranscriptMode::Record(entries) => entries,
+            _ => Vec::new(),
+        }
+    }
 
-        let r = ChaChaRng::from_seed(seed.clone());
-        Self { r, seed }
+    /// Whether a replaying transcript went through every recorded entry
+    /// without diverging.
+    pub(crate) fn finish_replay(&self) -> Result<(), ReplayError> {
+        if let TranscriptMode::Replay {
+            entries,
+            position,
+            diverged,
+        } = &self.mode
+        {
+            if let Some(entry) = diverged {
+                return Err(ReplayError::Diverges { entry: *entry });
+            }
+            if *position != entries.len() {
+                return Err(ReplayError::Stops { entries: *position });
+            }
+        }
+        Ok(())
     }
 
-    /// Refresh `self.seed` with new material. Achieved by setting
-    /// `self.seed = H(self.seed || new_seed)`.
-    #[inline]
-    pub fn absorb<'a, T: 'a + ToBytes>(&mut self, seed: &'a T) {
+    fn squeezed(&mut self, bytes: &[u8]) {
+        self.log(TranscriptEntry::Squeeze {
+            bytes: bytes.to_vec(),
+        });
+    }
+
+    fn log(&mut self, entry: TranscriptEntry) {
+        match &mut self.mode {
+            TranscriptMode::Plain => {}
+            TranscriptMode::Record(entries) => entries.push(entry),
+            TranscriptMode::Replay {
+                entries,
+                position,
+                diverged,
+            } => {
+                if diverged.is_none() && entries.get(*position) != Some(&entry) {
+                    *diverged = Some(*position);
+                }
+                *position += 1;
+            }
+        }
+    }
+
+    /// Absorb the commitments of a round. Only commitments are accepted, so
+    /// that nothing else sent to the committer can end up in the transcript.
+    pub fn absorb_commitments<'a, E: PairingEngine>(
+        &mut self,
+        label: &'static [u8],
+        comms: impl IntoIterator<Item = &'a Commitment<E>>,
+    ) {
         let mut bytes = Vec::new();
-        seed.write(&mut bytes).expect("failed to convert to bytes");
-        bytes.extend_from_slice(&self.seed);
+        for comm in comms {
+            comm.write(&mut bytes).expect("failed to convert to bytes");
+        }
+        self.append(label, &bytes);
+    }
 
-        let mut transcript = Transcript::new(b"MARLINSEED");
-        transcript.append_message(b"Seed", &bytes);
-        transcript.challenge_bytes(b"x", &mut self.seed);
+    pub fn absorb_evaluations<F: Field>(&mut self, label: &'static [u8], evals: &[F]) {
+        self.absorb(label, &evals);
+    }
 
-        let seed: [u8; 32] = FromBytes::read(self.seed.as_ref()).expect("failed to get [u32; 8]");
+    fn absorb<T: ToBytes>(&mut self, label: &'static [u8], item: &T) {
+        let mut bytes = Vec::new();
+        item.write(&mut bytes).expect("failed to convert to bytes");
+        self.append(label, &bytes);
+    }
+
+    /// Append `bytes` under `label` and reseed the rng from the transcript.
+    fn append(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.append_message(label, bytes);
+
+        let mut seed = [0u8; 32];
+        self.transcript.challenge_bytes(b"seed", &mut seed);
         self.r = ChaChaRng::from_seed(seed);
     }
+
+    fn append_message(&mut self, label: &'static [u8], bytes: &[u8]) {
+        self.transcript.append_message(label, bytes);
+        self.log(TranscriptEntry::Absorb {
+            label,
+            bytes: bytes.to_vec(),
+        });
+    }
+}
+
+fn modulus_bytes<F: Field>() -> Vec<u8> {
+    F::characteristic()
+        .iter()
+        .flat_map(|limb| limb.to_le_bytes().to_vec())
+        .collect()
 }
@@ -29,7 +29,6 @@ use std::{
 };
 
 use ark_ec::PairingEngine;
-use ark_ff::to_bytes;
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain, Polynomial};
 use ark_std::UniformRand;
 use rand::Rng;
@@ -52,7 +51,14 @@ pub use pc::UniversalParams;
 pub type VerifyKey<E> = IndexVerifierKey<E>;
 
 mod fs_rng;
-use fs_rng::FiatShamirRng;
+use fs_rng::{FiatShamirRng, TranscriptMode};
+pub use fs_rng::{ReplayError, TranscriptEntry, PROTOCOL_NAME};
+
+// the labels of the items absorbed by the transcript.
+const FIRST_ROUND: &[u8] = b"first round commitments";
+const SECOND_ROUND: &[u8] = b"second round commitments";
+const THIRD_ROUND: &[u8] = b"third round commitments";
+const EVALUATIONS: &[u8] = b"evaluations";
 
 pub fn universal_setup<E: PairingEngine, R: Rng>(
     max_degree: usize,
@@ -99,32 +105,65 @@ pub fn create_random_proof<E: PairingEngine, R: Rng, C: ConstraintSynthesizer<E:
     c: C,
     zk_rng: &mut R,
 ) -> Result<Proof<E>, SynthesisError> {
+    prove(ipk, c, zk_rng, TranscriptMode::Plain).map(|(proof, _)| proof)
+}
+
+/// `create_random_proof`, also returning every entry of the transcript, for
+/// `verify_proof_replayed` or as test vectors of other verifiers.
+pub fn create_recorded_proof<E: PairingEngine, R: Rng, C: ConstraintSynthesizer<E::Fr>>(
+    ipk: &IndexProverKey<'_, E>,
+    c: C,
+    zk_rng: &mut R,
+) -> Result<(Proof<E>, Vec<TranscriptEntry>), SynthesisError> {
+    let (proof, fs_rng) = prove(ipk, c, zk_rng, TranscriptMode::Record(Vec::new()))?;
+    Ok((proof, fs_rng.into_entries()))
+}
+
+fn prove<E: PairingEngine, R: Rng, C: ConstraintSynthesizer<E::Fr>>(
+    ipk: &IndexProverKey<'_, E>,
+    c: C,
+    zk_rng: &mut R,
+    mode: TranscriptMode,
+) -> Result<(Proof<E>, FiatShamirRng), SynthesisError> {
     // init
     let pstate = AHP::<E::Fr>::prover_init(&ipk.index, c)?;
     let public_input = pstate.public_input();
-    let mut fs_rng =
-        FiatShamirRng::from_seed(&to_bytes![&ipk.index_verifier_key, &public_input].unwrap());
+    let mut fs_rng = FiatShamirRng::with_mode(mode, &ipk.index_verifier_key, &public_input);
     // first round
     let (pstate, first_oracles) = AHP::<E::Fr>::prover_first_round(pstate, zk_rng)?;
-    let (first_comms, first_rands) =
-        PC::commit(&ipk.committer_key, first_oracles.iter(), Some(zk_rng))?;
+    // the hiding randomness of every round is derived from a single seed.
+    let first_rands = PC::<E>::derive_randomness(first_oracles.iter(), zk_rng.gen())?;
+    let first_comms =
+        PC::commit_with_randomness(&ipk.committer_key, first_oracles.iter(), &first_rands)?;
 
-    fs_rng.absorb(&to_bytes![first_comms].unwrap());
+    fs_rng.absorb_commitments(
+        FIRST_ROUND,
+        first_comms.iter().map(LabeledCommitment::commitment),
+    );
     let (vstate, verifier_first_msg) =
         AHP::<E::Fr>::verifier_first_round(ipk.index_verifier_key.index_info, &mut fs_rng)?;
     // second_round
-    let (pstate, second_oracles) = AHP::<E::Fr>::prover_second_round(pstate, &verifier_first_msg)?;
-    let (second_comms, second_rands) =
-        PC::commit(&ipk.committer_key, second_oracles.iter(), Some(zk_rng))?;
+    let (pstate, second_oracles) =
+        AHP::<E::Fr>::prover_second_round(pstate, &first_oracles, &verifier_first_msg)?;
+    let second_rands = PC::<E>::derive_randomness(second_oracles.iter(), zk_rng.gen())?;
+    let second_comms =
+        PC::commit_with_randomness(&ipk.committer_key, second_oracles.iter(), &second_rands)?;
 
-    fs_rng.absorb(&to_bytes![second_comms].unwrap());
+    fs_rng.absorb_commitments(
+        SECOND_ROUND,
+        second_comms.iter().map(LabeledCommitment::commitment),
+    );
     let (vstate, verifier_second_msg) = AHP::<E::Fr>::verifier_second_round(vstate, &mut fs_rng)?;
     // third_round
     let third_oracles = AHP::<E::Fr>::prover_third_round(pstate, &verifier_second_msg)?;
-    let (third_comms, third_rands) =
-        PC::commit(&ipk.committer_key, third_oracles.iter(), Some(zk_rng))?;
+    let third_rands = PC::<E>::derive_randomness(third_oracles.iter(), zk_rng.gen())?;
+    let third_comms =
+        PC::commit_with_randomness(&ipk.committer_key, third_oracles.iter(), &third_rands)?;
 
-    fs_rng.absorb(&to_bytes![third_comms].unwrap());
+    fs_rng.absorb_commitments(
+        THIRD_ROUND,
+        third_comms.iter().map(LabeledCommitment::commitment),
+    );
     let vstate = AHP::<E::Fr>::verifier_third_round(vstate, &mut fs_rng)?;
     // gathering opening elements
     let polynomials: Vec<_> = ipk
@@ -137,11 +176,10 @@ pub fn create_random_proof<E: PairingEngine, R: Rng, C: ConstraintSynthesizer<E:
 
     let randomnesses: Vec<_> = ipk
         .index_rands
-        .clone()
-        .into_iter()
-        .chain(first_rands)
-        .chain(second_rands)
-        .chain(third_rands)
+        .iter()
+        .chain(&first_rands)
+        .chain(&second_rands)
+        .chain(&third_rands)
         .collect();
 
     let query_set = AHP::<E::Fr>::verifier_query_set(&vstate);
@@ -154,7 +192,7 @@ pub fn create_random_proof<E: PairingEngine, R: Rng, C: ConstraintSynthesizer<E:
         let eval = p.polynomial().evaluate(&point);
         evaluations.push(eval);
     }
-    fs_rng.absorb(&evaluations);
+    fs_rng.absorb_evaluations(EVALUATIONS, &evaluations);
     let opening_challenge = u128::rand(&mut fs_rng).into();
 
     let opening_proofs = PC::batch_open(
@@ -162,7 +200,7 @@ pub fn create_random_proof<E: PairingEngine, R: Rng, C: ConstraintSynthesizer<E:
         polynomials,
         &query_set,
         opening_challenge,
-        &randomnesses,
+        randomnesses,
     )?;
 
     let commitments = vec![
@@ -173,11 +211,12 @@ pub fn create_random_proof<E: PairingEngine, R: Rng, C: ConstraintSynthesizer<E:
             .collect(),
         third_comms.iter().map(|p| p.commitment().clone()).collect(),
     ];
-    Ok(Proof {
+    let proof = Proof {
         commitments,
         evaluations,
         opening_proofs,
-    })
+    };
+    Ok((proof, fs_rng))
 }
 
 /// standard interface for verify proof.
@@ -186,23 +225,47 @@ pub fn verify_proof<E: PairingEngine>(
     proof: &Proof<E>,
     public_input: &[E::Fr],
 ) -> Result<bool, SynthesisError> {
-    let mut fs_rng = FiatShamirRng::from_seed(&to_bytes![&ivk, &public_input].unwrap());
+    let mut fs_rng = FiatShamirRng::new(ivk, public_input);
+    verify(ivk, proof, &mut fs_rng, public_input)
+}
+
+/// `verify_proof`, failing with the first entry of the verifier's transcript
+/// that differs from `recorded`, the transcript of `create_recorded_proof`.
+pub fn verify_proof_replayed<E: PairingEngine>(
+    ivk: &IndexVerifierKey<E>,
+    proof: &Proof<E>,
+    public_input: &[E::Fr],
+    recorded: Vec<TranscriptEntry>,
+) -> Result<bool, ReplayError> {
+    let mode = TranscriptMode::replay(recorded);
+    let mut fs_rng = FiatShamirRng::with_mode(mode, ivk, public_input);
+    let result = verify(ivk, proof, &mut fs_rng, public_input);
+    // a divergence explains a failure of the verifier after it.
+    fs_rng.finish_replay()?;
+    Ok(result?)
+}
 
+fn verify<E: PairingEngine>(
+    ivk: &IndexVerifierKey<E>,
+    proof: &Proof<E>,
+    fs_rng: &mut FiatShamirRng,
+    public_input: &[E::Fr],
+) -> Result<bool, SynthesisError> {
     let first_comms = &proof.commitments[0];
-    fs_rng.absorb(&to_bytes![first_comms].unwrap());
-    let (vstate, _) = AHP::<E::Fr>::verifier_first_round(ivk.index_info, &mut fs_rng)?;
+    fs_rng.absorb_commitments(FIRST_ROUND, first_comms);
+    let (vstate, _) = AHP::<E::Fr>::verifier_first_round(ivk.index_info, fs_rng)?;
 
     let second_comms = &proof.commitments[1];
-    fs_rng.absorb(&to_bytes![second_comms].unwrap());
-    let (vstate, _) = AHP::<E::Fr>::verifier_second_round(vstate, &mut fs_rng)?;
+    fs_rng.absorb_commitments(SECOND_ROUND, second_comms);
+    let (vstate, _) = AHP::<E::Fr>::verifier_second_round(vstate, fs_rng)?;
 
     let third_comms = &proof.commitments[2];
-    fs_rng.absorb(&to_bytes![third_comms].unwrap());
-    let vstate = AHP::<E::Fr>::verifier_third_round(vstate, &mut fs_rng)?;
+    fs_rng.absorb_commitments(THIRD_ROUND, third_comms);
+    let vstate = AHP::<E::Fr>::verifier_third_round(vstate, fs_rng)?;
 
     let query_set = AHP::<E::Fr>::verifier_query_set(&vstate);
-    fs_rng.absorb(&proof.evaluations);
-    let opening_challenge: E::Fr = u128::rand(&mut fs_rng).into();
+    fs_rng.absorb_evaluations(EVALUATIONS, &proof.evaluations);
+    let opening_challenge: E::Fr = u128::rand(fs_rng).into();
 
     let degree_bounds: Vec<_> = vec![None; ivk.index_comms.len()]
         .into_iter()
@@ -97,29 +97,42 @@ impl<E: PairingEngine> KZG10<E> {
         Ok((ck, vk))
     }
 
-    pub fn commit<R: RngCore>(
+    /// The blinding polynomial of a commitment hiding `hiding_bound`
+    /// evaluations, or the empty one without a bound.
+    pub fn sample_rand<R: RngCore>(
+        hiding_bound: Option<usize>,
+        rng: Option<&mut R>,
+    ) -> Result<Rand<E::Fr>, Error> {
+        match hiding_bound {
+            Some(hiding_degree) => {
+                let rng = rng.ok_or(Error::MissingRng)?;
+                Ok(Rand::rand(hiding_degree, rng))
+            }
+            None => Ok(Rand::empty()),
+        }
+    }
+
+    /// Commits to `p`, blinded by the already sampled `rand`.
+    pub fn commit_with_rand(
         ck: &Powers<'_, E>,
         p: &Polynomial<E::Fr>,
         hiding_bound: Option<usize>,
-        rng: Option<&mut R>,
-    ) -> Result<(Comm<E>, Rand<E::Fr>), Error> {
+        rand: &Rand<E::Fr>,
+    ) -> Result<Comm<E>, Error> {
         Self::check_degree_is_within_bounds(p.degree(), ck.supported_degree())?;
         let (num_leading_zeros, coeffs) = Self::skip_leading_zeros_and_convert_to_bigints(p);
         let mut comm =
             VariableBaseMSM::multi_scalar_mul(&ck.powers_of_g[num_leading_zeros..], &coeffs);
 
-        let mut rand = Rand::<E::Fr>::empty();
         if let Some(hiding_degree) = hiding_bound {
-            let mut rng = rng.ok_or(Error::MissingRng)?;
             Self::check_hiding_bound(hiding_degree, ck.size())?;
-            rand = Rand::rand(hiding_degree, &mut rng);
             let rand_coeffs = Self::convert_to_bigints(&rand.blinding_polynomial.coeffs);
             let rand_commitment =
                 VariableBaseMSM::multi_scalar_mul(&ck.powers_of_gamma_g, &rand_coeffs)
                     .into_affine();
             comm.add_assign_mixed(&rand_commitment);
         }
-        Ok((Comm(comm.into()), rand))
+        Ok(Comm(comm.into()))
     }
 
     pub fn open(
@@ -252,7 +265,8 @@ mod tests {
         };
         let hiding_bound = Some(1);
         let powers = ck.powers();
-        let (c, r) = KZG10::<E>::commit(&powers, &p, hiding_bound, Some(rng))?;
+        let r = KZG10::<E>::sample_rand(hiding_bound, Some(rng))?;
+        let c = KZG10::<E>::commit_with_rand(&powers, &p, hiding_bound, &r)?;
         let point = E::Fr::rand(rng);
         let value = p.evaluate(&point);
         let proof = KZG10::<E>::open(&powers, &p, point, &r)?;
@@ -2,7 +2,8 @@ use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{Field, One, Zero};
 use ark_poly::{polynomial::univariate::DensePolynomial, UVPolynomial};
 use core::marker::PhantomData;
-use rand::RngCore;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaChaRng;
 
 use crate::{BTreeMap, BTreeSet, ToString, Vec};
 
@@ -31,43 +32,90 @@ impl<E: PairingEngine> PC<E> {
         KZG10::trim(pp, supported_degree)
     }
 
+    /// Commits to every polynomial of a round. Their hiding randomness is
+    /// sampled from `rng` at once, before the first commitment.
     pub fn commit<'a>(
         ck: &CommitterKey<E>,
         polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<'a, E::Fr>>,
         rng: Option<&mut dyn RngCore>,
     ) -> Result<(Vec<LabeledCommitment<E>>, Vec<Randomness<E::Fr>>), Error> {
+        let polynomials: Vec<_> = polynomials.into_iter().collect();
+        let randomnesses = Self::sample_randomness(polynomials.iter().copied(), rng)?;
+        let commitments = Self::commit_with_randomness(ck, polynomials, &randomnesses)?;
+        Ok((commitments, randomnesses))
+    }
+
+    /// The hiding randomness of `polynomials`, in order, and for a
+    /// polynomial with a degree bound that of its shifted commitment right
+    /// after its own.
+    pub fn sample_randomness<'a>(
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<'a, E::Fr>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Vec<Randomness<E::Fr>>, Error> {
         let rng = &mut OptionalRng(rng);
+        polynomials
+            .into_iter()
+            .map(|p| {
+                let hiding_bound = p.hiding_bound();
+                let rand = KZG10::<E>::sample_rand(hiding_bound, Some(&mut *rng))?;
+                let shifted_rand = match p.degree_bound() {
+                    Some(_) => Some(KZG10::<E>::sample_rand(hiding_bound, Some(&mut *rng))?),
+                    None => None,
+                };
+                Ok(Randomness { rand, shifted_rand })
+            })
+            .collect()
+    }
+
+    /// `sample_randomness` from a ChaCha20 stream seeded with `seed`, so that
+    /// a deterministic prover gets the same randomness for the same seed.
+    pub fn derive_randomness<'a>(
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<'a, E::Fr>>,
+        seed: [u8; 32],
+    ) -> Result<Vec<Randomness<E::Fr>>, Error> {
+        let mut rng = ChaChaRng::from_seed(seed);
+        Self::sample_randomness(polynomials, Some(&mut rng))
+    }
+
+    /// Commits to `polynomials` with the randomness sampled for them.
+    pub fn commit_with_randomness<'a>(
+        ck: &CommitterKey<E>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<'a, E::Fr>>,
+        randomnesses: &[Randomness<E::Fr>],
+    ) -> Result<Vec<LabeledCommitment<E>>, Error> {
         let mut commitments = Vec::new();
-        let mut randomnesses = Vec::new();
-        for p in polynomials {
+        for (p, randomness) in polynomials.into_iter().zip(randomnesses) {
             let label = p.label();
             let polynomial = p.polynomial();
             let hiding_bound = p.hiding_bound();
             let degree_bound = p.degree_bound();
 
-            let (comm, rand) = KZG10::commit(&ck.powers(), polynomial, hiding_bound, Some(rng))?;
+            let comm =
+                KZG10::commit_with_rand(&ck.powers(), polynomial, hiding_bound, &randomness.rand)?;
 
-            let (shifted_comm, shifted_rand) = if let Some(degree_bound) = degree_bound {
+            let shifted_comm = if let Some(degree_bound) = degree_bound {
                 let shifted_powers = ck
                     .shifted_powers(degree_bound)
                     .ok_or(Error::DegreeOutOfBound)?;
-                let (shifted_comm, shifted_rand) =
-                    KZG10::commit(&shifted_powers, polynomial, hiding_bound, Some(rng))?;
-                (Some(shifted_comm), Some(shifted_rand))
+                let shifted_rand = randomness.shifted_rand.as_ref().ok_or(Error::MissingRng)?;
+                Some(KZG10::commit_with_rand(
+                    &shifted_powers,
+                    polynomial,
+                    hiding_bound,
+                    shifted_rand,
+                )?)
             } else {
-                (None, None)
+                None
             };
 
             let commitment = Commitment { comm, shifted_comm };
-            let randomness = Randomness { rand, shifted_rand };
             commitments.push(LabeledCommitment::new(
                 label.to_string(),
                 commitment,
                 degree_bound,
             ));
-            randomnesses.push(randomness);
         }
-        Ok((commitments, randomnesses))
+        Ok(commitments)
     }
 
     pub fn open<'a>(
@@ -387,6 +435,54 @@ mod tests {
         Ok(())
     }
 
+    fn derive_randomness_template<E: PairingEngine>() -> Result<(), Error> {
+        let rng = &mut test_rng();
+        let polynomials: Vec<_> = (0..3)
+            .map(|i| {
+                let polynomial = DensePolynomial::rand(5, rng);
+                let degree_bound = if i == 0 { None } else { Some(5) };
+                LabeledPolynomial::new_owned(format!("{}", i), polynomial, degree_bound, Some(1))
+            })
+            .collect();
+        let pp = PC::<E>::setup(10, rng)?;
+        let (ck, vk) = PC::<E>::trim(&pp, 8)?;
+
+        let seed = [7u8; 32];
+        let rands = PC::<E>::derive_randomness(&polynomials, seed)?;
+        assert!(rands[0].shifted_rand.is_none());
+        assert!(rands[1].shifted_rand.is_some());
+        let comms = PC::<E>::commit_with_randomness(&ck, &polynomials, &rands)?;
+        let again = PC::<E>::derive_randomness(&polynomials, seed)?;
+        let again = PC::<E>::commit_with_randomness(&ck, &polynomials, &again)?;
+        for (c, a) in comms.iter().zip(&again) {
+            assert_eq!(c.commitment().comm.0, a.commitment().comm.0);
+            assert_eq!(
+                c.commitment().shifted_comm.as_ref().map(|c| c.0),
+                a.commitment().shifted_comm.as_ref().map(|c| c.0)
+            );
+        }
+
+        let point = E::Fr::rand(rng);
+        let opening_challenge = E::Fr::rand(rng);
+        let values = polynomials.iter().map(|p| p.evaluate(point));
+        let proof = PC::<E>::open(&ck, &polynomials, point, opening_challenge, &rands)?;
+        assert!(PC::<E>::check(
+            &vk,
+            &comms,
+            point,
+            values,
+            &proof,
+            opening_challenge
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn derive_randomness_test() {
+        use ark_bls12_381::Bls12_381;
+        derive_randomness_template::<Bls12_381>().expect("test failed for Bls12_381");
+    }
+
     #[test]
     fn single_point_test() {
         let info = TestInfo {
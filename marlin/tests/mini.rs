@@ -3,7 +3,10 @@ use ark_ff::PrimeField;
 use ark_serialize::*;
 use ark_std::test_rng;
 use std::time::Instant;
-use zkp_marlin::{create_random_proof, index, universal_setup, verify_proof, Proof, VerifyKey};
+use zkp_marlin::{
+    create_random_proof, create_recorded_proof, index, universal_setup, verify_proof,
+    verify_proof_replayed, Proof, ReplayError, TranscriptEntry, VerifyKey,
+};
 use zkp_r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
 
 struct Mini<F: PrimeField> {
@@ -86,3 +89,51 @@ fn mini_marlin() {
     let proof2 = Proof::<E>::deserialize(&proof_bytes[..]).unwrap();
     assert!(verify_proof(&vk2, &proof2, &[Fr::from(10u32)]).unwrap());
 }
+
+#[test]
+fn mini_marlin_transcript_replay() {
+    let rng = &mut test_rng();
+    let blank = Mini::<Fr> {
+        x: None,
+        y: None,
+        z: None,
+        num: 4,
+    };
+    let srs = universal_setup::<E, _>(2usize.pow(8), rng).unwrap();
+    let (ipk, ivk) = index(&srs, blank).unwrap();
+
+    let circuit = Mini {
+        x: Some(Fr::from(2u32)),
+        y: Some(Fr::from(3u32)),
+        z: Some(Fr::from(10u32)),
+        num: 4,
+    };
+    let (proof, transcript) = create_recorded_proof(&ipk, circuit, rng).unwrap();
+    assert_eq!(
+        transcript[0],
+        TranscriptEntry::Absorb {
+            label: b"dom-sep",
+            bytes: zkp_marlin::PROTOCOL_NAME.to_vec(),
+        }
+    );
+    assert!(verify_proof_replayed(&ivk, &proof, &[Fr::from(10u32)], transcript.clone()).unwrap());
+
+    // a wrong public input diverges at its own entry.
+    assert!(matches!(
+        verify_proof_replayed(&ivk, &proof, &[Fr::from(11u32)], transcript.clone()),
+        Err(ReplayError::Diverges { entry: 4 })
+    ));
+
+    // the verifier goes past a truncated recording, or stops before the end
+    // of a longer one.
+    assert!(matches!(
+        verify_proof_replayed(&ivk, &proof, &[Fr::from(10u32)], transcript[..4].to_vec()),
+        Err(ReplayError::Diverges { entry: 4 })
+    ));
+    let mut longer = transcript.clone();
+    longer.push(transcript[0].clone());
+    match verify_proof_replayed(&ivk, &proof, &[Fr::from(10u32)], longer) {
+        Err(ReplayError::Stops { entries }) => assert_eq!(entries, transcript.len()),
+        _ => panic!("expected the verifier to stop before the recording"),
+    }
+}
@@ -0,0 +1,57 @@
+//! Prints JSON test vectors for the Solidity verifier of
+//! `plonk-verify-test`: a valid proof over BN254 and one under wrong public
+//! inputs.
+//!
+//!     cargo run --example test_vectors > plonk-origin-vectors.json
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{One, Zero};
+use ark_std::test_rng;
+use zkp_plonk::{test_vector, Composer, PCKey, Plonk};
+
+/// The circuit of the tests of `Plonk`, with no public input set.
+fn circuit() -> Composer<Fr> {
+    let mut cs = Composer::new();
+    let one = Fr::one();
+    let two = one + one;
+    let three = two + one;
+    let four = two + two;
+    let six = two + four;
+    let var_one = cs.alloc_and_assign(one);
+    let var_two = cs.alloc_and_assign(two);
+    let var_three = cs.alloc_and_assign(three);
+    let var_four = cs.alloc_and_assign(four);
+    let var_six = cs.alloc_and_assign(six);
+    cs.create_add_gate((var_one, one), (var_two, one), var_three, None, Fr::zero(), Fr::zero());
+    cs.create_add_gate((var_one, one), (var_three, one), var_four, None, Fr::zero(), Fr::zero());
+    cs.create_mul_gate(var_two, var_two, var_four, None, one, Fr::zero(), Fr::zero());
+    cs.create_mul_gate(var_one, var_two, var_six, None, two, two, Fr::zero());
+    cs.constrain_to_constant(var_six, six, Fr::zero());
+    cs
+}
+
+fn main() {
+    let rng = &mut test_rng();
+    let cs = circuit();
+    let ks = [Fr::one(), Fr::from(7_u64), Fr::from(13_u64), Fr::from(17_u64)];
+
+    let mut pckey = PCKey::setup(64, rng);
+    let (pk, vk) = Plonk::<Bn254>::keygen(&mut pckey, &cs, ks).expect("keygen");
+    let proof = Plonk::<Bn254>::prove(&mut pckey, &pk, &cs).expect("prove");
+
+    let public_inputs = cs.public_inputs();
+    let valid = test_vector("bn254-kzg-keccak", &vk, &mut pckey, public_inputs, &proof)
+        .expect("valid vector");
+    let mut wrong = public_inputs.to_vec();
+    wrong[0] += Fr::one();
+    let invalid = test_vector(
+        "bn254-kzg-keccak-wrong-public-input",
+        &vk,
+        &mut pckey,
+        &wrong,
+        &proof,
+    )
+    .expect("invalid vector");
+
+    println!("[{},\n{}]", valid.to_json(), invalid.to_json());
+}
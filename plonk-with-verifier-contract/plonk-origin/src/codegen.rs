@@ -0,0 +1,241 @@
+//! Solidity verifiers with the key of one circuit hardcoded, so that its
+//! proofs over BN254 can be checked on EVM chains. The source is
+//! self-contained: the libraries and the verifier of `plonk-verify-test`,
+//! with the generators of the `PCKey` in place of those they were written
+//! for and without their logging, then a contract holding the key, whose
+//! `verify_proof` takes the public inputs and the words of `proof_words`.
+//! The pairings are those of the precompiles.
+
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_poly::EvaluationDomain;
+use ark_std::{string::String, vec, vec::Vec};
+
+use crate::pc::PCKey;
+use crate::{Proof, VerifierKey};
+
+const CORE_LIB: &str = include_str!("../../plonk-verify-test/contracts/PlonkCoreLib.sol");
+const CORE_VERIFIER: &str = include_str!("../../plonk-verify-test/contracts/PlonkSingleCore.sol");
+
+/// The source of the contract `name`, verifying proofs of the circuit of
+/// `vk`, with `num_inputs` public inputs, under `pckey`.
+pub fn solidity_verifier(
+    name: &str,
+    vk: &VerifierKey<Bn254>,
+    pckey: &PCKey<Bn254>,
+    num_inputs: usize,
+) -> String {
+    let (g_x, g_y) = g1(&pckey.vk.g);
+    let lib = replace_body(
+        CORE_LIB,
+        "function P1() internal pure returns (G1Point memory) {",
+        &format!("        return G1Point({}, {});", g_x, g_y),
+    );
+    let lib = replace_body(
+        &lib,
+        "function P2() internal pure returns (G2Point memory) {",
+        &format!("        return G2Point({});", g2(&pckey.vk.h)),
+    );
+
+    let start = CORE_VERIFIER.find("contract Plonk4SingleVerifierWithAccessToDNext").unwrap();
+    let end = CORE_VERIFIER.find("contract SingleVerifierWithDeserialize").unwrap();
+    let deserialize = CORE_VERIFIER.find("    function deserialize_proof(").unwrap();
+    let close = CORE_VERIFIER.rfind('}').unwrap();
+
+    let mut source = String::from(PRAGMAS);
+    source.push_str(&without_logging(&lib[lib.find("library PairingsBn254").unwrap()..]));
+    source.push('\n');
+    source.push_str(&without_logging(&CORE_VERIFIER[start..end]));
+    source.push_str(&format!(
+        "contract {} is Plonk4SingleVerifierWithAccessToDNext {{\n",
+        name
+    ));
+    source.push_str("    uint256 constant SERIALIZED_PROOF_LENGTH = 32;\n\n");
+    source.push_str(&verification_key(vk, pckey, num_inputs));
+    source.push_str(VERIFY_PROOF);
+    source.push_str(CORE_VERIFIER[deserialize..close].trim_end());
+    source.push_str("\n}\n");
+    source
+}
+
+/// The words of `proof` in the order `deserialize_proof` reads them.
+pub fn proof_words(proof: &Proof<Bn254>) -> Vec<String> {
+    let mut points = vec![
+        proof.commitments[0][1].0,
+        proof.commitments[0][2].0,
+        proof.commitments[0][3].0,
+        proof.commitments[0][0].0,
+        proof.commitments[1][0].0,
+    ];
+    points.extend(proof.commitments[2].iter().map(|c| c.0));
+
+    let mut words = Vec::new();
+    for point in &points {
+        let (x, y) = g1(point);
+        words.push(x);
+        words.push(y);
+    }
+    //w123 0, z^, t, r, sigma_1 2 3
+    for &i in &[0, 1, 2, 3, 7, 8, 9, 4, 5, 6] {
+        words.push(word(&proof.evaluations[i]));
+    }
+    for point in &[proof.pi_w.0, proof.pi_wz.0] {
+        let (x, y) = g1(point);
+        words.push(x);
+        words.push(y);
+    }
+    words
+}
+
+/// The words of the key of `vk` and `pckey`, with `num_inputs` public
+/// inputs, in the order `test_interface` reads them: the domain size, the
+/// number of inputs, omega, the selector and permutation commitments as in
+/// `get_verification_key`, the non-residues and `beta H`, `c0` first.
+pub fn verification_key_words(
+    vk: &VerifierKey<Bn254>,
+    pckey: &PCKey<Bn254>,
+    num_inputs: usize,
+) -> Vec<String> {
+    let mut words = vec![
+        word(&Fr::from(vk.info.n as u64)),
+        word(&Fr::from(num_inputs as u64)),
+        word(&vk.info.domain_n.element(1)),
+    ];
+    for &i in &[1, 2, 3, 0, 4, 5, 7, 8, 9, 6] {
+        let (x, y) = g1(&vk.comms[i].0);
+        words.push(x);
+        words.push(y);
+    }
+    words.extend(vk.info.ks[1..].iter().map(word));
+    let beta_h = &pckey.vk.beta_h;
+    for coordinate in &[beta_h.x.c0, beta_h.x.c1, beta_h.y.c0, beta_h.y.c1] {
+        words.push(word(coordinate));
+    }
+    words
+}
+
+/// The words of the input of the pairing precompile the contract calls
+/// last, for the points `(lhs, rhs)` of `PCKey::pairing_inputs`: `-rhs`,
+/// `H`, `lhs` and `beta H`, in the order of `pairingProd2` and with the G2
+/// points in the form the precompile expects, `c1` first.
+pub fn pairing_input_words(lhs: &G1Affine, rhs: &G1Affine, pckey: &PCKey<Bn254>) -> Vec<String> {
+    let mut words = vec![];
+    for (p, q) in &[(-*rhs, &pckey.vk.h), (*lhs, &pckey.vk.beta_h)] {
+        let (x, y) = g1(p);
+        words.push(x);
+        words.push(y);
+        for coordinate in &[q.x.c1, q.x.c0, q.y.c1, q.y.c0] {
+            words.push(word(coordinate));
+        }
+    }
+    words
+}
+
+const PRAGMAS: &str = "pragma solidity >=0.5.0 <0.7.0;\npragma experimental ABIEncoderV2;\n";
+
+const VERIFY_PROOF: &str = "    function verify_proof(
+        uint256[] memory public_inputs,
+        uint256[] memory serialized_proof
+    ) public view returns (bool) {
+        VerificationKey memory vk = get_verification_key();
+        Proof memory proof = deserialize_proof(public_inputs, serialized_proof);
+        return verify(proof, vk);
+    }
+
+";
+
+/// `get_verification_key`, returning the constants of `vk`.
+fn verification_key(vk: &VerifierKey<Bn254>, pckey: &PCKey<Bn254>, num_inputs: usize) -> String {
+    let mut body = String::new();
+    body.push_str(&format!("        vk.domain_size = {};\n", vk.info.n));
+    body.push_str(&format!("        vk.num_inputs = {};\n", num_inputs));
+    body.push_str(&format!(
+        "        vk.omega = PairingsBn254.new_fr({});\n",
+        word(&vk.info.domain_n.element(1))
+    ));
+    // q_1 q_2 q_3 q_0 q_m q_c, then sigma_1 sigma_2 sigma_3 sigma_0
+    let points = [
+        ("selector_commitments", [1, 2, 3, 0, 4, 5].as_ref()),
+        ("permutation_commitments", [7, 8, 9, 6].as_ref()),
+    ];
+    for (field, indices) in &points {
+        for (i, &j) in indices.iter().enumerate() {
+            let (x, y) = g1(&vk.comms[j].0);
+            body.push_str(&format!(
+                "        vk.{}[{}] = PairingsBn254.new_g1({}, {});\n",
+                field, i, x, y
+            ));
+        }
+    }
+    for i in 0..3 {
+        body.push_str(&format!(
+            "        vk.permutation_non_residues[{}] = PairingsBn254.new_fr({});\n",
+            i,
+            word(&vk.info.ks[i + 1])
+        ));
+    }
+    body.push_str(&format!(
+        "        vk.g2_x = PairingsBn254.new_g2({});\n",
+        g2(&pckey.vk.beta_h)
+    ));
+
+    let signature = "function get_verification_key() internal pure returns (VerificationKey memory vk)";
+    format!("    {} {{\n{}    }}\n\n", signature, body)
+}
+
+pub(crate) fn word<F: PrimeField>(value: &F) -> String {
+    format!("0x{}", hex::encode(value.into_repr().to_bytes_be()))
+}
+
+/// The coordinates of `point`, with the point at infinity as `(0, 0)`, as
+/// the contracts take it.
+fn g1(point: &G1Affine) -> (String, String) {
+    if point.is_zero() {
+        (word(&point.x), word(&point.x))
+    } else {
+        (word(&point.x), word(&point.y))
+    }
+}
+
+/// The coordinates of `point` in the form the precompiles expect, `c1`
+/// first.
+fn g2(point: &G2Affine) -> String {
+    format!(
+        "[{}, {}], [{}, {}]",
+        word(&point.x.c1),
+        word(&point.x.c0),
+        word(&point.y.c1),
+        word(&point.y.c0)
+    )
+}
+
+/// `source` with the body of the function declared by `signature` replaced
+/// by `body`.
+fn replace_body(source: &str, signature: &str, body: &str) -> String {
+    let start = source.find(signature).unwrap() + signature.len();
+    let end = start + source[start..].find("\n    }\n").unwrap();
+    format!("{}\n{}{}", &source[..start], body, &source[end..])
+}
+
+/// `source` without the imports and the calls to `console`, nor the loops
+/// left empty without them.
+fn without_logging(source: &str) -> String {
+    let lines: Vec<_> = source.lines().collect();
+    let mut kept = vec![true; lines.len()];
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("import ") || line.contains("console.") {
+            kept[i] = false;
+            let in_loop = i > 0 && lines[i - 1].trim_start().starts_with("for (");
+            if in_loop && lines.get(i + 1).map(|l| l.trim()) == Some("}") {
+                kept[i - 1] = false;
+                kept[i + 1] = false;
+            }
+        }
+    }
+    let mut result = String::new();
+    for (line, _) in lines.iter().zip(&kept).filter(|(_, kept)| **kept) {
+        result.push_str(line);
+        result.push('\n');
+    }
+    result
+}
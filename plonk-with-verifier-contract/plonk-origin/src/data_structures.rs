@@ -1,9 +1,10 @@
 use ark_ec::PairingEngine;
-use ark_ff::FftField as Field;
+use ark_ff::{BigInteger, FftField as Field, PrimeField};
 use ark_poly::univariate::DensePolynomial;
 use ark_poly_commit::{ PolynomialCommitment};
 use ark_poly_commit::kzg10::Commitment;
 use ark_std::{vec::Vec};
+use std::io::{self, Write};
 
 use crate::ahp::{Index, IndexInfo};
 
@@ -37,3 +38,27 @@ pub struct Proof<E: PairingEngine> {
     pub pi_w: Commitment<E>,
     pub pi_wz: Commitment<E>,
 }
+
+/// The values `Plonk::verify` derives from a proof, labelled and in the
+/// order it derives them, see `Plonk::generate_verification_trace`.
+pub struct VerificationTrace<F: Field> {
+    /// beta, gamma, alpha, zeta, v and u.
+    pub challenges: Vec<(&'static str, F)>,
+    /// the evaluations of the proof, then those of the vanishing polynomial,
+    /// of the first Lagrange polynomial and of the public inputs at zeta.
+    pub evaluations: Vec<(&'static str, F)>,
+    /// see `PCKey::verifier_coefficients`.
+    pub coefficients: Vec<(&'static str, F)>,
+}
+
+impl<F: PrimeField> VerificationTrace<F> {
+    /// Writes every value as a big-endian word, challenges first, the way
+    /// the contract tests read their inputs.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let values = self.challenges.iter().chain(&self.evaluations).chain(&self.coefficients);
+        for (_, value) in values {
+            writer.write_all(&value.into_repr().to_bytes_be())?;
+        }
+        Ok(())
+    }
+}
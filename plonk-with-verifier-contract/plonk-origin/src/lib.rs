@@ -22,7 +22,7 @@ use ark_ff::{FftField as Field, ToBytes};
 use ark_poly::EvaluationDomain;
 use ark_poly::univariate::DensePolynomial;
 
-use ark_std::{marker::PhantomData,  vec, vec::Vec};
+use ark_std::{marker::PhantomData, string::ToString, vec, vec::Vec};
 // use digest::Digest;
 // use rand_core::RngCore;
 
@@ -36,17 +36,23 @@ mod composer;
 pub use crate::composer::Composer;
 
 mod ahp;
-use ahp::{AHPForPLONK };
-use crate::pc::PCKey;
+use ahp::{AHPForPLONK, VerifierState};
+pub use crate::pc::{PCKey, PreparedVKey, VKey};
 
 // mod rng;
 use crate::transcript::TranscriptLibrary;
-use crate::utils::{evaluate_first_lagrange_poly, generator};
+use crate::utils::{evaluate_first_lagrange_poly, evaluate_vanishing_poly, generator};
 
 mod utils;
 mod transcript;
 mod pc;
 
+mod codegen;
+pub use crate::codegen::{pairing_input_words, proof_words, solidity_verifier, verification_key_words};
+
+mod vectors;
+pub use crate::vectors::{test_vector, TestVector};
+
 
 pub type LabeledPolynomial<F> = ark_poly_commit::LabeledPolynomial<F, DensePolynomial<F>>;
 
@@ -296,28 +302,116 @@ impl<E: PairingEngine> Plonk<E> {
         proof: &Proof<E>,
         pckey: &mut PCKey<E>,
     ) -> Result<bool, Error> {
-        let vs = AHPForPLONK::verifier_init(&vk.info).unwrap();
+        let (vs, v, u) = Self::replay_transcript(vk, public_inputs, proof)?;
+
+        //w123 0, sigma_1 2 3, z^, t,  r
+        let evals = proof.evaluations.clone();
+        if !PCKey::<E>::verifier_equality_check(&vs, evals, public_inputs) {
+            return Ok(false);
+        }
+
+        let result = pckey.verify_pc(&vs, vk, proof, v, u);
+        Ok(result)
+    }
+
+    /// `verify` with the G2 points of the key prepared once beforehand, see
+    /// `VKey::prepare`, for verifiers that load `pvk` rather than the
+    /// `PCKey`.
+    pub fn verify_prepared(
+        vk: &VerifierKey<E>,
+        public_inputs: &[E::Fr],
+        proof: &Proof<E>,
+        pvk: &PreparedVKey<E>,
+    ) -> Result<bool, Error> {
+        let (vs, v, u) = Self::replay_transcript(vk, public_inputs, proof)?;
+
+        let evals = proof.evaluations.clone();
+        if !PCKey::<E>::verifier_equality_check(&vs, evals, public_inputs) {
+            return Ok(false);
+        }
+
+        let (lhs, rhs) = PCKey::pairing_inputs(pvk.g, &vs, vk, proof, v, u);
+        Ok(pvk.check_pairing(lhs, rhs))
+    }
+
+    /// The two G1 points of the pairing check `verify` ends with, see
+    /// `PCKey::pairing_inputs`, under the generator `g` of G1 of the key.
+    /// The proof itself is not checked.
+    pub fn pairing_inputs(
+        vk: &VerifierKey<E>,
+        public_inputs: &[E::Fr],
+        proof: &Proof<E>,
+        g: E::G1Affine,
+    ) -> Result<(E::G1Affine, E::G1Affine), Error> {
+        let (vs, v, u) = Self::replay_transcript(vk, public_inputs, proof)?;
+        Ok(PCKey::pairing_inputs(g, &vs, vk, proof, v, u))
+    }
+
+    /// Everything `verify` derives from `proof` on its way to the pairing
+    /// check, in order: the challenges of the transcript, the evaluations,
+    /// and the coefficients of the commitments it combines. The verifier
+    /// contract replays its steps one by one against these fixed inputs.
+    /// The proof itself is not checked.
+    pub fn generate_verification_trace(
+        vk: &VerifierKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::Fr],
+    ) -> Result<VerificationTrace<E::Fr>, Error> {
+        //w123 0, sigma_1 2 3, z^, t,  r
+        let labels = [
+            "w_1", "w_2", "w_3", "w_0", "sigma_1", "sigma_2", "sigma_3", "z_shifted", "t", "r",
+        ];
+        if let Some(label) = labels.get(proof.evaluations.len()) {
+            return Err(Error::MissingEvaluation(label.to_string()));
+        }
+
+        let (vs, v, u) = Self::replay_transcript(vk, public_inputs, proof)?;
+        let zeta = vs.zeta.unwrap();
+        let challenges = vec![
+            ("beta", vs.beta.unwrap()),
+            ("gamma", vs.gamma.unwrap()),
+            ("alpha", vs.alpha.unwrap()),
+            ("zeta", zeta),
+            ("v", v),
+            ("u", u),
+        ];
+
+        let domain_n = vk.info.domain_n;
+        let mut evaluations: Vec<_> =
+            labels.iter().copied().zip(proof.evaluations.iter().copied()).collect();
+        evaluations.push(("vanishing", evaluate_vanishing_poly(domain_n, zeta)));
+        evaluations.push(("l1", evaluate_first_lagrange_poly(domain_n, zeta)));
+        evaluations.push((
+            "pi",
+            PCKey::<E>::evaluate_public_inputs(domain_n, public_inputs, zeta),
+        ));
+
+        let coefficients = PCKey::<E>::verifier_coefficients(&vs, &proof.evaluations, v, u);
+
+        Ok(VerificationTrace {
+            challenges,
+            evaluations,
+            coefficients,
+        })
+    }
+
+    /// Replays the transcript of the verifier contract over `proof`, to the
+    /// state of the verifier after its rounds and the challenges `v` and `u`
+    /// of the openings.
+    fn replay_transcript<'a>(
+        vk: &'a VerifierKey<E>,
+        public_inputs: &[E::Fr],
+        proof: &Proof<E>,
+    ) -> Result<(VerifierState<'a, E::Fr>, E::Fr, E::Fr), Error> {
+        let vs = AHPForPLONK::verifier_init(&vk.info)?;
 
         let mut transcript = TranscriptLibrary::new();
         for public_input in public_inputs {
             transcript.update_with_fr(public_input);
         }
 
-        let first_comms = proof.commitments[0].clone();
-
-        let mut bytes = [0u8; 65];
-        for i in 1..4 {
-            first_comms[i].write(bytes.as_mut())?;
-            let mut x = [0u8; 32];
-            for j in 0..32 {
-                x[32-j-1] = bytes[j];
-            }
-            transcript.update_with_u256(x);
-            let mut y = [0u8; 32];
-            for j in 32..64 {
-                y[64-j-1] = bytes[j];
-            }
-            transcript.update_with_u256(y);
+        for comm in &proof.commitments[0][1..4] {
+            transcript.update_with_g1(comm)?;
         }
         let x = [0u8; 32];
         transcript.update_with_u256(x);
@@ -327,88 +421,34 @@ impl<E: PairingEngine> Plonk<E> {
         let beta: E::Fr = transcript.generate_challenge();
         let gamma: E::Fr = transcript.generate_challenge();
 
-        let (vs, first_msg) = AHPForPLONK::verifier_first_round(vs,  beta, gamma).unwrap();
-
-        let second_comms = proof.commitments[1].clone();
+        let (vs, _) = AHPForPLONK::verifier_first_round(vs, beta, gamma)?;
 
-        let mut bytes = [0u8; 65];
-        second_comms[0].write(bytes.as_mut())?;
-        let mut x = [0u8; 32];
-        for j in 0..32 {
-            x[32-j-1] = bytes[j];
-        }
-        transcript.update_with_u256(x);
-        let mut y = [0u8; 32];
-        for j in 32..64 {
-            y[64-j-1] = bytes[j];
-        }
-        transcript.update_with_u256(y);
+        transcript.update_with_g1(&proof.commitments[1][0])?;
 
         let alpha: E::Fr = transcript.generate_challenge();
 
-        let (vs, second_msg) = AHPForPLONK::verifier_second_round(vs,  alpha).unwrap();
+        let (vs, _) = AHPForPLONK::verifier_second_round(vs, alpha)?;
 
-        let third_comms = proof.commitments[2].clone();
-
-        let mut bytes = [0u8; 65];
-        for i in 0..third_comms.len() {
-            third_comms[i].write(bytes.as_mut())?;
-            let mut x = [0u8; 32];
-            for j in 0..32 {
-                x[32-j-1] = bytes[j];
-            }
-            transcript.update_with_u256(x);
-            let mut y = [0u8; 32];
-            for j in 32..64 {
-                y[64-j-1] = bytes[j];
-            }
-            transcript.update_with_u256(y);
+        for comm in &proof.commitments[2] {
+            transcript.update_with_g1(comm)?;
         }
 
         let zeta: E::Fr = transcript.generate_challenge();
 
-        let (vs, third_msg) = AHPForPLONK::verifier_third_round(vs,  zeta).unwrap();
+        let (vs, _) = AHPForPLONK::verifier_third_round(vs, zeta)?;
 
         //w123 0, sigma_1 2 3, z^, t,  r
-        let evals = proof.evaluations.clone();
-        for eval in &evals {
+        for eval in &proof.evaluations {
             transcript.update_with_fr(eval);
         }
         let v: E::Fr = transcript.generate_challenge();
 
-        let mut bytes = [0u8; 65];
-        proof.pi_w.write(bytes.as_mut())?;
-        let mut x = [0u8; 32];
-        for j in 0..32 {
-            x[32-j-1] = bytes[j];
-        }
-        transcript.update_with_u256(x);
-        let mut y = [0u8; 32];
-        for j in 32..64 {
-            y[64-j-1] = bytes[j];
-        }
-        transcript.update_with_u256(y);
-
-        let mut bytes = [0u8; 65];
-        proof.pi_wz.write(bytes.as_mut())?;
-        let mut x = [0u8; 32];
-        for j in 0..32 {
-            x[32-j-1] = bytes[j];
-        }
-        transcript.update_with_u256(x);
-        let mut y = [0u8; 32];
-        for j in 32..64 {
-            y[64-j-1] = bytes[j];
-        }
-        transcript.update_with_u256(y);
+        transcript.update_with_g1(&proof.pi_w)?;
+        transcript.update_with_g1(&proof.pi_wz)?;
 
         let u: E::Fr = transcript.generate_challenge();
 
-        let result = PCKey::<E>::verifier_equality_check(&vs, evals, public_inputs);
-        assert!(result);
-
-        let result = pckey.verify_pc(&vs, vk, proof, v, u);
-        Ok(result)
+        Ok((vs, v, u))
     }
 }
 
@@ -416,8 +456,10 @@ impl<E: PairingEngine> Plonk<E> {
 mod tests {
     use ark_ff::BigInteger256;
     use ark_ff::ToBytes;
+    use ark_ff::FromBytes;
     use ark_ff::BigInteger;
     use ark_ff::PrimeField;
+    use ark_ff::Field;
     // use ark_ff::FftField as Field;
     // use ark_poly::univariate::DensePolynomial;
     use ark_poly::EvaluationDomain;
@@ -682,4 +724,147 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_verification_trace() -> Result<(), Error>
+    {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let mut pckey = PCKey::setup(64, rng);
+        let (pk, vk) = PlonkInst::keygen(&mut pckey, &cs, ks())?;
+        let proof = PlonkInst::prove(&mut pckey, &pk, &cs)?;
+
+        let trace = PlonkInst::generate_verification_trace(&vk, &proof, cs.public_inputs())?;
+        let labels: Vec<_> = trace.challenges.iter().map(|(label, _)| *label).collect();
+        assert_eq!(labels, ["beta", "gamma", "alpha", "zeta", "v", "u"]);
+        assert_eq!(trace.evaluations.len(), proof.evaluations.len() + 3);
+        assert_eq!(trace.coefficients.len(), 24);
+
+        // the identity the verifier checks at zeta, from the trace alone
+        let value = |values: &[(&str, Fr)], label: &str| {
+            values.iter().find(|(l, _)| *l == label).unwrap().1
+        };
+        let evals = &trace.evaluations;
+        let (alpha, beta, gamma) = (trace.challenges[2].1, trace.challenges[0].1, trace.challenges[1].1);
+        let lhs = value(evals, "vanishing") * value(evals, "t");
+        let rhs = value(evals, "r") + value(evals, "pi")
+            - value(evals, "z_shifted")
+            * (value(evals, "w_1") + beta * value(evals, "sigma_1") + gamma)
+            * (value(evals, "w_2") + beta * value(evals, "sigma_2") + gamma)
+            * (value(evals, "w_3") + beta * value(evals, "sigma_3") + gamma)
+            * (value(evals, "w_0") + gamma)
+            * alpha
+            - value(evals, "l1") * alpha.square();
+        assert_eq!(lhs, rhs);
+
+        let mut bytes = Vec::new();
+        trace.write(&mut bytes)?;
+        assert_eq!(bytes.len(), 32 * (6 + 13 + 24));
+
+        let truncated = Proof {
+            commitments: proof.commitments.clone(),
+            evaluations: proof.evaluations[..9].to_vec(),
+            pi_w: proof.pi_w,
+            pi_wz: proof.pi_wz,
+        };
+        match PlonkInst::generate_verification_trace(&vk, &truncated, cs.public_inputs()) {
+            Err(Error::MissingEvaluation(label)) => assert_eq!(label, "r"),
+            _ => panic!("expected a missing evaluation"),
+        }
+
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), &proof, &mut pckey)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepared_verifier() -> Result<(), Error>
+    {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let mut pckey = PCKey::setup(64, rng);
+        let (pk, vk) = PlonkInst::keygen(&mut pckey, &cs, ks())?;
+        let proof = PlonkInst::prove(&mut pckey, &pk, &cs)?;
+
+        let mut bytes = Vec::new();
+        pckey.vk.prepare().write(&mut bytes).unwrap();
+        let pvk = PreparedVKey::<Bn254>::read(&bytes[..]).unwrap();
+        assert!(PlonkInst::verify_prepared(&vk, cs.public_inputs(), &proof, &pvk)?);
+
+        let mut wrong = cs.public_inputs().to_vec();
+        wrong[0] += Fr::one();
+        assert!(!PlonkInst::verify_prepared(&vk, &wrong, &proof, &pvk)?);
+
+        let (lhs, rhs) = PlonkInst::pairing_inputs(&vk, cs.public_inputs(), &proof, pvk.g)?;
+        assert!(pvk.check_pairing(lhs, rhs));
+        assert!(!pvk.check_pairing(rhs, lhs));
+
+        let words = pairing_input_words(&lhs, &rhs, &pckey);
+        assert_eq!(words.len(), 12);
+        assert_eq!(words[0], format!("0x{}", hex::encode((-rhs).x.into_repr().to_bytes_be())));
+        assert_eq!(words[2], format!("0x{}", hex::encode(pckey.vk.h.x.c1.into_repr().to_bytes_be())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_test_vector() -> Result<(), Error>
+    {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let mut pckey = PCKey::setup(64, rng);
+        let (pk, vk) = PlonkInst::keygen(&mut pckey, &cs, ks())?;
+        let proof = PlonkInst::prove(&mut pckey, &pk, &cs)?;
+
+        let vector = test_vector("valid", &vk, &mut pckey, cs.public_inputs(), &proof)?;
+        assert!(vector.verifies);
+        assert_eq!(vector.pairing.len(), 12);
+        assert_eq!(vector.vk.len(), 30);
+        assert_eq!(vector.proof, proof_words(&proof));
+        assert_eq!(vector.challenges.len(), 6);
+        assert_eq!(vector.evaluations.len(), proof.evaluations.len());
+        assert_eq!(vector.intermediates.len(), 3 + 24);
+        assert!(vector.to_json().ends_with(",\"verifies\":true}"));
+
+        let mut wrong = cs.public_inputs().to_vec();
+        wrong[0] += Fr::one();
+        let vector = test_vector("invalid", &vk, &mut pckey, &wrong, &proof)?;
+        assert!(!vector.verifies);
+        let input = format!("0x{}", hex::encode(wrong[0].into_repr().to_bytes_be()));
+        assert_eq!(vector.public_inputs[0], input);
+        Ok(())
+    }
+
+    #[test]
+    fn test_solidity_verifier() -> Result<(), Error>
+    {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let mut pckey = PCKey::setup(64, rng);
+        let (pk, vk) = PlonkInst::keygen(&mut pckey, &cs, ks())?;
+        let proof = PlonkInst::prove(&mut pckey, &pk, &cs)?;
+
+        let source = solidity_verifier("CircuitVerifier", &vk, &pckey, cs.public_inputs().len());
+        assert!(source.starts_with("pragma solidity"));
+        assert!(source.contains("contract CircuitVerifier is Plonk4SingleVerifierWithAccessToDNext {"));
+        assert!(!source.contains("console") && !source.contains("import "));
+        assert_eq!(source.matches("contract ").count(), 2);
+        assert_eq!(source.matches("function deserialize_proof(").count(), 1);
+        assert!(!source.contains("test_interface"));
+
+        let hex = |x: &ark_bn254::Fq| hex::encode(x.into_repr().to_bytes_be());
+        assert!(source.contains(&format!("return G1Point(0x{}, 0x{});", hex(&pckey.vk.g.x), hex(&pckey.vk.g.y))));
+        assert!(source.contains(&hex(&pckey.vk.h.x.c1)));
+        assert!(source.contains(&hex(&pckey.vk.beta_h.y.c0)));
+        assert!(source.contains(&format!("vk.num_inputs = {};", cs.public_inputs().len())));
+        assert_eq!(source.matches("PairingsBn254.new_g1(0x").count(), 10);
+
+        let words = proof_words(&proof);
+        assert_eq!(words.len(), 32);
+        assert!(words.iter().all(|w| w.len() == 2 + 64));
+        assert_eq!(words[27], format!("0x{}", hex::encode(proof.evaluations[6].into_repr().to_bytes_be())));
+        Ok(())
+    }
 }
@@ -1,3 +1,4 @@
+use ark_bn254::{Bn254, Fq2};
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_poly::polynomial::univariate::DensePolynomial;
 
@@ -7,9 +8,10 @@ use std::ops::AddAssign;
 use std::ops::SubAssign;
 use std::ops::Div;
 use ark_ec::msm::FixedBaseMSM;
-use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_ff::{Field, FromBytes, One, PrimeField, ToBytes, UniformRand, Zero};
 use ark_poly::{EvaluationDomain, UVPolynomial};
 use rand_core::RngCore;
+use std::io::{self, Read, Write};
 use crate::ahp::VerifierState;
 use crate::{Proof, VerifierKey};
 use crate::utils::{evaluate_first_lagrange_poly, evaluate_vanishing_poly, generator, pad_to_size};
@@ -35,6 +37,71 @@ pub struct VKey<E: PairingEngine> {
     pub beta_h: E::G2Affine,
 }
 
+/// `VKey` with `H` and `beta H` prepared for the Miller loop, once for all
+/// proofs, so that the final check of a verifier that loads it only runs
+/// the pairings of its two G1 points, see `PCKey::pairing_inputs`.
+#[derive(Clone)]
+pub struct PreparedVKey<E: PairingEngine> {
+    /// The generator of G1.
+    pub g: E::G1Affine,
+    pub prepared_h: E::G2Prepared,
+    pub prepared_beta_h: E::G2Prepared,
+}
+
+impl<E: PairingEngine> VKey<E> {
+    pub fn prepare(&self) -> PreparedVKey<E> {
+        PreparedVKey {
+            g: self.g,
+            prepared_h: self.h.into(),
+            prepared_beta_h: self.beta_h.into(),
+        }
+    }
+}
+
+impl<E: PairingEngine> PreparedVKey<E> {
+    /// Whether `e(lhs, beta H) = e(rhs, H)`, checked as one product of
+    /// pairings that shares the final exponentiation.
+    pub fn check_pairing(&self, lhs: E::G1Affine, rhs: E::G1Affine) -> bool {
+        E::product_of_pairings(&[
+            (lhs.into(), self.prepared_beta_h.clone()),
+            ((-rhs).into(), self.prepared_h.clone()),
+        ])
+        .is_one()
+    }
+}
+
+/// `g`, then each prepared point as the number of its line coefficients, as
+/// a `u64`, the coefficients and whether it is the point at infinity.
+impl ToBytes for PreparedVKey<Bn254> {
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.g.write(&mut writer)?;
+        for prepared in &[&self.prepared_h, &self.prepared_beta_h] {
+            (prepared.ell_coeffs.len() as u64).write(&mut writer)?;
+            prepared.write(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromBytes for PreparedVKey<Bn254> {
+    fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let g = FromBytes::read(&mut reader)?;
+        let mut read_prepared = || -> io::Result<<Bn254 as PairingEngine>::G2Prepared> {
+            let len = u64::read(&mut reader)?;
+            let mut ell_coeffs = Vec::new();
+            for _ in 0..len {
+                let (a, b, c) = (Fq2::read(&mut reader)?, Fq2::read(&mut reader)?, Fq2::read(&mut reader)?);
+                ell_coeffs.push((a, b, c));
+            }
+            let infinity = bool::read(&mut reader)?;
+            Ok(ark_ec::bn::G2Prepared { ell_coeffs, infinity })
+        };
+        let prepared_h = read_prepared()?;
+        let prepared_beta_h = read_prepared()?;
+        Ok(Self { g, prepared_h, prepared_beta_h })
+    }
+}
+
 impl<E: PairingEngine> PCKey<E> {
 
     pub fn setup<R>(max_degree :usize, rng: &mut R) -> Self
@@ -370,14 +437,92 @@ impl<E: PairingEngine> PCKey<E> {
         v: E::Fr,
         u: E::Fr,
     ) -> bool {
+        let (lhs, rhs) = Self::pairing_inputs(self.vk.g, vs, vk, proof, v, u);
+        self.vk.prepare().check_pairing(lhs, rhs)
+    }
+
+    /// The two G1 points `(lhs, rhs)` of the final check of `verify_pc`,
+    /// `e(lhs, beta H) = e(rhs, H)`, with `g` the generator of G1 of the key.
+    /// Everything else `verify_pc` does happens here, off the pairings.
+    pub fn pairing_inputs(
+        g: E::G1Affine,
+        vs: &VerifierState<'_, E::Fr>,
+        vk: &VerifierKey<E>,
+        proof: &Proof<E>,
+        v: E::Fr,
+        u: E::Fr,
+    ) -> (E::G1Affine, E::G1Affine) {
         //q0 q1 q2 q3 qm qc sigma_0 1 2 3
-        let comms1 = vk.comms.clone();
+        let comms1 = &vk.comms;
         // [w0123] [z] [t1234]
-        let comms2 = proof.commitments.clone();
-        //w123 0, sigma_1 2 3, z^, t,  r
-        let evals = proof.evaluations.clone();
+        let comms2 = &proof.commitments;
+        let coeffs = Self::verifier_coefficients(vs, &proof.evaluations, v, u);
+        let combine = |points: Vec<E::G1Projective>, scalars: &[(&str, E::Fr)]| {
+            points
+                .into_iter()
+                .zip(scalars)
+                .fold(E::G1Projective::zero(), |acc, (p, (_, c))| acc + p.mul(c.into_repr()))
+        };
+
+        let comm_r = combine(
+            vec![
+                comms1[0].0.into_projective(),
+                comms1[1].0.into_projective(),
+                comms1[2].0.into_projective(),
+                comms1[3].0.into_projective(),
+                comms1[4].0.into_projective(),
+                comms1[5].0.into_projective(),
+                comms2[1][0].0.into_projective(),
+                comms1[6].0.into_projective(),
+            ],
+            &coeffs[..8],
+        );
 
-        let ks = vk.info.ks;
+        let full_batched_polynomial_commitment = combine(
+            vec![
+                comms2[2][0].0.into_projective(),
+                comms2[2][1].0.into_projective(),
+                comms2[2][2].0.into_projective(),
+                comms2[2][3].0.into_projective(),
+                comm_r,
+                comms2[0][1].0.into_projective(),
+                comms2[0][2].0.into_projective(),
+                comms2[0][3].0.into_projective(),
+                comms2[0][0].0.into_projective(),
+                comms1[7].0.into_projective(),
+                comms1[8].0.into_projective(),
+                comms1[9].0.into_projective(),
+                comms2[1][0].0.into_projective(),
+            ],
+            &coeffs[8..21],
+        );
+
+        let group_encoded_batch_evaluation = g.into_projective().mul(coeffs[21].1.into_repr());
+
+        let lhs = proof.pi_w.0.into_projective() + proof.pi_wz.0.into_projective().mul(u.into_repr());
+
+        let rhs = combine(
+            vec![proof.pi_w.0.into_projective(), proof.pi_wz.0.into_projective()],
+            &coeffs[22..],
+        ) + full_batched_polynomial_commitment
+            - group_encoded_batch_evaluation;
+
+        (lhs.into_affine(), rhs.into_affine())
+    }
+
+    /// The coefficients `verify_pc` combines commitments with, labelled and
+    /// in order: those of the commitments in `[r]`, those of the commitments
+    /// batched at zeta and of `[z]` at the shifted zeta, the batched
+    /// evaluation, which multiplies `g`, and those of `[W]` and `[Wz]` in the
+    /// pairing. `evals` are those of the proof.
+    pub fn verifier_coefficients(
+        vs: &VerifierState<'_, E::Fr>,
+        evals: &[E::Fr],
+        v: E::Fr,
+        u: E::Fr,
+    ) -> Vec<(&'static str, E::Fr)> {
+        //w123 0, sigma_1 2 3, z^, t,  r
+        let ks = vs.info.ks;
         let alpha = vs.alpha.unwrap();
         let beta = vs.beta.unwrap();
         let gamma = vs.gamma.unwrap();
@@ -392,79 +537,62 @@ impl<E: PairingEngine> PCKey<E> {
         let zeta_2n = zeta_n.square();
         let zeta_3n: E::Fr = zeta_n * zeta_2n;
 
-        let v_2 = v.square();
-        let v_3 = v*v_2;
-        let v_4 = v_2.square();
-        let v_5 = v*v_4;
-        let v_6 = v*v_5;
-        let v_7 = v*v_6;
-        let v_8 = v*v_7;
-
-        let comm_r = comms1[0].0.into_projective().mul(evals[3].into_repr())
-            + comms1[1].0.into_projective().mul(evals[0].into_repr())
-            + comms1[2].0.into_projective().mul(evals[1].into_repr())
-            + comms1[3].0.into_projective().mul(evals[2].into_repr())
-            + comms1[4].0.into_projective().mul((evals[0] * evals[1]).into_repr())
-            + comms1[5].0.into_projective()
-
-            + comms2[1][0].0.into_projective()
-            .mul((alpha * (evals[3] + beta*ks[0]*zeta + gamma)
+        //v^0..v^8
+        let mut powers_of_v = vec![E::Fr::one()];
+        for i in 0..8 {
+            powers_of_v.push(powers_of_v[i] * v);
+        }
+        // t, r, w123 0, sigma_1 2 3
+        let batch_evaluation = [8, 9, 0, 1, 2, 3, 4, 5, 6]
+            .iter()
+            .zip(&powers_of_v)
+            .fold(u * evals[7], |acc, (&i, v_i)| acc + *v_i * evals[i]);
+
+        vec![
+            ("r.q_0", evals[3]),
+            ("r.q_1", evals[0]),
+            ("r.q_2", evals[1]),
+            ("r.q_3", evals[2]),
+            ("r.q_m", evals[0] * evals[1]),
+            ("r.q_c", E::Fr::one()),
+            ("r.z", alpha * (evals[3] + beta*ks[0]*zeta + gamma)
                 * (evals[0] + beta*ks[1]*zeta + gamma)
                 * (evals[1] + beta*ks[2]*zeta + gamma)
-                * (evals[2] + beta*ks[3]*zeta + gamma)).into_repr())
-
-            - comms1[6].0.into_projective()
-            .mul((alpha * beta * evals[7]
+                * (evals[2] + beta*ks[3]*zeta + gamma)
+                + l1_zeta * alpha_2),
+            ("r.sigma_0", -(alpha * beta * evals[7]
                 * (evals[0] + beta*evals[4] + gamma)
                 * (evals[1] + beta*evals[5] + gamma)
-                * (evals[2] + beta*evals[6] + gamma)).into_repr())
-
-            + comms2[1][0].0.into_projective()
-            .mul((l1_zeta * alpha_2).into_repr());
-
-        let full_batched_polynomial_commitment = comms2[2][0].0.into_projective()
-            + comms2[2][1].0.into_projective().mul(zeta_n.into_repr())
-            + comms2[2][2].0.into_projective().mul(zeta_2n.into_repr())
-            + comms2[2][3].0.into_projective().mul(zeta_3n.into_repr())
-
-            + comm_r.mul(v.into_repr())
-            + comms2[0][1].0.into_projective().mul(v_2.into_repr())
-            + comms2[0][2].0.into_projective().mul(v_3.into_repr())
-            + comms2[0][3].0.into_projective().mul(v_4.into_repr())
-            + comms2[0][0].0.into_projective().mul(v_5.into_repr())
-            + comms1[7].0.into_projective().mul(v_6.into_repr())
-            + comms1[8].0.into_projective().mul(v_7.into_repr())
-            + comms1[9].0.into_projective().mul(v_8.into_repr())
-
-            + comms2[1][0].0.into_projective().mul(u.into_repr());
-
-        let group_encoded_batch_evaluation = self.vk.g.into_projective()
-            .mul(
-            (evals[8]
-                + v*evals[9]
-                + v_2*evals[0]
-                + v_3*evals[1]
-                + v_4*evals[2]
-                + v_5*evals[3]
-                + v_6*evals[4]
-                + v_7*evals[5]
-                + v_8*evals[6]
-                + u*evals[7]).into_repr()
-            );
-
-        let inner = proof.pi_w.0.into_projective() + proof.pi_wz.0.into_projective().mul(u.into_repr());
-        let lhs = E::pairing(inner, self.vk.beta_h);
-
-        let omega: E::Fr = generator(vs.info.domain_n.clone());
-        let inner = proof.pi_w.0.into_projective().mul(zeta.into_repr())
-            + proof.pi_wz.0.into_projective().mul((u * zeta * omega).into_repr())
-            + full_batched_polynomial_commitment
-            - group_encoded_batch_evaluation;
-
-        let rhs = E::pairing(inner, self.vk.h);
-
-        lhs == rhs
+                * (evals[2] + beta*evals[6] + gamma))),
+            ("t_0", E::Fr::one()),
+            ("t_1", zeta_n),
+            ("t_2", zeta_2n),
+            ("t_3", zeta_3n),
+            ("r", powers_of_v[1]),
+            ("w_1", powers_of_v[2]),
+            ("w_2", powers_of_v[3]),
+            ("w_3", powers_of_v[4]),
+            ("w_0", powers_of_v[5]),
+            ("sigma_1", powers_of_v[6]),
+            ("sigma_2", powers_of_v[7]),
+            ("sigma_3", powers_of_v[8]),
+            ("z", u),
+            ("batch_evaluation", batch_evaluation),
+            ("pi_w", zeta),
+            ("pi_wz", u * zeta * generator(vs.info.domain_n)),
+        ]
+    }
 
+    /// The public inputs at `zeta`, as the contracts evaluate them.
+    pub fn evaluate_public_inputs(
+        domain_n: impl EvaluationDomain<E::Fr>,
+        public_inputs: &[E::Fr],
+        zeta: E::Fr,
+    ) -> E::Fr {
+        let pi_n = pad_to_size(public_inputs, domain_n.size());
+        let pi_poly = DensePolynomial::from_coefficients_vec(pi_n);
+        let pi_poly = LabeledPolynomial::<E::Fr>::new("pi".into(), pi_poly, None, None);
+        pi_poly.evaluate(&zeta)
     }
 
     pub fn verifier_equality_check(
@@ -482,12 +610,7 @@ impl<E: PairingEngine> PCKey<E> {
         let domain_n = vs.info.domain_n.clone();
 
         let v_zeta = evaluate_vanishing_poly(domain_n.clone(), zeta);
-        let pi_zeta = {
-            let pi_n = pad_to_size(public_inputs, domain_n.size());
-            let pi_poly = DensePolynomial::from_coefficients_vec(pi_n);
-            let pi_poly = LabeledPolynomial::<E::Fr>::new("pi".into(), pi_poly, None, None);
-            pi_poly.evaluate(&zeta)
-        };
+        let pi_zeta = Self::evaluate_public_inputs(domain_n.clone(), public_inputs, zeta);
 
         let l1_zeta = evaluate_first_lagrange_poly(vs.info.domain_n, zeta);
         let alpha_2 = alpha.square();
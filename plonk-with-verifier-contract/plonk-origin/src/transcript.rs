@@ -1,5 +1,6 @@
 use sha3::{Digest, Keccak256};
 use ark_ff::{BigInteger, FftField as Field, PrimeField, ToBytes};
+use std::io;
 
 //the same as on the contracts
 pub struct TranscriptLibrary {
@@ -52,6 +53,23 @@ impl TranscriptLibrary {
         self.update_with_u256(value);
     }
 
+    /// Absorbs a point of G1 as the contracts do, its coordinates as two
+    /// big-endian words.
+    pub fn update_with_g1(&mut self, point: &impl ToBytes) -> io::Result<()> {
+        //65 Preventing border crossing
+        let mut bytes = [0u8; 65];
+        point.write(bytes.as_mut())?;
+        let mut x = [0u8; 32];
+        let mut y = [0u8; 32];
+        for j in 0..32 {
+            x[32-j-1] = bytes[j];
+            y[32-j-1] = bytes[32+j];
+        }
+        self.update_with_u256(x);
+        self.update_with_u256(y);
+        Ok(())
+    }
+
     fn change_u32_to_bytes(&value: &u32) -> [u8; 4] {
         let musk = 0x000f as u32;
         let mut res = [0u8; 4];
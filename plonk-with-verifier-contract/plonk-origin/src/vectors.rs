@@ -0,0 +1,112 @@
+//! Test vectors for the Solidity verifier of `plonk-verify-test`, whose
+//! transcript hashes big-endian words with Keccak and so differs from the
+//! one of `zkp-plonk`. They follow the layout of the vectors of
+//! `zkp-plonk`, with every value in the words the contract takes.
+
+use ark_bn254::{Bn254, Fr};
+use ark_std::{string::String, vec::Vec};
+use std::fmt;
+
+use crate::codegen::{pairing_input_words, verification_key_words, word};
+use crate::error::Error;
+use crate::pc::PCKey;
+use crate::{Plonk, Proof, VerifierKey};
+
+/// A proof and what the verifier contract derives from it, see
+/// `test_vector`.
+pub struct TestVector {
+    pub name: String,
+    /// the words of the key in the order `test_interface` reads them, see
+    /// `verification_key_words`.
+    pub vk: Vec<String>,
+    /// the words of the proof, see `proof_words`.
+    pub proof: Vec<String>,
+    pub public_inputs: Vec<String>,
+    /// beta, gamma, alpha, zeta, v and u.
+    pub challenges: Vec<(&'static str, String)>,
+    /// the evaluations of the proof, labelled as in the verification trace.
+    pub evaluations: Vec<(&'static str, String)>,
+    /// the vanishing polynomial, the first Lagrange polynomial and the
+    /// public input polynomial at zeta, then the coefficients of the
+    /// commitments, see `PCKey::verifier_coefficients`.
+    pub intermediates: Vec<(&'static str, String)>,
+    /// the input of the final pairing check, see `pairing_input_words`.
+    pub pairing: Vec<String>,
+    /// whether the proof verifies under the key and public inputs.
+    pub verifies: bool,
+}
+
+/// The vector `name` of `proof` under `vk` and `public_inputs`.
+pub fn test_vector(
+    name: &str,
+    vk: &VerifierKey<Bn254>,
+    pckey: &mut PCKey<Bn254>,
+    public_inputs: &[Fr],
+    proof: &Proof<Bn254>,
+) -> Result<TestVector, Error> {
+    let trace = Plonk::<Bn254>::generate_verification_trace(vk, proof, public_inputs)?;
+    let words = |values: &[(&'static str, Fr)]| -> Vec<_> {
+        values.iter().map(|(label, value)| (*label, word(value))).collect()
+    };
+    let (evaluations, intermediates) = trace.evaluations.split_at(proof.evaluations.len());
+    let mut intermediates = words(intermediates);
+    intermediates.extend(words(&trace.coefficients));
+    let (lhs, rhs) = Plonk::<Bn254>::pairing_inputs(vk, public_inputs, proof, pckey.vk.g)?;
+
+    Ok(TestVector {
+        name: name.into(),
+        vk: verification_key_words(vk, pckey, public_inputs.len()),
+        proof: crate::proof_words(proof),
+        public_inputs: public_inputs.iter().map(word).collect(),
+        challenges: words(&trace.challenges),
+        evaluations: words(evaluations),
+        intermediates,
+        pairing: pairing_input_words(&lhs, &rhs, pckey),
+        verifies: Plonk::<Bn254>::verify(vk, public_inputs, proof, pckey)?,
+    })
+}
+
+impl TestVector {
+    /// Writes the vector as a JSON object, with the words in `0x` hex.
+    pub fn write_json<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "{{\"name\":\"{}\"", self.name)?;
+        write!(w, ",\"vk\":[{}]", Words(&self.vk))?;
+        write!(w, ",\"proof\":[{}]", Words(&self.proof))?;
+        write!(w, ",\"public_inputs\":[{}]", Words(&self.public_inputs))?;
+        write!(w, ",\"challenges\":{{{}}}", Labelled(&self.challenges))?;
+        write!(w, ",\"evaluations\":{{{}}}", Labelled(&self.evaluations))?;
+        write!(w, ",\"intermediates\":{{{}}}", Labelled(&self.intermediates))?;
+        write!(w, ",\"pairing\":[{}]", Words(&self.pairing))?;
+        write!(w, ",\"verifies\":{}}}", self.verifies)
+    }
+
+    /// The vector as JSON, see `write_json`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json).map(|_| json).unwrap_or_default()
+    }
+}
+
+struct Words<'a>(&'a [String]);
+
+impl fmt::Display for Words<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, word) in self.0.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(f, "{}\"{}\"", sep, word)?;
+        }
+        Ok(())
+    }
+}
+
+struct Labelled<'a>(&'a [(&'static str, String)]);
+
+impl fmt::Display for Labelled<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (label, word)) in self.0.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(f, "{}\"{}\":\"{}\"", sep, label, word)?;
+        }
+        Ok(())
+    }
+}
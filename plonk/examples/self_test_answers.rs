@@ -0,0 +1,33 @@
+//! Records the known answers of `Plonk::self_test` for each curve and
+//! scheme, from a build reviewed as the reference. Run again only when the
+//! encoding or the protocol changes on purpose.
+//!
+//!     cargo run --example self_test_answers
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::FftField;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::{ipa_pc::InnerProductArgPC, marlin_pc::MarlinKZG10};
+use blake2::Blake2s;
+use std::{fs, path::Path};
+use zkp_plonk::{Plonk, PCS};
+
+fn record<F, PC>(name: &str)
+where
+    F: FftField,
+    PC: PCS<F>,
+{
+    let (vk, proof, public_inputs) =
+        Plonk::<F, Blake2s, PC>::self_test_answer().expect("self test answer");
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/self_test");
+    for (extension, bytes) in [("vk", vk), ("proof", proof), ("inputs", public_inputs)].iter() {
+        let path = dir.join(format!("{}.{}", name, extension));
+        fs::write(&path, bytes).expect("write known answer");
+    }
+}
+
+fn main() {
+    record::<Fr, MarlinKZG10<Bls12_381, DensePolynomial<Fr>>>("bls12_381-kzg-blake2s");
+    type Ipa = InnerProductArgPC<ark_pallas::Affine, Blake2s, DensePolynomial<ark_pallas::Fr>>;
+    record::<ark_pallas::Fr, Ipa>("pallas-ipa-blake2s");
+}
@@ -0,0 +1,76 @@
+//! Prints JSON test vectors for verifiers in other languages: a valid proof
+//! and one under wrong public inputs, for each commitment scheme of this
+//! crate. Those of the Solidity verifier come from `plonk-origin`.
+//!
+//!     cargo run --example test_vectors > plonk-vectors.json
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::FftField;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::{ipa_pc::InnerProductArgPC, marlin_pc::MarlinKZG10};
+use ark_std::test_rng;
+use blake2::Blake2s;
+use zkp_plonk::{Composer, Error, Plonk, TestVector, PCS};
+
+/// x^3 + x + 5 == 35, with 35 as public input.
+fn circuit<F: FftField>() -> Composer<F> {
+    let mut cs = Composer::new();
+    let x = F::from(3_u64);
+    let var_x = cs.alloc_and_assign(x);
+    let var_x2 = cs.alloc_and_assign(x * x);
+    let var_x3 = cs.alloc_and_assign(x * x * x);
+    let var_y = cs.alloc_and_assign(x * x * x + x + F::from(5_u64));
+    cs.create_mul_gate(var_x, var_x, var_x2, None, F::one(), F::zero(), F::zero());
+    cs.create_mul_gate(var_x2, var_x, var_x3, None, F::one(), F::zero(), F::zero());
+    cs.create_add_gate(
+        (var_x3, F::one()),
+        (var_x, F::one()),
+        var_y,
+        None,
+        F::from(5_u64),
+        F::zero(),
+    );
+    cs.constrain_to_constant(var_y, F::zero(), F::from(35_u64));
+    cs
+}
+
+fn vectors<F, PC>(name: &str) -> Result<Vec<TestVector<F>>, Error<PC::Error>>
+where
+    F: FftField,
+    PC: PCS<F>,
+{
+    let rng = &mut test_rng();
+    let cs = circuit::<F>();
+    let ks = [F::one(), F::from(7_u64), F::from(13_u64), F::from(17_u64)];
+
+    let srs = Plonk::<F, Blake2s, PC>::setup(16, rng)?;
+    let (pk, vk) = Plonk::<F, Blake2s, PC>::keygen(&srs, &cs, &ks)?;
+
+    let proof = Plonk::<F, Blake2s, PC>::prove(&pk, &cs, rng)?;
+    let valid = Plonk::<F, Blake2s, PC>::test_vector(name, &vk, cs.public_inputs(), proof)?;
+
+    let mut public_inputs = cs.public_inputs().to_vec();
+    public_inputs[cs.size() - 1] += F::one();
+    let proof = Plonk::<F, Blake2s, PC>::prove(&pk, &cs, rng)?;
+    let invalid = Plonk::<F, Blake2s, PC>::test_vector(
+        &format!("{}-wrong-public-input", name),
+        &vk,
+        &public_inputs,
+        proof,
+    )?;
+    Ok(vec![valid, invalid])
+}
+
+fn main() {
+    let kzg = vectors::<Fr, MarlinKZG10<Bls12_381, DensePolynomial<Fr>>>("bls12_381-kzg-blake2s")
+        .expect("kzg vectors");
+    type Ipa = InnerProductArgPC<ark_pallas::Affine, Blake2s, DensePolynomial<ark_pallas::Fr>>;
+    let ipa = vectors::<ark_pallas::Fr, Ipa>("pallas-ipa-blake2s").expect("ipa vectors");
+
+    let json: Vec<String> = kzg
+        .iter()
+        .map(TestVector::to_json)
+        .chain(ipa.iter().map(TestVector::to_json))
+        .collect();
+    println!("[{}]", json.join(",\n"));
+}
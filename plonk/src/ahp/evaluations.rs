@@ -1,10 +1,13 @@
-use ark_ff::{FftField as Field, Zero};
-use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+use ark_ff::FftField as Field;
 use ark_poly_commit::{LCTerm, LinearCombination};
-use ark_std::{borrow::Borrow, format, vec, vec::Vec};
+use ark_std::{
+    borrow::{Borrow, Cow},
+    string::String,
+    vec::Vec,
+};
 
 use crate::data_structures::LabeledPolynomial;
-use crate::utils::scalar_mul;
+use crate::utils::{add_scaled, evaluate_many};
 
 use crate::ahp::Error;
 
@@ -17,41 +20,63 @@ impl<'a, F: Field> EvaluationsProvider<F> for ark_poly_commit::Evaluations<F, F>
         let key = (lc.label.clone(), point);
         self.get(&key)
             .copied()
-            .ok_or_else(|| Error::MissingEvaluation(lc.label.clone()))
+            .ok_or_else(|| Error::MissingEvaluation {
+                label: lc.label.clone(),
+            })
     }
 }
 
 impl<F: Field, T: Borrow<LabeledPolynomial<F>>> EvaluationsProvider<F> for Vec<T> {
+    /// The combination is summed in place, each term in parallel chunks,
+    /// and evaluated once, also in parallel chunks.
     fn get_lc_eval(&self, lc: &LinearCombination<F>, point: F) -> Result<F, Error> {
-        let mut acc = DensePolynomial::zero();
-        for (coeff, term) in lc.iter() {
-            acc = if let LCTerm::PolyLabel(label) = term {
-                let poly = self
-                    .iter()
-                    .find(|p| {
-                        let p: &LabeledPolynomial<F> = (*p).borrow();
-                        p.label() == label
-                    })
-                    .ok_or_else(|| {
-                        Error::MissingEvaluation(format!("Missing {} for {}", label, lc.label))
-                    })?
-                    .borrow();
-                acc + scalar_mul(poly, coeff)
-            } else {
-                assert!(term.is_one());
-                acc + DensePolynomial::from_coefficients_vec(vec![*coeff])
-            };
+        let coeffs = lc_coeffs(self, lc)?;
+        Ok(evaluate_many(&[(coeffs.as_ref(), point)])[0])
+    }
+}
+
+/// The coefficients of `lc` over `polys`, borrowed if it is a single
+/// polynomial, for `evaluate_many` to evaluate several combinations at once.
+pub fn lc_coeffs<'a, F: Field, T: Borrow<LabeledPolynomial<F>>>(
+    polys: &'a [T],
+    lc: &LinearCombination<F>,
+) -> Result<Cow<'a, [F]>, Error> {
+    let find = |label: &String| -> Result<&'a LabeledPolynomial<F>, Error> {
+        polys
+            .iter()
+            .map(|p| -> &LabeledPolynomial<F> { p.borrow() })
+            .find(|p| p.label() == label)
+            .ok_or_else(|| Error::MissingPolynomial {
+                label: label.clone(),
+                lc: lc.label.clone(),
+            })
+    };
+    if let [(coeff, LCTerm::PolyLabel(label))] = &lc.terms[..] {
+        if coeff.is_one() {
+            return Ok(Cow::Borrowed(&find(label)?.coeffs));
         }
+    }
 
-        let eval = acc.evaluate(&point);
-        Ok(eval)
+    let mut acc = Vec::new();
+    for (coeff, term) in lc.iter() {
+        if let LCTerm::PolyLabel(label) = term {
+            let poly = find(label)?;
+            if !coeff.is_zero() {
+                add_scaled(&mut acc, &poly.coeffs, coeff);
+            }
+        } else {
+            assert!(term.is_one());
+            add_scaled(&mut acc, &[F::one()], coeff);
+        }
     }
+    Ok(Cow::Owned(acc))
 }
 
 #[cfg(test)]
 mod test {
     use ark_bls12_381::Fr;
-    use ark_ff::UniformRand;
+    use ark_ff::{UniformRand, Zero};
+    use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
     use ark_std::{test_rng, vec};
 
     use rand_core::RngCore;
@@ -87,7 +112,7 @@ mod test {
         let eval: Fr = polys
             .iter()
             .zip(rands.iter())
-            .map(|(p, r)| *r * p.evaluate(&zeta))
+            .map(|(p, r)| *r * p.polynomial().evaluate(&zeta))
             .sum();
         assert_eq!(eval, lc_eval);
         Ok(())
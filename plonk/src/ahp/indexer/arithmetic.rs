@@ -1,73 +1,92 @@
 use ark_ff::FftField as Field;
-use ark_poly::EvaluationDomain;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
 use ark_poly_commit::LinearCombination;
-use ark_std::{cfg_into_iter, vec, vec::Vec};
+use ark_std::{cfg_into_iter, cfg_iter_mut, vec, vec::Vec};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use crate::utils::MIN_CHUNK;
 
-use crate::data_structures::LabeledPolynomial;
+use super::Column;
+use crate::ahp::Error;
+use crate::utils::shifted;
 
+#[derive(Clone)]
 pub struct ArithmeticKey<F: Field> {
-    pub q_0: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
-    pub q_1: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
-    pub q_2: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
-    pub q_3: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
-    pub q_m: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
-    pub q_c: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
-    pub q_arith: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
+    pub q_0: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub q_1: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub q_2: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub q_3: (DensePolynomial<F>, Vec<F>, Column<F>),
+    /// the coefficient of `w_4`, with five wires only.
+    pub q_4: Option<(DensePolynomial<F>, Vec<F>, Column<F>)>,
+    pub q_m: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub q_c: (DensePolynomial<F>, Vec<F>, Column<F>),
+    /// the coefficient of `w_0` on the next row.
+    pub q_next: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub q_arith: (DensePolynomial<F>, Vec<F>, Column<F>),
 }
 
 impl<F: Field> ArithmeticKey<F> {
+    /// The arithmetic term of the linearisation, with `q_4` if there is a
+    /// `w_4_zeta`, i.e. with five wires.
     pub(crate) fn construct_linear_combination(
         w_zeta: (F, F, F, F),
+        w_4_zeta: Option<F>,
+        w_0_shifted_zeta: F,
         q_arith_zeta: F,
     ) -> LinearCombination<F> {
         let (w_0_eval, w_1_eval, w_2_eval, w_3_eval) = w_zeta;
-        LinearCombination::new(
-            "arithmetic",
-            vec![
-                (q_arith_zeta * w_0_eval, "q_0"),
-                (q_arith_zeta * w_1_eval, "q_1"),
-                (q_arith_zeta * w_2_eval, "q_2"),
-                (q_arith_zeta * w_3_eval, "q_3"),
-                (q_arith_zeta * w_1_eval * w_2_eval, "q_m"),
-                (q_arith_zeta, "q_c"),
-            ],
-        )
-    }
-
-    pub(crate) fn iter(&self) -> impl Iterator<Item = &LabeledPolynomial<F>> {
-        vec![
-            &self.q_0.0,
-            &self.q_1.0,
-            &self.q_2.0,
-            &self.q_3.0,
-            &self.q_m.0,
-            &self.q_c.0,
-            &self.q_arith.0,
-        ]
-        .into_iter()
+        let mut terms = vec![
+            (q_arith_zeta * w_0_eval, "q_0"),
+            (q_arith_zeta * w_1_eval, "q_1"),
+            (q_arith_zeta * w_2_eval, "q_2"),
+            (q_arith_zeta * w_3_eval, "q_3"),
+        ];
+        if let Some(w_4_eval) = w_4_zeta {
+            terms.push((q_arith_zeta * w_4_eval, "q_4"));
+        }
+        terms.extend(vec![
+            (q_arith_zeta * w_1_eval * w_2_eval, "q_m"),
+            (q_arith_zeta, "q_c"),
+            (q_arith_zeta * w_0_shifted_zeta, "q_next"),
+        ]);
+        LinearCombination::new("arithmetic", terms)
     }
 
+    /// Adds the arithmetic term of the quotient on the 4n coset to `t`, with
+    /// `w_4_4n` given with five wires only.
     pub(crate) fn compute_quotient(
         &self,
         domain_4n: impl EvaluationDomain<F> + Sync,
         w_4n: (&[F], &[F], &[F], &[F]),
+        w_4_4n: Option<&[F]>,
         pi_4n: &[F],
-    ) -> Vec<F> {
+        t: &mut [F],
+    ) -> Result<(), Error> {
         let size = domain_4n.size();
+        let n = self.q_arith.1.len();
         let (w_0_4n, w_1_4n, w_2_4n, w_3_4n) = w_4n;
-        let q_0_2 = &self.q_0.2;
-        let q_1_2 = &self.q_1.2;
-        let q_2_2 = &self.q_2.2;
-        let q_3_2 = &self.q_3.2;
-        let q_m_2 = &self.q_m.2;
-        let q_c_2 = &self.q_c.2;
-        let q_arith_2 = &self.q_arith.2;
+        let q_0_2 = self.q_0.2.load()?;
+        let q_1_2 = self.q_1.2.load()?;
+        let q_2_2 = self.q_2.2.load()?;
+        let q_3_2 = self.q_3.2.load()?;
+        let q_m_2 = self.q_m.2.load()?;
+        let q_c_2 = self.q_c.2.load()?;
+        let q_next_2 = self.q_next.2.load()?;
+        let q_arith_2 = self.q_arith.2.load()?;
+        let q_4_2 = match &self.q_4 {
+            Some(q_4) => Some(q_4.2.load()?),
+            None => None,
+        };
+        let wide = |i: usize| match (&q_4_2, w_4_4n) {
+            (Some(q_4_2), Some(w_4_4n)) => q_4_2[i] * w_4_4n[i],
+            _ => F::zero(),
+        };
 
-        cfg_into_iter!((0..size))
+        cfg_into_iter!((0..size), MIN_CHUNK)
             .map(|i| {
+                let next = shifted(i, size, n);
                 Self::evaluate(
                     &w_0_4n[i],
                     &w_1_4n[i],
@@ -81,9 +100,13 @@ impl<F: Field> ArithmeticKey<F> {
                     &q_c_2[i],
                     &q_arith_2[i],
                     &pi_4n[i],
+                    &(q_next_2[i] * w_0_4n[next]),
+                    &wide(i),
                 )
             })
-            .collect()
+            .zip(cfg_iter_mut!(t))
+            .for_each(|(term, t)| *t += term);
+        Ok(())
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -100,6 +123,8 @@ impl<F: Field> ArithmeticKey<F> {
         q_c: &F,
         q_arith: &F,
         pi: &F,
+        next: &F,
+        wide: &F,
     ) -> F {
         if q_arith.is_zero() {
             F::zero()
@@ -110,7 +135,9 @@ impl<F: Field> ArithmeticKey<F> {
                 + (*q_3) * w_3
                 + (*q_m) * w_1 * w_2
                 + q_c
-                + pi)
+                + pi
+                + next
+                + wide)
                 * q_arith
         }
     }
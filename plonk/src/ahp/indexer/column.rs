@@ -0,0 +1,116 @@
+use ark_ff::FftField as Field;
+use ark_serialize::*;
+use ark_std::{borrow::Cow, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::{
+    fs::File,
+    io::{BufReader, Seek, SeekFrom},
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// The evaluations of a polynomial on the 4n coset. They take four times
+/// the memory of the rest of an index, so the index of a key read by
+/// `ProverKey::load_disk_backed` leaves them in its file, and each is read
+/// back only while the term of the quotient using it is computed.
+#[derive(Clone)]
+pub struct Column<F: Field>(Repr<F>);
+
+#[derive(Clone)]
+enum Repr<F: Field> {
+    Memory(Vec<F>),
+    /// the uncompressed encoding of `len` values at `offset` in the file at
+    /// `path`.
+    #[cfg(feature = "std")]
+    File {
+        path: Arc<PathBuf>,
+        offset: u64,
+        len: usize,
+    },
+}
+
+impl<F: Field> From<Vec<F>> for Column<F> {
+    fn from(values: Vec<F>) -> Self {
+        Column(Repr::Memory(values))
+    }
+}
+
+impl<F: Field> Column<F> {
+    /// The values, borrowed when in memory and read from the file
+    /// otherwise.
+    pub fn load(&self) -> Result<Cow<'_, [F]>, SerializationError> {
+        match &self.0 {
+            Repr::Memory(values) => Ok(Cow::Borrowed(values)),
+            #[cfg(feature = "std")]
+            Repr::File { path, offset, .. } => {
+                let mut file = File::open(path.as_path())?;
+                file.seek(SeekFrom::Start(*offset))?;
+                Ok(Cow::Owned(Vec::deserialize_uncompressed(BufReader::new(file))?))
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Repr::Memory(values) => values.len(),
+            #[cfg(feature = "std")]
+            Repr::File { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The column encoded uncompressed at the position of `reader`, in the
+    /// file at `path`, left there. It is read through once, so that it
+    /// only fails to load again if the file changes.
+    #[cfg(feature = "std")]
+    pub(crate) fn on_disk<R: Read + Seek>(
+        path: &Arc<PathBuf>,
+        reader: &mut R,
+    ) -> Result<Self, SerializationError> {
+        let offset = reader.stream_position()?;
+        let len = Vec::<F>::deserialize_uncompressed(&mut *reader)?.len();
+        Ok(Column(Repr::File {
+            path: path.clone(),
+            offset,
+            len,
+        }))
+    }
+
+    /// Writes the encoding of a `Vec` of the values, each by `write`.
+    fn write<W: Write>(
+        &self,
+        mut writer: W,
+        write: impl Fn(&F, &mut W) -> Result<(), SerializationError>,
+    ) -> Result<(), SerializationError> {
+        let values = self.load()?;
+        (values.len() as u64).serialize(&mut writer)?;
+        values.iter().try_for_each(|v| write(v, &mut writer))
+    }
+}
+
+/// Encoded as a `Vec` of its values.
+impl<F: Field> CanonicalSerialize for Column<F> {
+    fn serialize<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.write(writer, |v, w| v.serialize(w))
+    }
+
+    fn serialized_size(&self) -> usize {
+        0u64.serialized_size() + self.len() * F::zero().serialized_size()
+    }
+
+    fn serialize_uncompressed<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.write(writer, |v, w| v.serialize_uncompressed(w))
+    }
+
+    fn serialize_unchecked<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.write(writer, |v, w| v.serialize_unchecked(w))
+    }
+
+    fn uncompressed_size(&self) -> usize {
+        0u64.uncompressed_size() + self.len() * F::zero().uncompressed_size()
+    }
+}
@@ -0,0 +1,100 @@
+use ark_ff::FftField as Field;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
+use ark_poly_commit::LinearCombination;
+use ark_std::{cfg_into_iter, cfg_iter_mut, vec, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use crate::utils::MIN_CHUNK;
+
+use super::Column;
+use crate::ahp::Error;
+use crate::utils::shifted;
+
+/// The selectors of the addition of two points of a twisted Edwards curve
+/// `a x^2 + y^2 = 1 + d x^2 y^2`: `q_curve` is `1` on the row of an addition,
+/// `q_curve_a` and `q_curve_d` are `a` and `d` of its curve.
+///
+/// The row holds the points `(x_1, y_1)` and `(x_2, y_2)` in `w_0..w_3`, and
+/// the next row their sum `(x_3, y_3)` in `w_0, w_1`, then `p = x_1 y_2` and
+/// `r = y_1 x_2` in `w_2, w_3`:
+///
+/// - alpha^17: `p == x_1 y_2`
+/// - alpha^18: `r == y_1 x_2`
+/// - alpha^19: `x_3 (1 + d p r) == p + r`
+/// - alpha^20: `y_3 (1 - d p r) == y_1 y_2 - a x_1 x_2`
+///
+/// Each constraint is linear in the selectors, so none of them is opened.
+#[derive(Clone)]
+pub struct CurveKey<F: Field> {
+    pub q_curve: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub q_curve_a: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub q_curve_d: (DensePolynomial<F>, Vec<F>, Column<F>),
+}
+
+impl<F: Field> CurveKey<F> {
+    /// Adds the curve term of the quotient on the 4n coset to `t`.
+    pub(crate) fn compute_quotient(
+        &self,
+        domain_4n: impl EvaluationDomain<F> + Sync,
+        w_4n: (&[F], &[F], &[F], &[F]),
+        alpha: &F,
+        t: &mut [F],
+    ) -> Result<(), Error> {
+        let (w_0_4n, w_1_4n, w_2_4n, w_3_4n) = w_4n;
+        let size = domain_4n.size();
+        let n = self.q_curve.1.len();
+        let q_curve_2 = self.q_curve.2.load()?;
+        let q_curve_a_2 = self.q_curve_a.2.load()?;
+        let q_curve_d_2 = self.q_curve_d.2.load()?;
+
+        cfg_into_iter!((0..size), MIN_CHUNK)
+            .map(|i| {
+                let next = shifted(i, size, n);
+                if q_curve_2[i].is_zero() && q_curve_a_2[i].is_zero() && q_curve_d_2[i].is_zero() {
+                    return F::zero();
+                }
+                let (q, q_a, q_d) = Self::coefficients(
+                    (w_0_4n[i], w_1_4n[i], w_2_4n[i], w_3_4n[i]),
+                    (w_0_4n[next], w_1_4n[next], w_2_4n[next], w_3_4n[next]),
+                    *alpha,
+                );
+                q_curve_2[i] * q + q_curve_a_2[i] * q_a + q_curve_d_2[i] * q_d
+            })
+            .zip(cfg_iter_mut!(t))
+            .for_each(|(term, t)| *t += term);
+        Ok(())
+    }
+
+    pub(crate) fn construct_linear_combination(
+        w_zeta: (F, F, F, F),
+        w_shifted_zeta: (F, F, F, F),
+        alpha: F,
+    ) -> LinearCombination<F> {
+        let (q, q_a, q_d) = Self::coefficients(w_zeta, w_shifted_zeta, alpha);
+        LinearCombination::new(
+            "curve",
+            vec![(q, "q_curve"), (q_a, "q_curve_a"), (q_d, "q_curve_d")],
+        )
+    }
+
+    /// The coefficients of `q_curve`, `q_curve_a` and `q_curve_d` in the
+    /// constraints of a row.
+    fn coefficients(w: (F, F, F, F), w_next: (F, F, F, F), alpha: F) -> (F, F, F) {
+        let (x_1, y_1, x_2, y_2) = w;
+        let (x_3, y_3, p, r) = w_next;
+        let alpha_17 = alpha.pow(&[17]);
+        let alpha_2 = alpha.square();
+        let alpha_3 = alpha_2 * alpha;
+
+        let q = p - x_1 * y_2
+            + alpha * (r - y_1 * x_2)
+            + alpha_2 * (x_3 - p - r)
+            + alpha_3 * (y_3 - y_1 * y_2);
+        let q_a = alpha_3 * x_1 * x_2;
+        let q_d = alpha_2 * p * r * (x_3 - alpha * y_3);
+
+        (alpha_17 * q, alpha_17 * q_a, alpha_17 * q_d)
+    }
+}
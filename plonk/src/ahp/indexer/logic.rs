@@ -0,0 +1,105 @@
+use ark_ff::FftField as Field;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
+use ark_poly_commit::LinearCombination;
+use ark_std::{cfg_into_iter, cfg_iter_mut, vec, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use crate::utils::MIN_CHUNK;
+
+use super::Column;
+use crate::ahp::Error;
+use crate::utils::shifted;
+
+/// The selector of the logic gates, `1` on a row of an XOR and `2` on a row
+/// of an AND.
+///
+/// A row holds the accumulators of the two inputs and of the output in
+/// `w_0..w_2`, and the product of the limbs of the inputs in `w_3`. The next
+/// row holds the accumulators with one more 2-bit limb, `da`, `db` and `dc`,
+/// and with `s = da + db` and `p = w_3`:
+///
+/// - alpha^13: `da` is in `0..4`
+/// - alpha^14: `db` is in `0..4`
+/// - alpha^15: `p == da * db`
+/// - alpha^16: `6 dc == 6 (2 - q_logic) s - (5 - 3 q_logic) G`
+///
+/// where `G = p (83 - 81 s + 45 p + 18 s^2 - 18 s p + 4 p^2)` is `6 (da & db)`
+/// on limbs, and `da ^ db == s - 2 (da & db)`.
+#[derive(Clone)]
+pub struct LogicKey<F: Field> {
+    pub q_logic: (DensePolynomial<F>, Vec<F>, Column<F>),
+}
+
+impl<F: Field> LogicKey<F> {
+    /// Adds the logic term of the quotient on the 4n coset to `t`.
+    pub(crate) fn compute_quotient(
+        &self,
+        domain_4n: impl EvaluationDomain<F> + Sync,
+        w_4n: (&[F], &[F], &[F], &[F]),
+        alpha: &F,
+        t: &mut [F],
+    ) -> Result<(), Error> {
+        let (w_0_4n, w_1_4n, w_2_4n, w_3_4n) = w_4n;
+        let size = domain_4n.size();
+        let n = self.q_logic.1.len();
+        let q_logic_2 = self.q_logic.2.load()?;
+
+        cfg_into_iter!((0..size), MIN_CHUNK)
+            .map(|i| {
+                let next = shifted(i, size, n);
+                if q_logic_2[i].is_zero() {
+                    F::zero()
+                } else {
+                    q_logic_2[i]
+                        * Self::evaluate(
+                            q_logic_2[i],
+                            (w_0_4n[i], w_1_4n[i], w_2_4n[i], w_3_4n[i]),
+                            (w_0_4n[next], w_1_4n[next], w_2_4n[next]),
+                            *alpha,
+                        )
+                }
+            })
+            .zip(cfg_iter_mut!(t))
+            .for_each(|(term, t)| *t += term);
+        Ok(())
+    }
+
+    pub(crate) fn construct_linear_combination(
+        w_zeta: (F, F, F, F),
+        w_shifted_zeta: (F, F, F),
+        q_logic_zeta: F,
+        alpha: F,
+    ) -> LinearCombination<F> {
+        LinearCombination::new(
+            "logic",
+            vec![(
+                Self::evaluate(q_logic_zeta, w_zeta, w_shifted_zeta, alpha),
+                "q_logic",
+            )],
+        )
+    }
+
+    /// The constraints of a row with selector `q`, before the selector.
+    fn evaluate(q: F, w: (F, F, F, F), w_next: (F, F, F), alpha: F) -> F {
+        let (a, b, c, p) = w;
+        let (a_next, b_next, c_next) = w_next;
+        let four = F::from(4u64);
+        let da = a_next - four * a;
+        let db = b_next - four * b;
+        let dc = c_next - four * c;
+
+        let range = |x: F| x * (x - F::one()) * (x - F::from(2u64)) * (x - F::from(3u64));
+        let s = da + db;
+        let g = p
+            * (F::from(83u64) - F::from(81u64) * s
+                + F::from(45u64) * p
+                + F::from(18u64) * (s.square() - s * p)
+                + four * p.square());
+        let op = F::from(6u64) * (dc - (F::from(2u64) - q) * s)
+            + (F::from(5u64) - F::from(3u64) * q) * g;
+
+        alpha.pow(&[13]) * (range(da) + alpha * (range(db) + alpha * (p - da * db + alpha * op)))
+    }
+}
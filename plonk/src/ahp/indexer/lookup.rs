@@ -0,0 +1,301 @@
+use ark_ff::FftField as Field;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Evaluations};
+use ark_poly_commit::LinearCombination;
+use ark_std::{borrow::Cow, cfg_into_iter, cfg_iter_mut, iter, vec, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use crate::utils::MIN_CHUNK;
+
+use super::Column;
+use crate::ahp::Error;
+use crate::utils::{
+    evaluate_first_lagrange_poly, evaluate_last_lagrange_poly, grand_product, shifted,
+};
+use crate::Map;
+
+/// The lookup argument of plookup over the wires `w_1..w_3`. A row with
+/// `q_lookup = k` queries `f = k * (1 + eta * w_1 + eta^2 * w_2 + eta^3 *
+/// w_3)` in the table `table_0 + eta * table_1 + eta^2 * table_2 + eta^3 *
+/// table_3`, see `Composer::compose_tables`. The rows without a lookup query
+/// zero, which the table holds as padding.
+///
+/// With `h_1` and `h_2` the halves of `f` and the table sorted by the
+/// table, overlapping on one row, and `z_lookup` their grand product:
+///
+/// - `l_1 * (z_lookup - 1) = 0`
+/// - `l_n * (h_1 - h_2(gX)) = 0`
+/// - `(X - g^(n-1)) * (z_lookup * (1 + delta) * (lambda + f) * (lambda * (1
+///   + delta) + table + delta * table(gX)) - z_lookup(gX) * (lambda * (1 +
+///   delta) + h_1 + delta * h_1(gX)) * (lambda * (1 + delta) + h_2 + delta
+///   * h_2(gX))) = 0`
+/// - `l_n * (z_lookup - 1) = 0`
+///
+/// combined with alpha^9 to alpha^12, after the terms of MiMC. The last row
+/// is left out of the grand product, so it never holds a lookup.
+#[derive(Clone)]
+pub struct LookupKey<F: Field> {
+    pub q_lookup: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub table_0: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub table_1: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub table_2: (DensePolynomial<F>, Vec<F>, Column<F>),
+    pub table_3: (DensePolynomial<F>, Vec<F>, Column<F>),
+    /// the evaluations of the last Lagrange polynomial on the 4n coset.
+    pub ln_4n: Column<F>,
+}
+
+/// The evaluations the verifier computes the lookup terms from.
+pub(crate) struct LookupEvals<F: Field> {
+    /// `w_1`, `w_2` and `w_3` at zeta.
+    pub w_zeta: (F, F, F),
+    pub q_lookup_zeta: F,
+    pub table_zeta: F,
+    pub table_shifted_zeta: F,
+    pub h_1_zeta: F,
+    pub h_1_shifted_zeta: F,
+    pub h_2_shifted_zeta: F,
+    pub z_lookup_shifted_zeta: F,
+}
+
+/// `alpha^9` to `alpha^12`.
+fn alphas<F: Field>(alpha: F) -> (F, F, F, F) {
+    let alpha_9 = alpha.square().square().square() * alpha;
+    let alpha_10 = alpha_9 * alpha;
+    let alpha_11 = alpha_10 * alpha;
+    (alpha_9, alpha_10, alpha_11, alpha_11 * alpha)
+}
+
+impl<F: Field> LookupKey<F> {
+    /// The linear combination of the table columns whose evaluations are
+    /// the table.
+    pub(crate) fn table_linear_combination(eta: F) -> LinearCombination<F> {
+        let eta_2 = eta.square();
+        LinearCombination::new(
+            "table",
+            vec![
+                (F::one(), "table_0"),
+                (eta, "table_1"),
+                (eta_2, "table_2"),
+                (eta_2 * eta, "table_3"),
+            ],
+        )
+    }
+
+    /// Writes the table to `table`, on the domain, or on the 4n coset if
+    /// `on_coset`.
+    pub(crate) fn compute_table(
+        &self,
+        eta: &F,
+        on_coset: bool,
+        table: &mut [F],
+    ) -> Result<(), Error> {
+        let (t_0, t_1, t_2, t_3) = if on_coset {
+            (
+                self.table_0.2.load()?,
+                self.table_1.2.load()?,
+                self.table_2.2.load()?,
+                self.table_3.2.load()?,
+            )
+        } else {
+            (
+                Cow::Borrowed(&self.table_0.1[..]),
+                Cow::Borrowed(&self.table_1.1[..]),
+                Cow::Borrowed(&self.table_2.1[..]),
+                Cow::Borrowed(&self.table_3.1[..]),
+            )
+        };
+        let eta_2 = eta.square();
+        let eta_3 = eta_2 * eta;
+        cfg_iter_mut!(table).enumerate().for_each(|(i, t)| {
+            *t = t_0[i] + *eta * t_1[i] + eta_2 * t_2[i] + eta_3 * t_3[i];
+        });
+        Ok(())
+    }
+
+    /// The queries `f` on the domain.
+    pub(crate) fn compute_f(&self, w_n: (&[F], &[F], &[F]), eta: &F) -> Vec<F> {
+        let (w_1_n, w_2_n, w_3_n) = w_n;
+        let q_lookup_1 = &self.q_lookup.1;
+        cfg_into_iter!(0..q_lookup_1.len())
+            .map(|i| {
+                q_lookup_1[i] * (F::one() + *eta * (w_1_n[i] + *eta * (w_2_n[i] + *eta * w_3_n[i])))
+            })
+            .collect()
+    }
+
+    /// `h_1` and `h_2` on the domain: `f`, but its last row, and the table,
+    /// sorted by the table. Fails with the first row whose query is not in
+    /// the table.
+    pub(crate) fn compute_sorted(f: &[F], table: &[F]) -> Result<(Vec<F>, Vec<F>), usize> {
+        let n = table.len();
+        let mut first = Map::new();
+        for (j, value) in table.iter().enumerate() {
+            first.entry(*value).or_insert(j);
+        }
+        let mut counts = vec![0; n];
+        for (i, value) in f[..n - 1].iter().enumerate() {
+            counts[*first.get(value).ok_or(i)?] += 1;
+        }
+
+        // every query follows the first row of the table holding it.
+        let mut sorted = Vec::with_capacity(2 * n - 1);
+        for (value, count) in table.iter().zip(counts) {
+            sorted.extend(iter::repeat(*value).take(count + 1));
+        }
+        let h_2 = sorted[n - 1..].to_vec();
+        sorted.truncate(n);
+        Ok((sorted, h_2))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn compute_z(
+        domain_n: impl EvaluationDomain<F>,
+        f: &[F],
+        table: &[F],
+        h_1: &[F],
+        h_2: &[F],
+        delta: &F,
+        lambda: &F,
+    ) -> (DensePolynomial<F>, Vec<F>) {
+        let n = domain_n.size();
+        let one_delta = F::one() + delta;
+        let lambda_delta = *lambda * one_delta;
+
+        let ratios: Vec<_> = cfg_into_iter!(0..(n - 1))
+            .map(|i| {
+                let numerator = one_delta
+                    * (*lambda + f[i])
+                    * (lambda_delta + table[i] + *delta * table[i + 1]);
+                let denumerator = (lambda_delta + h_1[i] + *delta * h_1[i + 1])
+                    * (lambda_delta + h_2[i] + *delta * h_2[i + 1]);
+                #[cfg(not(feature = "secret-hardened"))]
+                let denumerator = denumerator.inverse().unwrap();
+                #[cfg(feature = "secret-hardened")]
+                let denumerator = crate::utils::ct_inverse(&denumerator);
+
+                numerator * denumerator
+            })
+            .collect();
+
+        let z = grand_product(&ratios);
+        assert_eq!(z[n - 1], F::one());
+        #[cfg(feature = "zeroize")]
+        crate::utils::zeroize(ratios);
+
+        let z_poly = Evaluations::from_vec_and_domain(z.clone(), domain_n).interpolate();
+
+        (z_poly, z)
+    }
+
+    /// Adds the lookup terms of the quotient on the 4n coset to `t`.
+    /// `linear_4n` and `l1_4n` are those of the permutation key.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn compute_quotient(
+        &self,
+        domain_n: impl EvaluationDomain<F>,
+        domain_4n: impl EvaluationDomain<F> + Sync,
+        linear_4n: &[F],
+        l1_4n: &[F],
+        w_4n: (&[F], &[F], &[F]),
+        table_4n: &[F],
+        lookup_4n: (&[F], &[F], &[F]),
+        challenges: (F, F, F),
+        alpha: &F,
+        t: &mut [F],
+    ) -> Result<(), Error> {
+        let (w_1_4n, w_2_4n, w_3_4n) = w_4n;
+        let (h_1_4n, h_2_4n, z_4n) = lookup_4n;
+        let (eta, delta, lambda) = challenges;
+        let (alpha_9, alpha_10, alpha_11, alpha_12) = alphas(*alpha);
+
+        let size = domain_4n.size();
+        let n = domain_n.size();
+        let last = domain_n.element(domain_n.size() - 1);
+        let one_delta = F::one() + delta;
+        let lambda_delta = lambda * one_delta;
+        let q_lookup_2 = self.q_lookup.2.load()?;
+        let ln_4n = self.ln_4n.load()?;
+
+        cfg_into_iter!((0..size), MIN_CHUNK)
+            .map(|i| {
+                let next = shifted(i, size, n);
+                let f = q_lookup_2[i]
+                    * (F::one() + eta * (w_1_4n[i] + eta * (w_2_4n[i] + eta * w_3_4n[i])));
+                let product = z_4n[i]
+                    * one_delta
+                    * (lambda + f)
+                    * (lambda_delta + table_4n[i] + delta * table_4n[next])
+                    - z_4n[next]
+                        * (lambda_delta + h_1_4n[i] + delta * h_1_4n[next])
+                        * (lambda_delta + h_2_4n[i] + delta * h_2_4n[next]);
+
+                (z_4n[i] - F::one()) * l1_4n[i] * alpha_9
+                    + (h_1_4n[i] - h_2_4n[next]) * ln_4n[i] * alpha_10
+                    + (linear_4n[i] - last) * product * alpha_11
+                    + (z_4n[i] - F::one()) * ln_4n[i] * alpha_12
+            })
+            .zip(cfg_iter_mut!(t))
+            .for_each(|(term, t)| *t += term);
+        Ok(())
+    }
+
+    /// The coefficients of `z_lookup` and `h_2` in the lookup terms at zeta,
+    /// and the rest of them, which the verifier adds to `r`.
+    fn linearize(
+        domain_n: impl EvaluationDomain<F>,
+        evals: &LookupEvals<F>,
+        challenges: (F, F, F),
+        alpha: F,
+        zeta: F,
+    ) -> (F, F, F) {
+        let (w_1, w_2, w_3) = evals.w_zeta;
+        let (eta, delta, lambda) = challenges;
+        let (alpha_9, alpha_10, alpha_11, alpha_12) = alphas(alpha);
+
+        let one_delta = F::one() + delta;
+        let lambda_delta = lambda * one_delta;
+        let last = domain_n.element(domain_n.size() - 1);
+        let l1_zeta = evaluate_first_lagrange_poly(domain_n, zeta);
+        let ln_zeta = evaluate_last_lagrange_poly(domain_n, zeta);
+
+        let f = evals.q_lookup_zeta * (F::one() + eta * (w_1 + eta * (w_2 + eta * w_3)));
+        let numerator = (zeta - last)
+            * one_delta
+            * (lambda + f)
+            * (lambda_delta + evals.table_zeta + delta * evals.table_shifted_zeta);
+        let denumerator = (zeta - last)
+            * evals.z_lookup_shifted_zeta
+            * (lambda_delta + evals.h_1_zeta + delta * evals.h_1_shifted_zeta);
+
+        let z_coeff = l1_zeta * alpha_9 + numerator * alpha_11 + ln_zeta * alpha_12;
+        let h_2_coeff = -denumerator * alpha_11;
+        let constant = -l1_zeta * alpha_9
+            + (evals.h_1_zeta - evals.h_2_shifted_zeta) * ln_zeta * alpha_10
+            + h_2_coeff * (lambda_delta + delta * evals.h_2_shifted_zeta)
+            - ln_zeta * alpha_12;
+        (z_coeff, h_2_coeff, constant)
+    }
+
+    pub(crate) fn construct_linear_combination(
+        domain_n: impl EvaluationDomain<F>,
+        evals: &LookupEvals<F>,
+        challenges: (F, F, F),
+        alpha: F,
+        zeta: F,
+    ) -> LinearCombination<F> {
+        let (z_coeff, h_2_coeff, _) = Self::linearize(domain_n, evals, challenges, alpha, zeta);
+        LinearCombination::new("lookup", vec![(z_coeff, "z_lookup"), (h_2_coeff, "h_2")])
+    }
+
+    /// The part of the lookup terms at zeta that is not in `r`.
+    pub(crate) fn evaluate_constant(
+        domain_n: impl EvaluationDomain<F>,
+        evals: &LookupEvals<F>,
+        challenges: (F, F, F),
+        alpha: F,
+        zeta: F,
+    ) -> F {
+        Self::linearize(domain_n, evals, challenges, alpha, zeta).2
+    }
+}
@@ -0,0 +1,177 @@
+use ark_ff::FftField as Field;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, Evaluations};
+use ark_poly_commit::LinearCombination;
+use ark_std::{cfg_into_iter, cfg_iter_mut, vec, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use crate::utils::MIN_CHUNK;
+
+use super::Column;
+use crate::ahp::Error;
+use crate::utils::{evaluate_first_lagrange_poly, grand_product, shifted};
+
+/// The memory argument over the wires. A row with `q_memory = k` records
+/// `f = w_0 + eta * w_1 + eta^2 * w_2 + eta^3 * w_3` in the memory `k`, and a
+/// row with `q_memory = -k` the same record in the sorted trace of `k`, see
+/// `Composer::close_memory`. Both sets of rows hold the same records when
+/// the grand product `z_memory` of `(lambda + f + eta^4 * q_memory) /
+/// (lambda + f - eta^4 * q_memory)` over the domain comes back to one:
+///
+/// - `l_1 * (z_memory - 1) = 0`
+/// - `z_memory * (lambda + f + eta^4 * q_memory) - z_memory(gX) * (lambda +
+///   f - eta^4 * q_memory) = 0`
+///
+/// combined with alpha^21 and alpha^22, after the terms of the curve gate.
+/// The rows without a memory contribute a ratio of one.
+#[derive(Clone)]
+pub struct MemoryKey<F: Field> {
+    pub q_memory: (DensePolynomial<F>, Vec<F>, Column<F>),
+}
+
+/// The evaluations the verifier computes the memory terms from.
+pub(crate) struct MemoryEvals<F: Field> {
+    /// `w_0..w_3` at zeta.
+    pub w_zeta: (F, F, F, F),
+    pub q_memory_zeta: F,
+    pub z_memory_shifted_zeta: F,
+}
+
+/// `alpha^21` and `alpha^22`.
+fn alphas<F: Field>(alpha: F) -> (F, F) {
+    let alpha_4 = alpha.square().square();
+    let alpha_16 = alpha_4.square().square();
+    let alpha_21 = alpha_16 * alpha_4 * alpha;
+    (alpha_21, alpha_21 * alpha)
+}
+
+/// The record `w_0 + eta * w_1 + eta^2 * w_2 + eta^3 * w_3`.
+fn record<F: Field>(w: (F, F, F, F), eta: F) -> F {
+    w.0 + eta * (w.1 + eta * (w.2 + eta * w.3))
+}
+
+impl<F: Field> MemoryKey<F> {
+    /// `z_memory` as a polynomial and on the domain, or `None` if the
+    /// records of the memories do not match their sorted traces.
+    pub(crate) fn compute_z(
+        &self,
+        domain_n: impl EvaluationDomain<F>,
+        w_n: (&[F], &[F], &[F], &[F]),
+        eta: &F,
+        lambda: &F,
+    ) -> Option<(DensePolynomial<F>, Vec<F>)> {
+        let (w_0_n, w_1_n, w_2_n, w_3_n) = w_n;
+        let n = domain_n.size();
+        let eta_4 = eta.square().square();
+        let q_memory_1 = &self.q_memory.1;
+
+        let ratios: Vec<_> = cfg_into_iter!(0..n)
+            .map(|i| {
+                let f = record((w_0_n[i], w_1_n[i], w_2_n[i], w_3_n[i]), *eta);
+                let numerator = *lambda + f + eta_4 * q_memory_1[i];
+                let denumerator = *lambda + f - eta_4 * q_memory_1[i];
+                #[cfg(not(feature = "secret-hardened"))]
+                let denumerator = denumerator.inverse().unwrap();
+                #[cfg(feature = "secret-hardened")]
+                let denumerator = crate::utils::ct_inverse(&denumerator);
+
+                numerator * denumerator
+            })
+            .collect();
+
+        let mut z = grand_product(&ratios);
+        let closed = z.pop() == Some(F::one());
+        #[cfg(feature = "zeroize")]
+        crate::utils::zeroize(ratios);
+        if !closed {
+            #[cfg(feature = "zeroize")]
+            crate::utils::zeroize(z);
+            return None;
+        }
+
+        let z_poly = Evaluations::from_vec_and_domain(z.clone(), domain_n).interpolate();
+
+        Some((z_poly, z))
+    }
+
+    /// Adds the memory terms of the quotient on the 4n coset to `t`.
+    /// `l1_4n` is the one of the permutation key.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn compute_quotient(
+        &self,
+        domain_4n: impl EvaluationDomain<F> + Sync,
+        l1_4n: &[F],
+        w_4n: (&[F], &[F], &[F], &[F]),
+        z_4n: &[F],
+        eta: &F,
+        lambda: &F,
+        alpha: &F,
+        t: &mut [F],
+    ) -> Result<(), Error> {
+        let (w_0_4n, w_1_4n, w_2_4n, w_3_4n) = w_4n;
+        let (alpha_21, alpha_22) = alphas(*alpha);
+        let eta_4 = eta.square().square();
+
+        let size = domain_4n.size();
+        let n = self.q_memory.1.len();
+        let q_memory_2 = self.q_memory.2.load()?;
+
+        cfg_into_iter!((0..size), MIN_CHUNK)
+            .map(|i| {
+                let next = shifted(i, size, n);
+                let f = record((w_0_4n[i], w_1_4n[i], w_2_4n[i], w_3_4n[i]), *eta);
+                let q = eta_4 * q_memory_2[i];
+                let product = z_4n[i] * (*lambda + f + q) - z_4n[next] * (*lambda + f - q);
+
+                (z_4n[i] - F::one()) * l1_4n[i] * alpha_21 + product * alpha_22
+            })
+            .zip(cfg_iter_mut!(t))
+            .for_each(|(term, t)| *t += term);
+        Ok(())
+    }
+
+    /// The coefficient of `z_memory` in the memory terms at zeta, and the
+    /// rest of them, which the verifier adds to `r`.
+    fn linearize(
+        domain_n: impl EvaluationDomain<F>,
+        evals: &MemoryEvals<F>,
+        challenges: (F, F),
+        alpha: F,
+        zeta: F,
+    ) -> (F, F) {
+        let (eta, lambda) = challenges;
+        let (alpha_21, alpha_22) = alphas(alpha);
+        let l1_zeta = evaluate_first_lagrange_poly(domain_n, zeta);
+
+        let f = record(evals.w_zeta, eta);
+        let q = eta.square().square() * evals.q_memory_zeta;
+
+        let z_coeff = l1_zeta * alpha_21 + (lambda + f + q) * alpha_22;
+        let constant =
+            -l1_zeta * alpha_21 - evals.z_memory_shifted_zeta * (lambda + f - q) * alpha_22;
+        (z_coeff, constant)
+    }
+
+    pub(crate) fn construct_linear_combination(
+        domain_n: impl EvaluationDomain<F>,
+        evals: &MemoryEvals<F>,
+        challenges: (F, F),
+        alpha: F,
+        zeta: F,
+    ) -> LinearCombination<F> {
+        let (z_coeff, _) = Self::linearize(domain_n, evals, challenges, alpha, zeta);
+        LinearCombination::new("memory", vec![(z_coeff, "z_memory")])
+    }
+
+    /// The part of the memory terms at zeta that is not in `r`.
+    pub(crate) fn evaluate_constant(
+        domain_n: impl EvaluationDomain<F>,
+        evals: &MemoryEvals<F>,
+        challenges: (F, F),
+        alpha: F,
+        zeta: F,
+    ) -> F {
+        Self::linearize(domain_n, evals, challenges, alpha, zeta).1
+    }
+}
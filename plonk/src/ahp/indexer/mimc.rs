@@ -1,29 +1,31 @@
 use ark_ff::FftField as Field;
-use crate::LabeledPolynomial;
-use ark_poly::EvaluationDomain;
+use super::Column;
+use crate::ahp::Error;
+use crate::utils::shifted;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
 use ark_poly_commit::LinearCombination;
-use ark_std::{cfg_into_iter, vec, vec::Vec};
+use ark_std::{cfg_into_iter, cfg_iter_mut, vec, vec::Vec};
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use crate::utils::MIN_CHUNK;
+
+#[derive(Clone)]
 pub struct MimcKey<F: Field> {
-    pub q_mimc: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
-    //pub q_mimc_c: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
+    pub q_mimc: (DensePolynomial<F>, Vec<F>, Column<F>),
+    //pub q_mimc_c: (DensePolynomial<F>, Vec<F>, Column<F>),
 }
 
 impl<F: Field> MimcKey<F>{
-    pub(crate) fn iter(&self) -> impl Iterator<Item = &LabeledPolynomial<F>> {
-        vec![
-            &self.q_mimc.0,
-            //&self.q_mimc_c.0,
-        ]
-            .into_iter()
-    }
-
+    /// Adds the MiMC term of the quotient on the 4n coset to `t`.
     pub(crate) fn compute_quotient(
         &self,
         domain_4n: impl EvaluationDomain<F> + Sync,
         w_4n: (&[F], &[F], &[F], &[F]),
         alpha: &F,
-    ) -> Vec<F> {
+        t: &mut [F],
+    ) -> Result<(), Error> {
         let alpha_2 = alpha.square();
         let alpha_4 = alpha_2.square();
         let alpha_8 = alpha_4.square();
@@ -31,16 +33,13 @@ impl<F: Field> MimcKey<F>{
 
         let (w_0_4n, w_1_4n, w_2_4n, w_3_4n) = w_4n;
         let size = domain_4n.size();
-        let q_mimc_2 = &self.q_mimc.2;
-        //let q_mimc_c_2 = &self.q_mimc_c.2;
+        let n = self.q_mimc.1.len();
+        let q_mimc_2 = self.q_mimc.2.load()?;
+        //let q_mimc_c_2 = self.q_mimc_c.2.load()?;
 
-        cfg_into_iter!((0..size))
+        cfg_into_iter!((0..size), MIN_CHUNK)
             .map(|i| {
-                let next = if i / 4 == (size / 4 - 1) {
-                    i % 4
-                } else {
-                    i + 4
-                };
+                let next = shifted(i, size, n);
                 if q_mimc_2[i].is_zero() {
                     F::zero()
                 } else {
@@ -51,7 +50,9 @@ impl<F: Field> MimcKey<F>{
                         )
                 }
             })
-            .collect()
+            .zip(cfg_iter_mut!(t))
+            .for_each(|(term, t)| *t += term);
+        Ok(())
     }
 
     pub(crate) fn construct_linear_combination(
@@ -77,12 +78,14 @@ impl<F: Field> MimcKey<F>{
         )
     }
 
+    /// Adds the MiMC term without a sponge to `t`.
     pub(crate) fn compute_quotient_nosponge(
         &self,
         domain_4n: impl EvaluationDomain<F> + Sync,
         w_4n: (&[F], &[F], &[F], &[F]),
         alpha: &F,
-    ) -> Vec<F> {
+        t: &mut [F],
+    ) -> Result<(), Error> {
         let alpha_2 = alpha.square();
         let alpha_4 = alpha_2.square();
         let alpha_8 = alpha_4.square();
@@ -90,16 +93,13 @@ impl<F: Field> MimcKey<F>{
 
         let (w_0_4n, w_1_4n, w_2_4n, w_3_4n) = w_4n;
         let size = domain_4n.size();
-        let q_mimc_2 = &self.q_mimc.2;
-        //let q_mimc_c_2 = &self.q_mimc_c.2;
+        let n = self.q_mimc.1.len();
+        let q_mimc_2 = self.q_mimc.2.load()?;
+        //let q_mimc_c_2 = self.q_mimc_c.2.load()?;
 
-        cfg_into_iter!((0..size))
+        cfg_into_iter!((0..size), MIN_CHUNK)
             .map(|i| {
-                let next = if i / 4 == (size / 4 - 1) {
-                    i % 4
-                } else {
-                    i + 4
-                };
+                let next = shifted(i, size, n);
                 if q_mimc_2[i].is_zero() {
                     F::zero()
                 } else {
@@ -110,7 +110,9 @@ impl<F: Field> MimcKey<F>{
                     )
                 }
             })
-            .collect()
+            .zip(cfg_iter_mut!(t))
+            .for_each(|(term, t)| *t += term);
+        Ok(())
     }
 
     pub(crate) fn construct_linear_combination_nosponge(
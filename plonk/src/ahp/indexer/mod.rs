@@ -1,26 +1,57 @@
 use ark_ff::FftField as Field;
-use ark_poly::{EvaluationDomain, Evaluations as EvaluationsOnDomain, GeneralEvaluationDomain};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations as EvaluationsOnDomain,
+    GeneralEvaluationDomain, UVPolynomial,
+};
 use ark_serialize::*;
-use ark_std::{cfg_into_iter, io, vec, vec::Vec};
+use ark_std::{
+    borrow::Cow,
+    cfg_into_iter, cfg_iter_mut, format, io,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use crate::utils::MIN_CHUNK;
 
 use crate::ahp::{AHPForPLONK, Error};
-use crate::composer::{Composer, Error as CSError, Selectors};
+use crate::composer::{wire_columns, Composer, Error as CSError, Selectors};
 use crate::data_structures::LabeledPolynomial;
-use crate::utils::{first_lagrange_poly, to_labeled, vanishing_poly};
+use crate::utils::{
+    first_lagrange_poly, last_lagrange_poly, quotient_chunk, quotient_chunks, shifted, to_labeled,
+    vanishing_poly,
+};
 
 mod arithmetic;
 pub use arithmetic::ArithmeticKey;
+mod column;
+pub use column::Column;
 mod permutation;
 mod mimc;
 pub use mimc::MimcKey;
+mod lookup;
+pub(crate) use lookup::LookupEvals;
+pub use lookup::LookupKey;
+mod logic;
+pub use logic::LogicKey;
+mod curve;
+pub use curve::CurveKey;
+mod memory;
+pub(crate) use memory::MemoryEvals;
+pub use memory::MemoryKey;
 
 pub use permutation::PermutationKey;
 use ark_poly_commit::LinearCombination;
 //use crate::ahp::indexer::mimc::MimcKey;
 
+/// The preprocessed circuit. Every evaluation on the coset of the quotient
+/// that does not depend on the witness or the challenges, i.e. of the
+/// selectors, sigmas, tables, Lagrange and vanishing polynomials, is
+/// computed here once, and only read by each proof.
+#[derive(Clone)]
 pub struct Index<F: Field> {
     pub info: IndexInfo<F>,
 
@@ -28,35 +59,60 @@ pub struct Index<F: Field> {
     permutation: PermutationKey<F>,
 
     domain_4n: GeneralEvaluationDomain<F>,
-    v_4n_inversed: Vec<F>,
+    v_4n_inversed: Column<F>,
 
-    q_range_key: (LabeledPolynomial<F>, Vec<F>, Vec<F>),
+    q_range_key: Key<F>,
     mimc: MimcKey<F>,
+    lookup: LookupKey<F>,
+    logic: LogicKey<F>,
+    curve: CurveKey<F>,
+    memory: MemoryKey<F>,
 }
 
+/// The number of random coefficients blinding each oracle but the quotient:
+/// the wires, `z`, `h_1`, `h_2`, `z_lookup` and `z_memory`. One more than
+/// the points they are opened at, `zeta` and `g * zeta`.
+pub const DEFAULT_BLINDING: usize = 3;
+
 #[derive(Debug, Clone)]
 pub struct IndexInfo<F: Field> {
     pub n: usize,
-    pub ks: [F; 4],
+    /// The coset of each wire column, as many as the wires of the circuit,
+    /// see `Composer::with_wires`.
+    pub ks: Vec<F>,
     pub domain_n: GeneralEvaluationDomain<F>,
+    /// The number of random coefficients of the multiple of the vanishing
+    /// polynomial added to each oracle but the quotient, see
+    /// `DEFAULT_BLINDING`.
+    pub blinding: usize,
+    /// Whether the public inputs are committed to and opened at `zeta`
+    /// rather than interpolated by the verifier, see
+    /// `Plonk::verify_committed`.
+    pub committed_inputs: bool,
+    /// The application the proofs are for, absorbed into their transcript,
+    /// see `CircuitIndex::with_context`.
+    pub context: Vec<u8>,
 }
 
 impl<F: Field> CanonicalSerialize for IndexInfo<F> {
     #[inline]
     fn serialize<W: io::Write>(&self, mut writer: W) -> Result<(), SerializationError> {
         self.n.serialize(&mut writer)?;
-        self.ks[0].serialize(&mut writer)?;
-        self.ks[1].serialize(&mut writer)?;
-        self.ks[2].serialize(&mut writer)?;
-        self.ks[3].serialize(&mut writer)?;
-        self.domain_n.serialize(&mut writer)
+        self.ks.serialize(&mut writer)?;
+        self.domain_n.serialize(&mut writer)?;
+        self.blinding.serialize(&mut writer)?;
+        self.committed_inputs.serialize(&mut writer)?;
+        self.context.serialize(&mut writer)
     }
 
     #[inline]
     fn serialized_size(&self) -> usize {
         self.n.serialized_size()
-            + self.ks[0].serialized_size() * 4
+            + self.ks.serialized_size()
             + self.domain_n.serialized_size()
+            + self.blinding.serialized_size()
+            + self.committed_inputs.serialized_size()
+            + self.context.serialized_size()
     }
 
     #[inline]
@@ -65,28 +121,31 @@ impl<F: Field> CanonicalSerialize for IndexInfo<F> {
         mut writer: W,
     ) -> Result<(), SerializationError> {
         self.n.serialize_uncompressed(&mut writer)?;
-        self.ks[0].serialize_uncompressed(&mut writer)?;
-        self.ks[1].serialize_uncompressed(&mut writer)?;
-        self.ks[2].serialize_uncompressed(&mut writer)?;
-        self.ks[3].serialize_uncompressed(&mut writer)?;
-        self.domain_n.serialize_uncompressed(&mut writer)
+        self.ks.serialize_uncompressed(&mut writer)?;
+        self.domain_n.serialize_uncompressed(&mut writer)?;
+        self.blinding.serialize_uncompressed(&mut writer)?;
+        self.committed_inputs.serialize_uncompressed(&mut writer)?;
+        self.context.serialize_uncompressed(&mut writer)
     }
 
     #[inline]
     fn serialize_unchecked<W: io::Write>(&self, mut writer: W) -> Result<(), SerializationError> {
         self.n.serialize_unchecked(&mut writer)?;
-        self.ks[0].serialize_unchecked(&mut writer)?;
-        self.ks[1].serialize_unchecked(&mut writer)?;
-        self.ks[2].serialize_unchecked(&mut writer)?;
-        self.ks[3].serialize_unchecked(&mut writer)?;
-        self.domain_n.serialize_unchecked(&mut writer)
+        self.ks.serialize_unchecked(&mut writer)?;
+        self.domain_n.serialize_unchecked(&mut writer)?;
+        self.blinding.serialize_unchecked(&mut writer)?;
+        self.committed_inputs.serialize_unchecked(&mut writer)?;
+        self.context.serialize_unchecked(&mut writer)
     }
 
     #[inline]
     fn uncompressed_size(&self) -> usize {
         self.n.uncompressed_size()
-            + self.ks[0].uncompressed_size() * 4
+            + self.ks.uncompressed_size()
             + self.domain_n.uncompressed_size()
+            + self.blinding.uncompressed_size()
+            + self.committed_inputs.uncompressed_size()
+            + self.context.uncompressed_size()
     }
 }
 
@@ -95,57 +154,366 @@ impl<F: Field> CanonicalDeserialize for IndexInfo<F> {
     fn deserialize<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
         let n = usize::deserialize(&mut reader)?;
 
-        let mut ks = [F::zero(); 4];
-        let mut vks: Vec<F> = vec![];
-        vks.push(F::deserialize(&mut reader)?);
-        vks.push(F::deserialize(&mut reader)?);
-        vks.push(F::deserialize(&mut reader)?);
-        vks.push(F::deserialize(&mut reader)?);
-        ks.copy_from_slice(&vks[..]);
+        let ks = Vec::<F>::deserialize(&mut reader)?;
 
         let domain_n = GeneralEvaluationDomain::<F>::deserialize(&mut reader)?;
+        let blinding = usize::deserialize(&mut reader)?;
+        let committed_inputs = bool::deserialize(&mut reader)?;
+        let context = Vec::<u8>::deserialize(&mut reader)?;
 
-        Ok(IndexInfo { n, ks, domain_n })
+        IndexInfo::checked(n, ks, domain_n, blinding, committed_inputs, context)
     }
 
     #[inline]
     fn deserialize_uncompressed<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
         let n = usize::deserialize_uncompressed(&mut reader)?;
 
-        let mut ks = [F::zero(); 4];
-        let mut vks: Vec<F> = vec![];
-        vks.push(F::deserialize_uncompressed(&mut reader)?);
-        vks.push(F::deserialize_uncompressed(&mut reader)?);
-        vks.push(F::deserialize_uncompressed(&mut reader)?);
-        vks.push(F::deserialize_uncompressed(&mut reader)?);
-        ks.copy_from_slice(&vks[..]);
+        let ks = Vec::<F>::deserialize_uncompressed(&mut reader)?;
 
         let domain_n = GeneralEvaluationDomain::<F>::deserialize_uncompressed(&mut reader)?;
+        let blinding = usize::deserialize_uncompressed(&mut reader)?;
+        let committed_inputs = bool::deserialize_uncompressed(&mut reader)?;
+        let context = Vec::<u8>::deserialize_uncompressed(&mut reader)?;
 
-        Ok(IndexInfo { n, ks, domain_n })
+        IndexInfo::checked(n, ks, domain_n, blinding, committed_inputs, context)
     }
 
     #[inline]
     fn deserialize_unchecked<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
         let n = usize::deserialize_unchecked(&mut reader)?;
 
-        let mut ks = [F::zero(); 4];
-        let mut vks: Vec<F> = vec![];
-        vks.push(F::deserialize_unchecked(&mut reader)?);
-        vks.push(F::deserialize_unchecked(&mut reader)?);
-        vks.push(F::deserialize_unchecked(&mut reader)?);
-        vks.push(F::deserialize_unchecked(&mut reader)?);
-        ks.copy_from_slice(&vks[..]);
+        let ks = Vec::<F>::deserialize_unchecked(&mut reader)?;
 
         let domain_n = GeneralEvaluationDomain::<F>::deserialize_unchecked(&mut reader)?;
+        let blinding = usize::deserialize_unchecked(&mut reader)?;
+        let committed_inputs = bool::deserialize_unchecked(&mut reader)?;
+        let context = Vec::<u8>::deserialize_unchecked(&mut reader)?;
+
+        Ok(IndexInfo {
+            n,
+            ks,
+            domain_n,
+            blinding,
+            committed_inputs,
+            context,
+        })
+    }
+}
 
-        Ok(IndexInfo { n, ks, domain_n })
+impl<F: Field> IndexInfo<F> {
+    /// Rejects a domain that is not the one of `n` gates, which would make
+    /// the verifier work over a domain of any size, a blinding longer than
+    /// the domain, and a number of wires other than three to five.
+    pub(crate) fn checked(
+        n: usize,
+        ks: Vec<F>,
+        domain_n: GeneralEvaluationDomain<F>,
+        blinding: usize,
+        committed_inputs: bool,
+        context: Vec<u8>,
+    ) -> Result<Self, SerializationError> {
+        if GeneralEvaluationDomain::<F>::new(n) != Some(domain_n)
+            || blinding > domain_n.size()
+            || !(3..=5).contains(&ks.len())
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(IndexInfo {
+            n,
+            ks,
+            domain_n,
+            blinding,
+            committed_inputs,
+            context,
+        })
+    }
+
+    /// The number of wire columns.
+    pub fn wires(&self) -> usize {
+        self.ks.len()
+    }
+
+    /// The wire columns, `w_1..w_3` with three wires, `w_0..w_3` with four
+    /// and `w_0..w_4` with five, each with its coset in `ks`.
+    pub fn columns(&self) -> &'static [usize] {
+        wire_columns(self.wires())
+    }
+
+    /// The number of chunks of the quotient polynomial, see
+    /// `utils::quotient_chunks`.
+    pub fn quotient_chunks(&self) -> usize {
+        quotient_chunks(self.wires())
+    }
+
+    /// The size of each chunk of the quotient polynomial.
+    pub fn quotient_chunk(&self) -> usize {
+        quotient_chunk(self.domain_n.size(), self.blinding, self.quotient_chunks())
+    }
+
+    /// The largest degree of the polynomials of a proof, which the
+    /// commitment key must support.
+    pub fn max_degree(&self) -> usize {
+        self.domain_n.size().max(self.quotient_chunk() - 1)
+    }
+}
+
+/// The labels of the selectors of an index of `wires` wire columns, in the
+/// order of `Selectors::iter`: `q_4` only with five wires, and a sigma per
+/// wire column.
+pub(crate) fn labels(wires: usize) -> Vec<String> {
+    let mut labels: Vec<String> = ["q_0", "q_1", "q_2", "q_3"]
+        .iter()
+        .map(|label| label.to_string())
+        .collect();
+    if wires == 5 {
+        labels.push("q_4".into());
+    }
+    labels.extend(
+        ["q_m", "q_c", "q_next", "q_arith"]
+            .iter()
+            .map(|label| label.to_string()),
+    );
+    labels.extend(
+        wire_columns(wires)
+            .iter()
+            .map(|column| format!("sigma_{}", column)),
+    );
+    labels.extend(
+        [
+            "q_range",
+            "q_mimc",
+            "q_lookup",
+            "table_0",
+            "table_1",
+            "table_2",
+            "table_3",
+            "q_logic",
+            "q_curve",
+            "q_curve_a",
+            "q_curve_d",
+            "q_memory",
+        ]
+        .iter()
+        .map(|label| label.to_string()),
+    );
+    labels
+}
+
+/// A selector as its polynomial, its values on the domain and its
+/// evaluations on the 4n coset.
+type Key<F> = (DensePolynomial<F>, Vec<F>, Column<F>);
+
+/// The compressed encoding holds the values of the selectors only, and the
+/// polynomials and 4n coset evaluations are recomputed when it is read. The
+/// uncompressed encoding persists them all, so that loading a prover key
+/// takes no FFTs.
+impl<F: Field> CanonicalSerialize for Index<F> {
+    fn serialize<W: io::Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.info.serialize(&mut writer)?;
+        for key in self.keys().iter() {
+            key.1.serialize(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.info.serialized_size()
+            + self
+                .keys()
+                .iter()
+                .map(|key| key.1.serialized_size())
+                .sum::<usize>()
+    }
+
+    fn serialize_uncompressed<W: io::Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        self.info.serialize_uncompressed(&mut writer)?;
+        for key in self.keys().iter() {
+            key.0.coeffs.serialize_uncompressed(&mut writer)?;
+            key.1.serialize_uncompressed(&mut writer)?;
+            key.2.serialize_uncompressed(&mut writer)?;
+        }
+        self.v_4n_inversed.serialize_uncompressed(&mut writer)?;
+        self.permutation.l1_4n.serialize_uncompressed(&mut writer)?;
+        self.permutation
+            .linear_4n
+            .serialize_uncompressed(&mut writer)?;
+        self.lookup.ln_4n.serialize_uncompressed(&mut writer)
+    }
+
+    fn uncompressed_size(&self) -> usize {
+        self.info.uncompressed_size()
+            + self
+                .keys()
+                .iter()
+                .map(|key| {
+                    key.0.coeffs.uncompressed_size()
+                        + key.1.uncompressed_size()
+                        + key.2.uncompressed_size()
+                })
+                .sum::<usize>()
+            + self.v_4n_inversed.uncompressed_size()
+            + self.permutation.l1_4n.uncompressed_size()
+            + self.permutation.linear_4n.uncompressed_size()
+            + self.lookup.ln_4n.uncompressed_size()
+    }
+}
+
+impl<F: Field> CanonicalDeserialize for Index<F> {
+    fn deserialize<R: io::Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let info = IndexInfo::<F>::deserialize(&mut reader)?;
+        let n = info.domain_n.size();
+        let wires = info.wires();
+        let labels = labels(wires);
+        let mut values = Vec::with_capacity(labels.len());
+        for _ in labels.iter() {
+            let v = Vec::<F>::deserialize(&mut reader)?;
+            if v.len() != n {
+                return Err(SerializationError::InvalidData);
+            }
+            values.push(v);
+        }
+
+        let mut values = values.into_iter();
+        let mut next = || values.next().unwrap();
+        let selectors = Selectors {
+            n,
+            q_0: next(),
+            q_1: next(),
+            q_2: next(),
+            q_3: next(),
+            q_4: if wires == 5 {
+                next()
+            } else {
+                vec![F::zero(); n]
+            },
+            q_m: next(),
+            q_c: next(),
+            q_next: next(),
+            q_arith: next(),
+            sigmas: (0..wires).map(|_| next()).collect(),
+            q_range: next(),
+            q_mimc: next(),
+            q_lookup: next(),
+            table_0: next(),
+            table_1: next(),
+            table_2: next(),
+            table_3: next(),
+            q_logic: next(),
+            q_curve: next(),
+            q_curve_a: next(),
+            q_curve_d: next(),
+            q_memory: next(),
+        };
+        let mut index = Self::from_selectors(selectors, info.ks, info.blinding)
+            .map_err(|_| SerializationError::InvalidData)?;
+        index.info.committed_inputs = info.committed_inputs;
+        index.info.context = info.context;
+        Ok(index)
+    }
+
+    fn deserialize_uncompressed<R: io::Read>(reader: R) -> Result<Self, SerializationError> {
+        Self::read_uncompressed(reader, |reader| {
+            Ok(Vec::<F>::deserialize_uncompressed(reader)?.into())
+        })
+    }
+}
+
+impl<F: Field> Index<F> {
+    /// Reads the uncompressed encoding, each evaluation on the 4n coset by
+    /// `column`.
+    fn read_uncompressed<R: io::Read>(
+        mut reader: R,
+        mut column: impl FnMut(&mut R) -> Result<Column<F>, SerializationError>,
+    ) -> Result<Self, SerializationError> {
+        let info = IndexInfo::<F>::deserialize_uncompressed(&mut reader)?;
+        let wires = info.wires();
+        let labels = labels(wires);
+        let mut keys = Vec::with_capacity(labels.len());
+        for _ in labels.iter() {
+            let coeffs = Vec::<F>::deserialize_uncompressed(&mut reader)?;
+            let values = Vec::<F>::deserialize_uncompressed(&mut reader)?;
+            let values_4n = column(&mut reader)?;
+            let poly = DensePolynomial::from_coefficients_vec(coeffs);
+            keys.push((poly, values, values_4n));
+        }
+        let v_4n_inversed = column(&mut reader)?;
+        let l1_4n = column(&mut reader)?;
+        let linear_4n = column(&mut reader)?;
+        let ln_4n = column(&mut reader)?;
+        let domain_4n =
+            GeneralEvaluationDomain::<F>::new(info.quotient_chunks() * info.quotient_chunk())
+                .ok_or(SerializationError::InvalidData)?;
+
+        let mut keys = keys.into_iter();
+        let mut next = || keys.next().unwrap();
+        Ok(Index {
+            arithmetic: ArithmeticKey {
+                q_0: next(),
+                q_1: next(),
+                q_2: next(),
+                q_3: next(),
+                q_4: if wires == 5 { Some(next()) } else { None },
+                q_m: next(),
+                q_c: next(),
+                q_next: next(),
+                q_arith: next(),
+            },
+            permutation: PermutationKey {
+                sigmas: (0..wires).map(|_| next()).collect(),
+                l1_4n,
+                linear_4n,
+            },
+            q_range_key: next(),
+            mimc: MimcKey { q_mimc: next() },
+            lookup: LookupKey {
+                q_lookup: next(),
+                table_0: next(),
+                table_1: next(),
+                table_2: next(),
+                table_3: next(),
+                ln_4n,
+            },
+            logic: LogicKey { q_logic: next() },
+            curve: CurveKey {
+                q_curve: next(),
+                q_curve_a: next(),
+                q_curve_d: next(),
+            },
+            memory: MemoryKey { q_memory: next() },
+            info,
+            domain_4n,
+            v_4n_inversed,
+        })
+    }
+
+    /// Reads the uncompressed encoding at the position of `reader`, in the
+    /// file at `path`, leaving the evaluations on the 4n coset there.
+    #[cfg(feature = "std")]
+    pub(crate) fn read_disk_backed<R: io::Read + std::io::Seek>(
+        reader: R,
+        path: &std::sync::Arc<std::path::PathBuf>,
+    ) -> Result<Self, SerializationError> {
+        Self::read_uncompressed(reader, |reader| Column::on_disk(path, reader))
     }
 }
 
 impl<F: Field> AHPForPLONK<F> {
-    pub fn index(cs: &Composer<F>, ks: [F; 4]) -> Result<Index<F>, Error> {
-        let selectors = cs.compose(&ks)?;
+    /// The index of `cs`, blinding the wires and `z` of its proofs with
+    /// `blinding` random coefficients each.
+    // only the prover, built with `std`, uses it.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub fn index(cs: &Composer<F>, ks: &[F], blinding: usize) -> Result<Index<F>, Error> {
+        Index::from_selectors(cs.compose(ks)?, ks.to_vec(), blinding)
+    }
+}
+
+impl<F: Field> Index<F> {
+    /// Interpolates the selectors and evaluates them on the coset of the
+    /// quotient, 4n without blinding, once for all proofs.
+    fn from_selectors(selectors: Selectors<F>, ks: Vec<F>, blinding: usize) -> Result<Self, Error> {
         let n = selectors.size();
         selectors.iter().for_each(|s| assert_eq!(s.len(), n));
 
@@ -154,108 +522,124 @@ impl<F: Field> AHPForPLONK<F> {
             q_1,
             q_2,
             q_3,
+            q_4,
             q_m,
             q_c,
+            q_next,
             q_arith,
-            sigma_0,
-            sigma_1,
-            sigma_2,
-            sigma_3,
+            sigmas,
             q_range,
             q_mimc,
             //q_mimc_c,
+            q_lookup,
+            table_0,
+            table_1,
+            table_2,
+            table_3,
+            q_logic,
+            q_curve,
+            q_curve_a,
+            q_curve_d,
+            q_memory,
             ..
         } = selectors;
 
-        let domain_n =
-            GeneralEvaluationDomain::<F>::new(n).ok_or(CSError::PolynomialDegreeTooLarge)?;
-        let domain_4n =
-            GeneralEvaluationDomain::<F>::new(4 * n).ok_or(CSError::PolynomialDegreeTooLarge)?;
-
-        let q_0_poly = to_labeled(
-            "q_0",
-            EvaluationsOnDomain::from_vec_and_domain(q_0.clone(), domain_n).interpolate(),
-        );
-        let q_1_poly = to_labeled(
-            "q_1",
-            EvaluationsOnDomain::from_vec_and_domain(q_1.clone(), domain_n).interpolate(),
-        );
-        let q_2_poly = to_labeled(
-            "q_2",
-            EvaluationsOnDomain::from_vec_and_domain(q_2.clone(), domain_n).interpolate(),
-        );
-        let q_3_poly = to_labeled(
-            "q_3",
-            EvaluationsOnDomain::from_vec_and_domain(q_3.clone(), domain_n).interpolate(),
-        );
-        let q_m_poly = to_labeled(
-            "q_m",
-            EvaluationsOnDomain::from_vec_and_domain(q_m.clone(), domain_n).interpolate(),
-        );
-        let q_c_poly = to_labeled(
-            "q_c",
-            EvaluationsOnDomain::from_vec_and_domain(q_c.clone(), domain_n).interpolate(),
-        );
-        let q_arith_poly = to_labeled(
-            "q_arith",
-            EvaluationsOnDomain::from_vec_and_domain(q_arith.clone(), domain_n).interpolate(),
-        );
-
-        let sigma_0_poly = to_labeled(
-            "sigma_0",
-            EvaluationsOnDomain::from_vec_and_domain(sigma_0.clone(), domain_n).interpolate(),
-        );
-        let sigma_1_poly = to_labeled(
-            "sigma_1",
-            EvaluationsOnDomain::from_vec_and_domain(sigma_1.clone(), domain_n).interpolate(),
-        );
-        let sigma_2_poly = to_labeled(
-            "sigma_2",
-            EvaluationsOnDomain::from_vec_and_domain(sigma_2.clone(), domain_n).interpolate(),
-        );
-        let sigma_3_poly = to_labeled(
-            "sigma_3",
-            EvaluationsOnDomain::from_vec_and_domain(sigma_3.clone(), domain_n).interpolate(),
-        );
-        let q_range_poly = to_labeled(
-            "q_range",
-            EvaluationsOnDomain::from_vec_and_domain(q_range.clone(), domain_n).interpolate(),
-        );
-        let q_mimc_poly = to_labeled(
-            "q_mimc",
-            EvaluationsOnDomain::from_vec_and_domain(q_mimc.clone(), domain_n).interpolate(),
-        );
+        let domain_n = GeneralEvaluationDomain::<F>::new(n)
+            .ok_or(CSError::PolynomialDegreeTooLarge { size: n })?;
+        if blinding > n {
+            return Err(CSError::PolynomialDegreeTooLarge { size: n + blinding }.into());
+        }
+        let chunks = quotient_chunks(sigmas.len());
+        let size_4n = chunks * quotient_chunk(n, blinding, chunks);
+        let domain_4n = GeneralEvaluationDomain::<F>::new(size_4n)
+            .ok_or(CSError::PolynomialDegreeTooLarge { size: size_4n })?;
+
+        let q_0_poly = EvaluationsOnDomain::from_vec_and_domain(q_0.clone(), domain_n).interpolate();
+        let q_1_poly = EvaluationsOnDomain::from_vec_and_domain(q_1.clone(), domain_n).interpolate();
+        let q_2_poly = EvaluationsOnDomain::from_vec_and_domain(q_2.clone(), domain_n).interpolate();
+        let q_3_poly = EvaluationsOnDomain::from_vec_and_domain(q_3.clone(), domain_n).interpolate();
+        let q_m_poly = EvaluationsOnDomain::from_vec_and_domain(q_m.clone(), domain_n).interpolate();
+        let q_c_poly = EvaluationsOnDomain::from_vec_and_domain(q_c.clone(), domain_n).interpolate();
+        let q_next_poly = EvaluationsOnDomain::from_vec_and_domain(q_next.clone(), domain_n).interpolate();
+        let q_arith_poly = EvaluationsOnDomain::from_vec_and_domain(q_arith.clone(), domain_n).interpolate();
+
+        let q_range_poly = EvaluationsOnDomain::from_vec_and_domain(q_range.clone(), domain_n).interpolate();
+        let q_mimc_poly = EvaluationsOnDomain::from_vec_and_domain(q_mimc.clone(), domain_n).interpolate();
+        let q_lookup_poly = EvaluationsOnDomain::from_vec_and_domain(q_lookup.clone(), domain_n).interpolate();
+        let table_0_poly = EvaluationsOnDomain::from_vec_and_domain(table_0.clone(), domain_n).interpolate();
+        let table_1_poly = EvaluationsOnDomain::from_vec_and_domain(table_1.clone(), domain_n).interpolate();
+        let table_2_poly = EvaluationsOnDomain::from_vec_and_domain(table_2.clone(), domain_n).interpolate();
+        let table_3_poly = EvaluationsOnDomain::from_vec_and_domain(table_3.clone(), domain_n).interpolate();
+        let q_logic_poly = EvaluationsOnDomain::from_vec_and_domain(q_logic.clone(), domain_n).interpolate();
+        let q_curve_poly = EvaluationsOnDomain::from_vec_and_domain(q_curve.clone(), domain_n).interpolate();
+        let q_curve_a_poly = EvaluationsOnDomain::from_vec_and_domain(q_curve_a.clone(), domain_n).interpolate();
+        let q_curve_d_poly = EvaluationsOnDomain::from_vec_and_domain(q_curve_d.clone(), domain_n).interpolate();
+        let q_memory_poly = EvaluationsOnDomain::from_vec_and_domain(q_memory.clone(), domain_n).interpolate();
         // let q_mimc_c_poly = to_labeled(
         //     "q_mimc_c",
         //     EvaluationsOnDomain::from_vec_and_domain(q_mimc_c.clone(), domain_n).interpolate(),
         // );
 
-        let q_0_4n = domain_4n.coset_fft(&q_0_poly);
-        let q_1_4n = domain_4n.coset_fft(&q_1_poly);
-        let q_2_4n = domain_4n.coset_fft(&q_2_poly);
-        let q_3_4n = domain_4n.coset_fft(&q_3_poly);
-        let q_m_4n = domain_4n.coset_fft(&q_m_poly);
-        let q_c_4n = domain_4n.coset_fft(&q_c_poly);
-        let q_arith_4n = domain_4n.coset_fft(&q_arith_poly);
-
-        let sigma_0_4n = domain_4n.coset_fft(&sigma_0_poly);
-        let sigma_1_4n = domain_4n.coset_fft(&sigma_1_poly);
-        let sigma_2_4n = domain_4n.coset_fft(&sigma_2_poly);
-        let sigma_3_4n = domain_4n.coset_fft(&sigma_3_poly);
-
-        let q_range_4n = domain_4n.coset_fft(&q_range_poly);
-        let q_mimc_4n = domain_4n.coset_fft(&q_mimc_poly);
+        let q_0_4n = Column::from(domain_4n.coset_fft(&q_0_poly));
+        let q_1_4n = Column::from(domain_4n.coset_fft(&q_1_poly));
+        let q_2_4n = Column::from(domain_4n.coset_fft(&q_2_poly));
+        let q_3_4n = Column::from(domain_4n.coset_fft(&q_3_poly));
+        let q_m_4n = Column::from(domain_4n.coset_fft(&q_m_poly));
+        let q_c_4n = Column::from(domain_4n.coset_fft(&q_c_poly));
+        let q_next_4n = Column::from(domain_4n.coset_fft(&q_next_poly));
+        let q_arith_4n = Column::from(domain_4n.coset_fft(&q_arith_poly));
+
+        let key = |values: Vec<F>| {
+            let poly =
+                EvaluationsOnDomain::from_vec_and_domain(values.clone(), domain_n).interpolate();
+            let values_4n = Column::from(domain_4n.coset_fft(&poly));
+            (poly, values, values_4n)
+        };
+        let q_4 = if sigmas.len() == 5 {
+            Some(key(q_4))
+        } else {
+            None
+        };
+        let sigmas = sigmas.into_iter().map(key).collect();
+
+        let q_range_4n = Column::from(domain_4n.coset_fft(&q_range_poly));
+        let q_mimc_4n = Column::from(domain_4n.coset_fft(&q_mimc_poly));
         //let q_mimc_c_4n = domain_4n.coset_fft(&q_mimc_c_poly);
 
+        let q_lookup_4n = Column::from(domain_4n.coset_fft(&q_lookup_poly));
+        let table_0_4n = Column::from(domain_4n.coset_fft(&table_0_poly));
+        let table_1_4n = Column::from(domain_4n.coset_fft(&table_1_poly));
+        let table_2_4n = Column::from(domain_4n.coset_fft(&table_2_poly));
+        let table_3_4n = Column::from(domain_4n.coset_fft(&table_3_poly));
+
+        let q_logic_4n = Column::from(domain_4n.coset_fft(&q_logic_poly));
+
+        let q_curve_4n = Column::from(domain_4n.coset_fft(&q_curve_poly));
+        let q_curve_a_4n = Column::from(domain_4n.coset_fft(&q_curve_a_poly));
+        let q_curve_d_4n = Column::from(domain_4n.coset_fft(&q_curve_d_poly));
+
+        let q_memory_4n = Column::from(domain_4n.coset_fft(&q_memory_poly));
+
         let v_poly = vanishing_poly(domain_n);
         let v_4n = domain_4n.coset_fft(&v_poly);
         let v_4n_inversed: Vec<_> = cfg_into_iter!(v_4n).map(|v| v.inverse().unwrap()).collect();
+        let v_4n_inversed = Column::from(v_4n_inversed);
 
         let l1_poly = first_lagrange_poly(domain_n);
-        let l1_4n = domain_4n.coset_fft(&l1_poly);
+        let l1_4n = Column::from(domain_4n.coset_fft(&l1_poly));
+        let linear_4n = Column::from(domain_4n.coset_fft(&[F::zero(), F::one()]));
+        let ln_poly = last_lagrange_poly(domain_n);
+        let ln_4n = Column::from(domain_4n.coset_fft(&ln_poly));
 
         Ok(Index {
-            info: IndexInfo { n, ks, domain_n },
+            info: IndexInfo {
+                n,
+                ks,
+                domain_n,
+                blinding,
+                committed_inputs: false,
+                context: Vec::new(),
+            },
 
             domain_4n,
 
@@ -266,35 +650,92 @@ impl<F: Field> AHPForPLONK<F> {
                 q_1: (q_1_poly, q_1, q_1_4n),
                 q_2: (q_2_poly, q_2, q_2_4n),
                 q_3: (q_3_poly, q_3, q_3_4n),
+                q_4,
 
                 q_m: (q_m_poly, q_m, q_m_4n),
                 q_c: (q_c_poly, q_c, q_c_4n),
+                q_next: (q_next_poly, q_next, q_next_4n),
 
                 q_arith: (q_arith_poly, q_arith, q_arith_4n),
             },
             permutation: PermutationKey {
-                sigma_0: (sigma_0_poly, sigma_0, sigma_0_4n),
-                sigma_1: (sigma_1_poly, sigma_1, sigma_1_4n),
-                sigma_2: (sigma_2_poly, sigma_2, sigma_2_4n),
-                sigma_3: (sigma_3_poly, sigma_3, sigma_3_4n),
+                sigmas,
                 l1_4n,
+                linear_4n,
             },
             q_range_key: (q_range_poly, q_range, q_range_4n),
             mimc: MimcKey {
                 q_mimc: (q_mimc_poly, q_mimc, q_mimc_4n),
                 //q_mimc_c: (q_mimc_c_poly, q_mimc_c, q_mimc_c_4n),
             },
+            lookup: LookupKey {
+                q_lookup: (q_lookup_poly, q_lookup, q_lookup_4n),
+                table_0: (table_0_poly, table_0, table_0_4n),
+                table_1: (table_1_poly, table_1, table_1_4n),
+                table_2: (table_2_poly, table_2, table_2_4n),
+                table_3: (table_3_poly, table_3, table_3_4n),
+                ln_4n,
+            },
+            logic: LogicKey {
+                q_logic: (q_logic_poly, q_logic, q_logic_4n),
+            },
+            curve: CurveKey {
+                q_curve: (q_curve_poly, q_curve, q_curve_4n),
+                q_curve_a: (q_curve_a_poly, q_curve_a, q_curve_a_4n),
+                q_curve_d: (q_curve_d_poly, q_curve_d, q_curve_d_4n),
+            },
+            memory: MemoryKey {
+                q_memory: (q_memory_poly, q_memory, q_memory_4n),
+            },
         })
     }
 }
 
 impl<F: Field> Index<F> {
-    //加上range,mimc到末尾
-    pub fn iter(&self) -> impl Iterator<Item = &LabeledPolynomial<F>> {
-        self.arithmetic.iter()
-            .chain(self.permutation.iter())
-            .chain(vec![&self.q_range_key.0].into_iter())
-            .chain(self.mimc.iter())
+    /// The keys of the selectors, in the order of `labels`.
+    fn keys(&self) -> Vec<&Key<F>> {
+        let arithmetic = &self.arithmetic;
+        let mut keys = vec![
+            &arithmetic.q_0,
+            &arithmetic.q_1,
+            &arithmetic.q_2,
+            &arithmetic.q_3,
+        ];
+        keys.extend(arithmetic.q_4.as_ref());
+        keys.extend(vec![
+            &arithmetic.q_m,
+            &arithmetic.q_c,
+            &arithmetic.q_next,
+            &arithmetic.q_arith,
+        ]);
+        keys.extend(self.permutation.sigmas.iter());
+        keys.extend(vec![
+            &self.q_range_key,
+            &self.mimc.q_mimc,
+            &self.lookup.q_lookup,
+            &self.lookup.table_0,
+            &self.lookup.table_1,
+            &self.lookup.table_2,
+            &self.lookup.table_3,
+            &self.logic.q_logic,
+            &self.curve.q_curve,
+            &self.curve.q_curve_a,
+            &self.curve.q_curve_d,
+            &self.memory.q_memory,
+        ]);
+        keys
+    }
+
+    /// The selector polynomials, labeled for the polynomial commitment in
+    /// the order of `labels`. The index keeps them unlabeled, as a
+    /// `LabeledPolynomial` holds its polynomial in an `Rc`, which would keep
+    /// a key from being shared with the threads of a pool.
+    pub fn labeled(&self) -> Vec<LabeledPolynomial<F>> {
+        self.keys()
+            .iter()
+            .zip(labels(self.info.wires()).iter())
+            .map(|(key, label)| to_labeled(label, key.0.clone()))
+            .collect()
     }
 
     pub fn size(&self) -> usize {
@@ -309,8 +750,8 @@ impl<F: Field> Index<F> {
         self.domain_4n
     }
 
-    pub(super) fn v_4n_inversed(&self) -> &[F] {
-        &self.v_4n_inversed
+    pub(super) fn v_4n_inversed(&self) -> Result<Cow<'_, [F]>, SerializationError> {
+        self.v_4n_inversed.load()
     }
 
     pub fn arithmetic_key(&self) -> &ArithmeticKey<F> {
@@ -325,36 +766,50 @@ impl<F: Field> Index<F> {
         &self.mimc
     }
 
-    pub fn q_range_key(&self) -> &(LabeledPolynomial<F>, Vec<F>, Vec<F>) {
+    pub fn lookup_key(&self) -> &LookupKey<F> {
+        &self.lookup
+    }
+
+    pub fn logic_key(&self) -> &LogicKey<F> {
+        &self.logic
+    }
+
+    pub fn curve_key(&self) -> &CurveKey<F> {
+        &self.curve
+    }
+
+    pub fn memory_key(&self) -> &MemoryKey<F> {
+        &self.memory
+    }
+
+    pub fn q_range_key(&self) -> &Key<F> {
         &self.q_range_key
     }
 
     //range放后面，也就是alpha从 3次方开始，到6次方
-    //t的一部分
+    //t的一部分，累加到 t 上
     pub fn compute_quotient_q_range(
         &self,
         domain_4n: impl EvaluationDomain<F> + Sync,
         w_4n: (&[F], &[F], &[F], &[F]),
         alpha: &F,
-    ) -> Vec<F> {
+        t: &mut [F],
+    ) -> Result<(), Error> {
         let alpha_2 = alpha.square();
         let alpha_3: F = alpha_2 * alpha;
         let alpha_4 = alpha_2.square();
 
         let (w_0_4n, w_1_4n, w_2_4n, w_3_4n) = w_4n;
         let size = domain_4n.size();
-        let q_range_2 = &self.q_range_key.2;
+        let n = self.info.domain_n.size();
+        let q_range_2 = self.q_range_key.2.load()?;
         let two: F = F::one() + F::one();
         let three: F = two + F::one();
         let four: F = two + two;
 
-        cfg_into_iter!((0..size))
+        cfg_into_iter!((0..size), MIN_CHUNK)
             .map(|i| {
-                let next = if i / 4 == (size / 4 - 1) {
-                    i % 4
-                } else {
-                    i + 4
-                };
+                let next = shifted(i, size, n);
                 if q_range_2[i].is_zero() {
                     F::zero()
                 } else {
@@ -366,7 +821,9 @@ impl<F: Field> Index<F> {
                     )
                 }
             })
-            .collect()
+            .zip(cfg_iter_mut!(t))
+            .for_each(|(term, t)| *t += term);
+        Ok(())
     }
 
     pub(crate) fn construct_linear_combination_q_range(
@@ -411,3 +868,46 @@ impl<F: Field> Index<F> {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::One;
+
+    use super::*;
+    use crate::ahp::DEFAULT_BLINDING;
+    use crate::tests::{circuit, ks};
+
+    #[test]
+    fn coset_evaluations() {
+        let index = AHPForPLONK::index(&circuit(), &ks(), DEFAULT_BLINDING).unwrap();
+        let domain_4n = index.domain_4n;
+        for key in index.keys().iter() {
+            assert_eq!(key.2.load().unwrap()[..], domain_4n.coset_fft(&key.0.coeffs)[..]);
+        }
+        let v_4n = domain_4n.coset_fft(&vanishing_poly(index.info.domain_n));
+        for (v, v_inversed) in v_4n.iter().zip(index.v_4n_inversed.load().unwrap().iter()) {
+            assert_eq!(*v * v_inversed, Fr::one());
+        }
+
+        // the compressed encoding leaves them out, and reading it computes
+        // them again.
+        let mut bytes = Vec::new();
+        index.serialize(&mut bytes).unwrap();
+        assert!(bytes.len() < index.uncompressed_size());
+        let read = Index::<Fr>::deserialize(&bytes[..]).unwrap();
+        for (key, read) in index.keys().iter().zip(read.keys().iter()) {
+            assert_eq!(key.2.load().unwrap(), read.2.load().unwrap());
+        }
+        let columns = |index: &Index<Fr>| {
+            let columns = [
+                &index.v_4n_inversed,
+                &index.permutation.l1_4n,
+                &index.permutation.linear_4n,
+                &index.lookup.ln_4n,
+            ];
+            columns.iter().map(|c| c.load().unwrap().to_vec()).collect::<Vec<_>>()
+        };
+        assert_eq!(columns(&read), columns(&index));
+    }
+}
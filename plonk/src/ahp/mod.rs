@@ -1,104 +1,170 @@
 use ark_ff::FftField as Field;
 
-use ark_poly_commit::LinearCombination;
-use ark_std::{marker::PhantomData, string::String, vec, vec::Vec};
-
-use crate::composer::Error as CSError;
-use crate::utils::generator;
+use ark_poly_commit::{Evaluations, LinearCombination, QuerySet};
+use ark_serialize::SerializationError;
+use ark_std::{
+    fmt, format,
+    marker::PhantomData,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use rand_core::RngCore;
+
+use crate::composer::{self, Composer, Error as CSError};
+use crate::data_structures::LabeledPolynomial;
+use crate::piop::PIOP;
+use crate::utils::{generator, quotient_chunks};
+use crate::workspace::ProverWorkspace;
 
 mod evaluations;
+#[cfg(feature = "std")]
+pub use evaluations::lc_coeffs;
 pub use evaluations::EvaluationsProvider;
 
 mod indexer;
-pub use indexer::{ArithmeticKey, Index, IndexInfo, PermutationKey, MimcKey};
+pub use indexer::{
+    ArithmeticKey, CurveKey, Index, IndexInfo, LogicKey, LookupKey, MemoryKey, PermutationKey,
+    MimcKey, DEFAULT_BLINDING,
+};
+use indexer::{LookupEvals, MemoryEvals};
 
 mod prover;
 pub use prover::ProverState;
 
 mod verifier;
-pub use verifier::{FirstMsg, SecondMsg, ThirdMsg, VerifierState};
+pub use verifier::{FirstMsg, FourthMsg, SecondMsg, ThirdMsg, VerifierState};
 
 pub struct AHPForPLONK<F: Field> {
     _field: PhantomData<F>,
 }
 
 impl<F: Field> AHPForPLONK<F> {
-    pub const LABELS: [&'static str; 9] =
-        ["w_0", "w_1", "w_2", "w_3", "z", "t_0", "t_1", "t_2", "t_3"];
+    /// The labels of the oracles of each round, for `wires` wire columns:
+    /// the wires, then `z`, `h_1` and `h_2`, then `z_lookup` and
+    /// `z_memory`, then the chunks of the quotient.
+    pub fn oracle_labels(wires: usize) -> Vec<Vec<String>> {
+        let labels = |labels: &[&str]| labels.iter().map(|l| l.to_string()).collect();
+        vec![
+            composer::wire_columns(wires)
+                .iter()
+                .map(|column| format!("w_{}", column))
+                .collect(),
+            labels(&["z", "h_1", "h_2"]),
+            labels(&["z_lookup", "z_memory"]),
+            (0..quotient_chunks(wires))
+                .map(|i| format!("t_{}", i))
+                .collect(),
+        ]
+    }
+
+    /// `t` as the sum of its `chunks` chunks of `chunk` coefficients.
+    pub(crate) fn t_linear_combination(
+        chunk: usize,
+        chunks: usize,
+        zeta: F,
+    ) -> LinearCombination<F> {
+        let zeta_n = zeta.pow(&[chunk as u64]);
+        let mut power = F::one();
+        let mut terms = Vec::with_capacity(chunks);
+        for i in 0..chunks {
+            terms.push((power, format!("t_{}", i)));
+            power *= zeta_n;
+        }
+        LinearCombination::new("t", terms)
+    }
 
     pub fn construct_linear_combinations(
         info: &IndexInfo<F>,
         first_msg: &FirstMsg<F>,
         second_msg: &SecondMsg<F>,
         third_msg: &ThirdMsg<F>,
+        fourth_msg: &FourthMsg<F>,
         evals: &impl EvaluationsProvider<F>,
     ) -> Result<Vec<LinearCombination<F>>, Error> {
-        let FirstMsg { beta, gamma } = *first_msg;
-        let SecondMsg { alpha } = *second_msg;
-        let ThirdMsg { zeta } = *third_msg;
-
-        let w_0 = LinearCombination::new("w_0", vec![(F::one(), "w_0")]);
-        let w_1 = LinearCombination::new("w_1", vec![(F::one(), "w_1")]);
-        let w_2 = LinearCombination::new("w_2", vec![(F::one(), "w_2")]);
-        let w_3 = LinearCombination::new("w_3", vec![(F::one(), "w_3")]);
+        let FirstMsg { beta, gamma, eta } = *first_msg;
+        let SecondMsg { delta, lambda } = *second_msg;
+        let ThirdMsg { alpha } = *third_msg;
+        let FourthMsg { zeta } = *fourth_msg;
+
+        let single = |label: String| LinearCombination::new(label.clone(), vec![(F::one(), label)]);
+        let columns = info.columns();
+        let wires: Vec<_> = columns.iter().map(|c| single(format!("w_{}", c))).collect();
+        let wire = |column: usize| wires.iter().find(|w| w.label == format!("w_{}", column));
+        // the wire columns the circuit has not are zero.
+        let wire_eval = |column: usize, point: F| match wire(column) {
+            Some(w) => evals.get_lc_eval(w, point),
+            None => Ok(F::zero()),
+        };
 
         let z = LinearCombination::new("z", vec![(F::one(), "z")]);
 
-        let t = {
-            let zeta_n = zeta.pow(&[info.n as u64]);
-            let zeta_2n = zeta_n.square();
-
-            LinearCombination::new(
-                "t",
-                vec![
-                    (F::one(), "t_0"),
-                    (zeta_n, "t_1"),
-                    (zeta_2n, "t_2"),
-                    (zeta_n * zeta_2n, "t_3"),
-                ],
-            )
-        };
+        let t = Self::t_linear_combination(info.quotient_chunk(), info.quotient_chunks(), zeta);
 
-        let sigma_0 = LinearCombination::new("sigma_0", vec![(F::one(), "sigma_0")]);
-        let sigma_1 = LinearCombination::new("sigma_1", vec![(F::one(), "sigma_1")]);
-        let sigma_2 = LinearCombination::new("sigma_2", vec![(F::one(), "sigma_2")]);
+        // the sigmas but the last, which is in `r`.
+        let (last, others) = columns.split_last().unwrap();
+        let sigmas: Vec<_> = others
+            .iter()
+            .map(|c| single(format!("sigma_{}", c)))
+            .collect();
 
         let q_arith = LinearCombination::new("q_arith", vec![(F::one(), "q_arith")]);
 
+        let q_lookup = LinearCombination::new("q_lookup", vec![(F::one(), "q_lookup")]);
+        let table = LookupKey::table_linear_combination(eta);
+        let h_1 = LinearCombination::new("h_1", vec![(F::one(), "h_1")]);
+        let h_2 = LinearCombination::new("h_2", vec![(F::one(), "h_2")]);
+        let z_lookup = LinearCombination::new("z_lookup", vec![(F::one(), "z_lookup")]);
+
+        let q_logic = LinearCombination::new("q_logic", vec![(F::one(), "q_logic")]);
+
+        let q_memory = LinearCombination::new("q_memory", vec![(F::one(), "q_memory")]);
+        let z_memory = LinearCombination::new("z_memory", vec![(F::one(), "z_memory")]);
+
         //let q_mimc_c = LinearCombination::new("q_mimc_c", vec![(F::one(), "q_mimc_c")]);
 
         let r = {
             //按标签找到多项式，并得到点zeta（w zeta）处的值
-            let w_0_zeta = evals.get_lc_eval(&w_0, zeta)?;
-            let w_1_zeta = evals.get_lc_eval(&w_1, zeta)?;
-            let w_2_zeta = evals.get_lc_eval(&w_2, zeta)?;
-            let w_3_zeta = evals.get_lc_eval(&w_3, zeta)?;
+            let w_0_zeta = wire_eval(0, zeta)?;
+            let w_1_zeta = wire_eval(1, zeta)?;
+            let w_2_zeta = wire_eval(2, zeta)?;
+            let w_3_zeta = wire_eval(3, zeta)?;
+            let w_4_zeta = match wire(4) {
+                Some(w_4) => Some(evals.get_lc_eval(w_4, zeta)?),
+                None => None,
+            };
 
             let shifted_zeta = zeta * generator(info.domain_n);
             let z_shifted_zeta = evals.get_lc_eval(&z, shifted_zeta)?;
 
-            let w_0_shifted_zeta = evals.get_lc_eval(&w_0, shifted_zeta)?;
+            let w_0_shifted_zeta = wire_eval(0, shifted_zeta)?;
 
-            let sigma_0_zeta = evals.get_lc_eval(&sigma_0, zeta)?;
-            let sigma_1_zeta = evals.get_lc_eval(&sigma_1, zeta)?;
-            let sigma_2_zeta = evals.get_lc_eval(&sigma_2, zeta)?;
+            let sigmas_zeta = sigmas
+                .iter()
+                .map(|sigma| evals.get_lc_eval(sigma, zeta))
+                .collect::<Result<Vec<_>, _>>()?;
             let q_arith_zeta = evals.get_lc_eval(&q_arith, zeta)?;
 
             //let q_mimc_c_zeta = evals.get_lc_eval(&q_mimc_c, zeta)?;
 
             let arith_lc = ArithmeticKey::construct_linear_combination(
                 (w_0_zeta, w_1_zeta, w_2_zeta, w_3_zeta),
+                w_4_zeta,
+                w_0_shifted_zeta,
                 q_arith_zeta,
             );
 
+            let wires_zeta = columns
+                .iter()
+                .map(|c| wire_eval(*c, zeta))
+                .collect::<Result<Vec<_>, _>>()?;
             let perm_lc = PermutationKey::construct_linear_combination(
                 info.domain_n,
                 &info.ks,
-                (w_0_zeta, w_1_zeta, w_2_zeta, w_3_zeta),
+                &wires_zeta,
                 z_shifted_zeta,
-                sigma_0_zeta,
-                sigma_1_zeta,
-                sigma_2_zeta,
+                &sigmas_zeta,
+                &format!("sigma_{}", last),
                 beta,
                 gamma,
                 alpha,
@@ -122,29 +188,296 @@ impl<F: Field> AHPForPLONK<F> {
                 alpha,
             );
 
+            let lookup_evals = LookupEvals {
+                w_zeta: (w_1_zeta, w_2_zeta, w_3_zeta),
+                q_lookup_zeta: evals.get_lc_eval(&q_lookup, zeta)?,
+                table_zeta: evals.get_lc_eval(&table, zeta)?,
+                table_shifted_zeta: evals.get_lc_eval(&table, shifted_zeta)?,
+                h_1_zeta: evals.get_lc_eval(&h_1, zeta)?,
+                h_1_shifted_zeta: evals.get_lc_eval(&h_1, shifted_zeta)?,
+                h_2_shifted_zeta: evals.get_lc_eval(&h_2, shifted_zeta)?,
+                z_lookup_shifted_zeta: evals.get_lc_eval(&z_lookup, shifted_zeta)?,
+            };
+            let lookup_lc = LookupKey::construct_linear_combination(
+                info.domain_n,
+                &lookup_evals,
+                (eta, delta, lambda),
+                alpha,
+                zeta,
+            );
+
+            let w_1_shifted_zeta = wire_eval(1, shifted_zeta)?;
+            let w_2_shifted_zeta = wire_eval(2, shifted_zeta)?;
+            let logic_lc = LogicKey::construct_linear_combination(
+                (w_0_zeta, w_1_zeta, w_2_zeta, w_3_zeta),
+                (w_0_shifted_zeta, w_1_shifted_zeta, w_2_shifted_zeta),
+                evals.get_lc_eval(&q_logic, zeta)?,
+                alpha,
+            );
+
+            let curve_lc = CurveKey::construct_linear_combination(
+                (w_0_zeta, w_1_zeta, w_2_zeta, w_3_zeta),
+                (
+                    w_0_shifted_zeta,
+                    w_1_shifted_zeta,
+                    w_2_shifted_zeta,
+                    wire_eval(3, shifted_zeta)?,
+                ),
+                alpha,
+            );
+
+            let memory_evals = MemoryEvals {
+                w_zeta: (w_0_zeta, w_1_zeta, w_2_zeta, w_3_zeta),
+                q_memory_zeta: evals.get_lc_eval(&q_memory, zeta)?,
+                z_memory_shifted_zeta: evals.get_lc_eval(&z_memory, shifted_zeta)?,
+            };
+            let memory_lc = MemoryKey::construct_linear_combination(
+                info.domain_n,
+                &memory_evals,
+                (eta, lambda),
+                alpha,
+                zeta,
+            );
+
             //todo 这里不用排序
             let mut r = LinearCombination::<F>::empty("r");
             r += &arith_lc;
             r += &perm_lc;
             r += &range_lc;
             r += &mimc_lc;
+            r += &lookup_lc;
+            r += &logic_lc;
+            r += &curve_lc;
+            r += &memory_lc;
             r
         };
 
-        let mut lcs = vec![
-            w_0, w_1, w_2, w_3, z, sigma_0, sigma_1, sigma_2, q_arith, t, r,
-        ];
+        let mut lcs = wires;
+        lcs.extend(sigmas);
+        lcs.extend(vec![
+            z, q_arith, t, r, q_lookup, table, h_1, h_2, z_lookup, q_logic, q_memory, z_memory,
+        ]);
+        if info.committed_inputs {
+            lcs.push(LinearCombination::new("pi", vec![(F::one(), "pi")]));
+        }
         lcs.sort_by(|a, b| a.label.cmp(&b.label));
 
         Ok(lcs)
     }
 }
 
+fn challenge<F: Field>(msgs: &[Vec<F>], round: usize, i: usize) -> Result<F, Error> {
+    msgs.get(round)
+        .and_then(|msg| msg.get(i))
+        .copied()
+        .ok_or(Error::MissingChallenge { round, index: i })
+}
+
+fn first_msg<F: Field>(msgs: &[Vec<F>]) -> Result<FirstMsg<F>, Error> {
+    Ok(FirstMsg {
+        beta: challenge(msgs, 0, 0)?,
+        gamma: challenge(msgs, 0, 1)?,
+        eta: challenge(msgs, 0, 2)?,
+    })
+}
+
+fn second_msg<F: Field>(msgs: &[Vec<F>]) -> Result<SecondMsg<F>, Error> {
+    Ok(SecondMsg {
+        delta: challenge(msgs, 1, 0)?,
+        lambda: challenge(msgs, 1, 1)?,
+    })
+}
+
+fn third_msg<F: Field>(msgs: &[Vec<F>]) -> Result<ThirdMsg<F>, Error> {
+    Ok(ThirdMsg {
+        alpha: challenge(msgs, 2, 0)?,
+    })
+}
+
+fn fourth_msg<F: Field>(msgs: &[Vec<F>]) -> Result<FourthMsg<F>, Error> {
+    Ok(FourthMsg {
+        zeta: challenge(msgs, 3, 0)?,
+    })
+}
+
+impl<'a, F: Field> PIOP<'a, F> for AHPForPLONK<F> {
+    type Index = Index<F>;
+    type Info = IndexInfo<F>;
+    type Circuit = Composer<F>;
+    type ProverState = ProverState<'a, F>;
+    type VerifierState = VerifierState<'a, F>;
+
+    const NUM_ROUNDS: usize = 4;
+
+    fn oracle_labels(info: &IndexInfo<F>) -> Vec<Vec<String>> {
+        Self::oracle_labels(info.wires())
+    }
+
+    fn init_prover(
+        index: &'a Index<F>,
+        cs: &Composer<F>,
+        ws: &mut ProverWorkspace<F>,
+    ) -> Result<ProverState<'a, F>, Error> {
+        Self::prover_init(cs, index, ws)
+    }
+
+    fn prove_round(
+        ps: ProverState<'a, F>,
+        round: usize,
+        index: &'a Index<F>,
+        cs: &Composer<F>,
+        msgs: &[Vec<F>],
+        ws: &mut ProverWorkspace<F>,
+        zk_rng: &mut dyn RngCore,
+    ) -> Result<(Option<ProverState<'a, F>>, Vec<LabeledPolynomial<F>>), Error> {
+        match round {
+            0 => {
+                let (ps, oracles) = Self::prover_first_round(ps, cs, ws, zk_rng)?;
+                Ok((Some(ps), oracles.wires))
+            }
+            1 => {
+                let (ps, oracles) = Self::prover_second_round(
+                    ps,
+                    &first_msg(msgs)?,
+                    &index.info.ks,
+                    ws,
+                    zk_rng,
+                )?;
+                let prover::SecondOracles { z, h_1, h_2 } = oracles;
+                Ok((Some(ps), vec![z, h_1, h_2]))
+            }
+            2 => {
+                let (ps, oracles) = Self::prover_third_round(ps, &second_msg(msgs)?, ws, zk_rng)?;
+                let prover::ThirdOracles { z_lookup, z_memory } = oracles;
+                Ok((Some(ps), vec![z_lookup, z_memory]))
+            }
+            3 => {
+                let oracles = Self::prover_fourth_round(ps, &third_msg(msgs)?, &index.info.ks, ws)?;
+                Ok((None, oracles.t))
+            }
+            _ => Err(Error::InvalidRound {
+                round,
+                rounds: Self::NUM_ROUNDS,
+            }),
+        }
+    }
+
+    fn resume_prover(
+        index: &'a Index<F>,
+        cs: &Composer<F>,
+        oracles: &[LabeledPolynomial<F>],
+        msgs: &[Vec<F>],
+        ws: &mut ProverWorkspace<F>,
+    ) -> Result<ProverState<'a, F>, Error> {
+        // z, h_1 and h_2 are sent in the second round, after the first
+        // message, and z_lookup and z_memory in the third, after the second.
+        let first = if msgs.len() > 1 {
+            Some(first_msg(msgs)?)
+        } else {
+            None
+        };
+        let second = if msgs.len() > 2 {
+            Some(second_msg(msgs)?)
+        } else {
+            None
+        };
+        Self::prover_resume(cs, index, oracles, first, second, ws)
+    }
+
+    fn init_verifier(info: &'a IndexInfo<F>) -> Result<VerifierState<'a, F>, Error> {
+        Self::verifier_init(info)
+    }
+
+    fn verify_round<R: RngCore>(
+        vs: VerifierState<'a, F>,
+        round: usize,
+        rng: &mut R,
+    ) -> Result<(VerifierState<'a, F>, Vec<F>), Error> {
+        match round {
+            0 => {
+                let (vs, msg) = Self::verifier_first_round(vs, rng)?;
+                Ok((vs, vec![msg.beta, msg.gamma, msg.eta]))
+            }
+            1 => {
+                let (vs, msg) = Self::verifier_second_round(vs, rng)?;
+                Ok((vs, vec![msg.delta, msg.lambda]))
+            }
+            2 => {
+                let (vs, msg) = Self::verifier_third_round(vs, rng)?;
+                Ok((vs, vec![msg.alpha]))
+            }
+            3 => {
+                let (vs, msg) = Self::verifier_fourth_round(vs, rng)?;
+                Ok((vs, vec![msg.zeta]))
+            }
+            _ => Err(Error::InvalidRound {
+                round,
+                rounds: Self::NUM_ROUNDS,
+            }),
+        }
+    }
+
+    fn query_set(vs: &VerifierState<'a, F>) -> QuerySet<F> {
+        Self::verifier_query_set(vs)
+    }
+
+    fn linear_combinations<E: EvaluationsProvider<F>>(
+        info: &IndexInfo<F>,
+        msgs: &[Vec<F>],
+        evals: &E,
+    ) -> Result<Vec<LinearCombination<F>>, Error> {
+        Self::construct_linear_combinations(
+            info,
+            &first_msg(msgs)?,
+            &second_msg(msgs)?,
+            &third_msg(msgs)?,
+            &fourth_msg(msgs)?,
+            evals,
+        )
+    }
+
+    fn equality_check(
+        vs: &VerifierState<'a, F>,
+        evals: &Evaluations<F, F>,
+        public_inputs: &[F],
+    ) -> Result<bool, Error> {
+        Self::verifier_equality_check(vs, evals, public_inputs)
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     SynthesisError(CSError),
-    MissingEvaluation(String),
-    Other,
+    /// No evaluation of the polynomial or linear combination `label`.
+    MissingEvaluation {
+        label: String,
+    },
+    /// The linear combination `lc` refers to a polynomial `label` that was
+    /// not provided.
+    MissingPolynomial {
+        label: String,
+        lc: String,
+    },
+    /// The verifier message of `round` has no challenge at `index`.
+    MissingChallenge {
+        round: usize,
+        index: usize,
+    },
+    /// The protocol has only `rounds` rounds.
+    InvalidRound {
+        round: usize,
+        rounds: usize,
+    },
+    /// The wires of `row` are not in the table of its lookup gate.
+    MissingLookup {
+        row: usize,
+    },
+    /// The accesses to the memories do not match their sorted records, e.g.
+    /// as a memory was not closed.
+    InconsistentMemory,
+    /// An evaluation of the index could not be read back from the file of a
+    /// key from `ProverKey::load_disk_backed`.
+    IndexFileError(SerializationError),
 }
 
 impl From<CSError> for Error {
@@ -153,8 +486,46 @@ impl From<CSError> for Error {
     }
 }
 
+impl From<SerializationError> for Error {
+    fn from(err: SerializationError) -> Error {
+        Error::IndexFileError(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::SynthesisError(err) => write!(f, "synthesis error: {}", err),
+            Error::MissingEvaluation { label } => write!(f, "missing evaluation of {}", label),
+            Error::MissingPolynomial { label, lc } => {
+                write!(f, "missing polynomial {} for {}", label, lc)
+            }
+            Error::MissingChallenge { round, index } => {
+                write!(f, "missing challenge {} of round {}", index, round)
+            }
+            Error::InvalidRound { round, rounds } => {
+                write!(f, "round {} of a {}-round protocol", round, rounds)
+            }
+            Error::MissingLookup { row } => write!(f, "lookup of row {} not in its table", row),
+            Error::InconsistentMemory => write!(f, "memory accesses do not match their records"),
+            Error::IndexFileError(err) => write!(f, "index file error: {}", err),
+        }
+    }
+}
+
+impl ark_std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn ark_std::error::Error + 'static)> {
+        match self {
+            Error::SynthesisError(err) => Some(err),
+            Error::IndexFileError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use ark_bls12_381::Fr;
     use ark_poly_commit::Evaluations;
     use ark_std::test_rng;
 
@@ -163,37 +534,50 @@ mod test {
     #[test]
     fn ahp() -> Result<(), Error> {
         //let cs = crate::tests::circuit();
-        let cs = crate::tests::circuit();
+        let cs = crate::tests::circuit::<Fr>();
         let ks = crate::tests::ks();
         let rng = &mut test_rng();
         println!("circuit size: {}", cs.size());
 
-        let index = AHPForPLONK::index(&cs, ks)?;
+        let index = AHPForPLONK::index(&cs, &ks, DEFAULT_BLINDING)?;
         println!("index size: {}", index.size());
-        let ps = AHPForPLONK::prover_init(&cs, &index)?;
+        let ws = &mut ProverWorkspace::new();
+        let ps = AHPForPLONK::prover_init(&cs, &index, ws)?;
         let vs = AHPForPLONK::verifier_init(&index.info)?;
 
-        let (ps, first_oracles) = AHPForPLONK::prover_first_round(ps, &cs)?;
+        let (ps, first_oracles) = AHPForPLONK::prover_first_round(ps, &cs, ws, rng)?;
         let (vs, first_msg) = AHPForPLONK::verifier_first_round(vs, rng)?;
 
-        let (ps, second_oracles) = AHPForPLONK::prover_second_round(ps, &first_msg, &ks)?;
+        let (ps, second_oracles) =
+            AHPForPLONK::prover_second_round(ps, &first_msg, &ks, ws, rng)?;
         let (vs, second_msg) = AHPForPLONK::verifier_second_round(vs, rng)?;
 
-        let third_oracles = AHPForPLONK::prover_third_round(ps, &second_msg, &ks)?;
+        let (ps, third_oracles) = AHPForPLONK::prover_third_round(ps, &second_msg, ws, rng)?;
         let (vs, third_msg) = AHPForPLONK::verifier_third_round(vs, rng)?;
 
-        let polynomials: Vec<_> = index
+        let fourth_oracles = AHPForPLONK::prover_fourth_round(ps, &third_msg, &ks, ws)?;
+        let (vs, fourth_msg) = AHPForPLONK::verifier_fourth_round(vs, rng)?;
+
+        let index_polys = index.labeled();
+        let oracles = first_oracles
+            .wires
             .iter()
-            .chain(first_oracles.iter())
-            .chain(second_oracles.iter())
-            .chain(third_oracles.iter())
-            .collect();
+            .chain(vec![
+                &second_oracles.z,
+                &second_oracles.h_1,
+                &second_oracles.h_2,
+                &third_oracles.z_lookup,
+                &third_oracles.z_memory,
+            ])
+            .chain(fourth_oracles.t.iter());
+        let polynomials: Vec<_> = index_polys.iter().chain(oracles).collect();
 
         let lcs = AHPForPLONK::construct_linear_combinations(
             &index.info,
             &first_msg,
             &second_msg,
             &third_msg,
+            &fourth_msg,
             &polynomials,
         )?;
 
@@ -206,7 +590,9 @@ mod test {
                     let lc = lcs
                         .iter()
                         .find(|lc| &lc.label == label)
-                        .ok_or_else(|| Error::MissingEvaluation(label.to_string()))?;
+                        .ok_or_else(|| Error::MissingEvaluation {
+                            label: label.to_string(),
+                        })?;
                     let eval = polynomials.get_lc_eval(&lc, *point)?;
                     evals.push((label.to_string(), eval));
                 }
@@ -2,134 +2,276 @@ use ark_ff::{FftField as Field, Zero};
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, Evaluations as EvaluationsOnDomain, UVPolynomial,
 };
-use ark_std::{cfg_iter, string::ToString, vec, vec::Vec};
+use ark_std::{cfg_iter_mut, format, string::ToString, vec, vec::Vec};
+use rand_core::RngCore;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use crate::utils::MIN_CHUNK;
 
 use crate::ahp::indexer::Index;
-use crate::ahp::verifier::{FirstMsg, SecondMsg};
+use crate::ahp::indexer::LookupKey;
+use crate::ahp::verifier::{FirstMsg, SecondMsg, ThirdMsg};
 use crate::ahp::{AHPForPLONK, Error};
 use crate::composer::{Composer, Witnesses};
 use crate::data_structures::LabeledPolynomial;
 use crate::utils::{pad_to_size, to_labeled};
+use crate::workspace::ProverWorkspace;
 
+/// The oracles sent so far, each as its values on the domain and its
+/// coefficients. They are only extended to the 4n coset in the last round,
+/// one quotient term at a time, so that the extensions of the earlier rounds
+/// do not stay alive through the later ones. The wire columns the circuit
+/// has not are zero, with no coefficients, from the start.
 pub struct ProverState<'a, F: Field> {
     index: &'a Index<F>,
-    pi_4n: Vec<F>,
+    /// the coefficients of the public inputs.
+    pi: Vec<F>,
 
     w_0: Option<(Vec<F>, Vec<F>)>,
     w_1: Option<(Vec<F>, Vec<F>)>,
     w_2: Option<(Vec<F>, Vec<F>)>,
     w_3: Option<(Vec<F>, Vec<F>)>,
+    w_4: Option<(Vec<F>, Vec<F>)>,
 
     z: Option<(Vec<F>, Vec<F>)>,
 
+    /// the compressed table, on the domain only.
+    table: Option<Vec<F>>,
+    h_1: Option<(Vec<F>, Vec<F>)>,
+    h_2: Option<(Vec<F>, Vec<F>)>,
+    z_lookup: Option<(Vec<F>, Vec<F>)>,
+
+    z_memory: Option<(Vec<F>, Vec<F>)>,
+
     beta: Option<F>,
     gamma: Option<F>,
+    eta: Option<F>,
+    delta: Option<F>,
+    lambda: Option<F>,
 }
 
-pub struct FirstOracles<F: Field> {
-    pub w_0: LabeledPolynomial<F>,
-    pub w_1: LabeledPolynomial<F>,
-    pub w_2: LabeledPolynomial<F>,
-    pub w_3: LabeledPolynomial<F>,
+impl<'a, F: Field> ProverState<'a, F> {
+    /// The values and coefficients of wire column `column`.
+    fn wire_mut(&mut self, column: usize) -> &mut Option<(Vec<F>, Vec<F>)> {
+        match column {
+            0 => &mut self.w_0,
+            1 => &mut self.w_1,
+            2 => &mut self.w_2,
+            3 => &mut self.w_3,
+            _ => &mut self.w_4,
+        }
+    }
 }
 
-impl<F: Field> FirstOracles<F> {
-    pub fn iter(&self) -> impl Iterator<Item = &LabeledPolynomial<F>> {
-        vec![&self.w_0, &self.w_1, &self.w_2, &self.w_3].into_iter()
+#[cfg(feature = "zeroize")]
+impl<'a, F: Field> Drop for ProverState<'a, F> {
+    fn drop(&mut self) {
+        let mut wires = [
+            &mut self.w_0,
+            &mut self.w_1,
+            &mut self.w_2,
+            &mut self.w_3,
+            &mut self.w_4,
+            &mut self.z,
+            &mut self.h_1,
+            &mut self.h_2,
+            &mut self.z_lookup,
+            &mut self.z_memory,
+        ];
+        for wire in wires.iter_mut() {
+            if let Some((values, coeffs)) = wire.take() {
+                crate::utils::zeroize(values);
+                crate::utils::zeroize(coeffs);
+            }
+        }
     }
 }
 
-pub struct SecondOracles<F: Field> {
-    pub z: LabeledPolynomial<F>,
+/// The wire columns of the circuit, see `IndexInfo::columns`.
+pub struct FirstOracles<F: Field> {
+    pub wires: Vec<LabeledPolynomial<F>>,
 }
 
-impl<F: Field> SecondOracles<F> {
-    pub fn iter(&self) -> impl Iterator<Item = &LabeledPolynomial<F>> {
-        vec![&self.z].into_iter()
-    }
+pub struct SecondOracles<F: Field> {
+    pub z: LabeledPolynomial<F>,
+    pub h_1: LabeledPolynomial<F>,
+    pub h_2: LabeledPolynomial<F>,
 }
 
 pub struct ThirdOracles<F: Field> {
-    pub t_0: LabeledPolynomial<F>,
-    pub t_1: LabeledPolynomial<F>,
-    pub t_2: LabeledPolynomial<F>,
-    pub t_3: LabeledPolynomial<F>,
+    pub z_lookup: LabeledPolynomial<F>,
+    pub z_memory: LabeledPolynomial<F>,
 }
 
-impl<F: Field> ThirdOracles<F> {
-    pub fn iter(&self) -> impl Iterator<Item = &LabeledPolynomial<F>> {
-        vec![&self.t_0, &self.t_1, &self.t_2, &self.t_3].into_iter()
-    }
+/// The chunks of the quotient, see `IndexInfo::quotient_chunks`.
+pub struct FourthOracles<F: Field> {
+    pub t: Vec<LabeledPolynomial<F>>,
 }
 
 impl<F: Field> AHPForPLONK<F> {
     pub fn prover_init<'a>(
         cs: &Composer<F>,
         index: &'a Index<F>,
+        _ws: &mut ProverWorkspace<F>,
     ) -> Result<ProverState<'a, F>, Error> {
         let domain_n = index.domain_n();
-        let domain_4n = index.domain_4n();
 
-        let pi = cs.public_inputs();
-        let pi_n = pad_to_size(pi, domain_n.size());
-        let pi_poly = EvaluationsOnDomain::from_vec_and_domain(pi_n, domain_n).interpolate();
-        let pi_4n = domain_4n.coset_fft(&pi_poly);
+        let pi_n = pad_to_size(cs.public_inputs(), domain_n.size());
+        let pi = EvaluationsOnDomain::from_vec_and_domain(pi_n, domain_n).interpolate();
 
-        Ok(ProverState {
+        let mut ps = ProverState {
             index,
-            pi_4n,
+            pi: pi.coeffs,
 
             w_0: None,
             w_1: None,
             w_2: None,
             w_3: None,
+            w_4: None,
 
             z: None,
 
+            table: None,
+            h_1: None,
+            h_2: None,
+            z_lookup: None,
+
+            z_memory: None,
+
             beta: None,
             gamma: None,
-        })
+            eta: None,
+            delta: None,
+            lambda: None,
+        };
+        let columns = index.info.columns();
+        for column in (0..5).filter(|column| !columns.contains(column)) {
+            *ps.wire_mut(column) = Some((vec![F::zero(); domain_n.size()], Vec::new()));
+        }
+        Ok(ps)
+    }
+
+    /// The state after the rounds that sent `oracles`, the wires, then z,
+    /// h_1 and h_2, then z_lookup and z_memory, with their values computed
+    /// again from their polynomials. The values are
+    /// read modulo `X^n - 1`, which drops the blinding. `first_msg` is
+    /// given once z was sent, and `second_msg` once z_lookup was.
+    pub fn prover_resume<'a>(
+        cs: &Composer<F>,
+        index: &'a Index<F>,
+        oracles: &[LabeledPolynomial<F>],
+        first_msg: Option<FirstMsg<F>>,
+        second_msg: Option<SecondMsg<F>>,
+        ws: &mut ProverWorkspace<F>,
+    ) -> Result<ProverState<'a, F>, Error> {
+        let mut ps = Self::prover_init(cs, index, ws)?;
+        let domain_n = index.domain_n();
+        let evaluate = |poly: &LabeledPolynomial<F>| {
+            let n = domain_n.size();
+            let mut values = vec![F::zero(); n];
+            for (i, c) in poly.coeffs.iter().enumerate() {
+                values[i % n] += c;
+            }
+            domain_n.fft_in_place(&mut values);
+            Some((values, poly.coeffs.clone()))
+        };
+
+        let mut oracles = oracles.iter();
+        for (column, poly) in index.info.columns().iter().zip(oracles.by_ref()) {
+            *ps.wire_mut(*column) = evaluate(poly);
+        }
+        if let Some(FirstMsg { beta, gamma, eta }) = first_msg {
+            ps.z = oracles.next().and_then(&evaluate);
+            ps.h_1 = oracles.next().and_then(&evaluate);
+            ps.h_2 = oracles.next().and_then(&evaluate);
+            ps.beta = Some(beta);
+            ps.gamma = Some(gamma);
+            ps.eta = Some(eta);
+        }
+        if let Some(SecondMsg { delta, lambda }) = second_msg {
+            ps.z_lookup = oracles.next().and_then(&evaluate);
+            ps.z_memory = oracles.next().and_then(evaluate);
+            ps.delta = Some(delta);
+            ps.lambda = Some(lambda);
+        }
+        if let Some(eta) = ps.eta {
+            ps.table = Some(Self::compute_table(index, &eta)?);
+        }
+        Ok(ps)
+    }
+
+    /// The table of the lookups on the domain.
+    fn compute_table(index: &Index<F>, eta: &F) -> Result<Vec<F>, Error> {
+        let mut table = vec![F::zero(); index.domain_n().size()];
+        index.lookup_key().compute_table(eta, false, &mut table)?;
+        Ok(table)
+    }
+
+    /// The coefficients of an oracle sent in an earlier round.
+    fn coeffs(oracle: &Option<(Vec<F>, Vec<F>)>) -> &[F] {
+        &oracle.as_ref().unwrap().1
+    }
+
+    /// Adds `b(X) * (X^n - 1)` to `poly`, with `b` of `blinding` random
+    /// coefficients: the values on the domain stay, and an opening at a
+    /// point outside of it no longer reveals anything about them.
+    fn blind(poly: &mut DensePolynomial<F>, blinding: usize, n: usize, zk_rng: &mut dyn RngCore) {
+        if blinding == 0 {
+            return;
+        }
+        poly.coeffs.resize(n + blinding, F::zero());
+        for i in 0..blinding {
+            let b = F::rand(zk_rng);
+            poly.coeffs[i] -= b;
+            poly.coeffs[n + i] += b;
+        }
     }
 
     pub fn prover_first_round<'a>(
         mut ps: ProverState<'a, F>,
         cs: &Composer<F>,
+        ws: &mut ProverWorkspace<F>,
+        zk_rng: &mut dyn RngCore,
     ) -> Result<(ProverState<'a, F>, FirstOracles<F>), Error> {
-        let witnesses = cs.synthesize()?;
-        let Witnesses { w_0, w_1, w_2, w_3 } = witnesses;
+        let witnesses =
+            tracing::debug_span!("synthesize", gates = cs.size()).in_scope(|| cs.synthesize())?;
 
         let domain_n = ps.index.domain_n();
+        let n = domain_n.size();
+        let span = tracing::debug_span!("fft", n);
+        let _enter = span.enter();
+        let blinding = ps.index.info.blinding;
         //返回 系数表示的多项式
-        let w_0_poly =
-            EvaluationsOnDomain::from_vec_and_domain(w_0.clone(), domain_n).interpolate();
-        let w_1_poly =
-            EvaluationsOnDomain::from_vec_and_domain(w_1.clone(), domain_n).interpolate();
-        let w_2_poly =
-            EvaluationsOnDomain::from_vec_and_domain(w_2.clone(), domain_n).interpolate();
-        let w_3_poly =
-            EvaluationsOnDomain::from_vec_and_domain(w_3.clone(), domain_n).interpolate();
-
-        let domain_4n = ps.index.domain_4n();
-        //4n的fft ？
-        let w_0_4n = domain_4n.coset_fft(&w_0_poly);
-        let w_1_4n = domain_4n.coset_fft(&w_1_poly);
-        let w_2_4n = domain_4n.coset_fft(&w_2_poly);
-        let w_3_4n = domain_4n.coset_fft(&w_3_poly);
-
-        let first_oracles = FirstOracles {
-            w_0: to_labeled("w_0", w_0_poly),
-            w_1: to_labeled("w_1", w_1_poly),
-            w_2: to_labeled("w_2", w_2_poly),
-            w_3: to_labeled("w_3", w_3_poly),
+        let mut interpolate = |w: &[F]| {
+            ws.yield_now();
+            // in place, with room for the blinding.
+            let mut coeffs = Vec::with_capacity(n + blinding);
+            coeffs.extend_from_slice(w);
+            domain_n.ifft_in_place(&mut coeffs);
+            let mut poly = DensePolynomial::from_coefficients_vec(coeffs);
+            Self::blind(&mut poly, blinding, n, zk_rng);
+            poly
         };
+        let Witnesses {
+            w_0,
+            w_1,
+            w_2,
+            w_3,
+            w_4,
+        } = witnesses;
+        let columns = ps.index.info.columns();
+        let mut wires = Vec::with_capacity(columns.len());
+        for (column, w) in vec![w_0, w_1, w_2, w_3, w_4].into_iter().enumerate() {
+            if columns.contains(&column) {
+                let poly = interpolate(&w);
+                *ps.wire_mut(column) = Some((w, poly.coeffs.clone()));
+                wires.push(to_labeled(&format!("w_{}", column), poly));
+            }
+        }
 
-        ps.w_0 = Some((w_0, w_0_4n));
-        ps.w_1 = Some((w_1, w_1_4n));
-        ps.w_2 = Some((w_2, w_2_4n));
-        ps.w_3 = Some((w_3, w_3_4n));
+        let first_oracles = FirstOracles { wires };
 
         Ok((ps, first_oracles))
     }
@@ -137,211 +279,357 @@ impl<F: Field> AHPForPLONK<F> {
     pub fn prover_second_round<'a>(
         mut ps: ProverState<'a, F>,
         msg: &FirstMsg<F>,
-        ks: &[F; 4],
+        ks: &[F],
+        ws: &mut ProverWorkspace<F>,
+        zk_rng: &mut dyn RngCore,
     ) -> Result<(ProverState<'a, F>, SecondOracles<F>), Error> {
-        let w_0 = &ps.w_0.as_ref().unwrap().0;
         let w_1 = &ps.w_1.as_ref().unwrap().0;
         let w_2 = &ps.w_2.as_ref().unwrap().0;
         let w_3 = &ps.w_3.as_ref().unwrap().0;
-        let FirstMsg { beta, gamma } = msg;
+        let w_n: Vec<_> = {
+            let wires = [&ps.w_0, &ps.w_1, &ps.w_2, &ps.w_3, &ps.w_4];
+            let columns = ps.index.info.columns();
+            columns
+                .iter()
+                .map(|c| &wires[*c].as_ref().unwrap().0[..])
+                .collect()
+        };
+        let FirstMsg { beta, gamma, eta } = msg;
+        let domain_n = ps.index.domain_n();
+
+        let span = tracing::debug_span!("permutation", n = domain_n.size());
+        let _enter = span.enter();
 
         let permutation_key = ps.index.permutation_key();
-        let (z_poly, z, z_4n) = permutation_key.compute_z(
-            ps.index.domain_n(),
-            ps.index.domain_4n(),
-            ks,
-            (w_0, w_1, w_2, w_3),
-            beta,
-            gamma,
-        );
+        let (mut z_poly, z) = permutation_key.compute_z(domain_n, ks, &w_n, beta, gamma);
+        let blinding = ps.index.info.blinding;
+        Self::blind(&mut z_poly, blinding, domain_n.size(), zk_rng);
+        ws.yield_now();
+
+        // the queries and the table, sorted together.
+        let table = Self::compute_table(ps.index, eta)?;
+        let f = ps.index.lookup_key().compute_f((w_1, w_2, w_3), eta);
+        let sorted = LookupKey::compute_sorted(&f, &table);
+        #[cfg(feature = "zeroize")]
+        crate::utils::zeroize(f);
+        let (h_1, h_2) = sorted.map_err(|row| Error::MissingLookup { row })?;
+
+        let mut interpolate = |h: &[F]| {
+            ws.yield_now();
+            let mut coeffs = h.to_vec();
+            domain_n.ifft_in_place(&mut coeffs);
+            DensePolynomial::from_coefficients_vec(coeffs)
+        };
+        let mut h_1_poly = interpolate(&h_1);
+        let mut h_2_poly = interpolate(&h_2);
+        Self::blind(&mut h_1_poly, blinding, domain_n.size(), zk_rng);
+        Self::blind(&mut h_2_poly, blinding, domain_n.size(), zk_rng);
+
+        ps.z = Some((z, z_poly.coeffs.clone()));
+        ps.table = Some(table);
+        ps.h_1 = Some((h_1, h_1_poly.coeffs.clone()));
+        ps.h_2 = Some((h_2, h_2_poly.coeffs.clone()));
+        ps.beta = Some(*beta);
+        ps.gamma = Some(*gamma);
+        ps.eta = Some(*eta);
 
         let second_oracles = SecondOracles {
             z: LabeledPolynomial::new("z".to_string(), z_poly, None, None),
+            h_1: to_labeled("h_1", h_1_poly),
+            h_2: to_labeled("h_2", h_2_poly),
         };
 
-        ps.z = Some((z, z_4n));
-        ps.beta = Some(*beta);
-        ps.gamma = Some(*gamma);
-
         Ok((ps, second_oracles))
     }
 
-    //改造t（pdf里的η就是这里的α
     pub fn prover_third_round<'a>(
-        ps: ProverState<'a, F>,
+        mut ps: ProverState<'a, F>,
         msg: &SecondMsg<F>,
-        ks: &[F; 4],
-    ) -> Result<ThirdOracles<F>, Error> {
+        ws: &mut ProverWorkspace<F>,
+        zk_rng: &mut dyn RngCore,
+    ) -> Result<(ProverState<'a, F>, ThirdOracles<F>), Error> {
+        let w_0 = &ps.w_0.as_ref().unwrap().0;
+        let w_1 = &ps.w_1.as_ref().unwrap().0;
+        let w_2 = &ps.w_2.as_ref().unwrap().0;
+        let w_3 = &ps.w_3.as_ref().unwrap().0;
+        let table = ps.table.as_ref().unwrap();
+        let h_1 = &ps.h_1.as_ref().unwrap().0;
+        let h_2 = &ps.h_2.as_ref().unwrap().0;
+        let SecondMsg { delta, lambda } = msg;
+
+        let span = tracing::debug_span!("lookup", n = ps.index.domain_n().size());
+        let _enter = span.enter();
+
+        let f = ps
+            .index
+            .lookup_key()
+            .compute_f((w_1, w_2, w_3), &ps.eta.unwrap());
+        let (mut z_poly, z) =
+            LookupKey::compute_z(ps.index.domain_n(), &f, table, h_1, h_2, delta, lambda);
+        #[cfg(feature = "zeroize")]
+        crate::utils::zeroize(f);
+        ws.yield_now();
+
+        let (mut z_memory_poly, z_memory) = tracing::debug_span!("memory")
+            .in_scope(|| {
+                ps.index.memory_key().compute_z(
+                    ps.index.domain_n(),
+                    (w_0, w_1, w_2, w_3),
+                    &ps.eta.unwrap(),
+                    lambda,
+                )
+            })
+            .ok_or(Error::InconsistentMemory)?;
+        ws.yield_now();
+
+        let blinding = ps.index.info.blinding;
+        let n = ps.index.domain_n().size();
+        Self::blind(&mut z_poly, blinding, n, zk_rng);
+        Self::blind(&mut z_memory_poly, blinding, n, zk_rng);
+
+        ps.z_lookup = Some((z, z_poly.coeffs.clone()));
+        ps.z_memory = Some((z_memory, z_memory_poly.coeffs.clone()));
+        ps.delta = Some(*delta);
+        ps.lambda = Some(*lambda);
+
+        let third_oracles = ThirdOracles {
+            z_lookup: to_labeled("z_lookup", z_poly),
+            z_memory: to_labeled("z_memory", z_memory_poly),
+        };
+
+        Ok((ps, third_oracles))
+    }
+
+    //改造t（pdf里的η就是这里的α
+    /// The oracles are extended to the 4n coset only around the terms that
+    /// use them and recycled right after, so that at most the wires, t and
+    /// the four polynomials of the lookup are extended at once.
+    pub fn prover_fourth_round<'a>(
+        ps: ProverState<'a, F>,
+        msg: &ThirdMsg<F>,
+        ks: &[F],
+        ws: &mut ProverWorkspace<F>,
+    ) -> Result<FourthOracles<F>, Error> {
         let domain_n = ps.index.domain_n();
         let domain_4n = ps.index.domain_4n();
+        let span = tracing::debug_span!("quotient", n_4n = domain_4n.size());
+        let _enter = span.enter();
+
+        let ThirdMsg { alpha } = *msg;
+        let extend = |ws: &mut ProverWorkspace<F>, coeffs: &[F]| {
+            ws.yield_now();
+            ws.coset_fft(domain_4n, coeffs)
+        };
 
-        let w_0_4n = &ps.w_0.as_ref().unwrap().1;
-        let w_1_4n = &ps.w_1.as_ref().unwrap().1;
-        let w_2_4n = &ps.w_2.as_ref().unwrap().1;
-        let w_3_4n = &ps.w_3.as_ref().unwrap().1;
-        let z_4n = &ps.z.as_ref().unwrap().1;
+        // the wires are used by most terms, so they stay extended throughout.
+        let w_0_4n = extend(ws, Self::coeffs(&ps.w_0));
+        let w_1_4n = extend(ws, Self::coeffs(&ps.w_1));
+        let w_2_4n = extend(ws, Self::coeffs(&ps.w_2));
+        let w_3_4n = extend(ws, Self::coeffs(&ps.w_3));
+        // `w_4` is only read by the arithmetic and permutation terms, with
+        // five wires.
+        let columns = ps.index.info.columns();
+        let w_4_4n = if columns.len() == 5 {
+            Some(extend(ws, Self::coeffs(&ps.w_4)))
+        } else {
+            None
+        };
+        let w_4n = (&w_0_4n[..], &w_1_4n[..], &w_2_4n[..], &w_3_4n[..]);
+        let wide_4n = w_4_4n.as_deref();
+        let wires_4n: Vec<_> = columns
+            .iter()
+            .map(|c| match c {
+                0 => &w_0_4n[..],
+                1 => &w_1_4n[..],
+                2 => &w_2_4n[..],
+                3 => &w_3_4n[..],
+                _ => wide_4n.unwrap(),
+            })
+            .collect();
 
-        let SecondMsg { alpha } = *msg;
+        // the terms are summed into a single buffer, which then becomes the
+        // coefficients of t.
+        let mut t = ws.take(domain_4n.size());
 
+        let pi_4n = extend(ws, &ps.pi);
         let arithmetic_key = ps.index.arithmetic_key();
-        let t_arith =
-            arithmetic_key.compute_quotient(domain_4n, (w_0_4n, w_1_4n, w_2_4n, w_3_4n), &ps.pi_4n);
+        arithmetic_key.compute_quotient(domain_4n, w_4n, wide_4n, &pi_4n, &mut t)?;
+        ws.recycle(pi_4n);
 
+        let z_4n = extend(ws, Self::coeffs(&ps.z));
         let permutation_key = ps.index.permutation_key();
-        let t_perm = permutation_key.compute_quotient(
+        permutation_key.compute_quotient(
             domain_4n,
             ks,
-            (w_0_4n, w_1_4n, w_2_4n, w_3_4n),
-            z_4n,
+            &wires_4n,
+            &z_4n,
             &ps.beta.unwrap(),
             &ps.gamma.unwrap(),
             &alpha,
-        );
+            &mut t,
+        )?;
+        ws.recycle(z_4n);
+        ws.yield_now();
 
         //range gate
-        let t_range = ps.index.compute_quotient_q_range(
-            domain_4n,
-            (w_0_4n, w_1_4n, w_2_4n, w_3_4n),
-            &alpha,
-        );
+        ps.index
+            .compute_quotient_q_range(domain_4n, w_4n, &alpha, &mut t)?;
+        ws.yield_now();
 
         //mimc
         let mimc_key = ps.index.mimc_key();
-        // let t_mimc = mimc_key.compute_quotient(
-        //     domain_4n,
-        //     (w_0_4n, w_1_4n, w_2_4n, w_3_4n),
-        //     &alpha,
-        // );
-        let t_mimc = mimc_key.compute_quotient_nosponge(
+        // mimc_key.compute_quotient(domain_4n, w_4n, &alpha, &mut t);
+        mimc_key.compute_quotient_nosponge(domain_4n, w_4n, &alpha, &mut t)?;
+        ws.yield_now();
+
+        let logic_key = ps.index.logic_key();
+        logic_key.compute_quotient(domain_4n, w_4n, &alpha, &mut t)?;
+        ws.yield_now();
+
+        let curve_key = ps.index.curve_key();
+        curve_key.compute_quotient(domain_4n, w_4n, &alpha, &mut t)?;
+        ws.yield_now();
+
+        // shared by the lookup and memory terms.
+        let l1_4n = permutation_key.l1_4n.load()?;
+        let lookup_key = ps.index.lookup_key();
+        let mut table_4n = ws.take(domain_4n.size());
+        lookup_key.compute_table(&ps.eta.unwrap(), true, &mut table_4n)?;
+        let h_1_4n = extend(ws, Self::coeffs(&ps.h_1));
+        let h_2_4n = extend(ws, Self::coeffs(&ps.h_2));
+        let z_lookup_4n = extend(ws, Self::coeffs(&ps.z_lookup));
+        lookup_key.compute_quotient(
+            domain_n,
+            domain_4n,
+            &permutation_key.linear_4n.load()?,
+            &l1_4n,
+            (&w_1_4n[..], &w_2_4n[..], &w_3_4n[..]),
+            &table_4n,
+            (&h_1_4n[..], &h_2_4n[..], &z_lookup_4n[..]),
+            (ps.eta.unwrap(), ps.delta.unwrap(), ps.lambda.unwrap()),
+            &alpha,
+            &mut t,
+        )?;
+        ws.recycle(table_4n);
+        ws.recycle(h_1_4n);
+        ws.recycle(h_2_4n);
+        ws.recycle(z_lookup_4n);
+
+        let z_memory_4n = extend(ws, Self::coeffs(&ps.z_memory));
+        let memory_key = ps.index.memory_key();
+        memory_key.compute_quotient(
             domain_4n,
-            (w_0_4n, w_1_4n, w_2_4n, w_3_4n),
+            &l1_4n,
+            w_4n,
+            &z_memory_4n,
+            &ps.eta.unwrap(),
+            &ps.lambda.unwrap(),
             &alpha,
-        );
-
-        let t: Vec<_> = cfg_iter!(t_arith)
-            .zip(&t_perm)
-            .zip(&t_range)
-            .zip(&t_mimc)
-            .zip(ps.index.v_4n_inversed())
-            .map(|((((t_arith, t_perm), t_range), t_mimc), vi)| (*t_arith + t_perm + t_range + t_mimc) * vi)
+            &mut t,
+        )?;
+        ws.recycle(z_memory_4n);
+        drop(l1_4n);
+        ws.recycle(w_0_4n);
+        ws.recycle(w_1_4n);
+        ws.recycle(w_2_4n);
+        ws.recycle(w_3_4n);
+        if let Some(w_4_4n) = w_4_4n {
+            ws.recycle(w_4_4n);
+        }
+        ws.yield_now();
+
+        cfg_iter_mut!(t, MIN_CHUNK)
+            .zip(&ps.index.v_4n_inversed()?[..])
+            .for_each(|(t, vi)| *t *= vi);
+        ws.yield_now();
+        domain_4n.coset_ifft_in_place(&mut t);
+        let t_poly = DensePolynomial::from_coefficients_vec(t);
+
+        // the oracles are not needed anymore, t is split in their place.
+        let chunk = ps.index.info.quotient_chunk();
+        let chunks = ps.index.info.quotient_chunks();
+        drop(ps);
+        let t = Self::split(chunk, chunks, t_poly)
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| LabeledPolynomial::new(format!("t_{}", i), t, None, None))
             .collect();
 
-        let t_poly = DensePolynomial::from_coefficients_vec(domain_4n.coset_ifft(&t));
-
-        let t_polys = Self::quad_split(domain_n.size(), t_poly);
-
-        let third_oracles = ThirdOracles {
-            t_0: LabeledPolynomial::new("t_0".into(), t_polys.0, None, None),
-            t_1: LabeledPolynomial::new("t_1".into(), t_polys.1, None, None),
-            t_2: LabeledPolynomial::new("t_2".into(), t_polys.2, None, None),
-            t_3: LabeledPolynomial::new("t_3".into(), t_polys.3, None, None),
-        };
-
-        Ok(third_oracles)
+        Ok(FourthOracles { t })
     }
 
-    //平均分成四段
-    fn quad_split(
-        n: usize,
-        poly: DensePolynomial<F>,
-    ) -> (
-        DensePolynomial<F>,
-        DensePolynomial<F>,
-        DensePolynomial<F>,
-        DensePolynomial<F>,
-    ) {
-        let mut poly_0 = DensePolynomial::zero();
-        let mut poly_1 = DensePolynomial::zero();
-        let mut poly_2 = DensePolynomial::zero();
-        let mut poly_3 = DensePolynomial::zero();
-
+    //平均分成 chunks 段
+    fn split(n: usize, chunks: usize, poly: DensePolynomial<F>) -> Vec<DensePolynomial<F>> {
         let mut coeffs = poly.coeffs.into_iter().peekable();
-        if coeffs.peek().is_some() {
-            let chunk: Vec<_> = coeffs.by_ref().take(n).collect();
-            poly_0 = DensePolynomial::from_coefficients_vec(chunk.to_vec());
-        }
-        if coeffs.peek().is_some() {
-            let chunk: Vec<_> = coeffs.by_ref().take(n).collect();
-            poly_1 = DensePolynomial::from_coefficients_vec(chunk.to_vec());
-        }
-        if coeffs.peek().is_some() {
-            let chunk: Vec<_> = coeffs.by_ref().take(n).collect();
-            poly_2 = DensePolynomial::from_coefficients_vec(chunk.to_vec());
-        }
-        if coeffs.peek().is_some() {
-            let chunk: Vec<_> = coeffs.by_ref().take(n).collect();
-            poly_3 = DensePolynomial::from_coefficients_vec(chunk.to_vec());
-        }
-
-        (poly_0, poly_1, poly_2, poly_3)
+        (0..chunks)
+            .map(|_| {
+                if coeffs.peek().is_some() {
+                    let chunk: Vec<_> = coeffs.by_ref().take(n).collect();
+                    DensePolynomial::from_coefficients_vec(chunk)
+                } else {
+                    DensePolynomial::zero()
+                }
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use ark_bls12_381::Fr;
-    use ark_ff::{Field, One, UniformRand};
+    use ark_ff::UniformRand;
     use ark_poly::{univariate::DensePolynomial, Polynomial};
-    use ark_poly_commit::LinearCombination;
     use ark_std::test_rng;
 
     use super::*;
     use crate::ahp::Error;
     use crate::ahp::EvaluationsProvider;
 
-    fn compare(n: usize, t: DensePolynomial<Fr>, zeta: Fr) -> Result<bool, Error> {
-        let (t_0, t_1, t_2, t_3) = AHPForPLONK::quad_split(n, t.clone());
-
-        let labeled_poly = |label: String, poly: DensePolynomial<Fr>| {
-            LabeledPolynomial::new(label, poly, None, None)
-        };
-        let ts = vec![
-            labeled_poly("t_0".to_string(), t_0),
-            labeled_poly("t_1".to_string(), t_1),
-            labeled_poly("t_2".to_string(), t_2),
-            labeled_poly("t_3".to_string(), t_3),
-        ];
+    fn compare(n: usize, chunks: usize, t: DensePolynomial<Fr>, zeta: Fr) -> Result<bool, Error> {
+        let ts: Vec<_> = AHPForPLONK::split(n, chunks, t.clone())
+            .into_iter()
+            .enumerate()
+            .map(|(i, t)| LabeledPolynomial::new(format!("t_{}", i), t, None, None))
+            .collect();
 
-        let t_lc = {
-            let zeta_n = zeta.pow(&[n as u64]);
-            let zeta_2n = zeta_n.square();
-
-            LinearCombination::new(
-                "t",
-                vec![
-                    (Fr::one(), "t_0"),
-                    (zeta_n, "t_1"),
-                    (zeta_2n, "t_2"),
-                    (zeta_n * zeta_2n, "t_3"),
-                ],
-            )
-        };
+        let t_lc = AHPForPLONK::t_linear_combination(n, chunks, zeta);
         let t_lc_zeta = ts.get_lc_eval(&t_lc, zeta)?;
         let t_zeta = t.evaluate(&zeta);
         Ok(t_lc_zeta == t_zeta)
     }
 
     #[test]
-    fn test_quad_split_full() -> Result<(), Error> {
+    fn test_split_full() -> Result<(), Error> {
         let rng = &mut test_rng();
         let n = 7;
 
         let t = DensePolynomial::<Fr>::rand(4 * n - 1, rng);
         let zeta = Fr::rand(rng);
-        let is_equal = compare(n, t, zeta)?;
+        let is_equal = compare(n, 4, t, zeta)?;
         assert!(is_equal);
         Ok(())
     }
 
     #[test]
-    fn test_quad_split_not_full() -> Result<(), Error> {
+    fn test_split_not_full() -> Result<(), Error> {
         let rng = &mut test_rng();
         let n = 7;
 
         let t = DensePolynomial::<Fr>::rand(2 * n, rng);
         let zeta = Fr::rand(rng);
-        let is_equal = compare(n, t, zeta)?;
+        let is_equal = compare(n, 4, t, zeta)?;
         assert!(is_equal);
         Ok(())
     }
+
+    #[test]
+    fn test_split_five() -> Result<(), Error> {
+        let rng = &mut test_rng();
+        let n = 7;
+
+        let t = DensePolynomial::<Fr>::rand(5 * n - 1, rng);
+        let zeta = Fr::rand(rng);
+        assert!(compare(n, 5, t, zeta)?);
+        Ok(())
+    }
 }
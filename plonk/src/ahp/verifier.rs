@@ -1,32 +1,42 @@
 use ark_ff::FftField as Field;
-use ark_poly::{EvaluationDomain, Evaluations as EvaluationsOnDomain, Polynomial};
+use ark_poly::EvaluationDomain;
 use ark_poly_commit::{Evaluations, QuerySet};
-use ark_std::string::ToString;
+use ark_std::{format, string::ToString};
 use rand_core::RngCore;
 
-use crate::ahp::indexer::IndexInfo;
+use crate::ahp::indexer::{IndexInfo, LookupEvals, LookupKey, MemoryEvals, MemoryKey};
 use crate::ahp::{AHPForPLONK, Error};
+use crate::data_structures::EvaluationsPoly;
 use crate::utils::{evaluate_first_lagrange_poly, evaluate_vanishing_poly, generator, pad_to_size};
 
 pub struct VerifierState<'a, F: Field> {
     info: &'a IndexInfo<F>,
 
-    alpha: Option<F>, // combination
-    beta: Option<F>,  // permutation
-    gamma: Option<F>, // permutation
-    zeta: Option<F>,  // evaluation
+    alpha: Option<F>,  // combination
+    beta: Option<F>,   // permutation
+    gamma: Option<F>,  // permutation
+    eta: Option<F>,    // lookup and memory, compression of the wires
+    delta: Option<F>,  // lookup
+    lambda: Option<F>, // lookup and memory
+    zeta: Option<F>,   // evaluation
 }
 
 pub struct FirstMsg<F: Field> {
     pub beta: F,
     pub gamma: F,
+    pub eta: F,
 }
 
 pub struct SecondMsg<F: Field> {
-    pub alpha: F,
+    pub delta: F,
+    pub lambda: F,
 }
 
 pub struct ThirdMsg<F: Field> {
+    pub alpha: F,
+}
+
+pub struct FourthMsg<F: Field> {
     pub zeta: F,
 }
 
@@ -38,6 +48,9 @@ impl<F: Field> AHPForPLONK<F> {
             alpha: None,
             beta: None,
             gamma: None,
+            eta: None,
+            delta: None,
+            lambda: None,
             zeta: None,
         })
     }
@@ -48,30 +61,44 @@ impl<F: Field> AHPForPLONK<F> {
     ) -> Result<(VerifierState<'a, F>, FirstMsg<F>), Error> {
         let beta = F::rand(rng);
         let gamma = F::rand(rng);
+        let eta = F::rand(rng);
         vs.beta = Some(beta);
         vs.gamma = Some(gamma);
+        vs.eta = Some(eta);
 
-        Ok((vs, FirstMsg { beta, gamma }))
+        Ok((vs, FirstMsg { beta, gamma, eta }))
     }
 
     pub fn verifier_second_round<'a, R: RngCore>(
         mut vs: VerifierState<'a, F>,
         rng: &mut R,
     ) -> Result<(VerifierState<'a, F>, SecondMsg<F>), Error> {
-        let alpha = F::rand(rng);
-        vs.alpha = Some(alpha);
+        let delta = F::rand(rng);
+        let lambda = F::rand(rng);
+        vs.delta = Some(delta);
+        vs.lambda = Some(lambda);
 
-        Ok((vs, SecondMsg { alpha }))
+        Ok((vs, SecondMsg { delta, lambda }))
     }
 
     pub fn verifier_third_round<'a, R: RngCore>(
         mut vs: VerifierState<'a, F>,
         rng: &mut R,
     ) -> Result<(VerifierState<'a, F>, ThirdMsg<F>), Error> {
+        let alpha = F::rand(rng);
+        vs.alpha = Some(alpha);
+
+        Ok((vs, ThirdMsg { alpha }))
+    }
+
+    pub fn verifier_fourth_round<'a, R: RngCore>(
+        mut vs: VerifierState<'a, F>,
+        rng: &mut R,
+    ) -> Result<(VerifierState<'a, F>, FourthMsg<F>), Error> {
         let zeta = F::rand(rng);
         vs.zeta = Some(zeta);
 
-        Ok((vs, ThirdMsg { zeta }))
+        Ok((vs, FourthMsg { zeta }))
     }
 
     pub fn verifier_query_set(vs: &VerifierState<'_, F>) -> QuerySet<F> {
@@ -86,25 +113,48 @@ impl<F: Field> AHPForPLONK<F> {
         // where label is the label of a polynomial in p
         // point_label is the label for the point (e.g., “beta”)
         // point is the location that p[label] is to be queried at.
-        query_set.insert(("w_0".into(), ("zeta".into(), zeta)));
-        query_set.insert(("w_1".into(), ("zeta".into(), zeta)));
-        query_set.insert(("w_2".into(), ("zeta".into(), zeta)));
-        query_set.insert(("w_3".into(), ("zeta".into(), zeta)));
+        // the wire columns, and the sigmas but the last, which is in `r`.
+        let columns = vs.info.columns();
+        for column in columns {
+            query_set.insert((format!("w_{}", column), ("zeta".into(), zeta)));
+        }
 
         query_set.insert(("z".into(), ("shifted_zeta".into(), zeta * g)));
 
-        query_set.insert(("sigma_0".into(), ("zeta".into(), zeta)));
-        query_set.insert(("sigma_1".into(), ("zeta".into(), zeta)));
-        query_set.insert(("sigma_2".into(), ("zeta".into(), zeta)));
+        for column in &columns[..columns.len() - 1] {
+            query_set.insert((format!("sigma_{}", column), ("zeta".into(), zeta)));
+        }
         query_set.insert(("q_arith".into(), ("zeta".into(), zeta)));
 
         query_set.insert(("t".into(), ("zeta".into(), zeta)));
         query_set.insert(("r".into(), ("zeta".into(), zeta)));
 
         //todo 找很久
-        query_set.insert(("w_0".into(), ("shifted_zeta".into(), zeta * g)));
+        if columns.contains(&0) {
+            query_set.insert(("w_0".into(), ("shifted_zeta".into(), zeta * g)));
+        }
         //query_set.insert(("q_mimc".into(), ("zeta".into(), zeta)));
 
+        query_set.insert(("q_lookup".into(), ("zeta".into(), zeta)));
+        query_set.insert(("table".into(), ("zeta".into(), zeta)));
+        query_set.insert(("table".into(), ("shifted_zeta".into(), zeta * g)));
+        query_set.insert(("h_1".into(), ("zeta".into(), zeta)));
+        query_set.insert(("h_1".into(), ("shifted_zeta".into(), zeta * g)));
+        query_set.insert(("h_2".into(), ("shifted_zeta".into(), zeta * g)));
+        query_set.insert(("z_lookup".into(), ("shifted_zeta".into(), zeta * g)));
+
+        query_set.insert(("q_logic".into(), ("zeta".into(), zeta)));
+        query_set.insert(("w_1".into(), ("shifted_zeta".into(), zeta * g)));
+        query_set.insert(("w_2".into(), ("shifted_zeta".into(), zeta * g)));
+        query_set.insert(("w_3".into(), ("shifted_zeta".into(), zeta * g)));
+
+        query_set.insert(("q_memory".into(), ("zeta".into(), zeta)));
+        query_set.insert(("z_memory".into(), ("shifted_zeta".into(), zeta * g)));
+
+        if vs.info.committed_inputs {
+            query_set.insert(("pi".into(), ("zeta".into(), zeta)));
+        }
+
         query_set
     }
 
@@ -116,32 +166,78 @@ impl<F: Field> AHPForPLONK<F> {
         let alpha = vs.alpha.unwrap();
         let beta = vs.beta.unwrap();
         let gamma = vs.gamma.unwrap();
+        let eta = vs.eta.unwrap();
+        let delta = vs.delta.unwrap();
+        let lambda = vs.lambda.unwrap();
         let zeta = vs.zeta.unwrap();
 
         let domain_n = vs.info.domain_n;
         let g = generator(domain_n);
         let v_zeta = evaluate_vanishing_poly(domain_n, zeta);
-        let pi_zeta = {
+        // committed public inputs come with their opening, the others are
+        // interpolated here
+        let pi_zeta = if vs.info.committed_inputs {
+            get_eval(evaluations, "pi", &zeta)?
+        } else {
             let pi_n = pad_to_size(public_inputs, domain_n.size());
-            let pi_poly = EvaluationsOnDomain::from_vec_and_domain(pi_n, domain_n).interpolate();
-            pi_poly.evaluate(&zeta)
+            EvaluationsPoly::new("pi".to_string(), pi_n, domain_n).evaluate(zeta)
         };
 
-        let w_0_zeta = get_eval(&evaluations, "w_0", &zeta)?;
-        let w_1_zeta = get_eval(&evaluations, "w_1", &zeta)?;
-        let w_2_zeta = get_eval(&evaluations, "w_2", &zeta)?;
-        let w_3_zeta = get_eval(&evaluations, "w_3", &zeta)?;
-
-        let z_shifted_zeta = get_eval(&evaluations, "z", &(zeta * g))?;
-
-        let sigma_0_zeta = get_eval(&evaluations, "sigma_0", &zeta)?;
-        let sigma_1_zeta = get_eval(&evaluations, "sigma_1", &zeta)?;
-        let sigma_2_zeta = get_eval(&evaluations, "sigma_2", &zeta)?;
-        let q_arith_zeta = get_eval(&evaluations, "q_arith", &zeta)?;
-        //let q_mimc_c_zeta = get_eval(&evaluations, "q_mimc_c", &zeta)?;
-
-        let t_zeta = get_eval(&evaluations, "t", &zeta)?;
-        let r_zeta = get_eval(&evaluations, "r", &zeta)?;
+        // the wire columns the circuit has not are zero.
+        let columns = vs.info.columns();
+        let w_zeta = |column: usize| {
+            if columns.contains(&column) {
+                get_eval(evaluations, &format!("w_{}", column), &zeta)
+            } else {
+                Ok(F::zero())
+            }
+        };
+        let w_0_zeta = w_zeta(0)?;
+        let w_1_zeta = w_zeta(1)?;
+        let w_2_zeta = w_zeta(2)?;
+        let w_3_zeta = w_zeta(3)?;
+
+        let z_shifted_zeta = get_eval(evaluations, "z", &(zeta * g))?;
+
+        // the permutation over the wire columns, with the sigma of the last
+        // one in `r`.
+        let (last, others) = columns.split_last().unwrap();
+        let mut permutation = w_zeta(*last)? + gamma;
+        for column in others {
+            let sigma_zeta = get_eval(evaluations, &format!("sigma_{}", column), &zeta)?;
+            permutation *= w_zeta(*column)? + beta * sigma_zeta + gamma;
+        }
+        let q_arith_zeta = get_eval(evaluations, "q_arith", &zeta)?;
+        //let q_mimc_c_zeta = get_eval(evaluations, "q_mimc_c", &zeta)?;
+
+        let t_zeta = get_eval(evaluations, "t", &zeta)?;
+        let r_zeta = get_eval(evaluations, "r", &zeta)?;
+
+        let lookup_evals = LookupEvals {
+            w_zeta: (w_1_zeta, w_2_zeta, w_3_zeta),
+            q_lookup_zeta: get_eval(evaluations, "q_lookup", &zeta)?,
+            table_zeta: get_eval(evaluations, "table", &zeta)?,
+            table_shifted_zeta: get_eval(evaluations, "table", &(zeta * g))?,
+            h_1_zeta: get_eval(evaluations, "h_1", &zeta)?,
+            h_1_shifted_zeta: get_eval(evaluations, "h_1", &(zeta * g))?,
+            h_2_shifted_zeta: get_eval(evaluations, "h_2", &(zeta * g))?,
+            z_lookup_shifted_zeta: get_eval(evaluations, "z_lookup", &(zeta * g))?,
+        };
+        let lookup_zeta = LookupKey::evaluate_constant(
+            domain_n,
+            &lookup_evals,
+            (eta, delta, lambda),
+            alpha,
+            zeta,
+        );
+
+        let memory_evals = MemoryEvals {
+            w_zeta: (w_0_zeta, w_1_zeta, w_2_zeta, w_3_zeta),
+            q_memory_zeta: get_eval(evaluations, "q_memory", &zeta)?,
+            z_memory_shifted_zeta: get_eval(evaluations, "z_memory", &(zeta * g))?,
+        };
+        let memory_zeta =
+            MemoryKey::evaluate_constant(domain_n, &memory_evals, (eta, lambda), alpha, zeta);
 
         let l1_zeta = evaluate_first_lagrange_poly(vs.info.domain_n, zeta);
         let alpha_2 = alpha.square();
@@ -149,13 +245,10 @@ impl<F: Field> AHPForPLONK<F> {
         let lhs :F = t_zeta * v_zeta;
         //不需要改，因为range,mimc 在r里了
         let rhs = r_zeta + q_arith_zeta * pi_zeta
-            - z_shifted_zeta
-                * (w_0_zeta + beta * sigma_0_zeta + gamma)
-                * (w_1_zeta + beta * sigma_1_zeta + gamma)
-                * (w_2_zeta + beta * sigma_2_zeta + gamma)
-                * (w_3_zeta + gamma)
-                * alpha
-            - l1_zeta * alpha_2;
+            - z_shifted_zeta * permutation * alpha
+            - l1_zeta * alpha_2
+            + lookup_zeta
+            + memory_zeta;
 
         //println!("{} {}", lhs, rhs);
 
@@ -166,6 +259,8 @@ impl<F: Field> AHPForPLONK<F> {
 fn get_eval<F: Field>(evaluations: &Evaluations<F, F>, label: &str, point: &F) -> Result<F, Error> {
     let eval = evaluations
         .get(&(label.to_string(), *point))
-        .ok_or_else(|| Error::MissingEvaluation(label.to_string()))?;
+        .ok_or_else(|| Error::MissingEvaluation {
+            label: label.to_string(),
+        })?;
     Ok(*eval)
 }
@@ -0,0 +1,350 @@
+//! Batch verification of many KZG PLONK proofs with one pairing check.
+//!
+//! The verifier of a proof under `marlin_pc::MarlinKZG10` ends with the
+//! pairing check of a [`KzgClaim`], `e(lhs, H) = e(rhs, beta H)`.
+//! `Plonk::kzg_claim` derives it from the proof and its public inputs,
+//! replaying the transcript of the proof and combining its openings as
+//! `check_combinations` does, without the pairings. `Plonk::batch_verify`
+//! derives the claims of `N` proofs, of the same circuit or not, absorbs
+//! them into a transcript of its own and checks their combination with the
+//! powers of a challenge `r` drawn from it: two pairings for the batch
+//! instead of two per proof, and an MSM of `2N` points of G1.
+//!
+//! It is not an aggregate of the proofs: the verifier reads every proof and
+//! replays its transcript. Unlike the Groth16 proofs of SnarkPack ([GMN21]),
+//! the claims of a PLONK proof depend on the challenges of its own
+//! transcript, so a commitment to the claims smaller than the proofs would
+//! not be bound to them.
+//!
+//! [GMN21]: https://eprint.iacr.org/2021/529
+
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{to_bytes, One, PrimeField, UniformRand, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::{
+    kzg10,
+    marlin_pc::{self, MarlinKZG10},
+    Error as PCError, LCTerm,
+};
+use ark_serialize::*;
+use ark_std::{
+    collections::{BTreeMap, BTreeSet},
+    string::String,
+    vec::Vec,
+};
+use digest::Digest;
+
+use crate::ahp::AHPForPLONK;
+use crate::data_structures::{Proof, VerifierKey};
+use crate::error::Error;
+use crate::pcs::LabeledCommitment;
+use crate::piop::{Compiler, Openings};
+use crate::rng::FiatShamirRng;
+use crate::shplonk::VerifierKey as ShplonkVerifierKey;
+use crate::Plonk;
+
+/// The domain separator of the transcript of a batch.
+const PROTOCOL_NAME: &[u8] = b"PLONK-BATCH";
+
+type Kzg<E> = MarlinKZG10<E, DensePolynomial<<E as PairingEngine>::Fr>>;
+
+/// The pairing check `e(lhs, H) = e(rhs, beta H)` of a KZG opening.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KzgClaim<E: PairingEngine> {
+    pub lhs: E::G1Affine,
+    pub rhs: E::G1Affine,
+}
+
+impl<E: PairingEngine> KzgClaim<E> {
+    /// Whether the claim holds under the KZG key `vk`.
+    pub fn holds(&self, vk: &ShplonkVerifierKey<E>) -> bool {
+        self.holds_for(vk.h, vk.beta_h)
+    }
+
+    fn holds_for(&self, h: E::G2Affine, beta_h: E::G2Affine) -> bool {
+        E::product_of_pairings(&[
+            (self.lhs.into(), h.into()),
+            ((-self.rhs).into(), beta_h.into()),
+        ])
+        .is_one()
+    }
+}
+
+impl<E: PairingEngine, D: Digest> Plonk<E::Fr, D, Kzg<E>> {
+    /// The pairing check `verify` ends with, or `None` if the proof fails
+    /// before it.
+    pub fn kzg_claim(
+        vk: &VerifierKey<E::Fr, Kzg<E>>,
+        public_inputs: &[E::Fr],
+        proof: &Proof<E::Fr, Kzg<E>>,
+    ) -> Result<Option<KzgClaim<E>>, Error<PCError>> {
+        if vk.info.committed_inputs {
+            return Err(Error::InputModeMismatch);
+        }
+        if proof.circuit_hash != vk.circuit_hash {
+            return Err(Error::CircuitMismatch);
+        }
+        let mut fs_rng = Self::transcript(vk, &to_bytes![public_inputs].unwrap());
+        let openings = Compiler::<E::Fr, D, Kzg<E>>::openings::<AHPForPLONK<E::Fr>>(
+            &vk.info,
+            Self::key_commitments(vk, None),
+            public_inputs,
+            proof,
+            &mut fs_rng,
+            &mut || {},
+        )?;
+        match openings {
+            Some(openings) => combine(&vk.rk, openings, &proof.pc_proof.proof, &mut fs_rng)
+                .map_err(Error::from_pc_err),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether every proof verifies under its key and public inputs, by the
+    /// claims of all of them in one pairing check. The keys must come from
+    /// the same universal setup.
+    #[allow(clippy::type_complexity)]
+    pub fn batch_verify(
+        proofs: &[(&VerifierKey<E::Fr, Kzg<E>>, &[E::Fr], &Proof<E::Fr, Kzg<E>>)],
+    ) -> Result<bool, Error<PCError>> {
+        let rk = match proofs.first() {
+            Some((vk, _, _)) => &vk.rk.vk,
+            None => return Ok(true),
+        };
+        let mut fs_rng =
+            FiatShamirRng::<D>::from_seed(&to_bytes![PROTOCOL_NAME, proofs.len() as u64].unwrap());
+        let mut lhs = Vec::with_capacity(proofs.len());
+        let mut rhs = Vec::with_capacity(proofs.len());
+        for (vk, public_inputs, proof) in proofs {
+            if (vk.rk.vk.h, vk.rk.vk.beta_h) != (rk.h, rk.beta_h) {
+                return Err(Error::SetupMismatch);
+            }
+            match Self::kzg_claim(vk, public_inputs, proof)? {
+                Some(claim) => {
+                    fs_rng.absorb(&to_bytes![claim.lhs, claim.rhs].unwrap());
+                    lhs.push(claim.lhs);
+                    rhs.push(claim.rhs);
+                }
+                None => return Ok(false),
+            }
+        }
+
+        let r = E::Fr::rand(&mut fs_rng);
+        let mut powers = Vec::with_capacity(proofs.len());
+        let mut power = E::Fr::one();
+        for _ in 0..proofs.len() {
+            powers.push(power.into_repr());
+            power *= r;
+        }
+        let claim = KzgClaim::<E> {
+            lhs: VariableBaseMSM::multi_scalar_mul(&lhs, &powers).into_affine(),
+            rhs: VariableBaseMSM::multi_scalar_mul(&rhs, &powers).into_affine(),
+        };
+        Ok(claim.holds_for(rk.h, rk.beta_h))
+    }
+}
+
+/// The claim of `kzg10::KZG10::batch_check` on the `openings` of a proof,
+/// with `proofs` the KZG proofs at each of their points and the randomizers
+/// drawn from `fs_rng`, as `MarlinKZG10::check_combinations` derives it, or
+/// `None` if it would fail before the pairings.
+fn combine<E: PairingEngine, D: Digest>(
+    rk: &marlin_pc::VerifierKey<E>,
+    openings: Openings<E::Fr, marlin_pc::Commitment<E>>,
+    proofs: &[kzg10::Proof<E>],
+    fs_rng: &mut FiatShamirRng<D>,
+) -> Result<Option<KzgClaim<E>>, PCError> {
+    let Openings {
+        lcs,
+        commitments,
+        query_set,
+        mut evaluations,
+        epsilon,
+    } = openings;
+    let commitments: BTreeMap<_, _> = commitments
+        .iter()
+        .map(|c: &LabeledCommitment<_>| (c.label(), c.commitment()))
+        .collect();
+
+    // the commitment to each linear combination, whose constant term is
+    // taken off its evaluations.
+    let mut lc_comms = BTreeMap::new();
+    for lc in &lcs {
+        let mut comm = E::G1Projective::zero();
+        for (coeff, term) in lc.iter() {
+            match term {
+                LCTerm::One => {
+                    for ((label, _), value) in evaluations.iter_mut() {
+                        if label == lc.label() {
+                            *value -= coeff;
+                        }
+                    }
+                }
+                LCTerm::PolyLabel(label) => {
+                    let c = commitments
+                        .get(label)
+                        .ok_or_else(|| PCError::MissingPolynomial {
+                            label: label.clone(),
+                        })?;
+                    // PLONK bounds no degree.
+                    if c.shifted_comm.is_some() {
+                        return Ok(None);
+                    }
+                    comm += &c.comm.0.mul(*coeff);
+                }
+            }
+        }
+        lc_comms.insert(lc.label().clone(), comm);
+    }
+
+    // the combinations opened at each point, in the order of the proofs.
+    let mut points: BTreeMap<&String, (E::Fr, BTreeSet<&String>)> = BTreeMap::new();
+    for (label, (point_label, point)) in query_set.iter() {
+        points
+            .entry(point_label)
+            .or_insert((*point, BTreeSet::new()))
+            .1
+            .insert(label);
+    }
+    if points.len() != proofs.len() {
+        return Ok(None);
+    }
+
+    let mut lhs = E::G1Projective::zero();
+    let mut rhs = E::G1Projective::zero();
+    let mut g_multiplier = E::Fr::zero();
+    let mut gamma_g_multiplier = E::Fr::zero();
+    let mut randomizer = E::Fr::one();
+    for ((point, labels), proof) in points.values().zip(proofs) {
+        let mut comm = E::G1Projective::zero();
+        let mut value = E::Fr::zero();
+        let mut challenge = E::Fr::one();
+        for label in labels {
+            let lc_comm = lc_comms
+                .get(*label)
+                .ok_or_else(|| PCError::MissingPolynomial {
+                    label: (*label).clone(),
+                })?;
+            let v = evaluations
+                .get(&((*label).clone(), *point))
+                .ok_or_else(|| PCError::MissingEvaluation {
+                    label: (*label).clone(),
+                })?;
+            comm += &lc_comm.mul(challenge.into_repr());
+            value += &(*v * challenge);
+            challenge *= epsilon;
+        }
+
+        comm += &proof.w.mul(*point);
+        lhs += &comm.mul(randomizer.into_repr());
+        rhs += &proof.w.mul(randomizer);
+        g_multiplier += &(randomizer * value);
+        if let Some(random_v) = proof.random_v {
+            gamma_g_multiplier += &(randomizer * random_v);
+        }
+        randomizer = u128::rand(fs_rng).into();
+    }
+    lhs -= &rk.vk.g.mul(g_multiplier);
+    lhs -= &rk.vk.gamma_g.mul(gamma_g_multiplier);
+
+    Ok(Some(KzgClaim {
+        lhs: lhs.into_affine(),
+        rhs: rhs.into_affine(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+    use blake2::Blake2s;
+
+    use super::*;
+    use crate::tests::{circuit, ks};
+
+    type PlonkInst = Plonk<Fr, Blake2s, Kzg<Bls12_381>>;
+
+    #[test]
+    fn test_kzg_claim() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let rk = &vk.rk.vk;
+
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        let claim = PlonkInst::kzg_claim(&vk, cs.public_inputs(), &proof)?.unwrap();
+        assert!(claim.holds_for(rk.h, rk.beta_h));
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        // an opening proof off its point passes up to the pairings.
+        let mut proof = PlonkInst::prove(&pk, &cs, rng)?;
+        proof.pc_proof.proof[0].w = proof.pc_proof.proof[0].w + rk.g;
+        let claim = PlonkInst::kzg_claim(&vk, cs.public_inputs(), &proof)?.unwrap();
+        assert!(!claim.holds_for(rk.h, rk.beta_h));
+        assert!(!PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        let mut wrong = cs.public_inputs().to_vec();
+        wrong[0] += Fr::one();
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert_eq!(PlonkInst::kzg_claim(&vk, &wrong, &proof)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_verify() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+        let cs = circuit();
+        let mut other = circuit();
+        let var = other.alloc_and_assign(Fr::one());
+        other.constrain_to_constant(var, Fr::one(), Fr::zero());
+
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let (other_pk, other_vk) = PlonkInst::keygen(&srs, &other, &ks())?;
+        let proofs = [
+            PlonkInst::prove(&pk, &cs, rng)?,
+            PlonkInst::prove(&other_pk, &other, rng)?,
+            PlonkInst::prove(&pk, &cs, rng)?,
+        ];
+        let (inputs, other_inputs) = (cs.public_inputs(), other.public_inputs());
+        let batch = [
+            (&vk, inputs, &proofs[0]),
+            (&other_vk, other_inputs, &proofs[1]),
+            (&vk, inputs, &proofs[2]),
+        ];
+        assert!(PlonkInst::batch_verify(&batch)?);
+        assert!(PlonkInst::batch_verify(&[])?);
+
+        // one opening proof off its point fails the batch.
+        let mut bad = PlonkInst::prove(&pk, &cs, rng)?;
+        let g = vk.rk.vk.g;
+        bad.pc_proof.proof[1].w = bad.pc_proof.proof[1].w + g;
+        let mut with_bad = batch.to_vec();
+        with_bad[1] = (&vk, inputs, &bad);
+        assert!(!PlonkInst::batch_verify(&with_bad)?);
+
+        let mut wrong = inputs.to_vec();
+        wrong[0] += Fr::one();
+        let mut with_wrong = batch.to_vec();
+        with_wrong[2] = (&vk, &wrong, &proofs[2]);
+        assert!(!PlonkInst::batch_verify(&with_wrong)?);
+
+        let mut swapped = batch.to_vec();
+        swapped[0] = (&other_vk, inputs, &proofs[0]);
+        assert!(matches!(
+            PlonkInst::batch_verify(&swapped),
+            Err(Error::CircuitMismatch)
+        ));
+
+        let other_srs = PlonkInst::setup(16, rng)?;
+        let (_, other_setup_vk) = PlonkInst::keygen(&other_srs, &cs, &ks())?;
+        let mut mixed = batch.to_vec();
+        mixed[2] = (&other_setup_vk, inputs, &proofs[2]);
+        assert!(matches!(
+            PlonkInst::batch_verify(&mixed),
+            Err(Error::SetupMismatch)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! Snapshots of a prover between its rounds, so that a long proof can be
+//! resumed after its machine is preempted.
+
+use ark_ff::FftField as Field;
+use ark_serialize::*;
+use ark_std::vec::Vec;
+use digest::Digest;
+
+use crate::error::Error;
+use crate::pcs::PCS;
+
+/// The state of a prover after its first rounds: the oracles it sent, their
+/// commitments and commitment randomness, and the transcript. It holds the
+/// witness polynomials, so it is as secret as the witness.
+///
+/// A prover resumed from a checkpoint replays the transcript from the
+/// commitments and checks it against the recorded one, so a checkpoint of
+/// another key, circuit or public input is rejected.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct Checkpoint<F: Field, PC: PCS<F>> {
+    /// the commitments of each round done.
+    pub(crate) commitments: Vec<Vec<PC::Commitment>>,
+    /// the coefficients of the oracles, in the order of their labels.
+    pub(crate) oracles: Vec<Vec<F>>,
+    pub(crate) rands: Vec<PC::Randomness>,
+    /// the transcript seed after the commitments of the last round.
+    pub(crate) transcript: Vec<u8>,
+    /// the digest of the oracles and randomness.
+    pub(crate) digest: Vec<u8>,
+}
+
+impl<F: Field, PC: PCS<F>> Checkpoint<F, PC> {
+    /// The number of rounds done.
+    pub fn rounds(&self) -> usize {
+        self.commitments.len()
+    }
+
+    /// The digest under `D` of the oracles and randomness.
+    // only the prover, built with `std`, uses it.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn content_digest<D: Digest>(
+        oracles: &[Vec<F>],
+        rands: &[PC::Randomness],
+    ) -> Result<Vec<u8>, Error<PC::Error>> {
+        let mut bytes = Vec::new();
+        oracles.serialize(&mut bytes)?;
+        rands.serialize(&mut bytes)?;
+        Ok(D::digest(&bytes).to_vec())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: Field, PC: PCS<F>> Checkpoint<F, PC> {
+    /// Writes the checkpoint to `path`, through a temporary file, so that a
+    /// write interrupted by a preemption leaves the previous one in place.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), Error<PC::Error>> {
+        let path = path.as_ref();
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+
+        let mut bytes = Vec::new();
+        self.serialize(&mut bytes)?;
+        std::fs::write(&partial, &bytes)?;
+        std::fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, Error<PC::Error>> {
+        let bytes = std::fs::read(path)?;
+        Ok(Self::deserialize(&bytes[..])?)
+    }
+}
@@ -0,0 +1,130 @@
+//! The layout of a verification on CKB, shared by the builders of the
+//! transactions and the scripts verifying them.
+//!
+//! The verifier key is the data of a dep cell, in the versioned encoding,
+//! see `VerifierKey::to_versioned_bytes`. The proof and its public inputs
+//! are in one field of the `WitnessArgs` of the script, `output_type` for a
+//! type script run on the cells it creates, as a molecule table
+//! `ProofPayload { proof: Bytes, public_inputs: Bytes }` of their versioned
+//! encodings. `WitnessArgs` is the molecule table of `ckb-types`:
+//! `{ lock: BytesOpt, input_type: BytesOpt, output_type: BytesOpt }`.
+//!
+//! A molecule table is its size, the offsets of its fields from its start,
+//! and the fields, all sizes and offsets as little-endian `u32`. `Bytes` is
+//! its length and its bytes, and `BytesOpt` is empty when absent.
+
+use ark_ff::FftField as Field;
+use ark_serialize::SerializationError;
+use ark_std::{convert::TryInto, vec::Vec};
+
+use crate::data_structures::{Proof, VerifierKey};
+use crate::encoding::public_inputs_to_bytes;
+use crate::pcs::PCS;
+
+/// The field of `WitnessArgs` holding the proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WitnessField {
+    Lock,
+    InputType,
+    OutputType,
+}
+
+impl WitnessField {
+    fn index(self) -> usize {
+        match self {
+            WitnessField::Lock => 0,
+            WitnessField::InputType => 1,
+            WitnessField::OutputType => 2,
+        }
+    }
+}
+
+/// The data of the dep cell holding `vk`.
+pub fn vk_cell_data<F: Field, PC: PCS<F>>(
+    vk: &VerifierKey<F, PC>,
+) -> Result<Vec<u8>, SerializationError> {
+    vk.to_versioned_bytes()
+}
+
+/// The `WitnessArgs` with `proof` and `public_inputs` in `field`, and the
+/// other fields absent.
+pub fn proof_witness<F: Field, PC: PCS<F>>(
+    proof: &Proof<F, PC>,
+    public_inputs: &[F],
+    field: WitnessField,
+) -> Result<Vec<u8>, SerializationError> {
+    let payload = table(&[
+        &bytes(&proof.to_versioned_bytes()?)[..],
+        &bytes(&public_inputs_to_bytes(public_inputs)?)[..],
+    ]);
+    let mut fields = [Vec::new(), Vec::new(), Vec::new()];
+    fields[field.index()] = bytes(&payload);
+    Ok(table(&[&fields[0][..], &fields[1][..], &fields[2][..]]))
+}
+
+/// The versioned encodings of the proof and of the public inputs in `field`
+/// of the `WitnessArgs` `witness`, in place, see `Plonk::verify_from_slices`.
+pub fn parse_proof_witness(
+    witness: &[u8],
+    field: WitnessField,
+) -> Result<(&[u8], &[u8]), SerializationError> {
+    let payload = match parse_table(witness, 3)?[field.index()] {
+        [] => return Err(SerializationError::InvalidData),
+        bytes => parse_bytes(bytes)?,
+    };
+    let fields = parse_table(payload, 2)?;
+    Ok((parse_bytes(fields[0])?, parse_bytes(fields[1])?))
+}
+
+fn bytes(value: &[u8]) -> Vec<u8> {
+    let mut bytes = (value.len() as u32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(value);
+    bytes
+}
+
+fn table(fields: &[&[u8]]) -> Vec<u8> {
+    let header = 4 * (fields.len() + 1);
+    let size = header + fields.iter().map(|f| f.len()).sum::<usize>();
+    let mut table = Vec::with_capacity(size);
+    table.extend_from_slice(&(size as u32).to_le_bytes());
+    let mut offset = header;
+    for field in fields {
+        table.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += field.len();
+    }
+    for field in fields {
+        table.extend_from_slice(field);
+    }
+    table
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<usize, SerializationError> {
+    let word = bytes.get(at..at + 4).ok_or(SerializationError::NotEnoughSpace)?;
+    Ok(u32::from_le_bytes(word.try_into().unwrap()) as usize)
+}
+
+/// The fields of a table of exactly `n` fields filling `bytes`.
+fn parse_table(bytes: &[u8], n: usize) -> Result<Vec<&[u8]>, SerializationError> {
+    let mut start = 4 * (n + 1);
+    if read_u32(bytes, 0)? != bytes.len() || read_u32(bytes, 4)? != start {
+        return Err(SerializationError::InvalidData);
+    }
+    let mut fields = Vec::with_capacity(n);
+    for i in 0..n {
+        let end = if i + 1 < n { read_u32(bytes, 4 * (i + 2))? } else { bytes.len() };
+        if end < start || end > bytes.len() {
+            return Err(SerializationError::InvalidData);
+        }
+        fields.push(&bytes[start..end]);
+        start = end;
+    }
+    Ok(fields)
+}
+
+/// The content of `Bytes` filling `bytes`.
+fn parse_bytes(bytes: &[u8]) -> Result<&[u8], SerializationError> {
+    if read_u32(bytes, 0)? != bytes.len() - 4 {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(&bytes[4..])
+}
@@ -0,0 +1,136 @@
+use ark_std::format;
+
+use crate::composer::{Composer, Field, Variable};
+
+/// Where `Composer::append` put the variables of the appended composer.
+#[derive(Debug, Clone, Copy)]
+pub struct VariableMap {
+    null_var: Variable,
+    offset: usize,
+}
+
+impl VariableMap {
+    /// The variable that `var` of the appended composer became.
+    pub fn get(&self, var: Variable) -> Variable {
+        if var.0 == 0 {
+            self.null_var
+        } else {
+            Variable(var.0 + self.offset)
+        }
+    }
+}
+
+impl<F: Field> Composer<F> {
+    /// Appends the gates of `other` after those of `self`, with its own
+    /// variables and public inputs, so that sub-circuits built apart end up
+    /// in one proof. The returned map gives the variables of `other` in
+    /// `self`, e.g. to connect them with `assert_equal`.
+    ///
+    /// The tables and the memories of `other` are registered again, and the
+    /// namespaces and labels of `other` are put in the current one. If either
+    /// composer is counting, the result is counting. The memories of `other`
+    /// must be closed, as its variables are not those of `self`. Gates of
+    /// `other` on a wire column that `self` does not have, see `with_wires`,
+    /// are reported when composing.
+    pub fn append(&mut self, other: &Composer<F>) -> VariableMap {
+        assert!(
+            other.memories.iter().all(|memory| memory.closed),
+            "appended composer with an open memory"
+        );
+        let map = VariableMap {
+            null_var: self.null_var,
+            offset: self.permutation.num_vars() - 1,
+        };
+        if other.counting {
+            self.counting = true;
+            self.assignment.clear();
+        }
+
+        // the null variable of `other` is the one of `self`.
+        for i in 1..other.permutation.num_vars() {
+            let var = self.permutation.alloc();
+            debug_assert_eq!(var, map.get(Variable(i)));
+            if !self.counting {
+                self.assignment.insert(var, other.assignment[&Variable(i)]);
+            }
+        }
+
+        for i in 0..other.n {
+            self.permutation.insert_gate(
+                map.get(other.w_0[i]),
+                map.get(other.w_1[i]),
+                map.get(other.w_2[i]),
+                map.get(other.w_3[i]),
+                self.n + i,
+            );
+            if other.w_4[i] != other.null_var {
+                self.permutation
+                    .insert(map.get(other.w_4[i]), 4, self.n + i);
+            }
+        }
+        self.w_0.extend(other.w_0.iter().map(|&v| map.get(v)));
+        self.w_1.extend(other.w_1.iter().map(|&v| map.get(v)));
+        self.w_2.extend(other.w_2.iter().map(|&v| map.get(v)));
+        self.w_3.extend(other.w_3.iter().map(|&v| map.get(v)));
+        self.w_4.extend(other.w_4.iter().map(|&v| map.get(v)));
+        self.pi.extend_from_slice(&other.pi);
+
+        self.q_0.extend_from_slice(&other.q_0);
+        self.q_1.extend_from_slice(&other.q_1);
+        self.q_2.extend_from_slice(&other.q_2);
+        self.q_3.extend_from_slice(&other.q_3);
+        self.q_4.extend_from_slice(&other.q_4);
+        self.q_m.extend_from_slice(&other.q_m);
+        self.q_c.extend_from_slice(&other.q_c);
+        self.q_next.extend_from_slice(&other.q_next);
+        self.q_arith.extend_from_slice(&other.q_arith);
+        self.q_range.extend_from_slice(&other.q_range);
+        self.q_mimc.extend_from_slice(&other.q_mimc);
+        // the tables of `other` come after those of `self`.
+        let tables = F::from(self.tables.len() as u64);
+        let remap = |&k: &F| if k.is_zero() { k } else { k + tables };
+        self.q_lookup.extend(other.q_lookup.iter().map(remap));
+        self.tables.extend_from_slice(&other.tables);
+        self.q_logic.extend_from_slice(&other.q_logic);
+        self.q_curve.extend_from_slice(&other.q_curve);
+        self.q_curve_a.extend_from_slice(&other.q_curve_a);
+        self.q_curve_d.extend_from_slice(&other.q_curve_d);
+        // so do its memories, whose sorted rows hold `-k`.
+        let memories = F::from(self.memories.len() as u64);
+        let remap = |&k: &F| {
+            if k.is_zero() {
+                k
+            } else if (1..=other.memories.len() as u64).any(|j| F::from(j) == k) {
+                k + memories
+            } else {
+                k - memories
+            }
+        };
+        self.q_memory.extend(other.q_memory.iter().map(remap));
+        self.memories.extend(other.memories.iter().cloned());
+
+        let prefix = self.namespace_path();
+        self.namespaces.push((self.n, prefix.clone()));
+        for (row, path) in &other.namespaces {
+            let path = match (prefix.is_empty(), path.is_empty()) {
+                (true, _) => path.clone(),
+                (false, true) => prefix.clone(),
+                (false, false) => format!("{}/{}", prefix, path),
+            };
+            self.namespaces.push((self.n + row, path));
+        }
+
+        for (var, label) in &other.labels {
+            let label = if prefix.is_empty() {
+                label.clone()
+            } else {
+                format!("{}/{}", prefix, label)
+            };
+            self.labels.insert(map.get(*var), label);
+        }
+
+        self.n += other.n;
+        self.namespaces.push((self.n, prefix));
+        map
+    }
+}
@@ -1,7 +1,10 @@
+use ark_std::vec::Vec;
+
 use crate::composer::{Composer, Field, Variable};
 
 impl<F: Field> Composer<F> {
-    /// q_m * l * r + q_l * l + q_r * r + q_o * o (+ q_aux * aux) + q_c + pi == 0
+    /// q_m * l * r + q_l * l + q_r * r + q_o * o (+ q_aux * aux) + q_c + pi
+    /// + q_next * (aux of the next row) == 0
     #[allow(clippy::too_many_arguments)]
     fn create_poly_gate(
         &mut self,
@@ -12,6 +15,7 @@ impl<F: Field> Composer<F> {
         q_m: F,
         q_c: F,
         pi: F,
+        q_next: F,
     ) {
         let index = self.n;
 
@@ -26,22 +30,45 @@ impl<F: Field> Composer<F> {
         self.w_1.push(l.0);
         self.w_2.push(r.0);
         self.w_3.push(o.0);
+        self.w_4.push(self.null_var);
         self.pi.push(pi);
 
         self.q_0.push(aux.1);
         self.q_1.push(l.1);
         self.q_2.push(r.1);
         self.q_3.push(o.1);
+        self.q_4.push(F::zero());
         self.q_m.push(q_m);
         self.q_c.push(q_c);
+        self.q_next.push(q_next);
         self.q_arith.push(F::one());
         self.q_range.push(F::zero());
         self.q_mimc.push(F::zero());
         //self.q_mimc_c.push(F::zero());
+        self.q_lookup.push(F::zero());
+        self.q_logic.push(F::zero());
+        self.q_curve.push(F::zero());
+        self.q_curve_a.push(F::zero());
+        self.q_curve_d.push(F::zero());
+        self.q_memory.push(F::zero());
 
         self.n += 1;
     }
 
+    /// Adds `q_4 * w_4` to the gate created last, for the wide gates of a
+    /// five-wire composer. The null variable adds nothing, as `w_4` only
+    /// holds copies of the variables put in it.
+    fn set_wide(&mut self, wide: (Variable, F)) {
+        let (var, q_4) = wide;
+        if var == self.null_var {
+            return;
+        }
+        let row = self.n - 1;
+        self.permutation.insert(var, 4, row);
+        self.w_4[row] = var;
+        self.q_4[row] = q_4;
+    }
+
     /// var == q_c + pi
     pub fn constrain_to_constant(&mut self, var: Variable, value: F, pi: F) {
         self.create_poly_gate(
@@ -52,6 +79,7 @@ impl<F: Field> Composer<F> {
             F::zero(),
             -value,
             -pi,
+            F::zero(),
         );
     }
 
@@ -65,6 +93,7 @@ impl<F: Field> Composer<F> {
             F::zero(),
             F::zero(),
             F::zero(),
+            F::zero(),
         )
     }
 
@@ -78,7 +107,24 @@ impl<F: Field> Composer<F> {
         q_c: F,
         pi: F,
     ) {
-        self.create_poly_gate(l, r, (o, -F::one()), aux, F::zero(), q_c, pi)
+        self.create_poly_gate(l, r, (o, -F::one()), aux, F::zero(), q_c, pi, F::zero())
+    }
+
+    /// q_l * l + q_r * r - o (+ q_aux * aux) + q_wide * wide + q_c + pi == 0,
+    /// with `wide` on `w_4`, which `compose` only accepts with five wires.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_wide_add_gate(
+        &mut self,
+        l: (Variable, F),
+        r: (Variable, F),
+        o: Variable,
+        aux: Option<(Variable, F)>,
+        wide: (Variable, F),
+        q_c: F,
+        pi: F,
+    ) {
+        self.create_add_gate(l, r, o, aux, q_c, pi);
+        self.set_wide(wide);
     }
 
     /// q_m * l * r - o (+ q_aux * aux) + q_c + pi == 0
@@ -101,7 +147,118 @@ impl<F: Field> Composer<F> {
             q_m,
             q_c,
             pi,
+            F::zero(),
         )
     }
 
+    /// The sum of `c * v` for `(v, c)` in `terms`, plus `q_c`, as a new
+    /// variable. A row adds three terms to the partial sum in its `w_0` and
+    /// passes it on to `w_0` of the next row, so that `k` terms take about
+    /// `k / 3` gates instead of `k / 2` add gates. With five wires a row adds
+    /// four, the fourth on `w_4`, and with three, which have no `w_0`, it
+    /// takes an add gate per term.
+    pub fn create_linear_combination_gate(&mut self, terms: &[(Variable, F)], q_c: F) -> Variable {
+        if self.wires == 3 {
+            return self.create_narrow_linear_combination(terms, q_c);
+        }
+        let value = |var| self.assignment.get(&var).copied().unwrap_or_else(F::zero);
+        let values: Vec<F> = terms.iter().map(|(var, c)| *c * value(*var)).collect();
+        let null = (self.null_var, F::zero());
+        let term = |i: usize| terms.get(i).copied().unwrap_or(null);
+        let weighted = |i: usize| values.get(i).copied().unwrap_or_else(F::zero);
+        let width = if self.wires == 5 { 4 } else { 3 };
+
+        let mut aux = term(0);
+        let mut partial = weighted(0);
+        let mut i = 1;
+        while terms.len() > i + width - 1 {
+            partial += (i..i + width).map(weighted).sum::<F>();
+            let next = self.alloc_and_assign(partial);
+            self.create_poly_gate(
+                term(i),
+                term(i + 1),
+                term(i + 2),
+                Some(aux),
+                F::zero(),
+                F::zero(),
+                F::zero(),
+                -F::one(),
+            );
+            if width == 4 {
+                self.set_wide(term(i + 3));
+            }
+            aux = (next, F::one());
+            i += width;
+        }
+
+        let sum = partial + (i..i + width - 1).map(weighted).sum::<F>() + q_c;
+        let out = self.alloc_and_assign(sum);
+        self.create_poly_gate(
+            term(i),
+            term(i + 1),
+            (out, -F::one()),
+            Some(aux),
+            F::zero(),
+            q_c,
+            F::zero(),
+            F::zero(),
+        );
+        if width == 4 {
+            self.set_wide(term(i + 2));
+        }
+        out
+    }
+
+    /// `create_linear_combination_gate` on `w_1..w_3`: each row adds a term
+    /// to the partial sum of the row before, in `w_1`.
+    fn create_narrow_linear_combination(&mut self, terms: &[(Variable, F)], q_c: F) -> Variable {
+        let null = (self.null_var, F::zero());
+        let mut terms = terms.to_vec();
+        terms.resize(terms.len().max(2), null);
+
+        let value = |cs: &Self, var| cs.assignment.get(&var).copied().unwrap_or_else(F::zero);
+        let mut partial = terms[0].1 * value(self, terms[0].0);
+        let mut acc = terms[0];
+        for (i, &(var, c)) in terms.iter().enumerate().skip(1) {
+            let constant = if i + 1 == terms.len() { q_c } else { F::zero() };
+            partial += c * value(self, var) + constant;
+            let next = self.alloc_and_assign(partial);
+            self.create_add_gate(acc, (var, c), next, None, constant, F::zero());
+            acc = (next, F::one());
+        }
+        acc.0
+    }
+
+    /// var * (var - 1) == 0
+    pub fn boolean_gate(&mut self, var: Variable) {
+        self.create_mul_gate(var, var, var, None, F::one(), F::zero(), F::zero());
+    }
+
+    /// cond ? a : b, for a `cond` constrained by `boolean_gate`
+    pub fn select(&mut self, cond: Variable, a: Variable, b: Variable) -> Variable {
+        let value = |var| self.assignment.get(&var).copied().unwrap_or_else(F::zero);
+        let (cond_value, a_value, b_value) = (value(cond), value(a), value(b));
+
+        let diff = self.alloc_and_assign(a_value - b_value);
+        self.create_add_gate(
+            (a, F::one()),
+            (b, -F::one()),
+            diff,
+            None,
+            F::zero(),
+            F::zero(),
+        );
+        // cond * (a - b) + b == out
+        let out = self.alloc_and_assign(cond_value * (a_value - b_value) + b_value);
+        self.create_mul_gate(
+            cond,
+            diff,
+            out,
+            Some((b, F::one())),
+            F::one(),
+            F::zero(),
+            F::zero(),
+        );
+        out
+    }
 }
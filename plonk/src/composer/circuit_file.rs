@@ -0,0 +1,227 @@
+//! A file format for compiled circuits, so that a prover loads the shape of
+//! a circuit instead of building it again for every proof, and circuits can
+//! be distributed as artifacts.
+//!
+//! A file is `MAGIC` followed by the canonical encoding of the circuit: `ks`,
+//! one coset per wire column, and the circuit hash, the number of variables, the selectors of the gates
+//! in the order of `Selectors`, the variables on the wires `w_0..w_4`, the
+//! public input of each gate as built, the tables, the number of memories,
+//! and the namespaces and labels. Reading it back checks the circuit hash.
+
+use ark_serialize::*;
+use ark_std::{string::String, vec::Vec};
+use digest::Digest;
+
+use crate::composer::{CompiledCircuit, Composer, Field, MemoryState, Variable};
+
+/// The first bytes of a circuit file, with its version.
+pub const MAGIC: [u8; 8] = *b"PLKCIR\x00\x02";
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+struct CircuitFile<F: Field> {
+    ks: Vec<F>,
+    circuit_hash: Vec<u8>,
+    variables: u64,
+    selectors: Vec<Vec<F>>,
+    wires: Vec<Vec<u64>>,
+    public_inputs: Vec<F>,
+    tables: Vec<Vec<(F, F, F)>>,
+    memories: u64,
+    namespaces: Vec<(u64, String)>,
+    labels: Vec<(u64, String)>,
+}
+
+impl<F: Field> CompiledCircuit<F> {
+    /// Writes the circuit, whose memories must all be closed.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        let cs = &self.cs;
+        if cs.memories.iter().any(|memory| !memory.closed) {
+            return Err(SerializationError::InvalidData);
+        }
+        let variables =
+            |wires: &Vec<Variable>| -> Vec<u64> { wires.iter().map(|var| var.0 as u64).collect() };
+        let mut labels: Vec<_> = cs
+            .labels
+            .iter()
+            .map(|(var, label)| (var.0 as u64, label.clone()))
+            .collect();
+        labels.sort();
+
+        let file = CircuitFile {
+            ks: self.ks.to_vec(),
+            circuit_hash: self.circuit_hash.clone(),
+            variables: cs.permutation.num_vars() as u64,
+            selectors: cs.gate_selectors().iter().map(|s| s.to_vec()).collect(),
+            wires: [&cs.w_0, &cs.w_1, &cs.w_2, &cs.w_3, &cs.w_4]
+                .iter()
+                .map(|wires| variables(wires))
+                .collect(),
+            public_inputs: cs.pi.clone(),
+            tables: cs
+                .tables
+                .iter()
+                .map(|rows| rows.iter().map(|row| (row[0], row[1], row[2])).collect())
+                .collect(),
+            memories: cs.memories.len() as u64,
+            namespaces: cs
+                .namespaces
+                .iter()
+                .map(|(row, path)| (*row as u64, path.clone()))
+                .collect(),
+            labels,
+        };
+        writer.write_all(&MAGIC)?;
+        file.serialize(&mut writer)
+    }
+
+    /// Reads a circuit written by `write`, rejecting one whose shape is
+    /// malformed or does not match its circuit hash under `D`.
+    pub fn read<D: Digest, R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(SerializationError::InvalidData);
+        }
+        let file = CircuitFile::<F>::deserialize(reader)?;
+
+        let n = file.public_inputs.len();
+        let variables = file.variables as usize;
+        let well_formed = (3..=5).contains(&file.ks.len())
+            && variables >= 1
+            && file.selectors.len() == 17
+            && file.selectors.iter().all(|selector| selector.len() == n)
+            && file.wires.len() == 5
+            && file
+                .wires
+                .iter()
+                .all(|wires| wires.len() == n && wires.iter().all(|var| *var < file.variables))
+            && file.namespaces.iter().all(|(row, _)| *row as usize <= n)
+            && file.labels.iter().all(|(var, _)| *var < file.variables);
+        if !well_formed {
+            return Err(SerializationError::InvalidData);
+        }
+
+        let mut cs = Composer::new_counting()
+            .with_wires(file.ks.len())
+            .map_err(|_| SerializationError::InvalidData)?;
+        for _ in 1..variables {
+            cs.permutation.alloc();
+        }
+        let wires: Vec<Vec<_>> = file
+            .wires
+            .iter()
+            .map(|wires| wires.iter().map(|var| Variable(*var as usize)).collect())
+            .collect();
+        for i in 0..n {
+            cs.permutation
+                .insert_gate(wires[0][i], wires[1][i], wires[2][i], wires[3][i], i);
+            if wires[4][i] != cs.null_var {
+                cs.permutation.insert(wires[4][i], 4, i);
+            }
+        }
+        let mut wires = wires.into_iter();
+        cs.w_0 = wires.next().unwrap_or_default();
+        cs.w_1 = wires.next().unwrap_or_default();
+        cs.w_2 = wires.next().unwrap_or_default();
+        cs.w_3 = wires.next().unwrap_or_default();
+        cs.w_4 = wires.next().unwrap_or_default();
+        for (selector, values) in cs.gate_selectors_mut().iter_mut().zip(file.selectors) {
+            **selector = values;
+        }
+        cs.pi = file.public_inputs;
+        cs.n = n;
+        cs.tables = file
+            .tables
+            .into_iter()
+            .map(|rows| rows.into_iter().map(|(a, b, c)| [a, b, c]).collect())
+            .collect();
+        let zero = cs.null_var;
+        cs.memories = (0..file.memories)
+            .map(|_| MemoryState::closed(zero))
+            .collect();
+        cs.namespaces = file
+            .namespaces
+            .into_iter()
+            .map(|(row, path)| (row as usize, path))
+            .collect();
+        cs.labels = file
+            .labels
+            .into_iter()
+            .map(|(var, label)| (Variable(var as usize), label))
+            .collect();
+
+        let ks = file.ks;
+        let circuit_hash = cs
+            .circuit_hash::<D>(&ks)
+            .map_err(|_| SerializationError::InvalidData)?;
+        if circuit_hash != file.circuit_hash {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(CompiledCircuit {
+            cs,
+            ks,
+            circuit_hash,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, Zero};
+    use blake2::Blake2s;
+
+    use crate::composer::mock::MockProver;
+    use crate::composer::CircuitBuilder;
+    use crate::tests::ks;
+
+    use super::*;
+
+    fn circuit(cs: &mut Composer<Fr>, x: u64) {
+        let xor = cs.add_xor_table(2);
+        cs.push_namespace("xor");
+        let a = cs.alloc_and_assign(Fr::from(x));
+        cs.label(a, "a");
+        let b = cs.alloc_and_assign(Fr::from(3u64));
+        let c = cs.lookup(xor, a, b);
+        cs.pop_namespace();
+
+        let zero = cs.alloc_and_assign(Fr::zero());
+        let ram = cs.add_memory(&[zero, zero]);
+        let one = cs.alloc_and_assign(Fr::one());
+        cs.memory_write(ram, one, c);
+        let d = cs.memory_read(ram, one);
+        cs.constrain_to_constant(d, Fr::zero(), Fr::from(x ^ 3));
+        cs.close_memory(ram).unwrap();
+    }
+
+    #[test]
+    fn circuit_file() {
+        let mut builder = CircuitBuilder::new();
+        circuit(&mut builder, 0);
+        let compiled = builder.compile::<Blake2s>(&ks()).unwrap();
+
+        let mut bytes = Vec::new();
+        compiled.write(&mut bytes).unwrap();
+        assert_eq!(bytes[..8], MAGIC);
+        let read = CompiledCircuit::<Fr>::read::<Blake2s, _>(&bytes[..]).unwrap();
+        assert_eq!(read.circuit_hash(), compiled.circuit_hash());
+        assert_eq!(read.circuit().shape_mismatch(compiled.circuit()), None);
+        assert_eq!(read.circuit().label_of(Variable(1)), Some("xor/a"));
+
+        // the witness is assigned to the circuit read back, without
+        // building it again.
+        let mut cs = Composer::new();
+        circuit(&mut cs, 1);
+        let witness = cs.export_witness::<Blake2s>(&ks()).unwrap();
+        let mut loaded = read.into_circuit();
+        loaded.import_witness::<Blake2s>(&witness, &ks()).unwrap();
+        assert_eq!(MockProver::run(&loaded, loaded.public_inputs()), Ok(()));
+
+        // `ks[0]`, after the magic and the length of `ks`.
+        bytes[16] ^= 1;
+        assert!(CompiledCircuit::<Fr>::read::<Blake2s, _>(&bytes[..]).is_err());
+        bytes[0] ^= 1;
+        assert!(CompiledCircuit::<Fr>::read::<Blake2s, _>(&bytes[..]).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! A circuit in two phases, as clinkv2 runs a circuit against a
+//! `VerifyAssignment` and a `ProveAssignment`: its shape, built once without
+//! values by a `CircuitBuilder` into a `CompiledCircuit`, from which the keys
+//! are generated, and its witness, assigned by a `WitnessFiller` for each
+//! proof by running the same circuit code with values.
+
+use ark_std::{
+    ops::{Deref, DerefMut},
+    vec::Vec,
+};
+use digest::Digest;
+
+use crate::composer::{Composer, Error, Field};
+
+/// Builds the shape of a circuit on a counting composer, see
+/// `Composer::new_counting`. The values given to it are dropped, so any
+/// value, e.g. zero, builds the same circuit.
+#[derive(Debug)]
+pub struct CircuitBuilder<F: Field> {
+    cs: Composer<F>,
+}
+
+/// The shape of a circuit, with no values: its gates, wiring and tables,
+/// and its circuit hash under `ks`.
+#[derive(Debug)]
+pub struct CompiledCircuit<F: Field> {
+    pub(crate) cs: Composer<F>,
+    pub(crate) ks: Vec<F>,
+    pub(crate) circuit_hash: Vec<u8>,
+}
+
+/// Assigns the witness of a `CompiledCircuit`, by building the circuit
+/// again on a composer that keeps its values.
+#[derive(Debug)]
+pub struct WitnessFiller<'a, F: Field> {
+    circuit: &'a CompiledCircuit<F>,
+    cs: Composer<F>,
+}
+
+impl<F: Field> CircuitBuilder<F> {
+    pub fn new() -> Self {
+        CircuitBuilder {
+            cs: Composer::new_counting(),
+        }
+    }
+
+    /// see `Composer::with_wires`.
+    pub fn with_wires(mut self, wires: usize) -> Result<Self, Error> {
+        self.cs = self.cs.with_wires(wires)?;
+        Ok(self)
+    }
+
+    /// The shape built so far, with its circuit hash under `ks`.
+    pub fn compile<D: Digest>(self, ks: &[F]) -> Result<CompiledCircuit<F>, Error> {
+        let circuit_hash = self.cs.circuit_hash::<D>(ks)?;
+        Ok(CompiledCircuit {
+            cs: self.cs,
+            ks: ks.to_vec(),
+            circuit_hash,
+        })
+    }
+}
+
+impl<F: Field> Default for CircuitBuilder<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field> Deref for CircuitBuilder<F> {
+    type Target = Composer<F>;
+
+    fn deref(&self) -> &Composer<F> {
+        &self.cs
+    }
+}
+
+impl<F: Field> DerefMut for CircuitBuilder<F> {
+    fn deref_mut(&mut self) -> &mut Composer<F> {
+        &mut self.cs
+    }
+}
+
+impl<F: Field> CompiledCircuit<F> {
+    /// The circuit as a counting composer, to generate its keys with
+    /// `Plonk::keygen` under `ks`, or to size it.
+    pub fn circuit(&self) -> &Composer<F> {
+        &self.cs
+    }
+
+    pub fn ks(&self) -> &[F] {
+        &self.ks
+    }
+
+    /// see `Composer::circuit_hash`.
+    pub fn circuit_hash(&self) -> &[u8] {
+        &self.circuit_hash
+    }
+
+    /// The circuit as a counting composer, e.g. to assign a `WitnessFile`
+    /// to with `Composer::import_witness` and prove it without building it
+    /// again.
+    pub fn into_circuit(self) -> Composer<F> {
+        self.cs
+    }
+
+    /// A filler for the witness of this circuit.
+    pub fn filler(&self) -> WitnessFiller<'_, F> {
+        let mut cs = Composer::new();
+        cs.wires = self.cs.wires;
+        WitnessFiller { circuit: self, cs }
+    }
+}
+
+impl<'a, F: Field> WitnessFiller<'a, F> {
+    /// The assigned circuit, to prove, once it has the shape of the compiled
+    /// one. The shape is compared gate by gate, which is cheaper than the
+    /// circuit hash that `Plonk::prove` checks again.
+    pub fn finish(self) -> Result<Composer<F>, Error> {
+        if let Some(check) = self.circuit.cs.shape_mismatch(&self.cs) {
+            return Err(Error::WitnessMismatch { check });
+        }
+        Ok(self.cs)
+    }
+}
+
+impl<'a, F: Field> Deref for WitnessFiller<'a, F> {
+    type Target = Composer<F>;
+
+    fn deref(&self) -> &Composer<F> {
+        &self.cs
+    }
+}
+
+impl<'a, F: Field> DerefMut for WitnessFiller<'a, F> {
+    fn deref_mut(&mut self) -> &mut Composer<F> {
+        &mut self.cs
+    }
+}
+
+impl<F: Field> Composer<F> {
+    /// The first difference between the shapes of two composers, i.e.
+    /// anything but their values and public inputs.
+    pub(crate) fn shape_mismatch(&self, other: &Self) -> Option<&'static str> {
+        if self.n != other.n {
+            return Some("gates");
+        }
+        if self.permutation.num_vars() != other.permutation.num_vars() {
+            return Some("variables");
+        }
+        if self.tables != other.tables {
+            return Some("tables");
+        }
+        if self.wires != other.wires
+            || [&self.w_0, &self.w_1, &self.w_2, &self.w_3, &self.w_4]
+                != [&other.w_0, &other.w_1, &other.w_2, &other.w_3, &other.w_4]
+        {
+            return Some("wires");
+        }
+        if self.gate_selectors() != other.gate_selectors() {
+            return Some("selectors");
+        }
+        None
+    }
+
+    /// The selectors of the gates, in the order of `Selectors`.
+    pub(crate) fn gate_selectors(&self) -> [&Vec<F>; 17] {
+        [
+            &self.q_0,
+            &self.q_1,
+            &self.q_2,
+            &self.q_3,
+            &self.q_4,
+            &self.q_m,
+            &self.q_c,
+            &self.q_next,
+            &self.q_arith,
+            &self.q_range,
+            &self.q_mimc,
+            &self.q_lookup,
+            &self.q_logic,
+            &self.q_curve,
+            &self.q_curve_a,
+            &self.q_curve_d,
+            &self.q_memory,
+        ]
+    }
+
+    pub(crate) fn gate_selectors_mut(&mut self) -> [&mut Vec<F>; 17] {
+        [
+            &mut self.q_0,
+            &mut self.q_1,
+            &mut self.q_2,
+            &mut self.q_3,
+            &mut self.q_4,
+            &mut self.q_m,
+            &mut self.q_c,
+            &mut self.q_next,
+            &mut self.q_arith,
+            &mut self.q_range,
+            &mut self.q_mimc,
+            &mut self.q_lookup,
+            &mut self.q_logic,
+            &mut self.q_curve,
+            &mut self.q_curve_a,
+            &mut self.q_curve_d,
+            &mut self.q_memory,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, Zero};
+    use blake2::Blake2s;
+
+    use crate::composer::mock::MockProver;
+    use crate::tests::ks;
+
+    use super::*;
+
+    /// `x * x + x == y` for a public `y`.
+    fn circuit(cs: &mut Composer<Fr>, x: Fr) {
+        let var_x = cs.alloc_and_assign(x);
+        let var_xx = cs.alloc_and_assign(x * x);
+        let var_y = cs.alloc_and_assign(x * x + x);
+        cs.create_mul_gate(
+            var_x,
+            var_x,
+            var_xx,
+            None,
+            Fr::one(),
+            Fr::zero(),
+            Fr::zero(),
+        );
+        cs.create_add_gate(
+            (var_xx, Fr::one()),
+            (var_x, Fr::one()),
+            var_y,
+            None,
+            Fr::zero(),
+            Fr::zero(),
+        );
+        cs.constrain_to_constant(var_y, Fr::zero(), x * x + x);
+    }
+
+    #[test]
+    fn two_phases() {
+        let mut builder = CircuitBuilder::new();
+        circuit(&mut builder, Fr::zero());
+        let compiled = builder.compile::<Blake2s>(&ks()).unwrap();
+
+        let mut filler = compiled.filler();
+        circuit(&mut filler, Fr::from(3u64));
+        let cs = filler.finish().unwrap();
+        assert_eq!(
+            cs.circuit_hash::<Blake2s>(&ks()).unwrap(),
+            compiled.circuit_hash()
+        );
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        let mut filler = compiled.filler();
+        circuit(&mut filler, Fr::from(3u64));
+        let null = filler.null_var;
+        filler.boolean_gate(null);
+        assert!(matches!(
+            filler.finish(),
+            Err(Error::WitnessMismatch { check: "gates" })
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+//! Points of a twisted Edwards curve over the field of the circuit, e.g.
+//! Jubjub over the scalar field of BLS12-381.
+//!
+//! An addition takes the row of `q_curve`, holding both points, and a row
+//! holding the sum with the products `x_1 y_2` and `y_1 x_2`. The addition
+//! law is complete when `a` is a square and `d` is not, so the points may be
+//! equal or the identity.
+
+use ark_ec::{twisted_edwards_extended::GroupAffine, TEModelParameters};
+use ark_ff::Zero;
+
+use crate::composer::{Composer, Field, Variable};
+use crate::utils::witness_inverse;
+
+/// A point of a twisted Edwards curve, as the variables of its coordinates.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Point {
+    x: Variable,
+    y: Variable,
+}
+
+impl Point {
+    pub fn x(&self) -> Variable {
+        self.x
+    }
+
+    pub fn y(&self) -> Variable {
+        self.y
+    }
+}
+
+impl<F: Field> Composer<F> {
+    /// Allocates `point`, constrained to be on the curve of `P`.
+    pub fn alloc_point<P: TEModelParameters<BaseField = F>>(
+        &mut self,
+        point: &GroupAffine<P>,
+    ) -> Point {
+        let x = self.alloc_and_assign(point.x);
+        let y = self.alloc_and_assign(point.y);
+        self.point_on_curve::<P>(x, y)
+    }
+
+    /// `point` as a constant of the circuit.
+    pub fn constant_point<P: TEModelParameters<BaseField = F>>(
+        &mut self,
+        point: &GroupAffine<P>,
+    ) -> Point {
+        let x = self.alloc_and_assign(point.x);
+        let y = self.alloc_and_assign(point.y);
+        self.constrain_to_constant(x, point.x, F::zero());
+        self.constrain_to_constant(y, point.y, F::zero());
+        Point { x, y }
+    }
+
+    /// The point `(x, y)`, constrained to be on the curve of `P`:
+    /// `a x^2 + y^2 == 1 + d x^2 y^2`.
+    pub fn point_on_curve<P: TEModelParameters<BaseField = F>>(
+        &mut self,
+        x: Variable,
+        y: Variable,
+    ) -> Point {
+        let value = |var| self.assignment.get(&var).copied().unwrap_or_else(F::zero);
+        let (x_value, y_value) = (value(x), value(y));
+
+        let xx = self.alloc_and_assign(x_value.square());
+        self.create_mul_gate(x, x, xx, None, F::one(), F::zero(), F::zero());
+        let yy = self.alloc_and_assign(y_value.square());
+        self.create_mul_gate(y, y, yy, None, F::one(), F::zero(), F::zero());
+        // d xx yy - yy - a xx + 1 == 0
+        self.create_mul_gate(
+            xx,
+            yy,
+            yy,
+            Some((xx, -P::COEFF_A)),
+            P::COEFF_D,
+            F::one(),
+            F::zero(),
+        );
+
+        Point { x, y }
+    }
+
+    /// a + b, on the curve of `P`
+    pub fn add_points<P: TEModelParameters<BaseField = F>>(&mut self, a: Point, b: Point) -> Point {
+        let value = |var| self.assignment.get(&var).copied().unwrap_or_else(F::zero);
+        let (x_1, y_1, x_2, y_2) = (value(a.x), value(a.y), value(b.x), value(b.y));
+
+        let (p, r) = (x_1 * y_2, y_1 * x_2);
+        let dpr = P::COEFF_D * p * r;
+        // the denominators only vanish off the curve, where the gate fails.
+        let x_3 = (p + r) * witness_inverse(&(F::one() + dpr));
+        let y_3 = (y_1 * y_2 - P::COEFF_A * x_1 * x_2) * witness_inverse(&(F::one() - dpr));
+
+        let sum = Point {
+            x: self.alloc_and_assign(x_3),
+            y: self.alloc_and_assign(y_3),
+        };
+        let p = self.alloc_and_assign(p);
+        let r = self.alloc_and_assign(r);
+
+        self.create_curve_gate_component([a.x, a.y, b.x, b.y], (F::one(), P::COEFF_A, P::COEFF_D));
+        self.create_curve_gate_component([sum.x, sum.y, p, r], (F::zero(), F::zero(), F::zero()));
+
+        sum
+    }
+
+    /// -point, i.e. `(-x, y)`, on any twisted Edwards curve
+    pub fn neg_point(&mut self, point: Point) -> Point {
+        let x = self.assignment.get(&point.x).copied().unwrap_or_else(F::zero);
+        let neg_x = self.alloc_and_assign(-x);
+        self.create_add_gate(
+            (point.x, -F::one()),
+            (point.x, F::zero()),
+            neg_x,
+            None,
+            F::zero(),
+            F::zero(),
+        );
+        Point {
+            x: neg_x,
+            y: point.y,
+        }
+    }
+
+    /// cond ? a : b, for a `cond` constrained by `boolean_gate`
+    pub fn select_point(&mut self, cond: Variable, a: Point, b: Point) -> Point {
+        Point {
+            x: self.select(cond, a.x, b.x),
+            y: self.select(cond, a.y, b.y),
+        }
+    }
+
+    /// The sum of `2^i point` over the `bits`, least significant first and
+    /// constrained by `boolean_gate`, by double-and-add from the identity.
+    pub fn mul_point<P: TEModelParameters<BaseField = F>>(
+        &mut self,
+        point: Point,
+        bits: &[Variable],
+    ) -> Point {
+        let mut acc = self.constant_point(&GroupAffine::<P>::zero());
+        let mut base = point;
+        for (i, bit) in bits.iter().enumerate() {
+            let sum = self.add_points::<P>(acc, base);
+            acc = self.select_point(*bit, sum, acc);
+            if i + 1 < bits.len() {
+                base = self.add_points::<P>(base, base);
+            }
+        }
+        acc
+    }
+
+    fn create_curve_gate_component(
+        &mut self,
+        w: [Variable; 4],
+        q: (F, F, F), // q_curve, q_curve_a, q_curve_d
+    ) {
+        let index = self.n;
+        self.permutation.insert_gate(w[0], w[1], w[2], w[3], index);
+
+        self.w_0.push(w[0]);
+        self.w_1.push(w[1]);
+        self.w_2.push(w[2]);
+        self.w_3.push(w[3]);
+        self.w_4.push(self.null_var);
+        self.pi.push(F::zero());
+
+        self.q_0.push(F::zero());
+        self.q_1.push(F::zero());
+        self.q_2.push(F::zero());
+        self.q_3.push(F::zero());
+        self.q_4.push(F::zero());
+        self.q_m.push(F::zero());
+        self.q_c.push(F::zero());
+        self.q_next.push(F::zero());
+        self.q_arith.push(F::zero());
+        self.q_range.push(F::zero());
+        self.q_mimc.push(F::zero());
+        self.q_lookup.push(F::zero());
+        self.q_logic.push(F::zero());
+        self.q_curve.push(q.0);
+        self.q_curve_a.push(q.1);
+        self.q_curve_d.push(q.2);
+        self.q_memory.push(F::zero());
+
+        self.n += 1;
+    }
+}
@@ -0,0 +1,122 @@
+//! Decomposition of a value into bits, or into chunks checked by a range
+//! table.
+//!
+//! The parts are constrained to sum back to the value with a linear
+//! combination gate and each is range checked, so that the value fits in
+//! `num_bits`. A bit takes a boolean gate, while a chunk of 8 or 16 bits takes a single
+//! lookup, which is cheaper for wide values when the table is registered
+//! anyway.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{format, vec::Vec};
+
+use crate::composer::{Composer, Error, Field, Table, Variable};
+
+impl<F: PrimeField + Field> Composer<F> {
+    /// The `num_bits` bits of `var`, least significant first, each
+    /// constrained by `boolean_gate` and summing to `var`, or an
+    /// `Error::OutOfRange` if `var` does not fit in them.
+    pub fn decompose(&mut self, var: Variable, num_bits: u32) -> Result<Vec<Variable>, Error> {
+        let bits = self.decompose_into(var, num_bits, 1)?;
+        for bit in bits.iter() {
+            self.boolean_gate(*bit);
+        }
+        Ok(bits)
+    }
+
+    /// The chunks of `chunk_bits` bits of `var` of `num_bits`, least
+    /// significant first, summing to `var` and each checked against
+    /// `table`, the table of `add_range_table(chunk_bits)`. A top chunk of
+    /// fewer bits is also checked shifted to the top of the table.
+    pub fn decompose_with_table(
+        &mut self,
+        var: Variable,
+        num_bits: u32,
+        table: Table,
+        chunk_bits: u32,
+    ) -> Result<Vec<Variable>, Error> {
+        assert!(chunk_bits > 0 && chunk_bits <= num_bits);
+        assert_eq!(
+            self.tables[table.0 - 1].len(),
+            1 << chunk_bits,
+            "not a range table of chunk_bits"
+        );
+
+        let chunks = self.decompose_into(var, num_bits, chunk_bits)?;
+        // the rows of a range table are `(x, 0, 0)`, which constrains `zero`.
+        let zero = self.alloc_and_assign(F::zero());
+        for chunk in chunks.iter() {
+            self.add_lookup_gate(table, *chunk, zero, zero);
+        }
+
+        // top < 2^rest iff top < 2^chunk_bits and top * 2^(chunk_bits - rest)
+        // < 2^chunk_bits
+        let rest = num_bits % chunk_bits;
+        if rest != 0 {
+            let top = chunks[chunks.len() - 1];
+            let shift = F::from(1u64 << (chunk_bits - rest));
+            let value = if self.counting {
+                F::zero()
+            } else {
+                self.assignment[&top] * shift
+            };
+            let shifted = self.alloc_and_assign(value);
+            self.create_add_gate(
+                (top, shift),
+                (top, F::zero()),
+                shifted,
+                None,
+                F::zero(),
+                F::zero(),
+            );
+            self.add_lookup_gate(table, shifted, zero, zero);
+        }
+
+        Ok(chunks)
+    }
+
+    /// The parts of `part_bits` bits of `var`, the top one of the remaining
+    /// bits, constrained to sum to `var` but not range checked, or an
+    /// `Error::OutOfRange` if `var` has more than `num_bits` bits. At the full
+    /// width of the field every value fits, but its bits are not unique.
+    fn decompose_into(
+        &mut self,
+        var: Variable,
+        num_bits: u32,
+        part_bits: u32,
+    ) -> Result<Vec<Variable>, Error> {
+        let (num_bits, part_bits) = (num_bits as usize, part_bits as usize);
+        assert!(num_bits > 0 && num_bits <= F::size_in_bits());
+
+        // a counting composer has no assignment, and the gates do not depend on it
+        let value: F = if self.counting {
+            F::zero()
+        } else {
+            self.assignment[&var]
+        };
+        let bits = value.into_repr();
+        let high_bits = (num_bits..F::size_in_bits()).fold(false, |acc, i| acc | bits.get_bit(i));
+        if high_bits {
+            return Err(Error::OutOfRange {
+                variable: format!("{}{}", self.variable_name(var), self.in_namespace()),
+                bits: num_bits as u32,
+            });
+        }
+
+        let mut coeff = F::one();
+        let shift = F::from(2u64).pow([part_bits as u64]);
+        let mut terms = Vec::with_capacity(num_bits.div_ceil(part_bits));
+        for start in (0..num_bits).step_by(part_bits) {
+            let part = (start..num_bits.min(start + part_bits))
+                .rev()
+                .fold(0u64, |acc, i| 2 * acc + bits.get_bit(i) as u64);
+            terms.push((self.alloc_and_assign(F::from(part)), coeff));
+            coeff *= shift;
+        }
+
+        let sum = self.create_linear_combination_gate(&terms, F::zero());
+        self.assert_equal(sum, var);
+
+        Ok(terms.into_iter().map(|(part, _)| part).collect())
+    }
+}
@@ -0,0 +1,262 @@
+//! Export a circuit as a Graphviz graph or as JSON, to audit small circuits.
+//!
+//! Every row is a gate with its types (the selectors `q_arith`, `q_range`,
+//! `q_mimc`, `q_lookup`, `q_logic`, `q_curve` and `q_memory` that are set on
+//! it), its namespace and the variables on its wires `w_0..w_3` (and `w_4`
+//! with five wires), with their labels, see `Composer::label`. Two wires holding the same variable are
+//! connected. A variable is unconstrained when none of its wires enters an
+//! equation: such a variable can hold any value without failing the proof.
+
+use ark_std::{fmt, format, string::String, vec, vec::Vec};
+
+use crate::composer::{Composer, Field, Variable};
+use crate::utils::Quoted;
+
+impl<F: Field> Composer<F> {
+    /// The gate types of `row`.
+    pub(crate) fn gate_types(&self, row: usize) -> Vec<&'static str> {
+        let mut types = Vec::new();
+        if !self.q_arith[row].is_zero() {
+            types.push("arithmetic");
+        }
+        if !self.q_range[row].is_zero() {
+            types.push("range");
+        }
+        if !self.q_mimc[row].is_zero() {
+            types.push("mimc");
+        }
+        if !self.q_lookup[row].is_zero() {
+            types.push("lookup");
+        }
+        if !self.q_logic[row].is_zero() {
+            types.push("logic");
+        }
+        if !self.q_curve[row].is_zero() {
+            types.push("curve");
+        }
+        if !self.q_memory[row].is_zero() {
+            types.push("memory");
+        }
+        types
+    }
+
+    /// Whether wire `w_{column}` of `row` enters an equation.
+    pub(crate) fn is_constrained(&self, row: usize, column: usize) -> bool {
+        let uses_all = |row: usize| {
+            !self.q_range[row].is_zero()
+                || !self.q_mimc[row].is_zero()
+                || !self.q_logic[row].is_zero()
+                || !self.q_curve[row].is_zero()
+        };
+        // range and mimc gates, and arithmetic gates with `q_next`, read
+        // `w_0` of the next row, logic gates `w_0..w_2` and curve gates all
+        // of its wires.
+        let reads_next = |row: usize| match column {
+            0 => uses_all(row) || !(self.q_arith[row] * self.q_next[row]).is_zero(),
+            1 | 2 => !self.q_logic[row].is_zero() || !self.q_curve[row].is_zero(),
+            3 => !self.q_curve[row].is_zero(),
+            _ => false,
+        };
+        if uses_all(row) || (row > 0 && reads_next(row - 1)) {
+            return true;
+        }
+        // lookup gates read `w_1..w_3`.
+        if (1..=3).contains(&column) && !self.q_lookup[row].is_zero() {
+            return true;
+        }
+        // memory rows are records of all their wires.
+        if !self.q_memory[row].is_zero() {
+            return true;
+        }
+        if self.q_arith[row].is_zero() {
+            return false;
+        }
+        match column {
+            0 => !self.q_0[row].is_zero(),
+            1 => !self.q_1[row].is_zero() || !self.q_m[row].is_zero(),
+            2 => !self.q_2[row].is_zero() || !self.q_m[row].is_zero(),
+            3 => !self.q_3[row].is_zero(),
+            _ => !self.q_4[row].is_zero(),
+        }
+    }
+
+    /// The variables on the wires of `row`, `w_4` only with five wires.
+    fn row_wires(&self, row: usize) -> Vec<Variable> {
+        let mut wires = vec![self.w_0[row], self.w_1[row], self.w_2[row], self.w_3[row]];
+        if self.wires == 5 {
+            wires.push(self.w_4[row]);
+        }
+        wires
+    }
+
+    /// The variables other than the null one, in allocation order, with
+    /// their wires as `(row, column)`.
+    fn variable_wires(&self) -> Vec<(Variable, Vec<(usize, usize)>)> {
+        let mut variables: Vec<_> = self
+            .permutation
+            .wires()
+            .filter(|(var, _)| **var != self.null_var)
+            .map(|(var, wires)| {
+                let wires = wires
+                    .iter()
+                    .map(|wire| (wire.row, wire.column))
+                    .collect();
+                (*var, wires)
+            })
+            .collect();
+        variables.sort_by_key(|(var, _)| *var);
+        variables
+    }
+
+    /// The variables whose wires, if any, enter no equation.
+    pub fn unconstrained(&self) -> Vec<Variable> {
+        self.variable_wires()
+            .into_iter()
+            .filter(|(_, wires)| !wires.iter().any(|&(i, c)| self.is_constrained(i, c)))
+            .map(|(var, _)| var)
+            .collect()
+    }
+
+    /// Writes the circuit as a Graphviz graph: a record per gate, clustered
+    /// by namespace, with an edge between consecutive wires of a variable,
+    /// and the unconstrained variables in red.
+    pub fn write_dot<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        writeln!(w, "graph circuit {{")?;
+        writeln!(w, "  node [shape=record];")?;
+
+        let mut cluster = None;
+        for row in 0..self.n {
+            let namespace = self.namespace_of(row);
+            if cluster != Some(namespace) {
+                if cluster.map_or(false, |c| !c.is_empty()) {
+                    writeln!(w, "  }}")?;
+                }
+                if !namespace.is_empty() {
+                    writeln!(w, "  subgraph \"cluster_{}\" {{", row)?;
+                    writeln!(w, "  label={};", Quoted(namespace))?;
+                }
+                cluster = Some(namespace);
+            }
+
+            let types = self.gate_types(row);
+            let wires = self.row_wires(row);
+            write!(w, "  g{} [label=\"{}: ", row, row)?;
+            if types.is_empty() {
+                write!(w, "none")?;
+            }
+            for (i, t) in types.iter().enumerate() {
+                write!(w, "{}{}", if i == 0 { "" } else { "+" }, t)?;
+            }
+            write!(w, "|{{")?;
+            for (column, var) in wires.iter().enumerate() {
+                if column > 0 {
+                    write!(w, "|")?;
+                }
+                write!(w, "<w{}> ", column)?;
+                if *var == self.null_var {
+                    write!(w, "-")?;
+                } else {
+                    write!(w, "v{}", var.0)?;
+                }
+            }
+            writeln!(w, "}}\"];")?;
+        }
+        if cluster.map_or(false, |c| !c.is_empty()) {
+            writeln!(w, "  }}")?;
+        }
+
+        for (var, wires) in self.variable_wires() {
+            let constrained = wires.iter().any(|&(i, c)| self.is_constrained(i, c));
+            if !constrained {
+                let label = format!("{} (unconstrained)", self.variable_name(var));
+                writeln!(
+                    w,
+                    "  v{} [shape=ellipse, color=red, label={}];",
+                    var.0,
+                    Quoted(&label)
+                )?;
+                for (row, column) in &wires {
+                    writeln!(w, "  v{} -- g{}:w{} [color=red];", var.0, row, column)?;
+                }
+                continue;
+            }
+            for pair in wires.windows(2) {
+                let ((a, c), (b, d)) = (pair[0], pair[1]);
+                writeln!(
+                    w,
+                    "  g{}:w{} -- g{}:w{} [label={}];",
+                    a,
+                    c,
+                    b,
+                    d,
+                    Quoted(&self.variable_name(var))
+                )?;
+            }
+        }
+        writeln!(w, "}}")
+    }
+
+    /// Writes the circuit as JSON: its `gates` with their `row`, `types`,
+    /// `namespace` and the variables on their `wires` (`null` for the null
+    /// variable), and its `variables` with their `id`, their `label` or
+    /// `null`, their `wires` as `[row, column]` and whether they are
+    /// `constrained`.
+    pub fn write_json<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "{{\"gates\":[")?;
+        for row in 0..self.n {
+            if row > 0 {
+                write!(w, ",")?;
+            }
+            write!(w, "{{\"row\":{},\"types\":[", row)?;
+            for (i, t) in self.gate_types(row).iter().enumerate() {
+                write!(w, "{}\"{}\"", if i == 0 { "" } else { "," }, t)?;
+            }
+            write!(
+                w,
+                "],\"namespace\":{},\"wires\":[",
+                Quoted(self.namespace_of(row))
+            )?;
+            let wires = self.row_wires(row);
+            for (column, var) in wires.iter().enumerate() {
+                let sep = if column == 0 { "" } else { "," };
+                if *var == self.null_var {
+                    write!(w, "{}null", sep)?;
+                } else {
+                    write!(w, "{}{}", sep, var.0)?;
+                }
+            }
+            write!(w, "]}}")?;
+        }
+
+        write!(w, "],\"variables\":[")?;
+        for (i, (var, wires)) in self.variable_wires().into_iter().enumerate() {
+            if i > 0 {
+                write!(w, ",")?;
+            }
+            write!(w, "{{\"id\":{},\"label\":", var.0)?;
+            match self.label_of(var) {
+                Some(label) => write!(w, "{}", Quoted(label))?,
+                None => write!(w, "null")?,
+            }
+            write!(w, ",\"wires\":[")?;
+            for (j, (row, column)) in wires.iter().enumerate() {
+                write!(w, "{}[{},{}]", if j == 0 { "" } else { "," }, row, column)?;
+            }
+            let constrained = wires.iter().any(|&(i, c)| self.is_constrained(i, c));
+            write!(w, "],\"constrained\":{}}}", constrained)?;
+        }
+        write!(w, "]}}")
+    }
+
+    /// The circuit as a Graphviz graph, see `write_dot`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot).map(|_| dot).unwrap_or_default()
+    }
+
+    /// The circuit as JSON, see `write_json`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json).map(|_| json).unwrap_or_default()
+    }
+}
@@ -0,0 +1,159 @@
+//! Verification of single openings of `Ipa` inside a circuit proven under
+//! KZG.
+//!
+//! An opening is proved locally with `Ipa` over the curve of `P`, a twisted
+//! Edwards curve over the field of the circuit, e.g. Jubjub in a circuit
+//! over the scalar field of BLS12-381, and `wrap_ipa_opening` checks it in a
+//! circuit proven by `Plonk` with `marlin_pc::MarlinKZG10`. The circuit
+//! takes `Ipa::full_check`, with the MSM over the generators that is linear
+//! in the size of the key, so its verifier takes a KZG verification instead
+//! of that MSM.
+//!
+//! The scalars of the check are derived from the transcript of the opening,
+//! in the scalar field of the curve, which is not native to the circuit.
+//! They are public inputs, with the points of the opening, and the verifier
+//! derives them with `Ipa::full_check`, in field operations only. Whole
+//! proofs of `Plonk` under `Ipa` are not wrapped: that would take their
+//! transcript and the rest of the verifier, over the scalar field of the
+//! curve, into the circuit as well.
+
+use ark_ec::{twisted_edwards_extended::GroupAffine, AffineCurve, TEModelParameters};
+use ark_ff::{to_bytes, PrimeField};
+use ark_std::{vec, vec::Vec};
+
+use crate::composer::{Composer, Error, Field, Point, Variable};
+use crate::ipa::{FullCheck, Proof, VerifierKey};
+
+/// The scalars of a `FullCheck` in the circuit, each less than the order
+/// of the curve.
+#[derive(Debug, Clone)]
+pub struct IpaScalars {
+    pub h: Variable,
+    pub l: Vec<Variable>,
+    pub r: Vec<Variable>,
+    pub generators: Vec<Variable>,
+}
+
+impl<F: PrimeField + Field> Composer<F> {
+    /// Allocates the scalars of `check`.
+    pub fn alloc_ipa_scalars<G: AffineCurve>(&mut self, check: &FullCheck<G>) -> IpaScalars {
+        let mut alloc = |s: &G::ScalarField| {
+            self.alloc_and_assign(F::from_le_bytes_mod_order(&to_bytes![s].unwrap()))
+        };
+        IpaScalars {
+            h: alloc(&check.h),
+            l: check.l.iter().map(&mut alloc).collect(),
+            r: check.r.iter().map(&mut alloc).collect(),
+            generators: check.generators.iter().map(&mut alloc).collect(),
+        }
+    }
+
+    /// Constrains the opening of `commitment` with the cross terms `l_vec`
+    /// and `r_vec` to pass its full check with `scalars` under `vk`, whose
+    /// generators are constants of the circuit. A scalar wider than the
+    /// scalar field of the curve is an `Error::OutOfRange`.
+    pub fn ipa_opening<P: TEModelParameters<BaseField = F>>(
+        &mut self,
+        vk: &VerifierKey<GroupAffine<P>>,
+        commitment: Point,
+        l_vec: &[Point],
+        r_vec: &[Point],
+        scalars: &IpaScalars,
+    ) -> Result<(), Error> {
+        let mut terms = vec![(self.constant_point(&vk.h), scalars.h)];
+        terms.extend(l_vec.iter().copied().zip(scalars.l.iter().copied()));
+        terms.extend(r_vec.iter().copied().zip(scalars.r.iter().copied()));
+        for (g, s) in vk.generators.iter().zip(&scalars.generators) {
+            let g = self.constant_point(g);
+            terms.push((g, *s));
+        }
+
+        let num_bits = P::ScalarField::size_in_bits() as u32;
+        let mut acc = commitment;
+        for (point, scalar) in terms {
+            let bits = self.decompose(scalar, num_bits)?;
+            let term = self.mul_point::<P>(point, &bits);
+            acc = self.add_points::<P>(acc, term);
+        }
+        // the sum is the identity, (0, 1).
+        self.constrain_to_constant(acc.x(), F::zero(), F::zero());
+        self.constrain_to_constant(acc.y(), F::one(), F::zero());
+        Ok(())
+    }
+
+    /// Allocates the opening `proof` with the scalars of its `check`, and
+    /// constrains it by `ipa_opening`. The combined commitment, the cross
+    /// terms of `proof` and the scalars are public inputs, in that order,
+    /// for the verifier to compare with those of `Ipa::full_check`.
+    pub fn wrap_ipa_opening<P: TEModelParameters<BaseField = F>>(
+        &mut self,
+        vk: &VerifierKey<GroupAffine<P>>,
+        proof: &Proof<GroupAffine<P>>,
+        check: &FullCheck<GroupAffine<P>>,
+    ) -> Result<(), Error> {
+        let commitment = self.alloc_point(&check.commitment);
+        let l_vec: Vec<_> = proof.l_vec.iter().map(|l| self.alloc_point(l)).collect();
+        let r_vec: Vec<_> = proof.r_vec.iter().map(|r| self.alloc_point(r)).collect();
+        let scalars = self.alloc_ipa_scalars(check);
+
+        let points = Some(&commitment).into_iter().chain(&l_vec).chain(&r_vec);
+        let public: Vec<_> = points
+            .flat_map(|p| vec![p.x(), p.y()])
+            .chain(Some(scalars.h))
+            .chain(scalars.l.iter().copied())
+            .chain(scalars.r.iter().copied())
+            .chain(scalars.generators.iter().copied())
+            .collect();
+        for var in public {
+            let value = self.assignment.get(&var).copied().unwrap_or_else(F::zero);
+            self.constrain_to_constant(var, F::zero(), value);
+        }
+
+        self.ipa_opening::<P>(vk, commitment, &l_vec, &r_vec, &scalars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsParameters, Fr as Scalar};
+    use ark_ff::{One, UniformRand};
+    use ark_poly::{univariate::DensePolynomial, Polynomial, UVPolynomial};
+    use ark_std::test_rng;
+    use blake2::Blake2s;
+
+    use crate::composer::mock::MockProver;
+    use crate::ipa::Ipa;
+    use crate::pcs::PCS;
+    use crate::tests::circuit;
+    use crate::utils::to_labeled;
+
+    type PC = Ipa<EdwardsAffine, Blake2s>;
+
+    #[test]
+    fn wrap_ipa_opening() {
+        let rng = &mut test_rng();
+        let pp = PC::setup(3, rng).unwrap();
+        let (ck, vk) = PC::trim(&pp, 3).unwrap();
+
+        let a = to_labeled("a", DensePolynomial::<Scalar>::rand(3, rng));
+        let (comms, rands) = PC::commit(&ck, Some(&a), None).unwrap();
+        let (point, challenge) = (Scalar::rand(rng), Scalar::rand(rng));
+        let proof = PC::open(&ck, Some(&a), &comms, point, challenge, &rands, None).unwrap();
+        let value = a.polynomial().evaluate(&point);
+        let check = PC::full_check(&vk, &comms, point, Some(value), &proof, challenge).unwrap();
+        assert!(check.holds(&vk, &proof));
+
+        let mut cs = circuit::<Fr>();
+        cs.wrap_ipa_opening::<EdwardsParameters>(&vk, &proof, &check).unwrap();
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        // the check of another value fails in the circuit.
+        let value = value + Scalar::one();
+        let check = PC::full_check(&vk, &comms, point, Some(value), &proof, challenge).unwrap();
+        assert!(!check.holds(&vk, &proof));
+        let mut cs = circuit::<Fr>();
+        cs.wrap_ipa_opening::<EdwardsParameters>(&vk, &proof, &check).unwrap();
+        assert!(MockProver::run(&cs, cs.public_inputs()).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Verification of KZG openings inside a circuit, for recursion.
+//!
+//! The opening `proof` of `commitment` to `value` at `point`, under a setup
+//! with the generators `g` and `h` and `beta h`, holds iff
+//! `e(commitment - value g + point proof, h) == e(proof, beta h)`. The
+//! circuit computes the two points paired with `h` and `beta h` and leaves
+//! the pairings to the verifier of the outer proof, which takes them as
+//! public inputs. The points are on the curve of `P`, a twisted Edwards model
+//! of G1 of the inner proof over the field of the circuit, so the inner and
+//! outer curves form a 2-chain, e.g. BLS12-377 in a circuit over the scalar
+//! field of BW6-761.
+//!
+//! Openings accumulated by `accumulate_pairings` take a single pairing check,
+//! so that only the outermost proof and one check go on-chain. The scalars
+//! of an opening are in the scalar field of the inner curve and enter as
+//! bits; the equality check of a full PlonK verifier is arithmetic over that
+//! field, which is not native to the circuit and is not provided here.
+
+use ark_ec::{twisted_edwards_extended::GroupAffine, TEModelParameters};
+use ark_ff::Zero;
+
+use crate::composer::{Composer, Field, Point, Variable};
+
+/// The points of a KZG check deferred to the verifier of the outer proof,
+/// which holds iff `e(lhs, h) == e(rhs, beta h)`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct DeferredPairing {
+    pub lhs: Point,
+    pub rhs: Point,
+}
+
+impl<F: Field> Composer<F> {
+    /// The deferred check of the opening `proof` of `commitment` at the
+    /// point and value of `point_bits` and `value_bits`, least significant
+    /// first and constrained by `boolean_gate`, with `g` the generator of
+    /// the setup.
+    pub fn kzg_opening<P: TEModelParameters<BaseField = F>>(
+        &mut self,
+        g: Point,
+        commitment: Point,
+        proof: Point,
+        point_bits: &[Variable],
+        value_bits: &[Variable],
+    ) -> DeferredPairing {
+        let value_g = self.mul_point::<P>(g, value_bits);
+        let neg_value_g = self.neg_point(value_g);
+        let point_proof = self.mul_point::<P>(proof, point_bits);
+        let lhs = self.add_points::<P>(commitment, point_proof);
+        let lhs = self.add_points::<P>(lhs, neg_value_g);
+
+        DeferredPairing { lhs, rhs: proof }
+    }
+
+    /// The sum of `r^i pairings[i]` for the `r` of `challenge_bits`, which
+    /// holds with all of `pairings` but, for a challenge fixed after them,
+    /// with negligible probability otherwise. The challenge must be derived
+    /// from the points, e.g. by hashing them in the circuit.
+    pub fn accumulate_pairings<P: TEModelParameters<BaseField = F>>(
+        &mut self,
+        pairings: &[DeferredPairing],
+        challenge_bits: &[Variable],
+    ) -> DeferredPairing {
+        let identity = self.constant_point(&GroupAffine::<P>::zero());
+        let mut acc = DeferredPairing {
+            lhs: identity,
+            rhs: identity,
+        };
+        // by Horner's rule, from the last one.
+        for (i, pairing) in pairings.iter().rev().enumerate() {
+            if i > 0 {
+                acc.lhs = self.mul_point::<P>(acc.lhs, challenge_bits);
+                acc.rhs = self.mul_point::<P>(acc.rhs, challenge_bits);
+            }
+            acc.lhs = self.add_points::<P>(acc.lhs, pairing.lhs);
+            acc.rhs = self.add_points::<P>(acc.rhs, pairing.rhs);
+        }
+        acc
+    }
+
+    /// Makes the coordinates of `pairing` public inputs, `lhs` then `rhs`,
+    /// by `constrain_to_constant`, for the verifier of the outer proof to
+    /// check.
+    pub fn expose_pairing(&mut self, pairing: DeferredPairing) {
+        let DeferredPairing { lhs, rhs } = pairing;
+        for var in [lhs.x(), lhs.y(), rhs.x(), rhs.y()].iter() {
+            let value = self.assignment.get(var).copied().unwrap_or_else(F::zero);
+            self.constrain_to_constant(*var, F::zero(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsParameters, Fr as Scalar};
+    use ark_ff::{BigInteger, Field as _, One, PrimeField, UniformRand};
+    use ark_std::{test_rng, vec::Vec};
+
+    use super::*;
+    use crate::composer::mock::MockProver;
+    use crate::tests::circuit;
+
+    /// A KZG setup over Jubjub, without pairings: `beta` is known, so a
+    /// deferred check holds iff `lhs == beta rhs`.
+    struct Setup {
+        g: EdwardsAffine,
+        beta: Scalar,
+    }
+
+    impl Setup {
+        /// A commitment to a polynomial with the value `value` at `beta`,
+        /// and the opening proof of its value `eval` at `point`.
+        fn open(
+            &self,
+            value: Scalar,
+            point: Scalar,
+            eval: Scalar,
+        ) -> (EdwardsAffine, EdwardsAffine) {
+            let quotient = (value - eval) * (self.beta - point).inverse().unwrap();
+            (self.mul(value), self.mul(quotient))
+        }
+
+        fn mul(&self, scalar: Scalar) -> EdwardsAffine {
+            self.g.mul(scalar.into_repr()).into_affine()
+        }
+
+        fn holds(&self, cs: &Composer<Fr>, pairing: DeferredPairing) -> bool {
+            let value =
+                |p: Point| EdwardsAffine::new(cs.assignment[&p.x()], cs.assignment[&p.y()]);
+            value(pairing.lhs) == value(pairing.rhs).mul(self.beta.into_repr()).into_affine()
+        }
+    }
+
+    fn alloc_bits(cs: &mut Composer<Fr>, scalar: Scalar) -> Vec<Variable> {
+        let bits = scalar.into_repr();
+        (0..Scalar::size_in_bits())
+            .map(|i| {
+                let bit = cs.alloc_and_assign(Fr::from(bits.get_bit(i) as u64));
+                cs.boolean_gate(bit);
+                bit
+            })
+            .collect()
+    }
+
+    #[test]
+    fn kzg_opening() {
+        let rng = &mut test_rng();
+        let setup = Setup {
+            g: EdwardsAffine::prime_subgroup_generator(),
+            beta: Scalar::rand(rng),
+        };
+
+        let mut cs = circuit::<Fr>();
+        let g = cs.constant_point(&setup.g);
+        let mut pairings = Vec::new();
+        for _ in 0..2 {
+            let (value, point, eval) = (Scalar::rand(rng), Scalar::rand(rng), Scalar::rand(rng));
+            let (commitment, proof) = setup.open(value, point, eval);
+            let commitment = cs.alloc_point(&commitment);
+            let proof = cs.alloc_point(&proof);
+            let point_bits = alloc_bits(&mut cs, point);
+            let eval_bits = alloc_bits(&mut cs, eval);
+            let pairing =
+                cs.kzg_opening::<EdwardsParameters>(g, commitment, proof, &point_bits, &eval_bits);
+            assert!(setup.holds(&cs, pairing));
+            pairings.push(pairing);
+        }
+
+        let challenge = alloc_bits(&mut cs, Scalar::rand(rng));
+        let acc = cs.accumulate_pairings::<EdwardsParameters>(&pairings, &challenge);
+        assert!(setup.holds(&cs, acc));
+        cs.expose_pairing(acc);
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        // an opening to another value fails the check.
+        let (commitment, proof) = setup.open(Scalar::rand(rng), Scalar::one(), Scalar::one());
+        let commitment = cs.alloc_point(&commitment);
+        let proof = cs.alloc_point(&proof);
+        let one = alloc_bits(&mut cs, Scalar::one());
+        let two = alloc_bits(&mut cs, Scalar::one() + Scalar::one());
+        let pairing = cs.kzg_opening::<EdwardsParameters>(g, commitment, proof, &one, &two);
+        assert!(!setup.holds(&cs, pairing));
+    }
+}
@@ -0,0 +1,151 @@
+//! Logic gates, the XOR and the AND of two values 2 bits at a time.
+//!
+//! A gate of `num_bits` takes `num_bits / 2` rows of `q_logic`, from the top
+//! limb down, and a last row holding the inputs and the output, as the
+//! accumulators of all their limbs. The accumulators of the first row are
+//! copies of a zero, so the inputs are also checked to fit in `num_bits`.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{format, vec, vec::Vec};
+
+use crate::composer::{Composer, Error, Field, Variable};
+
+impl<F: PrimeField + Field> Composer<F> {
+    /// a ^ b, for `a` and `b` of `num_bits` bits, or an `Error::OutOfRange`
+    /// if either is wider
+    pub fn xor_gate(&mut self, a: Variable, b: Variable, num_bits: u32) -> Result<Variable, Error> {
+        self.logic_gate(a, b, num_bits, F::one())
+    }
+
+    /// a & b, for `a` and `b` of `num_bits` bits, or an `Error::OutOfRange`
+    /// if either is wider
+    pub fn and_gate(&mut self, a: Variable, b: Variable, num_bits: u32) -> Result<Variable, Error> {
+        self.logic_gate(a, b, num_bits, F::from(2u64))
+    }
+
+    fn logic_gate(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        num_bits: u32,
+        q_logic: F,
+    ) -> Result<Variable, Error> {
+        let num_bits = num_bits as usize;
+        assert!(num_bits > 0 && num_bits % 2 == 0 && num_bits < F::size_in_bits());
+
+        //只计数时没有赋值，门的个数与值无关
+        let value = |var| {
+            if self.counting {
+                F::zero()
+            } else {
+                self.assignment[&var]
+            }
+        };
+        let bits = [value(a).into_repr(), value(b).into_repr()];
+        let high_bits = bits.iter().fold(false, |acc, bits| {
+            (num_bits..F::size_in_bits()).fold(acc, |acc, i| acc | bits.get_bit(i))
+        });
+        if high_bits {
+            return Err(Error::OutOfRange {
+                variable: format!(
+                    "{} or {}{}",
+                    self.variable_name(a),
+                    self.variable_name(b),
+                    self.in_namespace()
+                ),
+                bits: num_bits as u32,
+            });
+        }
+
+        let is_xor = q_logic.is_one();
+        let four = F::from(4u64);
+        let mut acc = vec![[F::zero(); 3]];
+        let mut products = Vec::with_capacity(num_bits / 2);
+        for m in (0..num_bits / 2).rev() {
+            let limb =
+                |bits: &F::BigInt| 2 * bits.get_bit(2 * m + 1) as u64 + bits.get_bit(2 * m) as u64;
+            let (limb_a, limb_b) = (limb(&bits[0]), limb(&bits[1]));
+            let limb_c = if is_xor {
+                limb_a ^ limb_b
+            } else {
+                limb_a & limb_b
+            };
+            products.push(F::from(limb_a * limb_b));
+
+            let [acc_a, acc_b, acc_c] = acc[acc.len() - 1];
+            acc.push([
+                four * acc_a + F::from(limb_a),
+                four * acc_b + F::from(limb_b),
+                four * acc_c + F::from(limb_c),
+            ]);
+        }
+
+        let zero = self.alloc_and_assign(F::zero());
+        let c = self.alloc_and_assign(acc[num_bits / 2][2]);
+        let mut vars = [zero, zero, zero];
+        for (row, product) in products.into_iter().enumerate() {
+            let (q_0, q_arith) = if row == 0 {
+                (F::one(), F::one())
+            } else {
+                (F::zero(), F::zero())
+            };
+            let product = self.alloc_and_assign(product);
+            self.create_logic_gate_component(vars, product, q_0, q_arith, q_logic);
+            vars = if row + 1 == num_bits / 2 {
+                [a, b, c]
+            } else {
+                let [acc_a, acc_b, acc_c] = acc[row + 1];
+                [
+                    self.alloc_and_assign(acc_a),
+                    self.alloc_and_assign(acc_b),
+                    self.alloc_and_assign(acc_c),
+                ]
+            };
+        }
+        // 最后一行的累加值就是输入和输出，由上一行的logic约束检查
+        self.create_logic_gate_component(vars, self.null_var, F::zero(), F::zero(), F::zero());
+
+        Ok(c)
+    }
+
+    //logic gate 的具体的小门
+    fn create_logic_gate_component(
+        &mut self,
+        acc: [Variable; 3], // w_0, w_1, w_2
+        product: Variable,  // w_3
+        q_0: F,
+        q_arith: F,
+        q_logic: F,
+    ) {
+        let index = self.n;
+        self.permutation
+            .insert_gate(acc[0], acc[1], acc[2], product, index);
+
+        self.w_0.push(acc[0]);
+        self.w_1.push(acc[1]);
+        self.w_2.push(acc[2]);
+        self.w_3.push(product);
+        self.w_4.push(self.null_var);
+        self.pi.push(F::zero());
+
+        self.q_0.push(q_0);
+        self.q_1.push(F::zero());
+        self.q_2.push(F::zero());
+        self.q_3.push(F::zero());
+        self.q_4.push(F::zero());
+        self.q_m.push(F::zero());
+        self.q_c.push(F::zero());
+        self.q_next.push(F::zero());
+        self.q_arith.push(q_arith);
+        self.q_range.push(F::zero());
+        self.q_mimc.push(F::zero());
+        self.q_lookup.push(F::zero());
+        self.q_logic.push(q_logic);
+        self.q_curve.push(F::zero());
+        self.q_curve_a.push(F::zero());
+        self.q_curve_d.push(F::zero());
+        self.q_memory.push(F::zero());
+
+        self.n += 1;
+    }
+}
@@ -0,0 +1,141 @@
+//! Lookup gates, which constrain the wires `w_1..w_3` of a row to a row of a
+//! table registered with the composer, with the argument of plookup.
+//!
+//! A lookup is a single gate whatever the size of its table, e.g. an XOR of
+//! two 4-bit values or a check that a value fits in 8 bits, both of which
+//! take many arithmetic gates. The tables are part of the index: the domain
+//! holds all their rows, a row of zeros after them, and a last row without a
+//! lookup, which the argument leaves out.
+
+use ark_std::{vec, vec::Vec};
+
+use crate::composer::{Composer, Field, Variable};
+
+/// A table registered with `Composer::add_table`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Table(pub(crate) usize);
+
+impl<F: Field> Composer<F> {
+    /// Registers a table of `rows`, which lookup gates check `(w_1, w_2,
+    /// w_3)` against.
+    pub fn add_table(&mut self, rows: Vec<[F; 3]>) -> Table {
+        self.tables.push(rows);
+        Table(self.tables.len())
+    }
+
+    /// The table of `(x, 0, 0)` for `x < 2^bits`, to check with
+    /// `add_lookup_gate(table, x, zero, zero)` that `x` fits in `bits` bits.
+    pub fn add_range_table(&mut self, bits: u32) -> Table {
+        let rows = (0..1u64 << bits)
+            .map(|x| [F::from(x), F::zero(), F::zero()])
+            .collect();
+        self.add_table(rows)
+    }
+
+    /// The table of `(a, b, a ^ b)` for `a, b < 2^bits`.
+    pub fn add_xor_table(&mut self, bits: u32) -> Table {
+        self.add_binary_table(bits, |a, b| a ^ b)
+    }
+
+    /// The table of `(a, b, a & b)` for `a, b < 2^bits`.
+    pub fn add_and_table(&mut self, bits: u32) -> Table {
+        self.add_binary_table(bits, |a, b| a & b)
+    }
+
+    fn add_binary_table(&mut self, bits: u32, op: impl Fn(u64, u64) -> u64) -> Table {
+        let mut rows = Vec::with_capacity(1 << (2 * bits));
+        for a in 0..1u64 << bits {
+            for b in 0..1u64 << bits {
+                rows.push([F::from(a), F::from(b), F::from(op(a, b))]);
+            }
+        }
+        self.add_table(rows)
+    }
+
+    /// (a, b, c) is a row of `table`
+    pub fn add_lookup_gate(&mut self, table: Table, a: Variable, b: Variable, c: Variable) {
+        assert!(table.0 >= 1 && table.0 <= self.tables.len());
+        let index = self.n;
+        self.permutation.insert_gate(self.null_var, a, b, c, index);
+
+        self.w_0.push(self.null_var);
+        self.w_1.push(a);
+        self.w_2.push(b);
+        self.w_3.push(c);
+        self.w_4.push(self.null_var);
+        self.pi.push(F::zero());
+
+        self.q_0.push(F::zero());
+        self.q_1.push(F::zero());
+        self.q_2.push(F::zero());
+        self.q_3.push(F::zero());
+        self.q_4.push(F::zero());
+        self.q_m.push(F::zero());
+        self.q_c.push(F::zero());
+        self.q_next.push(F::zero());
+        self.q_arith.push(F::zero());
+        self.q_range.push(F::zero());
+        self.q_mimc.push(F::zero());
+        self.q_lookup.push(F::from(table.0 as u64));
+        self.q_logic.push(F::zero());
+        self.q_curve.push(F::zero());
+        self.q_curve_a.push(F::zero());
+        self.q_curve_d.push(F::zero());
+        self.q_memory.push(F::zero());
+
+        self.n += 1;
+    }
+
+    /// The `c` of the first row `(a, b, c)` of `table`, constrained with a
+    /// lookup gate. It is zero if no row matches, and the circuit is then
+    /// not satisfied.
+    pub fn lookup(&mut self, table: Table, a: Variable, b: Variable) -> Variable {
+        let value = |var| self.assignment.get(&var).copied();
+        let c = match (value(a), value(b)) {
+            (Some(a), Some(b)) => self.tables[table.0 - 1]
+                .iter()
+                .find(|row| row[0] == a && row[1] == b)
+                .map_or_else(F::zero, |row| row[2]),
+            _ => F::zero(),
+        };
+        let c = self.alloc_and_assign(c);
+        self.add_lookup_gate(table, a, b, c);
+        c
+    }
+
+    /// The rows of all tables.
+    pub(crate) fn table_size(&self) -> usize {
+        self.tables.iter().map(Vec::len).sum()
+    }
+
+    /// The rows the domain must hold, see the module documentation.
+    pub(crate) fn rows(&self) -> usize {
+        if self.tables.is_empty() {
+            self.n
+        } else {
+            (self.n + 1).max(self.table_size() + 1)
+        }
+    }
+
+    /// The tables on a domain of `n` rows, as the columns `k`, `k * a`,
+    /// `k * b` and `k * c` of each row `(a, b, c)` of the table `k`, padded
+    /// with zeros. A lookup on `(w_1, w_2, w_3)` with `q_lookup = k` is then
+    /// the same linear combination of `q_lookup`, `q_lookup * w_1`, ... as
+    /// its row is of the columns.
+    pub(crate) fn compose_tables(&self, n: usize) -> [Vec<F>; 4] {
+        let mut columns = [vec![], vec![], vec![], vec![]];
+        for (k, table) in self.tables.iter().enumerate() {
+            let k = F::from(k as u64 + 1);
+            for row in table {
+                columns[0].push(k);
+                columns[1].push(k * row[0]);
+                columns[2].push(k * row[1]);
+                columns[3].push(k * row[2]);
+            }
+        }
+        for column in columns.iter_mut() {
+            column.resize(n, F::zero());
+        }
+        columns
+    }
+}
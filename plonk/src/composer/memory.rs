@@ -0,0 +1,306 @@
+//! Random-access memories, checked by a permutation argument instead of
+//! selecting the accessed cell out of all of them, which takes gates in the
+//! size of the memory for every access.
+//!
+//! An access is a row `(time, address, old, new)` on `q_memory = k` for the
+//! memory `k`, whose arithmetic gate fixes `time`: a read has `new == old`,
+//! and a write replaces `old` with `new`. The cells are first written at
+//! time zero with their initial values. `close_memory` lays the same records
+//! out sorted by address then time, on rows with `q_memory = -k`, and
+//! constrains each to follow the one before: the address grows by zero or
+//! one, a new address starts at time zero, and otherwise the time grows and
+//! `old` is the `new` of the previous access. The grand product `z_memory` of
+//! the prover then checks that both sets of rows hold the same records, see
+//! `MemoryKey`.
+
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+use crate::composer::{Composer, Error, Field, Variable};
+
+/// A memory registered with `Composer::add_memory`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Memory(pub(crate) usize);
+
+/// An access to a memory, as the values of its row.
+#[derive(Debug, Clone)]
+struct Record<F: Field> {
+    time: u64,
+    address: usize,
+    old: F,
+    new: F,
+}
+
+/// The accesses to a memory, until it is closed.
+#[derive(Debug, Clone)]
+pub(crate) struct MemoryState<F: Field> {
+    /// The values of the cells after the last access.
+    cells: Vec<F>,
+    records: Vec<Record<F>>,
+    /// The time of the last access.
+    time: u64,
+    /// A variable constrained to zero.
+    zero: Variable,
+    pub(crate) closed: bool,
+}
+
+impl<F: Field> MemoryState<F> {
+    /// A closed memory with no records, e.g. read back with its circuit.
+    pub(crate) fn closed(zero: Variable) -> Self {
+        MemoryState {
+            cells: Vec::new(),
+            records: Vec::new(),
+            time: 0,
+            zero,
+            closed: true,
+        }
+    }
+}
+
+impl<F: PrimeField + Field> Composer<F> {
+    /// Registers a memory of `init.len()` cells, addressed from zero and
+    /// holding `init`, to access with `memory_read` and `memory_write`.
+    pub fn add_memory(&mut self, init: &[Variable]) -> Memory {
+        let zero = self.alloc_and_assign(F::zero());
+        self.constrain_to_constant(zero, F::zero(), F::zero());
+        self.memories.push(MemoryState {
+            cells: Vec::with_capacity(init.len()),
+            records: Vec::with_capacity(init.len()),
+            time: 0,
+            zero,
+            closed: false,
+        });
+        let memory = Memory(self.memories.len());
+
+        for (address, var) in init.iter().enumerate() {
+            let value = self.value(*var);
+            let var_address = self.alloc_and_assign(F::from(address as u64));
+            // w_1 == address
+            self.add_memory_row(
+                F::from(memory.0 as u64),
+                [zero, var_address, zero, *var],
+                Some((F::zero(), F::one(), -F::from(address as u64))),
+            );
+            let state = &mut self.memories[memory.0 - 1];
+            state.cells.push(value);
+            state.records.push(Record {
+                time: 0,
+                address,
+                old: F::zero(),
+                new: value,
+            });
+        }
+        memory
+    }
+
+    /// The value at `address` of `memory`. An address past the end of the
+    /// memory panics, as no record can match it.
+    pub fn memory_read(&mut self, memory: Memory, address: Variable) -> Variable {
+        let index = self.address(memory, address);
+        let value = self.memories[memory.0 - 1].cells[index];
+        let var = self.alloc_and_assign(value);
+        self.add_access(memory, (address, index), (var, value), (var, value));
+        var
+    }
+
+    /// Writes `value` at `address` of `memory`.
+    pub fn memory_write(&mut self, memory: Memory, address: Variable, value: Variable) {
+        let index = self.address(memory, address);
+        let old = self.memories[memory.0 - 1].cells[index];
+        let var_old = self.alloc_and_assign(old);
+        let new = self.value(value);
+        self.add_access(memory, (address, index), (var_old, old), (value, new));
+        self.memories[memory.0 - 1].cells[index] = new;
+    }
+
+    /// Lays out the records of `memory` sorted by address then time, and
+    /// constrains each to follow the one before. A memory takes no access
+    /// once closed, and must be closed before the circuit is proven, or the
+    /// grand product fails.
+    pub fn close_memory(&mut self, memory: Memory) -> Result<(), Error> {
+        let state = &mut self.memories[memory.0 - 1];
+        assert!(!state.closed, "memory already closed");
+        state.closed = true;
+        let zero = state.zero;
+        let mut records = ark_std::mem::take(&mut state.records);
+        records.sort_by_key(|record| (record.address, record.time));
+        // the times of the accesses are below 2^bits.
+        let bits = (64 - state.time.leading_zeros()).max(1);
+
+        let k = -F::from(memory.0 as u64);
+        let mut previous: Option<([Variable; 4], &Record<F>)> = None;
+        for record in records.iter() {
+            let vars = [
+                self.alloc_and_assign(F::from(record.time)),
+                self.alloc_and_assign(F::from(record.address as u64)),
+                self.alloc_and_assign(record.old),
+                self.alloc_and_assign(record.new),
+            ];
+            self.add_memory_row(k, vars, None);
+
+            match previous {
+                // the first address starts at time zero.
+                None => self.assert_equal(vars[0], zero),
+                Some((prev, prev_record)) => {
+                    let step = (record.address != prev_record.address) as u64;
+                    self.constrain_step((prev, prev_record), (vars, record), (zero, step, bits))?;
+                }
+            }
+            previous = Some((vars, record));
+        }
+        Ok(())
+    }
+
+    /// Constrains the sorted record `next` to follow `prev`, with `step` the
+    /// growth of the address, either it stays and the time grows by less
+    /// than `2^bits` with `old` the previous `new`, or it grows by one and
+    /// the time is zero.
+    fn constrain_step(
+        &mut self,
+        prev: ([Variable; 4], &Record<F>),
+        next: ([Variable; 4], &Record<F>),
+        bounds: (Variable, u64, u32),
+    ) -> Result<(), Error> {
+        let ((prev, prev_record), (next, record)) = (prev, next);
+        let (zero, step, bits) = bounds;
+
+        // d = address - previous address, a bit
+        let d = self.alloc_and_assign(F::from(step));
+        self.create_add_gate(
+            (next[1], F::one()),
+            (prev[1], -F::one()),
+            d,
+            None,
+            F::zero(),
+            F::zero(),
+        );
+        self.boolean_gate(d);
+
+        // (1 - d) * (old - previous new) == 0
+        let diff = self.alloc_and_assign(record.old - prev_record.new);
+        self.create_add_gate(
+            (next[2], F::one()),
+            (prev[3], -F::one()),
+            diff,
+            None,
+            F::zero(),
+            F::zero(),
+        );
+        self.create_mul_gate(d, diff, diff, None, F::one(), F::zero(), F::zero());
+
+        // d * time == 0
+        self.create_mul_gate(d, next[0], zero, None, F::one(), F::zero(), F::zero());
+
+        // (1 - d) * (time - previous time - 1) < 2^bits
+        let gap = F::from(record.time) - F::from(prev_record.time) - F::one();
+        let g = self.alloc_and_assign(gap);
+        self.create_add_gate(
+            (next[0], F::one()),
+            (prev[0], -F::one()),
+            g,
+            None,
+            -F::one(),
+            F::zero(),
+        );
+        let e = self.alloc_and_assign(if step == 0 { gap } else { F::zero() });
+        // g - d * g - e == 0
+        self.create_mul_gate(
+            d,
+            g,
+            e,
+            Some((g, F::one())),
+            -F::one(),
+            F::zero(),
+            F::zero(),
+        );
+        self.range_gate(e, bits)
+    }
+
+    /// Adds an access of `address` replacing `old` with `new` at the next
+    /// time of `memory`.
+    fn add_access(
+        &mut self,
+        memory: Memory,
+        address: (Variable, usize),
+        old: (Variable, F),
+        new: (Variable, F),
+    ) {
+        let state = &mut self.memories[memory.0 - 1];
+        assert!(!state.closed, "memory closed");
+        state.time += 1;
+        let time = state.time;
+        state.records.push(Record {
+            time,
+            address: address.1,
+            old: old.1,
+            new: new.1,
+        });
+
+        let var_time = self.alloc_and_assign(F::from(time));
+        // w_0 == time
+        self.add_memory_row(
+            F::from(memory.0 as u64),
+            [var_time, address.0, old.0, new.0],
+            Some((F::one(), F::zero(), -F::from(time))),
+        );
+    }
+
+    /// The cell at `address` of `memory`, zero when counting.
+    fn address(&self, memory: Memory, address: Variable) -> usize {
+        assert!(memory.0 >= 1 && memory.0 <= self.memories.len());
+        if self.counting {
+            return 0;
+        }
+        let repr = self.assignment[&address].into_repr();
+        let limbs = repr.as_ref();
+        let size = self.memories[memory.0 - 1].cells.len();
+        assert!(
+            limbs[1..].iter().all(|limb| *limb == 0) && (limbs[0] as usize) < size,
+            "address out of range: {}{}",
+            self.variable_name(address),
+            self.in_namespace()
+        );
+        limbs[0] as usize
+    }
+
+    fn value(&self, var: Variable) -> F {
+        self.assignment.get(&var).copied().unwrap_or_else(F::zero)
+    }
+
+    /// A row `[w_0, w_1, w_2, w_3]` of `q_memory`, with the arithmetic gate
+    /// `q_0 * w_0 + q_1 * w_1 + q_c == 0` of `arith = (q_0, q_1, q_c)`.
+    fn add_memory_row(&mut self, q_memory: F, wires: [Variable; 4], arith: Option<(F, F, F)>) {
+        let index = self.n;
+        let [w_0, w_1, w_2, w_3] = wires;
+        self.permutation.insert_gate(w_0, w_1, w_2, w_3, index);
+
+        self.w_0.push(w_0);
+        self.w_1.push(w_1);
+        self.w_2.push(w_2);
+        self.w_3.push(w_3);
+        self.w_4.push(self.null_var);
+        self.pi.push(F::zero());
+
+        let (q_0, q_1, q_c) = arith.unwrap_or_else(|| (F::zero(), F::zero(), F::zero()));
+        self.q_0.push(q_0);
+        self.q_1.push(q_1);
+        self.q_2.push(F::zero());
+        self.q_3.push(F::zero());
+        self.q_4.push(F::zero());
+        self.q_m.push(F::zero());
+        self.q_c.push(q_c);
+        self.q_next.push(F::zero());
+        self.q_arith
+            .push(if arith.is_some() { F::one() } else { F::zero() });
+        self.q_range.push(F::zero());
+        self.q_mimc.push(F::zero());
+        self.q_lookup.push(F::zero());
+        self.q_logic.push(F::zero());
+        self.q_curve.push(F::zero());
+        self.q_curve_a.push(F::zero());
+        self.q_curve_d.push(F::zero());
+        self.q_memory.push(q_memory);
+
+        self.n += 1;
+    }
+}
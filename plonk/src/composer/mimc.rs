@@ -1,6 +1,7 @@
 use crate::Composer;
 use ark_ff::{PrimeField, Field};
 use crate::composer::Variable;
+use ark_std::vec::Vec;
 
 #[derive(Debug)]
 pub struct MimcC<F: Field>{
@@ -49,18 +50,27 @@ impl<F: PrimeField + Field> Composer<F> {
         self.w_1.push(l.0);
         self.w_2.push(r.0);
         self.w_3.push(o.0);
+        self.w_4.push(self.null_var);
         self.pi.push(pi);
 
         self.q_0.push(aux.1);
         self.q_1.push(l.1);
         self.q_2.push(r.1);
         self.q_3.push(o.1);
+        self.q_4.push(F::zero());
         self.q_m.push(q_m);
         self.q_c.push(q_c);
+        self.q_next.push(F::zero());
         self.q_arith.push(q_arith);
         self.q_range.push(F::zero());
         self.q_mimc.push(q_mimc);
         //self.q_mimc_c.push(q_mimc_c);
+        self.q_lookup.push(F::zero());
+        self.q_logic.push(F::zero());
+        self.q_curve.push(F::zero());
+        self.q_curve_a.push(F::zero());
+        self.q_curve_d.push(F::zero());
+        self.q_memory.push(F::zero());
 
         self.n += 1;
     }
@@ -0,0 +1,810 @@
+//! Check that a circuit is satisfied, without running the protocol.
+//!
+//! [`MockProver::run`] evaluates every gate on every row of the synthesized
+//! witness, with the same equations as the quotient polynomial, and checks
+//! that all wires of a variable carry its value and that the accesses to each
+//! memory hold its sorted records. Instead of a failed proof it reports each
+//! unsatisfied constraint with its row, gate, namespace, selectors and wire
+//! values, and each wrong copy with the label of its variable.
+//! `Composer::check_satisfied` runs it against the public inputs of the
+//! composer.
+
+use ark_std::{
+    fmt,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::composer::permutation::Wire;
+use crate::composer::{Composer, Error, Field, Memory, Variable, Witnesses};
+use crate::utils::pad_to_size;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Failure<F: Field> {
+    /// The circuit does not fit in an evaluation domain of the field.
+    CircuitTooLarge,
+    /// The composer only counts gates and has no values.
+    Unassigned,
+    /// There are more public inputs than rows.
+    PublicInputs { rows: usize, public_inputs: usize },
+    /// The gate on `row` puts a variable on `w_{column}`, which the composer
+    /// has not, see `Composer::with_wires`.
+    AbsentWire { row: usize, column: usize },
+    /// Constraint `constraint` of `gate` does not vanish on `row`, in
+    /// `namespace`, whose selectors are `selectors`, by name and without the
+    /// zero ones, and whose wires `w_0..w_3`, and `w_4` with five wires,
+    /// hold `wires`.
+    Gate {
+        row: usize,
+        gate: &'static str,
+        constraint: usize,
+        namespace: String,
+        selectors: Vec<(&'static str, F)>,
+        wires: Vec<F>,
+    },
+    /// Wire `w_{column}` of `row`, in `namespace`, holds `value` instead of
+    /// the value of `variable`, labeled `label`.
+    Copy {
+        variable: Variable,
+        label: Option<String>,
+        row: usize,
+        column: usize,
+        namespace: String,
+        value: F,
+        expected: F,
+    },
+    /// The rows of the accesses to `memory` do not hold the same records
+    /// as its sorted rows, e.g. as it was not closed.
+    Memory { memory: Memory },
+}
+
+impl<F: Field> fmt::Display for Failure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::CircuitTooLarge => write!(f, "no evaluation domain fits the circuit"),
+            Failure::Unassigned => write!(f, "the composer only counts gates"),
+            Failure::PublicInputs {
+                rows,
+                public_inputs,
+            } => write!(f, "{} public inputs for {} rows", public_inputs, rows),
+            Failure::AbsentWire { row, column } => {
+                write!(
+                    f,
+                    "the gate on row {} uses the absent wire w_{}",
+                    row, column
+                )
+            }
+            Failure::Gate {
+                row,
+                gate,
+                constraint,
+                namespace,
+                selectors,
+                wires,
+            } => {
+                write!(
+                    f,
+                    "constraint {} of the {} gate on row {}{} does not vanish, with",
+                    constraint,
+                    gate,
+                    row,
+                    InNamespace(namespace)
+                )?;
+                for (name, value) in selectors {
+                    write!(f, " {} = {},", name, value)?;
+                }
+                for (column, value) in wires.iter().enumerate() {
+                    let sep = if column == 0 { " " } else { ", " };
+                    write!(f, "{}w_{} = {}", sep, column, value)?;
+                }
+                Ok(())
+            }
+            Failure::Copy {
+                variable,
+                label,
+                row,
+                column,
+                namespace,
+                value,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "w_{} of row {}{} holds {} instead of {} of v{}",
+                    column,
+                    row,
+                    InNamespace(namespace),
+                    value,
+                    expected,
+                    variable.0
+                )?;
+                match label {
+                    Some(label) => write!(f, " ({})", label),
+                    None => Ok(()),
+                }
+            }
+            Failure::Memory { memory } => {
+                write!(f, "the accesses to {:?} do not match its records", memory)
+            }
+        }
+    }
+}
+
+/// ` in` a namespace, or nothing for the top one.
+struct InNamespace<'a>(&'a str);
+
+impl fmt::Display for InNamespace<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            write!(f, " in {}", self.0)
+        }
+    }
+}
+
+/// The selectors of `row` that are not zero, by name.
+fn selectors<F: Field>(cs: &Composer<F>, row: usize) -> Vec<(&'static str, F)> {
+    if row >= cs.n {
+        return Vec::new();
+    }
+    let selectors = [
+        ("q_0", &cs.q_0),
+        ("q_1", &cs.q_1),
+        ("q_2", &cs.q_2),
+        ("q_3", &cs.q_3),
+        ("q_4", &cs.q_4),
+        ("q_m", &cs.q_m),
+        ("q_c", &cs.q_c),
+        ("q_next", &cs.q_next),
+        ("q_arith", &cs.q_arith),
+        ("q_range", &cs.q_range),
+        ("q_mimc", &cs.q_mimc),
+        ("q_lookup", &cs.q_lookup),
+        ("q_logic", &cs.q_logic),
+        ("q_curve", &cs.q_curve),
+        ("q_curve_a", &cs.q_curve_a),
+        ("q_curve_d", &cs.q_curve_d),
+        ("q_memory", &cs.q_memory),
+    ];
+    selectors
+        .iter()
+        .filter(|(_, selector)| !selector[row].is_zero())
+        .map(|(name, selector)| (*name, selector[row]))
+        .collect()
+}
+
+pub struct MockProver;
+
+impl MockProver {
+    /// Check `cs` against `public_inputs`, as given to the verifier.
+    pub fn run<F: Field>(cs: &Composer<F>, public_inputs: &[F]) -> Result<(), Vec<Failure<F>>> {
+        let witnesses = cs.synthesize().map_err(|e| match e {
+            Error::Unassigned => vec![Failure::Unassigned],
+            _ => vec![Failure::CircuitTooLarge],
+        })?;
+        if let Err(Error::AbsentWire { row, column }) = cs.check_wires() {
+            return Err(vec![Failure::AbsentWire { row, column }]);
+        }
+        let Witnesses {
+            w_0,
+            w_1,
+            w_2,
+            w_3,
+            w_4,
+        } = &witnesses;
+
+        let n = w_0.len();
+        if public_inputs.len() > n {
+            return Err(vec![Failure::PublicInputs {
+                rows: n,
+                public_inputs: public_inputs.len(),
+            }]);
+        }
+        let pi = pad_to_size(public_inputs, n);
+
+        // selectors are zero on the padding rows.
+        let q = |selector: &[F], i: usize| selector.get(i).copied().unwrap_or_else(F::zero);
+        let two = F::one() + F::one();
+        let three = two + F::one();
+        let four = two + two;
+        let quad = |a: F, b: F| {
+            let d = a - four * b;
+            d * (d - F::one()) * (d - two) * (d - three)
+        };
+
+        let mut failures = Vec::new();
+        for i in 0..n {
+            let next = (i + 1) % n;
+            let mut wires = vec![w_0[i], w_1[i], w_2[i], w_3[i]];
+            if cs.wires() == 5 {
+                wires.push(w_4[i]);
+            }
+            let mut check = |gate, constraints: &[F]| {
+                for (constraint, value) in constraints.iter().enumerate() {
+                    if !value.is_zero() {
+                        failures.push(Failure::Gate {
+                            row: i,
+                            gate,
+                            constraint,
+                            namespace: cs.namespace_of(i).to_string(),
+                            selectors: selectors(cs, i),
+                            wires: wires.clone(),
+                        });
+                    }
+                }
+            };
+
+            if !q(&cs.q_arith, i).is_zero() {
+                check(
+                    "arithmetic",
+                    &[q(&cs.q_0, i) * w_0[i]
+                        + q(&cs.q_1, i) * w_1[i]
+                        + q(&cs.q_2, i) * w_2[i]
+                        + q(&cs.q_3, i) * w_3[i]
+                        + q(&cs.q_4, i) * w_4[i]
+                        + q(&cs.q_m, i) * w_1[i] * w_2[i]
+                        + q(&cs.q_c, i)
+                        + pi[i]
+                        + q(&cs.q_next, i) * w_0[next]],
+                );
+            }
+
+            if !q(&cs.q_range, i).is_zero() {
+                check(
+                    "range",
+                    &[
+                        quad(w_0[next], w_3[i]),
+                        quad(w_3[i], w_2[i]),
+                        quad(w_2[i], w_1[i]),
+                        quad(w_1[i], w_0[i]),
+                    ],
+                );
+            }
+
+            if !q(&cs.q_mimc, i).is_zero() {
+                let tmp = w_0[i] + w_2[i];
+                check(
+                    "mimc",
+                    &[
+                        w_3[i] - tmp.square() * tmp,
+                        w_0[next] - w_3[i] - w_1[i],
+                    ],
+                );
+            }
+
+            let table = q(&cs.q_lookup, i);
+            if !table.is_zero() {
+                let row = [w_1[i], w_2[i], w_3[i]];
+                let found = cs
+                    .tables
+                    .iter()
+                    .zip(1u64..)
+                    .find(|(_, k)| F::from(*k) == table)
+                    .map_or(false, |(rows, _)| rows.contains(&row));
+                check("lookup", &[if found { F::zero() } else { F::one() }]);
+            }
+
+            let op = q(&cs.q_logic, i);
+            if !op.is_zero() {
+                let da = w_0[next] - four * w_0[i];
+                let db = w_1[next] - four * w_1[i];
+                let dc = w_2[next] - four * w_2[i];
+                let (s, p) = (da + db, w_3[i]);
+                // six times the AND of the limbs.
+                let g = p
+                    * (F::from(83u64) - F::from(81u64) * s
+                        + F::from(45u64) * p
+                        + F::from(18u64) * (s.square() - s * p)
+                        + four * p.square());
+                check(
+                    "logic",
+                    &[
+                        quad(w_0[next], w_0[i]),
+                        quad(w_1[next], w_1[i]),
+                        p - da * db,
+                        F::from(6u64) * (dc - (two - op) * s) + (F::from(5u64) - three * op) * g,
+                    ],
+                );
+            }
+
+            if !q(&cs.q_curve, i).is_zero() {
+                let (a, d) = (q(&cs.q_curve_a, i), q(&cs.q_curve_d, i));
+                let (x_1, y_1, x_2, y_2) = (w_0[i], w_1[i], w_2[i], w_3[i]);
+                let (x_3, y_3, p, r) = (w_0[next], w_1[next], w_2[next], w_3[next]);
+                let dpr = d * p * r;
+                check(
+                    "curve",
+                    &[
+                        p - x_1 * y_2,
+                        r - y_1 * x_2,
+                        x_3 * (F::one() + dpr) - p - r,
+                        y_3 * (F::one() - dpr) - y_1 * y_2 + a * x_1 * x_2,
+                    ],
+                );
+            }
+        }
+
+        for k in 1..=cs.memories.len() {
+            let records = |q_memory: F| {
+                let mut records: Vec<_> = (0..cs.n)
+                    .filter(|i| cs.q_memory[*i] == q_memory)
+                    .map(|i| [w_0[i], w_1[i], w_2[i], w_3[i]])
+                    .collect();
+                records.sort();
+                records
+            };
+            let q_memory = F::from(k as u64);
+            if records(q_memory) != records(-q_memory) {
+                failures.push(Failure::Memory { memory: Memory(k) });
+            }
+        }
+
+        let columns = [w_0, w_1, w_2, w_3, w_4];
+        let mut copies = Vec::new();
+        for (variable, wires) in cs.permutation.wires() {
+            let expected = cs.assignment[variable];
+            for &Wire { column, row } in wires {
+                let value = columns[column][row];
+                if value != expected {
+                    let failure = Failure::Copy {
+                        variable: *variable,
+                        label: cs.label_of(*variable).map(ToString::to_string),
+                        row,
+                        column,
+                        namespace: cs.namespace_of(row).to_string(),
+                        value,
+                        expected,
+                    };
+                    copies.push((row, column, failure));
+                }
+            }
+        }
+        copies.sort_by_key(|(row, column, _)| (*row, *column));
+        failures.extend(copies.into_iter().map(|(_, _, failure)| failure));
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+impl<F: Field> Composer<F> {
+    /// Checks every gate against the assigned values and the public inputs
+    /// of the composer, before any proof, and reports each unsatisfied
+    /// constraint with its row, gate, selectors and wires, see
+    /// `MockProver::run`.
+    pub fn check_satisfied(&self) -> Result<(), Vec<Failure<F>>> {
+        MockProver::run(self, &self.pi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, Zero};
+
+    use super::*;
+    use crate::composer::range::RangeType;
+    use crate::composer::Point;
+    use crate::tests::{circuit, my_circuit};
+
+    #[test]
+    fn satisfied() {
+        let cs = circuit::<Fr>();
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        let cs = my_circuit();
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+    }
+
+    #[test]
+    fn unsatisfied_gate() {
+        let mut cs = circuit::<Fr>();
+        let var = cs.alloc_and_assign(Fr::from(3u64));
+        cs.constrain_to_constant(var, Fr::from(4u64), Fr::zero());
+        let row = cs.size() - 1;
+
+        let failures = MockProver::run(&cs, cs.public_inputs()).unwrap_err();
+        assert_eq!(
+            failures,
+            vec![Failure::Gate {
+                row,
+                gate: "arithmetic",
+                constraint: 0,
+                namespace: String::new(),
+                selectors: vec![
+                    ("q_1", Fr::one()),
+                    ("q_c", -Fr::from(4u64)),
+                    ("q_arith", Fr::one()),
+                ],
+                wires: vec![Fr::zero(), Fr::from(3u64), Fr::from(3u64), Fr::from(3u64)],
+            }]
+        );
+    }
+
+    #[test]
+    fn check_satisfied() {
+        let mut cs = circuit::<Fr>();
+        assert_eq!(cs.check_satisfied(), Ok(()));
+
+        let var = cs.alloc_and_assign(Fr::from(3u64));
+        cs.constrain_to_constant(var, Fr::zero(), Fr::from(4u64));
+        let row = cs.size() - 1;
+        let failures = cs.check_satisfied().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(
+            failures[0].to_string(),
+            format!(
+                "constraint 0 of the arithmetic gate on row {} does not vanish, with \
+                 q_1 = {}, q_arith = {}, w_0 = {}, w_1 = {}, w_2 = {}, w_3 = {}",
+                row,
+                Fr::one(),
+                Fr::one(),
+                Fr::zero(),
+                Fr::from(3u64),
+                Fr::from(3u64),
+                Fr::from(3u64),
+            )
+        );
+
+        let counting = Composer::<Fr>::new_counting();
+        assert_eq!(counting.check_satisfied(), Err(vec![Failure::Unassigned]));
+    }
+
+    #[test]
+    fn namespaces() {
+        let mut cs = circuit::<Fr>();
+        cs.push_namespace("merkle/level3");
+        let leaf = cs.alloc_and_assign(Fr::from(3u64));
+        cs.label(leaf, "leaf");
+        cs.constrain_to_constant(leaf, Fr::from(4u64), Fr::zero());
+        cs.pop_namespace();
+        assert_eq!(cs.label_of(leaf), Some("merkle/level3/leaf"));
+
+        let failures = cs.check_satisfied().unwrap_err();
+        assert!(matches!(
+            &failures[..],
+            [Failure::Gate { namespace, .. }] if namespace == "merkle/level3"
+        ));
+        assert!(failures[0].to_string().starts_with(&format!(
+            "constraint 0 of the arithmetic gate on row {} in merkle/level3 ",
+            cs.size() - 1
+        )));
+    }
+
+    #[test]
+    fn namespaced_out_of_range() {
+        let mut cs = circuit::<Fr>();
+        cs.push_namespace("merkle");
+        let var = cs.alloc_and_assign(Fr::from(8u64));
+        cs.label(var, "leaf");
+        assert_eq!(
+            cs.range_gate(var, 3).unwrap_err().to_string(),
+            "value out of range: v6 (merkle/leaf) in merkle is not below 2^3"
+        );
+    }
+
+    #[test]
+    fn wrong_public_inputs() {
+        let mut cs = circuit::<Fr>();
+        let var = cs.alloc_and_assign(Fr::from(5u64));
+        cs.constrain_to_constant(var, Fr::zero(), Fr::from(5u64));
+        cs.create_range_gate(var, RangeType::U8).unwrap();
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        let mut public_inputs = cs.public_inputs().to_vec();
+        public_inputs[5] += Fr::one();
+        let failures = MockProver::run(&cs, &public_inputs).unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [Failure::Gate {
+                row: 5,
+                gate: "arithmetic",
+                ..
+            }]
+        ));
+
+        let too_many = vec![Fr::zero(); 1 + cs.size().next_power_of_two()];
+        assert!(matches!(
+            MockProver::run(&cs, &too_many).unwrap_err()[..],
+            [Failure::PublicInputs { .. }]
+        ));
+    }
+
+    #[test]
+    fn range_gate() {
+        let mut cs = circuit::<Fr>();
+        let cases = [(0u64, 1), (1, 1), (5, 3), (255, 8), (1000, 10), (1 << 20, 21)];
+        for &(value, num_bits) in &cases {
+            let var = cs.alloc_and_assign(Fr::from(value));
+            cs.range_gate(var, num_bits).unwrap();
+        }
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+    }
+
+    #[test]
+    fn range_gate_out_of_range() {
+        let mut cs = circuit::<Fr>();
+        let var = cs.alloc_and_assign(Fr::from(8u64));
+        assert!(matches!(
+            cs.range_gate(var, 3),
+            Err(Error::OutOfRange { bits: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn logic_gates() {
+        let mut cs = circuit::<Fr>();
+        let a = cs.alloc_and_assign(Fr::from(0b1011_0110u64));
+        let b = cs.alloc_and_assign(Fr::from(0b0101_1100u64));
+        let xor = cs.xor_gate(a, b, 8).unwrap();
+        let and = cs.and_gate(a, b, 8).unwrap();
+        assert!(matches!(
+            cs.xor_gate(a, b, 4),
+            Err(Error::OutOfRange { bits: 4, .. })
+        ));
+        assert_eq!(cs.assignment[&xor], Fr::from(0b1110_1010u64));
+        assert_eq!(cs.assignment[&and], Fr::from(0b0001_0100u64));
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        cs.assignment.insert(xor, Fr::from(0b1110_1011u64));
+        let failures = MockProver::run(&cs, cs.public_inputs()).unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [Failure::Gate {
+                gate: "logic",
+                constraint: 3,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn curve_addition() {
+        use ark_ec::{AffineCurve, ProjectiveCurve};
+        use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsParameters};
+
+        let g = EdwardsAffine::prime_subgroup_generator();
+        let h = g.mul(5u64).into_affine();
+        let mut cs = circuit::<Fr>();
+        let a = cs.alloc_point(&g);
+        let b = cs.alloc_point(&h);
+        let identity = cs.constant_point(&EdwardsAffine::zero());
+        let sum = cs.add_points::<EdwardsParameters>(a, b);
+        let double = cs.add_points::<EdwardsParameters>(a, a);
+        let same = cs.add_points::<EdwardsParameters>(a, identity);
+
+        let value = |p: Point| EdwardsAffine::new(cs.assignment[&p.x()], cs.assignment[&p.y()]);
+        assert_eq!(value(sum), g.mul(6u64).into_affine());
+        assert_eq!(value(double), g.mul(2u64).into_affine());
+        assert_eq!(value(same), g);
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        cs.assignment.insert(sum.x(), g.x);
+        let failures = MockProver::run(&cs, cs.public_inputs()).unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [Failure::Gate {
+                gate: "curve",
+                constraint: 2,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn curve_multiplication() {
+        use ark_ec::{AffineCurve, ProjectiveCurve};
+        use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsParameters};
+
+        let g = EdwardsAffine::prime_subgroup_generator();
+        let mut cs = circuit::<Fr>();
+        let a = cs.alloc_point(&g);
+        // 0b1101
+        let bits: Vec<_> = [true, false, true, true]
+            .iter()
+            .map(|bit| {
+                let var = cs.alloc_and_assign(Fr::from(*bit as u64));
+                cs.boolean_gate(var);
+                var
+            })
+            .collect();
+        let product = cs.mul_point::<EdwardsParameters>(a, &bits);
+        let zero = cs.mul_point::<EdwardsParameters>(a, &bits[1..2]);
+
+        let value = |p: Point| EdwardsAffine::new(cs.assignment[&p.x()], cs.assignment[&p.y()]);
+        assert_eq!(value(product), g.mul(13u64).into_affine());
+        assert_eq!(value(zero), EdwardsAffine::zero());
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+    }
+
+    #[test]
+    fn boolean_and_select() {
+        let mut cs = circuit::<Fr>();
+        let zero = cs.alloc_and_assign(Fr::zero());
+        let one = cs.alloc_and_assign(Fr::one());
+        cs.boolean_gate(zero);
+        cs.boolean_gate(one);
+        let a = cs.alloc_and_assign(Fr::from(3u64));
+        let b = cs.alloc_and_assign(Fr::from(5u64));
+        let picked_a = cs.select(one, a, b);
+        let picked_b = cs.select(zero, a, b);
+        assert_eq!(cs.assignment[&picked_a], Fr::from(3u64));
+        assert_eq!(cs.assignment[&picked_b], Fr::from(5u64));
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        let two = cs.alloc_and_assign(Fr::from(2u64));
+        cs.boolean_gate(two);
+        let row = cs.size() - 1;
+        let failures = MockProver::run(&cs, cs.public_inputs()).unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [Failure::Gate {
+                row: r,
+                gate: "arithmetic",
+                ..
+            }] if r == row
+        ));
+    }
+
+    #[test]
+    fn linear_combination_gate() {
+        let mut cs = circuit::<Fr>();
+        let terms: Vec<_> = (1..=8u64)
+            .map(|i| (cs.alloc_and_assign(Fr::from(i)), Fr::from(i)))
+            .collect();
+        let size = cs.size();
+        let sum = cs.create_linear_combination_gate(&terms, Fr::one());
+        // 1^2 + ... + 8^2 + 1
+        assert_eq!(cs.assignment[&sum], Fr::from(205u64));
+        assert_eq!(cs.size(), size + 3);
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        cs.assignment.insert(terms[4].0, Fr::zero());
+        assert!(MockProver::run(&cs, cs.public_inputs()).is_err());
+    }
+
+    #[test]
+    fn decompose() {
+        let mut cs = circuit::<Fr>();
+        let var = cs.alloc_and_assign(Fr::from(0b1011_0110u64));
+        let bits = cs.decompose(var, 10).unwrap();
+        let values: Vec<_> = bits.iter().map(|bit| cs.assignment[bit]).collect();
+        let expected: Vec<_> = [0u64, 1, 1, 0, 1, 1, 0, 1, 0, 0]
+            .iter()
+            .map(|bit| Fr::from(*bit))
+            .collect();
+        assert_eq!(values, expected);
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        // 3 is not a bit, though 3 * 2 + 0 * 4 still sums to the value.
+        cs.assignment.insert(bits[1], Fr::from(3u64));
+        cs.assignment.insert(bits[2], Fr::zero());
+        let failures = MockProver::run(&cs, cs.public_inputs()).unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [Failure::Gate {
+                gate: "arithmetic",
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn decompose_with_table() {
+        let mut cs = circuit::<Fr>();
+        let table = cs.add_range_table(8);
+        let var = cs.alloc_and_assign(Fr::from(0x1234_5678u64));
+        let chunks = cs.decompose_with_table(var, 32, table, 8).unwrap();
+        let values: Vec<_> = chunks.iter().map(|chunk| cs.assignment[chunk]).collect();
+        let expected: Vec<_> = [0x78u64, 0x56, 0x34, 0x12]
+            .iter()
+            .map(|chunk| Fr::from(*chunk))
+            .collect();
+        assert_eq!(values, expected);
+
+        let var = cs.alloc_and_assign(Fr::from(1000u64));
+        let chunks = cs.decompose_with_table(var, 10, table, 8).unwrap();
+        // the top chunk shifted to 8 bits is allocated last.
+        let shifted = Variable(cs.permutation.num_vars() - 1);
+        assert_eq!(cs.assignment[&chunks[1]], Fr::from(3u64));
+        assert_eq!(cs.assignment[&shifted], Fr::from(3u64 << 6));
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        // a top chunk of 4 fits in 8 bits but not in the 2 remaining ones.
+        cs.assignment.insert(var, Fr::from(1000u64 + 256));
+        cs.assignment.insert(chunks[1], Fr::from(4u64));
+        cs.assignment.insert(shifted, Fr::from(4u64 << 6));
+        let failures = MockProver::run(&cs, cs.public_inputs()).unwrap_err();
+        assert!(failures
+            .iter()
+            .any(|failure| matches!(failure, Failure::Gate { gate: "lookup", .. })));
+    }
+
+    #[test]
+    fn decompose_out_of_range() {
+        let mut cs = circuit::<Fr>();
+        let var = cs.alloc_and_assign(Fr::from(8u64));
+        assert!(matches!(
+            cs.decompose(var, 3),
+            Err(Error::OutOfRange { bits: 3, .. })
+        ));
+        let table = cs.add_range_table(2);
+        assert!(matches!(
+            cs.decompose_with_table(var, 3, table, 2),
+            Err(Error::OutOfRange { bits: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn memory() {
+        let mut cs = circuit::<Fr>();
+        let init: Vec<_> = (0..4u64)
+            .map(|i| cs.alloc_and_assign(Fr::from(10 * i)))
+            .collect();
+        let ram = cs.add_memory(&init);
+        let one = cs.alloc_and_assign(Fr::one());
+        let two = cs.alloc_and_assign(Fr::from(2u64));
+        let seven = cs.alloc_and_assign(Fr::from(7u64));
+        let before = cs.memory_read(ram, two);
+        cs.memory_write(ram, two, seven);
+        let after = cs.memory_read(ram, two);
+        let other = cs.memory_read(ram, one);
+        assert_eq!(cs.assignment[&before], Fr::from(20u64));
+        assert_eq!(cs.assignment[&after], Fr::from(7u64));
+        assert_eq!(cs.assignment[&other], Fr::from(10u64));
+
+        // the accesses have no sorted records until the memory is closed.
+        assert_eq!(
+            MockProver::run(&cs, cs.public_inputs()),
+            Err(vec![Failure::Memory { memory: ram }])
+        );
+        cs.close_memory(ram).unwrap();
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        // reading the value from before the write.
+        cs.assignment.insert(after, Fr::from(20u64));
+        assert_eq!(
+            MockProver::run(&cs, cs.public_inputs()),
+            Err(vec![Failure::Memory { memory: ram }])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "address out of range")]
+    fn memory_out_of_range() {
+        let mut cs = circuit::<Fr>();
+        let zero = cs.alloc_and_assign(Fr::zero());
+        let ram = cs.add_memory(&[zero, zero]);
+        let two = cs.alloc_and_assign(Fr::from(2u64));
+        cs.memory_read(ram, two);
+    }
+
+    #[test]
+    fn unsatisfied_lookup() {
+        let mut cs = circuit::<Fr>();
+        let xor = cs.add_xor_table(2);
+        let a = cs.alloc_and_assign(Fr::from(1u64));
+        let b = cs.alloc_and_assign(Fr::from(3u64));
+        let c = cs.lookup(xor, a, b);
+        assert_eq!(cs.assignment[&c], Fr::from(2u64));
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        let four = cs.alloc_and_assign(Fr::from(4u64));
+        cs.add_lookup_gate(xor, four, b, c);
+        let row = cs.size() - 1;
+        let failures = MockProver::run(&cs, cs.public_inputs()).unwrap_err();
+        assert!(matches!(
+            failures[..],
+            [Failure::Gate {
+                row: r,
+                gate: "lookup",
+                ..
+            }] if r == row
+        ));
+    }
+}
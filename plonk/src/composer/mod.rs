@@ -1,15 +1,57 @@
 use ark_ff::FftField as Field;
-use ark_std::vec::Vec;
+use ark_std::{format, string::String, vec::Vec};
 
 use crate::Map;
 
 mod permutation;
+pub(crate) use permutation::wire_columns;
 use permutation::Permutation;
 
 mod arithmetic;
 
+mod append;
+pub use append::VariableMap;
+
+mod export;
+
+mod witness;
+pub use witness::{WitnessFile, MAGIC as WITNESS_MAGIC};
+
+mod compile;
+pub use compile::{CircuitBuilder, CompiledCircuit, WitnessFiller};
+
+mod circuit_file;
+pub use circuit_file::MAGIC as CIRCUIT_MAGIC;
+
+mod optimize;
+pub use optimize::Optimization;
+
+mod stats;
+pub use stats::Stats;
+
+mod lookup;
+pub use lookup::Table;
+
+mod logic;
+
+mod decompose;
+
+mod curve;
+pub use curve::Point;
+
+mod kzg;
+pub use kzg::DeferredPairing;
+
+mod ipa;
+pub use ipa::IpaScalars;
+
+mod memory;
+pub use memory::Memory;
+use memory::MemoryState;
+
 mod synthesize;
 pub(crate) mod range;
+pub(crate) mod mock;
 mod mimc;
 
 pub use synthesize::{Error, Selectors, Witnesses};
@@ -21,13 +63,19 @@ pub struct Variable(usize);
 #[derive(Debug)]
 pub struct Composer<F: Field> {
     n: usize,
+    /// The number of wire columns, see `with_wires`.
+    wires: usize,
 
     q_0: Vec<F>,
     q_1: Vec<F>,
     q_2: Vec<F>,
     q_3: Vec<F>,
+    /// The coefficient of `w_4`, which only the wide gates use.
+    q_4: Vec<F>,
     q_m: Vec<F>,
     q_c: Vec<F>,
+    /// The coefficient of `w_0` of the next row in the arithmetic gate.
+    q_next: Vec<F>,
 
     q_arith: Vec<F>,
 
@@ -37,29 +85,71 @@ pub struct Composer<F: Field> {
     w_1: Vec<Variable>,
     w_2: Vec<Variable>,
     w_3: Vec<Variable>,
+    w_4: Vec<Variable>,
 
     null_var: Variable,
     permutation: Permutation<F>,
     assignment: Map<Variable, F>,
+    /// Whether values are dropped instead of assigned, see `new_counting`.
+    counting: bool,
 
     q_range: Vec<F>,
 
     q_mimc: Vec<F>,
     //q_mimc_c: Vec<F>,
     mimc_c_container: MimcC<F>,
+
+    /// The table of the lookup on each row, from 1, or zero.
+    q_lookup: Vec<F>,
+    /// The rows of the tables, see `add_table`.
+    tables: Vec<Vec<[F; 3]>>,
+
+    /// `1` on the rows of an XOR and `2` on those of an AND, see `xor_gate`.
+    q_logic: Vec<F>,
+
+    /// `1` on the rows of a point addition, see `add_points`.
+    q_curve: Vec<F>,
+    /// The coefficients `a` and `d` of the curve of a point addition.
+    q_curve_a: Vec<F>,
+    q_curve_d: Vec<F>,
+
+    /// The memory `k` of a row of accesses, from 1, `-k` on its sorted
+    /// rows, or zero, see `add_memory`.
+    q_memory: Vec<F>,
+    /// The accesses of each memory.
+    memories: Vec<MemoryState<F>>,
+
+    /// The namespaces entered, see `push_namespace`.
+    namespace: Vec<String>,
+    /// The namespace path of the gates from a row on.
+    namespaces: Vec<(usize, String)>,
+    /// The labels of variables, within the namespace they were given in,
+    /// see `label`.
+    labels: Map<Variable, String>,
+}
+
+#[cfg(feature = "zeroize")]
+impl<F: Field> Drop for Composer<F> {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        self.assignment.values_mut().for_each(Zeroize::zeroize);
+    }
 }
 
 impl<F: Field> Composer<F> {
     pub fn new() -> Self {
         let mut cs = Composer {
             n: 0,
+            wires: 4,
 
             q_0: Vec::new(),
             q_1: Vec::new(),
             q_2: Vec::new(),
             q_3: Vec::new(),
+            q_4: Vec::new(),
             q_m: Vec::new(),
             q_c: Vec::new(),
+            q_next: Vec::new(),
             pi: Vec::new(),
 
             q_arith: Vec::new(),
@@ -68,28 +158,85 @@ impl<F: Field> Composer<F> {
             w_1: Vec::new(),
             w_2: Vec::new(),
             w_3: Vec::new(),
+            w_4: Vec::new(),
 
             null_var: Variable(0),
             permutation: Permutation::new(),
             assignment: Map::new(),
+            counting: false,
 
             q_range: Vec::new(),
             q_mimc: Vec::new(),
             //q_mimc_c: Vec::new(),
             mimc_c_container: MimcC::new(),
+
+            q_lookup: Vec::new(),
+            tables: Vec::new(),
+
+            q_logic: Vec::new(),
+
+            q_curve: Vec::new(),
+            q_curve_a: Vec::new(),
+            q_curve_d: Vec::new(),
+
+            q_memory: Vec::new(),
+            memories: Vec::new(),
+
+            namespace: Vec::new(),
+            namespaces: Vec::new(),
+            labels: Map::new(),
         };
         cs.null_var = cs.alloc_and_assign(F::zero());
 
         cs
     }
 
+    /// A composer that only counts gates. The values given to it are
+    /// dropped instead of assigned, so that a circuit can be sized, e.g.
+    /// with `srs_degree`, before its witnesses exist. It cannot be
+    /// synthesized.
+    pub fn new_counting() -> Self {
+        let mut cs = Self::new();
+        cs.counting = true;
+        cs.assignment.clear();
+        cs
+    }
+
+    /// The composer with `wires` wire columns instead of four, before any
+    /// gate: three, `w_1..w_3`, for fewer commitments and openings per proof,
+    /// or five, with `w_4`, for the wide gates, e.g. a linear combination of
+    /// four terms per row, see `create_wide_add_gate`.
+    ///
+    /// The keys take one coset `k` per wire. The gates on `w_0` of a
+    /// three-wire composer, i.e. those with an `aux` and the range, MiMC,
+    /// logic, curve and memory gates, make `compose` fail, and so do the
+    /// wide gates without five wires. Other counts, or a composer with
+    /// gates already, are rejected.
+    pub fn with_wires(mut self, wires: usize) -> Result<Self, Error> {
+        if !(3..=5).contains(&wires) {
+            return Err(Error::UnsupportedWires { wires });
+        }
+        if self.n != 0 {
+            return Err(Error::WiresAfterGates);
+        }
+        self.wires = wires;
+        Ok(self)
+    }
+
+    /// The number of wire columns, four unless set with `with_wires`.
+    pub fn wires(&self) -> usize {
+        self.wires
+    }
+
     pub fn size(&self) -> usize {
         self.n
     }
 
     pub fn alloc_and_assign(&mut self, value: F) -> Variable {
         let var = self.permutation.alloc();
-        self.assignment.insert(var, value);
+        if !self.counting {
+            self.assignment.insert(var, value);
+        }
 
         var
     }
@@ -97,6 +244,72 @@ impl<F: Field> Composer<F> {
     pub fn init_mimc(&mut self, n_rounds: usize, mimc_c: Vec<F>) {
         self.mimc_c_container.init_mimc_c(n_rounds,mimc_c);
     }
+
+    /// Puts the gates created from now on in the namespace `name`, within
+    /// the current one, until `pop_namespace`. Namespaces only label gates,
+    /// in the exports of the circuit, see `write_dot`, in the failures of
+    /// `check_satisfied` and in the panics of out of range values.
+    pub fn push_namespace(&mut self, name: impl Into<String>) {
+        self.namespace.push(name.into());
+        self.namespaces.push((self.n, self.namespace_path()));
+    }
+
+    /// Leaves the namespace entered last.
+    pub fn pop_namespace(&mut self) {
+        self.namespace.pop();
+        self.namespaces.push((self.n, self.namespace_path()));
+    }
+
+    /// Labels `var` with `name` in the current namespace, e.g. `leaf` in
+    /// `merkle/level3` as `merkle/level3/leaf`, for the same reports as the
+    /// namespaces of gates.
+    pub fn label(&mut self, var: Variable, name: impl Into<String>) {
+        let name = name.into();
+        let path = self.namespace_path();
+        let label = if path.is_empty() {
+            name
+        } else {
+            format!("{}/{}", path, name)
+        };
+        self.labels.insert(var, label);
+    }
+
+    /// The label of `var`, see `label`.
+    pub fn label_of(&self, var: Variable) -> Option<&str> {
+        self.labels.get(&var).map(|label| label.as_str())
+    }
+
+    /// The namespaces entered, joined by `/`.
+    fn namespace_path(&self) -> String {
+        self.namespace.join("/")
+    }
+
+    /// The namespace path of the gate on `row`.
+    pub(crate) fn namespace_of(&self, row: usize) -> &str {
+        self.namespaces
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= row)
+            .map_or("", |(_, path)| path)
+    }
+
+    /// `var` for reports, e.g. `v5 (merkle/level3/leaf)`.
+    pub(crate) fn variable_name(&self, var: Variable) -> String {
+        match self.label_of(var) {
+            Some(label) => format!("v{} ({})", var.0, label),
+            None => format!("v{}", var.0),
+        }
+    }
+
+    /// ` in` the current namespace, for panics while adding gates, or
+    /// nothing outside of any namespace.
+    pub(crate) fn in_namespace(&self) -> String {
+        if self.namespace.is_empty() {
+            String::new()
+        } else {
+            format!(" in {}", self.namespace_path())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -124,7 +337,9 @@ mod tests {
         let pi = pad_to_size(cs.public_inputs(), s.size());
 
         let witnesses = cs.synthesize().unwrap();
-        let Witnesses { w_0, w_1, w_2, w_3 } = witnesses;
+        let Witnesses {
+            w_0, w_1, w_2, w_3, ..
+        } = witnesses;
         assert_eq!(w_0.len(), s.q_0.len());
 
         // arithmetic
@@ -167,12 +382,157 @@ mod tests {
         let denumerator: Fr = (0..s.size())
             .into_iter()
             .map(|i| {
-                (w_0[i] + beta * s.sigma_0[i] + gamma)
-                    * (w_1[i] + beta * s.sigma_1[i] + gamma)
-                    * (w_2[i] + beta * s.sigma_2[i] + gamma)
-                    * (w_3[i] + beta * s.sigma_3[i] + gamma)
+                (w_0[i] + beta * s.sigmas[0][i] + gamma)
+                    * (w_1[i] + beta * s.sigmas[1][i] + gamma)
+                    * (w_2[i] + beta * s.sigmas[2][i] + gamma)
+                    * (w_3[i] + beta * s.sigmas[3][i] + gamma)
             })
             .product();
         assert_eq!(numerator, denumerator);
     }
+
+    #[test]
+    fn count_gates() {
+        use super::range::RangeType;
+        use blake2::Blake2s;
+
+        fn build(cs: &mut Composer<Fr>) {
+            let x = cs.alloc_and_assign(Fr::from(200_u64));
+            let y = cs.alloc_and_assign(Fr::from(400_u64));
+            cs.create_range_gate(x, RangeType::U8).unwrap();
+            cs.create_add_gate(
+                (x, Fr::one()),
+                (x, Fr::one()),
+                y,
+                None,
+                Fr::zero(),
+                Fr::zero(),
+            );
+        }
+
+        let mut cs = Composer::new();
+        build(&mut cs);
+        let mut counted = Composer::new_counting();
+        build(&mut counted);
+
+        assert_eq!(counted.size(), cs.size());
+        assert_eq!(counted.domain_size().unwrap(), cs.size().next_power_of_two());
+        assert_eq!(counted.srs_degree().unwrap(), cs.srs_degree().unwrap());
+        let ks = crate::tests::ks();
+        assert_eq!(
+            counted.circuit_hash::<Blake2s>(&ks).unwrap(),
+            cs.circuit_hash::<Blake2s>(&ks).unwrap()
+        );
+        assert!(matches!(counted.synthesize(), Err(Error::Unassigned)));
+    }
+
+    #[test]
+    fn append() {
+        use super::mock::MockProver;
+
+        let mut sub = Composer::new();
+        let x = sub.alloc_and_assign(Fr::from(3_u64));
+        let y = sub.alloc_and_assign(Fr::from(9_u64));
+        sub.create_mul_gate(x, x, y, None, Fr::one(), Fr::zero(), Fr::zero());
+        sub.constrain_to_constant(y, Fr::zero(), Fr::from(9_u64));
+
+        let mut cs = crate::tests::circuit::<Fr>();
+        let size = cs.size();
+        let mut pi = cs.public_inputs().to_vec();
+        let map = cs.append(&sub);
+        pi.extend_from_slice(sub.public_inputs());
+
+        assert_eq!(cs.size(), size + sub.size());
+        assert_eq!(cs.public_inputs(), &pi[..]);
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        let three = cs.alloc_and_assign(Fr::from(3_u64));
+        cs.assert_equal(three, map.get(x));
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+        cs.assert_equal(three, map.get(y));
+        assert!(MockProver::run(&cs, cs.public_inputs()).is_err());
+    }
+
+    #[test]
+    fn export() {
+        let mut cs = Composer::<Fr>::new();
+        let x = cs.alloc_and_assign(Fr::from(3_u64));
+        let y = cs.alloc_and_assign(Fr::from(9_u64));
+        let free = cs.alloc_and_assign(Fr::from(5_u64));
+        cs.push_namespace("square");
+        cs.create_mul_gate(x, x, y, None, Fr::one(), Fr::zero(), Fr::zero());
+        cs.pop_namespace();
+        // `free` is on a wire whose selector is zero.
+        cs.create_add_gate(
+            (y, Fr::one()),
+            (free, Fr::zero()),
+            y,
+            None,
+            Fr::zero(),
+            Fr::zero(),
+        );
+        assert_eq!(cs.unconstrained(), vec![free]);
+
+        let mut sub = Composer::new();
+        sub.push_namespace("inner");
+        sub.constrain_to_constant(sub.null_var, Fr::zero(), Fr::zero());
+        cs.push_namespace("outer");
+        cs.append(&sub);
+        cs.pop_namespace();
+        assert_eq!(cs.namespace_of(0), "square");
+        assert_eq!(cs.namespace_of(1), "");
+        assert_eq!(cs.namespace_of(2), "outer/inner");
+
+        let json: serde_json::Value = serde_json::from_str(&cs.to_json()).unwrap();
+        assert_eq!(json["gates"][0]["types"][0], "arithmetic");
+        assert_eq!(json["gates"][2]["namespace"], "outer/inner");
+        assert_eq!(json["gates"][0]["wires"][0], serde_json::Value::Null);
+        assert_eq!(json["variables"][2]["constrained"], false);
+
+        let dot = cs.to_dot();
+        assert!(dot.starts_with("graph circuit {"));
+        assert!(dot.contains("label=\"outer/inner\";"));
+        assert!(dot.contains("v3 (unconstrained)"));
+    }
+
+    #[test]
+    fn witness_file() {
+        use super::mock::MockProver;
+        use blake2::Blake2s;
+
+        fn build(cs: &mut Composer<Fr>, value: u64) {
+            let x = cs.alloc_and_assign(Fr::from(value));
+            let y = cs.alloc_and_assign(Fr::from(value * value));
+            cs.create_mul_gate(x, x, y, None, Fr::one(), Fr::zero(), Fr::zero());
+            cs.constrain_to_constant(y, Fr::zero(), Fr::from(value * value));
+        }
+        let ks = crate::tests::ks();
+
+        let mut cs = Composer::new();
+        build(&mut cs, 3);
+        let mut bytes = Vec::new();
+        cs.export_witness::<Blake2s>(&ks)
+            .unwrap()
+            .write(&mut bytes)
+            .unwrap();
+        assert_eq!(bytes[..8], WITNESS_MAGIC);
+        let witness = WitnessFile::read(&bytes[..]).unwrap();
+
+        let mut counted = Composer::new_counting();
+        build(&mut counted, 0);
+        counted.import_witness::<Blake2s>(&witness, &ks).unwrap();
+        assert_eq!(counted.public_inputs(), cs.public_inputs());
+        assert_eq!(MockProver::run(&counted, counted.public_inputs()), Ok(()));
+
+        let mut other = Composer::new_counting();
+        build(&mut other, 0);
+        other.constrain_to_constant(other.null_var, Fr::zero(), Fr::zero());
+        assert!(matches!(
+            other.import_witness::<Blake2s>(&witness, &ks),
+            Err(Error::WitnessMismatch { check: "circuit hash" })
+        ));
+
+        bytes[0] ^= 1;
+        assert!(WitnessFile::<Fr>::read(&bytes[..]).is_err());
+    }
 }
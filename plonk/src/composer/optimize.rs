@@ -0,0 +1,493 @@
+//! An optimization pass over a built circuit, to run before keygen, as
+//! gadgets leave avoidable gates in it that inflate the domain.
+//!
+//! `Composer::optimize` first puts the null variable on the wires that enter
+//! no equation, see `Composer::unconstrained`, as their copy constraints
+//! check nothing. It then merges the variables constrained to the same
+//! nonzero constant into one, dropping the other constant gates, and folds
+//! the gate of a variable used by exactly one other gate into that gate when
+//! the terms of both fit on one row, e.g. the additions of a chain. Last, it
+//! drops the folded rows and the variables left on no wire, and numbers the
+//! others again in order.
+//!
+//! Only arithmetic rows without `q_next`, that the row before does not read,
+//! are removed or rewritten, so that the custom gates still read the rows
+//! they were built with. Constants of zero are left alone, as `var == pi` is
+//! how a public input is taken. The pass only looks at the shape of the
+//! circuit, so a counting composer and an assigned one of the same circuit
+//! are optimized alike. The public inputs move with the rows, see
+//! `Optimization::public_inputs`.
+
+use ark_std::{vec, vec::Vec};
+
+use crate::composer::permutation::Permutation;
+use crate::composer::{wire_columns, Composer, Field, Variable};
+use crate::Map;
+
+/// What `Composer::optimize` did to a circuit: where its variables and the
+/// public inputs of its rows went.
+#[derive(Debug, Clone)]
+pub struct Optimization<F: Field> {
+    /// The new variable of each variable, by index, if it is still used.
+    variables: Vec<Option<Variable>>,
+    /// The public input of each row, as a combination of those of the rows
+    /// before.
+    rows: Vec<Vec<(usize, F)>>,
+    /// The rows of merged constant gates, whose public inputs must agree.
+    equal: Vec<(usize, usize)>,
+    /// The number of rows before.
+    size: usize,
+}
+
+impl<F: Field> Optimization<F> {
+    /// The variable that `var` became, if it is still used. A variable
+    /// merged into another becomes that one.
+    pub fn get(&self, var: Variable) -> Option<Variable> {
+        self.variables.get(var.0).copied().flatten()
+    }
+
+    pub fn removed_gates(&self) -> usize {
+        self.size - self.rows.len()
+    }
+
+    pub fn removed_variables(&self) -> usize {
+        self.variables.iter().filter(|var| var.is_none()).count()
+    }
+
+    /// The public inputs of the optimized circuit, from `public_inputs` of
+    /// the circuit before, or `None` if there are more of them than rows, or
+    /// if two merged constant gates take different public inputs, as the
+    /// optimized circuit assumes that their variables are equal.
+    pub fn public_inputs(&self, public_inputs: &[F]) -> Option<Vec<F>> {
+        if public_inputs.len() > self.size {
+            return None;
+        }
+        let pi = |row: usize| public_inputs.get(row).copied().unwrap_or_else(F::zero);
+        if self.equal.iter().any(|&(a, b)| pi(a) != pi(b)) {
+            return None;
+        }
+        Some(self.combine(public_inputs))
+    }
+
+    /// The public input of each row, from `public_inputs` of the rows
+    /// before.
+    fn combine(&self, public_inputs: &[F]) -> Vec<F> {
+        let pi = |row: usize| public_inputs.get(row).copied().unwrap_or_else(F::zero);
+        self.rows
+            .iter()
+            .map(|terms| {
+                terms
+                    .iter()
+                    .fold(F::zero(), |sum, &(row, c)| sum + c * pi(row))
+            })
+            .collect()
+    }
+}
+
+impl<F: Field> Composer<F> {
+    /// Optimizes the circuit, see the module docs. The variables held before
+    /// are numbered again, see `Optimization::get`, and the public inputs
+    /// are those of `Optimization::public_inputs`. The memories must be
+    /// closed.
+    pub fn optimize(&mut self) -> Optimization<F> {
+        assert!(
+            self.memories.iter().all(|memory| memory.closed),
+            "optimized composer with an open memory"
+        );
+        let size = self.n;
+        let free: Vec<bool> = (0..size).map(|row| self.is_free(row)).collect();
+        let mut removed = vec![false; size];
+        let mut rows: Vec<Vec<(usize, F)>> = (0..size).map(|row| vec![(row, F::one())]).collect();
+        let mut equal = Vec::new();
+
+        self.clear_dead_wires();
+        let merged = self.merge_constants(&free, &mut removed, &mut equal);
+        self.fold_gates(&free, &mut removed, &mut rows);
+
+        let kept: Vec<usize> = (0..size).filter(|row| !removed[*row]).collect();
+        let mut optimization = Optimization {
+            variables: Vec::new(),
+            rows: kept.iter().map(|&row| rows[row].clone()).collect(),
+            equal,
+            size,
+        };
+        self.pi = optimization.combine(&self.pi);
+        optimization.variables = self.compact(&kept, &merged);
+        optimization
+    }
+
+    /// Whether `row` is an arithmetic gate only, without `q_next`, that the
+    /// row before does not read, so that it can be removed or rewritten.
+    fn is_free(&self, row: usize) -> bool {
+        self.gate_types(row) == ["arithmetic"]
+            && self.q_next[row].is_zero()
+            && (row == 0 || !self.reads_next(row - 1))
+    }
+
+    /// Whether `row` reads wires of the next row, see `is_constrained`.
+    fn reads_next(&self, row: usize) -> bool {
+        !self.q_range[row].is_zero()
+            || !self.q_mimc[row].is_zero()
+            || !self.q_logic[row].is_zero()
+            || !self.q_curve[row].is_zero()
+            || !(self.q_arith[row] * self.q_next[row]).is_zero()
+    }
+
+    /// The wires `w_0..w_4` of `row`, with their coefficients `q_0..q_4`.
+    fn terms(&self, row: usize) -> [(Variable, F); 5] {
+        [
+            (self.w_0[row], self.q_0[row]),
+            (self.w_1[row], self.q_1[row]),
+            (self.w_2[row], self.q_2[row]),
+            (self.w_3[row], self.q_3[row]),
+            (self.w_4[row], self.q_4[row]),
+        ]
+    }
+
+    fn set_terms(&mut self, row: usize, terms: [(Variable, F); 5]) {
+        let [(w_0, q_0), (w_1, q_1), (w_2, q_2), (w_3, q_3), (w_4, q_4)] = terms;
+        self.w_0[row] = w_0;
+        self.w_1[row] = w_1;
+        self.w_2[row] = w_2;
+        self.w_3[row] = w_3;
+        self.w_4[row] = w_4;
+        self.q_0[row] = q_0;
+        self.q_1[row] = q_1;
+        self.q_2[row] = q_2;
+        self.q_3[row] = q_3;
+        self.q_4[row] = q_4;
+    }
+
+    /// Puts the null variable on the wires that enter no equation.
+    fn clear_dead_wires(&mut self) {
+        for row in 0..self.n {
+            let mut terms = self.terms(row);
+            for (column, term) in terms.iter_mut().enumerate() {
+                if !self.is_constrained(row, column) {
+                    term.0 = self.null_var;
+                }
+            }
+            self.set_terms(row, terms);
+        }
+    }
+
+    /// The variable of `row` and its value, if `row` only constrains one
+    /// variable to a nonzero constant.
+    fn constant(&self, row: usize) -> Option<(Variable, F)> {
+        if !self.q_m[row].is_zero() || self.q_c[row].is_zero() {
+            return None;
+        }
+        let terms = self.terms(row);
+        let mut nonzero = terms.iter().filter(|(_, c)| !c.is_zero());
+        match (nonzero.next(), nonzero.next()) {
+            (Some(&(var, c)), None) if var != self.null_var => Some((var, -self.q_c[row] / c)),
+            _ => None,
+        }
+    }
+
+    /// Merges the variables constrained to the same constant by free rows
+    /// into the first one, and removes the rows of the others. Returns the
+    /// merged variables, with the one each was merged into.
+    fn merge_constants(
+        &mut self,
+        free: &[bool],
+        removed: &mut [bool],
+        equal: &mut Vec<(usize, usize)>,
+    ) -> Map<Variable, Variable> {
+        let mut constants: Map<F, (Variable, usize)> = Map::new();
+        // the constant of each variable met, so that one variable is not
+        // merged into two.
+        let mut values: Map<Variable, F> = Map::new();
+        let mut merged = Map::new();
+        for row in (0..self.n).filter(|row| free[*row]) {
+            let (var, value) = match self.constant(row) {
+                Some(constant) => constant,
+                None => continue,
+            };
+            match (constants.get(&value).copied(), values.get(&var).copied()) {
+                (None, None) => {
+                    constants.insert(value, (var, row));
+                    values.insert(var, value);
+                }
+                (Some((first, first_row)), None) => {
+                    merged.insert(var, first);
+                    values.insert(var, value);
+                    removed[row] = true;
+                    equal.push((row, first_row));
+                }
+                (Some((_, first_row)), Some(other)) if other == value => {
+                    removed[row] = true;
+                    equal.push((row, first_row));
+                }
+                _ => {}
+            }
+        }
+
+        for row in 0..self.n {
+            let mut terms = self.terms(row);
+            for term in terms.iter_mut() {
+                if let Some(first) = merged.get(&term.0) {
+                    term.0 = *first;
+                }
+            }
+            self.set_terms(row, terms);
+        }
+        merged
+    }
+
+    /// Folds each free row into the other row of a variable on exactly two
+    /// rows, by solving the row for it, when the terms of both fit on one
+    /// row. Labeled variables are kept.
+    fn fold_gates(&mut self, free: &[bool], removed: &mut [bool], rows: &mut [Vec<(usize, F)>]) {
+        let mut uses: Map<Variable, Vec<usize>> = Map::new();
+        for row in (0..self.n).filter(|row| !removed[*row]) {
+            for (var, _) in self.terms(row).iter() {
+                if *var != self.null_var {
+                    uses.entry(*var).or_default().push(row);
+                }
+            }
+        }
+
+        for row in 0..self.n {
+            if removed[row] || !free[row] || !self.q_m[row].is_zero() {
+                continue;
+            }
+            for (t, _) in self.terms(row).iter() {
+                let target = match uses.get(t) {
+                    Some(used) if used.len() == 2 && !self.labels.contains_key(t) => {
+                        if used[0] == row {
+                            used[1]
+                        } else {
+                            used[0]
+                        }
+                    }
+                    _ => continue,
+                };
+                if target == row || !free[target] || !self.q_m[target].is_zero() {
+                    continue;
+                }
+                let (terms, k) = match self.folded(row, target, *t) {
+                    Some(folded) => folded,
+                    None => continue,
+                };
+
+                for r in [row, target].iter() {
+                    for (var, _) in self.terms(*r).iter() {
+                        if let Some(used) = uses.get_mut(var) {
+                            used.retain(|u| *u != row && *u != target);
+                        }
+                    }
+                }
+                // on the columns of the composer, in order.
+                let mut row_terms = [(self.null_var, F::zero()); 5];
+                for (term, &column) in terms.iter().zip(wire_columns(self.wires)) {
+                    row_terms[column] = *term;
+                }
+                self.set_terms(target, row_terms);
+                for (var, _) in terms.iter() {
+                    if *var != self.null_var {
+                        uses.entry(*var).or_default().push(target);
+                    }
+                }
+                let q_c = self.q_c[row];
+                self.q_c[target] += k * q_c;
+                let scaled: Vec<_> = rows[row].iter().map(|&(r, c)| (r, k * c)).collect();
+                rows[target].extend(scaled);
+                removed[row] = true;
+                break;
+            }
+        }
+    }
+
+    /// The terms of `target` with `t` replaced by its value from `row`,
+    /// and the factor of `row` in them, if they fit on one row.
+    fn folded(&self, row: usize, target: usize, t: Variable) -> Option<(Vec<(Variable, F)>, F)> {
+        let coefficient = |row: usize| {
+            self.terms(row)
+                .iter()
+                .find(|(var, c)| *var == t && !c.is_zero())
+                .map(|(_, c)| *c)
+        };
+        let k = -coefficient(target)? / coefficient(row)?;
+
+        let others = |row: usize| {
+            self.terms(row)
+                .iter()
+                .copied()
+                .filter(|(var, c)| *var != t && !c.is_zero())
+                .collect::<Vec<_>>()
+        };
+        let mut terms = others(target);
+        for (var, c) in others(row) {
+            match terms.iter_mut().find(|(v, _)| *v == var) {
+                Some(term) => term.1 += k * c,
+                None => terms.push((var, k * c)),
+            }
+        }
+        terms.retain(|(_, c)| !c.is_zero());
+        if terms.len() > self.wires {
+            None
+        } else {
+            Some((terms, k))
+        }
+    }
+
+    /// Keeps the rows `kept` and the variables on their wires, numbered in
+    /// order, and returns the new variable of each variable.
+    fn compact(
+        &mut self,
+        kept: &[usize],
+        merged: &Map<Variable, Variable>,
+    ) -> Vec<Option<Variable>> {
+        let num_vars = self.permutation.num_vars();
+        let mut used = vec![false; num_vars];
+        used[self.null_var.0] = true;
+        for &row in kept {
+            for (var, _) in self.terms(row).iter() {
+                used[var.0] = true;
+            }
+        }
+
+        let mut permutation = Permutation::new();
+        let mut variables = vec![None; num_vars];
+        for (var, used) in variables.iter_mut().zip(&used) {
+            if *used {
+                *var = Some(permutation.alloc());
+            }
+        }
+        for (var, first) in merged {
+            variables[var.0] = variables[first.0];
+        }
+        let null = self.null_var;
+        let map = |var: Variable| variables[var.0].unwrap_or(null);
+
+        for (i, &row) in kept.iter().enumerate() {
+            permutation.insert_gate(
+                map(self.w_0[row]),
+                map(self.w_1[row]),
+                map(self.w_2[row]),
+                map(self.w_3[row]),
+                i,
+            );
+            if map(self.w_4[row]) != null {
+                permutation.insert(map(self.w_4[row]), 4, i);
+            }
+        }
+        self.permutation = permutation;
+        self.w_0 = kept.iter().map(|&row| map(self.w_0[row])).collect();
+        self.w_1 = kept.iter().map(|&row| map(self.w_1[row])).collect();
+        self.w_2 = kept.iter().map(|&row| map(self.w_2[row])).collect();
+        self.w_3 = kept.iter().map(|&row| map(self.w_3[row])).collect();
+        self.w_4 = kept.iter().map(|&row| map(self.w_4[row])).collect();
+        for selector in self.gate_selectors_mut().iter_mut() {
+            let values = kept.iter().map(|&row| selector[row]).collect();
+            **selector = values;
+        }
+
+        self.assignment = self
+            .assignment
+            .iter()
+            .filter(|(var, _)| used[var.0])
+            .map(|(var, value)| (map(*var), *value))
+            .collect();
+        let mut labels = Map::new();
+        for (var, label) in self.labels.iter().filter(|(var, _)| used[var.0]) {
+            labels.insert(map(*var), label.clone());
+        }
+        // a merged variable lends its label to the one it was merged into.
+        for (var, label) in &self.labels {
+            if let Some(new) = variables[var.0] {
+                labels.entry(new).or_insert_with(|| label.clone());
+            }
+        }
+        self.labels = labels;
+
+        for (start, _) in self.namespaces.iter_mut() {
+            *start = match kept.binary_search(start) {
+                Ok(i) | Err(i) => i,
+            };
+        }
+        self.n = kept.len();
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, Zero};
+
+    use crate::composer::mock::MockProver;
+
+    use super::*;
+
+    /// `x + 2 == y` for a public `y`, by a chain of additions of two
+    /// constant gates of one, with an unused variable.
+    fn circuit(cs: &mut Composer<Fr>, x: u64) -> [Variable; 4] {
+        let var_x = cs.alloc_and_assign(Fr::from(x));
+        cs.label(var_x, "x");
+        let unused = cs.alloc_and_assign(Fr::from(5u64));
+        let a = cs.alloc_and_assign(Fr::one());
+        cs.constrain_to_constant(a, Fr::one(), Fr::zero());
+        let b = cs.alloc_and_assign(Fr::one());
+        cs.constrain_to_constant(b, Fr::one(), Fr::zero());
+
+        let s = cs.alloc_and_assign(Fr::from(x + 1));
+        cs.create_add_gate(
+            (var_x, Fr::one()),
+            (a, Fr::one()),
+            s,
+            None,
+            Fr::zero(),
+            Fr::zero(),
+        );
+        let y = cs.alloc_and_assign(Fr::from(x + 2));
+        cs.create_add_gate(
+            (s, Fr::one()),
+            (b, Fr::one()),
+            y,
+            None,
+            Fr::zero(),
+            Fr::zero(),
+        );
+        cs.constrain_to_constant(y, Fr::zero(), Fr::from(x + 2));
+        [var_x, unused, b, y]
+    }
+
+    #[test]
+    fn optimize() {
+        let mut cs = Composer::new();
+        let [x, unused, b, y] = circuit(&mut cs, 3);
+        let pi = cs.public_inputs().to_vec();
+        assert_eq!(cs.size(), 5);
+        assert_eq!(MockProver::run(&cs, &pi), Ok(()));
+
+        let optimization = cs.optimize();
+        assert_eq!(cs.size(), 1);
+        assert_eq!(optimization.removed_gates(), 4);
+        assert_eq!(optimization.removed_variables(), 5);
+        assert_eq!(optimization.get(x), Some(Variable(1)));
+        assert_eq!(cs.label_of(Variable(1)), Some("x"));
+        assert_eq!(optimization.get(unused), None);
+        assert_eq!(optimization.get(b), None);
+        assert_eq!(optimization.get(y), None);
+
+        let optimized = optimization.public_inputs(&pi).unwrap();
+        assert_eq!(cs.public_inputs(), &optimized[..]);
+        assert_eq!(MockProver::run(&cs, &optimized), Ok(()));
+        // `y` is still checked against its public input.
+        let mut wrong = pi.clone();
+        wrong[4] -= Fr::one();
+        let wrong = optimization.public_inputs(&wrong).unwrap();
+        assert!(MockProver::run(&cs, &wrong).is_err());
+        // the constant gates of `a` and `b` were merged.
+        let mut wrong = pi;
+        wrong[1] = Fr::one();
+        assert_eq!(optimization.public_inputs(&wrong), None);
+
+        let mut counting = Composer::new_counting();
+        circuit(&mut counting, 0);
+        counting.optimize();
+        assert_eq!(counting.shape_mismatch(&cs), None);
+    }
+}
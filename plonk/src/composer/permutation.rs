@@ -9,12 +9,22 @@ use core::marker::PhantomData;
 use crate::composer::{Field, Variable};
 use crate::Map;
 
+/// The wire columns of a composer of `wires` wires, see
+/// `Composer::with_wires`: `w_1..w_3` for three, with `w_0` for four and with
+/// `w_0` and `w_4` for five.
+pub(crate) fn wire_columns(wires: usize) -> &'static [usize] {
+    match wires {
+        3 => &[1, 2, 3],
+        4 => &[0, 1, 2, 3],
+        _ => &[0, 1, 2, 3, 4],
+    }
+}
+
+/// A cell of the wire columns.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
-pub(crate) enum Wire {
-    W0(usize),
-    W1(usize),
-    W2(usize),
-    W3(usize),
+pub(crate) struct Wire {
+    pub column: usize,
+    pub row: usize,
 }
 
 #[derive(Debug)]
@@ -39,6 +49,11 @@ impl<F: Field> Permutation<F> {
         var
     }
 
+    /// The number of variables allocated.
+    pub fn num_vars(&self) -> usize {
+        self.variable_map.len()
+    }
+
     pub fn insert_gate(
         &mut self,
         w_0: Variable,
@@ -47,10 +62,24 @@ impl<F: Field> Permutation<F> {
         w_3: Variable,
         index: usize,
     ) {
-        self.add_to_map(w_0, Wire::W0(index));
-        self.add_to_map(w_1, Wire::W1(index));
-        self.add_to_map(w_2, Wire::W2(index));
-        self.add_to_map(w_3, Wire::W3(index));
+        self.insert_row(&[w_0, w_1, w_2, w_3], index);
+    }
+
+    /// Places `vars` in the columns of row `index`, in order.
+    pub fn insert_row(&mut self, vars: &[Variable], index: usize) {
+        for (column, &var) in vars.iter().enumerate() {
+            self.add_to_map(var, Wire { column, row: index });
+        }
+    }
+
+    /// Places `var` in `column` of row `index`, e.g. in `w_4`, which only the
+    /// wide gates use.
+    pub fn insert(&mut self, var: Variable, column: usize, index: usize) {
+        self.add_to_map(var, Wire { column, row: index });
+    }
+
+    pub fn wires(&self) -> impl Iterator<Item = (&Variable, &Vec<Wire>)> {
+        self.variable_map.iter()
     }
 
     //todo 加个检查删除重复的，或者改compute_wire_permutation的实现
@@ -63,36 +92,45 @@ impl<F: Field> Permutation<F> {
 }
 
 impl<F: Field> Permutation<F> {
+    /// The permutation polynomials on the domain, one per column of
+    /// `columns`, whose cosets are `ks`. The wires of the other columns are
+    /// left out of the copy constraints.
     pub fn compute_sigmas(
         &self,
         domain_n: impl EvaluationDomain<F>,
-        ks: &[F; 4],
-    ) -> (Vec<F>, Vec<F>, Vec<F>, Vec<F>) {
-        let perms = self.compute_wire_permutation(domain_n.size());
+        ks: &[F],
+        columns: &[usize],
+    ) -> Vec<Vec<F>> {
+        let perms = self.compute_wire_permutation(domain_n.size(), columns);
 
         let roots: Vec<_> = domain_n.elements().collect();
-        let to = |&x| match x {
-            Wire::W0(i) => roots[i] * ks[0],
-            Wire::W1(i) => roots[i] * ks[1],
-            Wire::W2(i) => roots[i] * ks[2],
-            Wire::W3(i) => roots[i] * ks[3],
-        };
+        let mut coset = [F::zero(); 5];
+        for (&column, &k) in columns.iter().zip(ks) {
+            coset[column] = k;
+        }
+        let to = |x: &Wire| roots[x.row] * coset[x.column];
 
-        (
-            cfg_iter!(perms[0]).map(to).collect(),
-            cfg_iter!(perms[1]).map(to).collect(),
-            cfg_iter!(perms[2]).map(to).collect(),
-            cfg_iter!(perms[3]).map(to).collect(),
-        )
+        perms
+            .iter()
+            .map(|perm| cfg_iter!(perm).map(&to).collect())
+            .collect()
     }
 
-    fn compute_wire_permutation(&self, n: usize) -> [Vec<Wire>; 4] {
-        let mut perm_0: Vec<_> = (0..n).map(|i| Wire::W0(i)).collect();
-        let mut perm_1: Vec<_> = (0..n).map(|i| Wire::W1(i)).collect();
-        let mut perm_2: Vec<_> = (0..n).map(|i| Wire::W2(i)).collect();
-        let mut perm_3: Vec<_> = (0..n).map(|i| Wire::W3(i)).collect();
+    fn compute_wire_permutation(&self, n: usize, columns: &[usize]) -> Vec<Vec<Wire>> {
+        let mut perms: Vec<Vec<_>> = columns
+            .iter()
+            .map(|&column| (0..n).map(|row| Wire { column, row }).collect())
+            .collect();
+        let mut position = [0; 5];
+        for (i, &column) in columns.iter().enumerate() {
+            position[column] = i;
+        }
         //一个variable（信号signal）对应多条wire
         for (_, wires) in self.variable_map.iter() {
+            let wires: Vec<_> = wires
+                .iter()
+                .filter(|wire| columns.contains(&wire.column))
+                .collect();
             if wires.len() <= 1 {//跳过没有复制约束的
                 continue;
             }
@@ -102,18 +140,11 @@ impl<F: Field> Permutation<F> {
                     0 => wires.len() - 1,//0的下一个是 最后一个
                     _ => curr - 1,
                 };
-                let next_wire = &wires[next];
-
-                match curr_wire {
-                    Wire::W0(i) => perm_0[*i] = *next_wire,
-                    Wire::W1(i) => perm_1[*i] = *next_wire,
-                    Wire::W2(i) => perm_2[*i] = *next_wire,
-                    Wire::W3(i) => perm_3[*i] = *next_wire,
-                }
+                perms[position[curr_wire.column]][curr_wire.row] = *wires[next];
             }
         }
 
-        [perm_0, perm_1, perm_2, perm_3]
+        perms
     }
 }
 
@@ -137,8 +168,10 @@ mod tests {
         let domain_n = GeneralEvaluationDomain::<Fr>::new(cs.size()).unwrap();
         let roots: Vec<_> = domain_n.elements().collect();
 
-        let (sigma_0, sigma_1, sigma_2, sigma_3) =
-            cs.permutation.compute_sigmas(domain_n, &ks);
+        let sigmas = cs
+            .permutation
+            .compute_sigmas(domain_n, &ks, wire_columns(4));
+        assert_eq!(sigmas.len(), 4);
 
         let (id_0, id_1, id_2, id_3) = {
             let id_0: Vec<_> = cfg_iter!(roots).map(|r| ks[0] * r).collect();
@@ -148,7 +181,7 @@ mod tests {
             (id_0, id_1, id_2, id_3)
         };
 
-        let sigma: Vec<_> = [sigma_0, sigma_1, sigma_2, sigma_3]
+        let sigma: Vec<_> = sigmas
             .iter()
             .map(|sigma| sigma.iter().product())
             .collect();
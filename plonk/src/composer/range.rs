@@ -1,5 +1,6 @@
-use crate::composer::{Composer, Field, Variable};
+use crate::composer::{Composer, Error, Field, Variable};
 use ark_ff::{PrimeField, BigInteger};
+use ark_std::{format, vec};
 
 //标识要约束到哪种范围
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -16,91 +17,94 @@ impl<F: PrimeField + Field> Composer<F> {
         &mut self,
         var: Variable, //需要被约束范围的var（的id）
         range_type: RangeType,
-    ){
-        //var里的值。转为 大端u8数组（32个）
-        let value:&F = self.assignment.get(&var).unwrap();
-        let value_bigint = value.into_repr();
-        let value_u8bytes_be = value_bigint.to_bytes_be();
-        // for i in &value_u8bytes_be {
-        //     println!("{}", i);
-        // }
+    ) -> Result<(), Error> {
+        let range_bits = match range_type {
+            RangeType::U8 => 8,
+            RangeType::U16 => 16,
+            RangeType::U32 => 32,
+            RangeType::U64 => 64,
+        };
+        self.range_gate(var, range_bits)
+    }
 
-        let range_bytes = match range_type {
-            RangeType::U8 => {1usize}
-            RangeType::U16 => {2usize}
-            RangeType::U32 => {4usize}
-            RangeType::U64 => {8usize}
+    /// var < 2^num_bits
+    ///
+    /// `var` is split into 2-bit limbs from the top, and each row of
+    /// `q_range` checks that four consecutive accumulators grow by a limb,
+    /// the last one against `w_0` of the next row, which holds `var`. The
+    /// unused accumulators of the first row are copies of a zero, and an odd
+    /// `num_bits` adds a gate making the top limb a bit. A value of `var` of
+    /// `num_bits` bits or more is an `Error::OutOfRange`.
+    pub fn range_gate(&mut self, var: Variable, num_bits: u32) -> Result<(), Error> {
+        let num_bits = num_bits as usize;
+        assert!(num_bits > 0 && num_bits < F::size_in_bits());
+
+        //只计数时没有赋值，门的个数与值无关
+        let value: F = if self.counting {
+            F::zero()
+        } else {
+            *self.assignment.get(&var).unwrap()
         };
-        //验证确实在范围内（前面的byte=0）
-        for i in 0..(32-range_bytes) {
-            assert_eq!(0u8, *value_u8bytes_be.get(i).unwrap());
+        let bits = value.into_repr();
+        //先累积再判断，不按秘密值提前退出
+        let high_bits = (num_bits..F::size_in_bits()).fold(false, |acc, i| acc | bits.get_bit(i));
+        if high_bits {
+            return Err(Error::OutOfRange {
+                variable: format!("{}{}", self.variable_name(var), self.in_namespace()),
+                bits: num_bits as u32,
+            });
         }
-        //acc要能装下最大的u64 todo
-        let mut acc: Vec<u128> = vec![0; 4 * range_bytes + 1];
-        acc[0] = 0;
-        for i in 0..range_bytes {
-            let u8tmp = *value_u8bytes_be.get(32-range_bytes + i).unwrap();
-            //println!("{}", u8tmp);
-            //todo 加括号！移位运算符 优先级很低
-            acc[4*i + 1] = (acc[4*i] << 2) + (u8tmp >> 6) as u128;
-            acc[4*i + 2] = (acc[4*i + 1] << 2) + (u8tmp << 2 >> 6) as u128;
-            acc[4*i + 3] = (acc[4*i + 2] << 2) + (u8tmp << 4 >> 6) as u128;
-            acc[4*i + 4] = (acc[4*i + 3] << 2) + (u8tmp << 6 >> 6) as u128;
+
+        let limbs = (num_bits + 1) / 2;
+        let rows = (limbs + 3) / 4;
+        let pad = 4 * rows - limbs;
+
+        let zero = self.alloc_and_assign(F::zero());
+        let four = F::from(4u64);
+        let mut acc = vec![zero; pad + 1];
+        let mut sum = F::zero();
+        for m in (0..limbs).rev() {
+            let limb = 2 * bits.get_bit(2 * m + 1) as u64 + bits.get_bit(2 * m) as u64;
+            sum = four * sum + F::from(limb);
+            acc.push(self.alloc_and_assign(sum));
         }
-        println!("{}", acc[4*range_bytes]);
 
-        let acc1 = self.alloc_and_assign(F::from(acc[1]));
-        let acc2 = self.alloc_and_assign(F::from(acc[2]));
-        let acc3 = self.alloc_and_assign(F::from(acc[3]));
-        let aux = self.alloc_and_assign(F::from(acc[0]));
-        //生成小门
-        self.create_range_gate_component(
-            (acc1, F::zero()),
-            (acc2, F::zero()),
-            (acc3, F::zero()),
-            Option::from((aux, F::one())),
-            F::zero(),
-            F::zero(),
-            F::zero(),
-            F::one(),
-            F::one(),
-        );
-        for i in 1..range_bytes {
-            let acc1 = self.alloc_and_assign(F::from(acc[4*i +1]));
-            let acc2 = self.alloc_and_assign(F::from(acc[4*i +2]));
-            let acc3 = self.alloc_and_assign(F::from(acc[4*i +3]));
-            let aux = self.alloc_and_assign(F::from(acc[4*i]));
+        for row in 0..rows {
+            let (q_0, q_arith) = if row == 0 {
+                (F::one(), F::one())
+            } else {
+                (F::zero(), F::zero())
+            };
             self.create_range_gate_component(
-                (acc1, F::zero()),
-                (acc2, F::zero()),
-                (acc3, F::zero()),
-                Option::from((aux, F::zero())),
-                F::zero(),
+                (acc[4 * row + 1], F::zero()),
+                (acc[4 * row + 2], F::zero()),
+                (acc[4 * row + 3], F::zero()),
+                Some((acc[4 * row], q_0)),
                 F::zero(),
                 F::zero(),
                 F::zero(),
+                q_arith,
                 F::one(),
-            )
-        }
-            //todo 不需要！画蛇添足
-            // 此操作的index必须与紧接着创建的小门一致
-            // let index = self.n;
-            // self.permutation.add_range_constraint(var, Wire::W0(index));
-            let varzero1 = self.alloc_and_assign(F::zero());
-            let varzero2 = self.alloc_and_assign(F::zero());
-            let varzero3 = self.alloc_and_assign(F::zero());
-            self.create_range_gate_component(
-                (varzero1, F::zero()),
-                (varzero2, F::zero()),
-                (varzero3, F::zero()),
-                Option::from((var, F::zero())),
-                F::zero(),
-                F::zero(),
-                F::zero(),
-                F::zero(),
-                F::zero(),
             );
+        }
+        // 最后一个累加值就是var本身，由上一行的range约束检查
+        self.create_range_gate_component(
+            (self.null_var, F::zero()),
+            (self.null_var, F::zero()),
+            (self.null_var, F::zero()),
+            Some((var, F::zero())),
+            F::zero(),
+            F::zero(),
+            F::zero(),
+            F::zero(),
+            F::zero(),
+        );
 
+        if num_bits % 2 == 1 {
+            let top = acc[pad + 1];
+            self.create_mul_gate(top, top, top, None, F::one(), F::zero(), F::zero());
+        }
+        Ok(())
     }
 
     //range gate 的具体的小门
@@ -128,18 +132,27 @@ impl<F: PrimeField + Field> Composer<F> {
         self.w_1.push(l.0);
         self.w_2.push(r.0);
         self.w_3.push(o.0);
+        self.w_4.push(self.null_var);
         self.pi.push(pi);
 
         self.q_0.push(aux.1);
         self.q_1.push(l.1);
         self.q_2.push(r.1);
         self.q_3.push(o.1);
+        self.q_4.push(F::zero());
         self.q_m.push(q_m);
         self.q_c.push(q_c);
+        self.q_next.push(F::zero());
         self.q_arith.push(q_arith);
         self.q_range.push(q_range);
         self.q_mimc.push(F::zero());
         //self.q_mimc_c.push(F::zero());
+        self.q_lookup.push(F::zero());
+        self.q_logic.push(F::zero());
+        self.q_curve.push(F::zero());
+        self.q_curve_a.push(F::zero());
+        self.q_curve_d.push(F::zero());
+        self.q_memory.push(F::zero());
 
         self.n += 1;
     }
@@ -0,0 +1,85 @@
+//! The size of a circuit, read from its gates without building the index, so
+//! that a universal setup is sized before the expensive keygen.
+
+use crate::composer::{Composer, Error, Field};
+
+/// The gates of a circuit by type, and the sizes it needs. A gate of several
+/// types, e.g. an arithmetic memory row, counts once for each of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of gates, see `Composer::size`.
+    pub gates: usize,
+    pub arithmetic: usize,
+    pub range: usize,
+    pub mimc: usize,
+    pub lookup: usize,
+    pub logic: usize,
+    pub curve: usize,
+    pub memory: usize,
+    /// The number of variables, with the null one.
+    pub variables: usize,
+    /// The rows of all tables.
+    pub table_rows: usize,
+    /// The rows the domain must hold: the gates, and the rows of the tables
+    /// if there are any, see `Composer::add_table`.
+    pub rows: usize,
+    /// The size of the domain, once the rows are padded.
+    pub domain_size: usize,
+    /// The degree of the SRS that `Plonk::keygen` needs, see
+    /// `Composer::srs_degree`.
+    pub srs_degree: usize,
+}
+
+impl<F: Field> Composer<F> {
+    /// The statistics of the circuit, which a counting composer gives as
+    /// well, see `new_counting`.
+    pub fn stats(&self) -> Result<Stats, Error> {
+        let count = |selector: &[F]| selector.iter().filter(|q| !q.is_zero()).count();
+        let domain_size = self.domain_size()?;
+        Ok(Stats {
+            gates: self.n,
+            arithmetic: count(&self.q_arith),
+            range: count(&self.q_range),
+            mimc: count(&self.q_mimc),
+            lookup: count(&self.q_lookup),
+            logic: count(&self.q_logic),
+            curve: count(&self.q_curve),
+            memory: count(&self.q_memory),
+            variables: self.permutation.num_vars(),
+            table_rows: self.table_size(),
+            rows: self.rows(),
+            domain_size,
+            srs_degree: self.srs_degree()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::Zero;
+
+    #[test]
+    fn stats() {
+        let mut cs = crate::tests::circuit::<Fr>();
+        let stats = cs.stats().unwrap();
+        assert_eq!(stats.gates, 5);
+        assert_eq!(stats.arithmetic, 5);
+        assert_eq!(stats.variables, 6);
+        assert_eq!(stats.rows, 5);
+        assert_eq!(stats.domain_size, 8);
+        assert_eq!(stats.srs_degree, 10);
+
+        // the table of 16 rows, and a row after it, set the domain.
+        let xor = cs.add_xor_table(2);
+        let a = cs.alloc_and_assign(Fr::zero());
+        cs.lookup(xor, a, a);
+
+        let stats = cs.stats().unwrap();
+        assert_eq!(stats.gates, 6);
+        assert_eq!(stats.lookup, 1);
+        assert_eq!(stats.table_rows, 16);
+        assert_eq!(stats.rows, 17);
+        assert_eq!(stats.domain_size, 32);
+    }
+}
@@ -1,46 +1,116 @@
+use ark_ff::to_bytes;
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
-use ark_std::{cfg_iter, vec, vec::Vec};
+use ark_std::{cfg_iter, fmt, string::String, vec, vec::Vec};
+use digest::Digest;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-use crate::composer::{Composer, Field};
+use crate::ahp::DEFAULT_BLINDING;
+use crate::composer::{wire_columns, Composer, Field, Variable};
+use crate::utils::{quotient_chunk, quotient_chunks};
 
 #[derive(Debug)]
 pub enum Error {
-    PolynomialDegreeTooLarge,
+    /// The field has no evaluation domain of `size` elements.
+    PolynomialDegreeTooLarge { size: usize },
+    /// The composer only counts gates and has no witnesses.
+    Unassigned,
+    /// The `check` of a witness file against the composer failed.
+    WitnessMismatch { check: &'static str },
+    /// `ks` has `ks` cosets for a composer of `wires` wires.
+    CosetCount { wires: usize, ks: usize },
+    /// A composer of `wires` wires, only 3, 4 and 5 are supported.
+    UnsupportedWires { wires: usize },
+    /// The wires of a composer were set after its gates.
+    WiresAfterGates,
+    /// The gate on `row` puts a variable on `w_{column}`, which the
+    /// composer has not, see `Composer::with_wires`.
+    AbsentWire { row: usize, column: usize },
+    /// The value of `variable`, named as in the panics of the composer, is
+    /// not below `2^bits` though a range gate or decomposition needs it to.
+    OutOfRange { variable: String, bits: u32 },
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::PolynomialDegreeTooLarge { size } => {
+                write!(f, "no evaluation domain of size {}", size)
+            }
+            Error::Unassigned => write!(f, "the composer only counts gates"),
+            Error::WitnessMismatch { check } => write!(f, "witness mismatch: {}", check),
+            Error::CosetCount { wires, ks } => {
+                write!(f, "{} cosets for a composer of {} wires", ks, wires)
+            }
+            Error::UnsupportedWires { wires } => {
+                write!(f, "a composer of {} wires, only 3, 4 and 5 are supported", wires)
+            }
+            Error::WiresAfterGates => write!(f, "the wires of a composer set after its gates"),
+            Error::AbsentWire { row, column } => {
+                write!(
+                    f,
+                    "the gate on row {} uses the absent wire w_{}",
+                    row, column
+                )
+            }
+            Error::OutOfRange { variable, bits } => {
+                write!(f, "value out of range: {} is not below 2^{}", variable, bits)
+            }
+        }
+    }
+}
+
+impl ark_std::error::Error for Error {}
+
 pub struct Selectors<F: Field> {
-    n: usize,
+    pub(crate) n: usize,
     pub q_0: Vec<F>,
     pub q_1: Vec<F>,
     pub q_2: Vec<F>,
     pub q_3: Vec<F>,
+    /// the coefficient of `w_4`, zero but with five wires.
+    pub q_4: Vec<F>,
     pub q_m: Vec<F>,
     pub q_c: Vec<F>,
+    pub q_next: Vec<F>,
     pub q_arith: Vec<F>,
 
-    pub sigma_0: Vec<F>,
-    pub sigma_1: Vec<F>,
-    pub sigma_2: Vec<F>,
-    pub sigma_3: Vec<F>,
+    /// the permutation of each wire column, see `wire_columns`.
+    pub sigmas: Vec<Vec<F>>,
 
     pub q_range: Vec<F>,
     pub q_mimc: Vec<F>,
     //pub q_mimc_c: Vec<F>,
+
+    pub q_lookup: Vec<F>,
+    /// the tables, see `Composer::compose_tables`.
+    pub table_0: Vec<F>,
+    pub table_1: Vec<F>,
+    pub table_2: Vec<F>,
+    pub table_3: Vec<F>,
+
+    pub q_logic: Vec<F>,
+
+    pub q_curve: Vec<F>,
+    pub q_curve_a: Vec<F>,
+    pub q_curve_d: Vec<F>,
+
+    pub q_memory: Vec<F>,
 }
 
+/// The values of the wires, zero on the columns the composer has not.
 pub struct Witnesses<F: Field> {
     pub w_0: Vec<F>,
     pub w_1: Vec<F>,
     pub w_2: Vec<F>,
     pub w_3: Vec<F>,
+    pub w_4: Vec<F>,
 }
 
 impl<F: Field> Witnesses<F> {
     pub fn iter(&self) -> impl Iterator<Item = &Vec<F>> {
-        vec![&self.w_0, &self.w_1, &self.w_2, &self.w_3].into_iter()
+        vec![&self.w_0, &self.w_1, &self.w_2, &self.w_3, &self.w_4].into_iter()
     }
 }
 
@@ -48,42 +118,62 @@ impl<F: Field> Selectors<F> {
     pub fn size(&self) -> usize {
         self.n
     }
-    //
+
+    /// The number of wire columns, one per sigma.
+    pub fn wires(&self) -> usize {
+        self.sigmas.len()
+    }
+
+    /// The selectors, `q_4` only with five wires.
     pub fn iter(&self) -> impl Iterator<Item = &Vec<F>> {
-        vec![
-            &self.q_0,
-            &self.q_1,
-            &self.q_2,
-            &self.q_3,
-            &self.q_m,
-            &self.q_c,
-            &self.q_arith,
-            &self.sigma_0,
-            &self.sigma_1,
-            &self.sigma_2,
-            &self.sigma_3,
-            &self.q_range,
-            &self.q_mimc,
-            //&self.q_mimc_c,
-        ]
-        .into_iter()
+        let q_4 = if self.wires() == 5 {
+            Some(&self.q_4)
+        } else {
+            None
+        };
+        vec![&self.q_0, &self.q_1, &self.q_2, &self.q_3]
+            .into_iter()
+            .chain(q_4)
+            .chain(vec![&self.q_m, &self.q_c, &self.q_next, &self.q_arith])
+            .chain(self.sigmas.iter())
+            .chain(vec![
+                &self.q_range,
+                &self.q_mimc,
+                //&self.q_mimc_c,
+                &self.q_lookup,
+                &self.table_0,
+                &self.table_1,
+                &self.table_2,
+                &self.table_3,
+                &self.q_logic,
+                &self.q_curve,
+                &self.q_curve_a,
+                &self.q_curve_d,
+                &self.q_memory,
+            ])
     }
 }
 
 impl<F: Field> Composer<F> {
     // selectors
-    pub fn compose(&self, ks: &[F; 4]) -> Result<Selectors<F>, Error> {
-        let domain_n = GeneralEvaluationDomain::<F>::new(self.n)
-            .ok_or(Error::PolynomialDegreeTooLarge)?;
+    /// The selectors of the composer, with the sigmas of its wires under
+    /// `ks`, one coset per wire.
+    pub fn compose(&self, ks: &[F]) -> Result<Selectors<F>, Error> {
+        if ks.len() != self.wires {
+            return Err(Error::CosetCount {
+                wires: self.wires,
+                ks: ks.len(),
+            });
+        }
+        self.check_wires()?;
+        let domain_n = GeneralEvaluationDomain::<F>::new(self.rows())
+            .ok_or(Error::PolynomialDegreeTooLarge { size: self.rows() })?;
         let n = domain_n.size();
 
-        let (sigma_0, sigma_1, sigma_2, sigma_3) =
-            self.permutation.compute_sigmas(domain_n, &ks);
-
-        assert_eq!(sigma_0.len(), n);
-        assert_eq!(sigma_1.len(), n);
-        assert_eq!(sigma_2.len(), n);
-        assert_eq!(sigma_3.len(), n);
+        let sigmas = self
+            .permutation
+            .compute_sigmas(domain_n, ks, wire_columns(self.wires));
+        sigmas.iter().for_each(|sigma| assert_eq!(sigma.len(), n));
 
         let diff = n - self.n;
         let zeros = vec![F::zero(); diff];
@@ -91,6 +181,7 @@ impl<F: Field> Composer<F> {
             v.extend(zeros.iter());
             v
         };
+        let [table_0, table_1, table_2, table_3] = self.compose_tables(n);
 
         Ok(Selectors {
             n,
@@ -98,30 +189,92 @@ impl<F: Field> Composer<F> {
             q_1: pad(self.q_1.clone()),
             q_2: pad(self.q_2.clone()),
             q_3: pad(self.q_3.clone()),
+            q_4: pad(self.q_4.clone()),
             q_m: pad(self.q_m.clone()),
             q_c: pad(self.q_c.clone()),
+            q_next: pad(self.q_next.clone()),
             q_arith: pad(self.q_arith.clone()),
 
             q_range: pad(self.q_range.clone()),
             //todo 找了很久。。
             q_mimc: pad(self.q_mimc.clone()),
             //q_mimc_c: pad(self.q_range.clone()),
+            q_lookup: pad(self.q_lookup.clone()),
+            table_0,
+            table_1,
+            table_2,
+            table_3,
+
+            q_logic: pad(self.q_logic.clone()),
 
-            sigma_0,
-            sigma_1,
-            sigma_2,
-            sigma_3,
+            q_curve: pad(self.q_curve.clone()),
+            q_curve_a: pad(self.q_curve_a.clone()),
+            q_curve_d: pad(self.q_curve_d.clone()),
+
+            q_memory: pad(self.q_memory.clone()),
+
+            sigmas,
         })
     }
 
+    /// Fails on a variable on a wire column the composer has not: `w_0`
+    /// with three wires, and `w_4` with less than five.
+    pub(crate) fn check_wires(&self) -> Result<(), Error> {
+        let absent = |column: usize, wires: &[Variable]| match wires
+            .iter()
+            .position(|var| *var != self.null_var)
+        {
+            Some(row) => Err(Error::AbsentWire { row, column }),
+            None => Ok(()),
+        };
+        if self.wires == 3 {
+            absent(0, &self.w_0)?;
+        }
+        if self.wires < 5 {
+            absent(4, &self.w_4)?;
+        }
+        Ok(())
+    }
+
+    /// A canonical hash of the circuit: the domain size, `ks` and all
+    /// selectors, i.e. everything the index is built from.
+    pub fn circuit_hash<D: Digest>(&self, ks: &[F]) -> Result<Vec<u8>, Error> {
+        let selectors = self.compose(ks)?;
+
+        let mut hasher = D::new();
+        hasher.update(&to_bytes![selectors.size() as u64, ks.to_vec()].unwrap());
+        for selector in selectors.iter() {
+            hasher.update(&to_bytes![selector].unwrap());
+        }
+        Ok(hasher.finalize().to_vec())
+    }
+
     pub fn public_inputs(&self) -> &[F] {
         &self.pi
     }
 
+    /// The degree of the SRS that `Plonk::keygen` needs for this circuit,
+    /// i.e. that of its wires blinded with `DEFAULT_BLINDING` coefficients.
+    pub fn srs_degree(&self) -> Result<usize, Error> {
+        let n = self.domain_size()?;
+        let chunks = quotient_chunks(self.wires);
+        Ok(n.max(quotient_chunk(n, DEFAULT_BLINDING, chunks) - 1))
+    }
+
+    /// The size of the domain of this circuit, its rows padded.
+    pub fn domain_size(&self) -> Result<usize, Error> {
+        GeneralEvaluationDomain::<F>::new(self.rows())
+            .map(|d| d.size())
+            .ok_or(Error::PolynomialDegreeTooLarge { size: self.rows() })
+    }
+
     // synthesize witness vectors
     pub fn synthesize(&self) -> Result<Witnesses<F>, Error> {
-        let domain_n = GeneralEvaluationDomain::<F>::new(self.n)
-            .ok_or(Error::PolynomialDegreeTooLarge)?;
+        if self.counting {
+            return Err(Error::Unassigned);
+        }
+        let domain_n = GeneralEvaluationDomain::<F>::new(self.rows())
+            .ok_or(Error::PolynomialDegreeTooLarge { size: self.rows() })?;
         let n = domain_n.size();
 
         let assign = |&v| self.assignment[&v];
@@ -129,6 +282,7 @@ impl<F: Field> Composer<F> {
         let mut w_1: Vec<_> = cfg_iter!(self.w_1).map(assign).collect();
         let mut w_2: Vec<_> = cfg_iter!(self.w_2).map(assign).collect();
         let mut w_3: Vec<_> = cfg_iter!(self.w_3).map(assign).collect();
+        let mut w_4: Vec<_> = cfg_iter!(self.w_4).map(assign).collect();
 
         let diff = n - self.n;
         let zeros = vec![F::zero(); diff];
@@ -136,10 +290,17 @@ impl<F: Field> Composer<F> {
         w_1.extend(zeros.iter());
         w_2.extend(zeros.iter());
         w_3.extend(zeros.iter());
+        w_4.extend(zeros.iter());
 
         let mut pi = self.pi.clone();
         pi.extend(zeros.iter());
 
-        Ok(Witnesses { w_0, w_1, w_2, w_3 })
+        Ok(Witnesses {
+            w_0,
+            w_1,
+            w_2,
+            w_3,
+            w_4,
+        })
     }
 }
@@ -0,0 +1,101 @@
+//! A file format for witnesses, so that they can be generated apart from
+//! the prover, e.g. in a sandboxed process or in another language.
+//!
+//! A file is `MAGIC` followed by the canonical encoding of a `WitnessFile`:
+//! the circuit hash as a length-prefixed byte vector, then the values of the
+//! variables in allocation order and the public input of each gate, both as
+//! little-endian u64 lengths followed by the field elements.
+
+use ark_serialize::*;
+use ark_std::vec::Vec;
+use digest::Digest;
+
+use crate::composer::{Composer, Error, Field, Variable};
+
+/// The first bytes of a witness file, with its version.
+pub const MAGIC: [u8; 8] = *b"PLKWIT\x00\x01";
+
+/// The witness of a circuit, bound to it by `Composer::circuit_hash`.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct WitnessFile<F: Field> {
+    pub circuit_hash: Vec<u8>,
+    /// the value of each variable, the null variable first.
+    pub values: Vec<F>,
+    /// the public input of each gate.
+    pub public_inputs: Vec<F>,
+}
+
+impl<F: Field> WitnessFile<F> {
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        writer.write_all(&MAGIC)?;
+        self.serialize(&mut writer)
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(SerializationError::InvalidData);
+        }
+        Self::deserialize(reader)
+    }
+}
+
+impl<F: Field> Composer<F> {
+    /// The witness of the composer, for `import_witness`.
+    pub fn export_witness<D: Digest>(&self, ks: &[F]) -> Result<WitnessFile<F>, Error> {
+        if self.counting {
+            return Err(Error::Unassigned);
+        }
+        let values = (0..self.permutation.num_vars())
+            .map(|i| self.assignment[&Variable(i)])
+            .collect();
+        Ok(WitnessFile {
+            circuit_hash: self.circuit_hash::<D>(ks)?,
+            values,
+            public_inputs: self.pi.clone(),
+        })
+    }
+
+    /// Assigns the values and public inputs of `witness`, which must be of
+    /// the same circuit, e.g. to a counting composer built without them.
+    pub fn import_witness<D: Digest>(
+        &mut self,
+        witness: &WitnessFile<F>,
+        ks: &[F],
+    ) -> Result<(), Error> {
+        if witness.circuit_hash != self.circuit_hash::<D>(ks)? {
+            return Err(Error::WitnessMismatch {
+                check: "circuit hash",
+            });
+        }
+        if witness.values.len() != self.permutation.num_vars() {
+            return Err(Error::WitnessMismatch { check: "variables" });
+        }
+        if witness.public_inputs.len() != self.n {
+            return Err(Error::WitnessMismatch {
+                check: "public inputs",
+            });
+        }
+        if !witness.values.first().map_or(false, |v| v.is_zero()) {
+            return Err(Error::WitnessMismatch {
+                check: "null variable",
+            });
+        }
+
+        #[cfg(feature = "zeroize")]
+        {
+            use zeroize::Zeroize;
+            self.assignment.values_mut().for_each(Zeroize::zeroize);
+        }
+        self.assignment = witness
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (Variable(i), *value))
+            .collect();
+        self.pi = witness.public_inputs.clone();
+        self.counting = false;
+        Ok(())
+    }
+}
@@ -1,35 +1,222 @@
-use ark_ff::FftField as Field;
-use ark_poly::univariate::DensePolynomial;
-use ark_poly_commit::{BatchLCProof, PolynomialCommitment};
+use ark_ff::{batch_inversion, FftField as Field};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations, GeneralEvaluationDomain,
+};
+use ark_poly_commit::{PCCommitterKey, PCVerifierKey};
 use ark_serialize::*;
-use ark_std::{string::String, vec::Vec};
+use ark_std::{
+    any::type_name,
+    fmt,
+    string::{String, ToString},
+    vec::Vec,
+};
+use digest::Digest;
 
-use crate::ahp::{Index, IndexInfo};
+use crate::ahp::{AHPForPLONK, Index, IndexInfo};
+use crate::pcs::PCS;
+use crate::piop::PIOP;
+use crate::Arc;
 
-pub type UniversalParams<F, PC> =
-    <PC as PolynomialCommitment<F, DensePolynomial<F>>>::UniversalParams;
+pub type UniversalParams<F, PC> = <PC as PCS<F>>::UniversalParams;
 
 pub type LabeledPolynomial<F> = ark_poly_commit::LabeledPolynomial<F, DensePolynomial<F>>;
 
-pub struct ProverKey<F: Field, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+/// A labeled polynomial held as its evaluations over a domain. It is only
+/// interpolated once its coefficients are asked for, so a polynomial that is
+/// never committed to, such as the public inputs, is never interpolated.
+pub struct EvaluationsPoly<F: Field, D: EvaluationDomain<F> = GeneralEvaluationDomain<F>> {
+    label: String,
+    evals: Evaluations<F, D>,
+    poly: Option<DensePolynomial<F>>,
+}
+
+impl<F: Field, D: EvaluationDomain<F>> EvaluationsPoly<F, D> {
+    /// `evals` are the values on every element of `domain`, in order.
+    pub fn new(label: String, evals: Vec<F>, domain: D) -> Self {
+        EvaluationsPoly {
+            label,
+            evals: Evaluations::from_vec_and_domain(evals, domain),
+            poly: None,
+        }
+    }
+
+    pub fn label(&self) -> &String {
+        &self.label
+    }
+
+    pub fn evals(&self) -> &[F] {
+        &self.evals.evals
+    }
+
+    pub fn domain(&self) -> D {
+        self.evals.domain()
+    }
+
+    /// The coefficients, interpolated on the first call.
+    pub fn polynomial(&mut self) -> &DensePolynomial<F> {
+        let evals = &self.evals;
+        self.poly.get_or_insert_with(|| evals.interpolate_by_ref())
+    }
+
+    /// The value at `point`, by the barycentric formula over the nonzero
+    /// evaluations.
+    pub fn evaluate(&self, point: F) -> F {
+        let domain = self.domain();
+        let vanishing = domain.evaluate_vanishing_polynomial(point);
+        let nonzero: Vec<_> = (0..domain.size())
+            .zip(self.evals())
+            .filter(|(_, eval)| !eval.is_zero())
+            .map(|(i, eval)| (domain.element(i), *eval))
+            .collect();
+        if vanishing.is_zero() {
+            return nonzero
+                .iter()
+                .find(|(x, _)| *x == point)
+                .map_or_else(F::zero, |(_, eval)| *eval);
+        }
+
+        // L_i(point) = x_i * v(point) / (n * (point - x_i))
+        let mut denominators: Vec<_> = nonzero.iter().map(|(x, _)| point - x).collect();
+        batch_inversion(&mut denominators);
+        let sum: F = nonzero
+            .iter()
+            .zip(&denominators)
+            .map(|((x, eval), d)| *x * eval * d)
+            .sum();
+        sum * vanishing / domain.size_as_field_element()
+    }
+
+    pub fn into_labeled(mut self) -> LabeledPolynomial<F> {
+        self.polynomial();
+        LabeledPolynomial::new(self.label, self.poly.unwrap(), None, None)
+    }
+}
+
+/// The index of a circuit, from `Plonk::index`. It does not depend on the
+/// setup, so it can be cached and committed to by `Plonk::commit_index`
+/// under any setup large enough for it. It is encoded like the index of a
+/// `ProverKey`.
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CircuitIndex<F: Field> {
+    /// see `Composer::circuit_hash`.
+    pub circuit_hash: Vec<u8>,
+    pub index: Index<F>,
+}
+
+/// The index is encoded with its 4n coset evaluations when uncompressed, and
+/// recomputes them when read otherwise.
+pub struct ProverKey<F: Field, PC: PCS<F>> {
     pub vk: VerifierKey<F, PC>,
     pub rands: Vec<PC::Randomness>,
     pub index: Index<F>,
-    pub ck: PC::CommitterKey,
+    /// shared by the keys committed under the same `TrimmedSetup`, and
+    /// encoded in full with each of them.
+    pub ck: Arc<PC::CommitterKey>,
+}
+
+impl<F: Field, PC: PCS<F>> CanonicalSerialize
+    for ProverKey<F, PC>
+{
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.vk.serialize(&mut writer)?;
+        self.rands.serialize(&mut writer)?;
+        self.index.serialize(&mut writer)?;
+        self.ck.serialize(&mut writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.vk.serialized_size()
+            + self.rands.serialized_size()
+            + self.index.serialized_size()
+            + self.ck.serialized_size()
+    }
+
+    fn serialize_uncompressed<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.vk.serialize_uncompressed(&mut writer)?;
+        self.rands.serialize_uncompressed(&mut writer)?;
+        self.index.serialize_uncompressed(&mut writer)?;
+        self.ck.serialize_uncompressed(&mut writer)
+    }
+
+    fn serialize_unchecked<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.vk.serialize_unchecked(&mut writer)?;
+        self.rands.serialize_unchecked(&mut writer)?;
+        self.index.serialize_unchecked(&mut writer)?;
+        self.ck.serialize_unchecked(&mut writer)
+    }
+
+    fn uncompressed_size(&self) -> usize {
+        self.vk.uncompressed_size()
+            + self.rands.uncompressed_size()
+            + self.index.uncompressed_size()
+            + self.ck.uncompressed_size()
+    }
+}
+
+impl<F: Field, PC: PCS<F>> CanonicalDeserialize
+    for ProverKey<F, PC>
+{
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Ok(ProverKey {
+            vk: VerifierKey::deserialize(&mut reader)?,
+            rands: Vec::deserialize(&mut reader)?,
+            index: Index::deserialize(&mut reader)?,
+            ck: Arc::new(PC::CommitterKey::deserialize(&mut reader)?),
+        })
+    }
+
+    fn deserialize_uncompressed<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Ok(ProverKey {
+            vk: VerifierKey::deserialize_uncompressed(&mut reader)?,
+            rands: Vec::deserialize_uncompressed(&mut reader)?,
+            index: Index::deserialize_uncompressed(&mut reader)?,
+            ck: Arc::new(PC::CommitterKey::deserialize_uncompressed(&mut reader)?),
+        })
+    }
+
+    fn deserialize_unchecked<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Ok(ProverKey {
+            vk: VerifierKey::deserialize_unchecked(&mut reader)?,
+            rands: Vec::deserialize_unchecked(&mut reader)?,
+            index: Index::deserialize_unchecked(&mut reader)?,
+            ck: Arc::new(PC::CommitterKey::deserialize_unchecked(&mut reader)?),
+        })
+    }
+}
+
+/// A setup trimmed once to a degree, from `Plonk::trim`, under which
+/// `Plonk::commit_index_trimmed` commits the indices of any circuits up to
+/// that degree. Their prover keys share its committer key instead of each
+/// holding a copy.
+pub struct TrimmedSetup<F: Field, PC: PCS<F>> {
+    pub ck: Arc<PC::CommitterKey>,
+    pub rk: PC::VerifierKey,
+}
+
+impl<F: Field, PC: PCS<F>> TrimmedSetup<F, PC> {
+    /// The largest degree of the circuits it commits to, see
+    /// `Composer::srs_degree`. It is read from the verifier key, as the
+    /// committer key of `MarlinKZG10` counts its powers instead.
+    pub fn max_degree(&self) -> usize {
+        self.rk.supported_degree()
+    }
 }
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct VerifierKey<F: Field, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+pub struct VerifierKey<F: Field, PC: PCS<F>> {
     pub info: IndexInfo<F>,
+    /// see `Composer::circuit_hash`.
+    pub circuit_hash: Vec<u8>,
     pub comms: Vec<PC::Commitment>,
     pub labels: Vec<String>,
     pub rk: PC::VerifierKey,
 }
 
-impl<F: Field, PC: PolynomialCommitment<F, DensePolynomial<F>>> VerifierKey<F, PC> {
+impl<F: Field, PC: PCS<F>> VerifierKey<F, PC> {
     pub fn clone(&self) -> Self {
         Self {
             info: self.info.clone(),
+            circuit_hash: self.circuit_hash.clone(),
             comms: self.comms.clone(),
             labels: self.labels.clone(),
             rk: self.rk.clone(),
@@ -38,8 +225,301 @@ impl<F: Field, PC: PolynomialCommitment<F, DensePolynomial<F>>> VerifierKey<F, P
 }
 
 #[derive(CanonicalSerialize, CanonicalDeserialize)]
-pub struct Proof<F: Field, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+pub struct Proof<F: Field, PC: PCS<F>> {
+    /// the circuit this proof was created for.
+    pub circuit_hash: Vec<u8>,
     pub commitments: Vec<Vec<PC::Commitment>>,
     pub evaluations: Vec<F>,
-    pub pc_proof: BatchLCProof<F, DensePolynomial<F>, PC>,
+    pub pc_proof: PC::LCProof,
+}
+
+/// `T` read from all of `bytes`, failing on trailing bytes.
+pub(crate) fn read_exact<T: CanonicalDeserialize>(
+    mut bytes: &[u8],
+    compressed: bool,
+) -> Result<T, SerializationError> {
+    let value = if compressed {
+        T::deserialize(&mut bytes)?
+    } else {
+        T::deserialize_uncompressed(&mut bytes)?
+    };
+    if !bytes.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(value)
+}
+
+impl<F: Field, PC: PCS<F>> Proof<F, PC> {
+    /// Reads a proof from untrusted `bytes`, failing on trailing bytes and on
+    /// a number of commitments other than the protocol's.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        read_exact::<Self>(bytes, true)?.checked()
+    }
+
+    /// `from_bytes` for the uncompressed encoding.
+    pub fn from_uncompressed_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        read_exact::<Self>(bytes, false)?.checked()
+    }
+
+    /// The proof, if it has the protocol's number of commitments.
+    pub(crate) fn checked(self) -> Result<Self, SerializationError> {
+        // the first round sends a commitment per wire column.
+        let wires = self.commitments.first().map_or(0, Vec::len);
+        let rounds = AHPForPLONK::<F>::oracle_labels(wires);
+        if !(3..=5).contains(&wires)
+            || self.commitments.len() != AHPForPLONK::<F>::NUM_ROUNDS
+            || !self
+                .commitments
+                .iter()
+                .map(Vec::len)
+                .eq(rounds.iter().map(Vec::len))
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(self)
+    }
+}
+
+impl<F: Field, PC: PCS<F>> VerifierKey<F, PC> {
+    /// Reads a verifier key from untrusted `bytes`, failing on trailing bytes,
+    /// on commitments without labels and on a circuit larger than the key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        read_exact::<Self>(bytes, true)?.checked()
+    }
+
+    /// `from_bytes` for the uncompressed encoding.
+    pub fn from_uncompressed_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        read_exact::<Self>(bytes, false)?.checked()
+    }
+
+    /// An identifier of the circuit and of the setup the key was committed
+    /// under: the hash of its `info` and of its labelled commitments to the
+    /// selectors and sigmas, 32 bytes under e.g. Blake2s. The prover and the
+    /// verifier absorb it into the transcript, so a contract pinning it
+    /// only accepts proofs for that key.
+    pub fn digest<D: Digest>(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.info.serialize(&mut bytes).unwrap();
+        self.labels.serialize(&mut bytes).unwrap();
+        self.comms.serialize(&mut bytes).unwrap();
+        D::digest(&bytes).to_vec()
+    }
+
+    pub(crate) fn checked(self) -> Result<Self, SerializationError> {
+        if self.comms.len() != self.labels.len()
+            || self.info.max_degree() > self.rk.supported_degree()
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(self)
+    }
+}
+
+/// A summary of a key or a proof, for display and logging.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inspection {
+    /// "proof", "prover key", "verifier key" or "index".
+    pub object: &'static str,
+    /// the version of this crate, which fixes the protocol and encoding.
+    pub version: &'static str,
+    /// the curve of the scalar field, e.g. `bls12_381`.
+    pub curve: &'static str,
+    pub commitment_scheme: &'static str,
+    pub circuit_hash: Vec<u8>,
+    /// the size of the serialized object in bytes; for a prover key, the
+    /// size of its verifier key.
+    pub size: usize,
+    pub commitments: usize,
+    pub evaluations: usize,
+    /// the number of gates, unknown for proofs.
+    pub gates: Option<usize>,
+    pub domain_size: Option<usize>,
+    /// the degree the commitment keys are trimmed to, unknown for proofs
+    /// and indexes.
+    pub max_degree: Option<usize>,
+}
+
+/// `bls12_381` for `ark_ff::..::Fp256<ark_bls12_381::..::FrParameters>`, or
+/// the full type name of `F` if it does not follow that pattern.
+fn curve_name<F>() -> &'static str {
+    let name = type_name::<F>();
+    name.find('<')
+        .map(|i| &name[i + 1..])
+        .and_then(|params| params.split("::").next())
+        .map(|krate| krate.trim_start_matches("ark_"))
+        .filter(|curve| !curve.is_empty())
+        .unwrap_or(name)
+}
+
+impl<F: Field, PC: PCS<F>> VerifierKey<F, PC> {
+    pub fn inspect(&self) -> Inspection {
+        Inspection {
+            object: "verifier key",
+            version: env!("CARGO_PKG_VERSION"),
+            curve: curve_name::<F>(),
+            commitment_scheme: type_name::<PC>(),
+            circuit_hash: self.circuit_hash.clone(),
+            size: self.serialized_size(),
+            commitments: self.comms.len(),
+            evaluations: 0,
+            gates: Some(self.info.n),
+            domain_size: Some(self.info.domain_n.size()),
+            max_degree: Some(self.rk.supported_degree()),
+        }
+    }
+}
+
+impl<F: Field, PC: PCS<F>> ProverKey<F, PC> {
+    pub fn inspect(&self) -> Inspection {
+        Inspection {
+            object: "prover key",
+            max_degree: Some(self.ck.supported_degree()),
+            ..self.vk.inspect()
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: Field, PC: PCS<F>> ProverKey<F, PC> {
+    /// Writes the uncompressed encoding of the key to `path`, for
+    /// `load_disk_backed`. A disk-backed key is not saved over its own file.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), crate::Error<PC::Error>> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.serialize_uncompressed(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads the key saved to `path` by `save`, leaving the evaluations of
+    /// its index on the 4n coset, most of its size, in the file. The
+    /// quotient round of each proof reads them back one term at a time, so
+    /// that the prover holds a few of them at once instead of all of them;
+    /// the selectors, the commitment randomness and the committer key are
+    /// read into memory, as the commitment scheme takes them as values.
+    ///
+    /// Each evaluation is read through once here, and a proof fails with
+    /// `AHPError::IndexFileError` if the file is changed or removed after.
+    pub fn load_disk_backed(
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, crate::Error<PC::Error>> {
+        let path = Arc::new(path.as_ref().to_path_buf());
+        let mut reader = std::io::BufReader::new(std::fs::File::open(path.as_path())?);
+        Ok(ProverKey {
+            vk: VerifierKey::deserialize_uncompressed(&mut reader)?,
+            rands: Vec::deserialize_uncompressed(&mut reader)?,
+            index: Index::read_disk_backed(&mut reader, &path)?,
+            ck: Arc::new(PC::CommitterKey::deserialize_uncompressed(&mut reader)?),
+        })
+    }
+}
+
+impl<F: Field> CircuitIndex<F> {
+    /// The degree of the commitment key that committing to it needs.
+    pub fn size(&self) -> usize {
+        self.index.size()
+    }
+
+    /// This index, with the proofs under it opening a commitment to the
+    /// public inputs at `zeta` instead of the verifier interpolating them,
+    /// for circuits with many public inputs; see `Plonk::verify_committed`.
+    pub fn with_committed_inputs(mut self) -> Self {
+        self.index.info.committed_inputs = true;
+        self
+    }
+
+    /// This index, with the proofs under it bound to `context`, e.g. the
+    /// name and version of an application, so that they do not verify under
+    /// the keys of the same circuit in another context.
+    pub fn with_context(mut self, context: &[u8]) -> Self {
+        self.index.info.context = context.to_vec();
+        self
+    }
+
+    pub fn inspect(&self) -> Inspection {
+        Inspection {
+            object: "index",
+            version: env!("CARGO_PKG_VERSION"),
+            curve: curve_name::<F>(),
+            commitment_scheme: "-",
+            circuit_hash: self.circuit_hash.clone(),
+            size: self.serialized_size(),
+            commitments: 0,
+            evaluations: 0,
+            gates: Some(self.index.info.n),
+            domain_size: Some(self.index.info.domain_n.size()),
+            max_degree: None,
+        }
+    }
+}
+
+impl<F: Field, PC: PCS<F>> Proof<F, PC> {
+    pub fn inspect(&self) -> Inspection {
+        Inspection {
+            object: "proof",
+            version: env!("CARGO_PKG_VERSION"),
+            curve: curve_name::<F>(),
+            commitment_scheme: type_name::<PC>(),
+            circuit_hash: self.circuit_hash.clone(),
+            size: self.serialized_size(),
+            commitments: self.commitments.iter().map(|round| round.len()).sum(),
+            evaluations: self.evaluations.len(),
+            gates: None,
+            domain_size: None,
+            max_degree: None,
+        }
+    }
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_unknown = |v: Option<usize>| match v {
+            Some(v) => v.to_string(),
+            None => "-".into(),
+        };
+
+        writeln!(f, "plonk {} (version {})", self.object, self.version)?;
+        writeln!(f, "  curve:             {}", self.curve)?;
+        writeln!(f, "  commitment scheme: {}", self.commitment_scheme)?;
+        write!(f, "  circuit hash:      ")?;
+        for byte in &self.circuit_hash {
+            write!(f, "{:02x}", byte)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "  size:              {} bytes", self.size)?;
+        writeln!(f, "  commitments:       {}", self.commitments)?;
+        writeln!(f, "  evaluations:       {}", self.evaluations)?;
+        writeln!(f, "  gates:             {}", or_unknown(self.gates))?;
+        writeln!(f, "  domain size:       {}", or_unknown(self.domain_size))?;
+        write!(f, "  max degree:        {}", or_unknown(self.max_degree))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::{UniformRand, Zero};
+    use ark_poly::Polynomial;
+    use ark_std::test_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_evaluations_poly() {
+        let rng = &mut test_rng();
+        let domain = GeneralEvaluationDomain::<Fr>::new(8).unwrap();
+        let mut evals = vec![Fr::zero(); 8];
+        evals[1] = Fr::rand(rng);
+        evals[6] = Fr::rand(rng);
+
+        let mut poly = EvaluationsPoly::new("pi".to_string(), evals.clone(), domain);
+        let point = Fr::rand(rng);
+        let eval = poly.evaluate(point);
+        assert_eq!(poly.polynomial().evaluate(&point), eval);
+        assert_eq!(poly.evaluate(domain.element(6)), evals[6]);
+        assert_eq!(poly.evaluate(domain.element(2)), Fr::zero());
+
+        let labeled = poly.into_labeled();
+        assert_eq!(labeled.label(), "pi");
+        assert_eq!(labeled.evaluate(&point), eval);
+    }
 }
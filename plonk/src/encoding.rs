@@ -0,0 +1,340 @@
+//! Conversions of verifier keys and proofs between the canonical encodings,
+//! with compressed or uncompressed points, and a raw one whose points are
+//! written as big-endian `x || y`, as contracts on-chain read them.
+//!
+//! The versioned encoding is the fixed layout a script on-chain takes as
+//! input, see `Plonk::verify_from_slices`. A verifier key, a proof or a list
+//! of public inputs is a version byte, `ENCODING_VERSION`, a byte for what it
+//! holds, `1` for a verifier key, `2` for a proof and `3` for public inputs,
+//! and its compressed canonical encoding, little-endian. The public inputs
+//! are encoded as a `Vec` of field elements: their number as a `u64`, then
+//! each of them.
+
+use ark_ec::{AffineCurve, PairingEngine};
+use ark_ff::{FftField as Field, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_serialize::*;
+use ark_std::{iter, string::String, vec, vec::Vec};
+
+use crate::ahp::IndexInfo;
+use crate::data_structures::{read_exact, Proof, VerifierKey};
+use crate::pcs::PCS;
+
+/// An encoding of a verifier key or a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// the canonical encoding, as `CanonicalSerialize::serialize`.
+    Compressed,
+    /// the canonical encoding, as `CanonicalSerialize::serialize_uncompressed`.
+    Uncompressed,
+    /// the uncompressed encoding, except that each point is its coordinates
+    /// `x || y`, big-endian and without flags, and the point at infinity is
+    /// zeros. A coordinate over an extension field is its coefficients from
+    /// the highest, as in EIP-197.
+    Raw,
+}
+
+/// Copies an uncompressed encoding to or from the raw one, value by value.
+pub struct Recoder<'a> {
+    input: &'a [u8],
+    output: Vec<u8>,
+    to_raw: bool,
+}
+
+impl<'a> Recoder<'a> {
+    fn new(input: &'a [u8], to_raw: bool) -> Self {
+        Self {
+            input,
+            output: Vec::with_capacity(input.len()),
+            to_raw,
+        }
+    }
+
+    fn finish(self) -> Result<Vec<u8>, SerializationError> {
+        if !self.input.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(self.output)
+    }
+
+    /// Copies a value without points, and returns it.
+    pub fn value<T: CanonicalSerialize + CanonicalDeserialize>(
+        &mut self,
+    ) -> Result<T, SerializationError> {
+        let value = T::deserialize_uncompressed(&mut self.input)?;
+        value.serialize_uncompressed(&mut self.output)?;
+        Ok(value)
+    }
+
+    /// Copies the length of a vector.
+    pub fn length(&mut self) -> Result<usize, SerializationError> {
+        Ok(self.value::<u64>()? as usize)
+    }
+
+    /// Converts a point, checking that it is on the curve and in the
+    /// subgroup.
+    pub fn point<G: AffineCurve>(&mut self) -> Result<(), SerializationError> {
+        let coordinate = G::BaseField::zero().serialized_size();
+        if self.to_raw {
+            let point = G::deserialize_uncompressed(&mut self.input)?;
+            if point.is_zero() {
+                self.output.extend(iter::repeat(0).take(2 * coordinate));
+                return Ok(());
+            }
+            let mut bytes = Vec::new();
+            point.serialize_uncompressed(&mut bytes)?;
+            // the flags are in the top bits of `y`, or in a byte of their own.
+            if bytes.len() == 2 * coordinate {
+                bytes[2 * coordinate - 1] &= 0xff >> SWFlags::BIT_SIZE;
+            }
+            self.output.extend(bytes[..coordinate].iter().rev());
+            self.output
+                .extend(bytes[coordinate..2 * coordinate].iter().rev());
+        } else {
+            if self.input.len() < 2 * coordinate {
+                return Err(SerializationError::NotEnoughSpace);
+            }
+            let (raw, rest) = self.input.split_at(2 * coordinate);
+            self.input = rest;
+            let point = if raw.iter().all(Zero::is_zero) {
+                G::zero()
+            } else {
+                let mut bytes: Vec<u8> = raw[..coordinate].iter().rev().copied().collect();
+                bytes.extend(raw[coordinate..].iter().rev());
+                bytes.resize(G::zero().uncompressed_size(), 0);
+                G::deserialize_uncompressed(&bytes[..])?
+            };
+            point.serialize_uncompressed(&mut self.output)?;
+        }
+        Ok(())
+    }
+}
+
+/// A commitment scheme whose verifier keys and proofs have a raw encoding,
+/// given by the layout of their uncompressed encoding.
+pub trait RawEncoding<F: Field>: PCS<F> {
+    fn recode_commitment(r: &mut Recoder<'_>) -> Result<(), SerializationError>;
+
+    fn recode_batch_proof(r: &mut Recoder<'_>) -> Result<(), SerializationError>;
+
+    fn recode_verifier_key(r: &mut Recoder<'_>) -> Result<(), SerializationError>;
+}
+
+/// The points of `marlin_pc`: commitments, their shifted commitments and
+/// the opening proofs in G1, and the `h` and `beta * h` of the key in G2.
+impl<E: PairingEngine> RawEncoding<E::Fr> for MarlinKZG10<E, DensePolynomial<E::Fr>> {
+    fn recode_commitment(r: &mut Recoder<'_>) -> Result<(), SerializationError> {
+        r.point::<E::G1Affine>()?;
+        if r.value::<bool>()? {
+            r.point::<E::G1Affine>()?;
+        }
+        Ok(())
+    }
+
+    fn recode_batch_proof(r: &mut Recoder<'_>) -> Result<(), SerializationError> {
+        for _ in 0..r.length()? {
+            r.point::<E::G1Affine>()?;
+            r.value::<Option<E::Fr>>()?;
+        }
+        Ok(())
+    }
+
+    fn recode_verifier_key(r: &mut Recoder<'_>) -> Result<(), SerializationError> {
+        r.point::<E::G1Affine>()?;
+        r.point::<E::G1Affine>()?;
+        r.point::<E::G2Affine>()?;
+        r.point::<E::G2Affine>()?;
+        if r.value::<bool>()? {
+            for _ in 0..r.length()? {
+                r.value::<usize>()?;
+                r.point::<E::G1Affine>()?;
+            }
+        }
+        r.value::<usize>()?;
+        r.value::<usize>()?;
+        Ok(())
+    }
+}
+
+fn recode_vk<F: Field, PC: RawEncoding<F>>(r: &mut Recoder<'_>) -> Result<(), SerializationError> {
+    r.value::<IndexInfo<F>>()?;
+    r.value::<Vec<u8>>()?;
+    for _ in 0..r.length()? {
+        PC::recode_commitment(r)?;
+    }
+    r.value::<Vec<String>>()?;
+    PC::recode_verifier_key(r)
+}
+
+fn recode_proof<F: Field, PC: RawEncoding<F>>(
+    r: &mut Recoder<'_>,
+) -> Result<(), SerializationError> {
+    r.value::<Vec<u8>>()?;
+    for _ in 0..r.length()? {
+        for _ in 0..r.length()? {
+            PC::recode_commitment(r)?;
+        }
+    }
+    r.value::<Vec<F>>()?;
+    PC::recode_batch_proof(r)?;
+    r.value::<Option<Vec<F>>>()?;
+    Ok(())
+}
+
+fn recode(
+    bytes: &[u8],
+    to_raw: bool,
+    layout: fn(&mut Recoder<'_>) -> Result<(), SerializationError>,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut r = Recoder::new(bytes, to_raw);
+    layout(&mut r)?;
+    r.finish()
+}
+
+fn encode<T: CanonicalSerialize>(
+    value: &T,
+    encoding: Encoding,
+    layout: fn(&mut Recoder<'_>) -> Result<(), SerializationError>,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut bytes = Vec::new();
+    match encoding {
+        Encoding::Compressed => value.serialize(&mut bytes)?,
+        Encoding::Uncompressed => value.serialize_uncompressed(&mut bytes)?,
+        Encoding::Raw => {
+            value.serialize_uncompressed(&mut bytes)?;
+            bytes = recode(&bytes, true, layout)?;
+        }
+    }
+    Ok(bytes)
+}
+
+impl<F: Field, PC: RawEncoding<F>> VerifierKey<F, PC> {
+    /// The verifier key in `encoding`.
+    pub fn to_encoding(&self, encoding: Encoding) -> Result<Vec<u8>, SerializationError> {
+        encode(self, encoding, recode_vk::<F, PC>)
+    }
+
+    /// Reads a verifier key in `encoding` from untrusted `bytes`, with the
+    /// checks of `from_bytes`.
+    pub fn from_encoding(bytes: &[u8], encoding: Encoding) -> Result<Self, SerializationError> {
+        match encoding {
+            Encoding::Compressed => Self::from_bytes(bytes),
+            Encoding::Uncompressed => Self::from_uncompressed_bytes(bytes),
+            Encoding::Raw => {
+                Self::from_uncompressed_bytes(&recode(bytes, false, recode_vk::<F, PC>)?)
+            }
+        }
+    }
+}
+
+impl<F: Field, PC: RawEncoding<F>> Proof<F, PC> {
+    /// The proof in `encoding`.
+    pub fn to_encoding(&self, encoding: Encoding) -> Result<Vec<u8>, SerializationError> {
+        encode(self, encoding, recode_proof::<F, PC>)
+    }
+
+    /// Reads a proof in `encoding` from untrusted `bytes`, with the checks of
+    /// `from_bytes`.
+    pub fn from_encoding(bytes: &[u8], encoding: Encoding) -> Result<Self, SerializationError> {
+        match encoding {
+            Encoding::Compressed => Self::from_bytes(bytes),
+            Encoding::Uncompressed => Self::from_uncompressed_bytes(bytes),
+            Encoding::Raw => {
+                Self::from_uncompressed_bytes(&recode(bytes, false, recode_proof::<F, PC>)?)
+            }
+        }
+    }
+}
+
+/// Converts a verifier key from one encoding to another.
+pub fn reencode_verifier_key<F: Field, PC: RawEncoding<F>>(
+    bytes: &[u8],
+    from: Encoding,
+    to: Encoding,
+) -> Result<Vec<u8>, SerializationError> {
+    VerifierKey::<F, PC>::from_encoding(bytes, from)?.to_encoding(to)
+}
+
+/// Converts a proof from one encoding to another.
+pub fn reencode_proof<F: Field, PC: RawEncoding<F>>(
+    bytes: &[u8],
+    from: Encoding,
+    to: Encoding,
+) -> Result<Vec<u8>, SerializationError> {
+    Proof::<F, PC>::from_encoding(bytes, from)?.to_encoding(to)
+}
+
+/// The version of the versioned encoding written and read by this crate.
+pub const ENCODING_VERSION: u8 = 1;
+
+/// What a versioned encoding holds, its second byte.
+#[derive(Clone, Copy)]
+enum Kind {
+    VerifierKey = 1,
+    Proof = 2,
+    PublicInputs = 3,
+}
+
+/// `value` in the versioned encoding, as a `kind`.
+fn versioned<T: CanonicalSerialize>(
+    kind: Kind,
+    value: &T,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut bytes = vec![ENCODING_VERSION, kind as u8];
+    value.serialize(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The canonical encoding in `bytes`, if they are a versioned encoding of
+/// a `kind` in this version.
+fn unversioned(bytes: &[u8], kind: Kind) -> Result<&[u8], SerializationError> {
+    match bytes {
+        [ENCODING_VERSION, k, body @ ..] if *k == kind as u8 => Ok(body),
+        _ => Err(SerializationError::InvalidData),
+    }
+}
+
+impl<F: Field, PC: PCS<F>> VerifierKey<F, PC> {
+    /// The verifier key in the versioned encoding.
+    pub fn to_versioned_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        versioned(Kind::VerifierKey, self)
+    }
+
+    /// Reads a verifier key in the versioned encoding from untrusted `bytes`,
+    /// in place and with the checks of `from_bytes`.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        Self::from_bytes(unversioned(bytes, Kind::VerifierKey)?)
+    }
+}
+
+impl<F: Field, PC: PCS<F>> Proof<F, PC> {
+    /// The proof in the versioned encoding.
+    pub fn to_versioned_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        versioned(Kind::Proof, self)
+    }
+
+    /// Reads a proof in the versioned encoding from untrusted `bytes`, in
+    /// place and with the checks of `from_bytes`.
+    pub fn from_versioned_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        Self::from_bytes(unversioned(bytes, Kind::Proof)?)
+    }
+}
+
+/// `public_inputs` in the versioned encoding.
+pub fn public_inputs_to_bytes<F: Field>(
+    public_inputs: &[F],
+) -> Result<Vec<u8>, SerializationError> {
+    let mut bytes = vec![ENCODING_VERSION, Kind::PublicInputs as u8];
+    (public_inputs.len() as u64).serialize(&mut bytes)?;
+    for input in public_inputs {
+        input.serialize(&mut bytes)?;
+    }
+    Ok(bytes)
+}
+
+/// Reads public inputs in the versioned encoding from untrusted `bytes`, in
+/// place, rejecting trailing bytes.
+pub fn public_inputs_from_bytes<F: Field>(bytes: &[u8]) -> Result<Vec<F>, SerializationError> {
+    read_exact(unversioned(bytes, Kind::PublicInputs)?, true)
+}
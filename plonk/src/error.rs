@@ -1,21 +1,87 @@
-use ark_std::string::String;
+use ark_serialize::SerializationError;
+use ark_std::{fmt, string::String};
 
 use crate::ahp::Error as AHPError;
+use crate::composer::Error as CSError;
 
 #[derive(Debug)]
 pub enum Error<E> {
-    PolynomialDegreeTooLarge,
-    CircuitTooLarge,
-    AlreadyPreprocessed,
-    MissingEvaluation(String),
+    /// The circuit needs universal parameters of degree `needed`, see
+    /// `Composer::srs_degree`, above the `available` one, or its public
+    /// inputs do not fit in its domain of `available` rows.
+    CircuitTooLarge {
+        needed: usize,
+        available: usize,
+    },
+    /// The field has no evaluation domain of `size` elements, e.g. a circuit
+    /// padded to `size` rows is beyond the two-adicity of the field.
+    UnsupportedDomain {
+        size: usize,
+    },
+    /// The circuit, keys or proof were built for different circuits.
+    CircuitMismatch,
+    /// The prover was cancelled through its `CancellationToken`.
+    Cancelled,
+    /// The prover of a PIOP finished before its `round`.
+    ProverFinished {
+        round: usize,
+    },
+    /// No linear combination is labelled `label`.
+    MissingEvaluation {
+        label: String,
+    },
+    /// The universal setup `name` does not have the expected digest.
+    SrsDigestMismatch {
+        name: String,
+    },
+    /// The `check` of `Plonk::self_test` failed.
+    SelfTestFailed {
+        check: &'static str,
+    },
+    /// A prover cannot resume from a checkpoint failing its `check`, e.g.
+    /// the checkpoint of another key, circuit or public input.
+    CheckpointMismatch {
+        check: &'static str,
+    },
+    /// The public inputs were given as values to a key that takes a
+    /// commitment to them, or the other way around.
+    InputModeMismatch,
+    /// No verifier key is registered for the circuit, see `VkRegistry`.
+    UnknownCircuit,
+    /// The keys of a batch come from different universal setups, see
+    /// `Plonk::batch_verify`.
+    SetupMismatch,
+    /// The thread pool of `Plonk::prove_with_threads` could not be built.
+    #[cfg(feature = "parallel")]
+    ThreadPoolError(rayon::ThreadPoolBuildError),
     PolynomialCommitmentError(E),
     PolynomialProtocolError(AHPError),
-    Other,
+    SerializationError(SerializationError),
+    #[cfg(feature = "std")]
+    IoError(std::io::Error),
 }
 
 impl<E> From<AHPError> for Error<E> {
     fn from(err: AHPError) -> Self {
-        Error::PolynomialProtocolError(err)
+        match err {
+            AHPError::SynthesisError(CSError::PolynomialDegreeTooLarge { size }) => {
+                Error::UnsupportedDomain { size }
+            }
+            err => Error::PolynomialProtocolError(err),
+        }
+    }
+}
+
+impl<E> From<SerializationError> for Error<E> {
+    fn from(err: SerializationError) -> Self {
+        Error::SerializationError(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> From<std::io::Error> for Error<E> {
+    fn from(err: std::io::Error) -> Self {
+        Error::IoError(err)
     }
 }
 
@@ -24,3 +90,50 @@ impl<E> Error<E> {
         Error::PolynomialCommitmentError(err)
     }
 }
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::CircuitTooLarge { needed, available } => write!(
+                f,
+                "circuit needs a size of {} where {} is available",
+                needed, available
+            ),
+            Error::UnsupportedDomain { size } => write!(f, "no evaluation domain of size {}", size),
+            Error::CircuitMismatch => write!(f, "keys or proof of a different circuit"),
+            Error::Cancelled => write!(f, "prover cancelled"),
+            Error::ProverFinished { round } => write!(f, "prover finished before round {}", round),
+            Error::MissingEvaluation { label } => write!(f, "missing evaluation of {}", label),
+            Error::SrsDigestMismatch { name } => write!(f, "digest mismatch of setup {}", name),
+            Error::SelfTestFailed { check } => write!(f, "self test failed: {}", check),
+            Error::CheckpointMismatch { check } => write!(f, "checkpoint mismatch: {}", check),
+            Error::InputModeMismatch => write!(f, "public inputs committed or not as the key"),
+            Error::UnknownCircuit => write!(f, "no verifier key for the circuit"),
+            Error::SetupMismatch => write!(f, "keys of different setups"),
+            #[cfg(feature = "parallel")]
+            Error::ThreadPoolError(err) => write!(f, "thread pool error: {}", err),
+            Error::PolynomialCommitmentError(err) => {
+                write!(f, "polynomial commitment error: {}", err)
+            }
+            Error::PolynomialProtocolError(err) => write!(f, "polynomial protocol error: {}", err),
+            Error::SerializationError(err) => write!(f, "serialization error: {}", err),
+            #[cfg(feature = "std")]
+            Error::IoError(err) => write!(f, "io error: {}", err),
+        }
+    }
+}
+
+impl<E: ark_std::error::Error + 'static> ark_std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn ark_std::error::Error + 'static)> {
+        match self {
+            Error::PolynomialCommitmentError(err) => Some(err),
+            Error::PolynomialProtocolError(err) => Some(err),
+            Error::SerializationError(err) => Some(err),
+            #[cfg(feature = "parallel")]
+            Error::ThreadPoolError(err) => Some(err),
+            #[cfg(feature = "std")]
+            Error::IoError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Rough estimates of the prover's resources, for placing jobs before proving,
+//! and the size of proofs and the cost of verifying them, for comparing
+//! configurations.
+
+use ark_ff::FftField as Field;
+use ark_std::{log2, vec::Vec};
+use core::time::Duration;
+
+use crate::utils::quotient_chunks;
+
+/// Field multiplications per group addition.
+const ADD_MULS: u64 = 12;
+
+/// Field multiplications per point of the 4n domain to evaluate the
+/// constraints in the quotient.
+const QUOTIENT_MULS: u64 = 64;
+
+/// An affine point is counted as three field elements, which covers the
+/// larger base fields of pairing-friendly curves.
+const POINT_ELEMENTS: usize = 3;
+
+/// The length prefix of a serialized vector.
+const VEC_LEN: usize = 8;
+
+/// Field operations of the challenges and the final equality check.
+const EQUALITY_OPS: usize = 64;
+
+/// The predicted cost of a proof, from the domain sizes and the number of
+/// multi-scalar multiplications. The time is single-threaded and only right
+/// to within a small factor; the memory is an upper bound of the prover's
+/// working set, including the prover key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Estimate {
+    /// The size of the evaluation domain, and of every MSM.
+    pub domain_size: usize,
+    /// The number of FFTs, over the domain or its 4x extension.
+    pub ffts: usize,
+    /// The number of MSMs.
+    pub msms: usize,
+    /// The peak memory of the prover, in bytes.
+    pub peak_memory: usize,
+    /// The single-threaded prove time.
+    pub prove_time: Duration,
+}
+
+impl Estimate {
+    /// The estimate of a PlonK prover of `wires` wire columns over a domain
+    /// of size `n`, with the quotient on a coset of size `n_4n`, i.e. 4n
+    /// unless blinded or with five wires.
+    pub(crate) fn plonk<F: Field>(gates: usize, wires: usize, n: usize, n_4n: usize) -> Self {
+        let mut cost = Cost::new::<F>();
+        // `w_0` is extended even with three wires, as zero.
+        let extended = wires.max(4);
+        let chunks = quotient_chunks(wires);
+
+        // the public inputs, the wires, z, h_1, h_2, z_lookup and z_memory
+        // are interpolated and extended, and x is extended for the
+        // permutation quotient.
+        (0..6 + wires).for_each(|_| cost.fft(n));
+        (0..7 + extended).for_each(|_| cost.fft(n_4n));
+        cost.muls(QUOTIENT_MULS * n_4n as u64);
+        cost.fft(n_4n);
+
+        // commit to the wires, z, h_1, h_2, z_lookup, z_memory and the
+        // quotient chunks, and open at zeta and at the shifted zeta.
+        (0..wires + 7 + chunks).for_each(|_| cost.msm(n));
+
+        // the index holds 20 selectors, a sigma per wire and `q_4` with five
+        // wires, each as coefficients, evaluations and evaluations on the 4n
+        // domain, and three more 4n vectors.
+        let selectors = 20 + wires + (wires == 5) as usize;
+        let index = selectors * 2 * n + selectors * n_4n + 3 * n_4n;
+        let ck = POINT_ELEMENTS * (n + 1);
+        // the wires, z, h_1, h_2, z_lookup and z_memory as values and as
+        // coefficients, held by the prover and by the compiler, the
+        // compressed table and the public inputs. At most the wires and the
+        // four polynomials of the lookup are on the 4n domain at once.
+        let oracles = (wires + 5) * 3 * n + 2 * n + (extended + 4) * n_4n;
+        // the quotient terms are summed in place and become its coefficients.
+        let quotient = n_4n;
+        // the composer's 5 wires and 17 selectors per gate.
+        let composer = 22 * gates;
+
+        Estimate {
+            domain_size: n,
+            ffts: cost.ffts,
+            msms: cost.msms,
+            peak_memory: (index + ck + oracles + quotient + composer) * cost.field_bytes,
+            prove_time: cost.time(),
+        }
+    }
+}
+
+/// The opening proofs of a commitment scheme, which fix the size of a proof
+/// and the work of its verifier beyond what the protocol itself needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// `marlin_pc::MarlinKZG10`: one point per query point, all checked with
+    /// one product of two pairings.
+    Kzg,
+    /// `ipa_pc::InnerProductArgPC`: `2 log(d + 1)` points per query point,
+    /// checked with one MSM over the whole commitment key of degree `d`.
+    Ipa,
+}
+
+/// The size of a proof and the work of its verifier, for a verifier key and
+/// a backend. Commitments are assumed not to be hiding, as the prover's are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierEstimate {
+    /// The serialized size of a proof, in bytes.
+    pub proof_size: usize,
+    pub pairings: usize,
+    /// The sizes of the verifier's MSMs, in the order they are computed.
+    pub msms: Vec<usize>,
+    /// The field operations of the verifier outside its MSMs and pairings,
+    /// to within a small factor.
+    pub field_ops: usize,
+}
+
+impl VerifierEstimate {
+    /// `lc_terms` is the number of commitments in each linear combination
+    /// opened, and `queries` the number of them opened at each point. A
+    /// commitment is `comm_size` bytes and `max_degree` is the degree of the
+    /// verifier key.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn plonk<F: Field>(
+        backend: Backend,
+        n: usize,
+        circuit_hash: usize,
+        rounds: &[usize],
+        lc_terms: &[usize],
+        queries: &[usize],
+        comm_size: usize,
+        max_degree: usize,
+    ) -> Self {
+        let field_size = F::zero().serialized_size();
+        let points = queries.len();
+        let point_size = comm_size - 1;
+        let rounds_ipa = log2(max_degree + 1) as usize;
+        let (opening_size, pairings) = match backend {
+            // the proof point and `random_v: None`.
+            Backend::Kzg => (point_size + 1, 2),
+            // `l_vec`, `r_vec`, the final key, `c`, `hiding_comm: None` and
+            // `rand: None`.
+            Backend::Ipa => {
+                let l_r = 2 * (VEC_LEN + rounds_ipa * point_size);
+                (l_r + point_size + field_size + 2, 0)
+            }
+        };
+
+        let proof_size = VEC_LEN
+            + circuit_hash
+            + VEC_LEN
+            + rounds.iter().map(|k| VEC_LEN + k * comm_size).sum::<usize>()
+            + VEC_LEN
+            + queries.iter().sum::<usize>() * field_size
+            // the openings, and `evals: None`.
+            + VEC_LEN
+            + points * opening_size
+            + 1;
+
+        // the commitments of the linear combinations, then one combination
+        // of them per point.
+        let mut msms: Vec<_> = lc_terms.iter().copied().filter(|&k| k > 1).collect();
+        msms.extend(queries.iter().copied());
+        match backend {
+            Backend::Kzg => msms.push(2 * points + 1),
+            Backend::Ipa => {
+                msms.extend((0..points).map(|_| 2 * rounds_ipa + 1));
+                msms.push(max_degree + 1);
+            }
+        }
+
+        // the public inputs are evaluated at zeta by the barycentric formula,
+        // with a batch inversion over the domain.
+        let public_inputs = 5 * n;
+        let combinations = 2 * lc_terms.iter().sum::<usize>();
+        let field_ops = public_inputs + combinations + EQUALITY_OPS;
+
+        VerifierEstimate {
+            proof_size,
+            pairings,
+            msms,
+            field_ops,
+        }
+    }
+}
+
+/// Counts the field multiplications of a prover.
+struct Cost {
+    field_bytes: usize,
+    muls: u64,
+    ffts: usize,
+    msms: usize,
+}
+
+impl Cost {
+    fn new<F: Field>() -> Self {
+        Cost {
+            field_bytes: F::zero().serialized_size(),
+            muls: 0,
+            ffts: 0,
+            msms: 0,
+        }
+    }
+
+    fn muls(&mut self, muls: u64) {
+        self.muls += muls;
+    }
+
+    fn fft(&mut self, size: usize) {
+        self.ffts += 1;
+        self.muls += (size / 2) as u64 * log2(size) as u64;
+    }
+
+    /// Pippenger's algorithm, with windows of about `ln(size)` bits.
+    fn msm(&mut self, size: usize) {
+        self.msms += 1;
+        let c = log2(size) as usize * 7 / 10 + 2;
+        let windows = (self.field_bytes * 8).div_ceil(c);
+        self.muls += (windows * (size + (1 << c))) as u64 * ADD_MULS;
+    }
+
+    /// A multiplication of `l` limbs takes about `l^2` nanoseconds.
+    fn time(&self) -> Duration {
+        let limbs = (self.field_bytes as u64).div_ceil(8);
+        Duration::from_nanos(self.muls * limbs * limbs)
+    }
+}
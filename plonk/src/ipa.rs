@@ -0,0 +1,1013 @@
+//! An inner-product-argument commitment ([BCMS20], after Halo) with an
+//! accumulation scheme, so that the linear-time part of verifying openings
+//! is deferred and folded across many proofs.
+//!
+//! A commitment is `<coeffs, G>` for transparent generators `G`, derived
+//! from the digest `D`. An opening at `x` folds the coefficients, the powers
+//! of `x` and the generators in `log n` rounds with the challenges `u_j`,
+//! and ends with the single generator `U = <s, G>`, where `s` are the
+//! coefficients of `h(X) = prod_j (u_j^-1 + u_j X^(2^(k - 1 - j)))`. The
+//! succinct check verifies the rounds in time `log n` and leaves the claim
+//! on `U` as an [`Accumulator`]; only `decide` computes `<s, G>`, in linear
+//! time.
+//!
+//! `accumulate` folds accumulators into one by opening their random
+//! combination at a random point, and `verify_accumulation` checks that fold
+//! succinctly, so that a verifier of many proofs, or a recursive circuit,
+//! decides a single accumulator at the end. `batch_check` decides the
+//! accumulators of all its points with one MSM.
+//!
+//! `Plonk` takes it as its commitment scheme like any other
+//! [`PCS`](crate::PCS), opening each linear combination of a proof as a
+//! polynomial of its own. Its commitments are not hiding.
+//!
+//! [BCMS20]: https://eprint.iacr.org/2020/499
+
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, Field, One, PrimeField, UniformRand, Zero};
+use ark_poly::Polynomial;
+use ark_poly_commit::{
+    Evaluations, LinearCombination, PCCommitterKey, PCUniversalParams, PCVerifierKey, QuerySet,
+};
+use ark_serialize::*;
+use ark_std::{fmt, marker::PhantomData, string::String, vec, vec::Vec};
+use digest::Digest;
+use rand_core::RngCore;
+
+use crate::data_structures::LabeledPolynomial;
+use crate::pcs::{combine_commitments, combine_polynomials, Committed, LabeledCommitment, PCS};
+use crate::rng::FiatShamirRng;
+
+/// The domain separator of the generators and the transcripts.
+const PROTOCOL_NAME: &[u8] = b"PLONK-IPA-PC";
+
+/// The inner-product-argument commitment over the group of `G`, with the
+/// generators and the challenges drawn from `D`.
+pub struct Ipa<G: AffineCurve, D: Digest> {
+    _curve: PhantomData<G>,
+    _digest: PhantomData<D>,
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UniversalParams<G: AffineCurve> {
+    /// One generator per coefficient, a power of two of them.
+    pub generators: Vec<G>,
+    /// The generator of the inner product.
+    pub h: G,
+}
+
+/// The generators for the supported degree. The verifier needs all of them
+/// to decide accumulators, so both keys are the same.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitterKey<G: AffineCurve> {
+    pub generators: Vec<G>,
+    pub h: G,
+    /// the degree of the universal parameters it was trimmed from.
+    pub max_degree: usize,
+    /// the degree it was trimmed to, below its number of generators.
+    pub supported_degree: usize,
+}
+
+pub type VerifierKey<G> = CommitterKey<G>;
+
+impl<G: AffineCurve> PCUniversalParams for UniversalParams<G> {
+    fn max_degree(&self) -> usize {
+        self.generators.len() - 1
+    }
+}
+
+impl<G: AffineCurve> PCCommitterKey for CommitterKey<G> {
+    fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    fn supported_degree(&self) -> usize {
+        self.supported_degree
+    }
+}
+
+impl<G: AffineCurve> PCVerifierKey for CommitterKey<G> {
+    fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    fn supported_degree(&self) -> usize {
+        self.supported_degree
+    }
+}
+
+/// An opening: the cross terms of each round, the folded generator `U` and
+/// the folded coefficient.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof<G: AffineCurve> {
+    pub l_vec: Vec<G>,
+    pub r_vec: Vec<G>,
+    pub final_key: G,
+    pub c: G::ScalarField,
+}
+
+/// The openings of `batch_open`, one per point, in the order of the points
+/// in the query set.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchProof<G: AffineCurve> {
+    pub proofs: Vec<Proof<G>>,
+}
+
+/// The claim left by a succinct check, that `final_key` commits to the
+/// polynomial `h` of the `challenges`.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Accumulator<G: AffineCurve> {
+    pub challenges: Vec<G::ScalarField>,
+    pub final_key: G,
+}
+
+/// An opening as the single equation
+/// `C + h H + sum_j (l_j L_j + r_j R_j) + sum_i g_i G_i = 0`, over the
+/// combined commitment `C`, the cross terms of the proof and the key.
+#[derive(Clone, Debug)]
+pub struct FullCheck<G: AffineCurve> {
+    pub commitment: G,
+    pub h: G::ScalarField,
+    pub l: Vec<G::ScalarField>,
+    pub r: Vec<G::ScalarField>,
+    pub generators: Vec<G::ScalarField>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    DegreeIsZero,
+    /// The supported degree is beyond the universal parameters.
+    DegreeTooLarge {
+        degree: usize,
+        max_degree: usize,
+    },
+    /// A polynomial has more coefficients than the committer key has
+    /// generators.
+    TooManyCoefficients {
+        num_coefficients: usize,
+        num_generators: usize,
+    },
+    /// Degree bounds are not enforced by this scheme.
+    DegreeBound {
+        label: String,
+    },
+    /// An accumulator or a proof has `rounds` rounds, but the key takes
+    /// `expected`.
+    WrongRounds {
+        rounds: usize,
+        expected: usize,
+    },
+    /// No accumulator to fold.
+    NoAccumulators,
+    MissingPolynomial {
+        label: String,
+    },
+    MissingCommitment {
+        label: String,
+    },
+    MissingEvaluation {
+        label: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DegreeIsZero => write!(f, "degree is zero"),
+            Error::DegreeTooLarge { degree, max_degree } => write!(
+                f,
+                "degree {} is beyond the maximum degree {}",
+                degree, max_degree
+            ),
+            Error::TooManyCoefficients {
+                num_coefficients,
+                num_generators,
+            } => write!(
+                f,
+                "{} coefficients for {} generators",
+                num_coefficients, num_generators
+            ),
+            Error::DegreeBound { label } => write!(f, "degree bound on {}", label),
+            Error::WrongRounds { rounds, expected } => {
+                write!(f, "{} rounds where the key takes {}", rounds, expected)
+            }
+            Error::NoAccumulators => write!(f, "no accumulator to fold"),
+            Error::MissingPolynomial { label } => write!(f, "missing polynomial {}", label),
+            Error::MissingCommitment { label } => write!(f, "missing commitment {}", label),
+            Error::MissingEvaluation { label } => write!(f, "missing evaluation of {}", label),
+        }
+    }
+}
+
+impl ark_std::error::Error for Error {}
+
+impl<G: AffineCurve> Accumulator<G> {
+    /// `h` at `point`, in time `log n`.
+    pub fn evaluate(&self, point: G::ScalarField) -> G::ScalarField {
+        let rounds = self.challenges.len();
+        let mut powers = Vec::with_capacity(rounds);
+        let mut power = point;
+        for _ in 0..rounds {
+            powers.push(power);
+            power.square_in_place();
+        }
+        self.challenges
+            .iter()
+            .zip(powers.iter().rev())
+            .map(|(u, x)| u.inverse().unwrap() + *u * x)
+            .product()
+    }
+
+    /// The coefficients of `h`, in linear time.
+    pub fn coefficients(&self) -> Vec<G::ScalarField> {
+        let mut s = vec![G::ScalarField::one()];
+        for u in self.challenges.iter() {
+            let u_inv = u.inverse().unwrap();
+            s = s.iter().flat_map(|s| vec![*s * u_inv, *s * u]).collect();
+        }
+        s
+    }
+}
+
+impl<G: AffineCurve> FullCheck<G> {
+    /// Whether the equation holds, in an MSM of the size of the key.
+    pub fn holds(&self, vk: &VerifierKey<G>, proof: &Proof<G>) -> bool {
+        let mut bases = vec![self.commitment, vk.h];
+        let mut scalars = vec![G::ScalarField::one(), self.h];
+        bases.extend(proof.l_vec.iter().chain(&proof.r_vec));
+        scalars.extend(self.l.iter().chain(&self.r));
+        bases.extend(&vk.generators);
+        scalars.extend(&self.generators);
+        msm(&bases, &scalars).is_zero()
+    }
+}
+
+impl<G: AffineCurve, D: Digest> Ipa<G, D> {
+    /// A challenge of `fs_rng`, never zero as the rounds invert it.
+    fn challenge(fs_rng: &mut FiatShamirRng<D>) -> G::ScalarField {
+        loop {
+            let u = G::ScalarField::rand(fs_rng);
+            if !u.is_zero() {
+                return u;
+            }
+        }
+    }
+
+    /// The opening of the `coeffs` of `commitment` at `point` to `value`,
+    /// with the challenges of its rounds.
+    fn prove(
+        ck: &CommitterKey<G>,
+        coeffs: &[G::ScalarField],
+        commitment: &G,
+        point: G::ScalarField,
+        value: G::ScalarField,
+        fs_rng: &mut FiatShamirRng<D>,
+    ) -> Result<(Proof<G>, Vec<G::ScalarField>), Error> {
+        let n = ck.generators.len();
+        if coeffs.len() > n {
+            return Err(Error::TooManyCoefficients {
+                num_coefficients: coeffs.len(),
+                num_generators: n,
+            });
+        }
+        fs_rng.absorb(&to_bytes![commitment, point, value].unwrap());
+        let h = ck.h.mul(Self::challenge(fs_rng).into_repr()).into_affine();
+
+        let mut a = coeffs.to_vec();
+        a.resize(n, G::ScalarField::zero());
+        let mut b = Vec::with_capacity(n);
+        let mut power = G::ScalarField::one();
+        for _ in 0..n {
+            b.push(power);
+            power *= point;
+        }
+        let mut g = ck.generators.clone();
+
+        let (mut l_vec, mut r_vec, mut challenges) = (Vec::new(), Vec::new(), Vec::new());
+        while a.len() > 1 {
+            let half = a.len() / 2;
+            let (a_lo, a_hi) = a.split_at(half);
+            let (b_lo, b_hi) = b.split_at(half);
+            let (g_lo, g_hi) = g.split_at(half);
+
+            let l = msm(g_lo, a_hi) + h.mul(inner_product(a_hi, b_lo).into_repr());
+            let r = msm(g_hi, a_lo) + h.mul(inner_product(a_lo, b_hi).into_repr());
+            let lr = G::Projective::batch_normalization_into_affine(&[l, r]);
+            fs_rng.absorb(&to_bytes![lr[0], lr[1]].unwrap());
+            let u = Self::challenge(fs_rng);
+            let u_inv = u.inverse().unwrap();
+
+            let a_next = fold(a_lo, a_hi, u, u_inv);
+            let b_next = fold(b_lo, b_hi, u_inv, u);
+            let g_next: Vec<_> = g_lo
+                .iter()
+                .zip(g_hi)
+                .map(|(lo, hi)| lo.mul(u_inv.into_repr()) + hi.mul(u.into_repr()))
+                .collect();
+
+            a = a_next;
+            b = b_next;
+            g = G::Projective::batch_normalization_into_affine(&g_next);
+            l_vec.push(lr[0]);
+            r_vec.push(lr[1]);
+            challenges.push(u);
+        }
+
+        let proof = Proof {
+            l_vec,
+            r_vec,
+            final_key: g[0],
+            c: a[0],
+        };
+        Ok((proof, challenges))
+    }
+
+    /// The challenges of the opening `proof` of `commitment` at `point` to
+    /// `value`: the one scaling `H`, and those of the rounds.
+    fn challenges(
+        vk: &VerifierKey<G>,
+        commitment: &G,
+        point: G::ScalarField,
+        value: G::ScalarField,
+        proof: &Proof<G>,
+        fs_rng: &mut FiatShamirRng<D>,
+    ) -> Result<(G::ScalarField, Vec<G::ScalarField>), Error> {
+        let rounds = log2(vk.generators.len());
+        if proof.l_vec.len() != rounds || proof.r_vec.len() != rounds {
+            return Err(Error::WrongRounds {
+                rounds: proof.l_vec.len().max(proof.r_vec.len()),
+                expected: rounds,
+            });
+        }
+        fs_rng.absorb(&to_bytes![commitment, point, value].unwrap());
+        let xi = Self::challenge(fs_rng);
+
+        let mut challenges = Vec::with_capacity(rounds);
+        for (l, r) in proof.l_vec.iter().zip(&proof.r_vec) {
+            fs_rng.absorb(&to_bytes![l, r].unwrap());
+            challenges.push(Self::challenge(fs_rng));
+        }
+        Ok((xi, challenges))
+    }
+
+    /// Checks the rounds of `proof` in time `log n`, and leaves the claim on
+    /// its final key. `None` if the rounds do not hold.
+    fn succinct(
+        vk: &VerifierKey<G>,
+        commitment: &G,
+        point: G::ScalarField,
+        value: G::ScalarField,
+        proof: &Proof<G>,
+        fs_rng: &mut FiatShamirRng<D>,
+    ) -> Result<Option<Accumulator<G>>, Error> {
+        let (xi, challenges) = Self::challenges(vk, commitment, point, value, proof, fs_rng)?;
+        let h = vk.h.mul(xi.into_repr()).into_affine();
+        let acc = Accumulator {
+            challenges,
+            final_key: proof.final_key,
+        };
+
+        // C + v H + sum_j (u_j^-2 L_j + u_j^2 R_j) = c U + c h(x) H
+        let mut bases = vec![*commitment, h, proof.final_key];
+        let mut scalars = vec![
+            G::ScalarField::one(),
+            value - proof.c * acc.evaluate(point),
+            -proof.c,
+        ];
+        for ((l, r), u) in proof.l_vec.iter().zip(&proof.r_vec).zip(&acc.challenges) {
+            let u_2 = u.square();
+            bases.push(*l);
+            scalars.push(u_2.inverse().unwrap());
+            bases.push(*r);
+            scalars.push(u_2);
+        }
+        if msm(&bases, &scalars).is_zero() {
+            Ok(Some(acc))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The transcript of an opening under `opening_challenge`.
+    fn transcript(opening_challenge: G::ScalarField) -> FiatShamirRng<D> {
+        FiatShamirRng::from_seed(&to_bytes![PROTOCOL_NAME, opening_challenge].unwrap())
+    }
+
+    /// The combination of `commitments` and `values` with the powers of
+    /// `opening_challenge`.
+    fn combine<'a>(
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        values: impl IntoIterator<Item = G::ScalarField>,
+        opening_challenge: G::ScalarField,
+    ) -> (G, G::ScalarField) {
+        let bases: Vec<_> = commitments.into_iter().map(|c| *c.commitment()).collect();
+        let mut scalars = Vec::with_capacity(bases.len());
+        let mut value = G::ScalarField::zero();
+        let mut challenge = G::ScalarField::one();
+        for v in values {
+            scalars.push(challenge);
+            value += challenge * v;
+            challenge *= opening_challenge;
+        }
+        (msm(&bases, &scalars).into_affine(), value)
+    }
+
+    /// The succinct part of `check`: the accumulator of the opening `proof`
+    /// of `commitments` at `point` to `values`, combined with the powers of
+    /// `opening_challenge`, or `None` if its rounds do not hold.
+    pub fn succinct_check<'a>(
+        vk: &VerifierKey<G>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        point: G::ScalarField,
+        values: impl IntoIterator<Item = G::ScalarField>,
+        proof: &Proof<G>,
+        opening_challenge: G::ScalarField,
+    ) -> Result<Option<Accumulator<G>>, Error> {
+        let (commitment, value) = Self::combine(commitments, values, opening_challenge);
+        let fs_rng = &mut Self::transcript(opening_challenge);
+        Self::succinct(vk, &commitment, point, value, proof, fs_rng)
+    }
+
+    /// The scalars of `check` as a single equation, the succinct check with
+    /// `U` replaced by `<s, G>`, for verifiers that take its linear-time
+    /// part at once, e.g. a circuit wrapping the opening: see
+    /// `Composer::wrap_ipa_opening`.
+    pub fn full_check<'a>(
+        vk: &VerifierKey<G>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        point: G::ScalarField,
+        values: impl IntoIterator<Item = G::ScalarField>,
+        proof: &Proof<G>,
+        opening_challenge: G::ScalarField,
+    ) -> Result<FullCheck<G>, Error> {
+        let (commitment, value) = Self::combine(commitments, values, opening_challenge);
+        let fs_rng = &mut Self::transcript(opening_challenge);
+        let (xi, challenges) = Self::challenges(vk, &commitment, point, value, proof, fs_rng)?;
+        let acc = Accumulator {
+            challenges,
+            final_key: proof.final_key,
+        };
+
+        let squares: Vec<_> = acc.challenges.iter().map(|u| u.square()).collect();
+        Ok(FullCheck {
+            commitment,
+            h: xi * (value - proof.c * acc.evaluate(point)),
+            l: squares.iter().map(|u_2| u_2.inverse().unwrap()).collect(),
+            r: squares,
+            generators: acc.coefficients().iter().map(|s| -proof.c * s).collect(),
+        })
+    }
+
+    /// The transcript of a fold of `accumulators`, and its challenges: the
+    /// one combining them and the point their combination is opened at.
+    #[allow(clippy::type_complexity)]
+    fn fold_transcript(
+        accumulators: &[Accumulator<G>],
+        rounds: usize,
+    ) -> Result<(FiatShamirRng<D>, G::ScalarField, G::ScalarField), Error> {
+        if accumulators.is_empty() {
+            return Err(Error::NoAccumulators);
+        }
+        let mut fs_rng = FiatShamirRng::from_seed(&to_bytes![PROTOCOL_NAME].unwrap());
+        for acc in accumulators {
+            if acc.challenges.len() != rounds {
+                return Err(Error::WrongRounds {
+                    rounds: acc.challenges.len(),
+                    expected: rounds,
+                });
+            }
+            fs_rng.absorb(&to_bytes![acc.final_key, acc.challenges].unwrap());
+        }
+        let r = Self::challenge(&mut fs_rng);
+        let z = Self::challenge(&mut fs_rng);
+        Ok((fs_rng, r, z))
+    }
+
+    /// The combination with the powers of `r` of the final keys of
+    /// `accumulators` and of their `h` at `z`.
+    fn fold_claim(
+        accumulators: &[Accumulator<G>],
+        r: G::ScalarField,
+        z: G::ScalarField,
+    ) -> (G, G::ScalarField) {
+        let mut bases = Vec::with_capacity(accumulators.len());
+        let mut scalars = Vec::with_capacity(accumulators.len());
+        let mut value = G::ScalarField::zero();
+        let mut power = G::ScalarField::one();
+        for acc in accumulators {
+            bases.push(acc.final_key);
+            scalars.push(power);
+            value += power * acc.evaluate(z);
+            power *= r;
+        }
+        (msm(&bases, &scalars).into_affine(), value)
+    }
+
+    /// Folds `accumulators` into one, with the proof of the fold: the
+    /// opening of their combination with the powers of a challenge `r`, at
+    /// a challenge point. Linear in the size of the key, like one opening.
+    pub fn accumulate(
+        ck: &CommitterKey<G>,
+        accumulators: &[Accumulator<G>],
+    ) -> Result<(Accumulator<G>, Proof<G>), Error> {
+        let rounds = log2(ck.generators.len());
+        let (mut fs_rng, r, z) = Self::fold_transcript(accumulators, rounds)?;
+
+        let mut coeffs = vec![G::ScalarField::zero(); ck.generators.len()];
+        let mut power = G::ScalarField::one();
+        for acc in accumulators {
+            for (c, s) in coeffs.iter_mut().zip(acc.coefficients()) {
+                *c += power * s;
+            }
+            power *= r;
+        }
+        let (commitment, value) = Self::fold_claim(accumulators, r, z);
+
+        let (proof, challenges) = Self::prove(ck, &coeffs, &commitment, z, value, &mut fs_rng)?;
+        let acc = Accumulator {
+            challenges,
+            final_key: proof.final_key,
+        };
+        Ok((acc, proof))
+    }
+
+    /// The accumulator of the fold of `accumulators` by `proof`, checked in
+    /// time `log n` plus one MSM over the accumulators, or `None` if the
+    /// fold does not hold. Deciding it decides all of `accumulators`.
+    pub fn verify_accumulation(
+        vk: &VerifierKey<G>,
+        accumulators: &[Accumulator<G>],
+        proof: &Proof<G>,
+    ) -> Result<Option<Accumulator<G>>, Error> {
+        let rounds = log2(vk.generators.len());
+        let (mut fs_rng, r, z) = Self::fold_transcript(accumulators, rounds)?;
+        let (commitment, value) = Self::fold_claim(accumulators, r, z);
+        Self::succinct(vk, &commitment, z, value, proof, &mut fs_rng)
+    }
+
+    /// Whether the final key of each of `accumulators` commits to its `h`,
+    /// all with one MSM over the generators, combined with powers of a
+    /// random challenge from `rng`.
+    pub fn decide<R: RngCore>(
+        vk: &VerifierKey<G>,
+        accumulators: &[Accumulator<G>],
+        rng: &mut R,
+    ) -> Result<bool, Error> {
+        let rounds = log2(vk.generators.len());
+        let r = G::ScalarField::rand(rng);
+        let mut bases = vk.generators.clone();
+        let mut scalars = vec![G::ScalarField::zero(); bases.len()];
+        let mut power = G::ScalarField::one();
+        for acc in accumulators {
+            if acc.challenges.len() != rounds {
+                return Err(Error::WrongRounds {
+                    rounds: acc.challenges.len(),
+                    expected: rounds,
+                });
+            }
+            for (c, s) in scalars.iter_mut().zip(acc.coefficients()) {
+                *c += power * s;
+            }
+            bases.push(acc.final_key);
+            scalars.push(-power);
+            power *= r;
+        }
+        Ok(msm(&bases, &scalars).is_zero())
+    }
+}
+
+impl<G: AffineCurve, D: Digest> PCS<G::ScalarField> for Ipa<G, D> {
+    type UniversalParams = UniversalParams<G>;
+    type CommitterKey = CommitterKey<G>;
+    type VerifierKey = VerifierKey<G>;
+    type Commitment = G;
+    type Randomness = ();
+    type Proof = Proof<G>;
+    type BatchProof = BatchProof<G>;
+    type LCProof = BatchProof<G>;
+    type Error = Error;
+
+    /// Generators for `max_degree + 1` coefficients, rounded up to a power
+    /// of two, derived from `D` alone: `rng` is not used, and the setup is
+    /// the same for everyone.
+    fn setup<R: RngCore>(max_degree: usize, _rng: &mut R) -> Result<UniversalParams<G>, Error> {
+        if max_degree < 1 {
+            return Err(Error::DegreeIsZero);
+        }
+        let n = (max_degree + 1).next_power_of_two();
+        let mut fs_rng =
+            FiatShamirRng::<D>::from_seed(&to_bytes![PROTOCOL_NAME, n as u64].unwrap());
+        let generators: Vec<_> = (0..=n).map(|_| G::Projective::rand(&mut fs_rng)).collect();
+        let mut generators = G::Projective::batch_normalization_into_affine(&generators);
+        let h = generators.pop().unwrap();
+        Ok(UniversalParams { generators, h })
+    }
+
+    fn trim(
+        pp: &UniversalParams<G>,
+        supported_degree: usize,
+    ) -> Result<(CommitterKey<G>, VerifierKey<G>), Error> {
+        let max_degree = pp.generators.len() - 1;
+        if supported_degree > max_degree {
+            return Err(Error::DegreeTooLarge {
+                degree: supported_degree,
+                max_degree,
+            });
+        }
+
+        let n = (supported_degree + 1).next_power_of_two();
+        let ck = CommitterKey {
+            generators: pp.generators[..n].to_vec(),
+            h: pp.h,
+            max_degree,
+            supported_degree,
+        };
+        Ok((ck.clone(), ck))
+    }
+
+    fn commit<'a>(
+        ck: &CommitterKey<G>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<G::ScalarField>>,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<Committed<G::ScalarField, Self>, Error> {
+        let mut comms = Vec::new();
+        for p in polynomials {
+            if p.degree_bound().is_some() {
+                return Err(Error::DegreeBound {
+                    label: p.label().clone(),
+                });
+            }
+            let coeffs = &p.polynomial().coeffs;
+            if coeffs.len() > ck.generators.len() {
+                return Err(Error::TooManyCoefficients {
+                    num_coefficients: coeffs.len(),
+                    num_generators: ck.generators.len(),
+                });
+            }
+            let comm = msm(&ck.generators, coeffs).into_affine();
+            comms.push(LabeledCommitment::new(p.label().clone(), comm, None));
+        }
+        let rands = vec![(); comms.len()];
+        Ok((comms, rands))
+    }
+
+    fn open<'a>(
+        ck: &CommitterKey<G>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<G::ScalarField>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        point: G::ScalarField,
+        opening_challenge: G::ScalarField,
+        _rands: impl IntoIterator<Item = &'a ()>,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<Proof<G>, Error>
+    where
+        G: 'a,
+    {
+        let mut combined = vec![G::ScalarField::zero(); ck.generators.len()];
+        let mut values = Vec::new();
+        let mut challenge = G::ScalarField::one();
+        for p in polynomials {
+            let coeffs = &p.polynomial().coeffs;
+            if coeffs.len() > combined.len() {
+                return Err(Error::TooManyCoefficients {
+                    num_coefficients: coeffs.len(),
+                    num_generators: combined.len(),
+                });
+            }
+            for (acc, c) in combined.iter_mut().zip(coeffs) {
+                *acc += challenge * c;
+            }
+            values.push(p.polynomial().evaluate(&point));
+            challenge *= opening_challenge;
+        }
+        let (commitment, value) = Self::combine(commitments, values, opening_challenge);
+
+        let fs_rng = &mut Self::transcript(opening_challenge);
+        let (proof, _) = Self::prove(ck, &combined, &commitment, point, value, fs_rng)?;
+        Ok(proof)
+    }
+
+    fn check<'a, R: RngCore>(
+        vk: &VerifierKey<G>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        point: G::ScalarField,
+        values: impl IntoIterator<Item = G::ScalarField>,
+        proof: &Proof<G>,
+        opening_challenge: G::ScalarField,
+        rng: &mut R,
+    ) -> Result<bool, Error>
+    where
+        G: 'a,
+    {
+        match Self::succinct_check(vk, commitments, point, values, proof, opening_challenge)? {
+            Some(acc) => Self::decide(vk, &[acc], rng),
+            None => Ok(false),
+        }
+    }
+
+    fn batch_open<'a>(
+        ck: &CommitterKey<G>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<G::ScalarField>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        query_set: &QuerySet<G::ScalarField>,
+        opening_challenge: G::ScalarField,
+        _rands: impl IntoIterator<Item = &'a ()>,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchProof<G>, Error>
+    where
+        G: 'a,
+    {
+        let polynomials: Vec<_> = polynomials.into_iter().collect();
+        let commitments: Vec<_> = commitments.into_iter().collect();
+        let mut proofs = Vec::new();
+        for (point, labels) in labels_at_points(query_set) {
+            let mut opened = Vec::with_capacity(labels.len());
+            let mut comms = Vec::with_capacity(labels.len());
+            for label in labels {
+                let p = polynomials
+                    .iter()
+                    .find(|p| p.label() == label)
+                    .ok_or_else(|| Error::MissingPolynomial {
+                        label: label.clone(),
+                    })?;
+                let c = commitments
+                    .iter()
+                    .find(|c| c.label() == label)
+                    .ok_or_else(|| Error::MissingCommitment {
+                        label: label.clone(),
+                    })?;
+                opened.push(*p);
+                comms.push(*c);
+            }
+            let rands = vec![(); opened.len()];
+            let proof = Self::open(ck, opened, comms, point, opening_challenge, &rands, None)?;
+            proofs.push(proof);
+        }
+        Ok(BatchProof { proofs })
+    }
+
+    fn batch_check<'a, R: RngCore>(
+        vk: &VerifierKey<G>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        query_set: &QuerySet<G::ScalarField>,
+        evaluations: &Evaluations<G::ScalarField, G::ScalarField>,
+        proof: &BatchProof<G>,
+        opening_challenge: G::ScalarField,
+        rng: &mut R,
+    ) -> Result<bool, Error>
+    where
+        G: 'a,
+    {
+        let commitments: Vec<_> = commitments.into_iter().collect();
+        let points = labels_at_points(query_set);
+        if points.len() != proof.proofs.len() {
+            return Ok(false);
+        }
+
+        let mut accumulators = Vec::with_capacity(points.len());
+        for ((point, labels), proof) in points.into_iter().zip(&proof.proofs) {
+            let mut comms = Vec::with_capacity(labels.len());
+            let mut values = Vec::with_capacity(labels.len());
+            for label in labels {
+                let c = commitments
+                    .iter()
+                    .find(|c| c.label() == label)
+                    .ok_or_else(|| Error::MissingCommitment {
+                        label: label.clone(),
+                    })?;
+                let value = evaluations
+                    .get(&(label.clone(), point))
+                    .copied()
+                    .ok_or_else(|| Error::MissingEvaluation {
+                        label: label.clone(),
+                    })?;
+                comms.push(*c);
+                values.push(value);
+            }
+            match Self::succinct_check(vk, comms, point, values, proof, opening_challenge)? {
+                Some(acc) => accumulators.push(acc),
+                None => return Ok(false),
+            }
+        }
+        Self::decide(vk, &accumulators, rng)
+    }
+
+    fn open_combinations<'a>(
+        ck: &CommitterKey<G>,
+        lcs: impl IntoIterator<Item = &'a LinearCombination<G::ScalarField>>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<G::ScalarField>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        query_set: &QuerySet<G::ScalarField>,
+        opening_challenge: G::ScalarField,
+        _rands: impl IntoIterator<Item = &'a ()>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchProof<G>, Error>
+    where
+        G: 'a,
+    {
+        let lcs: Vec<_> = lcs.into_iter().collect();
+        let polynomials: Vec<_> = polynomials.into_iter().collect();
+        let commitments: Vec<_> = commitments.into_iter().collect();
+        let combined = combine_polynomials(&lcs, &polynomials)
+            .map_err(|label| Error::MissingPolynomial { label })?;
+        let comms = combine_commitments(&lcs, &commitments, ck.generators[0])
+            .map_err(|label| Error::MissingCommitment { label })?;
+        let rands = vec![(); combined.len()];
+        Self::batch_open(ck, &combined, &comms, query_set, opening_challenge, &rands, rng)
+    }
+
+    fn check_combinations<'a, R: RngCore>(
+        vk: &VerifierKey<G>,
+        lcs: impl IntoIterator<Item = &'a LinearCombination<G::ScalarField>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<G>>,
+        query_set: &QuerySet<G::ScalarField>,
+        evaluations: &Evaluations<G::ScalarField, G::ScalarField>,
+        proof: &BatchProof<G>,
+        opening_challenge: G::ScalarField,
+        rng: &mut R,
+    ) -> Result<bool, Error>
+    where
+        G: 'a,
+    {
+        let lcs: Vec<_> = lcs.into_iter().collect();
+        let commitments: Vec<_> = commitments.into_iter().collect();
+        let combined = combine_commitments(&lcs, &commitments, vk.generators[0])
+            .map_err(|label| Error::MissingCommitment { label })?;
+        Self::batch_check(
+            vk,
+            &combined,
+            query_set,
+            evaluations,
+            proof,
+            opening_challenge,
+            rng,
+        )
+    }
+}
+
+/// The labels opened at each distinct point of `query_set`, in its order.
+fn labels_at_points<F: Field>(query_set: &QuerySet<F>) -> Vec<(F, Vec<&String>)> {
+    let mut points: Vec<(F, Vec<&String>)> = Vec::new();
+    for (label, (_, point)) in query_set {
+        if let Some((_, labels)) = points.iter_mut().find(|(p, _)| p == point) {
+            labels.push(label);
+        } else {
+            points.push((*point, vec![label]));
+        }
+    }
+    points
+}
+
+fn msm<G: AffineCurve>(bases: &[G], scalars: &[G::ScalarField]) -> G::Projective {
+    let scalars: Vec<_> = scalars.iter().map(|s| s.into_repr()).collect();
+    VariableBaseMSM::multi_scalar_mul(&bases[..scalars.len()], &scalars)
+}
+
+fn inner_product<F: Field>(a: &[F], b: &[F]) -> F {
+    a.iter().zip(b).map(|(a, b)| *a * b).sum()
+}
+
+/// `x * lo + y * hi`, elementwise.
+fn fold<F: Field>(lo: &[F], hi: &[F], x: F, y: F) -> Vec<F> {
+    lo.iter().zip(hi).map(|(lo, hi)| x * lo + y * hi).collect()
+}
+
+fn log2(n: usize) -> usize {
+    ark_std::log2(n) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_pallas::{Affine, Fr};
+    use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+    use ark_std::{string::ToString, test_rng};
+    use blake2::Blake2s;
+
+    use super::*;
+    use crate::utils::to_labeled;
+
+    type PC = Ipa<Affine, Blake2s>;
+
+    /// The accumulator of an opening of a random polynomial of `ck`.
+    fn accumulator<R: RngCore>(ck: &CommitterKey<Affine>, rng: &mut R) -> Accumulator<Affine> {
+        let a = to_labeled("a", DensePolynomial::<Fr>::rand(15, rng));
+        let (comms, rands) = PC::commit(ck, Some(&a), None).unwrap();
+        let (point, challenge) = (Fr::rand(rng), Fr::rand(rng));
+        let proof = PC::open(ck, Some(&a), &comms, point, challenge, &rands, None).unwrap();
+        let values = vec![a.evaluate(&point)];
+        PC::succinct_check(ck, &comms, point, values, &proof, challenge)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_open() {
+        let rng = &mut test_rng();
+        let pp = PC::setup(16, rng).unwrap();
+        let (ck, vk) = PC::trim(&pp, 15).unwrap();
+
+        let a = to_labeled("a", DensePolynomial::<Fr>::rand(15, rng));
+        let b = to_labeled("b", DensePolynomial::<Fr>::rand(9, rng));
+        let (comms, rands) = PC::commit(&ck, vec![&a, &b], None).unwrap();
+
+        let point = Fr::rand(rng);
+        let challenge = Fr::rand(rng);
+        let values = vec![a.evaluate(&point), b.evaluate(&point)];
+        let proof = PC::open(&ck, vec![&a, &b], &comms, point, challenge, &rands, None).unwrap();
+        assert_eq!(proof.l_vec.len(), 4);
+        assert!(PC::check(&vk, &comms, point, values.clone(), &proof, challenge, rng).unwrap());
+        let full = PC::full_check(&vk, &comms, point, values.clone(), &proof, challenge).unwrap();
+        assert!(full.holds(&vk, &proof));
+        let wrong = vec![values[0], values[0]];
+        assert!(!PC::check(&vk, &comms, point, wrong.clone(), &proof, challenge, rng).unwrap());
+        let full = PC::full_check(&vk, &comms, point, wrong, &proof, challenge).unwrap();
+        assert!(!full.holds(&vk, &proof));
+
+        let c = to_labeled("c", DensePolynomial::<Fr>::rand(16, rng));
+        assert!(matches!(
+            PC::commit(&ck, Some(&c), None),
+            Err(Error::TooManyCoefficients { .. })
+        ));
+    }
+
+    #[test]
+    fn test_batch_open() {
+        let rng = &mut test_rng();
+        let pp = PC::setup(32, rng).unwrap();
+        let (ck, vk) = PC::trim(&pp, 31).unwrap();
+
+        let a = to_labeled("a", DensePolynomial::<Fr>::rand(15, rng));
+        let b = to_labeled("b", DensePolynomial::<Fr>::rand(31, rng));
+        let (comms, rands) = PC::commit(&ck, vec![&a, &b], None).unwrap();
+
+        let zeta = Fr::rand(rng);
+        let shifted = Fr::rand(rng);
+        let mut query_set = QuerySet::new();
+        let mut evaluations = Evaluations::new();
+        for (p, (name, x)) in [
+            (&a, ("zeta", zeta)),
+            (&b, ("zeta", zeta)),
+            (&b, ("shifted_zeta", shifted)),
+        ]
+        .iter()
+        {
+            query_set.insert((p.label().clone(), (name.to_string(), *x)));
+            evaluations.insert((p.label().clone(), *x), p.evaluate(x));
+        }
+
+        let challenge = Fr::rand(rng);
+        let polys = vec![&a, &b];
+        let proof =
+            PC::batch_open(&ck, polys, &comms, &query_set, challenge, &rands, None).unwrap();
+        assert_eq!(proof.proofs.len(), 2);
+        assert!(PC::batch_check(
+            &vk,
+            &comms,
+            &query_set,
+            &evaluations,
+            &proof,
+            challenge,
+            rng
+        )
+        .unwrap());
+
+        let key = (a.label().clone(), zeta);
+        *evaluations.get_mut(&key).unwrap() += Fr::one();
+        assert!(!PC::batch_check(
+            &vk,
+            &comms,
+            &query_set,
+            &evaluations,
+            &proof,
+            challenge,
+            rng
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_accumulate() {
+        let rng = &mut test_rng();
+        let pp = PC::setup(16, rng).unwrap();
+        let (ck, vk) = PC::trim(&pp, 15).unwrap();
+
+        let accumulators: Vec<_> = (0..3).map(|_| accumulator(&ck, rng)).collect();
+        assert_eq!(accumulators[0].challenges.len(), 4);
+        let point = Fr::rand(rng);
+        let coeffs = DensePolynomial::from_coefficients_vec(accumulators[0].coefficients());
+        assert_eq!(coeffs.evaluate(&point), accumulators[0].evaluate(point));
+
+        let (acc, proof) = PC::accumulate(&ck, &accumulators).unwrap();
+        let verified = PC::verify_accumulation(&vk, &accumulators, &proof).unwrap();
+        assert_eq!(verified, Some(acc.clone()));
+        assert!(PC::decide(&vk, ark_std::slice::from_ref(&acc), rng).unwrap());
+        assert!(PC::decide(&vk, &accumulators, rng).unwrap());
+
+        // a fold of other accumulators, or a wrong final key, fails.
+        let other = PC::verify_accumulation(&vk, &accumulators[..2], &proof).unwrap();
+        assert_eq!(other, None);
+        let mut wrong = acc;
+        wrong.final_key = accumulators[0].final_key;
+        assert!(!PC::decide(&vk, &[wrong], rng).unwrap());
+    }
+}
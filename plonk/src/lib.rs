@@ -1,6 +1,175 @@
 //! An implementation of the [`PlonK`].
 //!
 //! [`PlonK`]: https://eprint.iacr.org/2019/953.pdf
+//!
+//! The scheme is generic over the polynomial commitment. With a KZG scheme
+//! such as `marlin_pc::MarlinKZG10` it needs a universal trusted setup, while
+//! `ipa_pc::InnerProductArgPC` gives a transparent setup over any curve, e.g.
+//! Pallas or the G1 group of BLS12-381, with log-sized opening proofs.
+//! `Shplonk` is a KZG scheme behind the crate's own `PCS` trait whose batched
+//! openings take two points of G1 whatever the number of points opened at.
+//! `Ipa` is an inner-product-argument scheme behind it, with Halo-style
+//! accumulation: `Ipa::accumulate` folds the linear-time part of verifying
+//! many openings into one accumulator, decided once.
+//! `Plonk::batch_verify` checks many proofs under `MarlinKZG10` with one
+//! pairing check, on the KZG claims it derives from each proof, see
+//! [`batch`].
+//!
+//! `Composer::append` composes circuits built apart, such as prebuilt
+//! gadgets, into one, whose variables are then connected by `assert_equal`.
+//! `Composer::write_dot` and `Composer::write_json` export a circuit, with
+//! the namespaces of its gates and its unconstrained variables, for review.
+//! `Composer::push_namespace` and `Composer::label` name gates and variables,
+//! e.g. `merkle/level3`, in these exports, in the failures of
+//! `Composer::check_satisfied` and in the panics of out of range values.
+//! `Composer::export_witness` writes the values of a circuit to a
+//! `WitnessFile`, bound to it by its circuit hash, and
+//! `Composer::import_witness` assigns them to the same circuit built without
+//! values, so that witnesses can be generated in another process.
+//! `Composer::check_satisfied` evaluates every gate on the assigned values
+//! and reports the row, gate type, selectors and wires of each unsatisfied
+//! one, where a proof of the circuit would only fail.
+//! `CircuitBuilder` builds the shape of a circuit without values into a
+//! `CompiledCircuit`, whose keys are generated without any witness, and a
+//! `WitnessFiller` of it runs the same circuit code with values to prove.
+//! `CompiledCircuit::write` stores the shape as an artifact, which a prover
+//! reads back and assigns a `WitnessFile` to without building it again.
+//! `Composer::optimize` removes the unused variables, the duplicated constant
+//! gates and the gates folded into others, e.g. along chains of additions,
+//! before keygen, and maps the public inputs onto the rows left.
+//! `Composer::stats` counts the gates of a circuit by type, with the domain
+//! size and the SRS degree it needs, to size a universal setup beforehand.
+//!
+//! Circuits have four wire columns unless `Composer::with_wires` sets three
+//! or five, with a coset of `ks` per column for the permutation. Three wires
+//! drop `w_0`, and with it the custom gates, and leave arithmetic and lookup
+//! gates; five add `w_4` and its selector `q_4` to the arithmetic gate, e.g.
+//! `Composer::create_wide_add_gate`, and split the quotient into five chunks.
+//! Keys and proofs hold a commitment and a permutation polynomial per wire.
+//!
+//! `Composer::add_table` registers a table, e.g. an XOR of 4-bit values, and
+//! `Composer::lookup` constrains a row to be one of its rows with a single
+//! gate, by a plookup argument. `Composer::xor_gate` and
+//! `Composer::and_gate` take a row per 2 bits of their inputs instead.
+//! `Composer::add_points` adds two `Point`s of a twisted Edwards curve over
+//! the field, e.g. Jubjub over BLS12-381, in two rows of a complete-addition
+//! gate, and `Composer::mul_point` multiplies one by the bits of a scalar.
+//! On these, `Composer::kzg_opening` verifies a KZG opening of an inner proof
+//! up to its pairings, which `Composer::accumulate_pairings` batches into one
+//! `DeferredPairing` for the verifier of the outer proof.
+//! `Composer::wrap_ipa_opening` checks an opening of `Ipa` over Jubjub, with
+//! its linear-time MSM, so that the opening is verified through a proof
+//! under KZG; whole proofs under `Ipa` are not wrapped.
+//! `Composer::add_memory` registers a random-access memory, e.g. the RAM of
+//! a VM, whose reads and writes take a row each at any address: once
+//! `Composer::close_memory` lays them out sorted by address, a grand product
+//! checks that they are the same records.
+//!
+//! With the `zeroize` feature, the prover overwrites witness values and the
+//! polynomials derived from them once they are no longer needed. The
+//! `secret-hardened` feature implies it, and also inverts witness-dependent
+//! values in constant time, in the prover's grand products and in the
+//! witness assignment of the composer's gadgets, e.g. `Composer::add_points`.
+//! Commitment randomness is owned by the commitment scheme, and the
+//! Fiat-Shamir transcript only absorbs public data. The range gates and
+//! decompositions of the composer return `ComposerError::OutOfRange` for a
+//! value too wide for them, rather than panicking on it.
+//!
+//! The prover records `tracing` spans for each round and for synthesis, FFTs,
+//! commitments and openings, with their sizes; a subscriber reporting span
+//! durations shows where a proof spends its time. `Plonk::estimate` predicts
+//! the peak memory and prove time of a circuit before any of it is computed,
+//! and `Plonk::verifier_estimate` the size of its proofs and the cost of
+//! verifying them under a given commitment scheme.
+//! The prover keeps its polynomials as coefficients and extends them to the
+//! 4n coset only in the quotient round, at most nine of them at once.
+//! `Plonk::prove_with_workspace` keeps the prover's evaluation buffers in a
+//! `ProverWorkspace`, so that consecutive proofs do not allocate them again.
+//! A yield hook set on the workspace is called between the steps of the
+//! prover, and `Plonk::verify_yielding` takes one too, so that a single
+//! thread, e.g. in WASM without the `parallel` feature, can serve its event
+//! loop during a proof.
+//! `Plonk::prove_checkpointed` hands out a `Checkpoint` after each round, from
+//! which a preempted proof is resumed.
+//! `ProverKey::load_disk_backed` reads a key saved by `ProverKey::save`
+//! but leaves the evaluations of its index on the 4n coset in the file, and
+//! the quotient round reads each back only for the term using it, so that
+//! the keys of circuits of millions of gates fit on commodity machines.
+//!
+//! `Plonk::keygen` is `Plonk::index` followed by `Plonk::commit_index`. The
+//! `CircuitIndex` in between does not depend on the setup, so it can be
+//! stored and committed to again when the setup changes. `Plonk::trim`
+//! trims a setup once to a `TrimmedSetup`, under which
+//! `Plonk::commit_index_trimmed` and `Plonk::keygen_trimmed` generate the
+//! keys of many circuits, their prover keys sharing one committer key.
+//!
+//! The prover adds a random multiple of the vanishing polynomial to each wire,
+//! to the permutation polynomial `z`, to the sorted lookup vectors `h_1` and
+//! `h_2` and to the grand products `z_lookup` and `z_memory`, so that their
+//! openings reveal nothing about the witness, lookups and memory included.
+//! This raises their degree by `DEFAULT_BLINDING`, and that of the quotient
+//! chunks with it, which `Composer::srs_degree` accounts for;
+//! `Plonk::index_with_blinding` sets another amount.
+//!
+//! The verifier interpolates the public inputs at `zeta`, in time linear in
+//! their number. An index `CircuitIndex::with_committed_inputs` has the
+//! prover open a commitment to them there instead, so that
+//! `Plonk::verify_committed` only takes the commitment, from
+//! `Plonk::commit_public_inputs`, and the evaluation carried by the proof.
+//!
+//! With the `std` feature, `Plonk::load_srs` reads a named universal setup
+//! from the cache directory of an `SrsLoader`, or fetches it into the cache,
+//! after checking its digest and the subgroup of its points.
+//!
+//! `Proof::from_bytes` and `VerifierKey::from_bytes` read untrusted bytes,
+//! rejecting trailing bytes and shapes the verifier does not expect. With the
+//! `arbitrary` feature, `ProofBytes` and `VerifierKeyBytes` generate framed
+//! but otherwise arbitrary encodings of them, for fuzzing the verifier.
+//! Under KZG, `to_encoding` and `from_encoding` convert them between the
+//! compressed and uncompressed encodings and a raw one with big-endian
+//! `x || y` points, as read by contracts.
+//! `Plonk::verify_from_slices` verifies a proof straight from the byte
+//! slices of a versioned encoding of it, of its key and of its public
+//! inputs, as a CKB type script receives them.
+//! The [`ckb`] module lays them out in the cells and witnesses of a
+//! transaction, and `Plonk::verify_from_cells` verifies them from there.
+//! `VerifierKey::digest` hashes the commitments and parameters of a key
+//! into a short identifier, which the transcript of every proof under the
+//! key absorbs, so that a contract can pin a circuit by its digest alone.
+//! `VkRegistry` holds the keys of many circuits by their digests, and
+//! `VkRegistry::verify_by_id` verifies encoded proofs under any of them.
+//! The transcript absorbs each of its messages under a label, from the
+//! context string of `CircuitIndex::with_context` and the size of the
+//! circuit to the commitments of every round, so that a proof is not
+//! replayed under another circuit or in another application.
+//!
+//! `Plonk::test_vector` records a proof with its verifier key, public inputs,
+//! transcript challenges and intermediate values, as JSON test vectors for
+//! verifiers in other languages; see `examples/test_vectors.rs`.
+//!
+//! With the `serde` feature, proofs and verifier keys serialize as the hex
+//! of their canonical encoding, and `HexProof` and `HexVerifierKey` convert
+//! them to and from JSON field by field, for front-ends to display and relay.
+//!
+//! `Plonk::self_test` proves a fixed circuit from a fixed seed and compares
+//! the verifier key and the proof byte for byte with a `KnownAnswer` of a
+//! reference build, committed per curve and scheme, then verifies the known
+//! proof, to check a build, its features and its curve arithmetic before
+//! going live. `examples/self_test_answers.rs` records the known answers.
+//!
+//! Without the default `std` feature the crate is `no_std` and only needs
+//! `alloc`: it reads keys and proofs, replays the transcript and checks the
+//! openings, so that a CKB script links the verifier as is. The prover, from
+//! `Plonk::setup` and `Plonk::keygen` to `Plonk::prove` and
+//! `Plonk::self_test`, is built with `std` only.
+//!
+//! With the `parallel` feature, the quotient, the grand products of the
+//! permutation, the lookup and the memory, and the combinations opened are
+//! computed in chunks over the threads of the current rayon pool.
+//! `Plonk::keygen_in` and `Plonk::prove_in` run on a given `ThreadPool`
+//! instead of the global one, `Plonk::prove_with_threads` caps the threads of
+//! a single proof, and `Plonk::prove_many` proves a batch of circuits under
+//! one key on a pool, with a bound on the number of proofs in flight.
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(future_incompatible, nonstandard_style, rust_2018_idioms)]
 #![allow(clippy::op_ref, clippy::suspicious_op_assign_impl)]
@@ -13,70 +182,339 @@ extern crate alloc;
 
 #[cfg(not(feature = "std"))]
 use alloc::collections::BTreeMap as Map;
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
 
 #[cfg(feature = "std")]
 use std::collections::HashMap as Map;
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
 use ark_ff::{to_bytes, FftField as Field};
-use ark_poly::univariate::DensePolynomial;
-use ark_poly_commit::{Evaluations, LabeledCommitment, PCUniversalParams, PolynomialCommitment};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+#[cfg(feature = "std")]
+use ark_poly_commit::PCUniversalParams;
+use ark_poly_commit::{Evaluations, PCVerifierKey};
+use ark_serialize::CanonicalSerialize;
 
 use ark_std::{marker::PhantomData, string::ToString, vec, vec::Vec};
 use digest::Digest;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+#[cfg(feature = "std")]
 use rand_core::RngCore;
 
 mod error;
-use error::Error;
+pub use crate::error::Error;
 
 mod data_structures;
 pub use crate::data_structures::*;
 
 mod composer;
-pub use crate::composer::Composer;
+pub use crate::composer::{
+    mock::{Failure, MockProver},
+    range::RangeType,
+    CircuitBuilder, CompiledCircuit, Composer, DeferredPairing, Error as ComposerError,
+    IpaScalars, Memory, Point, Table, Optimization, Stats, Variable, VariableMap, WitnessFile,
+    WitnessFiller, CIRCUIT_MAGIC, WITNESS_MAGIC,
+};
 
 mod ahp;
-use ahp::{AHPForPLONK, EvaluationsProvider};
+pub use crate::ahp::{Error as AHPError, DEFAULT_BLINDING};
+use ahp::AHPForPLONK;
+#[cfg(feature = "std")]
+use ahp::IndexInfo;
+
+pub mod piop;
+pub use crate::piop::{Compiler, PIOP};
+
+pub mod pcs;
+pub use crate::pcs::PCS;
+use crate::pcs::LabeledCommitment;
+
+pub mod shplonk;
+pub use crate::shplonk::Shplonk;
+
+pub mod ipa;
+pub use crate::ipa::Ipa;
+
+pub mod batch;
+pub use crate::batch::KzgClaim;
+
+mod progress;
+pub use crate::progress::{CancellationToken, Phase, ProverHooks};
+
+mod estimate;
+pub use crate::estimate::{Backend, Estimate, VerifierEstimate};
+
+mod workspace;
+pub use crate::workspace::ProverWorkspace;
+
+mod checkpoint;
+pub use crate::checkpoint::Checkpoint;
+
+mod encoding;
+pub use crate::encoding::{
+    public_inputs_from_bytes, public_inputs_to_bytes, reencode_proof, reencode_verifier_key,
+    Encoding, RawEncoding, Recoder, ENCODING_VERSION,
+};
+
+mod registry;
+pub use crate::registry::VkRegistry;
+
+pub mod ckb;
+pub use crate::ckb::{parse_proof_witness, proof_witness, vk_cell_data, WitnessField};
+
+mod vectors;
+pub use crate::vectors::{TestVector, CHALLENGES};
+
+#[cfg(feature = "std")]
+mod self_test;
+#[cfg(feature = "std")]
+pub use crate::self_test::{KnownAnswer, BLS12_381_KZG_BLAKE2S, PALLAS_IPA_BLAKE2S};
+
+#[cfg(feature = "std")]
+mod srs;
+#[cfg(feature = "std")]
+pub use crate::srs::{Fetch, SrsLoader};
+
+#[cfg(feature = "parallel")]
+pub use rayon::{ThreadPool, ThreadPoolBuilder};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 mod rng;
 use crate::rng::FiatShamirRng;
 
 mod utils;
+use crate::utils::{
+    evaluate_first_lagrange_poly, evaluate_vanishing_poly, pad_to_size, quotient_chunk,
+    quotient_chunks,
+};
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "serde")]
+pub use crate::serde_impl::{HexCommitment, HexProof, HexVerifierKey};
+
+#[cfg(feature = "arbitrary")]
+mod wire;
+#[cfg(feature = "arbitrary")]
+pub use crate::wire::{ProofBytes, VerifierKeyBytes};
 
-pub struct Plonk<F: Field, D: Digest, PC: PolynomialCommitment<F, DensePolynomial<F>>> {
+pub struct Plonk<F: Field, D: Digest, PC: PCS<F>> {
     _field: PhantomData<F>,
     _digest: PhantomData<D>,
     _pc: PhantomData<PC>,
 }
 
-impl<F: Field, D: Digest, PC: PolynomialCommitment<F, DensePolynomial<F>>> Plonk<F, D, PC> {
+impl<F: Field, D: Digest, PC: PCS<F>> Plonk<F, D, PC> {
     pub const PROTOCOL_NAME: &'static [u8] = b"PLONK";
 
     //多项式承诺的setup
+    #[cfg(feature = "std")]
     pub fn setup<R: RngCore>(
         max_degree: usize,
         rng: &mut R,
     ) -> Result<UniversalParams<F, PC>, Error<PC::Error>> {
-        PC::setup(max_degree, None, rng).map_err(Error::from_pc_err)
+        PC::setup(max_degree, rng).map_err(Error::from_pc_err)
+    }
+
+    /// The content hash of `cs`, recorded in the keys and proofs.
+    pub fn circuit_hash(cs: &Composer<F>, ks: &[F]) -> Result<Vec<u8>, Error<PC::Error>> {
+        cs.circuit_hash::<D>(ks)
+            .map_err(|e| Error::from(ahp::Error::from(e)))
+    }
+
+    /// The predicted peak memory and prove time of `cs`, from its domain
+    /// sizes, without building the index.
+    pub fn estimate(cs: &Composer<F>) -> Result<Estimate, Error<PC::Error>> {
+        let domain_size = |size| {
+            GeneralEvaluationDomain::<F>::new(size)
+                .map(|d| d.size())
+                .ok_or(ComposerError::PolynomialDegreeTooLarge { size })
+        };
+        let n = domain_size(cs.rows()).map_err(ahp::Error::from)?;
+        let chunks = quotient_chunks(cs.wires());
+        let n_4n = domain_size(chunks * quotient_chunk(n, DEFAULT_BLINDING, chunks))
+            .map_err(ahp::Error::from)?;
+        Ok(Estimate::plonk::<F>(cs.size(), cs.wires(), n, n_4n))
+    }
+
+    /// The size of the proofs under `vk`, exact if `backend` is its
+    /// commitment scheme, and the pairings, MSMs and field operations of
+    /// verifying them.
+    pub fn verifier_estimate(
+        vk: &VerifierKey<F, PC>,
+        backend: Backend,
+    ) -> Result<VerifierEstimate, Error<PC::Error>> {
+        // the shape of the verification does not depend on the challenges.
+        let rng = &mut ChaChaRng::from_seed([0u8; 32]);
+        let mut vs = AHPForPLONK::init_verifier(&vk.info)?;
+        let mut msgs = Vec::new();
+        for round in 0..AHPForPLONK::<F>::NUM_ROUNDS {
+            let (next, msg) = AHPForPLONK::verify_round(vs, round, rng)?;
+            vs = next;
+            msgs.push(msg);
+        }
+
+        let mut evaluations = Evaluations::new();
+        let mut points: Vec<(F, usize)> = Vec::new();
+        for (label, (_, point)) in &AHPForPLONK::query_set(&vs) {
+            evaluations.insert((label.clone(), *point), F::one());
+            match points.iter_mut().find(|(p, _)| *p == *point) {
+                Some((_, queries)) => *queries += 1,
+                None => points.push((*point, 1)),
+            }
+        }
+        let lcs = AHPForPLONK::linear_combinations(&vk.info, &msgs, &evaluations)?;
+        let lc_terms: Vec<_> = lcs
+            .iter()
+            .map(|lc| lc.iter().filter(|(_, term)| !term.is_one()).count())
+            .collect();
+        let queries: Vec<_> = points.into_iter().map(|(_, queries)| queries).collect();
+
+        Ok(VerifierEstimate::plonk::<F>(
+            backend,
+            vk.info.n,
+            vk.circuit_hash.len(),
+            // the wires, z with h_1 and h_2, z_lookup with z_memory and the
+            // quotient chunks.
+            &AHPForPLONK::<F>::oracle_labels(vk.info.wires())
+                .iter()
+                .map(Vec::len)
+                .collect::<Vec<_>>(),
+            &lc_terms,
+            &queries,
+            vk.comms[0].serialized_size(),
+            vk.rk.supported_degree(),
+        ))
     }
 
+    /// The rows of `cs` are padded with empty gates to the next domain size,
+    /// and a setup too small for them is rejected before they are indexed.
+    #[cfg(feature = "std")]
     #[allow(clippy::type_complexity)]
     pub fn keygen(
         srs: &UniversalParams<F, PC>,
         cs: &Composer<F>,
-        ks: [F; 4],
+        ks: &[F],
+    ) -> Result<(ProverKey<F, PC>, VerifierKey<F, PC>), Error<PC::Error>> {
+        Self::check_degree(cs, srs.max_degree())?;
+        Self::commit_index(srs, Self::index(cs, ks)?)
+    }
+
+    /// Fails with `Error::CircuitTooLarge` if `cs` needs a setup of a degree
+    /// above `available`, and with `Error::UnsupportedDomain` if the field
+    /// has no domain for it.
+    #[cfg(feature = "std")]
+    fn check_degree(cs: &Composer<F>, available: usize) -> Result<(), Error<PC::Error>> {
+        let needed = cs.srs_degree().map_err(ahp::Error::from)?;
+        if available < needed {
+            return Err(Error::CircuitTooLarge { needed, available });
+        }
+        Ok(())
+    }
+
+    /// The first half of `keygen`: the selectors of `cs`, interpolated and
+    /// evaluated on the coset of the quotient. This is the expensive part,
+    /// and it does not depend on the setup.
+    #[cfg(feature = "std")]
+    pub fn index(cs: &Composer<F>, ks: &[F]) -> Result<CircuitIndex<F>, Error<PC::Error>> {
+        Self::index_with_blinding(cs, ks, DEFAULT_BLINDING)
+    }
+
+    /// `index`, blinding the wires and `z` of the proofs with `blinding`
+    /// random coefficients each instead of `DEFAULT_BLINDING`. Zero turns the
+    /// blinding off, which is only sound for proofs that hide nothing, e.g.
+    /// to compare the cost of the blinding in benchmarks.
+    #[cfg(feature = "std")]
+    pub fn index_with_blinding(
+        cs: &Composer<F>,
+        ks: &[F],
+        blinding: usize,
+    ) -> Result<CircuitIndex<F>, Error<PC::Error>> {
+        Ok(CircuitIndex {
+            circuit_hash: Self::circuit_hash(cs, ks)?,
+            index: AHPForPLONK::index(cs, ks, blinding)?,
+        })
+    }
+
+    /// The second half of `keygen`: the commitments to the selectors of
+    /// `index` under `srs`.
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    pub fn commit_index(
+        srs: &UniversalParams<F, PC>,
+        index: CircuitIndex<F>,
+    ) -> Result<(ProverKey<F, PC>, VerifierKey<F, PC>), Error<PC::Error>> {
+        let setup = Self::trim(srs, index.index.info.max_degree())?;
+        Self::commit_index_trimmed(&setup, index)
+    }
+
+    /// `srs` trimmed to circuits of up to `max_degree`, see
+    /// `Composer::srs_degree`, once for all the keys committed under it.
+    #[cfg(feature = "std")]
+    pub fn trim(
+        srs: &UniversalParams<F, PC>,
+        max_degree: usize,
+    ) -> Result<TrimmedSetup<F, PC>, Error<PC::Error>> {
+        if srs.max_degree() < max_degree {
+            return Err(Error::CircuitTooLarge {
+                needed: max_degree,
+                available: srs.max_degree(),
+            });
+        }
+        let (ck, rk) = PC::trim(srs, max_degree).map_err(Error::from_pc_err)?;
+        Ok(TrimmedSetup {
+            ck: Arc::new(ck),
+            rk,
+        })
+    }
+
+    /// `keygen` under a setup trimmed once with `trim`, whose committer key
+    /// the prover key shares.
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    pub fn keygen_trimmed(
+        setup: &TrimmedSetup<F, PC>,
+        cs: &Composer<F>,
+        ks: &[F],
+    ) -> Result<(ProverKey<F, PC>, VerifierKey<F, PC>), Error<PC::Error>> {
+        Self::check_degree(cs, setup.max_degree())?;
+        Self::commit_index_trimmed(setup, Self::index(cs, ks)?)
+    }
+
+    /// `commit_index` under a setup trimmed once with `trim`, whose
+    /// committer key the prover key shares.
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    pub fn commit_index_trimmed(
+        setup: &TrimmedSetup<F, PC>,
+        index: CircuitIndex<F>,
     ) -> Result<(ProverKey<F, PC>, VerifierKey<F, PC>), Error<PC::Error>> {
-        let index = AHPForPLONK::index(cs, ks)?;
-        if srs.max_degree() < index.size() {
-            return Err(Error::CircuitTooLarge);
+        let CircuitIndex {
+            circuit_hash,
+            index,
+        } = index;
+        let degree = index.info.max_degree();
+        if setup.max_degree() < degree {
+            return Err(Error::CircuitTooLarge {
+                needed: degree,
+                available: setup.max_degree(),
+            });
         }
 
-        let (ck, vk) = PC::trim(srs, index.size(), 0, None).map_err(Error::from_pc_err)?;
+        let ck = setup.ck.clone();
         //index.iter就是arithmetic.iter连接上permutation.iter，实质是 LabeledPolynomial表示的q0 q1 ...(arithmetickey里) 和 sigma0123（PermutationKey里）
         //pub fn iter(&self) -> impl Iterator<Item = &LabeledPolynomial<F>> {
         //         self.arithmetic.iter().chain(self.permutation.iter())
         //     }
         //依次为[q0], [q1], [q2], [q3], [qm], [qc], [qarith], [sigma_0], [sigma_1], [sigma_2], [sigma_3], [qrange], [q_mimc]
-        let (comms, rands) = PC::commit(&ck, index.iter(), None).map_err(Error::from_pc_err)?;
+        let (comms, rands) = PC::commit(&ck, &index.labeled(), None).map_err(Error::from_pc_err)?;
         let labels = comms.iter().map(|c| c.label().clone()).collect();
         let comms = comms.iter().map(|c| c.commitment().clone()).collect();
 
@@ -85,9 +523,10 @@ impl<F: Field, D: Digest, PC: PolynomialCommitment<F, DensePolynomial<F>>> Plonk
         // }
 
         let vk = VerifierKey {
+            circuit_hash,
             comms,
             labels,
-            rk: vk,
+            rk: setup.rk.clone(),
             info: index.info.clone(),
         };
         let pk = ProverKey {
@@ -100,124 +539,301 @@ impl<F: Field, D: Digest, PC: PolynomialCommitment<F, DensePolynomial<F>>> Plonk
         Ok((pk, vk))
     }
 
+    /// `keygen` on the threads of `pool` instead of the global pool; a pool
+    /// of `n` threads is `ThreadPoolBuilder::new().num_threads(n).build()`.
+    #[cfg(feature = "parallel")]
+    #[allow(clippy::type_complexity)]
+    pub fn keygen_in(
+        pool: &ThreadPool,
+        srs: &UniversalParams<F, PC>,
+        cs: &Composer<F>,
+        ks: &[F],
+    ) -> Result<(ProverKey<F, PC>, VerifierKey<F, PC>), Error<PC::Error>>
+    where
+        UniversalParams<F, PC>: Sync,
+        ProverKey<F, PC>: Send,
+        VerifierKey<F, PC>: Send,
+        PC::Error: Send,
+    {
+        pool.install(|| Self::keygen(srs, cs, ks))
+    }
+
+    #[cfg(feature = "std")]
     pub fn prove(
         pk: &ProverKey<F, PC>,
         cs: &Composer<F>,
         zk_rng: &mut dyn RngCore,
     ) -> Result<Proof<F, PC>, Error<PC::Error>> {
-        let public_inputs = cs.public_inputs();
+        Self::prove_with(pk, cs, zk_rng, ProverHooks::default())
+    }
 
-        let mut fs_rng =
-            FiatShamirRng::<D>::from_seed(&to_bytes![&Self::PROTOCOL_NAME, public_inputs].unwrap());
-
-        let ps = AHPForPLONK::prover_init(cs, &pk.index)?;
-        let vs = AHPForPLONK::verifier_init(&pk.vk.info)?;
-
-        let (ps, first_oracles) = AHPForPLONK::prover_first_round(ps, &cs)?;
-        let (first_comms, first_rands) =
-            PC::commit(&pk.ck, first_oracles.iter(), Some(zk_rng)).map_err(Error::from_pc_err)?;
-        fs_rng.absorb(&to_bytes![first_comms].unwrap());
-        let (vs, first_msg) = AHPForPLONK::verifier_first_round(vs, &mut fs_rng)?;
-
-        let (ps, second_oracles) =
-            AHPForPLONK::prover_second_round(ps, &first_msg, &pk.vk.info.ks)?;
-        let (second_comms, second_rands) =
-            PC::commit(&pk.ck, second_oracles.iter(), Some(zk_rng)).map_err(Error::from_pc_err)?;
-        fs_rng.absorb(&to_bytes![second_comms].unwrap());
-        let (vs, second_msg) = AHPForPLONK::verifier_second_round(vs, &mut fs_rng)?;
-
-        let third_oracles = AHPForPLONK::prover_third_round(ps, &second_msg, &pk.vk.info.ks)?;
-        let (third_comms, third_rands) =
-            PC::commit(&pk.ck, third_oracles.iter(), Some(zk_rng)).map_err(Error::from_pc_err)?;
-        fs_rng.absorb(&to_bytes![third_comms].unwrap());
-        let (vs, third_msg) = AHPForPLONK::verifier_third_round(vs, &mut fs_rng)?;
-
-        let polynomials: Vec<_> = pk
-            //q。。一堆
-            .index.iter()
-            //w0123
-            .chain(first_oracles.iter())
-            //z
-            .chain(second_oracles.iter())
-            //t0123
-            .chain(third_oracles.iter())
-            .collect();
+    /// `prove`, reporting each phase to `hooks.progress` and stopping with
+    /// `Error::Cancelled` before the next phase once `hooks.cancel` is
+    /// cancelled.
+    #[cfg(feature = "std")]
+    pub fn prove_with(
+        pk: &ProverKey<F, PC>,
+        cs: &Composer<F>,
+        zk_rng: &mut dyn RngCore,
+        hooks: ProverHooks<'_>,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>> {
+        Self::prove_with_workspace(pk, cs, zk_rng, hooks, &mut ProverWorkspace::new())
+    }
 
-        //取labeledcommi的commi（二维向量）
-        let commitments = vec![
-            first_comms.iter().map(|c| c.commitment().clone()).collect(),
-            second_comms.iter().map(|c| c.commitment().clone()).collect(),
-            third_comms.iter().map(|c| c.commitment().clone()).collect(),
-        ];
+    /// `prove_with`, taking the prover's buffers from `workspace` and
+    /// handing them back to it, to be reused by the next proof.
+    #[cfg(feature = "std")]
+    pub fn prove_with_workspace(
+        pk: &ProverKey<F, PC>,
+        cs: &Composer<F>,
+        zk_rng: &mut dyn RngCore,
+        hooks: ProverHooks<'_>,
+        workspace: &mut ProverWorkspace<F>,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>> {
+        Self::prove_from(pk, cs, zk_rng, hooks, workspace, None, None)
+    }
+
+    /// `prove_with`, starting after the rounds of `resume` if given, and
+    /// handing the state after each round to `checkpoint`, e.g. to write it
+    /// with `Checkpoint::save`. A prover preempted mid-proof is resumed from
+    /// the last checkpoint saved.
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    pub fn prove_checkpointed(
+        pk: &ProverKey<F, PC>,
+        cs: &Composer<F>,
+        zk_rng: &mut dyn RngCore,
+        hooks: ProverHooks<'_>,
+        resume: Option<Checkpoint<F, PC>>,
+        checkpoint: &mut dyn FnMut(&Checkpoint<F, PC>) -> Result<(), Error<PC::Error>>,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>> {
+        let workspace = &mut ProverWorkspace::new();
+        Self::prove_from(pk, cs, zk_rng, hooks, workspace, resume, Some(checkpoint))
+    }
+
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    fn prove_from(
+        pk: &ProverKey<F, PC>,
+        cs: &Composer<F>,
+        zk_rng: &mut dyn RngCore,
+        mut hooks: ProverHooks<'_>,
+        workspace: &mut ProverWorkspace<F>,
+        resume: Option<Checkpoint<F, PC>>,
+        checkpoint: Option<&mut dyn FnMut(&Checkpoint<F, PC>) -> Result<(), Error<PC::Error>>>,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>> {
+        if Self::circuit_hash(cs, &pk.vk.info.ks)? != pk.vk.circuit_hash {
+            return Err(Error::CircuitMismatch);
+        }
+        let public_inputs = cs.public_inputs();
+
+        // committed public inputs are opened with the index, and the
+        // transcript absorbs their commitment instead of their values.
+        let (pi, inputs) = if pk.vk.info.committed_inputs {
+            let (poly, comm, rand) = Self::commit_inputs(&pk.ck, &pk.vk.info, public_inputs)?;
+            let inputs = to_bytes![comm.commitment()].unwrap();
+            (Some((poly, comm, rand)), inputs)
+        } else {
+            (None, to_bytes![public_inputs].unwrap())
+        };
+        let mut fs_rng = Self::transcript(&pk.vk, &inputs);
 
-        //vk里的commitment（q0123..），然后是三个round的labeledcomms
-        let labeled_commitments: Vec<_> = pk
+        //vk里的commitment（q0123..），三个round的labeledcomms由Compiler接在后面
+        let labeled_comms: Vec<_> = pk
             .vk
-            .comms.iter().cloned()
+            .comms
+            .iter()
+            .cloned()
             .zip(pk.vk.labels.iter())
             .map(|(c, l)| LabeledCommitment::new(l.to_string(), c, None))
-            .chain(first_comms.iter().cloned())
-            .chain(second_comms.iter().cloned())
-            .chain(third_comms.iter().cloned())
+            .chain(pi.as_ref().map(|(_, comm, _)| comm.clone()))
             .collect();
-
-        let randomnesses: Vec<_> = pk
-            .rands.iter()
-            .chain(first_rands.iter())
-            .chain(second_rands.iter())
-            .chain(third_rands.iter())
+        let rands: Vec<_> = pk
+            .rands
+            .iter()
+            .cloned()
+            .chain(pi.as_ref().map(|(_, _, rand)| rand.clone()))
             .collect();
 
-        //合并一些多项式为r后，需要commit和open的多项式们
-        //只是标记了’哪些多项式‘会在哪个点open
-        let qs = AHPForPLONK::verifier_query_set(&vs);
-        //优化2：把多项式们 线性组合(r在里面现场构造)，lcs已排序
-        let lcs = AHPForPLONK::construct_linear_combinations(
+        let index_polys = pk.index.labeled();
+        let mut proof = Compiler::<F, D, PC>::prove_from::<AHPForPLONK<F>>(
+            &pk.ck,
+            &pk.index,
             &pk.vk.info,
-            &first_msg,
-            &second_msg,
-            &third_msg,
-            &polynomials,
+            index_polys.iter().chain(pi.as_ref().map(|(poly, _, _)| poly)).collect(),
+            &labeled_comms,
+            &rands,
+            cs,
+            &mut fs_rng,
+            zk_rng,
+            &mut hooks,
+            workspace,
+            resume,
+            checkpoint,
         )?;
+        proof.circuit_hash = pk.vk.circuit_hash.clone();
+        Ok(proof)
+    }
 
-        //qs中的多项式进行open，并排序
-        let evaluations: Vec<_> = {
-            let mut evals = Vec::new();
-            for (label, (_, point)) in &qs {
-                //对于qs中的每一个标签，从lcs中找到对应的多项式并open
-                let lc = lcs.iter()
-                    .find(|lc| &lc.label == label)
-                    .ok_or_else(|| Error::MissingEvaluation(label.to_string()))?;
-                //open的值
-                let eval = polynomials.get_lc_eval(&lc, *point)?;
-                evals.push((label.to_string(), eval));
-            }
-            evals.sort_by(|a, b| a.0.cmp(&b.0));
-            evals.into_iter().map(|x| x.1).collect()
+    /// The Fiat-Shamir transcript of a proof under `vk`: the protocol name,
+    /// then the context of `vk`, its circuit hash, digest and size and the
+    /// encoded public `inputs`, or their commitment, each under a label.
+    fn transcript(vk: &VerifierKey<F, PC>, inputs: &[u8]) -> FiatShamirRng<D> {
+        let mut fs_rng = FiatShamirRng::from_seed(&Self::PROTOCOL_NAME);
+        fs_rng.absorb_labeled(b"context", &vk.info.context);
+        fs_rng.absorb_labeled(b"circuit_hash", &vk.circuit_hash);
+        fs_rng.absorb_labeled(b"vk_digest", &vk.digest::<D>());
+        fs_rng.absorb_labeled(b"size", &(vk.info.n as u64).to_le_bytes());
+        let label = if vk.info.committed_inputs {
+            &b"public_input_commitment"[..]
+        } else {
+            &b"public_inputs"[..]
         };
-        //evaluation生成epsilon
-        fs_rng.absorb(&evaluations);
-        let epsilon = F::rand(&mut fs_rng);
+        fs_rng.absorb_labeled(label, inputs);
+        fs_rng
+    }
 
-        //优化2：一堆多项式的线性组合只需要一个proof就可以验证commitment是否与open相符
-        //epsilon是pdf里W的v吗（可以只用一个随机数
-        let pc_proof = PC::open_combinations(
-            &pk.ck,
-            &lcs,
-            polynomials,
-            &labeled_commitments,
-            &qs,
-            epsilon,
-            randomnesses,
-            Some(zk_rng),
-        )
-        .map_err(Error::from_pc_err)?;
-        let proof = Proof {
-            commitments,
-            evaluations, //无label的
-            pc_proof,
-        };
-        Ok(proof)
+    /// The commitment to `public_inputs` that proofs under `pk` are verified
+    /// against with `verify_committed`, if its index takes committed inputs.
+    /// It is computed without hiding, so anyone holding the commitment key
+    /// gets the same commitment from the same inputs.
+    #[cfg(feature = "std")]
+    pub fn commit_public_inputs(
+        pk: &ProverKey<F, PC>,
+        public_inputs: &[F],
+    ) -> Result<PC::Commitment, Error<PC::Error>> {
+        let (_, comm, _) = Self::commit_inputs(&pk.ck, &pk.vk.info, public_inputs)?;
+        Ok(comm.commitment().clone())
+    }
+
+    /// The public inputs interpolated over the domain of `info`, labelled
+    /// `pi`, with their commitment and its randomness.
+    #[cfg(feature = "std")]
+    #[allow(clippy::type_complexity)]
+    fn commit_inputs(
+        ck: &PC::CommitterKey,
+        info: &IndexInfo<F>,
+        public_inputs: &[F],
+    ) -> Result<
+        (
+            LabeledPolynomial<F>,
+            LabeledCommitment<PC::Commitment>,
+            PC::Randomness,
+        ),
+        Error<PC::Error>,
+    > {
+        let domain_n = info.domain_n;
+        if !info.committed_inputs {
+            return Err(Error::InputModeMismatch);
+        }
+        if public_inputs.len() > domain_n.size() {
+            return Err(Error::CircuitTooLarge {
+                needed: public_inputs.len(),
+                available: domain_n.size(),
+            });
+        }
+        let pi_n = pad_to_size(public_inputs, domain_n.size());
+        let poly = EvaluationsPoly::new("pi".to_string(), pi_n, domain_n).into_labeled();
+        let (mut comms, mut rands) =
+            PC::commit(ck, Some(&poly), None).map_err(Error::from_pc_err)?;
+        Ok((poly, comms.remove(0), rands.remove(0)))
+    }
+
+    /// `prove_with` on the threads of `pool` instead of the global pool, so
+    /// that concurrent proofs can be given disjoint sets of cores.
+    #[cfg(feature = "parallel")]
+    pub fn prove_in(
+        pool: &ThreadPool,
+        pk: &ProverKey<F, PC>,
+        cs: &Composer<F>,
+        zk_rng: &mut (dyn RngCore + Send),
+        hooks: ProverHooks<'_>,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>>
+    where
+        ProverKey<F, PC>: Sync,
+        Proof<F, PC>: Send,
+        PC::Error: Send,
+    {
+        pool.install(|| Self::prove_with(pk, cs, zk_rng, hooks))
+    }
+
+    /// `prove_with` on a pool of `threads` threads built for this proof, so
+    /// that the quotient, the grand products and the openings use at most
+    /// that many cores; zero is rayon's default of one thread per core.
+    #[cfg(feature = "parallel")]
+    pub fn prove_with_threads(
+        threads: usize,
+        pk: &ProverKey<F, PC>,
+        cs: &Composer<F>,
+        zk_rng: &mut (dyn RngCore + Send),
+        hooks: ProverHooks<'_>,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>>
+    where
+        ProverKey<F, PC>: Sync,
+        Proof<F, PC>: Send,
+        PC::Error: Send,
+    {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(Error::ThreadPoolError)?;
+        Self::prove_in(&pool, pk, cs, zk_rng, hooks)
+    }
+
+    /// Proves each of `circuits` under `pk` on the threads of `pool`, with at
+    /// most `concurrency` proofs in flight, so that at most `concurrency`
+    /// sets of prover buffers are alive at once. Each worker reuses its own
+    /// `ProverWorkspace` from one proof to the next, and every proof has its
+    /// own zero-knowledge rng, seeded from `zk_rng`.
+    ///
+    /// The results are in the order of `circuits`.
+    #[cfg(feature = "parallel")]
+    pub fn prove_many(
+        pool: &ThreadPool,
+        pk: &ProverKey<F, PC>,
+        circuits: &[Composer<F>],
+        concurrency: usize,
+        zk_rng: &mut dyn RngCore,
+    ) -> Vec<Result<Proof<F, PC>, Error<PC::Error>>>
+    where
+        ProverKey<F, PC>: Sync,
+        Proof<F, PC>: Send,
+        PC::Error: Send,
+    {
+        let seeds: Vec<[u8; 32]> = circuits
+            .iter()
+            .map(|_| {
+                let mut seed = [0u8; 32];
+                zk_rng.fill_bytes(&mut seed);
+                seed
+            })
+            .collect();
+        let next = AtomicUsize::new(0);
+        let workers = concurrency.max(1).min(circuits.len());
+
+        let proved: Vec<Vec<_>> = pool.install(|| {
+            (0..workers)
+                .into_par_iter()
+                .map(|_| {
+                    let mut workspace = ProverWorkspace::new();
+                    let mut proved = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= circuits.len() {
+                            return proved;
+                        }
+                        let (cs, ws) = (&circuits[i], &mut workspace);
+                        let rng = &mut ChaChaRng::from_seed(seeds[i]);
+                        let hooks = ProverHooks::default();
+                        proved.push((i, Self::prove_with_workspace(pk, cs, rng, hooks, ws)));
+                    }
+                })
+                .collect()
+        });
+
+        let mut proved: Vec<_> = proved.into_iter().flatten().collect();
+        proved.sort_by_key(|(i, _)| *i);
+        proved.into_iter().map(|(_, proof)| proof).collect()
     }
 
     pub fn verify(
@@ -225,100 +841,177 @@ impl<F: Field, D: Digest, PC: PolynomialCommitment<F, DensePolynomial<F>>> Plonk
         public_inputs: &[F],
         proof: Proof<F, PC>,
     ) -> Result<bool, Error<PC::Error>> {
-        //alpha beta gamma 这些要通过协议交互过程自己计算出来
-        let vs = AHPForPLONK::verifier_init(&vk.info)?;
-        let mut fs_rng =
-            FiatShamirRng::<D>::from_seed(&to_bytes![&Self::PROTOCOL_NAME, public_inputs].unwrap());
-
-        let first_comms = &proof.commitments[0];
-        fs_rng.absorb(&to_bytes![first_comms].unwrap());
-        let (vs, first_msg) = AHPForPLONK::verifier_first_round(vs, &mut fs_rng)?;
-
-        let second_comms = &proof.commitments[1];
-        fs_rng.absorb(&to_bytes![second_comms].unwrap());
-        let (vs, second_msg) = AHPForPLONK::verifier_second_round(vs, &mut fs_rng)?;
-
-        let third_comms = &proof.commitments[2];
-        fs_rng.absorb(&to_bytes![third_comms].unwrap());
-        let (vs, third_msg) = AHPForPLONK::verifier_third_round(vs, &mut fs_rng)?;
-
-        //只是标记了’哪些多项式‘会在哪个点open
-        let query_set = AHPForPLONK::verifier_query_set(&vs);
-        fs_rng.absorb(&proof.evaluations);
-        let epsilon = F::rand(&mut fs_rng);
-
-        //接口？
-        //qs中的多项式的label排序，然后和proof里对应的值 组合在一起
-        let evaluations = {
-            let mut evaluation_labels: Vec<_> = query_set
-                .iter().cloned()
-                .map(|(l, (_, p))| (l, p))
-                .collect();
-            evaluation_labels.sort_by(|a, b| a.0.cmp(&b.0));
+        Self::verify_yielding(vk, public_inputs, proof, &mut || {})
+    }
+
+    /// `verify`, calling `yield_now` between its steps, like the yield hook
+    /// of a `ProverWorkspace`.
+    pub fn verify_yielding(
+        vk: &VerifierKey<F, PC>,
+        public_inputs: &[F],
+        proof: Proof<F, PC>,
+        yield_now: &mut dyn FnMut(),
+    ) -> Result<bool, Error<PC::Error>> {
+        if vk.info.committed_inputs {
+            return Err(Error::InputModeMismatch);
+        }
+        let inputs = to_bytes![public_inputs].unwrap();
+        Self::verify_from(vk, &inputs, public_inputs, None, proof, yield_now)
+    }
 
-            let mut evaluations = Evaluations::new();
-            for (q, eval) in evaluation_labels.into_iter().zip(&proof.evaluations) {
-                evaluations.insert(q, *eval);
+    /// `verify` from the versioned encodings of the key, the proof and the
+    /// public inputs, read in place from the slices, see `ENCODING_VERSION`.
+    /// Bytes that do not decode, in another version or of another kind of
+    /// value, fail verification like an invalid proof.
+    pub fn verify_from_slices(vk_bytes: &[u8], proof_bytes: &[u8], pi_bytes: &[u8]) -> bool {
+        let decoded = VerifierKey::<F, PC>::from_versioned_bytes(vk_bytes).and_then(|vk| {
+            let proof = Proof::<F, PC>::from_versioned_bytes(proof_bytes)?;
+            Ok((vk, proof, public_inputs_from_bytes(pi_bytes)?))
+        });
+        match decoded {
+            Ok((vk, proof, public_inputs)) => {
+                Self::verify(&vk, &public_inputs, proof).unwrap_or(false)
             }
-            evaluations
-        };
+            Err(_) => false,
+        }
+    }
 
-        //验证’最终大等式‘是否相等
-        // if !AHPForPLONK::verifier_equality_check(&vs, &evaluations, public_inputs)? {
-        //     return Ok(false);
-        // };
-        let tmp = AHPForPLONK::verifier_equality_check(&vs, &evaluations, public_inputs)?;
-        assert_eq!(tmp, true);
-
-        let pc_check = {
-            let labels: Vec<_> = vk
-                .labels.iter().cloned()
-                .chain(AHPForPLONK::<F>::LABELS.iter().map(|l| l.to_string()))
-                .collect();
+    /// `verify_from_slices` of the data of the dep cell holding the key and
+    /// of the `WitnessArgs` holding the proof in `field`, see [`ckb`].
+    pub fn verify_from_cells(vk_data: &[u8], witness: &[u8], field: WitnessField) -> bool {
+        match parse_proof_witness(witness, field) {
+            Ok((proof_bytes, pi_bytes)) => {
+                Self::verify_from_slices(vk_data, proof_bytes, pi_bytes)
+            }
+            Err(_) => false,
+        }
+    }
 
-            //和labels一一对应，组合成LabeledCommitment
-            let labeled_commitments: Vec<_> = vk
-                .comms.iter().cloned()
-                //w0 w1 w2 w3
-                .chain(first_comms.iter().cloned())
-                //z
-                .chain(second_comms.iter().cloned())
-                //t0 t1 t2 t3
-                .chain(third_comms.iter().cloned())
-                .zip(labels.iter())
-                .map(|(c, l)| LabeledCommitment::new(l.to_string(), c, None))
-                .collect();
+    /// `verify` under a key whose index takes committed public inputs, see
+    /// `CircuitIndex::with_committed_inputs`, given only `pi_commitment`, as
+    /// computed by `commit_public_inputs`. The proof carries the evaluation
+    /// of the inputs at `zeta`, which is opened against `pi_commitment` with
+    /// the other polynomials.
+    pub fn verify_committed(
+        vk: &VerifierKey<F, PC>,
+        pi_commitment: &PC::Commitment,
+        proof: Proof<F, PC>,
+    ) -> Result<bool, Error<PC::Error>> {
+        if !vk.info.committed_inputs {
+            return Err(Error::InputModeMismatch);
+        }
+        let inputs = to_bytes![pi_commitment].unwrap();
+        let pi = LabeledCommitment::new("pi".to_string(), pi_commitment.clone(), None);
+        Self::verify_from(vk, &inputs, &[], Some(pi), proof, &mut || {})
+    }
 
-            //evals既可以像这里 直接接收evaluations，也可以像prover那里接收Vec<Borrow<LabeledPolynomial<F>>>。（因为evaluations.rs里给它实现了
-            let lcs = AHPForPLONK::construct_linear_combinations(
-                &vk.info,
-                &first_msg,
-                &second_msg,
-                &third_msg,
-                &evaluations,
-            )?;
-
-            PC::check_combinations(
-                &vk.rk,
-                &lcs,
-                &labeled_commitments,
-                &query_set,
-                &evaluations,
-                &proof.pc_proof,
-                epsilon,
-                &mut ark_std::test_rng(), // we now impl default rng (not use)
-            )
-            .map_err(Error::from_pc_err)?
-        };
-        Ok(pc_check)
+    /// Verifies `proof` from the transcript seeded with `inputs`, with the
+    /// commitment to the public inputs if they are committed to, and their
+    /// values if not.
+    fn verify_from(
+        vk: &VerifierKey<F, PC>,
+        inputs: &[u8],
+        public_inputs: &[F],
+        pi: Option<LabeledCommitment<PC::Commitment>>,
+        proof: Proof<F, PC>,
+        yield_now: &mut dyn FnMut(),
+    ) -> Result<bool, Error<PC::Error>> {
+        if proof.circuit_hash != vk.circuit_hash {
+            return Err(Error::CircuitMismatch);
+        }
+        let mut fs_rng = Self::transcript(vk, inputs);
+
+        Compiler::<F, D, PC>::verify_with::<AHPForPLONK<F>>(
+            &vk.rk,
+            &vk.info,
+            Self::key_commitments(vk, pi),
+            public_inputs,
+            &proof,
+            &mut fs_rng,
+            yield_now,
+        )
+    }
+
+    /// The labelled commitments of `vk`, with `pi` if the public inputs are
+    /// committed to.
+    fn key_commitments(
+        vk: &VerifierKey<F, PC>,
+        pi: Option<LabeledCommitment<PC::Commitment>>,
+    ) -> Vec<LabeledCommitment<PC::Commitment>> {
+        vk.comms
+            .iter()
+            .cloned()
+            .zip(vk.labels.iter())
+            .map(|(c, l)| LabeledCommitment::new(l.to_string(), c, None))
+            .chain(pi)
+            .collect()
+    }
+
+    /// A test vector named `name` of `proof`: the encodings of `vk` and of
+    /// the proof, the challenges and values a verifier derives from them, and
+    /// whether the proof verifies.
+    pub fn test_vector(
+        name: &str,
+        vk: &VerifierKey<F, PC>,
+        public_inputs: &[F],
+        proof: Proof<F, PC>,
+    ) -> Result<TestVector<F>, Error<PC::Error>> {
+        let proof = proof.checked()?;
+        let mut vk_bytes = Vec::new();
+        vk.serialize(&mut vk_bytes)?;
+        let mut proof_bytes = Vec::new();
+        proof.serialize(&mut proof_bytes)?;
+
+        let mut fs_rng = Self::transcript(vk, &to_bytes![public_inputs].unwrap());
+        let (_, msgs, query_set, epsilon) =
+            Compiler::<F, D, PC>::replay::<AHPForPLONK<F>>(&vk.info, &proof, &mut fs_rng)?;
+        let zeta = msgs[3][0];
+        let challenges = CHALLENGES
+            .iter()
+            .copied()
+            .zip(msgs.into_iter().flatten().chain(Some(epsilon)))
+            .collect();
+        // the evaluations are in the order of the query set.
+        let evaluations = query_set
+            .into_iter()
+            .zip(&proof.evaluations)
+            .map(|((label, (point, _)), value)| (label, point, *value))
+            .collect();
+
+        let domain_n = vk.info.domain_n;
+        let pi = pad_to_size(public_inputs, domain_n.size());
+        let intermediates = vec![
+            ("vanishing", evaluate_vanishing_poly(domain_n, zeta)),
+            (
+                "first_lagrange",
+                evaluate_first_lagrange_poly(domain_n, zeta),
+            ),
+            (
+                "public_input",
+                EvaluationsPoly::new("pi".to_string(), pi, domain_n).evaluate(zeta),
+            ),
+        ];
+
+        Ok(TestVector {
+            name: name.to_string(),
+            vk: vk_bytes,
+            proof: proof_bytes,
+            public_inputs: public_inputs.to_vec(),
+            challenges,
+            evaluations,
+            intermediates,
+            verifies: Self::verify(vk, public_inputs, proof)?,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use ark_bls12_381::{Bls12_381, Fr};
-    use ark_ff::{One, Zero, Field};
-    use ark_poly_commit::{marlin_pc::MarlinKZG10, Error as PCError};
+    use ark_ec::AffineCurve;
+    use ark_ff::{FftField, One, Zero, Field};
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::{ipa_pc::InnerProductArgPC, marlin_pc::MarlinKZG10, Error as PCError};
     use ark_std::test_rng;
 
     use blake2::Blake2s;
@@ -331,20 +1024,18 @@ mod tests {
     type PC = MarlinKZG10<Bls12_381, DensePolynomial<Fr>>;
     type PlonkInst = Plonk<Fr, Blake2s, PC>;
 
-    pub fn ks() -> [Fr; 4] {
+    pub fn ks<F: FftField>() -> [F; 4] {
         [
-            Fr::one(),
-            Fr::from(7_u64),
-            Fr::from(13_u64),
-            Fr::from(17_u64),
+            F::one(),
+            F::from(7_u64),
+            F::from(13_u64),
+            F::from(17_u64),
         ]
     }
 
-    pub fn circuit() -> Composer<Fr> {
+    pub fn circuit<F: FftField>() -> Composer<F> {
         let mut cs = Composer::new();
-        let mimc_c = vec![Fr::zero(), Fr::one(), Fr::one(), Fr::one(), Fr::zero()];
-        //cs.init_mimc(mimc_c);
-        let one = Fr::one();
+        let one = F::one();
         let two = one + one;
         let three = two + one;
         let four = two + two;
@@ -359,28 +1050,28 @@ mod tests {
             (var_two, one),
             var_three,
             None,
-            Fr::zero(),
-            Fr::zero(),
+            F::zero(),
+            F::zero(),
         );
         cs.create_add_gate(
             (var_one, one),
             (var_three, one),
             var_four,
             None,
-            Fr::zero(),
-            Fr::zero(),
+            F::zero(),
+            F::zero(),
         );
         cs.create_mul_gate(
             var_two,
             var_two,
             var_four,
             None,
-            Fr::one(),
-            Fr::zero(),
-            Fr::zero(),
+            F::one(),
+            F::zero(),
+            F::zero(),
         );
-        cs.create_mul_gate(var_one, var_two, var_six, None, two, two, Fr::zero());
-        cs.constrain_to_constant(var_six, six, Fr::zero());
+        cs.create_mul_gate(var_one, var_two, var_six, None, two, two, F::zero());
+        cs.constrain_to_constant(var_six, six, F::zero());
 
         cs
     }
@@ -442,7 +1133,7 @@ mod tests {
         cs.create_range_gate(
             var_6,
             RangeType::U16,
-        );
+        ).unwrap();
 
         let FV8 = four.double();
         //println!("{}", FV128);
@@ -472,12 +1163,1336 @@ mod tests {
         let ks = ks();
         println!("size of the circuit: {}", cs.size());
 
-        let srs = PlonkInst::setup(16, rng)?;
-        let (pk, vk) = PlonkInst::keygen(&srs, &cs, ks)?;
+        let srs = PlonkInst::setup(cs.srs_degree().map_err(ahp::Error::from)?, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks)?;
         let proof = PlonkInst::prove(&pk, &cs, rng)?;
         let result = PlonkInst::verify(&vk, cs.public_inputs(), proof)?;
         assert_eq!(result, true);
         //assert!(result);
         Ok(())
     }
+
+    type Ipa<G> = Plonk<
+        <G as AffineCurve>::ScalarField,
+        Blake2s,
+        InnerProductArgPC<G, Blake2s, DensePolynomial<<G as AffineCurve>::ScalarField>>,
+    >;
+
+    fn test_plonk_ipa<G: AffineCurve>() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit::<G::ScalarField>();
+        let ks = ks();
+
+        let srs = Ipa::<G>::setup(16, rng)?;
+        let (pk, vk) = Ipa::<G>::keygen(&srs, &cs, &ks)?;
+        let proof = Ipa::<G>::prove(&pk, &cs, rng)?;
+        let estimate = Ipa::<G>::verifier_estimate(&vk, Backend::Ipa)?;
+        assert_eq!(estimate.proof_size, proof.serialized_size());
+        assert_eq!(estimate.pairings, 0);
+        assert!(Ipa::<G>::verify(&vk, cs.public_inputs(), proof)?);
+
+        let mut public_inputs = cs.public_inputs().to_vec();
+        public_inputs[0] += G::ScalarField::one();
+        let proof = Ipa::<G>::prove(&pk, &cs, rng)?;
+        assert!(!Ipa::<G>::verify(&vk, &public_inputs, proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_ipa_pallas() -> Result<(), Error<PCError>> {
+        test_plonk_ipa::<ark_pallas::Affine>()
+    }
+
+    #[test]
+    fn test_plonk_ipa_bls12_381() -> Result<(), Error<PCError>> {
+        test_plonk_ipa::<ark_bls12_381::G1Affine>()
+    }
+
+    #[test]
+    fn test_plonk_shplonk() -> Result<(), Error<crate::shplonk::Error>> {
+        type Shplonk = Plonk<Fr, Blake2s, crate::shplonk::Shplonk<Bls12_381, Blake2s>>;
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = Shplonk::setup(16, rng)?;
+        let (pk, vk) = Shplonk::keygen(&srs, &cs, &ks())?;
+        let proof = Shplonk::prove(&pk, &cs, rng)?;
+        assert!(Shplonk::verify(&vk, cs.public_inputs(), proof)?);
+
+        let mut public_inputs = cs.public_inputs().to_vec();
+        public_inputs[0] += Fr::one();
+        let proof = Shplonk::prove(&pk, &cs, rng)?;
+        assert!(!Shplonk::verify(&vk, &public_inputs, proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_own_ipa() -> Result<(), Error<crate::ipa::Error>> {
+        type OwnIpa = Plonk<Fr, Blake2s, crate::ipa::Ipa<ark_bls12_381::G1Affine, Blake2s>>;
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = OwnIpa::setup(16, rng)?;
+        let (pk, vk) = OwnIpa::keygen(&srs, &cs, &ks())?;
+        let proof = OwnIpa::prove(&pk, &cs, rng)?;
+        assert!(OwnIpa::verify(&vk, cs.public_inputs(), proof)?);
+
+        let mut public_inputs = cs.public_inputs().to_vec();
+        public_inputs[0] += Fr::one();
+        let proof = OwnIpa::prove(&pk, &cs, rng)?;
+        assert!(!OwnIpa::verify(&vk, &public_inputs, proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_circuit_mismatch() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+
+        let mut other = circuit();
+        let var = other.alloc_and_assign(Fr::one());
+        other.constrain_to_constant(var, Fr::one(), Fr::zero());
+        assert!(matches!(
+            PlonkInst::prove(&pk, &other, rng),
+            Err(Error::CircuitMismatch)
+        ));
+
+        let (other_pk, _) = PlonkInst::keygen(&srs, &other, &ks())?;
+        let proof = PlonkInst::prove(&other_pk, &other, rng)?;
+        assert!(matches!(
+            PlonkInst::verify(&vk, other.public_inputs(), proof),
+            Err(Error::CircuitMismatch)
+        ));
+
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_three_wires() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+        let cosets = [Fr::from(7_u64), Fr::from(13_u64), Fr::from(17_u64)];
+
+        // 3 * (1 + 2) + 4 * 5 == 29, public.
+        let mut cs = Composer::new().with_wires(3).unwrap();
+        let one = cs.alloc_and_assign(Fr::one());
+        let two = cs.alloc_and_assign(Fr::from(2_u64));
+        let five = cs.alloc_and_assign(Fr::from(5_u64));
+        let sum = cs.create_linear_combination_gate(
+            &[
+                (one, Fr::from(3_u64)),
+                (two, Fr::from(3_u64)),
+                (five, Fr::from(4_u64)),
+            ],
+            Fr::zero(),
+        );
+        cs.constrain_to_constant(sum, Fr::zero(), Fr::from(29_u64));
+        assert_eq!(cs.check_satisfied(), Ok(()));
+
+        let srs = PlonkInst::setup(16, rng)?;
+        assert_eq!(srs.max_degree(), 16);
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &cosets)?;
+        assert_eq!(vk.info.wires(), 3);
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert_eq!(proof.commitments[0].len(), 3);
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        let mut wrong = cs.public_inputs().to_vec();
+        wrong[0] += Fr::one();
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(!PlonkInst::verify(&vk, &wrong, proof)?);
+
+        // `w_0` is absent with three wires.
+        let mut wide = Composer::new().with_wires(3).unwrap();
+        let var = wide.alloc_and_assign(Fr::one());
+        wide.create_add_gate(
+            (var, Fr::one()),
+            (var, Fr::one()),
+            var,
+            Some((var, -Fr::one())),
+            Fr::zero(),
+            Fr::zero(),
+        );
+        assert!(matches!(
+            PlonkInst::keygen(&srs, &wide, &cosets),
+            Err(Error::PolynomialProtocolError(AHPError::SynthesisError(
+                ComposerError::AbsentWire { row: 0, column: 0 }
+            )))
+        ));
+        assert!(matches!(
+            PlonkInst::keygen(&srs, &cs, &ks()),
+            Err(Error::PolynomialProtocolError(AHPError::SynthesisError(
+                ComposerError::CosetCount { wires: 3, ks: 4 }
+            )))
+        ));
+
+        assert!(matches!(
+            Composer::<Fr>::new().with_wires(6),
+            Err(ComposerError::UnsupportedWires { wires: 6 })
+        ));
+        assert!(matches!(
+            wide.with_wires(4),
+            Err(ComposerError::WiresAfterGates)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_five_wires() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+        let cosets = [
+            Fr::one(),
+            Fr::from(7_u64),
+            Fr::from(13_u64),
+            Fr::from(17_u64),
+            Fr::from(23_u64),
+        ];
+
+        // 1 + 2 + 3 + 4 == 10 on one row, then a sum of five terms, public.
+        let build = |four: u64| {
+            let mut cs = Composer::new().with_wires(5).unwrap();
+            let vars: Vec<_> = [1, 2, 3, four]
+                .iter()
+                .map(|i| cs.alloc_and_assign(Fr::from(*i)))
+                .collect();
+            let ten = cs.alloc_and_assign(Fr::from(10_u64));
+            cs.create_wide_add_gate(
+                (vars[0], Fr::one()),
+                (vars[1], Fr::one()),
+                ten,
+                Some((vars[2], Fr::one())),
+                (vars[3], Fr::one()),
+                Fr::zero(),
+                Fr::zero(),
+            );
+            let terms: Vec<_> = vars
+                .iter()
+                .chain(Some(&ten))
+                .zip(1..)
+                .map(|(var, c)| (*var, Fr::from(c as u64)))
+                .collect();
+            let sum = cs.create_linear_combination_gate(&terms, Fr::zero());
+            let total = 1 + 2 * 2 + 3 * 3 + 4 * four + 5 * 10;
+            cs.constrain_to_constant(sum, Fr::zero(), Fr::from(total));
+            cs
+        };
+        let cs = build(4);
+        assert_eq!(cs.check_satisfied(), Ok(()));
+
+        let srs = PlonkInst::setup(32, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &cosets)?;
+        assert_eq!(vk.info.wires(), 5);
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert_eq!(proof.commitments[0].len(), 5);
+        assert_eq!(proof.commitments[3].len(), 5);
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        let mut wrong = cs.public_inputs().to_vec();
+        wrong[0] += Fr::one();
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(!PlonkInst::verify(&vk, &wrong, proof)?);
+
+        // a wrong value on `w_4`, failing the wide gate only.
+        let bad = build(5);
+        assert!(bad.check_satisfied().is_err());
+        let proof = PlonkInst::prove(&pk, &bad, rng)?;
+        assert!(!PlonkInst::verify(&vk, bad.public_inputs(), proof)?);
+
+        // `w_4` is absent with four wires.
+        let mut narrow = Composer::new();
+        let var = narrow.alloc_and_assign(Fr::one());
+        narrow.create_wide_add_gate(
+            (var, Fr::one()),
+            (var, Fr::one()),
+            var,
+            None,
+            (var, -Fr::one()),
+            Fr::zero(),
+            Fr::zero(),
+        );
+        assert!(matches!(
+            PlonkInst::keygen(&srs, &narrow, &ks()),
+            Err(Error::PolynomialProtocolError(AHPError::SynthesisError(
+                ComposerError::AbsentWire { row: 0, column: 4 }
+            )))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_lookup() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let mut cs = circuit();
+        let xor = cs.add_xor_table(2);
+        let range = cs.add_range_table(4);
+        let a = cs.alloc_and_assign(Fr::from(1u64));
+        let b = cs.alloc_and_assign(Fr::from(3u64));
+        let c = cs.lookup(xor, a, b);
+        cs.constrain_to_constant(c, Fr::from(2u64), Fr::zero());
+        let zero = cs.alloc_and_assign(Fr::zero());
+        cs.add_lookup_gate(range, b, zero, zero);
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+
+        let srs = PlonkInst::setup(128, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        let mut bad = circuit();
+        let range = bad.add_range_table(4);
+        let a = bad.alloc_and_assign(Fr::from(16u64));
+        let zero = bad.alloc_and_assign(Fr::zero());
+        bad.add_lookup_gate(range, a, zero, zero);
+        let row = bad.size() - 1;
+        let (pk, _) = PlonkInst::keygen(&srs, &bad, &ks())?;
+        assert!(matches!(
+            PlonkInst::prove(&pk, &bad, rng),
+            Err(Error::PolynomialProtocolError(AHPError::MissingLookup { row: r })) if r == row
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_memory() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let build = |close: bool| {
+            let mut cs = circuit();
+            let init: Vec<_> = (0..4u64)
+                .map(|i| cs.alloc_and_assign(Fr::from(i + 1)))
+                .collect();
+            let ram = cs.add_memory(&init);
+            let address = cs.alloc_and_assign(Fr::from(3u64));
+            let x = cs.memory_read(ram, address);
+            let y = cs.alloc_and_assign(Fr::from(8u64));
+            cs.create_add_gate(
+                (x, Fr::one()),
+                (x, Fr::one()),
+                y,
+                None,
+                Fr::zero(),
+                Fr::zero(),
+            );
+            cs.memory_write(ram, address, y);
+            let z = cs.memory_read(ram, address);
+            cs.constrain_to_constant(z, Fr::from(8u64), Fr::zero());
+            if close {
+                cs.close_memory(ram).unwrap();
+            }
+            cs
+        };
+
+        let cs = build(true);
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+        let srs = PlonkInst::setup(cs.srs_degree().map_err(ahp::Error::from)?, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        let open = build(false);
+        let (pk, _) = PlonkInst::keygen(&srs, &open, &ks())?;
+        assert!(matches!(
+            PlonkInst::prove(&pk, &open, rng),
+            Err(Error::PolynomialProtocolError(AHPError::InconsistentMemory))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_compiled() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let build = |cs: &mut Composer<Fr>, x: Fr| {
+            let var_x = cs.alloc_and_assign(x);
+            cs.range_gate(var_x, 8).unwrap();
+            cs.constrain_to_constant(var_x, Fr::zero(), x);
+        };
+        let mut builder = CircuitBuilder::new();
+        build(&mut builder, Fr::zero());
+        let compiled = builder
+            .compile::<Blake2s>(&ks())
+            .map_err(ahp::Error::from)?;
+
+        let srs = PlonkInst::setup(64, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, compiled.circuit(), compiled.ks())?;
+        assert_eq!(vk.circuit_hash, compiled.circuit_hash());
+
+        let mut filler = compiled.filler();
+        build(&mut filler, Fr::from(200u64));
+        let cs = filler.finish().map_err(ahp::Error::from)?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_logic() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let mut cs = circuit();
+        let a = cs.alloc_and_assign(Fr::from(0xb6u64));
+        let b = cs.alloc_and_assign(Fr::from(0x5cu64));
+        let xor = cs.xor_gate(a, b, 8).map_err(ahp::Error::from)?;
+        cs.constrain_to_constant(xor, Fr::from(0xeau64), Fr::zero());
+        let and = cs.and_gate(a, b, 8).map_err(ahp::Error::from)?;
+        cs.constrain_to_constant(and, Fr::from(0x14u64), Fr::zero());
+
+        let srs = PlonkInst::setup(64, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_curve() -> Result<(), Error<PCError>> {
+        use ark_ec::ProjectiveCurve;
+        use ark_ed_on_bls12_381::{EdwardsAffine, EdwardsParameters};
+
+        let rng = &mut test_rng();
+
+        let g = EdwardsAffine::prime_subgroup_generator();
+        let mut cs = circuit();
+        let a = cs.alloc_point(&g);
+        let double = cs.add_points::<EdwardsParameters>(a, a);
+        let triple = cs.add_points::<EdwardsParameters>(double, a);
+        let expected = g.mul(3u64).into_affine();
+        cs.constrain_to_constant(triple.x(), expected.x, Fr::zero());
+        cs.constrain_to_constant(triple.y(), expected.y, Fr::zero());
+
+        let srs = PlonkInst::setup(64, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plonk_linear_combination() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let mut cs = circuit();
+        let terms: Vec<_> = (1..=8u64)
+            .map(|i| (cs.alloc_and_assign(Fr::from(i)), Fr::from(i)))
+            .collect();
+        let sum = cs.create_linear_combination_gate(&terms, Fr::one());
+        cs.constrain_to_constant(sum, Fr::from(205u64), Fr::zero());
+
+        let srs = PlonkInst::setup(64, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_bytes() -> Result<(), Error<PCError>> {
+        use ark_serialize::CanonicalSerialize;
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let mut proof = PlonkInst::prove(&pk, &cs, rng)?;
+
+        let mut vk_bytes = Vec::new();
+        vk.serialize(&mut vk_bytes)?;
+        let vk = VerifierKey::<Fr, PC>::from_bytes(&vk_bytes)?;
+        vk_bytes.push(0);
+        assert!(VerifierKey::<Fr, PC>::from_bytes(&vk_bytes).is_err());
+
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes)?;
+        let read = Proof::<Fr, PC>::from_bytes(&bytes)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), read)?);
+        bytes.push(0);
+        assert!(Proof::<Fr, PC>::from_bytes(&bytes).is_err());
+
+        proof.commitments[2].pop();
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes)?;
+        assert!(Proof::<Fr, PC>::from_bytes(&bytes).is_err());
+
+        #[cfg(feature = "arbitrary")]
+        {
+            use arbitrary::{Arbitrary, Unstructured};
+            let noise: Vec<u8> = (0..4096).map(|i| (i * 37 % 251) as u8).collect();
+            let u = &mut Unstructured::new(&noise);
+            let proof = ProofBytes::arbitrary(u).unwrap();
+            assert!(Proof::<Fr, PC>::from_bytes(&proof.to_bytes()).is_err());
+            let vk = VerifierKeyBytes::arbitrary(u).unwrap();
+            assert!(VerifierKey::<Fr, PC>::from_bytes(&vk.to_bytes()).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_test_vector() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        let num_evaluations = proof.evaluations.len();
+        let vector = PlonkInst::test_vector("kzg", &vk, cs.public_inputs(), proof)?;
+        assert!(vector.verifies);
+        assert_eq!(vector.challenges.len(), CHALLENGES.len());
+        assert_eq!(vector.evaluations.len(), num_evaluations);
+        let zeta = vector.challenges[6].1;
+        let n = vk.info.domain_n.size() as u64;
+        assert_eq!(vector.intermediates[0].1, zeta.pow([n]) - Fr::one());
+
+        let json: serde_json::Value = serde_json::from_str(&vector.to_json()).unwrap();
+        assert_eq!(json["name"], "kzg");
+        assert_eq!(json["proof"].as_str().unwrap().len(), 2 * vector.proof.len());
+        assert!(json["challenges"]["epsilon"].is_string());
+        assert_eq!(json["verifies"], true);
+
+        let mut public_inputs = cs.public_inputs().to_vec();
+        public_inputs[0] += Fr::one();
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        let vector = PlonkInst::test_vector("kzg-bad-input", &vk, &public_inputs, proof)?;
+        assert!(!vector.verifies);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encoding() -> Result<(), Error<PCError>> {
+        use ark_ff::{BigInteger, PrimeField};
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+
+        let raw = vk.to_encoding(Encoding::Raw)?;
+        let uncompressed = vk.to_encoding(Encoding::Uncompressed)?;
+        assert_eq!(raw.len(), uncompressed.len());
+        let compressed =
+            reencode_verifier_key::<Fr, PC>(&raw, Encoding::Raw, Encoding::Compressed)?;
+        assert_eq!(compressed, vk.to_encoding(Encoding::Compressed)?);
+        let vk = VerifierKey::<Fr, PC>::from_encoding(&raw, Encoding::Raw)?;
+
+        let raw = proof.to_encoding(Encoding::Raw)?;
+        let point = proof.commitments[0][0].comm.0;
+        let mut x_y = point.x.into_repr().to_bytes_be();
+        x_y.extend(point.y.into_repr().to_bytes_be());
+        let offset = proof.circuit_hash.serialized_size() + 16;
+        assert_eq!(&raw[offset..offset + 96], &x_y[..]);
+
+        let proof = Proof::<Fr, PC>::from_encoding(&raw, Encoding::Raw)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        let mut raw = raw;
+        raw[offset + 95] ^= 1;
+        assert!(Proof::<Fr, PC>::from_encoding(&raw, Encoding::Raw).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_from_slices() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+
+        let vk_bytes = vk.to_versioned_bytes()?;
+        let proof_bytes = proof.to_versioned_bytes()?;
+        let pi_bytes = public_inputs_to_bytes(cs.public_inputs())?;
+        assert_eq!(&vk_bytes[..2], &[ENCODING_VERSION, 1]);
+        assert_eq!(&proof_bytes[2..], &proof.to_encoding(Encoding::Compressed)?[..]);
+        assert!(PlonkInst::verify_from_slices(&vk_bytes, &proof_bytes, &pi_bytes));
+
+        let mut other_version = proof_bytes.clone();
+        other_version[0] += 1;
+        assert!(!PlonkInst::verify_from_slices(&vk_bytes, &other_version, &pi_bytes));
+        assert!(!PlonkInst::verify_from_slices(&proof_bytes, &vk_bytes, &pi_bytes));
+        let truncated = &proof_bytes[..proof_bytes.len() - 1];
+        assert!(!PlonkInst::verify_from_slices(&vk_bytes, truncated, &pi_bytes));
+
+        let mut inputs = cs.public_inputs().to_vec();
+        inputs.push(Fr::one());
+        let pi_bytes = public_inputs_to_bytes(&inputs)?;
+        assert_eq!(public_inputs_from_bytes::<Fr>(&pi_bytes)?, inputs);
+        assert!(!PlonkInst::verify_from_slices(&vk_bytes, &proof_bytes, &pi_bytes));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ckb_layout() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+
+        let vk_data = vk_cell_data(&vk)?;
+        let witness = proof_witness(&proof, cs.public_inputs(), WitnessField::OutputType)?;
+        // the lock and the input type are absent, as in `WitnessArgs::default`
+        assert_eq!(&witness[4..12], &[16, 0, 0, 0, 16, 0, 0, 0]);
+        let (proof_bytes, pi_bytes) = parse_proof_witness(&witness, WitnessField::OutputType)?;
+        assert_eq!(proof_bytes, &proof.to_versioned_bytes()?[..]);
+        assert_eq!(pi_bytes, &public_inputs_to_bytes(cs.public_inputs())?[..]);
+        assert!(PlonkInst::verify_from_cells(&vk_data, &witness, WitnessField::OutputType));
+
+        assert!(parse_proof_witness(&witness, WitnessField::InputType).is_err());
+        assert!(!PlonkInst::verify_from_cells(&vk_data, &witness, WitnessField::Lock));
+        let mut wrong_size = witness.clone();
+        wrong_size.push(0);
+        assert!(parse_proof_witness(&wrong_size, WitnessField::OutputType).is_err());
+        let mut wrong_offset = witness.clone();
+        wrong_offset[8] += 1;
+        assert!(parse_proof_witness(&wrong_offset, WitnessField::OutputType).is_err());
+        assert!(parse_proof_witness(&witness[..3], WitnessField::OutputType).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vk_registry() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let mut other = circuit();
+        let one = other.alloc_and_assign(Fr::one());
+        other.constrain_to_constant(one, Fr::one(), Fr::zero());
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let (_, other_vk) = PlonkInst::keygen(&srs, &other, &ks())?;
+        let proof_bytes = PlonkInst::prove(&pk, &cs, rng)?.to_versioned_bytes()?;
+        let pi_bytes = public_inputs_to_bytes(cs.public_inputs())?;
+
+        let mut registry = VkRegistry::<Fr, Blake2s, PC>::new();
+        assert!(registry.is_empty());
+        let id = vk.digest::<Blake2s>();
+        let other_id = registry.register_bytes(&other_vk.to_versioned_bytes()?)?;
+        assert_eq!(other_id, other_vk.digest::<Blake2s>());
+        assert!(registry.register(vk.clone()).is_none());
+        assert!(registry.register(vk).is_some());
+        assert_eq!(registry.len(), 2);
+
+        assert!(registry.verify_by_id(&id, &proof_bytes, &pi_bytes)?);
+        assert!(matches!(
+            registry.verify_by_id(&other_id, &proof_bytes, &pi_bytes),
+            Err(Error::CircuitMismatch)
+        ));
+        assert!(matches!(
+            registry.verify_by_id(&id, &pi_bytes, &pi_bytes),
+            Err(Error::SerializationError(_))
+        ));
+
+        assert!(registry.remove(&id).is_some());
+        assert!(matches!(
+            registry.verify_by_id(&id, &proof_bytes, &pi_bytes),
+            Err(Error::UnknownCircuit)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prover_key_serialization() -> Result<(), Error<PCError>> {
+        use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+
+        let mut compressed = Vec::new();
+        pk.serialize(&mut compressed)?;
+        assert_eq!(compressed.len(), pk.serialized_size());
+        let mut uncompressed = Vec::new();
+        pk.serialize_uncompressed(&mut uncompressed)?;
+        assert_eq!(uncompressed.len(), pk.uncompressed_size());
+        assert!(uncompressed.len() > compressed.len());
+
+        let pks = [
+            ProverKey::<Fr, PC>::deserialize(&compressed[..])?,
+            ProverKey::<Fr, PC>::deserialize_uncompressed(&uncompressed[..])?,
+        ];
+        for pk in pks.iter() {
+            let proof = PlonkInst::prove(pk, &cs, rng)?;
+            assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_disk_backed_prover_key() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+
+        let path = std::env::temp_dir().join("zkp-plonk-test-disk-backed-key");
+        pk.save(&path)?;
+        let disk_backed = ProverKey::<Fr, PC>::load_disk_backed(&path)?;
+        let proof = PlonkInst::prove(&disk_backed, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        // the evaluations are read from the file at each proof.
+        std::fs::remove_file(&path)?;
+        let result = PlonkInst::prove(&disk_backed, &cs, rng);
+        assert!(matches!(
+            result,
+            Err(Error::PolynomialProtocolError(AHPError::IndexFileError(_)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialization_round_trip() -> Result<(), Error<PCError>> {
+        use ark_serialize::CanonicalSerialize;
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+
+        let (mut vk_compressed, mut vk_uncompressed) = (Vec::new(), Vec::new());
+        vk.serialize(&mut vk_compressed)?;
+        vk.serialize_uncompressed(&mut vk_uncompressed)?;
+        assert_eq!(vk_compressed.len(), vk.serialized_size());
+        assert_eq!(vk_uncompressed.len(), vk.uncompressed_size());
+        let (mut compressed, mut uncompressed) = (Vec::new(), Vec::new());
+        proof.serialize(&mut compressed)?;
+        proof.serialize_uncompressed(&mut uncompressed)?;
+        assert_eq!(compressed.len(), proof.serialized_size());
+        assert_eq!(uncompressed.len(), proof.uncompressed_size());
+        assert!(uncompressed.len() > compressed.len());
+
+        let vks = [
+            VerifierKey::<Fr, PC>::from_bytes(&vk_compressed)?,
+            VerifierKey::<Fr, PC>::from_uncompressed_bytes(&vk_uncompressed)?,
+        ];
+        let proofs = vec![
+            Proof::<Fr, PC>::from_bytes(&compressed)?,
+            Proof::<Fr, PC>::from_uncompressed_bytes(&uncompressed)?,
+        ];
+        for (vk, proof) in vks.iter().zip(proofs) {
+            // the values read back encode to the same bytes.
+            let mut bytes = Vec::new();
+            vk.serialize(&mut bytes)?;
+            assert_eq!(bytes, vk_compressed);
+            let mut bytes = Vec::new();
+            proof.serialize(&mut bytes)?;
+            assert_eq!(bytes, compressed);
+
+            assert!(PlonkInst::verify(vk, cs.public_inputs(), proof)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_verifier_key_digest() -> Result<(), Error<PCError>> {
+        use ark_serialize::CanonicalSerialize;
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let digest = vk.digest::<Blake2s>();
+        assert_eq!(digest.len(), 32);
+        assert_eq!(pk.vk.digest::<Blake2s>(), digest);
+
+        let mut bytes = Vec::new();
+        vk.serialize(&mut bytes)?;
+        let read = VerifierKey::<Fr, PC>::from_bytes(&bytes)?;
+        assert_eq!(read.digest::<Blake2s>(), digest);
+
+        // the same circuit committed under another setup is another key.
+        let (_, other) = PlonkInst::keygen(&PlonkInst::setup(16, rng)?, &cs, &ks())?;
+        assert_eq!(other.circuit_hash, vk.circuit_hash);
+        assert_ne!(other.digest::<Blake2s>(), digest);
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(!PlonkInst::verify(&other, cs.public_inputs(), proof)?);
+
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&read, cs.public_inputs(), proof)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_context() -> Result<(), Error<PCError>> {
+        use ark_serialize::CanonicalSerialize;
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (_, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        assert!(vk.info.context.is_empty());
+
+        let index = PlonkInst::index(&cs, &ks())?;
+        let (pk_a, vk_a) = PlonkInst::commit_index(&srs, index.clone().with_context(b"app-a"))?;
+        let (_, vk_b) = PlonkInst::commit_index(&srs, index.with_context(b"app-b"))?;
+        assert_eq!(vk_a.circuit_hash, vk_b.circuit_hash);
+
+        let mut bytes = Vec::new();
+        vk_a.serialize(&mut bytes)?;
+        let read = VerifierKey::<Fr, PC>::from_bytes(&bytes)?;
+        assert_eq!(read.info.context, b"app-a".to_vec());
+
+        let proof = PlonkInst::prove(&pk_a, &cs, rng)?;
+        assert!(PlonkInst::verify(&read, cs.public_inputs(), proof)?);
+        for vk in [vk, vk_b].iter() {
+            let proof = PlonkInst::prove(&pk_a, &cs, rng)?;
+            assert!(!PlonkInst::verify(vk, cs.public_inputs(), proof)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_trimmed_setup() -> Result<(), Error<PCError>> {
+        use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let mut other = circuit();
+        let var = other.alloc_and_assign(Fr::one());
+        other.constrain_to_constant(var, Fr::one(), Fr::zero());
+
+        let srs = PlonkInst::setup(16, rng)?;
+        let setup = PlonkInst::trim(&srs, 16)?;
+        assert_eq!(setup.max_degree(), 16);
+        let (pk, vk) = PlonkInst::keygen_trimmed(&setup, &cs, &ks())?;
+        let (other_pk, other_vk) = PlonkInst::keygen_trimmed(&setup, &other, &ks())?;
+        assert!(Arc::ptr_eq(&pk.ck, &setup.ck));
+        assert!(Arc::ptr_eq(&other_pk.ck, &setup.ck));
+
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        let proof = PlonkInst::prove(&other_pk, &other, rng)?;
+        assert!(PlonkInst::verify(&other_vk, other.public_inputs(), proof)?);
+
+        // a prover key read back holds its own committer key.
+        let mut bytes = Vec::new();
+        pk.serialize(&mut bytes)?;
+        let pk = ProverKey::<Fr, PC>::deserialize(&bytes[..])?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+
+        assert!(matches!(
+            PlonkInst::trim(&srs, 17),
+            Err(Error::CircuitTooLarge { needed: 17, .. })
+        ));
+        let small = PlonkInst::trim(&srs, 4)?;
+        assert!(matches!(
+            PlonkInst::keygen_trimmed(&small, &cs, &ks()),
+            Err(Error::CircuitTooLarge { available: 4, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_errors() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        // a setup too small is rejected before the circuit is indexed.
+        let cs = circuit();
+        let degree = cs.srs_degree().unwrap();
+        let srs = PlonkInst::setup(degree - 1, rng)?;
+        assert!(matches!(
+            PlonkInst::keygen(&srs, &cs, &ks()),
+            Err(Error::CircuitTooLarge { needed, available })
+                if needed == degree && available == degree - 1
+        ));
+
+        let size = 1 << 40;
+        let err = AHPError::from(ComposerError::PolynomialDegreeTooLarge { size });
+        assert!(matches!(
+            Error::<PCError>::from(err),
+            Error::UnsupportedDomain { size: s } if s == size
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_blinding() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        for &blinding in [0, 1, DEFAULT_BLINDING, 8].iter() {
+            let index = PlonkInst::index_with_blinding(&cs, &ks(), blinding)?;
+            let (pk, vk) = PlonkInst::commit_index(&srs, index)?;
+            assert_eq!(vk.info.blinding, blinding);
+            let proof = PlonkInst::prove(&pk, &cs, rng)?;
+            let other = PlonkInst::prove(&pk, &cs, rng)?;
+            // the oracles are committed to without hiding, so only their
+            // blinding tells two proofs of the same witness apart: the wires,
+            // then z, h_1 and h_2, then z_lookup and z_memory.
+            for round in 0..3 {
+                let same = proof.commitments[round] == other.commitments[round];
+                assert_eq!(same, blinding == 0);
+            }
+            assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+            assert!(PlonkInst::verify(&vk, cs.public_inputs(), other)?);
+        }
+
+        // the quotient chunks grow with the blinding, past the domain.
+        let index = PlonkInst::index_with_blinding(&cs, &ks(), 8)?;
+        assert!(matches!(
+            PlonkInst::commit_index(&PlonkInst::setup(8, rng)?, index),
+            Err(Error::CircuitTooLarge { needed: 16, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_committed_inputs() -> Result<(), Error<PCError>> {
+        use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let index = PlonkInst::index(&cs, &ks())?.with_committed_inputs();
+        let (pk, vk) = PlonkInst::commit_index(&srs, index)?;
+        let mut bytes = Vec::new();
+        vk.serialize(&mut bytes)?;
+        let vk = VerifierKey::<Fr, PC>::deserialize(&bytes[..])?;
+        assert!(vk.info.committed_inputs);
+
+        let pi_commitment = PlonkInst::commit_public_inputs(&pk, cs.public_inputs())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(PlonkInst::verify_committed(&vk, &pi_commitment, proof)?);
+
+        let mut public_inputs = cs.public_inputs().to_vec();
+        public_inputs[0] += Fr::one();
+        let other = PlonkInst::commit_public_inputs(&pk, &public_inputs)?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(!PlonkInst::verify_committed(&vk, &other, proof)?);
+
+        // the inputs are given as the key takes them.
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(matches!(
+            PlonkInst::verify(&vk, cs.public_inputs(), proof),
+            Err(Error::InputModeMismatch)
+        ));
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        assert!(matches!(
+            PlonkInst::commit_public_inputs(&pk, cs.public_inputs()),
+            Err(Error::InputModeMismatch)
+        ));
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        assert!(matches!(
+            PlonkInst::verify_committed(&vk, &pi_commitment, proof),
+            Err(Error::InputModeMismatch)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_index() -> Result<(), Error<PCError>> {
+        use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let index = PlonkInst::index(&cs, &ks())?;
+        let mut bytes = Vec::new();
+        index.serialize(&mut bytes)?;
+        let index = CircuitIndex::<Fr>::deserialize(&bytes[..])?;
+        assert_eq!(index.inspect().gates, Some(index.size()));
+
+        for &degree in [16, 32].iter() {
+            let srs = PlonkInst::setup(degree, rng)?;
+            let (pk, vk) = PlonkInst::commit_index(&srs, index.clone())?;
+            let proof = PlonkInst::prove(&pk, &cs, rng)?;
+            assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_inspect() -> Result<(), Error<PCError>> {
+        use ark_serialize::CanonicalSerialize;
+
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+
+        let vk_info = vk.inspect();
+        assert_eq!(vk_info.curve, "bls12_381");
+        assert_eq!(vk_info.gates, Some(vk.info.n));
+        assert_eq!(vk_info.domain_size, Some(vk.info.n.next_power_of_two()));
+        assert_eq!(vk_info.commitments, vk.labels.len());
+        assert_eq!(vk_info.size, vk.serialized_size());
+
+        let pk_info = pk.inspect();
+        assert_eq!(pk_info.object, "prover key");
+        assert_eq!(pk_info.circuit_hash, vk_info.circuit_hash);
+
+        let proof_info = proof.inspect();
+        assert_eq!(proof_info.circuit_hash, vk_info.circuit_hash);
+        assert_eq!(proof_info.evaluations, proof.evaluations.len());
+        assert_eq!(proof_info.size, proof.serialized_size());
+        assert_eq!(proof_info.gates, None);
+        assert!(proof_info.to_string().contains("plonk proof"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_self_test() -> Result<(), Error<PCError>> {
+        PlonkInst::self_test(&BLS12_381_KZG_BLAKE2S)?;
+        Ipa::<ark_pallas::Affine>::self_test(&PALLAS_IPA_BLAKE2S)
+    }
+
+    #[test]
+    fn test_self_test_rejects_other_answers() {
+        let mut proof = BLS12_381_KZG_BLAKE2S.proof.to_vec();
+        let last = proof.len() - 1;
+        proof[last] ^= 1;
+        let tampered = KnownAnswer {
+            proof: Box::leak(proof.into_boxed_slice()),
+            ..BLS12_381_KZG_BLAKE2S
+        };
+        assert!(matches!(
+            PlonkInst::self_test(&tampered),
+            Err(Error::SelfTestFailed { check: "the proof is the known one" })
+        ));
+        assert!(matches!(
+            PlonkInst::self_test(&PALLAS_IPA_BLAKE2S),
+            Err(Error::SelfTestFailed { .. })
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_load_srs() -> Result<(), Error<PCError>> {
+        use ark_serialize::CanonicalSerialize;
+        use digest::Digest;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let srs = PlonkInst::setup(16, &mut test_rng())?;
+        let mut bytes = Vec::new();
+        srs.serialize(&mut bytes)?;
+        let digest = Blake2s::digest(&bytes).to_vec();
+
+        let cache_dir = std::env::temp_dir().join("zkp-plonk-test-load-srs");
+        let _ = std::fs::remove_dir_all(&cache_dir);
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let counter = fetches.clone();
+        let loader = SrsLoader::new(&cache_dir).with_remote(
+            "https://example.org/srs/",
+            Box::new(move |url| {
+                assert_eq!(url, "https://example.org/srs/test-2^4");
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(bytes.clone())
+            }),
+        );
+
+        // fetched once, then read from the cache.
+        for _ in 0..2 {
+            let loaded = PlonkInst::load_srs(&loader, "test-2^4", &digest)?;
+            assert_eq!(loaded.max_degree(), srs.max_degree());
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        let mut wrong = digest;
+        wrong[0] ^= 1;
+        let result = PlonkInst::load_srs(&loader, "test-2^4", &wrong);
+        assert!(matches!(result, Err(Error::SrsDigestMismatch { .. })));
+        assert!(PlonkInst::load_srs(&loader, "../test-2^4", &wrong).is_err());
+
+        std::fs::remove_dir_all(&cache_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate() -> Result<(), Error<PCError>> {
+        let cs = circuit();
+        let estimate = PlonkInst::estimate(&cs)?;
+        assert!(estimate.domain_size.is_power_of_two());
+        assert!(estimate.domain_size >= cs.size());
+        assert_eq!(estimate.msms, 15);
+
+        let mut large = circuit();
+        let zero = large.alloc_and_assign(Fr::zero());
+        for _ in 0..64 {
+            large.create_add_gate(
+                (zero, Fr::one()),
+                (zero, Fr::one()),
+                zero,
+                None,
+                Fr::zero(),
+                Fr::zero(),
+            );
+        }
+        let larger = PlonkInst::estimate(&large)?;
+        assert!(larger.peak_memory > estimate.peak_memory);
+        assert!(larger.prove_time > estimate.prove_time);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verifier_estimate() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+        // one opening at `zeta` and one at `shifted_zeta`.
+        assert_eq!(proof.pc_proof.proof.len(), 2);
+
+        let estimate = PlonkInst::verifier_estimate(&vk, Backend::Kzg)?;
+        assert_eq!(estimate.proof_size, proof.serialized_size());
+        assert_eq!(estimate.pairings, 2);
+        assert_eq!(estimate.msms.last(), Some(&5));
+        assert!(estimate.field_ops > 5 * vk.info.n);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_with_hooks() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+
+        let mut phases = Vec::new();
+        let mut progress = |phase: Phase| phases.push(phase);
+        let hooks = ProverHooks {
+            progress: Some(&mut progress),
+            cancel: None,
+        };
+        let proof = PlonkInst::prove_with(&pk, &cs, rng, hooks)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        assert_eq!(phases.len(), 2 * 4 + 2);
+        assert_eq!(
+            phases[0],
+            Phase::Round {
+                round: 0,
+                rounds: 4
+            }
+        );
+        assert_eq!(phases[1], Phase::Commit { round: 0 });
+        assert_eq!(phases[9], Phase::Open);
+
+        let cancel = CancellationToken::new();
+        let mut progress = |phase: Phase| {
+            if phase == Phase::Evaluate {
+                cancel.cancel();
+            }
+        };
+        let hooks = ProverHooks {
+            progress: Some(&mut progress),
+            cancel: Some(&cancel),
+        };
+        assert!(matches!(
+            PlonkInst::prove_with(&pk, &cs, rng, hooks),
+            Err(Error::Cancelled)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_checkpointed() -> Result<(), Error<PCError>> {
+        use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+
+        let mut saved = Vec::new();
+        let mut save = |checkpoint: &Checkpoint<Fr, PC>| {
+            let mut bytes = Vec::new();
+            checkpoint.serialize(&mut bytes)?;
+            saved.push(bytes);
+            Ok(())
+        };
+        let hooks = ProverHooks::default();
+        let proof = PlonkInst::prove_checkpointed(&pk, &cs, rng, hooks, None, &mut save)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        assert_eq!(saved.len(), 4);
+
+        for (round, bytes) in saved.iter().enumerate() {
+            let resume = Checkpoint::<Fr, PC>::deserialize(&bytes[..])?;
+            assert_eq!(resume.rounds(), round + 1);
+            let hooks = ProverHooks::default();
+            let proof =
+                PlonkInst::prove_checkpointed(&pk, &cs, rng, hooks, Some(resume), &mut |_| Ok(()))?;
+            assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        }
+
+        let mut resume = Checkpoint::<Fr, PC>::deserialize(&saved[0][..])?;
+        resume.transcript[0] ^= 1;
+        let hooks = ProverHooks::default();
+        let result =
+            PlonkInst::prove_checkpointed(&pk, &cs, rng, hooks, Some(resume), &mut |_| Ok(()));
+        assert!(matches!(
+            result,
+            Err(Error::CheckpointMismatch {
+                check: "transcript"
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_with_workspace() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+
+        let mut workspace = ProverWorkspace::new();
+        let proof =
+            PlonkInst::prove_with_workspace(&pk, &cs, rng, ProverHooks::default(), &mut workspace)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        let capacity = workspace.capacity();
+        assert!(capacity >= 6 * 4 * vk.info.n.next_power_of_two());
+        // the wires, t and the four polynomials of the lookup.
+        let n_4n = (4 * vk.info.quotient_chunk()).next_power_of_two();
+        assert!(capacity <= 9 * n_4n);
+
+        let proof =
+            PlonkInst::prove_with_workspace(&pk, &cs, rng, ProverHooks::default(), &mut workspace)?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        assert_eq!(workspace.capacity(), capacity);
+        Ok(())
+    }
+
+    #[test]
+    fn test_yield_hook() -> Result<(), Error<PCError>> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+
+        let yields = Arc::new(AtomicUsize::new(0));
+        let mut workspace = ProverWorkspace::new();
+        let counter = yields.clone();
+        workspace.set_yield_hook(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let proof =
+            PlonkInst::prove_with_workspace(&pk, &cs, rng, ProverHooks::default(), &mut workspace)?;
+        // at least once per round, per query and before the opening.
+        let proved = yields.load(Ordering::Relaxed);
+        assert!(proved > 4 + 23);
+
+        let mut verified = 0;
+        assert!(PlonkInst::verify_yielding(
+            &vk,
+            cs.public_inputs(),
+            proof,
+            &mut || verified += 1
+        )?);
+        assert_eq!(verified, 3);
+
+        workspace.clear_yield_hook();
+        PlonkInst::prove_with_workspace(&pk, &cs, rng, ProverHooks::default(), &mut workspace)?;
+        assert_eq!(yields.load(Ordering::Relaxed), proved);
+        Ok(())
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_thread_pool() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen_in(&pool, &srs, &cs, &ks())?;
+        let proof = PlonkInst::prove_in(&pool, &pk, &cs, rng, ProverHooks::default())?;
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        Ok(())
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_prove_with_threads() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        for threads in 1..4 {
+            let proof =
+                PlonkInst::prove_with_threads(threads, &pk, &cs, rng, ProverHooks::default())?;
+            assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_prove_many() -> Result<(), Error<PCError>> {
+        let rng = &mut test_rng();
+        let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+
+        let circuits = vec![circuit(), circuit(), circuit(), circuit(), circuit()];
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &circuits[0], &ks())?;
+        let proofs = PlonkInst::prove_many(&pool, &pk, &circuits, 2, rng);
+        assert_eq!(proofs.len(), circuits.len());
+        for (cs, proof) in circuits.iter().zip(proofs) {
+            assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof?)?);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() -> Result<(), Error<PCError>> {
+        use ark_std::convert::TryFrom;
+
+        let rng = &mut test_rng();
+
+        let cs = circuit();
+        let srs = PlonkInst::setup(16, rng)?;
+        let (pk, vk) = PlonkInst::keygen(&srs, &cs, &ks())?;
+        let proof = PlonkInst::prove(&pk, &cs, rng)?;
+
+        let vk: VerifierKey<Fr, PC> =
+            serde_json::from_str(&serde_json::to_string(&vk).unwrap()).unwrap();
+        let json = serde_json::to_string(&proof).unwrap();
+        assert!(json.starts_with('"'));
+        let proof: Proof<Fr, PC> = serde_json::from_str(&json).unwrap();
+
+        let hex_vk = HexVerifierKey::from(&vk);
+        let json = serde_json::to_string(&hex_vk).unwrap();
+        assert_eq!(serde_json::from_str::<HexVerifierKey>(&json).unwrap(), hex_vk);
+        let vk = VerifierKey::<Fr, PC>::try_from(&hex_vk).unwrap();
+
+        let mut hex_proof = HexProof::from(&proof);
+        let json: serde_json::Value = serde_json::to_value(&hex_proof).unwrap();
+        assert_eq!(json["commitments"][1][0]["label"], "z");
+        assert_eq!(serde_json::from_value::<HexProof>(json).unwrap(), hex_proof);
+        let decoded = Proof::<Fr, PC>::try_from(&hex_proof).unwrap();
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), decoded)?);
+
+        hex_proof.commitments[0].swap(0, 1);
+        assert!(Proof::<Fr, PC>::try_from(&hex_proof).is_err());
+        hex_proof.commitments[0].swap(0, 1);
+        hex_proof.evaluations[0].push('0');
+        assert!(Proof::<Fr, PC>::try_from(&hex_proof).is_err());
+
+        assert!(PlonkInst::verify(&vk, cs.public_inputs(), proof)?);
+        Ok(())
+    }
 }
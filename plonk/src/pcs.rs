@@ -0,0 +1,553 @@
+//! A minimal polynomial commitment interface owned by this crate.
+//!
+//! [`PCS`] only asks for what the protocols here actually use: commit (in
+//! coefficient or evaluation form), single-point openings, batched openings
+//! over a query set and the openings of linear combinations that `Plonk`
+//! and the [`Compiler`](crate::Compiler) make. New backends (FRI,
+//! Zeromorph, accelerated KZG) implement it directly, as
+//! [`Shplonk`](crate::Shplonk) and [`Ipa`](crate::Ipa) do, while every
+//! scheme of `ark-poly-commit` over univariate polynomials is one through a
+//! blanket implementation.
+
+use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{FftField as Field, PrimeField, ToBytes};
+use ark_poly::{
+    univariate::DensePolynomial, Evaluations as EvaluationsOnDomain, GeneralEvaluationDomain,
+    UVPolynomial,
+};
+use ark_poly_commit::{
+    ipa_pc::InnerProductArgPC, marlin_pc::MarlinKZG10, BatchLCProof, Evaluations, LCTerm,
+    LinearCombination, PCCommitterKey, PCUniversalParams, PCVerifierKey,
+    PolynomialCommitment as ArkPC, QuerySet,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Write};
+use ark_std::{string::String, vec::Vec};
+use digest::Digest;
+use rand_core::RngCore;
+
+use crate::data_structures::LabeledPolynomial;
+use crate::utils::{add_scaled, to_labeled};
+
+/// A commitment with the label of the committed polynomial.
+#[derive(Clone, Debug)]
+pub struct LabeledCommitment<C> {
+    label: String,
+    commitment: C,
+    degree_bound: Option<usize>,
+}
+
+impl<C> LabeledCommitment<C> {
+    pub fn new(label: String, commitment: C, degree_bound: Option<usize>) -> Self {
+        Self {
+            label,
+            commitment,
+            degree_bound,
+        }
+    }
+
+    pub fn label(&self) -> &String {
+        &self.label
+    }
+
+    pub fn commitment(&self) -> &C {
+        &self.commitment
+    }
+
+    pub fn degree_bound(&self) -> Option<usize> {
+        self.degree_bound
+    }
+}
+
+/// Only the commitment is written, as the transcripts absorb it.
+impl<C: ToBytes> ToBytes for LabeledCommitment<C> {
+    fn write<W: Write>(&self, writer: W) -> ark_std::io::Result<()> {
+        self.commitment.write(writer)
+    }
+}
+
+pub trait PCS<F: Field> {
+    type UniversalParams: PCUniversalParams;
+    type CommitterKey: PCCommitterKey;
+    type VerifierKey: PCVerifierKey;
+    type Commitment: Clone + ToBytes + CanonicalSerialize + CanonicalDeserialize;
+    type Randomness: Clone + CanonicalSerialize + CanonicalDeserialize;
+    type Proof;
+    type BatchProof;
+    /// The opening of linear combinations of the committed polynomials, as
+    /// proofs hold it.
+    type LCProof: CanonicalSerialize + CanonicalDeserialize;
+    type Error: ark_std::error::Error;
+
+    fn setup<R: RngCore>(
+        max_degree: usize,
+        rng: &mut R,
+    ) -> Result<Self::UniversalParams, Self::Error>;
+
+    fn trim(
+        pp: &Self::UniversalParams,
+        supported_degree: usize,
+    ) -> Result<(Self::CommitterKey, Self::VerifierKey), Self::Error>;
+
+    fn commit<'a>(
+        ck: &Self::CommitterKey,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<F>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Committed<F, Self>, Self::Error>;
+
+    /// Commit to the polynomial with `evaluations` over their domain. By
+    /// default it is interpolated and committed in coefficient form; schemes
+    /// with a Lagrange basis can commit to the evaluations directly.
+    #[allow(clippy::type_complexity)]
+    fn commit_evaluations(
+        ck: &Self::CommitterKey,
+        label: &str,
+        evaluations: EvaluationsOnDomain<F, GeneralEvaluationDomain<F>>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<(LabeledCommitment<Self::Commitment>, Self::Randomness), Self::Error> {
+        let polynomial = to_labeled(label, evaluations.interpolate());
+        let (mut comms, mut rands) = Self::commit(ck, Some(&polynomial), rng)?;
+        Ok((comms.remove(0), rands.remove(0)))
+    }
+
+    /// Open all `polynomials` at `point`, combined with powers of
+    /// `opening_challenge`.
+    #[allow(clippy::too_many_arguments)]
+    fn open<'a>(
+        ck: &Self::CommitterKey,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<F>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        point: F,
+        opening_challenge: F,
+        rands: impl IntoIterator<Item = &'a Self::Randomness>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Self::Error>
+    where
+        Self::Commitment: 'a,
+        Self::Randomness: 'a;
+
+    #[allow(clippy::too_many_arguments)]
+    fn check<'a, R: RngCore>(
+        vk: &Self::VerifierKey,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        point: F,
+        values: impl IntoIterator<Item = F>,
+        proof: &Self::Proof,
+        opening_challenge: F,
+        rng: &mut R,
+    ) -> Result<bool, Self::Error>
+    where
+        Self::Commitment: 'a;
+
+    /// Open the polynomials at every point of `query_set`.
+    #[allow(clippy::too_many_arguments)]
+    fn batch_open<'a>(
+        ck: &Self::CommitterKey,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<F>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        query_set: &QuerySet<F>,
+        opening_challenge: F,
+        rands: impl IntoIterator<Item = &'a Self::Randomness>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::BatchProof, Self::Error>
+    where
+        Self::Commitment: 'a,
+        Self::Randomness: 'a;
+
+    #[allow(clippy::too_many_arguments)]
+    fn batch_check<'a, R: RngCore>(
+        vk: &Self::VerifierKey,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        query_set: &QuerySet<F>,
+        evaluations: &Evaluations<F, F>,
+        proof: &Self::BatchProof,
+        opening_challenge: F,
+        rng: &mut R,
+    ) -> Result<bool, Self::Error>
+    where
+        Self::Commitment: 'a;
+
+    /// Open the linear combinations `lcs` of `polynomials` at the points of
+    /// `query_set`, which names the combinations.
+    #[allow(clippy::too_many_arguments)]
+    fn open_combinations<'a>(
+        ck: &Self::CommitterKey,
+        lcs: impl IntoIterator<Item = &'a LinearCombination<F>>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<F>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        query_set: &QuerySet<F>,
+        opening_challenge: F,
+        rands: impl IntoIterator<Item = &'a Self::Randomness>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::LCProof, Self::Error>
+    where
+        Self::Commitment: 'a,
+        Self::Randomness: 'a;
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_combinations<'a, R: RngCore>(
+        vk: &Self::VerifierKey,
+        lcs: impl IntoIterator<Item = &'a LinearCombination<F>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+        query_set: &QuerySet<F>,
+        evaluations: &Evaluations<F, F>,
+        proof: &Self::LCProof,
+        opening_challenge: F,
+        rng: &mut R,
+    ) -> Result<bool, Self::Error>
+    where
+        Self::Commitment: 'a;
+}
+
+/// The commitments of `PCS::commit`, with their randomness.
+pub type Committed<F, PC> = (
+    Vec<LabeledCommitment<<PC as PCS<F>>::Commitment>>,
+    Vec<<PC as PCS<F>>::Randomness>,
+);
+
+/// The polynomials of `lcs` over `polynomials`, labelled as the
+/// combinations, for schemes that open a combination as a polynomial of its
+/// own. Fails with the label of a missing polynomial.
+pub(crate) fn combine_polynomials<F: Field>(
+    lcs: &[&LinearCombination<F>],
+    polynomials: &[&LabeledPolynomial<F>],
+) -> Result<Vec<LabeledPolynomial<F>>, String> {
+    lcs.iter()
+        .map(|lc| {
+            let mut coeffs = Vec::new();
+            for (coeff, term) in lc.iter() {
+                match term {
+                    LCTerm::One => add_scaled(&mut coeffs, &[F::one()], coeff),
+                    LCTerm::PolyLabel(label) => {
+                        let p = polynomials
+                            .iter()
+                            .find(|p| p.label() == label)
+                            .ok_or_else(|| label.clone())?;
+                        add_scaled(&mut coeffs, &p.polynomial().coeffs, coeff);
+                    }
+                }
+            }
+            let poly = DensePolynomial::from_coefficients_vec(coeffs);
+            Ok(to_labeled(&lc.label, poly))
+        })
+        .collect()
+}
+
+/// The commitments to the polynomials of `combine_polynomials`, for schemes
+/// without hiding whose commitments are points, with `one` the commitment
+/// to the constant polynomial one. Fails with the label of a missing
+/// commitment.
+pub(crate) fn combine_commitments<G: AffineCurve>(
+    lcs: &[&LinearCombination<G::ScalarField>],
+    commitments: &[&LabeledCommitment<G>],
+    one: G,
+) -> Result<Vec<LabeledCommitment<G>>, String> {
+    lcs.iter()
+        .map(|lc| {
+            let mut bases = Vec::with_capacity(lc.terms.len());
+            let mut scalars = Vec::with_capacity(lc.terms.len());
+            for (coeff, term) in lc.iter() {
+                let base = match term {
+                    LCTerm::One => one,
+                    LCTerm::PolyLabel(label) => *commitments
+                        .iter()
+                        .find(|c| c.label() == label)
+                        .ok_or_else(|| label.clone())?
+                        .commitment(),
+                };
+                bases.push(base);
+                scalars.push(coeff.into_repr());
+            }
+            let comm = VariableBaseMSM::multi_scalar_mul(&bases, &scalars).into_affine();
+            Ok(LabeledCommitment::new(lc.label.clone(), comm, None))
+        })
+        .collect()
+}
+
+fn to_ark<C: ark_poly_commit::PCCommitment>(
+    c: &LabeledCommitment<C>,
+) -> ark_poly_commit::LabeledCommitment<C> {
+    ark_poly_commit::LabeledCommitment::new(c.label.clone(), c.commitment.clone(), c.degree_bound)
+}
+
+fn from_ark<C: ark_poly_commit::PCCommitment>(
+    c: ark_poly_commit::LabeledCommitment<C>,
+) -> LabeledCommitment<C> {
+    LabeledCommitment::new(c.label().clone(), c.commitment().clone(), c.degree_bound())
+}
+
+/// The schemes of `ark-poly-commit` `Plonk` takes, set up without degree
+/// bounds or hiding bounds. Linear combinations are opened as the scheme
+/// opens them, e.g. from combined commitments under `marlin_pc`.
+macro_rules! impl_ark_pcs {
+    (impl<$($param:ident: $bound:path),*> PCS<$field:ty> for $pc:ty) => {
+        impl<$($param: $bound),*> PCS<$field> for $pc {
+            type UniversalParams = <$pc as ArkPC<$field, DensePolynomial<$field>>>::UniversalParams;
+            type CommitterKey = <$pc as ArkPC<$field, DensePolynomial<$field>>>::CommitterKey;
+            type VerifierKey = <$pc as ArkPC<$field, DensePolynomial<$field>>>::VerifierKey;
+            type Commitment = <$pc as ArkPC<$field, DensePolynomial<$field>>>::Commitment;
+            type Randomness = <$pc as ArkPC<$field, DensePolynomial<$field>>>::Randomness;
+            type Proof = <$pc as ArkPC<$field, DensePolynomial<$field>>>::Proof;
+            type BatchProof = <$pc as ArkPC<$field, DensePolynomial<$field>>>::BatchProof;
+            type LCProof = BatchLCProof<$field, DensePolynomial<$field>, $pc>;
+            type Error = <$pc as ArkPC<$field, DensePolynomial<$field>>>::Error;
+
+            fn setup<R: RngCore>(
+                max_degree: usize,
+                rng: &mut R,
+            ) -> Result<Self::UniversalParams, Self::Error> {
+                <$pc as ArkPC<$field, DensePolynomial<$field>>>::setup(max_degree, None, rng)
+            }
+
+            fn trim(
+                pp: &Self::UniversalParams,
+                supported_degree: usize,
+            ) -> Result<(Self::CommitterKey, Self::VerifierKey), Self::Error> {
+                <$pc as ArkPC<$field, DensePolynomial<$field>>>::trim(pp, supported_degree, 0, None)
+            }
+
+            fn commit<'a>(
+                ck: &Self::CommitterKey,
+                polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<$field>>,
+                rng: Option<&mut dyn RngCore>,
+            ) -> Result<Committed<$field, Self>, Self::Error> {
+                let (comms, rands) = <$pc as ArkPC<$field, DensePolynomial<$field>>>::commit(ck, polynomials, rng)?;
+                Ok((comms.into_iter().map(from_ark).collect(), rands))
+            }
+
+            fn open<'a>(
+                ck: &Self::CommitterKey,
+                polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<$field>>,
+                commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+                point: $field,
+                opening_challenge: $field,
+                rands: impl IntoIterator<Item = &'a Self::Randomness>,
+                rng: Option<&mut dyn RngCore>,
+            ) -> Result<Self::Proof, Self::Error>
+            where
+                Self::Commitment: 'a,
+                Self::Randomness: 'a,
+            {
+                let polynomials: Vec<&LabeledPolynomial<$field>> = polynomials.into_iter().collect();
+                let rands: Vec<&Self::Randomness> = rands.into_iter().collect();
+                let comms: Vec<_> = commitments.into_iter().map(to_ark).collect();
+                <$pc as ArkPC<$field, DensePolynomial<$field>>>::open(
+                    ck,
+                    polynomials,
+                    &comms,
+                    &point,
+                    opening_challenge,
+                    rands,
+                    rng,
+                )
+            }
+
+            fn check<'a, R: RngCore>(
+                vk: &Self::VerifierKey,
+                commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+                point: $field,
+                values: impl IntoIterator<Item = $field>,
+                proof: &Self::Proof,
+                opening_challenge: $field,
+                rng: &mut R,
+            ) -> Result<bool, Self::Error>
+            where
+                Self::Commitment: 'a,
+            {
+                let comms: Vec<_> = commitments.into_iter().map(to_ark).collect();
+                <$pc as ArkPC<$field, DensePolynomial<$field>>>::check(
+                    vk,
+                    &comms,
+                    &point,
+                    values,
+                    proof,
+                    opening_challenge,
+                    Some(rng),
+                )
+            }
+
+            fn batch_open<'a>(
+                ck: &Self::CommitterKey,
+                polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<$field>>,
+                commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+                query_set: &QuerySet<$field>,
+                opening_challenge: $field,
+                rands: impl IntoIterator<Item = &'a Self::Randomness>,
+                rng: Option<&mut dyn RngCore>,
+            ) -> Result<Self::BatchProof, Self::Error>
+            where
+                Self::Commitment: 'a,
+                Self::Randomness: 'a,
+            {
+                let polynomials: Vec<&LabeledPolynomial<$field>> = polynomials.into_iter().collect();
+                let rands: Vec<&Self::Randomness> = rands.into_iter().collect();
+                let comms: Vec<_> = commitments.into_iter().map(to_ark).collect();
+                <$pc as ArkPC<$field, DensePolynomial<$field>>>::batch_open(
+                    ck,
+                    polynomials,
+                    &comms,
+                    query_set,
+                    opening_challenge,
+                    rands,
+                    rng,
+                )
+            }
+
+            fn batch_check<'a, R: RngCore>(
+                vk: &Self::VerifierKey,
+                commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+                query_set: &QuerySet<$field>,
+                evaluations: &Evaluations<$field, $field>,
+                proof: &Self::BatchProof,
+                opening_challenge: $field,
+                rng: &mut R,
+            ) -> Result<bool, Self::Error>
+            where
+                Self::Commitment: 'a,
+            {
+                let comms: Vec<_> = commitments.into_iter().map(to_ark).collect();
+                <$pc as ArkPC<$field, DensePolynomial<$field>>>::batch_check(
+                    vk,
+                    &comms,
+                    query_set,
+                    evaluations,
+                    proof,
+                    opening_challenge,
+                    rng,
+                )
+            }
+
+            fn open_combinations<'a>(
+                ck: &Self::CommitterKey,
+                lcs: impl IntoIterator<Item = &'a LinearCombination<$field>>,
+                polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<$field>>,
+                commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+                query_set: &QuerySet<$field>,
+                opening_challenge: $field,
+                rands: impl IntoIterator<Item = &'a Self::Randomness>,
+                rng: Option<&mut dyn RngCore>,
+            ) -> Result<Self::LCProof, Self::Error>
+            where
+                Self::Commitment: 'a,
+                Self::Randomness: 'a,
+            {
+                let lcs: Vec<&LinearCombination<$field>> = lcs.into_iter().collect();
+                let polynomials: Vec<&LabeledPolynomial<$field>> = polynomials.into_iter().collect();
+                let rands: Vec<&Self::Randomness> = rands.into_iter().collect();
+                let comms: Vec<_> = commitments.into_iter().map(to_ark).collect();
+                <$pc as ArkPC<$field, DensePolynomial<$field>>>::open_combinations(
+                    ck,
+                    lcs,
+                    polynomials,
+                    &comms,
+                    query_set,
+                    opening_challenge,
+                    rands,
+                    rng,
+                )
+            }
+
+            fn check_combinations<'a, R: RngCore>(
+                vk: &Self::VerifierKey,
+                lcs: impl IntoIterator<Item = &'a LinearCombination<$field>>,
+                commitments: impl IntoIterator<Item = &'a LabeledCommitment<Self::Commitment>>,
+                query_set: &QuerySet<$field>,
+                evaluations: &Evaluations<$field, $field>,
+                proof: &Self::LCProof,
+                opening_challenge: $field,
+                rng: &mut R,
+            ) -> Result<bool, Self::Error>
+            where
+                Self::Commitment: 'a,
+            {
+                let lcs: Vec<&LinearCombination<$field>> = lcs.into_iter().collect();
+                let comms: Vec<_> = commitments.into_iter().map(to_ark).collect();
+                <$pc as ArkPC<$field, DensePolynomial<$field>>>::check_combinations(
+                    vk,
+                    lcs,
+                    &comms,
+                    query_set,
+                    evaluations,
+                    proof,
+                    opening_challenge,
+                    rng,
+                )
+            }
+        }
+    };
+}
+
+impl_ark_pcs!(impl<E: PairingEngine> PCS<E::Fr> for MarlinKZG10<E, DensePolynomial<E::Fr>>);
+impl_ark_pcs!(
+    impl<G: AffineCurve, D: Digest> PCS<G::ScalarField>
+        for InnerProductArgPC<G, D, DensePolynomial<G::ScalarField>>
+);
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::UniformRand;
+    use ark_poly::{EvaluationDomain, UVPolynomial};
+    use ark_poly_commit::marlin_pc::MarlinKZG10;
+    use ark_std::{string::ToString, test_rng};
+
+    use super::{
+        to_labeled, DensePolynomial, Evaluations, EvaluationsOnDomain, GeneralEvaluationDomain,
+        QuerySet, PCS,
+    };
+
+    type PC = MarlinKZG10<Bls12_381, DensePolynomial<Fr>>;
+
+    #[test]
+    fn test_ark_adapter() {
+        let rng = &mut test_rng();
+        let pp = PC::setup(32, rng).unwrap();
+        let (ck, vk) = PC::trim(&pp, 32).unwrap();
+
+        let a = to_labeled("a", DensePolynomial::<Fr>::rand(15, rng));
+        let b = to_labeled("b", DensePolynomial::<Fr>::rand(31, rng));
+        let (comms, rands) = PC::commit(&ck, vec![&a, &b], None).unwrap();
+
+        let point = Fr::rand(rng);
+        let challenge = Fr::rand(rng);
+        let values = vec![a.evaluate(&point), b.evaluate(&point)];
+        let proof = PC::open(&ck, vec![&a, &b], &comms, point, challenge, &rands, None).unwrap();
+        assert!(PC::check(&vk, &comms, point, values.clone(), &proof, challenge, rng).unwrap());
+        let wrong = vec![values[0], values[0]];
+        assert!(!PC::check(&vk, &comms, point, wrong, &proof, challenge, rng).unwrap());
+
+        let other = Fr::rand(rng);
+        let mut query_set = QuerySet::new();
+        let mut evaluations = Evaluations::new();
+        for (p, (name, x)) in [(&a, ("point", point)), (&b, ("other", other))].iter() {
+            query_set.insert((p.label().clone(), (name.to_string(), *x)));
+            evaluations.insert((p.label().clone(), *x), p.evaluate(x));
+        }
+        let proof =
+            PC::batch_open(&ck, vec![&a, &b], &comms, &query_set, challenge, &rands, None).unwrap();
+        assert!(PC::batch_check(
+            &vk,
+            &comms,
+            &query_set,
+            &evaluations,
+            &proof,
+            challenge,
+            rng
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_commit_evaluations() {
+        let rng = &mut test_rng();
+        let pp = PC::setup(16, rng).unwrap();
+        let (ck, _) = PC::trim(&pp, 16).unwrap();
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(16).unwrap();
+        let poly = DensePolynomial::<Fr>::rand(15, rng);
+        let evaluations = EvaluationsOnDomain::from_vec_and_domain(domain.fft(&poly), domain);
+
+        let (comm, _) = PC::commit_evaluations(&ck, "p", evaluations, None).unwrap();
+        let (comms, _) = PC::commit(&ck, Some(&to_labeled("p", poly)), None).unwrap();
+        assert_eq!(comm.commitment(), comms[0].commitment());
+    }
+}
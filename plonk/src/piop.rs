@@ -0,0 +1,538 @@
+//! A polynomial IOP abstraction, and the compiler that turns any PIOP into a
+//! non-interactive argument with a polynomial commitment scheme and the
+//! Fiat-Shamir transform.
+//!
+//! A protocol only describes its rounds: the oracles the prover sends, the
+//! challenges the verifier answers with, the query set, the linear
+//! combinations to open and the final equality check. The commitment,
+//! transcript and opening logic is shared by every protocol through
+//! [`Compiler`].
+//!
+//! The openings are batched by point: every polynomial and linear
+//! combination queried at a point is opened in one proof, combined with the
+//! powers of the challenge `epsilon` drawn after the evaluations. A proof
+//! thus holds one opening per point of the query set, e.g. `zeta` and
+//! `shifted_zeta` for PLONK, whatever the number of its evaluations, and
+//! `check_combinations` checks them all at once, with one product of two
+//! pairings under KZG.
+
+use ark_ff::{to_bytes, FftField as Field, ToBytes};
+#[cfg(feature = "std")]
+use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+use ark_poly_commit::{Evaluations, LinearCombination, QuerySet};
+use ark_serialize::CanonicalSerialize;
+use ark_std::{
+    marker::PhantomData,
+    string::{String, ToString},
+    vec::Vec,
+};
+use digest::Digest;
+use rand_core::RngCore;
+
+use crate::ahp::{Error as AHPError, EvaluationsProvider};
+use crate::data_structures::{LabeledPolynomial, Proof};
+use crate::error::Error;
+use crate::pcs::{LabeledCommitment, PCS};
+use crate::rng::FiatShamirRng;
+use crate::workspace::ProverWorkspace;
+#[cfg(feature = "std")]
+use crate::{
+    ahp::lc_coeffs,
+    checkpoint::Checkpoint,
+    progress::{Phase, ProverHooks},
+    utils::{evaluate_many, to_labeled},
+};
+
+/// A public-coin polynomial IOP with a fixed number of rounds. In every round
+/// the prover sends oracles, then the verifier answers with field challenges.
+pub trait PIOP<'a, F: Field> {
+    /// The preprocessed circuit, held by the prover.
+    type Index: 'a;
+    /// The public part of the index, held by the verifier.
+    type Info: 'a;
+    type Circuit: ?Sized;
+    type ProverState: 'a;
+    type VerifierState: 'a;
+
+    const NUM_ROUNDS: usize;
+
+    /// Labels of the prover oracles of each round, which depend on the
+    /// index, e.g. on its number of wires.
+    fn oracle_labels(info: &Self::Info) -> Vec<Vec<String>>;
+
+    fn init_prover(
+        index: &'a Self::Index,
+        circuit: &Self::Circuit,
+        ws: &mut ProverWorkspace<F>,
+    ) -> Result<Self::ProverState, AHPError>;
+
+    /// Run prover round `round` given the verifier messages so far. The state
+    /// is `None` after the last round. Scratch vectors are taken from `ws`,
+    /// and handed back once no round needs them. `zk_rng` blinds the oracles.
+    #[allow(clippy::type_complexity)]
+    fn prove_round(
+        ps: Self::ProverState,
+        round: usize,
+        index: &'a Self::Index,
+        circuit: &Self::Circuit,
+        msgs: &[Vec<F>],
+        ws: &mut ProverWorkspace<F>,
+        zk_rng: &mut dyn RngCore,
+    ) -> Result<(Option<Self::ProverState>, Vec<LabeledPolynomial<F>>), AHPError>;
+
+    /// The prover state after the rounds that sent `oracles`, answered with
+    /// `msgs`, as `prove_round` left it; for resuming from a checkpoint.
+    fn resume_prover(
+        index: &'a Self::Index,
+        circuit: &Self::Circuit,
+        oracles: &[LabeledPolynomial<F>],
+        msgs: &[Vec<F>],
+        ws: &mut ProverWorkspace<F>,
+    ) -> Result<Self::ProverState, AHPError>;
+
+    fn init_verifier(info: &'a Self::Info) -> Result<Self::VerifierState, AHPError>;
+
+    fn verify_round<R: RngCore>(
+        vs: Self::VerifierState,
+        round: usize,
+        rng: &mut R,
+    ) -> Result<(Self::VerifierState, Vec<F>), AHPError>;
+
+    fn query_set(vs: &Self::VerifierState) -> QuerySet<F>;
+
+    fn linear_combinations<E: EvaluationsProvider<F>>(
+        info: &Self::Info,
+        msgs: &[Vec<F>],
+        evals: &E,
+    ) -> Result<Vec<LinearCombination<F>>, AHPError>;
+
+    fn equality_check(
+        vs: &Self::VerifierState,
+        evals: &Evaluations<F, F>,
+        public_inputs: &[F],
+    ) -> Result<bool, AHPError>;
+}
+
+/// Compiles a [`PIOP`] with the commitment scheme `PC`, using `D` for the
+/// Fiat-Shamir transcript.
+pub struct Compiler<F: Field, D: Digest, PC: PCS<F>> {
+    _field: PhantomData<F>,
+    _digest: PhantomData<D>,
+    _pc: PhantomData<PC>,
+}
+
+impl<F: Field, D: Digest, PC: PCS<F>> Compiler<F, D, PC> {
+    /// `preprocessed` are the index polynomials, committed to in
+    /// `preprocessed_comms` with `preprocessed_rands`. The rounds take their
+    /// buffers from `workspace`.
+    #[cfg(feature = "std")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove<'a, P: PIOP<'a, F>>(
+        ck: &PC::CommitterKey,
+        index: &'a P::Index,
+        info: &'a P::Info,
+        preprocessed: Vec<&'a LabeledPolynomial<F>>,
+        preprocessed_comms: &[LabeledCommitment<PC::Commitment>],
+        preprocessed_rands: &[PC::Randomness],
+        circuit: &P::Circuit,
+        fs_rng: &mut FiatShamirRng<D>,
+        zk_rng: &mut dyn RngCore,
+        hooks: &mut ProverHooks<'_>,
+        workspace: &mut ProverWorkspace<F>,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>> {
+        Self::prove_from::<P>(
+            ck,
+            index,
+            info,
+            preprocessed,
+            preprocessed_comms,
+            preprocessed_rands,
+            circuit,
+            fs_rng,
+            zk_rng,
+            hooks,
+            workspace,
+            None,
+            None,
+        )
+    }
+
+    /// `prove`, starting after the rounds of `resume` if given, and handing
+    /// the state after each round to `checkpoint` if given.
+    #[cfg(feature = "std")]
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn prove_from<'a, P: PIOP<'a, F>>(
+        ck: &PC::CommitterKey,
+        index: &'a P::Index,
+        info: &'a P::Info,
+        preprocessed: Vec<&'a LabeledPolynomial<F>>,
+        preprocessed_comms: &[LabeledCommitment<PC::Commitment>],
+        preprocessed_rands: &[PC::Randomness],
+        circuit: &P::Circuit,
+        fs_rng: &mut FiatShamirRng<D>,
+        zk_rng: &mut dyn RngCore,
+        hooks: &mut ProverHooks<'_>,
+        workspace: &mut ProverWorkspace<F>,
+        resume: Option<Checkpoint<F, PC>>,
+        mut checkpoint: Option<&mut dyn FnMut(&Checkpoint<F, PC>) -> Result<(), Error<PC::Error>>>,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>> {
+        let span = tracing::info_span!("prove", rounds = P::NUM_ROUNDS);
+        let _enter = span.enter();
+
+        let mut vs = P::init_verifier(info)?;
+
+        let mut msgs = Vec::with_capacity(P::NUM_ROUNDS);
+        let mut oracles = Vec::new();
+        let mut commitments = Vec::with_capacity(P::NUM_ROUNDS);
+        let mut labeled_commitments = preprocessed_comms.to_vec();
+        let mut rands = Vec::new();
+
+        let oracle_labels: Vec<_> = P::oracle_labels(info).into_iter().flatten().collect();
+        let mut ps = match resume {
+            None => Some(P::init_prover(index, circuit, workspace)?),
+            Some(resume) => {
+                let num_oracles: usize = resume.commitments.iter().map(Vec::len).sum();
+                if resume.rounds() > P::NUM_ROUNDS
+                    || num_oracles > oracle_labels.len()
+                    || resume.oracles.len() != num_oracles
+                    || resume.rands.len() != num_oracles
+                {
+                    return Err(Error::CheckpointMismatch { check: "oracles" });
+                }
+                let digest =
+                    Checkpoint::<F, PC>::content_digest::<D>(&resume.oracles, &resume.rands)?;
+                if digest != resume.digest {
+                    return Err(Error::CheckpointMismatch { check: "digest" });
+                }
+
+                // the challenges are drawn again from the commitments.
+                for (round, comms) in resume.commitments.iter().enumerate() {
+                    absorb_round(fs_rng, round, comms);
+                    let (next, msg) = P::verify_round(vs, round, fs_rng)?;
+                    vs = next;
+                    msgs.push(msg);
+                }
+                if fs_rng.seed() != &resume.transcript[..] {
+                    return Err(Error::CheckpointMismatch {
+                        check: "transcript",
+                    });
+                }
+
+                let Checkpoint {
+                    commitments: resumed_commitments,
+                    oracles: resumed_oracles,
+                    rands: resumed_rands,
+                    ..
+                } = resume;
+                labeled_commitments.extend(
+                    resumed_commitments
+                        .iter()
+                        .flatten()
+                        .cloned()
+                        .zip(oracle_labels.iter())
+                        .map(|(c, l)| LabeledCommitment::new(l.to_string(), c, None)),
+                );
+                oracles.extend(resumed_oracles.into_iter().zip(oracle_labels.iter()).map(
+                    |(coeffs, l)| to_labeled(l, DensePolynomial::from_coefficients_vec(coeffs)),
+                ));
+                commitments = resumed_commitments;
+                rands = resumed_rands;
+
+                if msgs.len() == P::NUM_ROUNDS {
+                    None
+                } else {
+                    Some(P::resume_prover(
+                        index, circuit, &oracles, &msgs, workspace,
+                    )?)
+                }
+            }
+        };
+
+        for round in msgs.len()..P::NUM_ROUNDS {
+            let span = tracing::debug_span!("round", round);
+            let _enter = span.enter();
+
+            hooks.enter(Phase::Round {
+                round,
+                rounds: P::NUM_ROUNDS,
+            })?;
+            workspace.yield_now();
+            let state = ps.take().ok_or(Error::ProverFinished { round })?;
+            let (next, round_oracles) = P::prove_round(
+                state,
+                round,
+                index,
+                circuit,
+                &msgs,
+                workspace,
+                &mut *zk_rng,
+            )?;
+            ps = next;
+
+            hooks.enter(Phase::Commit { round })?;
+            workspace.yield_now();
+            let (round_comms, round_rands) =
+                tracing::debug_span!("commit", polynomials = round_oracles.len())
+                    .in_scope(|| PC::commit(ck, round_oracles.iter(), Some(&mut *zk_rng)))
+                    .map_err(Error::from_pc_err)?;
+            absorb_round(fs_rng, round, &round_comms);
+
+            let (next, msg) = P::verify_round(vs, round, fs_rng)?;
+            vs = next;
+            msgs.push(msg);
+
+            commitments.push(round_comms.iter().map(|c| c.commitment().clone()).collect());
+            labeled_commitments.extend(round_comms);
+            rands.extend(round_rands);
+            oracles.extend(round_oracles);
+
+            if let Some(checkpoint) = checkpoint.as_mut() {
+                let coeffs: Vec<_> = oracles.iter().map(|p| p.coeffs.clone()).collect();
+                checkpoint(&Checkpoint {
+                    commitments: commitments.clone(),
+                    digest: Checkpoint::<F, PC>::content_digest::<D>(&coeffs, &rands)?,
+                    oracles: coeffs,
+                    rands: rands.clone(),
+                    transcript: fs_rng.seed().to_vec(),
+                })?;
+            }
+        }
+
+        let polynomials: Vec<&LabeledPolynomial<F>> =
+            preprocessed.into_iter().chain(oracles.iter()).collect();
+        let randomnesses: Vec<&PC::Randomness> =
+            preprocessed_rands.iter().chain(rands.iter()).collect();
+
+        let qs = P::query_set(&vs);
+        let lcs = P::linear_combinations(info, &msgs, &polynomials)?;
+
+        hooks.enter(Phase::Evaluate)?;
+        let evaluations: Vec<_> = {
+            let span = tracing::debug_span!("evaluate", queries = qs.len());
+            let _enter = span.enter();
+            let mut combinations = Vec::with_capacity(qs.len());
+            for (label, (_, point)) in &qs {
+                workspace.yield_now();
+                let lc = lcs.iter().find(|lc| &lc.label == label).ok_or_else(|| {
+                    Error::MissingEvaluation {
+                        label: label.to_string(),
+                    }
+                })?;
+                combinations.push((label, lc_coeffs(&polynomials, lc)?, *point));
+            }
+            workspace.yield_now();
+            // all at once, so that the chunks of every polynomial share the
+            // threads.
+            let queries: Vec<_> = combinations
+                .iter()
+                .map(|(_, coeffs, point)| (coeffs.as_ref(), *point))
+                .collect();
+            let mut evals: Vec<_> = combinations
+                .iter()
+                .map(|(label, _, _)| label)
+                .zip(evaluate_many(&queries))
+                .collect();
+            evals.sort_by(|a, b| a.0.cmp(b.0));
+            evals.into_iter().map(|x| x.1).collect()
+        };
+        fs_rng.absorb_labeled(b"evaluations", &to_bytes![evaluations].unwrap());
+        let epsilon = F::rand(fs_rng);
+
+        hooks.enter(Phase::Open)?;
+        workspace.yield_now();
+        let pc_proof = tracing::debug_span!("open", polynomials = polynomials.len())
+            .in_scope(|| {
+                PC::open_combinations(
+                    ck,
+                    &lcs,
+                    polynomials,
+                    &labeled_commitments,
+                    &qs,
+                    epsilon,
+                    randomnesses,
+                    Some(zk_rng),
+                )
+            })
+            .map_err(Error::from_pc_err)?;
+
+        Ok(Proof {
+            circuit_hash: Vec::new(),
+            commitments,
+            evaluations,
+            pc_proof,
+        })
+    }
+
+    /// The verifier messages of each round of `proof`, its query set and
+    /// the challenge `epsilon` of its openings, as `verify` derives them
+    /// from `fs_rng`.
+    #[allow(clippy::type_complexity)]
+    pub fn replay<'a, P: PIOP<'a, F>>(
+        info: &'a P::Info,
+        proof: &Proof<F, PC>,
+        fs_rng: &mut FiatShamirRng<D>,
+    ) -> Result<(P::VerifierState, Vec<Vec<F>>, QuerySet<F>, F), Error<PC::Error>> {
+        let mut vs = P::init_verifier(info)?;
+        let mut msgs = Vec::with_capacity(P::NUM_ROUNDS);
+        for (round, comms) in proof.commitments.iter().enumerate() {
+            absorb_round(fs_rng, round, comms);
+            let (next, msg) = P::verify_round(vs, round, fs_rng)?;
+            vs = next;
+            msgs.push(msg);
+        }
+
+        let query_set = P::query_set(&vs);
+        fs_rng.absorb_labeled(b"evaluations", &to_bytes![proof.evaluations].unwrap());
+        let epsilon = F::rand(fs_rng);
+        Ok((vs, msgs, query_set, epsilon))
+    }
+
+    pub fn verify<'a, P: PIOP<'a, F>>(
+        rk: &PC::VerifierKey,
+        info: &'a P::Info,
+        preprocessed_comms: Vec<LabeledCommitment<PC::Commitment>>,
+        public_inputs: &[F],
+        proof: &Proof<F, PC>,
+        fs_rng: &mut FiatShamirRng<D>,
+    ) -> Result<bool, Error<PC::Error>> {
+        Self::verify_with::<P>(
+            rk,
+            info,
+            preprocessed_comms,
+            public_inputs,
+            proof,
+            fs_rng,
+            &mut || {},
+        )
+    }
+
+    /// `verify`, calling `yield_now` between its steps.
+    pub fn verify_with<'a, P: PIOP<'a, F>>(
+        rk: &PC::VerifierKey,
+        info: &'a P::Info,
+        preprocessed_comms: Vec<LabeledCommitment<PC::Commitment>>,
+        public_inputs: &[F],
+        proof: &Proof<F, PC>,
+        fs_rng: &mut FiatShamirRng<D>,
+        yield_now: &mut dyn FnMut(),
+    ) -> Result<bool, Error<PC::Error>> {
+        let openings = Self::openings::<P>(
+            info,
+            preprocessed_comms,
+            public_inputs,
+            proof,
+            fs_rng,
+            yield_now,
+        )?;
+        match openings {
+            Some(openings) => PC::check_combinations(
+                rk,
+                &openings.lcs,
+                &openings.commitments,
+                &openings.query_set,
+                &openings.evaluations,
+                &proof.pc_proof,
+                openings.epsilon,
+                fs_rng,
+            )
+            .map_err(Error::from_pc_err),
+            None => Ok(false),
+        }
+    }
+
+    /// The openings `verify_with` checks with `PC::check_combinations`, with
+    /// `fs_rng` left as that check takes it, or `None` if the proof fails
+    /// before them.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn openings<'a, P: PIOP<'a, F>>(
+        info: &'a P::Info,
+        preprocessed_comms: Vec<LabeledCommitment<PC::Commitment>>,
+        public_inputs: &[F],
+        proof: &Proof<F, PC>,
+        fs_rng: &mut FiatShamirRng<D>,
+        yield_now: &mut dyn FnMut(),
+    ) -> Result<Option<Openings<F, PC::Commitment>>, Error<PC::Error>> {
+        let oracle_labels = P::oracle_labels(info);
+        if proof.commitments.len() != P::NUM_ROUNDS
+            || !proof
+                .commitments
+                .iter()
+                .map(Vec::len)
+                .eq(oracle_labels.iter().map(Vec::len))
+        {
+            return Ok(None);
+        }
+
+        let (vs, msgs, query_set, epsilon) = Self::replay::<P>(info, proof, fs_rng)?;
+        if proof.evaluations.len() != query_set.len() {
+            return Ok(None);
+        }
+        yield_now();
+
+        let evaluations = {
+            let mut evaluation_labels: Vec<_> = query_set
+                .iter()
+                .cloned()
+                .map(|(l, (_, p))| (l, p))
+                .collect();
+            evaluation_labels.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let mut evaluations = Evaluations::new();
+            for (q, eval) in evaluation_labels.into_iter().zip(&proof.evaluations) {
+                evaluations.insert(q, *eval);
+            }
+            evaluations
+        };
+
+        if !P::equality_check(&vs, &evaluations, public_inputs)? {
+            return Ok(None);
+        }
+        yield_now();
+
+        let commitments: Vec<_> = preprocessed_comms
+            .into_iter()
+            .chain(
+                proof
+                    .commitments
+                    .iter()
+                    .flatten()
+                    .cloned()
+                    .zip(oracle_labels.iter().flatten())
+                    .map(|(c, l)| LabeledCommitment::new(l.to_string(), c, None)),
+            )
+            .collect();
+
+        let lcs = P::linear_combinations(info, &msgs, &evaluations)?;
+        yield_now();
+
+        // schemes like IPA batch their final checks into one MSM with random
+        // weights, which must not be known to the prover before the proof.
+        let mut pc_proof_bytes = Vec::new();
+        proof.pc_proof.serialize(&mut pc_proof_bytes)?;
+        fs_rng.absorb_labeled(b"pc_proof", &pc_proof_bytes);
+
+        Ok(Some(Openings {
+            lcs,
+            commitments,
+            query_set,
+            evaluations,
+            epsilon,
+        }))
+    }
+}
+
+/// The linear combinations of a proof to open, over the commitments of the
+/// key and of the proof, at the points of `query_set` to `evaluations`,
+/// combined with the powers of `epsilon`.
+pub(crate) struct Openings<F: Field, C> {
+    pub lcs: Vec<LinearCombination<F>>,
+    pub commitments: Vec<LabeledCommitment<C>>,
+    pub query_set: QuerySet<F>,
+    pub evaluations: Evaluations<F, F>,
+    pub epsilon: F,
+}
+
+/// Absorbs the commitments the prover sent in `round`, labelled with it.
+fn absorb_round<D: Digest>(fs_rng: &mut FiatShamirRng<D>, round: usize, comms: &impl ToBytes) {
+    fs_rng.absorb_labeled(b"round", &to_bytes![round as u64, comms].unwrap());
+}
@@ -0,0 +1,61 @@
+//! Progress reports and cancellation of a running prover.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::Error;
+use crate::Arc;
+
+/// A phase of the prover, reported as it starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// The oracles of `round`, out of `rounds`, are computed.
+    Round { round: usize, rounds: usize },
+    /// The oracles of `round` are committed to.
+    Commit { round: usize },
+    /// The polynomials are evaluated at the queried points.
+    Evaluate,
+    /// The evaluations are proven.
+    Open,
+}
+
+/// Cancels the provers it is shared with at their next phase.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The optional hooks of `Plonk::prove_with`.
+#[derive(Default)]
+pub struct ProverHooks<'a> {
+    /// Called as each phase starts.
+    pub progress: Option<&'a mut (dyn FnMut(Phase) + Send)>,
+    /// Checked before each phase; a cancelled prover returns
+    /// `Error::Cancelled`.
+    pub cancel: Option<&'a CancellationToken>,
+}
+
+impl ProverHooks<'_> {
+    // only the prover, built with `std`, uses it.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn enter<E>(&mut self, phase: Phase) -> Result<(), Error<E>> {
+        if self.cancel.map_or(false, CancellationToken::is_cancelled) {
+            return Err(Error::Cancelled);
+        }
+        if let Some(progress) = self.progress.as_mut() {
+            progress(phase);
+        }
+        Ok(())
+    }
+}
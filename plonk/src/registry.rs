@@ -0,0 +1,80 @@
+//! Verifier keys of many circuits, found by their digests, for a verifier
+//! that takes proofs of any circuit registered with it, e.g. a script
+//! on-chain or a relayer.
+
+use ark_ff::FftField as Field;
+use ark_serialize::SerializationError;
+use ark_std::{marker::PhantomData, vec::Vec};
+use digest::Digest;
+
+use crate::data_structures::{Proof, VerifierKey};
+use crate::encoding::public_inputs_from_bytes;
+use crate::{Error, Map, Plonk, PCS};
+
+/// Verifier keys by their digests under `D`, see `VerifierKey::digest`.
+pub struct VkRegistry<F: Field, D: Digest, PC: PCS<F>> {
+    keys: Map<Vec<u8>, VerifierKey<F, PC>>,
+    _digest: PhantomData<D>,
+}
+
+impl<F: Field, D: Digest, PC: PCS<F>> Default
+    for VkRegistry<F, D, PC>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field, D: Digest, PC: PCS<F>> VkRegistry<F, D, PC> {
+    pub fn new() -> Self {
+        VkRegistry {
+            keys: Map::new(),
+            _digest: PhantomData,
+        }
+    }
+
+    /// Registers `vk` under its digest, and returns the key it replaces.
+    pub fn register(&mut self, vk: VerifierKey<F, PC>) -> Option<VerifierKey<F, PC>> {
+        self.keys.insert(vk.digest::<D>(), vk)
+    }
+
+    /// Registers the verifier key in the versioned encoding `vk_bytes`, see
+    /// `VerifierKey::from_versioned_bytes`, and returns its digest.
+    pub fn register_bytes(&mut self, vk_bytes: &[u8]) -> Result<Vec<u8>, SerializationError> {
+        let vk = VerifierKey::from_versioned_bytes(vk_bytes)?;
+        let circuit_id = vk.digest::<D>();
+        self.register(vk);
+        Ok(circuit_id)
+    }
+
+    pub fn remove(&mut self, circuit_id: &[u8]) -> Option<VerifierKey<F, PC>> {
+        self.keys.remove(circuit_id)
+    }
+
+    pub fn get(&self, circuit_id: &[u8]) -> Option<&VerifierKey<F, PC>> {
+        self.keys.get(circuit_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// `Plonk::verify` of the proof and the public inputs in the versioned
+    /// encoding under the key registered as `circuit_id`. A proof of another
+    /// circuit fails with `Error::CircuitMismatch`.
+    pub fn verify_by_id(
+        &self,
+        circuit_id: &[u8],
+        proof_bytes: &[u8],
+        pi_bytes: &[u8],
+    ) -> Result<bool, Error<PC::Error>> {
+        let vk = self.get(circuit_id).ok_or(Error::UnknownCircuit)?;
+        let proof = Proof::from_versioned_bytes(proof_bytes)?;
+        let public_inputs = public_inputs_from_bytes(pi_bytes)?;
+        Plonk::<F, D, PC>::verify(vk, &public_inputs, proof)
+    }
+}
@@ -57,6 +57,25 @@ impl<D: Digest> FiatShamirRng<D> {
         }
     }
 
+    /// Refresh `self.seed` with `data` under `label`, both prefixed with
+    /// their lengths, so that distinct sequences of labelled messages never
+    /// absorb the same bytes.
+    pub fn absorb_labeled(&mut self, label: &[u8], data: &[u8]) {
+        let mut bytes = Vec::with_capacity(16 + label.len() + data.len());
+        bytes.extend_from_slice(&(label.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(label);
+        bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(data);
+        self.absorb(&bytes);
+    }
+
+    /// The current seed, which commits to everything absorbed so far.
+    // only the prover, built with `std`, uses it.
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    pub(crate) fn seed(&self) -> &[u8] {
+        &self.seed
+    }
+
     /// Refresh `self.seed` with new material. Achieved by setting
     /// `self.seed = H(self.seed || new_seed)`.
     #[inline]
@@ -0,0 +1,130 @@
+//! A self test of a build, for deployments to run before going live.
+
+use ark_ff::{FftField as Field, FftParameters};
+use ark_std::vec::Vec;
+use digest::Digest;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use crate::{encoding::public_inputs_to_bytes, Composer, Error, Plonk, PCS};
+
+/// The seed of every random choice of the self test, so that it proves the
+/// same statement with the same randomness on every run.
+const SEED: [u8; 32] = *b"ckb-zkp plonk self test seed 001";
+
+/// The verifier key, proof and public inputs of the self test, in the
+/// versioned encoding, as a reference build produced them.
+#[derive(Clone, Copy, Debug)]
+pub struct KnownAnswer {
+    pub vk: &'static [u8],
+    pub proof: &'static [u8],
+    pub public_inputs: &'static [u8],
+}
+
+/// The known answer for BLS12-381 under `MarlinKZG10`, with `Blake2s`.
+pub const BLS12_381_KZG_BLAKE2S: KnownAnswer = KnownAnswer {
+    vk: include_bytes!("self_test/bls12_381-kzg-blake2s.vk"),
+    proof: include_bytes!("self_test/bls12_381-kzg-blake2s.proof"),
+    public_inputs: include_bytes!("self_test/bls12_381-kzg-blake2s.inputs"),
+};
+
+/// The known answer for Pallas under `InnerProductArgPC`, with `Blake2s`.
+pub const PALLAS_IPA_BLAKE2S: KnownAnswer = KnownAnswer {
+    vk: include_bytes!("self_test/pallas-ipa-blake2s.vk"),
+    proof: include_bytes!("self_test/pallas-ipa-blake2s.proof"),
+    public_inputs: include_bytes!("self_test/pallas-ipa-blake2s.inputs"),
+};
+
+impl<F: Field, D: Digest, PC: PCS<F>> Plonk<F, D, PC> {
+    /// Checks the field arithmetic against identities every FFT field
+    /// satisfies, then checks the build against `known`, the answer of a
+    /// reference build for the same curve, scheme and digest: the verifier
+    /// key and the proof of a fixed circuit from a fixed seed must equal it
+    /// byte for byte, its proof must verify and be rejected for a wrong
+    /// public input.
+    ///
+    /// Fails with `Error::SelfTestFailed` naming the first failed check.
+    pub fn self_test(known: &KnownAnswer) -> Result<(), Error<PC::Error>> {
+        let check = |ok: bool, check: &'static str| {
+            if ok {
+                Ok(())
+            } else {
+                Err(Error::SelfTestFailed { check })
+            }
+        };
+
+        let seven = F::from(7_u64);
+        check(
+            seven.pow(F::characteristic()) == seven,
+            "x^p == x in the scalar field",
+        )?;
+        let root = F::two_adic_root_of_unity();
+        let half_order = [1_u64 << (F::FftParams::TWO_ADICITY - 1)];
+        check(
+            root.pow(half_order) == -F::one(),
+            "the two-adic root of unity has order 2^s",
+        )?;
+
+        let (vk, proof, public_inputs) = Self::self_test_answer()?;
+        check(vk == known.vk, "the verifier key is the known one")?;
+        check(proof == known.proof, "the proof is the known one")?;
+        check(
+            public_inputs == known.public_inputs,
+            "the public inputs are the known ones",
+        )?;
+
+        check(
+            Self::verify_from_slices(known.vk, known.proof, known.public_inputs),
+            "the known proof verifies",
+        )?;
+        let mut wrong = circuit::<F>().public_inputs().to_vec();
+        wrong[2] += F::one();
+        check(
+            !Self::verify_from_slices(known.vk, known.proof, &public_inputs_to_bytes(&wrong)?),
+            "the known proof is rejected for a wrong public input",
+        )
+    }
+
+    /// The verifier key, proof and public inputs of the self test in this
+    /// build, in the versioned encoding; `examples/self_test_answers.rs`
+    /// records them as a `KnownAnswer`.
+    #[allow(clippy::type_complexity)]
+    pub fn self_test_answer() -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), Error<PC::Error>> {
+        let rng = &mut ChaChaRng::from_seed(SEED);
+        let cs = circuit::<F>();
+        let ks = [F::one(), F::from(7_u64), F::from(13_u64), F::from(17_u64)];
+        let srs = Self::setup(16, rng)?;
+        let (pk, vk) = Self::keygen(&srs, &cs, &ks)?;
+        let proof = Self::prove(&pk, &cs, &mut ChaChaRng::from_seed(SEED))?;
+
+        Ok((
+            vk.to_versioned_bytes()?,
+            proof.to_versioned_bytes()?,
+            public_inputs_to_bytes(cs.public_inputs())?,
+        ))
+    }
+}
+
+/// `x * x + x == 12`, for the public `12` and `x = 3`.
+fn circuit<F: Field>() -> Composer<F> {
+    let mut cs = Composer::new();
+    let three = F::from(3_u64);
+    let nine = F::from(9_u64);
+    let twelve = F::from(12_u64);
+
+    let x = cs.alloc_and_assign(three);
+    let x_squared = cs.alloc_and_assign(nine);
+    let y = cs.alloc_and_assign(twelve);
+    cs.create_mul_gate(x, x, x_squared, None, F::one(), F::zero(), F::zero());
+    cs.create_add_gate(
+        (x_squared, F::one()),
+        (x, F::one()),
+        y,
+        None,
+        F::zero(),
+        F::zero(),
+    );
+    cs.constrain_to_constant(y, F::zero(), twelve);
+
+    cs
+}
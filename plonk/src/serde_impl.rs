@@ -0,0 +1,320 @@
+//! Serde support through the canonical encoding: a hex string in
+//! human-readable formats such as JSON, and raw bytes otherwise.
+//!
+//! `HexProof` and `HexVerifierKey` lay the fields of a proof and a key out
+//! one by one instead, for front-ends that display them.
+
+use ark_ff::FftField as Field;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{convert::TryFrom, fmt, string::String, vec::Vec};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ahp::{AHPForPLONK, IndexInfo};
+use crate::data_structures::{read_exact, Proof, VerifierKey};
+use crate::pcs::PCS;
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 * bytes.len());
+    for byte in bytes {
+        hex.push(HEX[(byte >> 4) as usize] as char);
+        hex.push(HEX[(byte & 0xf) as usize] as char);
+    }
+    hex
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+struct BytesVisitor;
+
+impl<'de> de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "canonically serialized bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+fn serialize<T: CanonicalSerialize, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut bytes = Vec::with_capacity(value.serialized_size());
+    value
+        .serialize(&mut bytes)
+        .map_err(<S::Error as ser::Error>::custom)?;
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&to_hex(&bytes))
+    } else {
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+fn deserialize<'de, T: CanonicalDeserialize, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    let bytes = if deserializer.is_human_readable() {
+        let hex = <String as Deserialize>::deserialize(deserializer)?;
+        from_hex(&hex).ok_or_else(|| <D::Error as de::Error>::custom("invalid hex string"))?
+    } else {
+        deserializer.deserialize_bytes(BytesVisitor)?
+    };
+    T::deserialize(&bytes[..]).map_err(<D::Error as de::Error>::custom)
+}
+
+impl<F: Field, PC: PCS<F>> Serialize for Proof<F, PC> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(self, serializer)
+    }
+}
+
+impl<'de, F: Field, PC: PCS<F>> Deserialize<'de>
+    for Proof<F, PC>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer)
+    }
+}
+
+impl<F: Field, PC: PCS<F>> Serialize for VerifierKey<F, PC> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(self, serializer)
+    }
+}
+
+impl<'de, F: Field, PC: PCS<F>> Deserialize<'de>
+    for VerifierKey<F, PC>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer)
+    }
+}
+
+/// A labelled commitment of a `HexProof` or a `HexVerifierKey`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HexCommitment {
+    pub label: String,
+    pub value: String,
+}
+
+/// The fields of a `Proof`, for JavaScript front-ends and block explorers
+/// to display and relay proofs without linking this crate. Byte strings are
+/// in hex, and so are field elements and commitments, of their compressed
+/// canonical encoding, little-endian:
+///
+/// ```json
+/// {
+///   "circuit_hash": "<hex>",
+///   "commitments": [
+///     [{ "label": "w_0", "value": "<hex>" }, ..., { "label": "w_3", ... }],
+///     [{ "label": "z", ... }, { "label": "h_1", ... }, { "label": "h_2", ... }],
+///     [{ "label": "z_lookup", ... }, { "label": "z_memory", ... }],
+///     [{ "label": "t_0", ... }, ..., { "label": "t_3", ... }]
+///   ],
+///   "evaluations": ["<hex>", ...],
+///   "pc_proof": "<hex>"
+/// }
+/// ```
+///
+/// The commitments are grouped by round, and the evaluations are in the
+/// order of the labels of the combinations they are of. `pc_proof` is the
+/// opening proof of the commitment scheme, left encoded as a whole.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HexProof {
+    pub circuit_hash: String,
+    pub commitments: Vec<Vec<HexCommitment>>,
+    pub evaluations: Vec<String>,
+    pub pc_proof: String,
+}
+
+/// The fields of a `VerifierKey`, laid out as those of a `HexProof`:
+///
+/// ```json
+/// {
+///   "n": 1024,
+///   "ks": ["<hex>", "<hex>", "<hex>", "<hex>"],
+///   "blinding": 3,
+///   "committed_inputs": false,
+///   "context": "<hex>",
+///   "circuit_hash": "<hex>",
+///   "commitments": [{ "label": "q_0", "value": "<hex>" }, ...],
+///   "rk": "<hex>"
+/// }
+/// ```
+///
+/// `n` is the number of gates, whose domain is implied, `ks` has a coset per
+/// wire column, three to five, and `rk` is the verifier key of the
+/// commitment scheme, left encoded as a whole.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HexVerifierKey {
+    pub n: usize,
+    pub ks: Vec<String>,
+    pub blinding: usize,
+    pub committed_inputs: bool,
+    pub context: String,
+    pub circuit_hash: String,
+    pub commitments: Vec<HexCommitment>,
+    pub rk: String,
+}
+
+fn encode<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::with_capacity(value.serialized_size());
+    value.serialize(&mut bytes).unwrap();
+    to_hex(&bytes)
+}
+
+fn decode<T: CanonicalDeserialize>(hex: &str) -> Result<T, SerializationError> {
+    read_exact(&bytes(hex)?, true)
+}
+
+fn bytes(hex: &str) -> Result<Vec<u8>, SerializationError> {
+    from_hex(hex).ok_or(SerializationError::InvalidData)
+}
+
+fn commitment<T: CanonicalSerialize>(label: &str, value: &T) -> HexCommitment {
+    HexCommitment {
+        label: label.into(),
+        value: encode(value),
+    }
+}
+
+impl<F: Field, PC: PCS<F>> From<&Proof<F, PC>> for HexProof {
+    fn from(proof: &Proof<F, PC>) -> Self {
+        let wires = proof.commitments.first().map_or(0, Vec::len);
+        let labels = AHPForPLONK::<F>::oracle_labels(wires);
+        let commitments = proof
+            .commitments
+            .iter()
+            .zip(labels.iter())
+            .map(|(round, labels)| {
+                round.iter().zip(labels).map(|(c, label)| commitment(label, c)).collect()
+            })
+            .collect();
+        HexProof {
+            circuit_hash: to_hex(&proof.circuit_hash),
+            commitments,
+            evaluations: proof.evaluations.iter().map(encode).collect(),
+            pc_proof: encode(&proof.pc_proof),
+        }
+    }
+}
+
+/// Fails on invalid hex or encodings, and on commitments other than the
+/// protocol's, in number or labels.
+impl<F: Field, PC: PCS<F>> TryFrom<&HexProof>
+    for Proof<F, PC>
+{
+    type Error = SerializationError;
+
+    fn try_from(proof: &HexProof) -> Result<Self, SerializationError> {
+        let wires = proof.commitments.first().map_or(0, Vec::len);
+        let labels: Vec<Vec<_>> = proof
+            .commitments
+            .iter()
+            .map(|round| round.iter().map(|c| c.label.as_str()).collect())
+            .collect();
+        if labels != AHPForPLONK::<F>::oracle_labels(wires) {
+            return Err(SerializationError::InvalidData);
+        }
+        let commitments = proof
+            .commitments
+            .iter()
+            .map(|round| round.iter().map(|c| decode(&c.value)).collect())
+            .collect::<Result<_, _>>()?;
+        Proof {
+            circuit_hash: bytes(&proof.circuit_hash)?,
+            commitments,
+            evaluations: proof.evaluations.iter().map(|e| decode(e)).collect::<Result<_, _>>()?,
+            pc_proof: decode(&proof.pc_proof)?,
+        }
+        .checked()
+    }
+}
+
+impl<F: Field, PC: PCS<F>> From<&VerifierKey<F, PC>>
+    for HexVerifierKey
+{
+    fn from(vk: &VerifierKey<F, PC>) -> Self {
+        let info = &vk.info;
+        HexVerifierKey {
+            n: info.n,
+            ks: info.ks.iter().map(encode).collect(),
+            blinding: info.blinding,
+            committed_inputs: info.committed_inputs,
+            context: to_hex(&info.context),
+            circuit_hash: to_hex(&vk.circuit_hash),
+            commitments: vk.labels.iter().zip(&vk.comms).map(|(l, c)| commitment(l, c)).collect(),
+            rk: encode(&vk.rk),
+        }
+    }
+}
+
+/// Fails on invalid hex or encodings, and on the keys `from_bytes` rejects.
+impl<F: Field, PC: PCS<F>> TryFrom<&HexVerifierKey>
+    for VerifierKey<F, PC>
+{
+    type Error = SerializationError;
+
+    fn try_from(vk: &HexVerifierKey) -> Result<Self, SerializationError> {
+        let ks = vk.ks.iter().map(|k| decode(k)).collect::<Result<_, _>>()?;
+        let domain_n =
+            GeneralEvaluationDomain::<F>::new(vk.n).ok_or(SerializationError::InvalidData)?;
+        let info = IndexInfo::checked(
+            vk.n,
+            ks,
+            domain_n,
+            vk.blinding,
+            vk.committed_inputs,
+            bytes(&vk.context)?,
+        )?;
+        VerifierKey {
+            info,
+            circuit_hash: bytes(&vk.circuit_hash)?,
+            comms: vk.commitments.iter().map(|c| decode(&c.value)).collect::<Result<_, _>>()?,
+            labels: vk.commitments.iter().map(|c| c.label.clone()).collect(),
+            rk: decode(&vk.rk)?,
+        }
+        .checked()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_hex, to_hex};
+
+    #[test]
+    fn hex() {
+        let bytes = [0x00, 0x1f, 0xa0, 0xff];
+        assert_eq!(to_hex(&bytes), "001fa0ff");
+        assert_eq!(from_hex("001fA0ff").unwrap(), bytes);
+        assert!(from_hex("001").is_none());
+        assert!(from_hex("0g").is_none());
+    }
+}
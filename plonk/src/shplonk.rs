@@ -0,0 +1,703 @@
+//! SHPLONK ([BDFG20], scheme 2): a KZG commitment whose batched openings are
+//! two points of G1 and one product of two pairings, however many sets of
+//! points the polynomials are opened at.
+//!
+//! Each polynomial `f_i` is opened at a set of points `S_i`, whose union is
+//! `T`. The prover commits to `h = sum_i gamma^i (f_i - r_i) / Z_{S_i}` as
+//! `w`, where `r_i` interpolates `f_i` over `S_i` and `gamma` is the opening
+//! challenge. At a point `z` derived from `w`, the polynomial
+//! `L = sum_i gamma^i Z_{T \ S_i}(z) (f_i - r_i(z)) - Z_T(z) h` is zero, and
+//! `w_prime` opens it there. The verifier derives the commitment to `L` from
+//! those of the `f_i` in one MSM.
+//!
+//! Per-point KZG, e.g. `marlin_pc::MarlinKZG10`, gives a point per opening
+//! point instead, so the two schemes are the same size at the two points of
+//! PLONK, `zeta` and `zeta * g`, and SHPLONK is smaller beyond them.
+//! `Plonk` takes it as its commitment scheme like any other [`PCS`](crate::PCS),
+//! opening each linear combination of a proof as a polynomial of its own
+//! against a commitment the verifier combines in one MSM, so that the
+//! proof holds its two points only. Its commitments are not hiding.
+//!
+//! [BDFG20]: https://eprint.iacr.org/2020/081
+
+use ark_ec::{
+    msm::{FixedBaseMSM, VariableBaseMSM},
+    PairingEngine, ProjectiveCurve,
+};
+use ark_ff::{to_bytes, Field, One, PrimeField, UniformRand, Zero};
+use ark_poly_commit::{
+    Evaluations, LinearCombination, PCCommitterKey, PCUniversalParams, PCVerifierKey, QuerySet,
+};
+use ark_serialize::*;
+use ark_std::{fmt, marker::PhantomData, string::String, vec, vec::Vec};
+use digest::Digest;
+use rand_core::RngCore;
+
+use crate::batch::KzgClaim;
+use crate::data_structures::LabeledPolynomial;
+use crate::pcs::{combine_commitments, combine_polynomials, Committed, LabeledCommitment, PCS};
+use crate::rng::FiatShamirRng;
+
+/// KZG with SHPLONK batched openings, over the pairing `E`, with the point
+/// `z` drawn from a transcript hashed with `D`.
+pub struct Shplonk<E: PairingEngine, D: Digest> {
+    _engine: PhantomData<E>,
+    _digest: PhantomData<D>,
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UniversalParams<E: PairingEngine> {
+    /// `beta^i G` for `i` up to the maximum degree.
+    pub powers_of_g: Vec<E::G1Affine>,
+    /// The generator of G2.
+    pub h: E::G2Affine,
+    /// `beta H`.
+    pub beta_h: E::G2Affine,
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommitterKey<E: PairingEngine> {
+    /// `beta^i G` for `i` up to the supported degree.
+    pub powers_of_g: Vec<E::G1Affine>,
+    /// the degree of the universal parameters it was trimmed from.
+    pub max_degree: usize,
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierKey<E: PairingEngine> {
+    pub g: E::G1Affine,
+    pub h: E::G2Affine,
+    pub beta_h: E::G2Affine,
+    pub max_degree: usize,
+    pub supported_degree: usize,
+}
+
+impl<E: PairingEngine> PCUniversalParams for UniversalParams<E> {
+    fn max_degree(&self) -> usize {
+        self.powers_of_g.len() - 1
+    }
+}
+
+impl<E: PairingEngine> PCCommitterKey for CommitterKey<E> {
+    fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    fn supported_degree(&self) -> usize {
+        self.powers_of_g.len() - 1
+    }
+}
+
+impl<E: PairingEngine> PCVerifierKey for VerifierKey<E> {
+    fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    fn supported_degree(&self) -> usize {
+        self.supported_degree
+    }
+}
+
+/// A batched opening: the commitment to `h`, and the opening of `L` at `z`.
+/// It is also the opening of linear combinations, each opened as a
+/// polynomial of its own.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchProof<E: PairingEngine> {
+    pub w: E::G1Affine,
+    pub w_prime: E::G1Affine,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    DegreeIsZero,
+    /// The supported degree is beyond the universal parameters.
+    DegreeTooLarge {
+        degree: usize,
+        max_degree: usize,
+    },
+    /// A polynomial has more coefficients than the committer key has powers.
+    TooManyCoefficients {
+        num_coefficients: usize,
+        num_powers: usize,
+    },
+    /// Degree bounds are not enforced by this scheme.
+    DegreeBound {
+        label: String,
+    },
+    MissingPolynomial {
+        label: String,
+    },
+    MissingCommitment {
+        label: String,
+    },
+    MissingEvaluation {
+        label: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DegreeIsZero => write!(f, "degree is zero"),
+            Error::DegreeTooLarge { degree, max_degree } => write!(
+                f,
+                "degree {} is beyond the maximum degree {}",
+                degree, max_degree
+            ),
+            Error::TooManyCoefficients {
+                num_coefficients,
+                num_powers,
+            } => write!(
+                f,
+                "{} coefficients for {} powers",
+                num_coefficients, num_powers
+            ),
+            Error::DegreeBound { label } => write!(f, "degree bound on {}", label),
+            Error::MissingPolynomial { label } => write!(f, "missing polynomial {}", label),
+            Error::MissingCommitment { label } => write!(f, "missing commitment {}", label),
+            Error::MissingEvaluation { label } => write!(f, "missing evaluation of {}", label),
+        }
+    }
+}
+
+impl ark_std::error::Error for Error {}
+
+impl<E: PairingEngine, D: Digest> Shplonk<E, D> {
+    /// The point at which `L` is opened, bound to `w` and, through the
+    /// opening challenge, to the transcript before it.
+    fn challenge(opening_challenge: E::Fr, w: &E::G1Affine) -> E::Fr {
+        let mut fs_rng = FiatShamirRng::<D>::from_seed(&to_bytes![opening_challenge, w].unwrap());
+        E::Fr::rand(&mut fs_rng)
+    }
+
+    /// The pairing check of `batch_check`, without the pairings: the claim
+    /// `e(L + z W', H) = e(W', beta H)`.
+    pub fn batch_claim<'a>(
+        vk: &VerifierKey<E>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<E::G1Affine>>,
+        query_set: &QuerySet<E::Fr>,
+        evaluations: &Evaluations<E::Fr, E::Fr>,
+        proof: &BatchProof<E>,
+        opening_challenge: E::Fr,
+    ) -> Result<KzgClaim<E>, Error>
+    where
+        E::G1Affine: 'a,
+    {
+        let commitments: Vec<_> = commitments.into_iter().collect();
+        let sets = point_sets(query_set);
+        let all = union(&sets);
+        let z = Self::challenge(opening_challenge, &proof.w);
+
+        // the commitment to L, plus z w_prime.
+        let mut bases = Vec::with_capacity(sets.len() + 3);
+        let mut scalars = Vec::with_capacity(sets.len() + 3);
+        let mut constant = E::Fr::zero();
+        let mut gamma = E::Fr::one();
+        for (label, points) in &sets {
+            let comm = commitments
+                .iter()
+                .find(|c| c.label() == *label)
+                .ok_or_else(|| Error::MissingCommitment {
+                    label: (*label).clone(),
+                })?;
+            let values = points
+                .iter()
+                .map(|x| {
+                    evaluations
+                        .get(&((*label).clone(), *x))
+                        .copied()
+                        .ok_or_else(|| Error::MissingEvaluation {
+                            label: (*label).clone(),
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let factor = gamma * vanishing_outside(&all, points, z);
+            bases.push(*comm.commitment());
+            scalars.push(factor);
+            constant += factor * interpolate(points, &values, z);
+            gamma *= opening_challenge;
+        }
+        bases.push(vk.g);
+        scalars.push(-constant);
+        bases.push(proof.w);
+        scalars.push(-vanishing(&all, z));
+        bases.push(proof.w_prime);
+        scalars.push(z);
+
+        Ok(KzgClaim {
+            lhs: msm::<E>(&bases, &scalars).into_affine(),
+            rhs: proof.w_prime,
+        })
+    }
+}
+
+impl<E: PairingEngine, D: Digest> PCS<E::Fr> for Shplonk<E, D> {
+    type UniversalParams = UniversalParams<E>;
+    type CommitterKey = CommitterKey<E>;
+    type VerifierKey = VerifierKey<E>;
+    type Commitment = E::G1Affine;
+    type Randomness = ();
+    type Proof = E::G1Affine;
+    type BatchProof = BatchProof<E>;
+    type LCProof = BatchProof<E>;
+    type Error = Error;
+
+    fn setup<R: RngCore>(max_degree: usize, rng: &mut R) -> Result<UniversalParams<E>, Error> {
+        if max_degree < 1 {
+            return Err(Error::DegreeIsZero);
+        }
+        let beta = E::Fr::rand(rng);
+        let g = E::G1Projective::rand(rng);
+        let h = E::G2Projective::rand(rng);
+
+        let mut powers_of_beta = vec![E::Fr::one()];
+        let mut cur = beta;
+        for _ in 0..max_degree {
+            powers_of_beta.push(cur);
+            cur *= &beta;
+        }
+
+        let window_size = FixedBaseMSM::get_mul_window_size(max_degree + 1);
+        let scalar_bits = E::Fr::size_in_bits();
+        let g_table = FixedBaseMSM::get_window_table(scalar_bits, window_size, g);
+        let powers_of_g = FixedBaseMSM::multi_scalar_mul::<E::G1Projective>(
+            scalar_bits,
+            window_size,
+            &g_table,
+            &powers_of_beta,
+        );
+
+        Ok(UniversalParams {
+            powers_of_g: E::G1Projective::batch_normalization_into_affine(&powers_of_g),
+            h: h.into_affine(),
+            beta_h: h.mul(beta.into_repr()).into_affine(),
+        })
+    }
+
+    fn trim(
+        pp: &UniversalParams<E>,
+        supported_degree: usize,
+    ) -> Result<(CommitterKey<E>, VerifierKey<E>), Error> {
+        let max_degree = pp.powers_of_g.len() - 1;
+        if supported_degree > max_degree {
+            return Err(Error::DegreeTooLarge {
+                degree: supported_degree,
+                max_degree,
+            });
+        }
+
+        let ck = CommitterKey {
+            powers_of_g: pp.powers_of_g[..=supported_degree].to_vec(),
+            max_degree,
+        };
+        let vk = VerifierKey {
+            g: pp.powers_of_g[0],
+            h: pp.h,
+            beta_h: pp.beta_h,
+            max_degree,
+            supported_degree,
+        };
+        Ok((ck, vk))
+    }
+
+    fn commit<'a>(
+        ck: &CommitterKey<E>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<E::Fr>>,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<Committed<E::Fr, Self>, Error> {
+        let mut comms = Vec::new();
+        for p in polynomials {
+            if p.degree_bound().is_some() {
+                return Err(Error::DegreeBound {
+                    label: p.label().clone(),
+                });
+            }
+            let comm = commit::<E>(&ck.powers_of_g, &p.polynomial().coeffs)?;
+            comms.push(LabeledCommitment::new(
+                p.label().clone(),
+                comm.into_affine(),
+                None,
+            ));
+        }
+        let rands = vec![(); comms.len()];
+        Ok((comms, rands))
+    }
+
+    fn open<'a>(
+        ck: &CommitterKey<E>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<E::Fr>>,
+        _commitments: impl IntoIterator<Item = &'a LabeledCommitment<E::G1Affine>>,
+        point: E::Fr,
+        opening_challenge: E::Fr,
+        _rands: impl IntoIterator<Item = &'a ()>,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<E::G1Affine, Error>
+    where
+        E::G1Affine: 'a,
+    {
+        let mut combined = Vec::new();
+        let mut challenge = E::Fr::one();
+        for p in polynomials {
+            add_scaled(&mut combined, &p.polynomial().coeffs, challenge);
+            challenge *= opening_challenge;
+        }
+        let witness = divide_by_linear(&combined, point);
+        Ok(commit::<E>(&ck.powers_of_g, &witness)?.into_affine())
+    }
+
+    fn check<'a, R: RngCore>(
+        vk: &VerifierKey<E>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<E::G1Affine>>,
+        point: E::Fr,
+        values: impl IntoIterator<Item = E::Fr>,
+        proof: &E::G1Affine,
+        opening_challenge: E::Fr,
+        _rng: &mut R,
+    ) -> Result<bool, Error>
+    where
+        E::G1Affine: 'a,
+    {
+        let mut bases: Vec<_> = commitments.into_iter().map(|c| *c.commitment()).collect();
+        let mut scalars = Vec::with_capacity(bases.len() + 2);
+        let mut value = E::Fr::zero();
+        let mut challenge = E::Fr::one();
+        for v in values {
+            scalars.push(challenge);
+            value += challenge * v;
+            challenge *= opening_challenge;
+        }
+
+        // e(C - v G + x W, H) = e(W, beta H)
+        bases.push(vk.g);
+        scalars.push(-value);
+        bases.push(*proof);
+        scalars.push(point);
+        let lhs = msm::<E>(&bases, &scalars).into_affine();
+        Ok(KzgClaim { lhs, rhs: *proof }.holds(vk))
+    }
+
+    fn batch_open<'a>(
+        ck: &CommitterKey<E>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<E::Fr>>,
+        _commitments: impl IntoIterator<Item = &'a LabeledCommitment<E::G1Affine>>,
+        query_set: &QuerySet<E::Fr>,
+        opening_challenge: E::Fr,
+        _rands: impl IntoIterator<Item = &'a ()>,
+        _rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchProof<E>, Error>
+    where
+        E::G1Affine: 'a,
+    {
+        let polynomials: Vec<_> = polynomials.into_iter().collect();
+        let sets = point_sets(query_set);
+        let mut opened = Vec::with_capacity(sets.len());
+        for (label, points) in &sets {
+            let p = polynomials
+                .iter()
+                .find(|p| p.label() == *label)
+                .ok_or_else(|| Error::MissingPolynomial {
+                    label: (*label).clone(),
+                })?;
+            opened.push((&p.polynomial().coeffs, points));
+        }
+
+        // h, with f_i - r_i divided by Z_{S_i} as the quotient of f_i.
+        let mut h = Vec::new();
+        let mut gamma = E::Fr::one();
+        for (coeffs, points) in &opened {
+            let mut quotient = coeffs.to_vec();
+            for point in points.iter() {
+                quotient = divide_by_linear(&quotient, *point);
+            }
+            add_scaled(&mut h, &quotient, gamma);
+            gamma *= opening_challenge;
+        }
+        let w = commit::<E>(&ck.powers_of_g, &h)?.into_affine();
+
+        let z = Self::challenge(opening_challenge, &w);
+        let all = union(&sets);
+        let mut l = Vec::new();
+        let mut gamma = E::Fr::one();
+        for (coeffs, points) in &opened {
+            let values: Vec<_> = points.iter().map(|x| evaluate(coeffs, *x)).collect();
+            let factor = gamma * vanishing_outside(&all, points, z);
+            add_scaled(&mut l, coeffs, factor);
+            add_scaled(&mut l, &[-interpolate(points, &values, z)], factor);
+            gamma *= opening_challenge;
+        }
+        add_scaled(&mut l, &h, -vanishing(&all, z));
+        let w_prime = commit::<E>(&ck.powers_of_g, &divide_by_linear(&l, z))?.into_affine();
+
+        Ok(BatchProof { w, w_prime })
+    }
+
+    fn batch_check<'a, R: RngCore>(
+        vk: &VerifierKey<E>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<E::G1Affine>>,
+        query_set: &QuerySet<E::Fr>,
+        evaluations: &Evaluations<E::Fr, E::Fr>,
+        proof: &BatchProof<E>,
+        opening_challenge: E::Fr,
+        _rng: &mut R,
+    ) -> Result<bool, Error>
+    where
+        E::G1Affine: 'a,
+    {
+        let claim = Self::batch_claim(
+            vk,
+            commitments,
+            query_set,
+            evaluations,
+            proof,
+            opening_challenge,
+        )?;
+        Ok(claim.holds(vk))
+    }
+
+    fn open_combinations<'a>(
+        ck: &CommitterKey<E>,
+        lcs: impl IntoIterator<Item = &'a LinearCombination<E::Fr>>,
+        polynomials: impl IntoIterator<Item = &'a LabeledPolynomial<E::Fr>>,
+        _commitments: impl IntoIterator<Item = &'a LabeledCommitment<E::G1Affine>>,
+        query_set: &QuerySet<E::Fr>,
+        opening_challenge: E::Fr,
+        _rands: impl IntoIterator<Item = &'a ()>,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<BatchProof<E>, Error>
+    where
+        E::G1Affine: 'a,
+    {
+        let lcs: Vec<_> = lcs.into_iter().collect();
+        let polynomials: Vec<_> = polynomials.into_iter().collect();
+        let combined = combine_polynomials(&lcs, &polynomials)
+            .map_err(|label| Error::MissingPolynomial { label })?;
+        let rands = vec![(); combined.len()];
+        // `batch_open` does not read the commitments.
+        Self::batch_open(ck, &combined, &[], query_set, opening_challenge, &rands, rng)
+    }
+
+    fn check_combinations<'a, R: RngCore>(
+        vk: &VerifierKey<E>,
+        lcs: impl IntoIterator<Item = &'a LinearCombination<E::Fr>>,
+        commitments: impl IntoIterator<Item = &'a LabeledCommitment<E::G1Affine>>,
+        query_set: &QuerySet<E::Fr>,
+        evaluations: &Evaluations<E::Fr, E::Fr>,
+        proof: &BatchProof<E>,
+        opening_challenge: E::Fr,
+        rng: &mut R,
+    ) -> Result<bool, Error>
+    where
+        E::G1Affine: 'a,
+    {
+        let lcs: Vec<_> = lcs.into_iter().collect();
+        let commitments: Vec<_> = commitments.into_iter().collect();
+        let combined = combine_commitments(&lcs, &commitments, vk.g)
+            .map_err(|label| Error::MissingCommitment { label })?;
+        Self::batch_check(
+            vk,
+            &combined,
+            query_set,
+            evaluations,
+            proof,
+            opening_challenge,
+            rng,
+        )
+    }
+}
+
+fn msm<E: PairingEngine>(bases: &[E::G1Affine], scalars: &[E::Fr]) -> E::G1Projective {
+    let scalars: Vec<_> = scalars.iter().map(|s| s.into_repr()).collect();
+    VariableBaseMSM::multi_scalar_mul(&bases[..scalars.len()], &scalars)
+}
+
+fn commit<E: PairingEngine>(
+    powers_of_g: &[E::G1Affine],
+    coeffs: &[E::Fr],
+) -> Result<E::G1Projective, Error> {
+    if coeffs.len() > powers_of_g.len() {
+        return Err(Error::TooManyCoefficients {
+            num_coefficients: coeffs.len(),
+            num_powers: powers_of_g.len(),
+        });
+    }
+    Ok(msm::<E>(powers_of_g, coeffs))
+}
+
+/// The distinct points each polynomial of `query_set` is opened at.
+fn point_sets<F: Field>(query_set: &QuerySet<F>) -> Vec<(&String, Vec<F>)> {
+    let mut sets: Vec<(&String, Vec<F>)> = Vec::new();
+    for (label, (_, point)) in query_set {
+        if let Some((_, points)) = sets.iter_mut().find(|(l, _)| *l == label) {
+            if !points.contains(point) {
+                points.push(*point);
+            }
+        } else {
+            sets.push((label, vec![*point]));
+        }
+    }
+    sets
+}
+
+fn union<F: Field>(sets: &[(&String, Vec<F>)]) -> Vec<F> {
+    let mut all = Vec::new();
+    for point in sets.iter().flat_map(|(_, points)| points) {
+        if !all.contains(point) {
+            all.push(*point);
+        }
+    }
+    all
+}
+
+/// `Z_S(z)`, for the points `S`.
+fn vanishing<F: Field>(points: &[F], z: F) -> F {
+    points.iter().map(|x| z - x).product()
+}
+
+/// `Z_{T \ S}(z)`, for the points `T` and `S`.
+fn vanishing_outside<F: Field>(all: &[F], points: &[F], z: F) -> F {
+    all.iter()
+        .filter(|x| !points.contains(x))
+        .map(|x| z - x)
+        .product()
+}
+
+/// The polynomial through `values` at `points`, at `z`.
+fn interpolate<F: Field>(points: &[F], values: &[F], z: F) -> F {
+    let mut result = F::zero();
+    for (j, (x_j, v_j)) in points.iter().zip(values).enumerate() {
+        let mut numerator = F::one();
+        let mut denominator = F::one();
+        for (m, x_m) in points.iter().enumerate() {
+            if m != j {
+                numerator *= z - x_m;
+                denominator *= *x_j - x_m;
+            }
+        }
+        // the points are distinct.
+        result += *v_j * numerator * denominator.inverse().unwrap();
+    }
+    result
+}
+
+fn evaluate<F: Field>(coeffs: &[F], x: F) -> F {
+    coeffs.iter().rev().fold(F::zero(), |acc, c| acc * x + c)
+}
+
+/// The quotient of `coeffs` by `X - point`, without the remainder.
+fn divide_by_linear<F: Field>(coeffs: &[F], point: F) -> Vec<F> {
+    let mut quotient = vec![F::zero(); coeffs.len().saturating_sub(1)];
+    let mut carry = F::zero();
+    for i in (1..coeffs.len()).rev() {
+        carry = coeffs[i] + carry * point;
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
+
+fn add_scaled<F: Field>(acc: &mut Vec<F>, coeffs: &[F], scalar: F) {
+    if acc.len() < coeffs.len() {
+        acc.resize(coeffs.len(), F::zero());
+    }
+    for (a, c) in acc.iter_mut().zip(coeffs) {
+        *a += scalar * c;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_poly::{univariate::DensePolynomial, UVPolynomial};
+    use ark_std::{string::ToString, test_rng};
+    use blake2::Blake2s;
+
+    use super::*;
+    use crate::utils::to_labeled;
+
+    type PC = Shplonk<Bls12_381, Blake2s>;
+
+    #[test]
+    fn test_batch_open() {
+        let rng = &mut test_rng();
+        let pp = PC::setup(32, rng).unwrap();
+        let (ck, vk) = PC::trim(&pp, 32).unwrap();
+
+        let a = to_labeled("a", DensePolynomial::<Fr>::rand(15, rng));
+        let b = to_labeled("b", DensePolynomial::<Fr>::rand(31, rng));
+        let c = to_labeled("c", DensePolynomial::<Fr>::rand(7, rng));
+        let (comms, rands) = PC::commit(&ck, vec![&a, &b, &c], None).unwrap();
+
+        // a at zeta, b at both points and c at the shifted one.
+        let zeta = Fr::rand(rng);
+        let shifted = Fr::rand(rng);
+        let mut query_set = QuerySet::new();
+        let mut evaluations = Evaluations::new();
+        for (p, (name, x)) in [
+            (&a, ("zeta", zeta)),
+            (&b, ("zeta", zeta)),
+            (&b, ("shifted_zeta", shifted)),
+            (&c, ("shifted_zeta", shifted)),
+        ]
+        .iter()
+        {
+            query_set.insert((p.label().clone(), (name.to_string(), *x)));
+            evaluations.insert((p.label().clone(), *x), p.evaluate(x));
+        }
+
+        let challenge = Fr::rand(rng);
+        let polys = vec![&a, &b, &c];
+        let proof =
+            PC::batch_open(&ck, polys, &comms, &query_set, challenge, &rands, None).unwrap();
+        assert!(PC::batch_check(
+            &vk,
+            &comms,
+            &query_set,
+            &evaluations,
+            &proof,
+            challenge,
+            rng
+        )
+        .unwrap());
+
+        let other = Fr::rand(rng);
+        assert!(
+            !PC::batch_check(&vk, &comms, &query_set, &evaluations, &proof, other, rng).unwrap()
+        );
+        let key = (b.label().clone(), shifted);
+        *evaluations.get_mut(&key).unwrap() += Fr::one();
+        assert!(!PC::batch_check(
+            &vk,
+            &comms,
+            &query_set,
+            &evaluations,
+            &proof,
+            challenge,
+            rng
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_open() {
+        let rng = &mut test_rng();
+        let pp = PC::setup(16, rng).unwrap();
+        let (ck, vk) = PC::trim(&pp, 16).unwrap();
+
+        let a = to_labeled("a", DensePolynomial::<Fr>::rand(15, rng));
+        let b = to_labeled("b", DensePolynomial::<Fr>::rand(16, rng));
+        let (comms, rands) = PC::commit(&ck, vec![&a, &b], None).unwrap();
+
+        let point = Fr::rand(rng);
+        let challenge = Fr::rand(rng);
+        let values = vec![a.evaluate(&point), b.evaluate(&point)];
+        let proof = PC::open(&ck, vec![&a, &b], &comms, point, challenge, &rands, None).unwrap();
+        assert!(PC::check(&vk, &comms, point, values.clone(), &proof, challenge, rng).unwrap());
+        let wrong = vec![values[0], values[0]];
+        assert!(!PC::check(&vk, &comms, point, wrong, &proof, challenge, rng).unwrap());
+
+        let c = to_labeled("c", DensePolynomial::<Fr>::rand(17, rng));
+        assert!(matches!(
+            PC::commit(&ck, Some(&c), None),
+            Err(Error::TooManyCoefficients { .. })
+        ));
+    }
+}
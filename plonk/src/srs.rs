@@ -0,0 +1,100 @@
+//! Loading universal setups by name, from a cache directory or a remote.
+
+use ark_ff::FftField as Field;
+use ark_serialize::CanonicalDeserialize;
+use digest::Digest;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, Plonk, UniversalParams, PCS};
+
+/// Fetches the bytes at a URL. The loader does not depend on an HTTP client,
+/// applications plug in their own.
+pub type Fetch = Box<dyn Fn(&str) -> io::Result<Vec<u8>> + Send + Sync>;
+
+/// Finds universal setups by name, e.g. `ppot-bls12381-2^21`, in a cache
+/// directory, and fetches the missing ones from `<base_url>/<name>` into it.
+pub struct SrsLoader {
+    cache_dir: PathBuf,
+    remote: Option<(String, Fetch)>,
+}
+
+impl SrsLoader {
+    /// A loader of the setups already in `cache_dir`.
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        SrsLoader {
+            cache_dir: cache_dir.into(),
+            remote: None,
+        }
+    }
+
+    /// Fetch the setups missing from the cache from `base_url`.
+    pub fn with_remote(mut self, base_url: impl Into<String>, fetch: Fetch) -> Self {
+        self.remote = Some((base_url.into(), fetch));
+        self
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// The bytes of the setup `name`, from the cache or else from the remote,
+    /// in which case they are cached once their digest is `digest`.
+    fn bytes<D: Digest>(&self, name: &str, digest: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        if name.is_empty() || name.contains(&['/', '\\'][..]) || name.starts_with('.') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "setup names cannot be paths",
+            ));
+        }
+        let path = self.cache_dir.join(name);
+        match fs::read(&path) {
+            Ok(bytes) => return Ok(Some(bytes)),
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            Err(_) => {}
+        }
+
+        let (base_url, fetch) = match &self.remote {
+            Some(remote) => remote,
+            None => return Err(io::ErrorKind::NotFound.into()),
+        };
+        let bytes = fetch(&format!("{}/{}", base_url.trim_end_matches('/'), name))?;
+        if &D::digest(&bytes)[..] != digest {
+            return Ok(None);
+        }
+
+        // a partially written file must never be taken for the setup.
+        fs::create_dir_all(&self.cache_dir)?;
+        let partial = self.cache_dir.join(format!(".{}.partial", name));
+        fs::write(&partial, &bytes)?;
+        fs::rename(&partial, &path)?;
+        Ok(Some(bytes))
+    }
+}
+
+impl<F: Field, D: Digest, PC: PCS<F>> Plonk<F, D, PC> {
+    /// The universal setup `name` of `loader`, checked against its `digest`
+    /// under `D`. Its points are checked to be in the prime-order subgroup
+    /// as they are deserialized.
+    pub fn load_srs(
+        loader: &SrsLoader,
+        name: &str,
+        digest: &[u8],
+    ) -> Result<UniversalParams<F, PC>, Error<PC::Error>>
+    where
+        UniversalParams<F, PC>: CanonicalDeserialize,
+    {
+        let bytes = loader.bytes::<D>(name, digest)?;
+        let bytes = match bytes {
+            Some(bytes) if &D::digest(&bytes)[..] == digest => bytes,
+            _ => {
+                return Err(Error::SrsDigestMismatch {
+                    name: name.to_string(),
+                })
+            }
+        };
+        Ok(CanonicalDeserialize::deserialize(&bytes[..])?)
+    }
+}
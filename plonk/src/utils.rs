@@ -1,24 +1,16 @@
-use ark_ff::{FftField as Field, Zero};
+use ark_ff::FftField as Field;
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, Evaluations as EvaluationsOnDomain, UVPolynomial,
 };
-use ark_std::{cfg_iter, string::ToString, vec, vec::Vec};
+use ark_std::{
+    cfg_chunks_mut, cfg_into_iter, cfg_iter, cfg_iter_mut, fmt, string::ToString, vec, vec::Vec,
+};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use crate::data_structures::LabeledPolynomial;
 
-pub fn scalar_mul<F: Field>(poly: &DensePolynomial<F>, scalar: &F) -> DensePolynomial<F> {
-    if poly.is_zero() || scalar.is_zero() {
-        return DensePolynomial::zero();
-    }
-    let coeffs: Vec<_> = cfg_iter!(poly.coeffs)
-        .map(|coeff| *scalar * coeff)
-        .collect();
-    DensePolynomial::from_coefficients_vec(coeffs)
-}
-
 pub fn generator<F: Field>(domain: impl EvaluationDomain<F>) -> F {
     domain.element(1)
 }
@@ -42,6 +34,18 @@ pub fn first_lagrange_poly<F: Field>(domain: impl EvaluationDomain<F>) -> DenseP
     EvaluationsOnDomain::from_vec_and_domain(l, domain).interpolate()
 }
 
+pub fn last_lagrange_poly<F: Field>(domain: impl EvaluationDomain<F>) -> DensePolynomial<F> {
+    let mut l = vec![F::zero(); domain.size()];
+    l[domain.size() - 1] = F::one();
+    EvaluationsOnDomain::from_vec_and_domain(l, domain).interpolate()
+}
+
+/// The last Lagrange polynomial at `zeta`, which is the first one at
+/// `zeta * g`.
+pub fn evaluate_last_lagrange_poly<F: Field>(domain: impl EvaluationDomain<F>, zeta: F) -> F {
+    evaluate_first_lagrange_poly(domain, zeta * generator(domain))
+}
+
 pub fn evaluate_first_lagrange_poly<F: Field>(domain: impl EvaluationDomain<F>, zeta: F) -> F {
     let n = domain.size() as u64;
     let numerator = zeta.pow(&[n]) - F::one();
@@ -49,6 +53,28 @@ pub fn evaluate_first_lagrange_poly<F: Field>(domain: impl EvaluationDomain<F>,
     numerator * denumerator
 }
 
+/// The number of chunks of the quotient of a circuit of `wires` wires: its
+/// degree is at most that of the permutation term, of one factor more than
+/// the wires, and that of the range term, of five.
+pub fn quotient_chunks(wires: usize) -> usize {
+    wires.max(4)
+}
+
+/// The size of each of the `chunks` chunks of the quotient, for a domain of
+/// size `n` and every oracle blinded with `blinding` coefficients. The
+/// largest term of the quotient, that of the permutation, has up to
+/// `chunks + 1` of them as factors; those of the lookup have at most three,
+/// next to the table and the selector, and those of the memory two.
+pub fn quotient_chunk(n: usize, blinding: usize, chunks: usize) -> usize {
+    n + ((chunks + 1) * blinding).saturating_sub(1) / chunks
+}
+
+/// The index of the evaluation at `g * x` on a coset of `size` points, of
+/// the one at `x`, for the generator `g` of the domain of size `n`.
+pub fn shifted(i: usize, size: usize, n: usize) -> usize {
+    (i + size / n) % size
+}
+
 pub fn pad_to_size<F: Field>(v: &[F], expected_size: usize) -> Vec<F> {
     let diff = expected_size - v.len();
     let zeros = vec![F::zero(); diff];
@@ -58,6 +84,208 @@ pub fn pad_to_size<F: Field>(v: &[F], expected_size: usize) -> Vec<F> {
     v
 }
 
+/// The fewest points of the 4n coset in a parallel task of the quotient;
+/// below it, the overhead of splitting exceeds the work.
+#[cfg(feature = "parallel")]
+pub const MIN_CHUNK: usize = 1 << 10;
+
+/// The length of the chunks that split `len` items evenly over the threads
+/// of the current pool, one chunk per thread.
+pub fn chunk_size(len: usize) -> usize {
+    #[cfg(feature = "parallel")]
+    let threads = rayon::current_num_threads();
+    #[cfg(not(feature = "parallel"))]
+    let threads = 1;
+    ((len + threads - 1) / threads).max(1)
+}
+
+/// The running products of `ratios` from one, i.e. `z[0] = 1` and
+/// `z[i + 1] = z[i] * ratios[i]`, as for the grand products of the
+/// permutation, the lookup and the memory. The products are computed within
+/// one chunk per thread in parallel, and each chunk is then scaled by the
+/// product of the chunks before it.
+pub fn grand_product<F: Field>(ratios: &[F]) -> Vec<F> {
+    let mut z = Vec::with_capacity(ratios.len() + 1);
+    z.push(F::one());
+    z.extend_from_slice(ratios);
+
+    let chunk = chunk_size(ratios.len());
+    cfg_chunks_mut!(z[1..], chunk).for_each(|products| {
+        for i in 1..products.len() {
+            let previous = products[i - 1];
+            products[i] *= previous;
+        }
+    });
+    let offsets: Vec<_> = z[1..]
+        .chunks(chunk)
+        .scan(F::one(), |acc, products| {
+            let offset = *acc;
+            *acc *= products[products.len() - 1];
+            Some(offset)
+        })
+        .collect();
+    cfg_chunks_mut!(z[1..], chunk)
+        .zip(cfg_iter!(offsets))
+        .for_each(|(products, offset)| products.iter_mut().for_each(|p| *p *= offset));
+    #[cfg(feature = "zeroize")]
+    zeroize(offsets);
+
+    z
+}
+
+/// Adds `scalar * coeffs` to the coefficients in `acc`, in parallel.
+pub fn add_scaled<F: Field>(acc: &mut Vec<F>, coeffs: &[F], scalar: &F) {
+    if acc.len() < coeffs.len() {
+        acc.resize(coeffs.len(), F::zero());
+    }
+    cfg_iter_mut!(acc[..coeffs.len()], MIN_CHUNK)
+        .zip(cfg_iter!(coeffs, MIN_CHUNK))
+        .for_each(|(a, c)| *a += *scalar * c);
+}
+
+/// The values of the polynomials of `queries` at their points, i.e. of the
+/// coefficients `queries[i].0` at `queries[i].1`. Each polynomial is split
+/// into one chunk per thread, and the chunks of all of them are evaluated
+/// by Horner's rule in parallel, each then shifted by a power of its point.
+pub fn evaluate_many<F: Field>(queries: &[(&[F], F)]) -> Vec<F> {
+    #[cfg(feature = "parallel")]
+    let chunk = |len| chunk_size(len).max(MIN_CHUNK);
+    #[cfg(not(feature = "parallel"))]
+    let chunk = chunk_size;
+
+    let tasks: Vec<_> = queries
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (coeffs, point))| {
+            let size = chunk(coeffs.len());
+            coeffs
+                .chunks(size)
+                .enumerate()
+                .map(move |(j, c)| (i, (j * size) as u64, c, *point))
+        })
+        .collect();
+    let partial: Vec<_> = cfg_into_iter!(tasks)
+        .map(|(i, start, coeffs, point)| {
+            let value = coeffs.iter().rev().fold(F::zero(), |acc, c| acc * point + c);
+            (i, value * point.pow(&[start]))
+        })
+        .collect();
+
+    let mut values = vec![F::zero(); queries.len()];
+    for (i, value) in partial {
+        values[i] += value;
+    }
+    values
+}
+
+/// Inverse by Fermat's little theorem, in time independent of `x`. Only
+/// meant for prime fields; the inverse of zero is zero.
+#[cfg(feature = "secret-hardened")]
+pub fn ct_inverse<F: Field>(x: &F) -> F {
+    // p - 2, for an odd prime p.
+    let mut exp = F::characteristic().to_vec();
+    let mut borrow = 2;
+    for limb in exp.iter_mut() {
+        let (v, b) = limb.overflowing_sub(borrow);
+        *limb = v;
+        borrow = b as u64;
+    }
+    x.pow(exp)
+}
+
+/// The inverse of a value derived from the witness, zero for zero. With the
+/// `secret-hardened` feature it is `ct_inverse`.
+pub fn witness_inverse<F: Field>(x: &F) -> F {
+    #[cfg(feature = "secret-hardened")]
+    let inverse = ct_inverse(x);
+    #[cfg(not(feature = "secret-hardened"))]
+    let inverse = x.inverse().unwrap_or_else(F::zero);
+    inverse
+}
+
+/// Overwrite secret values before releasing their memory.
+#[cfg(feature = "zeroize")]
+pub fn zeroize<F: Field>(mut v: Vec<F>) {
+    use zeroize::Zeroize;
+    v.iter_mut().for_each(Zeroize::zeroize);
+}
+
 pub fn to_labeled<F: Field>(label: &str, poly: DensePolynomial<F>) -> LabeledPolynomial<F> {
     LabeledPolynomial::new(label.to_string(), poly, None, None)
 }
+
+/// A string quoted and escaped for both JSON and Graphviz.
+pub struct Quoted<'a>(pub &'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' | '\\' => write!(f, "\\{}", c)?,
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, UniformRand, Zero};
+    use ark_poly::Polynomial;
+    use ark_std::test_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_grand_product() {
+        let rng = &mut test_rng();
+        for len in [0, 1, 7, 64, 1000].iter() {
+            let ratios: Vec<_> = (0..*len).map(|_| Fr::rand(rng)).collect();
+            let z = grand_product(&ratios);
+            assert_eq!(z.len(), len + 1);
+            let mut acc = Fr::one();
+            for (i, ratio) in ratios.iter().enumerate() {
+                assert_eq!(z[i], acc);
+                acc *= ratio;
+            }
+            assert_eq!(z[*len], acc);
+        }
+    }
+
+    #[test]
+    fn test_add_scaled() {
+        let rng = &mut test_rng();
+        let a: Vec<_> = (0..5).map(|_| Fr::rand(rng)).collect();
+        let b: Vec<_> = (0..9).map(|_| Fr::rand(rng)).collect();
+        let scalar = Fr::rand(rng);
+
+        let mut acc = a.clone();
+        add_scaled(&mut acc, &b, &scalar);
+        assert_eq!(acc.len(), b.len());
+        for (i, c) in acc.iter().enumerate() {
+            let a_i = a.get(i).copied().unwrap_or_else(Fr::zero);
+            assert_eq!(*c, a_i + scalar * b[i]);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_many() {
+        let rng = &mut test_rng();
+        let polys: Vec<_> = [0, 1, 10, 3000]
+            .iter()
+            .map(|len| (0..*len).map(|_| Fr::rand(rng)).collect::<Vec<_>>())
+            .collect();
+        let points: Vec<_> = polys.iter().map(|_| Fr::rand(rng)).collect();
+        let queries: Vec<_> = polys.iter().map(Vec::as_slice).zip(points.iter().copied()).collect();
+
+        let values = evaluate_many(&queries);
+        for ((coeffs, point), value) in queries.iter().zip(values) {
+            let poly = DensePolynomial::from_coefficients_slice(coeffs);
+            assert_eq!(poly.evaluate(point), value);
+        }
+    }
+}
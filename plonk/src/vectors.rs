@@ -0,0 +1,100 @@
+//! Test vectors for verifiers written in other languages, such as the CKB
+//! script, to check that they read keys and proofs and derive challenges
+//! byte for byte as this crate does. The Solidity verifier checks proofs of
+//! `plonk-origin`, under another transcript, and takes its vectors from the
+//! `test_vectors` example of that crate.
+
+use ark_ff::FftField as Field;
+use ark_std::{fmt, string::String, vec::Vec};
+
+use crate::utils::Quoted;
+
+/// The names of the challenges of the transcript, in the order they are
+/// drawn.
+pub const CHALLENGES: &[&str] = &[
+    "beta", "gamma", "eta", "delta", "lambda", "alpha", "zeta", "epsilon",
+];
+
+/// A proof and what a verifier derives from it, see `Plonk::test_vector`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestVector<F: Field> {
+    pub name: String,
+    /// the canonical encoding of the verifier key.
+    pub vk: Vec<u8>,
+    /// the canonical encoding of the proof.
+    pub proof: Vec<u8>,
+    pub public_inputs: Vec<F>,
+    /// the challenges, named as in `CHALLENGES`.
+    pub challenges: Vec<(&'static str, F)>,
+    /// the evaluations of the proof, with the labels of their polynomial
+    /// and of their point.
+    pub evaluations: Vec<(String, String, F)>,
+    /// the vanishing polynomial, the first Lagrange polynomial and the
+    /// public input polynomial at `zeta`.
+    pub intermediates: Vec<(&'static str, F)>,
+    /// whether the proof verifies under the key and public inputs.
+    pub verifies: bool,
+}
+
+impl<F: Field> TestVector<F> {
+    /// Writes the vector as a JSON object. Byte strings are in hex, and
+    /// field elements in hex of their canonical, little-endian, encoding.
+    pub fn write_json<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        write!(w, "{{\"name\":{}", Quoted(&self.name))?;
+        write!(w, ",\"vk\":\"{}\"", Hex(&self.vk))?;
+        write!(w, ",\"proof\":\"{}\"", Hex(&self.proof))?;
+
+        write!(w, ",\"public_inputs\":[")?;
+        for (i, input) in self.public_inputs.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(w, "{}\"{}\"", sep, Element(input))?;
+        }
+        write!(w, "],\"challenges\":{{")?;
+        for (i, (name, value)) in self.challenges.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(w, "{}\"{}\":\"{}\"", sep, name, Element(value))?;
+        }
+        write!(w, "}},\"evaluations\":[")?;
+        for (i, (label, point, value)) in self.evaluations.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(
+                w,
+                "{}{{\"label\":{},\"point\":{},\"value\":\"{}\"}}",
+                sep,
+                Quoted(label),
+                Quoted(point),
+                Element(value)
+            )?;
+        }
+        write!(w, "],\"intermediates\":{{")?;
+        for (i, (name, value)) in self.intermediates.iter().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            write!(w, "{}\"{}\":\"{}\"", sep, name, Element(value))?;
+        }
+        write!(w, "}},\"verifies\":{}}}", self.verifies)
+    }
+
+    /// The vector as JSON, see `write_json`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json).map(|_| json).unwrap_or_default()
+    }
+}
+
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+struct Element<'a, F: Field>(&'a F);
+
+impl<F: Field> fmt::Display for Element<'_, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = Vec::new();
+        self.0.serialize(&mut bytes).map_err(|_| fmt::Error)?;
+        Hex(&bytes).fmt(f)
+    }
+}
@@ -0,0 +1,82 @@
+//! Byte-level views of proofs and verifier keys, for fuzzing the verifier
+//! with inputs that get past the length prefixes of the encoding.
+
+use arbitrary::Arbitrary;
+use ark_serialize::CanonicalSerialize;
+use ark_std::{string::String, vec::Vec};
+
+/// A proof with every element as arbitrary bytes. `to_bytes` frames them
+/// like a `Proof`, to be read back with `Proof::from_bytes`.
+#[derive(Arbitrary, Clone, Debug)]
+pub struct ProofBytes {
+    pub circuit_hash: Vec<u8>,
+    pub commitments: Vec<Vec<Vec<u8>>>,
+    pub evaluations: Vec<Vec<u8>>,
+    pub pc_proof: Vec<u8>,
+}
+
+impl ProofBytes {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        put(&mut bytes, &self.circuit_hash);
+        put_len(&mut bytes, self.commitments.len());
+        for round in &self.commitments {
+            put_all(&mut bytes, round);
+        }
+        put_all(&mut bytes, &self.evaluations);
+        bytes.extend_from_slice(&self.pc_proof);
+        bytes
+    }
+}
+
+/// A verifier key with every element as arbitrary bytes. `to_bytes` frames
+/// them like a `VerifierKey`, to be read back with `VerifierKey::from_bytes`.
+#[derive(Arbitrary, Clone, Debug)]
+pub struct VerifierKeyBytes {
+    pub n: u64,
+    pub ks: Vec<Vec<u8>>,
+    pub domain_n: Vec<u8>,
+    pub blinding: u64,
+    pub committed_inputs: bool,
+    pub context: Vec<u8>,
+    pub circuit_hash: Vec<u8>,
+    pub comms: Vec<Vec<u8>>,
+    pub labels: Vec<String>,
+    pub rk: Vec<u8>,
+}
+
+impl VerifierKeyBytes {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.n.to_le_bytes().to_vec();
+        put_all(&mut bytes, &self.ks);
+        bytes.extend_from_slice(&self.domain_n);
+        bytes.extend_from_slice(&self.blinding.to_le_bytes());
+        bytes.push(self.committed_inputs as u8);
+        put(&mut bytes, &self.context);
+        put(&mut bytes, &self.circuit_hash);
+        put_all(&mut bytes, &self.comms);
+        put_len(&mut bytes, self.labels.len());
+        for label in &self.labels {
+            put(&mut bytes, label.as_bytes());
+        }
+        bytes.extend_from_slice(&self.rk);
+        bytes
+    }
+}
+
+/// The length prefix of a serialized vector.
+fn put_len(bytes: &mut Vec<u8>, len: usize) {
+    (len as u64).serialize(bytes).unwrap();
+}
+
+/// `item` as a serialized `Vec<u8>`.
+fn put(bytes: &mut Vec<u8>, item: &[u8]) {
+    put_len(bytes, item.len());
+    bytes.extend_from_slice(item);
+}
+
+/// The length of `items`, then each of them as is.
+fn put_all(bytes: &mut Vec<u8>, items: &[Vec<u8>]) {
+    put_len(bytes, items.len());
+    items.iter().for_each(|item| bytes.extend_from_slice(item));
+}
@@ -0,0 +1,87 @@
+//! Scratch buffers shared by the rounds of a prover.
+
+use ark_ff::FftField as Field;
+use ark_poly::EvaluationDomain;
+use ark_std::{boxed::Box, vec::Vec};
+
+/// The evaluation buffers of a prover. The oracles are kept as values and
+/// coefficients between rounds, and the quotient round extends each to the
+/// 4n coset only around the terms using it, taking the 4n vectors from the
+/// workspace and handing them back right after. At most the wires, the
+/// quotient and the four polynomials of the lookup are extended at once, so
+/// a workspace holds nine 4n buffers, ten with five wires, and, kept across
+/// proofs, allocates them only once.
+///
+/// With the `zeroize` feature, a buffer is overwritten as it is handed back.
+///
+/// A workspace also holds the yield hook of its prover, see
+/// `set_yield_hook`.
+#[derive(Default)]
+pub struct ProverWorkspace<F: Field> {
+    buffers: Vec<Vec<F>>,
+    yield_hook: Option<Box<dyn FnMut() + Send>>,
+}
+
+impl<F: Field> ProverWorkspace<F> {
+    pub fn new() -> Self {
+        ProverWorkspace {
+            buffers: Vec::new(),
+            yield_hook: None,
+        }
+    }
+
+    /// Calls `hook` between the steps of the prover using the workspace:
+    /// its phases, the FFTs of each wire and the terms of the quotient. On a
+    /// single thread, e.g. without the `parallel` feature in WASM, the hook
+    /// hands control back to the event loop between steps.
+    pub fn set_yield_hook(&mut self, hook: impl FnMut() + Send + 'static) {
+        self.yield_hook = Some(Box::new(hook));
+    }
+
+    pub fn clear_yield_hook(&mut self) {
+        self.yield_hook = None;
+    }
+
+    pub(crate) fn yield_now(&mut self) {
+        if let Some(hook) = self.yield_hook.as_mut() {
+            hook();
+        }
+    }
+
+    /// The number of field elements the held buffers have room for.
+    pub fn capacity(&self) -> usize {
+        self.buffers.iter().map(Vec::capacity).sum()
+    }
+
+    /// A buffer of `len` zeros, reusing the smallest held buffer that is
+    /// large enough.
+    pub(crate) fn take(&mut self, len: usize) -> Vec<F> {
+        let mut buffer = self
+            .buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.capacity() >= len)
+            .min_by_key(|(_, b)| b.capacity())
+            .map(|(i, _)| i)
+            .map(|i| self.buffers.swap_remove(i))
+            .unwrap_or_else(|| Vec::with_capacity(len));
+        buffer.clear();
+        buffer.resize(len, F::zero());
+        buffer
+    }
+
+    pub(crate) fn recycle(&mut self, mut buffer: Vec<F>) {
+        #[cfg(feature = "zeroize")]
+        buffer.iter_mut().for_each(zeroize::Zeroize::zeroize);
+        buffer.clear();
+        self.buffers.push(buffer);
+    }
+
+    /// `domain.coset_fft(coeffs)`, into a buffer of the workspace.
+    pub(crate) fn coset_fft(&mut self, domain: impl EvaluationDomain<F>, coeffs: &[F]) -> Vec<F> {
+        let mut evals = self.take(domain.size());
+        evals[..coeffs.len()].copy_from_slice(coeffs);
+        domain.coset_fft_in_place(&mut evals);
+        evals
+    }
+}
@@ -0,0 +1,123 @@
+//! Differential tests over random circuits: valid circuits must pass the mock
+//! prover and verify, mutated ones must be caught by both.
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::{One, UniformRand, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_std::test_rng;
+use blake2::Blake2s;
+use rand_core::RngCore;
+use zkp_plonk::{Composer, MockProver, Plonk, Variable};
+
+type PlonkInst = Plonk<Fr, Blake2s, MarlinKZG10<Bls12_381, DensePolynomial<Fr>>>;
+
+const MAX_DEGREE: usize = 128;
+const ROUNDS: usize = 4;
+
+fn ks() -> [Fr; 4] {
+    [
+        Fr::one(),
+        Fr::from(7_u64),
+        Fr::from(13_u64),
+        Fr::from(17_u64),
+    ]
+}
+
+fn pick<R: RngCore>(wires: &[(Variable, Fr)], rng: &mut R) -> (Variable, Fr) {
+    wires[rng.next_u32() as usize % wires.len()]
+}
+
+/// A random circuit of add and mul gates over a few private inputs, with
+/// some wires exposed as public inputs and some pinned to constants. With
+/// `bad_gate`, the output of that gate is assigned a wrong value.
+fn random_circuit<R: RngCore>(bad_gate: Option<usize>, rng: &mut R) -> Composer<Fr> {
+    let mut cs = Composer::new();
+
+    let mut wires = Vec::new();
+    for _ in 0..2 + rng.next_u32() % 3 {
+        let value = Fr::rand(rng);
+        wires.push((cs.alloc_and_assign(value), value));
+    }
+
+    let gates = 8 + rng.next_u32() as usize % 24;
+    for gate in 0..gates {
+        let (l, l_value) = pick(&wires, rng);
+        let (r, r_value) = pick(&wires, rng);
+        let q_c = Fr::rand(rng);
+
+        let bad = if Some(gate) == bad_gate {
+            Fr::one()
+        } else {
+            Fr::zero()
+        };
+
+        let (o, value) = if rng.next_u32() % 2 == 0 {
+            let (q_l, q_r) = (Fr::rand(rng), Fr::rand(rng));
+            let value = q_l * l_value + q_r * r_value + q_c + bad;
+            let o = cs.alloc_and_assign(value);
+            cs.create_add_gate((l, q_l), (r, q_r), o, None, q_c, Fr::zero());
+            (o, value)
+        } else {
+            let q_m = Fr::rand(rng);
+            let value = q_m * l_value * r_value + q_c + bad;
+            let o = cs.alloc_and_assign(value);
+            cs.create_mul_gate(l, r, o, None, q_m, q_c, Fr::zero());
+            (o, value)
+        };
+        wires.push((o, value));
+    }
+
+    for _ in 0..1 + rng.next_u32() % 3 {
+        let (var, value) = pick(&wires, rng);
+        cs.constrain_to_constant(var, Fr::zero(), value);
+    }
+    let (var, value) = pick(&wires, rng);
+    cs.constrain_to_constant(var, value, Fr::zero());
+
+    assert!(cs.srs_degree().unwrap() <= MAX_DEGREE);
+    cs
+}
+
+fn prove_and_verify(cs: &Composer<Fr>, public_inputs: &[Fr]) -> bool {
+    let rng = &mut test_rng();
+
+    let srs = PlonkInst::setup(MAX_DEGREE, rng).unwrap();
+    let (pk, vk) = PlonkInst::keygen(&srs, cs, &ks()).unwrap();
+    let proof = PlonkInst::prove(&pk, cs, rng).unwrap();
+    PlonkInst::verify(&vk, public_inputs, proof).unwrap()
+}
+
+#[test]
+fn random_circuits_complete() {
+    let rng = &mut test_rng();
+    for _ in 0..ROUNDS {
+        let cs = random_circuit(None, rng);
+        assert_eq!(MockProver::run(&cs, cs.public_inputs()), Ok(()));
+        assert!(prove_and_verify(&cs, cs.public_inputs()));
+    }
+}
+
+#[test]
+fn random_circuits_wrong_witness() {
+    let rng = &mut test_rng();
+    for _ in 0..ROUNDS {
+        let cs = random_circuit(Some(rng.next_u32() as usize % 8), rng);
+        assert!(MockProver::run(&cs, cs.public_inputs()).is_err());
+        assert!(!prove_and_verify(&cs, cs.public_inputs()));
+    }
+}
+
+#[test]
+fn random_circuits_wrong_public_inputs() {
+    let rng = &mut test_rng();
+    for _ in 0..ROUNDS {
+        let cs = random_circuit(None, rng);
+
+        let mut public_inputs = cs.public_inputs().to_vec();
+        let i = public_inputs.len() - 2;
+        public_inputs[i] += Fr::one();
+        assert!(MockProver::run(&cs, &public_inputs).is_err());
+        assert!(!prove_and_verify(&cs, &public_inputs));
+    }
+}